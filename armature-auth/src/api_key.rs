@@ -9,6 +9,7 @@
 //! - Key rotation and expiration
 //! - Rate limiting per key
 //! - Scopes/permissions per key
+//! - Hashed storage: the plaintext key is only returned once, at creation
 //! - Database-agnostic with DI
 //!
 //! # Usage
@@ -25,7 +26,7 @@
 //! #[async_trait::async_trait]
 //! impl ApiKeyStore for MyApiKeyStore {
 //!     async fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError> { Ok(()) }
-//!     async fn find_by_key(&self, key: &str) -> Result<Option<ApiKey>, ApiKeyError> { Ok(None) }
+//!     async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyError> { Ok(None) }
 //!     async fn find_by_id(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyError> { Ok(None) }
 //!     async fn list_by_user(&self, user_id: &str) -> Result<Vec<ApiKey>, ApiKeyError> { Ok(vec![]) }
 //!     async fn revoke(&self, key_id: &str) -> Result<(), ApiKeyError> { Ok(()) }
@@ -36,14 +37,14 @@
 //! let store: Arc<dyn ApiKeyStore> = Arc::new(MyApiKeyStore);
 //! let manager = ApiKeyManager::new(store);
 //!
-//! // Generate API key
-//! let api_key = manager.generate("user_123", vec!["read".to_string(), "write".to_string()]).await?;
-//! println!("API Key: {}", api_key.key);
+//! // Generate API key; show the plaintext to the user once, it is not stored
+//! let generated = manager.generate("user_123", ["read", "write"]).await?;
+//! println!("API Key: {}", generated.key);
 //!
-//! // Validate API key
-//! if let Some(key) = manager.validate(&api_key.key).await? {
-//!     println!("Valid key for user: {}", key.user_id);
-//! }
+//! // Verify API key and enforce its scopes
+//! let key = manager.verify(&generated.key).await?;
+//! key.require_scopes(&["read"])?;
+//! println!("Valid key for user: {}", key.user_id);
 //! # Ok(())
 //! # }
 //! ```
@@ -54,9 +55,14 @@ use chrono::{DateTime, Duration, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::sync::Arc;
 use thiserror::Error;
 
+use crate::guard::Guard;
+use crate::{AuthError, Result as AuthResult};
+use armature_core::HttpRequest;
+
 /// API Key errors
 #[derive(Debug, Error)]
 pub enum ApiKeyError {
@@ -79,14 +85,23 @@ pub enum ApiKeyError {
     RateLimitExceeded,
 }
 
+impl From<ApiKeyError> for AuthError {
+    fn from(err: ApiKeyError) -> Self {
+        match err {
+            ApiKeyError::InsufficientPermissions(scope) => AuthError::MissingPermission(scope),
+            err => AuthError::ApiKeyError(err.to_string()),
+        }
+    }
+}
+
 /// API Key structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKey {
     /// Unique key ID
     pub id: String,
 
-    /// The actual API key (store hash, not plaintext!)
-    pub key: String,
+    /// SHA-256 hash of the key (the plaintext is never stored)
+    pub key_hash: String,
 
     /// User/account ID this key belongs to
     pub user_id: String,
@@ -94,8 +109,8 @@ pub struct ApiKey {
     /// Key name/description
     pub name: Option<String>,
 
-    /// Scopes/permissions
-    pub scopes: Vec<String>,
+    /// Scopes/permissions (`*` grants every scope)
+    pub scopes: HashSet<String>,
 
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
@@ -113,6 +128,34 @@ pub struct ApiKey {
     pub rate_limit: Option<u32>,
 }
 
+impl ApiKey {
+    /// Check if key has required scope
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope) || self.scopes.contains("*")
+    }
+
+    /// Require every scope, failing with the first one missing
+    pub fn require_scopes(&self, scopes: &[&str]) -> Result<(), ApiKeyError> {
+        match scopes.iter().find(|scope| !self.has_scope(scope)) {
+            Some(scope) => Err(ApiKeyError::InsufficientPermissions(scope.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A newly generated API key
+///
+/// The only place the plaintext key is available: hand it to the user now,
+/// it cannot be recovered later.
+#[derive(Debug, Clone)]
+pub struct GeneratedApiKey {
+    /// The plaintext API key
+    pub key: String,
+
+    /// The stored key record
+    pub api_key: ApiKey,
+}
+
 /// API Key storage trait (implement with your database)
 ///
 /// Users must provide their own implementation using their database of choice.
@@ -121,8 +164,8 @@ pub trait ApiKeyStore: Send + Sync {
     /// Save or update an API key
     async fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError>;
 
-    /// Find API key by the key string (should query by hash)
-    async fn find_by_key(&self, key: &str) -> Result<Option<ApiKey>, ApiKeyError>;
+    /// Find API key by the hash of the key string
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyError>;
 
     /// Find API key by ID
     async fn find_by_id(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyError>;
@@ -164,7 +207,7 @@ impl ApiKeyManager {
     /// # #[async_trait::async_trait]
     /// # impl ApiKeyStore for MyStore {
     /// #     async fn save(&self, key: &ApiKey) -> Result<(), ApiKeyError> { Ok(()) }
-    /// #     async fn find_by_key(&self, key: &str) -> Result<Option<ApiKey>, ApiKeyError> { Ok(None) }
+    /// #     async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyError> { Ok(None) }
     /// #     async fn find_by_id(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyError> { Ok(None) }
     /// #     async fn list_by_user(&self, user_id: &str) -> Result<Vec<ApiKey>, ApiKeyError> { Ok(vec![]) }
     /// #     async fn revoke(&self, key_id: &str) -> Result<(), ApiKeyError> { Ok(()) }
@@ -195,27 +238,26 @@ impl ApiKeyManager {
 
     /// Generate a new API key
     ///
+    /// Only the key's hash is stored. The plaintext is returned here once.
+    ///
     /// # Examples
     ///
     /// ```no_run
     /// # use armature_auth::api_key::*;
     /// # use std::sync::Arc;
     /// # async fn example(manager: ApiKeyManager) -> Result<(), ApiKeyError> {
-    /// let key = manager.generate(
-    ///     "user_123",
-    ///     vec!["read".to_string(), "write".to_string()]
-    /// ).await?;
+    /// let generated = manager.generate("user_123", ["read", "write"]).await?;
     ///
-    /// println!("API Key: {}", key.key);
-    /// println!("Key ID: {}", key.id);
+    /// println!("API Key: {}", generated.key);
+    /// println!("Key ID: {}", generated.api_key.id);
     /// # Ok(())
     /// # }
     /// ```
     pub async fn generate(
         &self,
         user_id: impl Into<String>,
-        scopes: Vec<String>,
-    ) -> Result<ApiKey, ApiKeyError> {
+        scopes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<GeneratedApiKey, ApiKeyError> {
         let key_id = uuid::Uuid::new_v4().to_string();
         let raw_key = self.generate_random_key();
         let key_string = format!("{}_{}", self.key_prefix, raw_key);
@@ -224,10 +266,10 @@ impl ApiKeyManager {
 
         let api_key = ApiKey {
             id: key_id,
-            key: key_string.clone(),
+            key_hash: Self::hash_key(&key_string),
             user_id: user_id.into(),
             name: None,
-            scopes,
+            scopes: scopes.into_iter().map(Into::into).collect(),
             created_at: Utc::now(),
             expires_at,
             last_used_at: None,
@@ -235,20 +277,24 @@ impl ApiKeyManager {
             rate_limit: None,
         };
 
-        // Store key (implementation should hash the key!)
         self.store.save(&api_key).await?;
 
-        Ok(api_key)
+        Ok(GeneratedApiKey {
+            key: key_string,
+            api_key,
+        })
     }
 
-    /// Validate an API key
+    /// Verify an API key
     ///
-    /// Returns the API key if valid, None if invalid, or an error.
-    pub async fn validate(&self, key: &str) -> Result<Option<ApiKey>, ApiKeyError> {
-        let api_key = match self.store.find_by_key(key).await? {
-            Some(k) => k,
-            None => return Ok(None),
-        };
+    /// Returns the key record, with its scopes, if the key exists and is
+    /// neither revoked nor expired. Records the use in `last_used_at`.
+    pub async fn verify(&self, key: &str) -> Result<ApiKey, ApiKeyError> {
+        let mut api_key = self
+            .store
+            .find_by_hash(&Self::hash_key(key))
+            .await?
+            .ok_or(ApiKeyError::Invalid)?;
 
         // Check if revoked
         if api_key.revoked {
@@ -263,17 +309,27 @@ impl ApiKeyManager {
         }
 
         // Update last used timestamp
-        self.store.update_last_used(&api_key.id, Utc::now()).await?;
+        let now = Utc::now();
+        self.store.update_last_used(&api_key.id, now).await?;
+        api_key.last_used_at = Some(now);
 
-        Ok(Some(api_key))
+        Ok(api_key)
+    }
+
+    /// Validate an API key
+    ///
+    /// Like [`verify`](Self::verify), but returns None for unknown keys.
+    pub async fn validate(&self, key: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        match self.verify(key).await {
+            Ok(api_key) => Ok(Some(api_key)),
+            Err(ApiKeyError::Invalid) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     /// Check if key has required scope
     pub fn has_scope(&self, api_key: &ApiKey, required_scope: &str) -> bool {
-        api_key
-            .scopes
-            .iter()
-            .any(|s| s == required_scope || s == "*")
+        api_key.has_scope(required_scope)
     }
 
     /// Revoke an API key
@@ -287,7 +343,7 @@ impl ApiKeyManager {
     }
 
     /// Rotate an API key (revoke old, generate new)
-    pub async fn rotate(&self, old_key_id: &str) -> Result<ApiKey, ApiKeyError> {
+    pub async fn rotate(&self, old_key_id: &str) -> Result<GeneratedApiKey, ApiKeyError> {
         // Get old key
         let old_key = self
             .store
@@ -317,6 +373,57 @@ impl ApiKeyManager {
     }
 }
 
+/// API key guard
+///
+/// Verifies the key in the `X-API-Key` header and requires it to carry every
+/// configured scope.
+#[derive(Clone)]
+pub struct ApiKeyGuard {
+    manager: Arc<ApiKeyManager>,
+    header: String,
+    scopes: Vec<String>,
+}
+
+impl ApiKeyGuard {
+    /// Create guard requiring the given scopes
+    pub fn new(manager: Arc<ApiKeyManager>, scopes: Vec<String>) -> Self {
+        Self {
+            manager,
+            header: "x-api-key".to_string(),
+            scopes,
+        }
+    }
+
+    /// Read the key from a different header (default: "X-API-Key")
+    pub fn with_header(mut self, header: impl Into<String>) -> Self {
+        self.header = header.into();
+        self
+    }
+
+    /// Verify the request's API key and check its scopes
+    pub async fn authorize(&self, request: &HttpRequest) -> AuthResult<ApiKey> {
+        let key = request
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.header))
+            .map(|(_, value)| value)
+            .ok_or(AuthError::Unauthorized)?;
+
+        let api_key = self.manager.verify(key).await?;
+        let scopes: Vec<&str> = self.scopes.iter().map(String::as_str).collect();
+        api_key.require_scopes(&scopes)?;
+        Ok(api_key)
+    }
+}
+
+#[async_trait]
+impl Guard for ApiKeyGuard {
+    async fn can_activate(&self, request: &HttpRequest) -> AuthResult<bool> {
+        self.authorize(request).await?;
+        Ok(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -341,9 +448,9 @@ mod tests {
             Ok(())
         }
 
-        async fn find_by_key(&self, key: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyError> {
             let keys = self.keys.lock().unwrap();
-            Ok(keys.iter().find(|k| k.key == key).cloned())
+            Ok(keys.iter().find(|k| k.key_hash == key_hash).cloned())
         }
 
         async fn find_by_id(&self, id: &str) -> Result<Option<ApiKey>, ApiKeyError> {
@@ -386,14 +493,29 @@ mod tests {
         let store = Arc::new(InMemoryStore::new());
         let manager = ApiKeyManager::new(store);
 
-        let key = manager
-            .generate("user_123", vec!["read".to_string()])
-            .await
-            .unwrap();
+        let generated = manager.generate("user_123", ["read"]).await.unwrap();
 
-        assert!(key.key.starts_with("ak_"));
-        assert_eq!(key.user_id, "user_123");
-        assert_eq!(key.scopes, vec!["read"]);
+        assert!(generated.key.starts_with("ak_"));
+        assert_eq!(generated.api_key.user_id, "user_123");
+        assert_eq!(
+            generated.api_key.scopes,
+            HashSet::from(["read".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_never_holds_plaintext() {
+        let store = Arc::new(InMemoryStore::new());
+        let manager = ApiKeyManager::new(store.clone());
+
+        let generated = manager.generate("user_123", ["read"]).await.unwrap();
+        let rotated = manager.rotate(&generated.api_key.id).await.unwrap();
+        manager.verify(&rotated.key).await.unwrap();
+
+        let stored = serde_json::to_string(&*store.keys.lock().unwrap()).unwrap();
+        assert!(!stored.contains(&generated.key));
+        assert!(!stored.contains(&rotated.key));
+        assert!(stored.contains(&ApiKeyManager::hash_key(&rotated.key)));
     }
 
     #[tokio::test]
@@ -401,14 +523,40 @@ mod tests {
         let store = Arc::new(InMemoryStore::new());
         let manager = ApiKeyManager::new(store);
 
-        let key = manager
-            .generate("user_123", vec!["read".to_string()])
-            .await
-            .unwrap();
-        let validated = manager.validate(&key.key).await.unwrap();
+        let generated = manager.generate("user_123", ["read"]).await.unwrap();
+        let validated = manager.validate(&generated.key).await.unwrap();
 
         assert!(validated.is_some());
         assert_eq!(validated.unwrap().user_id, "user_123");
+        assert!(manager.validate("ak_unknown").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_updates_last_used() {
+        let store = Arc::new(InMemoryStore::new());
+        let manager = ApiKeyManager::new(store.clone());
+
+        let generated = manager.generate("user_123", ["read"]).await.unwrap();
+        assert!(generated.api_key.last_used_at.is_none());
+
+        let verified = manager.verify(&generated.key).await.unwrap();
+        let stored = store.keys.lock().unwrap()[0].clone();
+        assert!(verified.last_used_at.is_some());
+        assert_eq!(stored.last_used_at, verified.last_used_at);
+
+        let result = manager.verify("ak_unknown").await;
+        assert!(matches!(result, Err(ApiKeyError::Invalid)));
+    }
+
+    #[tokio::test]
+    async fn test_expired_api_key_rejected() {
+        let store = Arc::new(InMemoryStore::new());
+        let manager = ApiKeyManager::new(store).with_expiration(Some(Duration::milliseconds(-1)));
+
+        let generated = manager.generate("user_123", ["read"]).await.unwrap();
+
+        let result = manager.verify(&generated.key).await;
+        assert!(matches!(result, Err(ApiKeyError::Expired)));
     }
 
     #[tokio::test]
@@ -416,13 +564,10 @@ mod tests {
         let store = Arc::new(InMemoryStore::new());
         let manager = ApiKeyManager::new(store);
 
-        let key = manager
-            .generate("user_123", vec!["read".to_string()])
-            .await
-            .unwrap();
-        manager.revoke(&key.id).await.unwrap();
+        let generated = manager.generate("user_123", ["read"]).await.unwrap();
+        manager.revoke(&generated.api_key.id).await.unwrap();
 
-        let result = manager.validate(&key.key).await;
+        let result = manager.validate(&generated.key).await;
         assert!(matches!(result, Err(ApiKeyError::Revoked)));
     }
 
@@ -432,12 +577,42 @@ mod tests {
         let manager = ApiKeyManager::new(store);
 
         let key = manager
-            .generate("user_123", vec!["read".to_string(), "write".to_string()])
+            .generate("user_123", ["read", "write"])
             .await
-            .unwrap();
+            .unwrap()
+            .api_key;
 
         assert!(manager.has_scope(&key, "read"));
         assert!(manager.has_scope(&key, "write"));
         assert!(!manager.has_scope(&key, "admin"));
+        assert!(key.require_scopes(&["read", "write"]).is_ok());
+        assert!(matches!(
+            key.require_scopes(&["read", "admin"]),
+            Err(ApiKeyError::InsufficientPermissions(scope)) if scope == "admin"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_guard_enforces_scopes() {
+        let manager = Arc::new(ApiKeyManager::new(Arc::new(InMemoryStore::new())));
+        let reader = manager.generate("user_123", ["read"]).await.unwrap();
+        let admin = manager.generate("user_456", ["*"]).await.unwrap();
+
+        let guard = ApiKeyGuard::new(manager, vec!["write".to_string()]);
+        let request = |key: &str| {
+            let mut request = HttpRequest::new("GET".to_string(), "/".to_string());
+            request
+                .headers
+                .insert("X-API-Key".to_string(), key.to_string());
+            request
+        };
+
+        let result = guard.can_activate(&request(&reader.key)).await;
+        assert!(matches!(result, Err(AuthError::MissingPermission(scope)) if scope == "write"));
+        assert!(guard.can_activate(&request(&admin.key)).await.unwrap());
+
+        let missing = HttpRequest::new("GET".to_string(), "/".to_string());
+        let result = guard.can_activate(&missing).await;
+        assert!(matches!(result, Err(AuthError::Unauthorized)));
     }
 }
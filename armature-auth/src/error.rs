@@ -10,6 +10,9 @@ pub enum AuthError {
     #[error("Invalid credentials")]
     InvalidCredentials,
 
+    #[error("Account locked, retry after {} seconds", retry_after.as_secs().max(1))]
+    AccountLocked { retry_after: std::time::Duration },
+
     #[error("User not found")]
     UserNotFound,
 
@@ -60,6 +63,9 @@ pub enum AuthError {
 
     #[error("Passwordless auth error: {0}")]
     PasswordlessError(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
 }
 
 pub type Result<T> = std::result::Result<T, AuthError>;
@@ -3,6 +3,8 @@
 use crate::{AuthError, AuthUser, Result};
 use armature_core::HttpRequest;
 use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 /// Guard trait for protecting routes
 #[async_trait]
@@ -61,19 +63,139 @@ impl Guard for AuthGuard {
     }
 }
 
+/// Role hierarchy with role-granted permissions
+///
+/// Declares which roles imply others (`superadmin` implies `admin` implies
+/// `user`) and which permissions each role grants. Implications are
+/// transitive and permissions are inherited along them. Lookups are resolved
+/// when the hierarchy is built, so guard checks don't allocate.
+///
+/// # Examples
+///
+/// ```
+/// use armature_auth::{RoleGuard, RoleHierarchy, UserContext};
+///
+/// let hierarchy = RoleHierarchy::new()
+///     .inherit("superadmin", &["admin"])
+///     .inherit("admin", &["user"])
+///     .grant("admin", &["users:write"])
+///     .grant("user", &["users:read"]);
+///
+/// let user = UserContext::new("u1".to_string()).with_role("superadmin".to_string());
+/// assert!(hierarchy.has_role(&user, "user"));
+/// assert!(hierarchy.has_permission(&user, "users:write"));
+///
+/// let guard = RoleGuard::new("admin").with_hierarchy(hierarchy);
+/// assert!(guard.check_roles(&user));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RoleHierarchy {
+    /// Roles each role directly implies
+    implies: HashMap<String, Vec<String>>,
+    /// Permissions each role directly grants
+    grants: HashMap<String, Vec<String>>,
+    /// Roles that imply each role, directly or transitively
+    implied_by: HashMap<String, Vec<String>>,
+    /// Roles that grant each permission, directly or by inheritance
+    granted_by: HashMap<String, Vec<String>>,
+}
+
+impl RoleHierarchy {
+    /// Create an empty hierarchy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that `role` implies each of `implied`
+    pub fn inherit(mut self, role: &str, implied: &[&str]) -> Self {
+        let entry = self.implies.entry(role.to_string()).or_default();
+        entry.extend(implied.iter().map(|r| r.to_string()));
+        self.resolve();
+        self
+    }
+
+    /// Declare that `role` grants each of `permissions`
+    pub fn grant(mut self, role: &str, permissions: &[&str]) -> Self {
+        let entry = self.grants.entry(role.to_string()).or_default();
+        entry.extend(permissions.iter().map(|p| p.to_string()));
+        self.resolve();
+        self
+    }
+
+    /// Check if user has `role`, directly or through a role implying it
+    pub fn has_role<T: AuthUser + ?Sized>(&self, user: &T, role: &str) -> bool {
+        user.has_role(role)
+            || self
+                .implied_by
+                .get(role)
+                .is_some_and(|roles| roles.iter().any(|r| user.has_role(r)))
+    }
+
+    /// Check if user has `permission`, directly or through one of their roles
+    pub fn has_permission<T: AuthUser + ?Sized>(&self, user: &T, permission: &str) -> bool {
+        user.has_permission(permission)
+            || self
+                .granted_by
+                .get(permission)
+                .is_some_and(|roles| roles.iter().any(|r| user.has_role(r)))
+    }
+
+    /// Recompute the transitive lookups
+    fn resolve(&mut self) {
+        self.implied_by.clear();
+        self.granted_by.clear();
+
+        let roles: HashSet<&String> = self.implies.keys().chain(self.grants.keys()).collect();
+        for &role in &roles {
+            // Every role reachable from `role`, including itself
+            let mut reachable = HashSet::from([role]);
+            let mut pending = vec![role];
+            while let Some(current) = pending.pop() {
+                for implied in self.implies.get(current).into_iter().flatten() {
+                    if reachable.insert(implied) {
+                        pending.push(implied);
+                    }
+                }
+            }
+
+            for &implied in &reachable {
+                if implied != role {
+                    self.implied_by
+                        .entry(implied.clone())
+                        .or_default()
+                        .push(role.clone());
+                }
+                for permission in self.grants.get(implied).into_iter().flatten() {
+                    let granted_by = self.granted_by.entry(permission.clone()).or_default();
+                    if !granted_by.contains(role) {
+                        granted_by.push(role.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Role-based authorization guard
 #[derive(Clone)]
 pub struct RoleGuard {
     required_roles: Vec<String>,
     require_all: bool,
+    hierarchy: Option<Arc<RoleHierarchy>>,
 }
 
 impl RoleGuard {
+    /// Create a guard that requires a single role
+    pub fn new(role: impl Into<String>) -> Self {
+        Self::any(vec![role.into()])
+    }
+
     /// Create a guard that requires ANY of the roles
     pub fn any(roles: Vec<String>) -> Self {
         Self {
             required_roles: roles,
             require_all: false,
+            hierarchy: None,
         }
     }
 
@@ -82,17 +204,27 @@ impl RoleGuard {
         Self {
             required_roles: roles,
             require_all: true,
+            hierarchy: None,
         }
     }
 
+    /// Also accept roles that imply the required ones
+    pub fn with_hierarchy(mut self, hierarchy: impl Into<Arc<RoleHierarchy>>) -> Self {
+        self.hierarchy = Some(hierarchy.into());
+        self
+    }
+
     /// Check if user has required roles
     pub fn check_roles<T: AuthUser>(&self, user: &T) -> bool {
-        let role_refs: Vec<&str> = self.required_roles.iter().map(|s| s.as_str()).collect();
+        let has_role = |role: &String| match &self.hierarchy {
+            Some(hierarchy) => hierarchy.has_role(user, role),
+            None => user.has_role(role),
+        };
 
         if self.require_all {
-            user.has_all_roles(&role_refs)
+            self.required_roles.iter().all(has_role)
         } else {
-            user.has_any_role(&role_refs)
+            self.required_roles.iter().any(has_role)
         }
     }
 }
@@ -117,6 +249,7 @@ impl Guard for RoleGuard {
 pub struct PermissionGuard {
     required_permissions: Vec<String>,
     require_all: bool,
+    hierarchy: Option<Arc<RoleHierarchy>>,
 }
 
 impl PermissionGuard {
@@ -125,6 +258,7 @@ impl PermissionGuard {
         Self {
             required_permissions: permissions,
             require_all: false,
+            hierarchy: None,
         }
     }
 
@@ -133,19 +267,27 @@ impl PermissionGuard {
         Self {
             required_permissions: permissions,
             require_all: true,
+            hierarchy: None,
         }
     }
 
+    /// Also accept permissions granted by the user's roles
+    pub fn with_hierarchy(mut self, hierarchy: impl Into<Arc<RoleHierarchy>>) -> Self {
+        self.hierarchy = Some(hierarchy.into());
+        self
+    }
+
     /// Check if user has required permissions
     pub fn check_permissions<T: AuthUser>(&self, user: &T) -> bool {
+        let has_permission = |perm: &String| match &self.hierarchy {
+            Some(hierarchy) => hierarchy.has_permission(user, perm),
+            None => user.has_permission(perm),
+        };
+
         if self.require_all {
-            self.required_permissions
-                .iter()
-                .all(|perm| user.has_permission(perm))
+            self.required_permissions.iter().all(has_permission)
         } else {
-            self.required_permissions
-                .iter()
-                .any(|perm| user.has_permission(perm))
+            self.required_permissions.iter().any(has_permission)
         }
     }
 }
@@ -208,4 +350,77 @@ mod tests {
         let guard = PermissionGuard::all(vec!["read".to_string(), "delete".to_string()]);
         assert!(!guard.check_permissions(&user));
     }
+
+    fn hierarchy() -> Arc<RoleHierarchy> {
+        Arc::new(
+            RoleHierarchy::new()
+                .inherit("superadmin", &["admin"])
+                .inherit("admin", &["user", "auditor"])
+                .grant("admin", &["users:write"])
+                .grant("user", &["users:read"])
+                .grant("auditor", &["logs:read"]),
+        )
+    }
+
+    fn user_with_role(role: &str) -> UserContext {
+        UserContext::new("user123".to_string()).with_role(role.to_string())
+    }
+
+    #[test]
+    fn test_role_hierarchy() {
+        let superadmin = user_with_role("superadmin");
+        let user = user_with_role("user");
+
+        // Transitive
+        let guard = RoleGuard::new("user").with_hierarchy(hierarchy());
+        assert!(guard.check_roles(&superadmin));
+        assert!(guard.check_roles(&user));
+
+        // Not upwards
+        let guard = RoleGuard::new("admin").with_hierarchy(hierarchy());
+        assert!(guard.check_roles(&superadmin));
+        assert!(!guard.check_roles(&user));
+
+        let guard = RoleGuard::all(vec!["user".to_string(), "auditor".to_string()])
+            .with_hierarchy(hierarchy());
+        assert!(guard.check_roles(&superadmin));
+        assert!(!guard.check_roles(&user));
+
+        // Without a hierarchy, roles match exactly
+        assert!(!RoleGuard::new("admin").check_roles(&superadmin));
+    }
+
+    #[test]
+    fn test_permission_resolution() {
+        let hierarchy = hierarchy();
+        let superadmin = user_with_role("superadmin");
+        let user = user_with_role("user");
+
+        for permission in ["users:read", "users:write", "logs:read"] {
+            assert!(hierarchy.has_permission(&superadmin, permission));
+        }
+        assert!(hierarchy.has_permission(&user, "users:read"));
+        assert!(!hierarchy.has_permission(&user, "users:write"));
+
+        // Directly held permissions still count
+        let direct = user.clone().with_permission("billing:read".to_string());
+        assert!(hierarchy.has_permission(&direct, "billing:read"));
+
+        let guard = PermissionGuard::all(vec!["users:read".to_string(), "logs:read".to_string()])
+            .with_hierarchy(hierarchy);
+        assert!(guard.check_permissions(&superadmin));
+        assert!(!guard.check_permissions(&user));
+    }
+
+    #[test]
+    fn test_role_hierarchy_cycle() {
+        let hierarchy = RoleHierarchy::new()
+            .inherit("a", &["b"])
+            .inherit("b", &["a"])
+            .grant("b", &["p"]);
+
+        assert!(hierarchy.has_role(&user_with_role("a"), "b"));
+        assert!(hierarchy.has_role(&user_with_role("b"), "a"));
+        assert!(hierarchy.has_permission(&user_with_role("a"), "p"));
+    }
 }
@@ -8,14 +8,17 @@
 //! - 🔐 **JWT Authentication** - Token-based auth with `armature-jwt`
 //! - 🌐 **OAuth2** - Google, Auth0, Microsoft, AWS Cognito, Okta
 //! - 🔑 **SAML** - Enterprise SAML 2.0 authentication (requires `saml` feature)
-//! - 🔒 **Password Hashing** - Secure bcrypt-based hashing
+//! - 🔒 **Password Hashing** - Argon2id and bcrypt, with transparent upgrades
 //! - 🛡️ **Guards** - Route protection with auth and role guards
+//! - 🚦 **Login Throttling** - Per-identity lockout against credential stuffing
+//! - 🗝️ **Passkeys** - WebAuthn registration and authentication (requires `webauthn` feature)
 //! - 👤 **User Context** - Request-scoped user information
 //!
 //! ## Cargo Features
 //!
 //! - `default` - Core authentication (JWT, OAuth2, password hashing)
 //! - `saml` - SAML 2.0 support (requires openssl/xmlsec1 system libraries)
+//! - `webauthn` - WebAuthn passkeys
 //!
 //! ### SAML System Requirements
 //!
@@ -77,13 +80,16 @@
 //!
 //! let provider = GoogleProvider::new(config)?;
 //!
-//! // Generate authorization URL
-//! let (auth_url, state) = provider.authorization_url()?;
+//! // Generate authorization URL, with a PKCE verifier (enabled by default
+//! // for Google) to keep alongside the state
+//! let (auth_url, state, pkce_verifier) = provider.authorization_url()?;
 //! println!("Redirect user to: {}", auth_url);
 //! println!("State: {}", state.secret());
 //!
 //! // After callback, exchange code for token
-//! let token = provider.exchange_code("auth-code".to_string()).await?;
+//! let token = provider
+//!     .exchange_code("auth-code".to_string(), pkce_verifier)
+//!     .await?;
 //! println!("Access token: {}", token.access_token);
 //! # Ok(())
 //! # }
@@ -153,25 +159,37 @@ pub mod providers;
 #[cfg(feature = "saml")]
 pub mod saml;
 pub mod strategy;
+pub mod throttle;
 #[cfg(feature = "two-factor")]
 pub mod two_factor;
 pub mod user;
+#[cfg(feature = "webauthn")]
+pub mod webauthn;
 
-pub use api_key::{ApiKey, ApiKeyError, ApiKeyManager, ApiKeyStore};
+pub use api_key::{ApiKey, ApiKeyError, ApiKeyGuard, ApiKeyManager, ApiKeyStore, GeneratedApiKey};
 pub use error::{AuthError, Result};
-pub use guard::{AuthGuard, Guard, RoleGuard};
-pub use oauth2::{OAuth2Provider, OAuth2Token, OAuth2UserInfo};
+pub use guard::{AuthGuard, Guard, PermissionGuard, RoleGuard, RoleHierarchy};
+pub use oauth2::{OAuth2Provider, OAuth2Token, OAuth2UserInfo, PkceCodeVerifier, RefreshingToken};
 pub use password::{PasswordHasher, PasswordVerifier};
-pub use passwordless::{MagicLinkToken, PasswordlessError, WebAuthnManager};
+pub use passwordless::{
+    InMemoryMagicLinkStore, MagicLinkManager, MagicLinkStore, MagicLinkToken, PasswordlessError,
+    WebAuthnManager,
+};
 #[cfg(feature = "saml")]
 pub use saml::{
     ContactInfo, IdpMetadata, SamlAssertion, SamlAuthRequest, SamlConfig, SamlProvider,
     SamlServiceProvider,
 };
 pub use strategy::{AuthStrategy, JwtStrategy, LocalStrategy};
+pub use throttle::{InMemoryLoginAttemptStore, LoginAttemptStore, LoginThrottle};
 #[cfg(feature = "two-factor")]
 pub use two_factor::{BackupCodes, TotpSecret, TwoFactorError};
 pub use user::{AuthUser, UserContext};
+#[cfg(feature = "webauthn")]
+pub use webauthn::{
+    InMemoryWebAuthnChallengeStore, InMemoryWebAuthnCredentialStore, StoredCredential,
+    VerifiedAuth, WebAuthnChallengeStore, WebAuthnConfig, WebAuthnCredentialStore, WebAuthnUser,
+};
 
 // Re-export providers
 pub use providers::{
@@ -251,6 +269,12 @@ impl AuthService {
         self.password_hasher.verify(password, hash)
     }
 
+    /// Check whether a password hash should be replaced after a successful
+    /// login, because it uses another algorithm or other parameters
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        self.password_hasher.needs_rehash(hash)
+    }
+
     /// Get JWT manager
     pub fn jwt_manager(&self) -> Option<&JwtManager> {
         self.jwt_manager.as_deref()
@@ -282,9 +306,9 @@ impl Default for AuthService {
 /// ```
 pub mod prelude {
     pub use crate::AuthService;
-    pub use crate::api_key::{ApiKey, ApiKeyManager, ApiKeyStore};
+    pub use crate::api_key::{ApiKey, ApiKeyGuard, ApiKeyManager, ApiKeyStore};
     pub use crate::error::{AuthError, Result};
-    pub use crate::guard::{AuthGuard, Guard, RoleGuard};
+    pub use crate::guard::{AuthGuard, Guard, PermissionGuard, RoleGuard, RoleHierarchy};
     pub use crate::oauth2::{OAuth2Provider, OAuth2Token, OAuth2UserInfo};
     pub use crate::password::{PasswordHasher, PasswordVerifier};
     pub use crate::strategy::{AuthStrategy, JwtStrategy, LocalStrategy};
@@ -2,16 +2,22 @@
 
 use crate::{AuthError, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 use oauth2::basic::{BasicClient, BasicTokenType};
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EmptyExtraTokenFields,
-    EndpointSet, RedirectUrl, Scope, StandardErrorResponse, StandardRevocableToken,
-    StandardTokenIntrospectionResponse, StandardTokenResponse, TokenResponse, TokenUrl,
+    EndpointSet, PkceCodeChallenge, RedirectUrl, Scope, StandardErrorResponse,
+    StandardRevocableToken, StandardTokenIntrospectionResponse, StandardTokenResponse,
+    TokenResponse, TokenUrl,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 use url::Url;
 
+pub use oauth2::PkceCodeVerifier;
+
 /// Type alias for a fully configured OAuth2 client
 type ConfiguredClient = oauth2::Client<
     StandardErrorResponse<oauth2::basic::BasicErrorResponseType>,
@@ -33,15 +39,29 @@ pub trait OAuth2Provider: Send + Sync {
     fn name(&self) -> &str;
 
     /// Get authorization URL
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)>;
+    ///
+    /// With PKCE enabled, the URL carries an S256 code challenge and the
+    /// matching verifier is returned. Keep it with the state and pass it to
+    /// [`exchange_code`](Self::exchange_code).
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)>;
 
     /// Exchange authorization code for token
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token>;
+    ///
+    /// `pkce_verifier` is required when the provider has PKCE enabled.
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token>;
 
     /// Get user info from token
     async fn get_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo>;
 
     /// Refresh access token
+    ///
+    /// Requests a new token with `grant_type=refresh_token`. If the provider
+    /// rotates refresh tokens, the returned token carries the new one;
+    /// otherwise it keeps `refresh_token`.
     async fn refresh_token(&self, refresh_token: String) -> Result<OAuth2Token>;
 }
 
@@ -54,6 +74,18 @@ pub struct OAuth2Token {
     pub refresh_token: Option<String>,
     pub scope: Option<String>,
     pub id_token: Option<String>, // For OIDC
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>, // Computed from expires_in on receipt
+}
+
+impl OAuth2Token {
+    /// Whether the token expires within `window` from now
+    ///
+    /// Tokens without a known expiry never expire.
+    pub fn expires_within(&self, window: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now() + window)
+    }
 }
 
 impl From<StandardTokenResponse<EmptyExtraTokenFields, oauth2::basic::BasicTokenType>>
@@ -74,6 +106,10 @@ impl From<StandardTokenResponse<EmptyExtraTokenFields, oauth2::basic::BasicToken
                     .join(" ")
             }),
             id_token: None, // BasicClient doesn't support OIDC by default
+            expires_at: token
+                .expires_in()
+                .and_then(|d| Duration::from_std(d).ok())
+                .map(|d| Utc::now() + d),
         }
     }
 }
@@ -102,6 +138,7 @@ pub struct OAuth2Config {
     pub redirect_url: String,
     pub scopes: Vec<String>,
     pub user_info_url: Option<String>,
+    pub pkce: bool,
 }
 
 impl OAuth2Config {
@@ -120,6 +157,7 @@ impl OAuth2Config {
             redirect_url,
             scopes: Vec::new(),
             user_info_url: None,
+            pkce: false,
         }
     }
 
@@ -132,6 +170,12 @@ impl OAuth2Config {
         self.user_info_url = Some(url);
         self
     }
+
+    /// Enable PKCE (S256) for the authorization code flow (default: disabled)
+    pub fn with_pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
 }
 
 /// Generic OAuth2 provider implementation
@@ -172,27 +216,44 @@ impl OAuth2Provider for GenericOAuth2Provider {
         &self.name
     }
 
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)> {
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)> {
         let mut auth_request = self.client.authorize_url(CsrfToken::new_random);
 
         for scope in &self.config.scopes {
             auth_request = auth_request.add_scope(Scope::new(scope.clone()));
         }
 
+        let mut pkce_verifier = None;
+        if self.config.pkce {
+            let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+            auth_request = auth_request.set_pkce_challenge(challenge);
+            pkce_verifier = Some(verifier);
+        }
+
         let (url, csrf_token) = auth_request.url();
-        Ok((url, csrf_token))
+        Ok((url, csrf_token, pkce_verifier))
     }
 
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token> {
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token> {
+        let mut request = self.client.exchange_code(AuthorizationCode::new(code));
+        match pkce_verifier {
+            Some(verifier) => request = request.set_pkce_verifier(verifier),
+            None if self.config.pkce => {
+                return Err(AuthError::Configuration(
+                    "PKCE is enabled but no code verifier was provided".into(),
+                ));
+            }
+            None => {}
+        }
+
         let http_client = oauth2::reqwest::Client::new();
-        let token = self
-            .client
-            .exchange_code(AuthorizationCode::new(code))
-            .request_async(&http_client)
-            .await
-            .map_err(|e| {
-                AuthError::AuthenticationFailed(format!("Token exchange failed: {}", e))
-            })?;
+        let token = request.request_async(&http_client).await.map_err(|e| {
+            AuthError::AuthenticationFailed(format!("Token exchange failed: {}", e))
+        })?;
 
         Ok(token.into())
     }
@@ -231,12 +292,74 @@ impl OAuth2Provider for GenericOAuth2Provider {
         let http_client = oauth2::reqwest::Client::new();
         let token = self
             .client
-            .exchange_refresh_token(&oauth2::RefreshToken::new(refresh_token))
+            .exchange_refresh_token(&oauth2::RefreshToken::new(refresh_token.clone()))
             .request_async(&http_client)
             .await
             .map_err(|e| AuthError::AuthenticationFailed(format!("Token refresh failed: {}", e)))?;
 
-        Ok(token.into())
+        // Providers that don't rotate refresh tokens omit them on refresh
+        let mut token = OAuth2Token::from(token);
+        token.refresh_token.get_or_insert(refresh_token);
+        Ok(token)
+    }
+}
+
+/// OAuth2 token that renews itself
+///
+/// [`access_token`](Self::access_token) refreshes the token through its
+/// provider when it is about to expire. Concurrent callers wait for a single
+/// refresh, so a rotated refresh token is never presented twice.
+pub struct RefreshingToken {
+    provider: Arc<dyn OAuth2Provider>,
+    token: Mutex<OAuth2Token>,
+    leeway: Duration,
+}
+
+impl RefreshingToken {
+    /// Wrap a token obtained from `provider`
+    ///
+    /// The token is refreshed 60 seconds before it expires by default.
+    pub fn new(provider: Arc<dyn OAuth2Provider>, token: OAuth2Token) -> Self {
+        Self {
+            provider,
+            token: Mutex::new(token),
+            leeway: Duration::seconds(60),
+        }
+    }
+
+    /// Set how long before expiry the token is refreshed
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// Get a valid access token, refreshing it first if it is about to expire
+    ///
+    /// Fails with [`AuthError::TokenExpired`] if the token needs refreshing
+    /// but has no refresh token.
+    pub async fn access_token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if token.expires_within(self.leeway) {
+            *token = self.renew(&token).await?;
+        }
+        Ok(token.access_token.clone())
+    }
+
+    /// Refresh the token now, regardless of its expiry
+    pub async fn refresh(&self) -> Result<OAuth2Token> {
+        let mut token = self.token.lock().await;
+        *token = self.renew(&token).await?;
+        Ok(token.clone())
+    }
+
+    /// Get the current token without refreshing it
+    pub async fn token(&self) -> OAuth2Token {
+        self.token.lock().await.clone()
+    }
+
+    async fn renew(&self, token: &OAuth2Token) -> Result<OAuth2Token> {
+        let refresh_token = token.refresh_token.clone().ok_or(AuthError::TokenExpired)?;
+        self.provider.refresh_token(refresh_token).await
     }
 }
 
@@ -259,5 +382,185 @@ mod tests {
         assert_eq!(config.client_id, "client_id");
         assert_eq!(config.scopes.len(), 2);
         assert!(config.user_info_url.is_some());
+        assert!(!config.pkce);
+    }
+
+    fn provider(pkce: bool) -> GenericOAuth2Provider {
+        let config = OAuth2Config::new(
+            "client_id".to_string(),
+            "client_secret".to_string(),
+            "https://example.com/auth".to_string(),
+            "https://example.com/token".to_string(),
+            "https://example.com/callback".to_string(),
+        )
+        .with_pkce(pkce);
+        GenericOAuth2Provider::new("test".to_string(), config).unwrap()
+    }
+
+    fn query_param(url: &Url, name: &str) -> Option<String> {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+        use sha2::{Digest, Sha256};
+
+        let (url, _, verifier) = provider(true).authorization_url().unwrap();
+        let verifier = verifier.unwrap();
+
+        let expected = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.secret().as_bytes()));
+        assert_eq!(query_param(&url, "code_challenge"), Some(expected));
+        assert_eq!(
+            query_param(&url, "code_challenge_method").as_deref(),
+            Some("S256")
+        );
+    }
+
+    #[test]
+    fn test_pkce_disabled() {
+        let (url, state, verifier) = provider(false).authorization_url().unwrap();
+
+        assert!(verifier.is_none());
+        assert!(query_param(&url, "code_challenge").is_none());
+        assert_eq!(query_param(&url, "state"), Some(state.secret().clone()));
+    }
+
+    /// Serve one canned JSON response per connection, returning the request
+    /// bodies received.
+    async fn mock_token_endpoint(
+        responses: Vec<&'static str>,
+    ) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/token", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let mut bodies = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                let body_start = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break i + 4;
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map_or(0, |len| len.trim().parse().unwrap());
+                while request.len() < body_start + length {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                bodies.push(String::from_utf8_lossy(&request[body_start..]).into_owned());
+
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+                stream.write_all(reply.as_bytes()).await.unwrap();
+            }
+            bodies
+        });
+
+        (url, server)
+    }
+
+    fn token(access_token: &str, expires_in: i64, refresh_token: Option<&str>) -> OAuth2Token {
+        OAuth2Token {
+            access_token: access_token.to_string(),
+            token_type: "bearer".to_string(),
+            expires_in: None,
+            refresh_token: refresh_token.map(str::to_string),
+            scope: None,
+            id_token: None,
+            expires_at: Some(Utc::now() + Duration::seconds(expires_in)),
+        }
+    }
+
+    fn provider_with_token_url(token_url: String) -> Arc<dyn OAuth2Provider> {
+        let config = OAuth2Config::new(
+            "client_id".to_string(),
+            "client_secret".to_string(),
+            "https://example.com/auth".to_string(),
+            token_url,
+            "https://example.com/callback".to_string(),
+        );
+        Arc::new(GenericOAuth2Provider::new("test".to_string(), config).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token() {
+        let (url, server) = mock_token_endpoint(vec![
+            r#"{"access_token":"access-2","token_type":"bearer","expires_in":3600}"#,
+        ])
+        .await;
+        let provider = provider_with_token_url(url);
+
+        let token = provider
+            .refresh_token("refresh-1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(token.access_token, "access-2");
+        assert_eq!(token.refresh_token.as_deref(), Some("refresh-1"));
+        assert!(!token.expires_within(Duration::minutes(59)));
+        assert!(token.expires_within(Duration::minutes(61)));
+
+        let bodies = server.await.unwrap();
+        assert!(bodies[0].contains("grant_type=refresh_token"));
+        assert!(bodies[0].contains("refresh_token=refresh-1"));
+    }
+
+    #[tokio::test]
+    async fn test_refreshing_token() {
+        let (url, server) = mock_token_endpoint(vec![
+            r#"{"access_token":"access-2","token_type":"bearer","expires_in":3600,"refresh_token":"refresh-2"}"#,
+        ])
+        .await;
+        let provider = provider_with_token_url(url);
+
+        // Still valid: no request is made
+        let fresh = RefreshingToken::new(provider.clone(), token("access-0", 3600, None));
+        assert_eq!(fresh.access_token().await.unwrap(), "access-0");
+
+        // About to expire: refreshed once, picking up the rotated refresh token
+        let expiring = RefreshingToken::new(provider, token("access-1", 30, Some("refresh-1")));
+        assert_eq!(expiring.access_token().await.unwrap(), "access-2");
+        assert_eq!(expiring.access_token().await.unwrap(), "access-2");
+        assert_eq!(
+            expiring.token().await.refresh_token.as_deref(),
+            Some("refresh-2")
+        );
+
+        let bodies = server.await.unwrap();
+        assert_eq!(bodies.len(), 1);
+        assert!(bodies[0].contains("refresh_token=refresh-1"));
+    }
+
+    #[tokio::test]
+    async fn test_refreshing_token_without_refresh_token() {
+        let provider = provider(false);
+        let token = RefreshingToken::new(Arc::new(provider), token("access-1", 0, None));
+
+        assert!(matches!(
+            token.access_token().await,
+            Err(AuthError::TokenExpired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pkce_verifier_required() {
+        let result = provider(true).exchange_code("code".to_string(), None).await;
+        assert!(matches!(result, Err(AuthError::Configuration(_))));
     }
 }
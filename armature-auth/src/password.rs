@@ -2,24 +2,73 @@
 
 use crate::{AuthError, Result};
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, Params, Version,
     password_hash::{
         PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString, rand_core::OsRng,
     },
 };
 
 /// Password hashing algorithm
+///
+/// Determines how new hashes are created. Verification detects the algorithm
+/// from the hash itself, so hashes made with any algorithm keep verifying.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HashAlgorithm {
     /// Bcrypt (slower but battle-tested)
-    Bcrypt,
-    /// Argon2 (modern, recommended)
-    Argon2,
+    Bcrypt {
+        /// Work factor (4-31)
+        cost: u32,
+    },
+    /// Argon2id (modern, recommended)
+    Argon2id {
+        /// Cost parameters
+        params: Argon2Params,
+    },
+}
+
+impl HashAlgorithm {
+    /// Bcrypt with the default cost
+    pub fn bcrypt() -> Self {
+        Self::Bcrypt {
+            cost: bcrypt::DEFAULT_COST,
+        }
+    }
+
+    /// Argon2id with the default parameters
+    pub fn argon2id() -> Self {
+        Self::Argon2id {
+            params: Argon2Params::default(),
+        }
+    }
+}
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory size in KiB
+    pub memory_kib: u32,
+    /// Number of iterations
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// The OWASP-recommended minimum: 19 MiB, 2 iterations, 1 lane
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
 }
 
 /// Password hasher for secure password hashing and verification.
 ///
-/// Supports multiple algorithms including Bcrypt and Argon2.
+/// Supports Bcrypt and Argon2id. Any supported hash verifies regardless of
+/// the configured algorithm, and [`needs_rehash`](Self::needs_rehash) reports
+/// hashes that should be upgraded to it.
 ///
 /// # Examples
 ///
@@ -29,7 +78,7 @@ pub enum HashAlgorithm {
 /// use armature_auth::{PasswordHasher, PasswordVerifier, password::HashAlgorithm};
 ///
 /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let hasher = PasswordHasher::new(HashAlgorithm::Argon2);
+/// let hasher = PasswordHasher::new(HashAlgorithm::argon2id());
 ///
 /// // Hash a password
 /// let hash = hasher.hash("supersecret")?;
@@ -49,12 +98,30 @@ pub enum HashAlgorithm {
 /// use armature_auth::{PasswordHasher, PasswordVerifier, password::HashAlgorithm};
 ///
 /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
-/// let hasher = PasswordHasher::new(HashAlgorithm::Bcrypt);
+/// let hasher = PasswordHasher::new(HashAlgorithm::Bcrypt { cost: 10 });
 /// let hash = hasher.hash("mypassword")?;
 /// assert!(hasher.verify("mypassword", &hash)?);
 /// # Ok(())
 /// # }
 /// ```
+///
+/// Upgrading hashes on login:
+///
+/// ```
+/// use armature_auth::{PasswordHasher, PasswordVerifier, password::HashAlgorithm};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let stored = PasswordHasher::new(HashAlgorithm::Bcrypt { cost: 4 }).hash("mypassword")?;
+/// let hasher = PasswordHasher::argon2id();
+///
+/// if hasher.verify("mypassword", &stored)? && hasher.needs_rehash(&stored) {
+///     let upgraded = hasher.hash("mypassword")?;
+///     // Save `upgraded` in place of `stored`
+/// #   assert!(!hasher.needs_rehash(&upgraded));
+/// }
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Clone)]
 pub struct PasswordHasher {
     algorithm: HashAlgorithm,
@@ -66,24 +133,57 @@ impl PasswordHasher {
         Self { algorithm }
     }
 
+    /// Create a bcrypt hasher with the default cost
+    pub fn bcrypt() -> Self {
+        Self::new(HashAlgorithm::bcrypt())
+    }
+
+    /// Create an Argon2id hasher with the default parameters
+    pub fn argon2id() -> Self {
+        Self::new(HashAlgorithm::argon2id())
+    }
+
+    /// Get the algorithm used for new hashes
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
     /// Hash a password
     pub fn hash(&self, password: &str) -> Result<String> {
         match self.algorithm {
-            HashAlgorithm::Bcrypt => self.hash_bcrypt(password),
-            HashAlgorithm::Argon2 => self.hash_argon2(password),
+            HashAlgorithm::Bcrypt { cost } => self.hash_bcrypt(password, cost),
+            HashAlgorithm::Argon2id { params } => self.hash_argon2(password, params),
+        }
+    }
+
+    /// Check whether a hash was made with a different algorithm or different
+    /// parameters than this hasher uses
+    ///
+    /// Call after a successful verification, and if it returns `true`, store
+    /// a fresh hash of the password. Unrecognized hashes always need a rehash.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        match self.algorithm {
+            HashAlgorithm::Bcrypt { cost } => bcrypt_cost(hash) != Some(cost),
+            HashAlgorithm::Argon2id { params } => argon2id_params(hash) != Some(params),
         }
     }
 
     /// Hash with bcrypt
-    fn hash_bcrypt(&self, password: &str) -> Result<String> {
-        bcrypt::hash(password, bcrypt::DEFAULT_COST)
-            .map_err(|e| AuthError::PasswordHashError(e.to_string()))
+    fn hash_bcrypt(&self, password: &str, cost: u32) -> Result<String> {
+        bcrypt::hash(password, cost).map_err(|e| AuthError::PasswordHashError(e.to_string()))
     }
 
-    /// Hash with argon2
-    fn hash_argon2(&self, password: &str) -> Result<String> {
+    /// Hash with argon2id
+    fn hash_argon2(&self, password: &str, params: Argon2Params) -> Result<String> {
         let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
+        let params = Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            None,
+        )
+        .map_err(|e| AuthError::PasswordHashError(e.to_string()))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
 
         let password_hash = argon2
             .hash_password(password.as_bytes(), &salt)
@@ -95,8 +195,32 @@ impl PasswordHasher {
 
 impl Default for PasswordHasher {
     fn default() -> Self {
-        Self::new(HashAlgorithm::Argon2)
+        Self::argon2id()
+    }
+}
+
+/// Read the cost of a bcrypt hash (`$2b$12$...`)
+fn bcrypt_cost(hash: &str) -> Option<u32> {
+    let rest = hash.strip_prefix("$2")?;
+    let (_variant, rest) = rest.split_once('$')?;
+    let (cost, _) = rest.split_once('$')?;
+    cost.parse().ok()
+}
+
+/// Read the parameters of a current-version Argon2id hash
+fn argon2id_params(hash: &str) -> Option<Argon2Params> {
+    let hash = PasswordHash::new(hash).ok()?;
+    if hash.algorithm != Algorithm::Argon2id.ident() || hash.version != Some(Version::V0x13 as u32)
+    {
+        return None;
     }
+
+    let params = Params::try_from(&hash).ok()?;
+    Some(Argon2Params {
+        memory_kib: params.m_cost(),
+        iterations: params.t_cost(),
+        parallelism: params.p_cost(),
+    })
 }
 
 /// Password verifier
@@ -132,9 +256,19 @@ impl PasswordVerifier for PasswordHasher {
 mod tests {
     use super::*;
 
+    fn fast_argon2id() -> HashAlgorithm {
+        HashAlgorithm::Argon2id {
+            params: Argon2Params {
+                memory_kib: 1024,
+                iterations: 1,
+                parallelism: 1,
+            },
+        }
+    }
+
     #[test]
     fn test_bcrypt_hashing() {
-        let hasher = PasswordHasher::new(HashAlgorithm::Bcrypt);
+        let hasher = PasswordHasher::new(HashAlgorithm::bcrypt());
         let password = "test-password-123";
 
         let hash = hasher.hash(password).unwrap();
@@ -146,11 +280,11 @@ mod tests {
 
     #[test]
     fn test_argon2_hashing() {
-        let hasher = PasswordHasher::new(HashAlgorithm::Argon2);
+        let hasher = PasswordHasher::new(HashAlgorithm::argon2id());
         let password = "test-password-456";
 
         let hash = hasher.hash(password).unwrap();
-        assert!(hash.starts_with("$argon2"));
+        assert!(hash.starts_with("$argon2id$"));
 
         assert!(hasher.verify(password, &hash).unwrap());
         assert!(!hasher.verify("wrong-password", &hash).unwrap());
@@ -158,17 +292,49 @@ mod tests {
 
     #[test]
     fn test_auto_detect_algorithm() {
-        let bcrypt_hasher = PasswordHasher::new(HashAlgorithm::Bcrypt);
-        let argon2_hasher = PasswordHasher::new(HashAlgorithm::Argon2);
+        let bcrypt_hasher = PasswordHasher::new(HashAlgorithm::Bcrypt { cost: 4 });
+        let argon2_hasher = PasswordHasher::new(fast_argon2id());
 
         let password = "test-password";
 
         let bcrypt_hash = bcrypt_hasher.hash(password).unwrap();
         let argon2_hash = argon2_hasher.hash(password).unwrap();
 
-        // Should work regardless of hasher algorithm
-        let verifier = PasswordHasher::default();
-        assert!(verifier.verify(password, &bcrypt_hash).unwrap());
-        assert!(verifier.verify(password, &argon2_hash).unwrap());
+        // Should work regardless of hasher algorithm or parameters
+        for verifier in [PasswordHasher::default(), bcrypt_hasher, argon2_hasher] {
+            assert!(verifier.verify(password, &bcrypt_hash).unwrap());
+            assert!(verifier.verify(password, &argon2_hash).unwrap());
+            assert!(!verifier.verify("wrong-password", &bcrypt_hash).unwrap());
+            assert!(!verifier.verify("wrong-password", &argon2_hash).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let bcrypt_hasher = PasswordHasher::new(HashAlgorithm::Bcrypt { cost: 4 });
+        let argon2_hasher = PasswordHasher::new(fast_argon2id());
+
+        let bcrypt_hash = bcrypt_hasher.hash("password").unwrap();
+        let argon2_hash = argon2_hasher.hash("password").unwrap();
+
+        // Same algorithm and parameters
+        assert!(!bcrypt_hasher.needs_rehash(&bcrypt_hash));
+        assert!(!argon2_hasher.needs_rehash(&argon2_hash));
+
+        // Different algorithm
+        assert!(argon2_hasher.needs_rehash(&bcrypt_hash));
+        assert!(bcrypt_hasher.needs_rehash(&argon2_hash));
+
+        // Different parameters
+        assert!(PasswordHasher::new(HashAlgorithm::Bcrypt { cost: 5 }).needs_rehash(&bcrypt_hash));
+        assert!(PasswordHasher::argon2id().needs_rehash(&argon2_hash));
+
+        // Other Argon2 variants and unknown formats
+        let argon2i_hash = Argon2::new(Algorithm::Argon2i, Version::V0x13, Params::default())
+            .hash_password(b"password", &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string();
+        assert!(PasswordHasher::argon2id().needs_rehash(&argon2i_hash));
+        assert!(argon2_hasher.needs_rehash("plaintext"));
     }
 }
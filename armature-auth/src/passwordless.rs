@@ -6,8 +6,9 @@
 //!
 //! - Magic link generation and verification
 //! - Email-based passwordless login
-//! - WebAuthn registration and authentication
+//! - WebAuthn registration and authentication (see [`crate::webauthn`])
 //! - Time-limited tokens
+//! - Pluggable, single-use token storage
 //!
 //! # Usage
 //!
@@ -28,15 +29,40 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Token Storage
+//!
+//! [`MagicLinkManager`] issues and verifies tokens through a
+//! [`MagicLinkStore`], so that any instance can verify a link issued by
+//! another. [`InMemoryMagicLinkStore`] suits a single instance.
+//!
+//! ```
+//! use armature_auth::passwordless::*;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), PasswordlessError> {
+//! let manager = MagicLinkManager::new(Arc::new(InMemoryMagicLinkStore::new()));
+//!
+//! let token = manager.issue("user@example.com").await?;
+//! let link = token.to_url("https://myapp.com/auth/verify");
+//!
+//! // The first verification consumes the token
+//! let verified = manager.verify(&token.token).await?;
+//! assert_eq!(verified.identifier, "user@example.com");
+//! assert!(manager.verify(&token.token).await.is_err());
+//! # Ok(())
+//! # }
+//! ```
 
+use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use thiserror::Error;
 
-#[cfg(feature = "webauthn")]
-use webauthn_rs::prelude::*;
-
 /// Passwordless authentication errors
 #[derive(Debug, Error)]
 pub enum PasswordlessError {
@@ -52,8 +78,17 @@ pub enum PasswordlessError {
     #[error("WebAuthn error: {0}")]
     WebAuthn(String),
 
+    #[error("No pending WebAuthn challenge")]
+    ChallengeNotFound,
+
+    #[error("Signature counter did not increase; authenticator may be cloned")]
+    ClonedAuthenticator,
+
     #[error("Feature not enabled: {0}")]
     FeatureNotEnabled(&'static str),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
 }
 
 /// Magic Link Token
@@ -144,135 +179,118 @@ impl MagicLinkToken {
     }
 }
 
-/// WebAuthn Configuration
-#[cfg(feature = "webauthn")]
-#[derive(Debug, Clone)]
-pub struct WebAuthnConfig {
-    /// Relying party ID (your domain)
-    pub rp_id: String,
-
-    /// Relying party name
-    pub rp_name: String,
+/// Magic link token storage trait (implement with Redis or your database)
+///
+/// Tokens are single-use: [`consume`](Self::consume) must remove the token
+/// atomically, so that concurrent verifications of the same link succeed at
+/// most once, even across instances.
+#[async_trait]
+pub trait MagicLinkStore: Send + Sync {
+    /// Save a token, expiring it after `ttl`
+    async fn save(
+        &self,
+        token: &MagicLinkToken,
+        ttl: std::time::Duration,
+    ) -> Result<(), PasswordlessError>;
 
-    /// Origin URL
-    pub origin: Url,
+    /// Remove and return a token, or `None` if it is unknown, already
+    /// consumed, or expired
+    async fn consume(&self, token: &str) -> Result<Option<MagicLinkToken>, PasswordlessError>;
 }
 
-#[cfg(feature = "webauthn")]
-impl WebAuthnConfig {
-    pub fn new(
-        rp_id: impl Into<String>,
-        rp_name: impl Into<String>,
-        origin: impl Into<String>,
-    ) -> Result<Self, PasswordlessError> {
-        Ok(Self {
-            rp_id: rp_id.into(),
-            rp_name: rp_name.into(),
-            origin: Url::parse(&origin.into())
-                .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?,
-        })
-    }
+/// In-memory magic link store
+///
+/// Suitable for a single instance. Expired tokens are purged on each save.
+#[derive(Debug, Default)]
+pub struct InMemoryMagicLinkStore {
+    tokens: Mutex<HashMap<String, (MagicLinkToken, Instant)>>,
 }
 
-/// WebAuthn Manager
-#[cfg(feature = "webauthn")]
-pub struct WebAuthnManager {
-    webauthn: Webauthn,
+impl InMemoryMagicLinkStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
 }
 
-#[cfg(feature = "webauthn")]
-impl WebAuthnManager {
-    /// Create new WebAuthn manager
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// use armature_auth::passwordless::*;
-    ///
-    /// # #[cfg(feature = "webauthn")]
-    /// # fn example() -> Result<(), PasswordlessError> {
-    /// let config = WebAuthnConfig::new(
-    ///     "example.com",
-    ///     "Example App",
-    ///     "https://example.com"
-    /// )?;
-    ///
-    /// let manager = WebAuthnManager::new(config)?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn new(config: WebAuthnConfig) -> Result<Self, PasswordlessError> {
-        let rp_origin = config.origin.clone();
-        let builder = WebauthnBuilder::new(&config.rp_id, &rp_origin)
-            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?;
-
-        let builder = builder.rp_name(&config.rp_name);
-
-        let webauthn = builder
-            .build()
-            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?;
+#[async_trait]
+impl MagicLinkStore for InMemoryMagicLinkStore {
+    async fn save(
+        &self,
+        token: &MagicLinkToken,
+        ttl: std::time::Duration,
+    ) -> Result<(), PasswordlessError> {
+        let now = Instant::now();
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.retain(|_, (_, deadline)| *deadline > now);
+        tokens.insert(token.token.clone(), (token.clone(), now + ttl));
+        Ok(())
+    }
 
-        Ok(Self { webauthn })
+    async fn consume(&self, token: &str) -> Result<Option<MagicLinkToken>, PasswordlessError> {
+        let mut tokens = self.tokens.lock().unwrap();
+        Ok(tokens
+            .remove(token)
+            .filter(|(_, deadline)| *deadline > Instant::now())
+            .map(|(token, _)| token))
     }
+}
 
-    /// Start registration (credential creation)
+/// Magic Link Manager
+///
+/// Issues magic link tokens and verifies them with injected storage.
+pub struct MagicLinkManager {
+    store: Arc<dyn MagicLinkStore>,
+    ttl: std::time::Duration,
+}
+
+impl MagicLinkManager {
+    /// Create new magic link manager with injected store
     ///
-    /// Returns challenge that should be sent to client.
-    pub fn start_registration(
-        &self,
-        user_id: &[u8],
-        username: &str,
-        display_name: &str,
-    ) -> Result<(CreationChallengeResponse, PasskeyRegistration), PasswordlessError> {
-        let user_unique_id = UserId::from(user_id);
-
-        let (ccr, reg_state) = self
-            .webauthn
-            .start_passkey_registration(user_unique_id, username, display_name, None)
-            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?;
-
-        Ok((ccr, reg_state))
+    /// Tokens expire after 15 minutes by default.
+    pub fn new(store: Arc<dyn MagicLinkStore>) -> Self {
+        Self {
+            store,
+            ttl: std::time::Duration::from_secs(15 * 60),
+        }
     }
 
-    /// Finish registration
-    ///
-    /// Verifies client response and returns credential.
-    pub fn finish_registration(
-        &self,
-        reg: &RegisterPublicKeyCredential,
-        state: &PasskeyRegistration,
-    ) -> Result<Passkey, PasswordlessError> {
-        self.webauthn
-            .finish_passkey_registration(reg, state)
-            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))
+    /// Set token lifetime
+    pub fn with_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.ttl = ttl;
+        self
     }
 
-    /// Start authentication
-    ///
-    /// Returns challenge that should be sent to client.
-    pub fn start_authentication(
+    /// Generate a token for `identifier` and save it to the store
+    pub async fn issue(
         &self,
-        passkeys: &[Passkey],
-    ) -> Result<(RequestChallengeResponse, PasskeyAuthentication), PasswordlessError> {
-        self.webauthn
-            .start_passkey_authentication(passkeys)
-            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))
+        identifier: impl Into<String>,
+    ) -> Result<MagicLinkToken, PasswordlessError> {
+        let token = MagicLinkToken::generate(identifier, self.ttl)?;
+        self.store.save(&token, self.ttl).await?;
+        Ok(token)
     }
 
-    /// Finish authentication
+    /// Verify and consume a token
     ///
-    /// Verifies client response.
-    pub fn finish_authentication(
-        &self,
-        auth: &PublicKeyCredential,
-        state: &PasskeyAuthentication,
-    ) -> Result<AuthenticationResult, PasswordlessError> {
-        self.webauthn
-            .finish_passkey_authentication(auth, state)
-            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))
+    /// Returns the token, marked used, if it was issued, has not expired,
+    /// and has not been verified before.
+    pub async fn verify(&self, token: &str) -> Result<MagicLinkToken, PasswordlessError> {
+        let mut token = self
+            .store
+            .consume(token)
+            .await?
+            .ok_or(PasswordlessError::InvalidToken)?;
+
+        token.verify()?;
+        token.mark_used();
+        Ok(token)
     }
 }
 
+#[cfg(feature = "webauthn")]
+pub use crate::webauthn::{WebAuthnConfig, WebAuthnManager};
+
 #[cfg(not(feature = "webauthn"))]
 pub struct WebAuthnManager;
 
@@ -341,4 +359,58 @@ mod tests {
             Err(PasswordlessError::TokenExpired)
         ));
     }
+
+    #[tokio::test]
+    async fn test_magic_link_manager() {
+        let manager = MagicLinkManager::new(Arc::new(InMemoryMagicLinkStore::new()));
+
+        let token = manager.issue("user@example.com").await.unwrap();
+        let verified = manager.verify(&token.token).await.unwrap();
+        assert_eq!(verified.identifier, "user@example.com");
+        assert!(verified.used);
+
+        assert!(matches!(
+            manager.verify(&token.token).await,
+            Err(PasswordlessError::InvalidToken)
+        ));
+        assert!(matches!(
+            manager.verify("unknown").await,
+            Err(PasswordlessError::InvalidToken)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_magic_link_store_expiry() {
+        let manager = MagicLinkManager::new(Arc::new(InMemoryMagicLinkStore::new()))
+            .with_ttl(std::time::Duration::from_millis(10));
+
+        let token = manager.issue("user@example.com").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        assert!(manager.verify(&token.token).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_magic_link_consumed_once_concurrently() {
+        let manager = Arc::new(MagicLinkManager::new(Arc::new(
+            InMemoryMagicLinkStore::new(),
+        )));
+        let token = manager.issue("user@example.com").await.unwrap();
+
+        let attempts: Vec<_> = (0..32)
+            .map(|_| {
+                let manager = Arc::clone(&manager);
+                let token = token.token.clone();
+                tokio::spawn(async move { manager.verify(&token).await.is_ok() })
+            })
+            .collect();
+
+        let mut successes = 0;
+        for attempt in attempts {
+            if attempt.await.unwrap() {
+                successes += 1;
+            }
+        }
+        assert_eq!(successes, 1);
+    }
 }
@@ -5,7 +5,7 @@ use crate::oauth2::{
     GenericOAuth2Provider, OAuth2Config, OAuth2Provider, OAuth2Token, OAuth2UserInfo,
 };
 use async_trait::async_trait;
-use oauth2::CsrfToken;
+use oauth2::{CsrfToken, PkceCodeVerifier};
 use url::Url;
 
 /// Auth0 provider configuration
@@ -16,6 +16,7 @@ pub struct Auth0Config {
     pub redirect_url: String,
     pub domain: String, // e.g., "myapp.us.auth0.com" or "mycompany.auth0.com"
     pub scopes: Vec<String>,
+    pub pkce: bool,               // PKCE (S256) on the authorization code flow
     pub audience: Option<String>, // Optional API audience
 }
 
@@ -36,6 +37,7 @@ impl Auth0Config {
                 "email".to_string(),
                 "profile".to_string(),
             ],
+            pkce: true,
             audience: None,
         }
     }
@@ -45,6 +47,12 @@ impl Auth0Config {
         self
     }
 
+    /// Enable or disable PKCE (default: enabled)
+    pub fn with_pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
+
     pub fn with_audience(mut self, audience: String) -> Self {
         self.audience = Some(audience);
         self
@@ -71,6 +79,7 @@ impl Auth0Provider {
             config.redirect_url,
         )
         .with_scopes(config.scopes)
+        .with_pkce(config.pkce)
         .with_user_info_url(user_info_url);
 
         let inner = GenericOAuth2Provider::new("auth0".to_string(), oauth2_config)?;
@@ -88,19 +97,23 @@ impl OAuth2Provider for Auth0Provider {
         self.inner.name()
     }
 
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)> {
-        let (mut url, csrf) = self.inner.authorization_url()?;
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)> {
+        let (mut url, csrf, pkce_verifier) = self.inner.authorization_url()?;
 
         // Add audience parameter if specified
         if let Some(ref audience) = self.audience {
             url.query_pairs_mut().append_pair("audience", audience);
         }
 
-        Ok((url, csrf))
+        Ok((url, csrf, pkce_verifier))
     }
 
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token> {
-        self.inner.exchange_code(code).await
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token> {
+        self.inner.exchange_code(code, pkce_verifier).await
     }
 
     async fn get_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo> {
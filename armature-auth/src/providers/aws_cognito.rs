@@ -5,7 +5,7 @@ use crate::oauth2::{
     GenericOAuth2Provider, OAuth2Config, OAuth2Provider, OAuth2Token, OAuth2UserInfo,
 };
 use async_trait::async_trait;
-use oauth2::CsrfToken;
+use oauth2::{CsrfToken, PkceCodeVerifier};
 use url::Url;
 
 /// AWS Cognito provider configuration
@@ -17,6 +17,7 @@ pub struct AwsCognitoConfig {
     pub user_pool_domain: String, // e.g., "my-app.auth.us-east-1.amazoncognito.com"
     pub region: String,           // e.g., "us-east-1"
     pub scopes: Vec<String>,
+    pub pkce: bool, // PKCE (S256) on the authorization code flow
 }
 
 impl AwsCognitoConfig {
@@ -38,6 +39,7 @@ impl AwsCognitoConfig {
                 "email".to_string(),
                 "profile".to_string(),
             ],
+            pkce: true,
         }
     }
 
@@ -45,6 +47,12 @@ impl AwsCognitoConfig {
         self.scopes = scopes;
         self
     }
+
+    /// Enable or disable PKCE (default: enabled)
+    pub fn with_pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
 }
 
 /// AWS Cognito provider
@@ -66,6 +74,7 @@ impl AwsCognitoProvider {
             config.redirect_url,
         )
         .with_scopes(config.scopes)
+        .with_pkce(config.pkce)
         .with_user_info_url(user_info_url);
 
         let inner = GenericOAuth2Provider::new("aws-cognito".to_string(), oauth2_config)?;
@@ -80,12 +89,16 @@ impl OAuth2Provider for AwsCognitoProvider {
         self.inner.name()
     }
 
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)> {
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)> {
         self.inner.authorization_url()
     }
 
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token> {
-        self.inner.exchange_code(code).await
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token> {
+        self.inner.exchange_code(code, pkce_verifier).await
     }
 
     async fn get_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo> {
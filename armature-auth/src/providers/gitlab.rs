@@ -31,6 +31,7 @@ impl GitLabProvider {
             redirect_url,
         )
         .with_scopes(vec!["read_user".to_string()])
+        .with_pkce(true)
         .with_user_info_url(USER_INFO_URL.to_string())
     }
 
@@ -5,7 +5,7 @@ use crate::oauth2::{
     GenericOAuth2Provider, OAuth2Config, OAuth2Provider, OAuth2Token, OAuth2UserInfo,
 };
 use async_trait::async_trait;
-use oauth2::CsrfToken;
+use oauth2::{CsrfToken, PkceCodeVerifier};
 use url::Url;
 
 /// Google OAuth2 provider configuration
@@ -15,6 +15,7 @@ pub struct GoogleConfig {
     pub client_secret: String,
     pub redirect_url: String,
     pub scopes: Vec<String>,
+    pub pkce: bool, // PKCE (S256) on the authorization code flow
 }
 
 impl GoogleConfig {
@@ -28,6 +29,7 @@ impl GoogleConfig {
                 "email".to_string(),
                 "profile".to_string(),
             ],
+            pkce: true,
         }
     }
 
@@ -35,6 +37,12 @@ impl GoogleConfig {
         self.scopes = scopes;
         self
     }
+
+    /// Enable or disable PKCE (default: enabled)
+    pub fn with_pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
 }
 
 /// Google OAuth2 provider
@@ -52,6 +60,7 @@ impl GoogleProvider {
             config.redirect_url,
         )
         .with_scopes(config.scopes)
+        .with_pkce(config.pkce)
         .with_user_info_url("https://www.googleapis.com/oauth2/v2/userinfo".to_string());
 
         let inner = GenericOAuth2Provider::new("google".to_string(), oauth2_config)?;
@@ -66,12 +75,16 @@ impl OAuth2Provider for GoogleProvider {
         self.inner.name()
     }
 
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)> {
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)> {
         self.inner.authorization_url()
     }
 
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token> {
-        self.inner.exchange_code(code).await
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token> {
+        self.inner.exchange_code(code, pkce_verifier).await
     }
 
     async fn get_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo> {
@@ -5,7 +5,7 @@ use crate::oauth2::{
     GenericOAuth2Provider, OAuth2Config, OAuth2Provider, OAuth2Token, OAuth2UserInfo,
 };
 use async_trait::async_trait;
-use oauth2::CsrfToken;
+use oauth2::{CsrfToken, PkceCodeVerifier};
 use url::Url;
 
 /// Microsoft Entra provider configuration
@@ -16,6 +16,7 @@ pub struct MicrosoftEntraConfig {
     pub redirect_url: String,
     pub tenant_id: String, // "common", "organizations", "consumers", or specific tenant ID
     pub scopes: Vec<String>,
+    pub pkce: bool, // PKCE (S256) on the authorization code flow
 }
 
 impl MicrosoftEntraConfig {
@@ -35,6 +36,7 @@ impl MicrosoftEntraConfig {
                 "email".to_string(),
                 "profile".to_string(),
             ],
+            pkce: true,
         }
     }
 
@@ -43,6 +45,12 @@ impl MicrosoftEntraConfig {
         self
     }
 
+    /// Enable or disable PKCE (default: enabled)
+    pub fn with_pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
+
     /// Create config for common tenant (any Azure AD account)
     pub fn common(client_id: String, client_secret: String, redirect_url: String) -> Self {
         Self::new(client_id, client_secret, redirect_url, "common".to_string())
@@ -93,6 +101,7 @@ impl MicrosoftEntraProvider {
             config.redirect_url,
         )
         .with_scopes(config.scopes)
+        .with_pkce(config.pkce)
         .with_user_info_url("https://graph.microsoft.com/v1.0/me".to_string());
 
         let inner = GenericOAuth2Provider::new("microsoft-entra".to_string(), oauth2_config)?;
@@ -107,12 +116,16 @@ impl OAuth2Provider for MicrosoftEntraProvider {
         self.inner.name()
     }
 
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)> {
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)> {
         self.inner.authorization_url()
     }
 
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token> {
-        self.inner.exchange_code(code).await
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token> {
+        self.inner.exchange_code(code, pkce_verifier).await
     }
 
     async fn get_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo> {
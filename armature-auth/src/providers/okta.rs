@@ -5,7 +5,7 @@ use crate::oauth2::{
     GenericOAuth2Provider, OAuth2Config, OAuth2Provider, OAuth2Token, OAuth2UserInfo,
 };
 use async_trait::async_trait;
-use oauth2::CsrfToken;
+use oauth2::{CsrfToken, PkceCodeVerifier};
 use url::Url;
 
 /// Okta provider configuration
@@ -16,6 +16,7 @@ pub struct OktaConfig {
     pub redirect_url: String,
     pub domain: String, // e.g., "dev-12345.okta.com" or "mycompany.okta.com"
     pub scopes: Vec<String>,
+    pub pkce: bool, // PKCE (S256) on the authorization code flow
 }
 
 impl OktaConfig {
@@ -35,6 +36,7 @@ impl OktaConfig {
                 "email".to_string(),
                 "profile".to_string(),
             ],
+            pkce: true,
         }
     }
 
@@ -42,6 +44,12 @@ impl OktaConfig {
         self.scopes = scopes;
         self
     }
+
+    /// Enable or disable PKCE (default: enabled)
+    pub fn with_pkce(mut self, pkce: bool) -> Self {
+        self.pkce = pkce;
+        self
+    }
 }
 
 /// Okta provider
@@ -63,6 +71,7 @@ impl OktaProvider {
             config.redirect_url,
         )
         .with_scopes(config.scopes)
+        .with_pkce(config.pkce)
         .with_user_info_url(user_info_url);
 
         let inner = GenericOAuth2Provider::new("okta".to_string(), oauth2_config)?;
@@ -77,12 +86,16 @@ impl OAuth2Provider for OktaProvider {
         self.inner.name()
     }
 
-    fn authorization_url(&self) -> Result<(Url, CsrfToken)> {
+    fn authorization_url(&self) -> Result<(Url, CsrfToken, Option<PkceCodeVerifier>)> {
         self.inner.authorization_url()
     }
 
-    async fn exchange_code(&self, code: String) -> Result<OAuth2Token> {
-        self.inner.exchange_code(code).await
+    async fn exchange_code(
+        &self,
+        code: String,
+        pkce_verifier: Option<PkceCodeVerifier>,
+    ) -> Result<OAuth2Token> {
+        self.inner.exchange_code(code, pkce_verifier).await
     }
 
     async fn get_user_info(&self, token: &OAuth2Token) -> Result<OAuth2UserInfo> {
@@ -0,0 +1,302 @@
+//! Login Throttling
+//!
+//! Slows down credential stuffing and password guessing by locking an
+//! account after repeated failed logins.
+//!
+//! Unlike request rate limiting, attempts are keyed on the login identity
+//! (username, email, ...), so an attacker spreading guesses across many IP
+//! addresses is still throttled.
+//!
+//! # Features
+//!
+//! - Lockout after a configurable number of failures
+//! - Exponential backoff for failures past the threshold
+//! - Automatic reset after a quiet window, or on successful login
+//! - Database-agnostic attempt storage
+//!
+//! # Usage
+//!
+//! ```
+//! use armature_auth::throttle::*;
+//! use armature_auth::{AuthError, AuthService};
+//! use std::sync::Arc;
+//!
+//! # async fn example(auth: AuthService, email: &str, password: &str, hash: &str)
+//! #     -> Result<(), AuthError> {
+//! let throttle = LoginThrottle::new(Arc::new(InMemoryLoginAttemptStore::new()));
+//!
+//! // Fails with AuthError::AccountLocked while locked out
+//! throttle.check(email).await?;
+//!
+//! if auth.verify_password(password, hash)? {
+//!     throttle.record_success(email).await?;
+//! } else {
+//!     throttle.record_failure(email).await?;
+//!     return Err(AuthError::InvalidCredentials);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{AuthError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Failed login attempts for one identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoginAttempts {
+    /// Consecutive failures
+    pub failures: u32,
+
+    /// Time of the most recent failure
+    pub last_failure: DateTime<Utc>,
+}
+
+/// Login attempt storage trait (implement with Redis or your database)
+#[async_trait]
+pub trait LoginAttemptStore: Send + Sync {
+    /// Get the attempts for an identifier, if any have not expired
+    async fn get(&self, identifier: &str) -> Result<Option<LoginAttempts>>;
+
+    /// Atomically record a failure and return the updated attempts
+    ///
+    /// The record expires `ttl` after this failure, after which counting
+    /// starts over.
+    async fn record_failure(&self, identifier: &str, ttl: Duration) -> Result<LoginAttempts>;
+
+    /// Clear the attempts for an identifier
+    async fn reset(&self, identifier: &str) -> Result<()>;
+}
+
+/// In-memory login attempt store
+///
+/// Suitable for a single instance.
+#[derive(Debug, Default)]
+pub struct InMemoryLoginAttemptStore {
+    attempts: Mutex<HashMap<String, (LoginAttempts, DateTime<Utc>)>>,
+}
+
+impl InMemoryLoginAttemptStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for InMemoryLoginAttemptStore {
+    async fn get(&self, identifier: &str) -> Result<Option<LoginAttempts>> {
+        let attempts = self.attempts.lock().unwrap();
+        Ok(attempts
+            .get(identifier)
+            .filter(|(_, expires_at)| *expires_at > Utc::now())
+            .map(|(attempts, _)| *attempts))
+    }
+
+    async fn record_failure(&self, identifier: &str, ttl: Duration) -> Result<LoginAttempts> {
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+
+        let mut attempts = self.attempts.lock().unwrap();
+        attempts.retain(|_, (_, expires_at)| *expires_at > now);
+
+        let (entry, entry_expires_at) = attempts.entry(identifier.to_string()).or_insert((
+            LoginAttempts {
+                failures: 0,
+                last_failure: now,
+            },
+            expires_at,
+        ));
+        entry.failures = entry.failures.saturating_add(1);
+        entry.last_failure = now;
+        *entry_expires_at = expires_at;
+        Ok(*entry)
+    }
+
+    async fn reset(&self, identifier: &str) -> Result<()> {
+        self.attempts.lock().unwrap().remove(identifier);
+        Ok(())
+    }
+}
+
+/// Login throttle
+///
+/// After `max_failures` consecutive failures, the identifier is locked for
+/// `lockout`, doubling with each further failure up to `max_lockout`.
+/// Failures are forgotten once none has occurred for `window`, or on a
+/// successful login.
+pub struct LoginThrottle {
+    store: Arc<dyn LoginAttemptStore>,
+    max_failures: u32,
+    lockout: Duration,
+    max_lockout: Duration,
+    window: Duration,
+}
+
+impl LoginThrottle {
+    /// Create new login throttle with injected store
+    ///
+    /// Defaults to locking for 30 seconds after 5 failures, backing off to at
+    /// most 15 minutes, and forgetting failures after 15 quiet minutes.
+    pub fn new(store: Arc<dyn LoginAttemptStore>) -> Self {
+        Self {
+            store,
+            max_failures: 5,
+            lockout: Duration::from_secs(30),
+            max_lockout: Duration::from_secs(15 * 60),
+            window: Duration::from_secs(15 * 60),
+        }
+    }
+
+    /// Set the number of failures that triggers a lockout
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    /// Set the first lockout duration and the cap it doubles up to
+    pub fn with_lockout(mut self, lockout: Duration, max_lockout: Duration) -> Self {
+        self.lockout = lockout;
+        self.max_lockout = max_lockout.max(lockout);
+        self
+    }
+
+    /// Set how long after the last failure the count resets
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Check whether an identifier may attempt to log in
+    ///
+    /// Call before verifying credentials. Fails with
+    /// [`AuthError::AccountLocked`] while the identifier is locked out.
+    pub async fn check(&self, identifier: &str) -> Result<()> {
+        let Some(attempts) = self.store.get(identifier).await? else {
+            return Ok(());
+        };
+
+        match self.retry_after(&attempts) {
+            Some(retry_after) => Err(AuthError::AccountLocked { retry_after }),
+            None => Ok(()),
+        }
+    }
+
+    /// Record a failed login
+    pub async fn record_failure(&self, identifier: &str) -> Result<()> {
+        self.store.record_failure(identifier, self.window).await?;
+        Ok(())
+    }
+
+    /// Record a successful login, clearing previous failures
+    pub async fn record_success(&self, identifier: &str) -> Result<()> {
+        self.store.reset(identifier).await
+    }
+
+    /// Remaining lockout time, if `attempts` lock the identifier
+    fn retry_after(&self, attempts: &LoginAttempts) -> Option<Duration> {
+        let excess = attempts.failures.checked_sub(self.max_failures)?;
+        let lockout = 2u32
+            .checked_pow(excess)
+            .and_then(|factor| self.lockout.checked_mul(factor))
+            .map_or(self.max_lockout, |lockout| lockout.min(self.max_lockout));
+
+        let locked_for = (Utc::now() - attempts.last_failure)
+            .to_std()
+            .unwrap_or_default();
+        lockout.checked_sub(locked_for).filter(|d| !d.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle() -> LoginThrottle {
+        LoginThrottle::new(Arc::new(InMemoryLoginAttemptStore::new()))
+            .with_max_failures(3)
+            .with_lockout(Duration::from_secs(60), Duration::from_secs(600))
+    }
+
+    async fn fail(throttle: &LoginThrottle, identifier: &str, times: u32) {
+        for _ in 0..times {
+            throttle.record_failure(identifier).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lockout_after_threshold() {
+        let throttle = throttle();
+
+        fail(&throttle, "user@example.com", 2).await;
+        assert!(throttle.check("user@example.com").await.is_ok());
+
+        fail(&throttle, "user@example.com", 1).await;
+        let Err(AuthError::AccountLocked { retry_after }) =
+            throttle.check("user@example.com").await
+        else {
+            panic!("expected lockout");
+        };
+        assert!(retry_after > Duration::from_secs(55) && retry_after <= Duration::from_secs(60));
+
+        // Other identities are unaffected
+        assert!(throttle.check("other@example.com").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lockout_backoff() {
+        let throttle = throttle();
+
+        // Each failure past the threshold doubles the lockout, up to the cap
+        fail(&throttle, "user@example.com", 5).await;
+        let Err(AuthError::AccountLocked { retry_after }) =
+            throttle.check("user@example.com").await
+        else {
+            panic!("expected lockout");
+        };
+        assert!(retry_after > Duration::from_secs(235) && retry_after <= Duration::from_secs(240));
+
+        fail(&throttle, "user@example.com", 40).await;
+        let Err(AuthError::AccountLocked { retry_after }) =
+            throttle.check("user@example.com").await
+        else {
+            panic!("expected lockout");
+        };
+        assert!(retry_after > Duration::from_secs(595) && retry_after <= Duration::from_secs(600));
+    }
+
+    #[tokio::test]
+    async fn test_reset_on_success() {
+        let throttle = throttle();
+
+        fail(&throttle, "user@example.com", 3).await;
+        assert!(throttle.check("user@example.com").await.is_err());
+
+        throttle.record_success("user@example.com").await.unwrap();
+        assert!(throttle.check("user@example.com").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reset_after_window() {
+        let throttle = LoginThrottle::new(Arc::new(InMemoryLoginAttemptStore::new()))
+            .with_max_failures(2)
+            .with_lockout(Duration::from_millis(20), Duration::from_millis(20))
+            .with_window(Duration::from_millis(50));
+
+        fail(&throttle, "user@example.com", 2).await;
+        assert!(throttle.check("user@example.com").await.is_err());
+
+        // The lockout ends, but failures still count until the window passes
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(throttle.check("user@example.com").await.is_ok());
+        fail(&throttle, "user@example.com", 1).await;
+        assert!(throttle.check("user@example.com").await.is_err());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        fail(&throttle, "user@example.com", 1).await;
+        assert!(throttle.check("user@example.com").await.is_ok());
+    }
+}
@@ -0,0 +1,766 @@
+//! WebAuthn Passkeys
+//!
+//! Complete registration and authentication ceremonies built on
+//! `webauthn-rs`, with pluggable credential and challenge storage.
+//!
+//! # Features
+//!
+//! - Passkey registration and authentication
+//! - Origin and relying party ID validation
+//! - Single-use, expiring challenges
+//! - Signature counter checks to detect cloned authenticators
+//! - Database-agnostic credential storage
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use armature_auth::passwordless::PasswordlessError;
+//! use armature_auth::webauthn::*;
+//! use std::sync::Arc;
+//!
+//! # async fn example(
+//! #     registration: RegisterPublicKeyCredential,
+//! #     assertion: PublicKeyCredential,
+//! # ) -> Result<(), PasswordlessError> {
+//! let config = WebAuthnConfig::new("example.com", "Example App", "https://example.com")?;
+//! let manager = WebAuthnManager::new(config, Arc::new(InMemoryWebAuthnCredentialStore::new()))?;
+//!
+//! let user = WebAuthnUser::new(Uuid::new_v4(), "alice", "Alice");
+//!
+//! // Registration: pass the options to navigator.credentials.create()
+//! let options = manager.start_registration(&user).await?;
+//! let credential = manager.finish_registration(user.id, &registration).await?;
+//!
+//! // Authentication: pass the options to navigator.credentials.get()
+//! let options = manager.start_authentication(user.id).await?;
+//! let verified = manager.finish_authentication(user.id, &assertion).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::passwordless::PasswordlessError;
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url::Url;
+use webauthn_rs::prelude::{
+    Credential, PasskeyAuthentication, PasskeyRegistration, Webauthn, WebauthnBuilder,
+    WebauthnError,
+};
+
+pub use webauthn_rs::prelude::{
+    CreationChallengeResponse as CredentialCreationOptions, Passkey, PublicKeyCredential,
+    RegisterPublicKeyCredential, RequestChallengeResponse as CredentialRequestOptions, Uuid,
+};
+
+type Result<T> = std::result::Result<T, PasswordlessError>;
+
+/// WebAuthn Configuration
+#[derive(Debug, Clone)]
+pub struct WebAuthnConfig {
+    /// Relying party ID (your domain)
+    pub rp_id: String,
+
+    /// Relying party name
+    pub rp_name: String,
+
+    /// Origin URL
+    pub origin: Url,
+}
+
+impl WebAuthnConfig {
+    pub fn new(
+        rp_id: impl Into<String>,
+        rp_name: impl Into<String>,
+        origin: impl Into<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            rp_id: rp_id.into(),
+            rp_name: rp_name.into(),
+            origin: Url::parse(&origin.into())
+                .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?,
+        })
+    }
+}
+
+/// User registering or presenting a passkey
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebAuthnUser {
+    /// Stable, opaque user handle (never an email or username)
+    pub id: Uuid,
+
+    /// Account name shown by the authenticator
+    pub name: String,
+
+    /// Display name shown by the authenticator
+    pub display_name: String,
+}
+
+impl WebAuthnUser {
+    /// Create a new user
+    pub fn new(id: Uuid, name: impl Into<String>, display_name: impl Into<String>) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            display_name: display_name.into(),
+        }
+    }
+}
+
+/// A registered passkey
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCredential {
+    /// Credential ID (base64url)
+    pub id: String,
+
+    /// Owning user
+    pub user_id: Uuid,
+
+    /// Public key and verification state
+    pub passkey: Passkey,
+
+    /// Last signature counter seen (0 if the authenticator has none)
+    pub counter: u32,
+
+    /// Registration timestamp
+    pub created_at: DateTime<Utc>,
+
+    /// Last successful authentication
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A successful passkey authentication
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedAuth {
+    /// Authenticated user
+    pub user_id: Uuid,
+
+    /// Credential used (base64url)
+    pub credential_id: String,
+
+    /// Signature counter reported by the authenticator
+    pub counter: u32,
+
+    /// Whether the authenticator verified the user (PIN, biometric)
+    pub user_verified: bool,
+}
+
+/// Server-side state of an unfinished ceremony
+///
+/// Holds the challenge sent to the client. Keep it on the server: anyone who
+/// can modify it can bypass verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WebAuthnCeremony {
+    /// Waiting for navigator.credentials.create()
+    Registration(PasskeyRegistration),
+    /// Waiting for navigator.credentials.get()
+    Authentication(PasskeyAuthentication),
+}
+
+/// Passkey storage trait (implement with your database)
+#[async_trait]
+pub trait WebAuthnCredentialStore: Send + Sync {
+    /// Save or update a credential
+    async fn save(&self, credential: &StoredCredential) -> Result<()>;
+
+    /// Find a credential by ID
+    async fn find(&self, credential_id: &str) -> Result<Option<StoredCredential>>;
+
+    /// List all credentials for a user
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<StoredCredential>>;
+}
+
+/// Ceremony state storage trait (implement with Redis or your database)
+///
+/// One ceremony may be pending per user; starting another replaces it.
+#[async_trait]
+pub trait WebAuthnChallengeStore: Send + Sync {
+    /// Save a pending ceremony, expiring after `ttl`
+    async fn save(&self, user_id: Uuid, ceremony: &WebAuthnCeremony, ttl: Duration) -> Result<()>;
+
+    /// Atomically remove and return the pending ceremony, if not expired
+    async fn consume(&self, user_id: Uuid) -> Result<Option<WebAuthnCeremony>>;
+}
+
+/// In-memory passkey store
+///
+/// Suitable for a single instance.
+#[derive(Debug, Default)]
+pub struct InMemoryWebAuthnCredentialStore {
+    credentials: Mutex<HashMap<String, StoredCredential>>,
+}
+
+impl InMemoryWebAuthnCredentialStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl WebAuthnCredentialStore for InMemoryWebAuthnCredentialStore {
+    async fn save(&self, credential: &StoredCredential) -> Result<()> {
+        let mut credentials = self.credentials.lock().unwrap();
+        credentials.insert(credential.id.clone(), credential.clone());
+        Ok(())
+    }
+
+    async fn find(&self, credential_id: &str) -> Result<Option<StoredCredential>> {
+        Ok(self.credentials.lock().unwrap().get(credential_id).cloned())
+    }
+
+    async fn list_by_user(&self, user_id: Uuid) -> Result<Vec<StoredCredential>> {
+        let credentials = self.credentials.lock().unwrap();
+        Ok(credentials
+            .values()
+            .filter(|c| c.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+}
+
+/// In-memory ceremony state store
+///
+/// Suitable for a single instance. Expired ceremonies are purged on each save.
+#[derive(Debug, Default)]
+pub struct InMemoryWebAuthnChallengeStore {
+    ceremonies: Mutex<HashMap<Uuid, (WebAuthnCeremony, Instant)>>,
+}
+
+impl InMemoryWebAuthnChallengeStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl WebAuthnChallengeStore for InMemoryWebAuthnChallengeStore {
+    async fn save(&self, user_id: Uuid, ceremony: &WebAuthnCeremony, ttl: Duration) -> Result<()> {
+        let now = Instant::now();
+        let mut ceremonies = self.ceremonies.lock().unwrap();
+        ceremonies.retain(|_, (_, expires_at)| *expires_at > now);
+        ceremonies.insert(user_id, (ceremony.clone(), now + ttl));
+        Ok(())
+    }
+
+    async fn consume(&self, user_id: Uuid) -> Result<Option<WebAuthnCeremony>> {
+        let mut ceremonies = self.ceremonies.lock().unwrap();
+        Ok(ceremonies
+            .remove(&user_id)
+            .filter(|(_, expires_at)| *expires_at > Instant::now())
+            .map(|(ceremony, _)| ceremony))
+    }
+}
+
+/// WebAuthn Manager
+///
+/// Runs passkey ceremonies against injected credential and challenge
+/// storage. Challenges expire after 5 minutes by default.
+pub struct WebAuthnManager {
+    webauthn: Webauthn,
+    credentials: Arc<dyn WebAuthnCredentialStore>,
+    challenges: Arc<dyn WebAuthnChallengeStore>,
+    challenge_ttl: Duration,
+}
+
+impl WebAuthnManager {
+    /// Create new WebAuthn manager with injected credential store
+    ///
+    /// Pending ceremonies are kept in memory; use
+    /// [`with_challenge_store`](Self::with_challenge_store) when running
+    /// more than one instance.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use armature_auth::passwordless::PasswordlessError;
+    /// use armature_auth::webauthn::*;
+    /// use std::sync::Arc;
+    ///
+    /// # fn example() -> Result<(), PasswordlessError> {
+    /// let config = WebAuthnConfig::new(
+    ///     "example.com",
+    ///     "Example App",
+    ///     "https://example.com"
+    /// )?;
+    ///
+    /// let manager = WebAuthnManager::new(config, Arc::new(InMemoryWebAuthnCredentialStore::new()))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(
+        config: WebAuthnConfig,
+        credentials: Arc<dyn WebAuthnCredentialStore>,
+    ) -> Result<Self> {
+        let builder = WebauthnBuilder::new(&config.rp_id, &config.origin)
+            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?;
+
+        let builder = builder.rp_name(&config.rp_name);
+
+        let webauthn = builder
+            .build()
+            .map_err(|e| PasswordlessError::WebAuthn(e.to_string()))?;
+
+        Ok(Self {
+            webauthn,
+            credentials,
+            challenges: Arc::new(InMemoryWebAuthnChallengeStore::new()),
+            challenge_ttl: Duration::from_secs(5 * 60),
+        })
+    }
+
+    /// Set the store for pending ceremonies
+    pub fn with_challenge_store(mut self, challenges: Arc<dyn WebAuthnChallengeStore>) -> Self {
+        self.challenges = challenges;
+        self
+    }
+
+    /// Set how long a ceremony may take (default: 5 minutes)
+    pub fn with_challenge_ttl(mut self, ttl: Duration) -> Self {
+        self.challenge_ttl = ttl;
+        self
+    }
+
+    /// Start registering a passkey
+    ///
+    /// Returns the options to pass to `navigator.credentials.create()`.
+    /// Authenticators that already hold one of the user's passkeys are
+    /// excluded.
+    pub async fn start_registration(
+        &self,
+        user: &WebAuthnUser,
+    ) -> Result<CredentialCreationOptions> {
+        let existing: Vec<_> = self
+            .credentials
+            .list_by_user(user.id)
+            .await?
+            .iter()
+            .map(|c| c.passkey.cred_id().clone())
+            .collect();
+
+        let (options, state) = self
+            .webauthn
+            .start_passkey_registration(
+                user.id,
+                &user.name,
+                &user.display_name,
+                Some(existing).filter(|ids| !ids.is_empty()),
+            )
+            .map_err(webauthn_error)?;
+
+        self.challenges
+            .save(
+                user.id,
+                &WebAuthnCeremony::Registration(state),
+                self.challenge_ttl,
+            )
+            .await?;
+        Ok(options)
+    }
+
+    /// Finish registering a passkey
+    ///
+    /// Verifies the client response against the pending challenge, origin
+    /// and relying party ID, then stores the credential.
+    pub async fn finish_registration(
+        &self,
+        user_id: Uuid,
+        response: &RegisterPublicKeyCredential,
+    ) -> Result<StoredCredential> {
+        let Some(WebAuthnCeremony::Registration(state)) = self.challenges.consume(user_id).await?
+        else {
+            return Err(PasswordlessError::ChallengeNotFound);
+        };
+
+        let passkey = self
+            .webauthn
+            .finish_passkey_registration(response, &state)
+            .map_err(webauthn_error)?;
+
+        let id = credential_id(passkey.cred_id());
+        if self.credentials.find(&id).await?.is_some() {
+            return Err(PasswordlessError::WebAuthn(
+                "Credential already registered".to_string(),
+            ));
+        }
+
+        // Start from the counter in the attestation so a copy made before
+        // registration cannot replay lower values
+        let counter = Credential::from(passkey.clone()).counter;
+        let credential = StoredCredential {
+            id,
+            user_id,
+            passkey,
+            counter,
+            created_at: Utc::now(),
+            last_used_at: None,
+        };
+        self.credentials.save(&credential).await?;
+        Ok(credential)
+    }
+
+    /// Start authenticating with a passkey
+    ///
+    /// Returns the options to pass to `navigator.credentials.get()`.
+    pub async fn start_authentication(&self, user_id: Uuid) -> Result<CredentialRequestOptions> {
+        let passkeys: Vec<Passkey> = self
+            .credentials
+            .list_by_user(user_id)
+            .await?
+            .into_iter()
+            .map(|c| c.passkey)
+            .collect();
+        if passkeys.is_empty() {
+            return Err(PasswordlessError::WebAuthn(
+                "No passkeys registered".to_string(),
+            ));
+        }
+
+        let (options, state) = self
+            .webauthn
+            .start_passkey_authentication(&passkeys)
+            .map_err(webauthn_error)?;
+
+        self.challenges
+            .save(
+                user_id,
+                &WebAuthnCeremony::Authentication(state),
+                self.challenge_ttl,
+            )
+            .await?;
+        Ok(options)
+    }
+
+    /// Finish authenticating with a passkey
+    ///
+    /// Verifies the assertion signature against the pending challenge,
+    /// origin and relying party ID. Fails with
+    /// [`PasswordlessError::ClonedAuthenticator`] if the signature counter
+    /// did not increase, as happens when a credential was copied.
+    pub async fn finish_authentication(
+        &self,
+        user_id: Uuid,
+        response: &PublicKeyCredential,
+    ) -> Result<VerifiedAuth> {
+        let Some(WebAuthnCeremony::Authentication(state)) =
+            self.challenges.consume(user_id).await?
+        else {
+            return Err(PasswordlessError::ChallengeNotFound);
+        };
+
+        let result = self
+            .webauthn
+            .finish_passkey_authentication(response, &state)
+            .map_err(webauthn_error)?;
+
+        let id = credential_id(result.cred_id());
+        let mut credential = self
+            .credentials
+            .find(&id)
+            .await?
+            .filter(|c| c.user_id == user_id)
+            .ok_or(PasswordlessError::InvalidToken)?;
+
+        check_counter(credential.counter, result.counter())?;
+
+        credential.passkey.update_credential(&result);
+        credential.counter = result.counter();
+        credential.last_used_at = Some(Utc::now());
+        self.credentials.save(&credential).await?;
+
+        Ok(VerifiedAuth {
+            user_id,
+            credential_id: id,
+            counter: credential.counter,
+            user_verified: result.user_verified(),
+        })
+    }
+}
+
+/// Encode a credential ID for storage
+fn credential_id(id: impl AsRef<[u8]>) -> String {
+    URL_SAFE_NO_PAD.encode(id)
+}
+
+/// Reject a signature counter that did not increase
+///
+/// Authenticators without a counter always report 0.
+fn check_counter(stored: u32, presented: u32) -> Result<()> {
+    if (stored != 0 || presented != 0) && presented <= stored {
+        return Err(PasswordlessError::ClonedAuthenticator);
+    }
+    Ok(())
+}
+
+fn webauthn_error(e: WebauthnError) -> PasswordlessError {
+    match e {
+        WebauthnError::CredentialPossibleCompromise => PasswordlessError::ClonedAuthenticator,
+        e => PasswordlessError::WebAuthn(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ciborium::Value;
+    use p256::ecdsa::{Signature, SigningKey, signature::Signer};
+    use serde_json::json;
+    use sha2::{Digest, Sha256};
+
+    const RP_ID: &str = "example.com";
+    const ORIGIN: &str = "https://example.com";
+
+    /// Software authenticator with a fixed ES256 key
+    #[derive(Clone)]
+    struct SoftAuthenticator {
+        key: SigningKey,
+        credential_id: Vec<u8>,
+        counter: u32,
+        origin: &'static str,
+    }
+
+    impl SoftAuthenticator {
+        fn new() -> Self {
+            Self {
+                key: SigningKey::from_slice(&[7u8; 32]).unwrap(),
+                credential_id: vec![42u8; 16],
+                counter: 0,
+                origin: ORIGIN,
+            }
+        }
+
+        /// Respond to navigator.credentials.create()
+        fn register(&self, options: &CredentialCreationOptions) -> RegisterPublicKeyCredential {
+            let point = self.key.verifying_key().to_encoded_point(false);
+            let cose_key = Value::Map(vec![
+                (Value::from(1), Value::from(2)),  // kty: EC2
+                (Value::from(3), Value::from(-7)), // alg: ES256
+                (Value::from(-1), Value::from(1)), // crv: P-256
+                (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+                (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+            ]);
+
+            let mut attested = vec![0u8; 16]; // AAGUID
+            attested.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            attested.extend_from_slice(&self.credential_id);
+            ciborium::into_writer(&cose_key, &mut attested).unwrap();
+
+            // User present, user verified, attested credential data
+            let auth_data = self.auth_data(0x45, &attested);
+            let attestation = Value::Map(vec![
+                (Value::from("fmt"), Value::from("none")),
+                (Value::from("attStmt"), Value::Map(vec![])),
+                (Value::from("authData"), Value::Bytes(auth_data)),
+            ]);
+            let mut attestation_object = Vec::new();
+            ciborium::into_writer(&attestation, &mut attestation_object).unwrap();
+
+            let client_data = self.client_data("webauthn.create", options);
+            serde_json::from_value(json!({
+                "id": b64(&self.credential_id),
+                "rawId": b64(&self.credential_id),
+                "response": {
+                    "attestationObject": b64(&attestation_object),
+                    "clientDataJSON": b64(&client_data),
+                },
+                "type": "public-key",
+                "extensions": {},
+            }))
+            .unwrap()
+        }
+
+        /// Respond to navigator.credentials.get(), bumping the counter
+        fn authenticate(&mut self, options: &CredentialRequestOptions) -> PublicKeyCredential {
+            self.counter += 1;
+
+            // User present, user verified
+            let auth_data = self.auth_data(0x05, &[]);
+            let client_data = self.client_data("webauthn.get", options);
+
+            let mut signed = auth_data.clone();
+            signed.extend_from_slice(&Sha256::digest(&client_data));
+            let signature: Signature = self.key.sign(&signed);
+
+            serde_json::from_value(json!({
+                "id": b64(&self.credential_id),
+                "rawId": b64(&self.credential_id),
+                "response": {
+                    "authenticatorData": b64(&auth_data),
+                    "clientDataJSON": b64(&client_data),
+                    "signature": b64(signature.to_der().as_bytes()),
+                    "userHandle": null,
+                },
+                "type": "public-key",
+                "extensions": {},
+            }))
+            .unwrap()
+        }
+
+        fn auth_data(&self, flags: u8, attested: &[u8]) -> Vec<u8> {
+            let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+            data.push(flags);
+            data.extend_from_slice(&self.counter.to_be_bytes());
+            data.extend_from_slice(attested);
+            data
+        }
+
+        /// Client data for the challenge in `options`, as a browser would send
+        fn client_data(&self, kind: &str, options: &impl Serialize) -> Vec<u8> {
+            let options = serde_json::to_value(options).unwrap();
+            serde_json::to_vec(&json!({
+                "type": kind,
+                "challenge": options["publicKey"]["challenge"],
+                "origin": self.origin,
+                "crossOrigin": false,
+            }))
+            .unwrap()
+        }
+    }
+
+    fn b64(bytes: &[u8]) -> String {
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn manager() -> WebAuthnManager {
+        let config = WebAuthnConfig::new(RP_ID, "Example App", ORIGIN).unwrap();
+        WebAuthnManager::new(config, Arc::new(InMemoryWebAuthnCredentialStore::new())).unwrap()
+    }
+
+    async fn register(manager: &WebAuthnManager, user: &WebAuthnUser) -> SoftAuthenticator {
+        let authenticator = SoftAuthenticator::new();
+        let options = manager.start_registration(user).await.unwrap();
+        manager
+            .finish_registration(user.id, &authenticator.register(&options))
+            .await
+            .unwrap();
+        authenticator
+    }
+
+    #[tokio::test]
+    async fn test_registration_stores_reported_counter() {
+        let manager = manager();
+        let user = WebAuthnUser::new(Uuid::new_v4(), "alice", "Alice");
+        let mut authenticator = SoftAuthenticator::new();
+        authenticator.counter = 5;
+        let mut clone = authenticator.clone();
+        clone.counter = 2;
+
+        let options = manager.start_registration(&user).await.unwrap();
+        let credential = manager
+            .finish_registration(user.id, &authenticator.register(&options))
+            .await
+            .unwrap();
+        assert_eq!(credential.counter, 5);
+
+        // A counter below the registered one is a copy
+        let options = manager.start_authentication(user.id).await.unwrap();
+        let result = manager
+            .finish_authentication(user.id, &clone.authenticate(&options))
+            .await;
+        assert!(matches!(
+            result,
+            Err(PasswordlessError::ClonedAuthenticator)
+        ));
+
+        let options = manager.start_authentication(user.id).await.unwrap();
+        let verified = manager
+            .finish_authentication(user.id, &authenticator.authenticate(&options))
+            .await
+            .unwrap();
+        assert_eq!(verified.counter, 6);
+    }
+
+    #[tokio::test]
+    async fn test_registration_and_authentication() {
+        let manager = manager();
+        let user = WebAuthnUser::new(Uuid::new_v4(), "alice", "Alice");
+        let mut authenticator = register(&manager, &user).await;
+
+        for expected in 1..=2 {
+            let options = manager.start_authentication(user.id).await.unwrap();
+            let verified = manager
+                .finish_authentication(user.id, &authenticator.authenticate(&options))
+                .await
+                .unwrap();
+
+            assert_eq!(verified.user_id, user.id);
+            assert_eq!(verified.credential_id, b64(&authenticator.credential_id));
+            assert_eq!(verified.counter, expected);
+            assert!(verified.user_verified);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cloned_authenticator_rejected() {
+        let manager = manager();
+        let user = WebAuthnUser::new(Uuid::new_v4(), "alice", "Alice");
+        let mut authenticator = register(&manager, &user).await;
+        let mut clone = authenticator.clone();
+
+        let options = manager.start_authentication(user.id).await.unwrap();
+        let response = authenticator.authenticate(&options);
+        manager
+            .finish_authentication(user.id, &response)
+            .await
+            .unwrap();
+
+        // The copy signs with a counter the server has already seen
+        let options = manager.start_authentication(user.id).await.unwrap();
+        let result = manager
+            .finish_authentication(user.id, &clone.authenticate(&options))
+            .await;
+        assert!(matches!(
+            result,
+            Err(PasswordlessError::ClonedAuthenticator)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_challenge_single_use() {
+        let manager = manager();
+        let user = WebAuthnUser::new(Uuid::new_v4(), "alice", "Alice");
+        let mut authenticator = register(&manager, &user).await;
+
+        let options = manager.start_authentication(user.id).await.unwrap();
+        let response = authenticator.authenticate(&options);
+        manager
+            .finish_authentication(user.id, &response)
+            .await
+            .unwrap();
+
+        // Replaying the response finds no pending challenge
+        let result = manager.finish_authentication(user.id, &response).await;
+        assert!(matches!(result, Err(PasswordlessError::ChallengeNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_origin_rejected() {
+        let manager = manager();
+        let user = WebAuthnUser::new(Uuid::new_v4(), "alice", "Alice");
+
+        // A phishing page relaying the ceremony from another origin
+        let mut authenticator = SoftAuthenticator::new();
+        authenticator.origin = "https://evil.example";
+
+        let options = manager.start_registration(&user).await.unwrap();
+        let result = manager
+            .finish_registration(user.id, &authenticator.register(&options))
+            .await;
+        assert!(matches!(result, Err(PasswordlessError::WebAuthn(_))));
+        assert!(manager.start_authentication(user.id).await.is_err());
+    }
+
+    #[test]
+    fn test_check_counter() {
+        assert!(check_counter(0, 0).is_ok());
+        assert!(check_counter(0, 1).is_ok());
+        assert!(check_counter(5, 6).is_ok());
+        assert!(check_counter(5, 5).is_err());
+        assert!(check_counter(5, 0).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Azure Functions request conversion.
 
+use armature_core::Scheme;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -111,6 +112,14 @@ impl FunctionRequest {
         serde_json::from_str(json)
     }
 
+    /// Get the URI scheme from the request URL.
+    ///
+    /// Returns `None` when the URL is a bare path.
+    pub fn scheme(&self) -> Option<Scheme> {
+        let (scheme, _) = self.url.split_once("://")?;
+        Scheme::parse(scheme)
+    }
+
     /// Get a header value (case-insensitive).
     pub fn header(&self, name: &str) -> Option<&str> {
         self.headers
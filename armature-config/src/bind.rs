@@ -0,0 +1,249 @@
+// Deserialization of environment variables into typed structs
+
+use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+use serde::de::{self, IntoDeserializer, Visitor};
+use std::collections::BTreeMap;
+
+/// Environment variables arranged as a tree of string values
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum EnvNode {
+    Value(String),
+    Map(BTreeMap<String, EnvNode>),
+}
+
+impl EnvNode {
+    /// Insert a value under a path of lowercase segments.
+    ///
+    /// When a variable names both a value and a parent of other variables
+    /// (`APP_DB=x` and `APP_DB__PORT=1`), the nested variables win regardless
+    /// of order.
+    pub(crate) fn insert(&mut self, path: &[&str], value: String) {
+        let EnvNode::Map(map) = self else {
+            return;
+        };
+        let Some((first, rest)) = path.split_first() else {
+            return;
+        };
+
+        if rest.is_empty() {
+            map.entry(first.to_string())
+                .or_insert(EnvNode::Value(value));
+            return;
+        }
+
+        let child = map
+            .entry(first.to_string())
+            .or_insert_with(|| EnvNode::Map(BTreeMap::new()));
+        if let EnvNode::Value(_) = child {
+            *child = EnvNode::Map(BTreeMap::new());
+        }
+        child.insert(rest, value);
+    }
+}
+
+/// Convert a raw variable value to JSON using the same rules as binding.
+pub(crate) fn coerce(value: String) -> serde_json::Value {
+    serde::Deserialize::deserialize(EnvNode::Value(value))
+        .expect("a scalar always deserializes into a JSON value")
+}
+
+impl<'de> IntoDeserializer<'de, Error> for EnvNode {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Interpret a string the way it would most likely be written in a file:
+/// `true`/`false` as booleans, then integers, then floats.
+fn visit_coerced<'de, V: Visitor<'de>>(value: String, visitor: V) -> Result<V::Value, Error> {
+    if value.eq_ignore_ascii_case("true") {
+        visitor.visit_bool(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        visitor.visit_bool(false)
+    } else if let Ok(n) = value.parse::<i64>() {
+        visitor.visit_i64(n)
+    } else if let Ok(n) = value.parse::<u64>() {
+        visitor.visit_u64(n)
+    } else if let Some(n) = value.parse::<f64>().ok().filter(|n| n.is_finite()) {
+        visitor.visit_f64(n)
+    } else {
+        visitor.visit_string(value)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for EnvNode {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            EnvNode::Value(value) => visit_coerced(value, visitor),
+            EnvNode::Map(map) => visitor.visit_map(MapDeserializer::new(map.into_iter())),
+        }
+    }
+
+    // String targets keep the raw value, so `PASSWORD=1234` still binds to a
+    // `String` field.
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            EnvNode::Value(value) => visitor.visit_string(value),
+            map => map.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            EnvNode::Value(value) if value.is_empty() => visitor.visit_none(),
+            node => visitor.visit_some(node),
+        }
+    }
+
+    /// Comma-separated values bind to sequences: `HOSTS=a,b,c`.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            EnvNode::Value(value) => {
+                let items: Vec<EnvNode> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| EnvNode::Value(item.to_string()))
+                    .collect();
+                visitor.visit_seq(SeqDeserializer::new(items.into_iter()))
+            }
+            map => map.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            EnvNode::Value(value) => visitor.visit_enum(value.into_deserializer()),
+            map => map.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 bytes byte_buf
+        unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    fn tree(vars: &[(&str, &str)]) -> EnvNode {
+        let mut root = EnvNode::Map(BTreeMap::new());
+        for (key, value) in vars {
+            let path: Vec<&str> = key.split('.').collect();
+            root.insert(&path, value.to_string());
+        }
+        root
+    }
+
+    #[test]
+    fn test_coercion() {
+        #[derive(Deserialize)]
+        struct Config {
+            port: u16,
+            ratio: f64,
+            debug: bool,
+            secret: String,
+            missing: Option<String>,
+            empty: Option<String>,
+            hosts: Vec<String>,
+        }
+
+        let config = Config::deserialize(tree(&[
+            ("port", "5432"),
+            ("ratio", "0.25"),
+            ("debug", "TRUE"),
+            ("secret", "1234"),
+            ("empty", ""),
+            ("hosts", "a, b,c"),
+        ]))
+        .unwrap();
+
+        assert_eq!(config.port, 5432);
+        assert_eq!(config.ratio, 0.25);
+        assert!(config.debug);
+        assert_eq!(config.secret, "1234");
+        assert_eq!(config.missing, None);
+        assert_eq!(config.empty, None);
+        assert_eq!(config.hosts, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_invalid_number() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            port: u16,
+        }
+
+        assert!(Config::deserialize(tree(&[("port", "not-a-port")])).is_err());
+        assert!(Config::deserialize(tree(&[("port", "70000")])).is_err());
+    }
+
+    #[test]
+    fn test_nested_wins_over_value() {
+        let root = tree(&[("db.port", "1"), ("db", "x")]);
+        assert_eq!(root, tree(&[("db", "x"), ("db.port", "1")]));
+
+        let serde_json::Value::Object(map) = serde_json::Value::deserialize(root).unwrap() else {
+            panic!("expected an object");
+        };
+        assert_eq!(map["db"], serde_json::json!({"port": 1}));
+    }
+
+    #[test]
+    fn test_coerce_to_json() {
+        assert_eq!(coerce("false".to_string()), serde_json::json!(false));
+        assert_eq!(coerce("-12".to_string()), serde_json::json!(-12));
+        assert_eq!(coerce("1.5".to_string()), serde_json::json!(1.5));
+        assert_eq!(
+            coerce("localhost".to_string()),
+            serde_json::json!("localhost")
+        );
+    }
+
+    #[test]
+    fn test_enum_from_string() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Mode {
+            Development,
+            Production,
+        }
+
+        #[derive(Deserialize)]
+        struct Config {
+            mode: Mode,
+        }
+
+        let config = Config::deserialize(tree(&[("mode", "production")])).unwrap();
+        assert_eq!(config.mode, Mode::Production);
+    }
+}
@@ -0,0 +1,291 @@
+// Layered configuration sources
+
+use crate::bind::coerce;
+use crate::source::{Source, Sources};
+use crate::{ConfigError, ConfigLoader, ConfigManager, EnvLoader, FileFormat, Result, tree};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+
+/// Builder that layers configuration sources with explicit precedence
+///
+/// Sources are applied from lowest to highest precedence, regardless of the
+/// order they are registered in:
+///
+/// 1. [`defaults`](Self::defaults)
+/// 2. [`file`](Self::file)
+/// 3. [`env`](Self::env)
+/// 4. [`overrides`](Self::overrides)
+///
+/// Within the same kind, later registrations win. Layers are merged key by
+/// key, so a file that only sets `database.host` keeps `database.port` from
+/// the defaults. The origin of every value is recorded and available through
+/// [`ConfigManager::source_of`].
+///
+/// # Example
+///
+/// ```no_run
+/// use armature_config::{ConfigBuilder, FileFormat};
+/// use std::collections::HashMap;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let manager = ConfigBuilder::new()
+///     .defaults(HashMap::from([("server.port".to_string(), 8080.into())]))
+///     .file("config.toml", FileFormat::Toml)
+///     .env("APP") // APP_SERVER__PORT=9090 overrides the file
+///     .build()?;
+///
+/// let port = manager.get_int("server.port")?;
+/// println!("port {} from {:?}", port, manager.source_of("server.port"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ConfigBuilder {
+    layers: Vec<Layer>,
+}
+
+#[derive(Debug)]
+enum Layer {
+    Defaults(HashMap<String, Value>),
+    File(String, FileFormat),
+    Env {
+        prefix: Option<String>,
+        vars: Option<Vec<(String, String)>>,
+    },
+    Overrides(HashMap<String, Value>),
+}
+
+impl Layer {
+    fn precedence(&self) -> u8 {
+        match self {
+            Layer::Defaults(_) => 0,
+            Layer::File(..) => 1,
+            Layer::Env { .. } => 2,
+            Layer::Overrides(_) => 3,
+        }
+    }
+}
+
+impl ConfigBuilder {
+    /// Create a builder with no sources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add default values (lowest precedence)
+    ///
+    /// Keys may use dot notation.
+    pub fn defaults(mut self, values: HashMap<String, Value>) -> Self {
+        self.layers.push(Layer::Defaults(values));
+        self
+    }
+
+    /// Add a configuration file
+    pub fn file(mut self, path: impl Into<String>, format: FileFormat) -> Self {
+        self.layers.push(Layer::File(path.into(), format));
+        self
+    }
+
+    /// Add environment variables starting with `{prefix}_`
+    ///
+    /// Names are mapped to keys as in [`EnvLoader::bind`]: the prefix is
+    /// stripped, the rest lowercased, and `__` separates nesting levels. An
+    /// empty prefix reads every variable. Values are coerced to booleans and
+    /// numbers, except where a lower layer already holds a string for the key.
+    /// The prefix also becomes the built manager's environment prefix.
+    pub fn env(mut self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        self.layers.push(Layer::Env {
+            prefix: (!prefix.is_empty()).then_some(prefix),
+            vars: None,
+        });
+        self
+    }
+
+    /// Add explicit overrides (highest precedence)
+    ///
+    /// Keys may use dot notation.
+    pub fn overrides(mut self, values: HashMap<String, Value>) -> Self {
+        self.layers.push(Layer::Overrides(values));
+        self
+    }
+
+    /// Apply all sources into a new manager
+    pub fn build(self) -> Result<ConfigManager> {
+        let mut layers = self.layers;
+        layers.sort_by_key(Layer::precedence);
+
+        let mut config = HashMap::new();
+        let mut sources = Sources::default();
+        let mut env_prefix = None;
+
+        for layer in layers {
+            match layer {
+                Layer::Defaults(values) => {
+                    apply(&mut config, &mut sources, values, Source::Default);
+                }
+                Layer::File(path, format) => {
+                    let Value::Object(map) = ConfigLoader::new(format).load_file(&path)? else {
+                        return Err(ConfigError::ParseError(format!(
+                            "{} does not contain a table at the top level",
+                            path
+                        )));
+                    };
+                    apply(&mut config, &mut sources, map, Source::File(path.into()));
+                }
+                Layer::Env { prefix, vars } => {
+                    let loader = EnvLoader::new(prefix.clone());
+                    let vars = vars.unwrap_or_else(|| env::vars().collect());
+                    for (var, value) in vars {
+                        let Some(key) = loader.nested_key(&var) else {
+                            continue;
+                        };
+                        let value = match tree::get(&config, &key) {
+                            Some(Value::String(_)) => Value::String(value),
+                            _ => coerce(value),
+                        };
+                        tree::insert(&mut config, &key, value);
+                        sources.record(&key, Source::Env(var));
+                    }
+                    env_prefix = prefix;
+                }
+                Layer::Overrides(values) => {
+                    apply(&mut config, &mut sources, values, Source::Override);
+                }
+            }
+        }
+
+        Ok(ConfigManager::from_parts(config, sources, env_prefix))
+    }
+
+    #[cfg(test)]
+    fn env_vars(mut self, prefix: &str, vars: &[(&str, &str)]) -> Self {
+        self.layers.push(Layer::Env {
+            prefix: Some(prefix.to_string()),
+            vars: Some(
+                vars.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+        });
+        self
+    }
+}
+
+/// Merge values leaf by leaf, recording `source` for each.
+fn apply(
+    config: &mut HashMap<String, Value>,
+    sources: &mut Sources,
+    values: impl IntoIterator<Item = (String, Value)>,
+    source: Source,
+) {
+    let mut leaves = Vec::new();
+    for (key, value) in values {
+        tree::leaves(key, value, &mut leaves);
+    }
+
+    for (key, value) in leaves {
+        tree::insert(config, &key, value);
+        sources.record(&key, source.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "armature-config-{}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+            name
+        ));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let path = temp_file(
+            "layers.toml",
+            r#"
+                name = "from-file"
+
+                [database]
+                host = "db.internal"
+                port = 5432
+            "#,
+        );
+        let file = path.to_str().unwrap();
+
+        // Registered in reverse order; precedence still applies
+        let manager = ConfigBuilder::new()
+            .overrides(HashMap::from([("name".to_string(), json!("override"))]))
+            .env_vars(
+                "APP",
+                &[
+                    ("APP_DATABASE__PORT", "6543"),
+                    ("APP_DATABASE__USER", "admin"),
+                ],
+            )
+            .file(file, FileFormat::Toml)
+            .defaults(HashMap::from([
+                ("database.pool".to_string(), json!(10)),
+                ("database.port".to_string(), json!(1)),
+            ]))
+            .build()
+            .unwrap();
+
+        assert_eq!(manager.get_int("database.port").unwrap(), 6543);
+        assert_eq!(manager.get_string("database.host").unwrap(), "db.internal");
+        assert_eq!(manager.get_string("database.user").unwrap(), "admin");
+        assert_eq!(manager.get_int("database.pool").unwrap(), 10);
+        assert_eq!(manager.get_string("name").unwrap(), "override");
+
+        assert_eq!(
+            manager.source_of("database.port"),
+            Some(Source::Env("APP_DATABASE__PORT".to_string()))
+        );
+        assert_eq!(
+            manager.source_of("database.host"),
+            Some(Source::File(path.clone()))
+        );
+        assert_eq!(manager.source_of("database.pool"), Some(Source::Default));
+        assert_eq!(manager.source_of("name"), Some(Source::Override));
+        assert_eq!(manager.source_of("missing"), None);
+
+        manager.set("database.port", 7000).unwrap();
+        assert_eq!(manager.source_of("database.port"), Some(Source::Set));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_env_keeps_string_type() {
+        let manager = ConfigBuilder::new()
+            .defaults(HashMap::from([("password".to_string(), json!("changeme"))]))
+            .env_vars("APP", &[("APP_PASSWORD", "1234"), ("APP_DEBUG", "true")])
+            .build()
+            .unwrap();
+
+        assert_eq!(manager.get_string("password").unwrap(), "1234");
+        assert!(manager.get_bool("debug").unwrap());
+    }
+
+    #[test]
+    fn test_missing_file_fails() {
+        let result = ConfigBuilder::new()
+            .file("/nonexistent/armature.toml", FileFormat::Toml)
+            .build();
+
+        assert!(matches!(result, Err(ConfigError::LoadError(_))));
+    }
+}
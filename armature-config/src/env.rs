@@ -1,10 +1,13 @@
 // Environment variable loading
 
+use crate::bind::EnvNode;
 use crate::{ConfigError, Result};
-use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 
 /// Environment variable loader
+#[derive(Debug)]
 pub struct EnvLoader {
     prefix: Option<String>,
 }
@@ -48,6 +51,80 @@ impl EnvLoader {
     pub fn load_var_or(&self, key: &str, default: &str) -> String {
         self.load_var(key).unwrap_or_else(|_| default.to_string())
     }
+
+    /// Map a variable name to a dotted configuration key
+    ///
+    /// Returns `None` for variables without the prefix or with an empty path
+    /// segment. `APP_DATABASE__PORT` maps to `database.port` with prefix `APP`.
+    pub(crate) fn nested_key(&self, var: &str) -> Option<String> {
+        let name = match &self.prefix {
+            Some(prefix) => var.strip_prefix(prefix.as_str())?.strip_prefix('_')?,
+            None => var,
+        };
+
+        let key = name.to_lowercase().replace("__", ".");
+        if key.split('.').any(str::is_empty) {
+            return None;
+        }
+        Some(key)
+    }
+
+    /// Deserialize environment variables into a typed struct
+    ///
+    /// Only variables starting with `{prefix}_` are used when a prefix is set,
+    /// and the prefix is stripped. Names are lowercased and `__` separates
+    /// nesting levels, so with prefix `APP`, `APP_DATABASE__PORT=5432` binds to
+    /// `database.port`. Values are coerced to the target field's type: `true`
+    /// and `false` become booleans, numeric strings become integers or floats,
+    /// comma-separated values become sequences, and an empty value is `None`
+    /// for an `Option`.
+    ///
+    /// ```
+    /// use armature_config::EnvLoader;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Database {
+    ///     port: u16,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct AppConfig {
+    ///     debug: bool,
+    ///     database: Database,
+    /// }
+    ///
+    /// let loader = EnvLoader::new(Some("APP".to_string()));
+    /// let config: AppConfig = loader
+    ///     .bind_vars([
+    ///         ("APP_DEBUG".to_string(), "true".to_string()),
+    ///         ("APP_DATABASE__PORT".to_string(), "5432".to_string()),
+    ///     ])
+    ///     .unwrap();
+    ///
+    /// assert!(config.debug);
+    /// assert_eq!(config.database.port, 5432);
+    /// ```
+    pub fn bind<T: DeserializeOwned>(&self) -> Result<T> {
+        self.bind_vars(env::vars())
+    }
+
+    /// Deserialize the given variables as [`bind`](Self::bind) does for the
+    /// process environment
+    pub fn bind_vars<T: DeserializeOwned>(
+        &self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<T> {
+        let mut root = EnvNode::Map(BTreeMap::new());
+        for (var, value) in vars {
+            if let Some(key) = self.nested_key(&var) {
+                let path: Vec<&str> = key.split('.').collect();
+                root.insert(&path, value);
+            }
+        }
+
+        T::deserialize(root).map_err(|e| ConfigError::DeserializationError(e.to_string()))
+    }
 }
 
 impl Default for EnvLoader {
@@ -92,6 +169,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bind_strips_prefix_and_nests() {
+        #[derive(serde::Deserialize)]
+        struct Database {
+            host: String,
+            port: u16,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct AppConfig {
+            name: String,
+            database: Database,
+        }
+
+        let vars = [
+            ("APP_NAME", "demo"),
+            ("APP_DATABASE__HOST", "localhost"),
+            ("APP_DATABASE__PORT", "5432"),
+            ("APPLE_NAME", "ignored"),
+            ("OTHER_NAME", "ignored"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let loader = EnvLoader::new(Some("APP".to_string()));
+        let config: AppConfig = loader.bind_vars(vars).unwrap();
+
+        assert_eq!(config.name, "demo");
+        assert_eq!(config.database.host, "localhost");
+        assert_eq!(config.database.port, 5432);
+    }
+
+    #[test]
+    fn test_bind_without_prefix() {
+        #[derive(serde::Deserialize)]
+        struct Server {
+            port: u16,
+            debug: bool,
+        }
+
+        let vars = [("SERVER__PORT", "8080"), ("SERVER__DEBUG", "false")]
+            .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let server: HashMap<String, Server> = EnvLoader::new(None).bind_vars(vars).unwrap();
+        assert_eq!(server["server"].port, 8080);
+        assert!(!server["server"].debug);
+    }
+
+    #[test]
+    fn test_bind_type_error() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            port: u16,
+        }
+
+        let vars = [("APP_PORT".to_string(), "http".to_string())];
+        let result: Result<Config> = EnvLoader::new(Some("APP".to_string())).bind_vars(vars);
+        assert!(matches!(result, Err(ConfigError::DeserializationError(_))));
+    }
+
     #[test]
     fn test_env_loader_prefix() {
         let loader = EnvLoader::new(Some("MY_APP".to_string()));
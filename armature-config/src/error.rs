@@ -1,5 +1,6 @@
 // Error types for configuration management
 
+use crate::validation::FieldError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -16,6 +17,9 @@ pub enum ConfigError {
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    #[error("Validation failed: {}", join_errors(.0))]
+    ValidationErrors(Vec<FieldError>),
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
@@ -27,6 +31,14 @@ pub enum ConfigError {
 
     #[error("Environment variable error: {0}")]
     EnvError(#[from] std::env::VarError),
+
+    #[error("Secret could not be resolved: {0}")]
+    SecretUnresolved(String),
 }
 
 pub type Result<T> = std::result::Result<T, ConfigError>;
+
+fn join_errors(errors: &[FieldError]) -> String {
+    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    errors.join("; ")
+}
@@ -0,0 +1,154 @@
+// Serializing the configuration tree
+
+use crate::{ConfigError, FileFormat, Result, tree};
+use serde_json::Value;
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "***";
+
+/// Key patterns redacted on export unless replaced with
+/// [`ConfigManager::set_sensitive_keys`](crate::ConfigManager::set_sensitive_keys).
+pub const DEFAULT_SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "private_key",
+    "credential",
+];
+
+/// Replace values under keys containing any of `patterns` (case-insensitive).
+///
+/// A matching key hides its whole subtree.
+pub(crate) fn redact(value: &mut Value, patterns: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if patterns.iter().any(|pattern| key.contains(pattern)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value, patterns);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, patterns)),
+        _ => {}
+    }
+}
+
+/// Serialize a configuration tree in the given format.
+pub(crate) fn serialize(value: &Value, format: FileFormat) -> Result<String> {
+    let serialization_error = |e: &dyn std::fmt::Display| {
+        ConfigError::SerializationError(format!("{:?} export: {}", format, e))
+    };
+
+    match format {
+        FileFormat::Json => {
+            serde_json::to_string_pretty(value).map_err(|e| serialization_error(&e))
+        }
+        FileFormat::Toml => {
+            // TOML has no null; convert first so the error names the problem
+            let table = toml::Value::try_from(value).map_err(|e| serialization_error(&e))?;
+            toml::to_string_pretty(&table).map_err(|e| serialization_error(&e))
+        }
+        FileFormat::Yaml => serde_yaml::to_string(value).map_err(|e| serialization_error(&e)),
+        FileFormat::Env => Ok(to_env(value)),
+    }
+}
+
+/// Write `KEY__NESTED=value` lines, the inverse of environment binding.
+fn to_env(value: &Value) -> String {
+    let Value::Object(map) = value else {
+        return String::new();
+    };
+
+    let mut leaves = Vec::new();
+    for (key, value) in map.clone() {
+        tree::leaves(key, value, &mut leaves);
+    }
+    leaves.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut out = String::new();
+    for (key, value) in leaves {
+        let value = match value {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            Value::Array(items)
+                if items
+                    .iter()
+                    .all(|item| !item.is_object() && !item.is_array()) =>
+            {
+                let items: Vec<String> = items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    })
+                    .collect();
+                items.join(",")
+            }
+            other => other.to_string(),
+        };
+
+        let name = key.replace('.', "__").to_uppercase();
+        if value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_whitespace() || "#\"'=".contains(c))
+        {
+            out.push_str(&format!("{}=\"{}\"\n", name, value.replace('"', "\\\"")));
+        } else {
+            out.push_str(&format!("{}={}\n", name, value));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact() {
+        let patterns: Vec<String> = DEFAULT_SENSITIVE_KEYS
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let mut value = json!({
+            "database": {"host": "db", "Password": "hunter2"},
+            "secrets": {"a": 1},
+            "services": [{"name": "api", "api_key": "abc"}],
+        });
+        redact(&mut value, &patterns);
+
+        assert_eq!(
+            value,
+            json!({
+                "database": {"host": "db", "Password": REDACTED},
+                "secrets": REDACTED,
+                "services": [{"name": "api", "api_key": REDACTED}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_toml_rejects_null() {
+        let result = serialize(&json!({"a": null}), FileFormat::Toml);
+        assert!(matches!(result, Err(ConfigError::SerializationError(_))));
+    }
+
+    #[test]
+    fn test_env_export() {
+        let value = json!({
+            "app": {"name": "my app", "port": 8080, "hosts": ["a", "b"]},
+            "debug": true,
+        });
+
+        assert_eq!(
+            serialize(&value, FileFormat::Env).unwrap(),
+            "APP__HOSTS=a,b\nAPP__NAME=\"my app\"\nAPP__PORT=8080\nDEBUG=true\n"
+        );
+    }
+}
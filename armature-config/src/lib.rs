@@ -124,44 +124,157 @@
 //! # }
 //! ```
 
+mod bind;
+pub mod builder;
 pub mod config_service;
 pub mod env;
 pub mod error;
+pub mod export;
 pub mod loader;
+pub mod secret;
+pub mod source;
+pub mod transaction;
+mod tree;
+pub mod typed;
 pub mod validation;
+pub mod watch;
 
+pub use builder::ConfigBuilder;
 pub use config_service::ConfigService;
 pub use env::EnvLoader;
 pub use error::{ConfigError, Result};
+pub use export::{DEFAULT_SENSITIVE_KEYS, REDACTED};
 pub use loader::{ConfigLoader, FileFormat};
-pub use validation::{ConfigValidator, Validate};
-
+pub use secret::{EnvSecretResolver, FileSecretResolver, SecretResolver};
+pub use source::Source;
+pub use transaction::Transaction;
+pub use typed::ConfigHandle;
+pub use validation::{ConfigValidator, FieldError, Validate, ValidationContext};
+pub use watch::{ReloadCallback, WatchGuard};
+
+use secret::Secrets;
 use serde::de::DeserializeOwned;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use source::Sources;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
+
+/// Reload callback as stored by the manager
+type SharedReloadCallback = Arc<dyn Fn(&ConfigManager) + Send + Sync>;
 
 /// Main configuration manager
 #[derive(Clone)]
 pub struct ConfigManager {
     config: Arc<RwLock<HashMap<String, serde_json::Value>>>,
+    defaults: Arc<RwLock<HashMap<String, serde_json::Value>>>,
+    sources: Arc<RwLock<Sources>>,
+    secrets: Arc<Secrets>,
+    version: Arc<AtomicU64>,
+    sensitive_keys: Arc<RwLock<Vec<String>>>,
     env_prefix: Option<String>,
+    reload_callbacks: Arc<RwLock<Vec<SharedReloadCallback>>>,
 }
 
 impl ConfigManager {
     /// Create a new configuration manager
     pub fn new() -> Self {
-        Self {
-            config: Arc::new(RwLock::new(HashMap::new())),
-            env_prefix: None,
-        }
+        Self::from_parts(HashMap::new(), Sources::default(), None)
     }
 
     /// Create with environment variable prefix
     pub fn with_prefix(prefix: String) -> Self {
+        Self::from_parts(HashMap::new(), Sources::default(), Some(prefix))
+    }
+
+    pub(crate) fn from_parts(
+        config: HashMap<String, serde_json::Value>,
+        sources: Sources,
+        env_prefix: Option<String>,
+    ) -> Self {
         Self {
-            config: Arc::new(RwLock::new(HashMap::new())),
-            env_prefix: Some(prefix),
+            config: Arc::new(RwLock::new(config)),
+            defaults: Arc::new(RwLock::new(HashMap::new())),
+            sources: Arc::new(RwLock::new(sources)),
+            secrets: Arc::new(Secrets::default()),
+            version: Arc::new(AtomicU64::new(0)),
+            sensitive_keys: Arc::new(RwLock::new(
+                DEFAULT_SENSITIVE_KEYS
+                    .iter()
+                    .map(|k| k.to_string())
+                    .collect(),
+            )),
+            env_prefix,
+            reload_callbacks: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// Add fallback values for keys the configuration does not set
+    ///
+    /// Defaults are kept apart from the configuration: [`get`](Self::get),
+    /// [`load_validated`](Self::load_validated) and typed handles fall back to
+    /// them, and tables are merged key by key, but [`has`](Self::has),
+    /// [`keys`](Self::keys) and exports only see explicitly set values.
+    /// Defaults are not affected by [`remove`](Self::remove) or
+    /// [`clear`](Self::clear). Keys may use dot notation; calling this again
+    /// adds to the existing defaults.
+    ///
+    /// ```
+    /// use armature_config::ConfigManager;
+    /// use std::collections::HashMap;
+    ///
+    /// let manager = ConfigManager::new()
+    ///     .with_defaults(HashMap::from([("server.port".to_string(), 8080.into())]));
+    ///
+    /// assert_eq!(manager.get_int("server.port").unwrap(), 8080);
+    /// assert!(!manager.has("server.port"));
+    /// ```
+    pub fn with_defaults(self, defaults: HashMap<String, serde_json::Value>) -> Self {
+        let mut leaves = Vec::new();
+        for (key, value) in defaults {
+            tree::leaves(key, value, &mut leaves);
+        }
+        {
+            let mut stored = self.defaults.write().unwrap();
+            for (key, value) in leaves {
+                tree::insert(&mut stored, &key, value);
+            }
         }
+        self.bump_version();
+        self
+    }
+
+    /// Get a key's value with defaults applied, for diagnostics
+    ///
+    /// Returns the value [`get`](Self::get) would deserialize, except that
+    /// secret references are left as written. Returns `None` if neither the
+    /// configuration nor the defaults contain the key.
+    pub fn effective_value(&self, key: &str) -> Option<serde_json::Value> {
+        self.lookup(&self.config.read().unwrap(), key)
+    }
+
+    /// Look up a key in `config`, applying defaults
+    fn lookup(
+        &self,
+        config: &HashMap<String, serde_json::Value>,
+        key: &str,
+    ) -> Option<serde_json::Value> {
+        let defaults = self.defaults.read().unwrap();
+
+        match (tree::get(config, key), tree::get(&defaults, key)) {
+            (Some(value), Some(default)) => {
+                let mut merged = default.clone();
+                tree::merge(&mut merged, value.clone());
+                Some(merged)
+            }
+            (value, default) => value.or(default).cloned(),
+        }
+    }
+
+    /// The whole configuration tree with defaults applied
+    fn effective_tree(&self) -> serde_json::Value {
+        let mut tree = tree::to_value(&self.defaults.read().unwrap());
+        tree::merge(&mut tree, tree::to_value(&self.config.read().unwrap()));
+        tree
     }
 
     /// Load configuration from environment variables
@@ -169,14 +282,52 @@ impl ConfigManager {
         let loader = EnvLoader::new(self.env_prefix.clone());
         let env_vars = loader.load()?;
 
-        let mut config = self.config.write().unwrap();
+        let mut config = self.write_config();
+        let mut sources = self.sources.write().unwrap();
         for (key, value) in env_vars {
+            let var = match &self.env_prefix {
+                Some(prefix) => format!("{}_{}", prefix, key.to_uppercase()),
+                None => key.to_uppercase(),
+            };
+            sources.record(&key, Source::Env(var));
             config.insert(key, serde_json::Value::String(value));
         }
 
         Ok(())
     }
 
+    /// Deserialize environment variables into a typed struct
+    ///
+    /// Uses the manager's environment prefix; see [`EnvLoader::bind`] for how
+    /// names are mapped and values coerced. Values are read from the process
+    /// environment only and are not stored in the manager.
+    ///
+    /// ```no_run
+    /// use armature_config::ConfigManager;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Database {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct AppConfig {
+    ///     database: Database,
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // APP_DATABASE__HOST=localhost APP_DATABASE__PORT=5432
+    /// let manager = ConfigManager::with_prefix("APP".to_string());
+    /// let config: AppConfig = manager.bind_env()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bind_env<T: DeserializeOwned>(&self) -> Result<T> {
+        EnvLoader::new(self.env_prefix.clone()).bind()
+    }
+
     /// Load configuration from .env file
     pub fn load_dotenv(&self, path: Option<&str>) -> Result<()> {
         if let Some(path) = path {
@@ -192,9 +343,11 @@ impl ConfigManager {
         let loader = ConfigLoader::new(format);
         let data = loader.load_file(path)?;
 
-        let mut config = self.config.write().unwrap();
+        let mut config = self.write_config();
+        let mut sources = self.sources.write().unwrap();
         if let serde_json::Value::Object(map) = data {
             for (key, value) in map {
+                sources.record(&key, Source::File(path.into()));
                 config.insert(key, value);
             }
         }
@@ -202,27 +355,184 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Load a file and reload it whenever it changes on disk
+    ///
+    /// The file is loaded immediately, then watched until the returned guard
+    /// is dropped. Bursts of change events are debounced by
+    /// [`WATCH_DEBOUNCE`](watch::WATCH_DEBOUNCE). Each reload parses the whole
+    /// file before taking the write lock and applies it in one step, so readers
+    /// see either the old or the new file, never a mix; keys that disappeared
+    /// from the file are removed. A file that fails to parse is ignored until
+    /// the next change. Callbacks registered with [`on_reload`](Self::on_reload)
+    /// run after every successful reload.
+    ///
+    /// ```no_run
+    /// use armature_config::{ConfigManager, FileFormat};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let manager = ConfigManager::new();
+    /// manager.on_reload(Box::new(|config| {
+    ///     println!("port is now {}", config.get_int("port").unwrap_or(0));
+    /// }));
+    ///
+    /// let _guard = manager.watch_file("config.json", FileFormat::Json)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn watch_file(&self, path: &str, format: FileFormat) -> Result<WatchGuard> {
+        watch::watch(self, path, format)
+    }
+
+    /// Register a callback fired after each successful file reload
+    pub fn on_reload(&self, callback: ReloadCallback) {
+        let mut callbacks = self.reload_callbacks.write().unwrap();
+        callbacks.push(Arc::from(callback));
+    }
+
+    /// Replace the keys previously loaded from a file with its current contents
+    ///
+    /// Returns the top-level keys the file now provides.
+    pub(crate) fn reload_file(
+        &self,
+        path: &str,
+        format: FileFormat,
+        previous: &HashSet<String>,
+    ) -> Result<HashSet<String>> {
+        let loader = ConfigLoader::new(format);
+        let serde_json::Value::Object(map) = loader.load_file(path)? else {
+            return Err(ConfigError::ParseError(format!(
+                "{} does not contain a table at the top level",
+                path
+            )));
+        };
+        let keys: HashSet<String> = map.keys().cloned().collect();
+
+        let mut config = self.write_config();
+        let mut sources = self.sources.write().unwrap();
+        for key in previous.difference(&keys) {
+            config.remove(key);
+            sources.forget(key);
+        }
+        for key in &keys {
+            sources.record(key, Source::File(path.into()));
+        }
+        config.extend(map);
+
+        Ok(keys)
+    }
+
+    pub(crate) fn fire_reload_callbacks(&self) {
+        // Call outside the lock so callbacks can register further callbacks
+        let callbacks = self.reload_callbacks.read().unwrap().clone();
+        for callback in callbacks {
+            callback(self);
+        }
+    }
+
     /// Set a configuration value
+    ///
+    /// Dotted keys are stored as nested objects: setting `"database.host"`
+    /// creates (or updates) the `database` object, leaving its other fields
+    /// untouched.
     pub fn set<T: serde::Serialize>(&self, key: &str, value: T) -> Result<()> {
         let json_value = serde_json::to_value(value)
             .map_err(|e| ConfigError::SerializationError(e.to_string()))?;
 
-        let mut config = self.config.write().unwrap();
-        config.insert(key.to_string(), json_value);
+        let mut config = self.write_config();
+        tree::insert(&mut config, key, json_value);
+        self.sources.write().unwrap().record(key, Source::Set);
 
         Ok(())
     }
 
     /// Get a configuration value
+    ///
+    /// Dotted keys traverse nested objects, so `get::<DatabaseConfig>("database")`
+    /// returns the whole subtree. A literal key containing dots (for example one
+    /// loaded from a flat file) takes precedence over the nested path.
+    ///
+    /// When a [`SecretResolver`] is registered, `${secret:name}` references
+    /// are replaced with previously resolved values; a reference that has not
+    /// been resolved yet fails with [`ConfigError::SecretUnresolved`]. Use
+    /// [`get_resolved`](Self::get_resolved) to resolve it.
     pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
-        let config = self.config.read().unwrap();
+        self.decode(self.get_value(key)?)
+    }
+
+    /// Substitute cached secrets and deserialize
+    fn decode<T: DeserializeOwned>(&self, mut value: serde_json::Value) -> Result<T> {
+        if self.secrets.has_resolver() {
+            self.secrets.substitute(&mut value)?;
+        }
 
-        let value = config
-            .get(key)
-            .ok_or_else(|| ConfigError::KeyNotFound(key.to_string()))?;
+        serde_json::from_value(value).map_err(|e| ConfigError::DeserializationError(e.to_string()))
+    }
+
+    /// Get a configuration value, resolving secret references
+    ///
+    /// References such as `${secret:db_password}`, whole or embedded in a
+    /// longer string, are resolved through the registered [`SecretResolver`]
+    /// on first use and cached until invalidated.
+    ///
+    /// ```
+    /// use armature_config::{ConfigManager, EnvSecretResolver};
+    ///
+    /// # tokio_test::block_on(async {
+    /// let manager = ConfigManager::new();
+    /// manager.set_secret_resolver(EnvSecretResolver::new(None));
+    /// manager.set("home", "${secret:home}").unwrap();
+    ///
+    /// # if std::env::var("HOME").is_ok() {
+    /// let home: String = manager.get_resolved("home").await.unwrap();
+    /// assert_eq!(home, std::env::var("HOME").unwrap());
+    /// # }
+    /// # });
+    /// ```
+    pub async fn get_resolved<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        let mut value = self.get_value(key)?;
+        if self.secrets.fetch(&value).await? {
+            self.bump_version();
+        }
+        self.secrets.substitute(&mut value)?;
+
+        serde_json::from_value(value).map_err(|e| ConfigError::DeserializationError(e.to_string()))
+    }
+
+    /// Resolve every secret reference in the configuration
+    ///
+    /// Call this at startup so that the synchronous [`get`](Self::get) and
+    /// [`load_validated`](Self::load_validated) can substitute all secrets.
+    pub async fn resolve_secrets(&self) -> Result<()> {
+        let value = self.effective_tree();
+        if self.secrets.fetch(&value).await? {
+            self.bump_version();
+        }
+        Ok(())
+    }
+
+    /// Register the resolver for `${secret:name}` references
+    ///
+    /// Replacing the resolver clears cached secrets.
+    pub fn set_secret_resolver(&self, resolver: impl SecretResolver + 'static) {
+        self.secrets.set_resolver(Arc::new(resolver));
+        self.bump_version();
+    }
 
-        serde_json::from_value(value.clone())
-            .map_err(|e| ConfigError::DeserializationError(e.to_string()))
+    /// Drop a cached secret so the next lookup resolves it again
+    pub fn invalidate_secret(&self, name: &str) {
+        self.secrets.invalidate(name);
+        self.bump_version();
+    }
+
+    /// Drop all cached secrets
+    pub fn invalidate_secrets(&self) {
+        self.secrets.invalidate_all();
+        self.bump_version();
+    }
+
+    fn get_value(&self, key: &str) -> Result<serde_json::Value> {
+        self.effective_value(key)
+            .ok_or_else(|| ConfigError::KeyNotFound(key.to_string()))
     }
 
     /// Get a configuration value with default
@@ -250,25 +560,155 @@ impl ConfigManager {
         self.get(key)
     }
 
-    /// Check if a key exists
+    /// Check if a key is explicitly set
+    ///
+    /// Keys that only have a value through [`with_defaults`](Self::with_defaults)
+    /// are not reported.
     pub fn has(&self, key: &str) -> bool {
         let config = self.config.read().unwrap();
-        config.contains_key(key)
+        tree::get(&config, key).is_some()
     }
 
     /// Remove a key from the configuration
     pub fn remove(&self, key: &str) {
-        let mut config = self.config.write().unwrap();
-        config.remove(key);
+        let mut config = self.write_config();
+        tree::remove(&mut config, key);
+        self.sources.write().unwrap().forget(key);
     }
 
     /// Clear all configuration
     pub fn clear(&self) {
-        let mut config = self.config.write().unwrap();
+        let mut config = self.write_config();
         config.clear();
+        self.sources.write().unwrap().clear();
+    }
+
+    /// Get where a key's current value came from
+    ///
+    /// Values inside a table report the source of the table unless they were
+    /// set individually. Keys only present in the fallback defaults report
+    /// [`Source::Default`]. Returns `None` for missing keys.
+    ///
+    /// ```
+    /// use armature_config::{ConfigManager, Source};
+    ///
+    /// let manager = ConfigManager::new();
+    /// manager.set("port", 8080).unwrap();
+    ///
+    /// assert_eq!(manager.source_of("port"), Some(Source::Set));
+    /// assert_eq!(manager.source_of("host"), None);
+    /// ```
+    pub fn source_of(&self, key: &str) -> Option<Source> {
+        let config = self.config.read().unwrap();
+        if tree::get(&config, key).is_none() {
+            return tree::get(&self.defaults.read().unwrap(), key).map(|_| Source::Default);
+        }
+        self.sources.read().unwrap().lookup(key)
     }
 
-    /// Get all configuration keys
+    /// Get a cached, typed handle to a configuration key
+    ///
+    /// The handle deserializes the value once and again only after the
+    /// configuration changes, which makes it suitable for hot paths.
+    ///
+    /// ```
+    /// use armature_config::ConfigManager;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Limits {
+    ///     max_body: usize,
+    /// }
+    ///
+    /// let manager = ConfigManager::new();
+    /// manager.set("limits.max_body", 1024).unwrap();
+    ///
+    /// let limits = manager.typed::<Limits>("limits").unwrap();
+    /// assert_eq!(limits.get().max_body, 1024);
+    ///
+    /// manager.set("limits.max_body", 2048).unwrap();
+    /// assert_eq!(limits.get().max_body, 2048);
+    /// ```
+    pub fn typed<T: DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        key: &str,
+    ) -> Result<ConfigHandle<T>> {
+        ConfigHandle::new(self, key)
+    }
+
+    /// Get the configuration version
+    ///
+    /// The version increases with every change to the configuration: sets,
+    /// removals, loads, merges, file reloads, and changes to resolved secrets.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Lock the configuration for writing and bump the version
+    ///
+    /// The version changes while the lock is held, so a reader that sees the
+    /// new version and then takes the read lock also sees the new data.
+    fn write_config(&self) -> RwLockWriteGuard<'_, HashMap<String, serde_json::Value>> {
+        let config = self.config.write().unwrap();
+        self.bump_version();
+        config
+    }
+
+    /// Apply several changes atomically
+    ///
+    /// The closure stages changes on a copy of the configuration through the
+    /// [`Transaction`]. If it returns `Ok`, all changes are committed at once
+    /// and the version is bumped a single time; if it returns `Err`, nothing
+    /// changes and the error is returned. Readers never see part of a batch.
+    ///
+    /// The configuration is locked for writing while the closure runs, so it
+    /// must read through the transaction rather than the manager and should
+    /// not block.
+    ///
+    /// ```
+    /// use armature_config::ConfigManager;
+    ///
+    /// let manager = ConfigManager::new();
+    /// manager.set("primary", "db-1").unwrap();
+    ///
+    /// manager
+    ///     .update(|tx| {
+    ///         let old: String = tx.get("primary")?;
+    ///         tx.set("primary", "db-2")?;
+    ///         tx.set("replica", old)?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(manager.get_string("replica").unwrap(), "db-1");
+    /// ```
+    pub fn update<R>(&self, f: impl FnOnce(&mut Transaction<'_>) -> Result<R>) -> Result<R> {
+        let mut config = self.config.write().unwrap();
+        let mut tx = Transaction::new(self, config.clone());
+        let result = f(&mut tx)?;
+
+        let (staged, changes) = tx.into_parts();
+        if !changes.is_empty() {
+            *config = staged;
+            self.bump_version();
+
+            let mut sources = self.sources.write().unwrap();
+            for (key, source) in changes {
+                match source {
+                    Some(source) => sources.record(&key, source),
+                    None => sources.forget(&key),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get all top-level configuration keys
     pub fn keys(&self) -> Vec<String> {
         let config = self.config.read().unwrap();
         config.keys().cloned().collect()
@@ -277,20 +717,28 @@ impl ConfigManager {
     /// Merge configuration from another manager
     pub fn merge(&self, other: &ConfigManager) -> Result<()> {
         let other_config = other.config.read().unwrap();
-        let mut config = self.config.write().unwrap();
+        let other_sources = other.sources.read().unwrap();
+        let mut config = self.write_config();
+        let mut sources = self.sources.write().unwrap();
 
         for (key, value) in other_config.iter() {
             config.insert(key.clone(), value.clone());
+            sources.forget(key);
         }
+        sources.extend(&other_sources);
 
         Ok(())
     }
 
     /// Load and validate configuration
+    ///
+    /// Missing keys are filled from [`with_defaults`](Self::with_defaults)
+    /// before deserializing and validating.
     pub fn load_validated<T: DeserializeOwned + Validate>(&self) -> Result<T> {
-        let config = self.config.read().unwrap();
-        let json_value =
-            serde_json::Value::Object(config.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+        let mut json_value = self.effective_tree();
+        if self.secrets.has_resolver() {
+            self.secrets.substitute(&mut json_value)?;
+        }
 
         let validated: T = serde_json::from_value(json_value)
             .map_err(|e| ConfigError::DeserializationError(e.to_string()))?;
@@ -299,6 +747,75 @@ impl ConfigManager {
 
         Ok(validated)
     }
+
+    /// Serialize the whole configuration tree
+    ///
+    /// With `redact`, values under keys matching the sensitive key list are
+    /// replaced with [`REDACTED`]. Secret references are exported as written,
+    /// never resolved. [`FileFormat::Env`] writes `KEY__NESTED=value` lines
+    /// that [`EnvLoader::bind`] reads back.
+    ///
+    /// TOML cannot represent null, so exporting a tree containing nulls as
+    /// TOML fails with [`ConfigError::SerializationError`].
+    ///
+    /// ```
+    /// use armature_config::{ConfigManager, FileFormat};
+    ///
+    /// let manager = ConfigManager::new();
+    /// manager.set("database.host", "localhost").unwrap();
+    /// manager.set("database.password", "hunter2").unwrap();
+    ///
+    /// let toml = manager.dump(FileFormat::Toml, true).unwrap();
+    /// assert!(toml.contains("host = \"localhost\""));
+    /// assert!(toml.contains("password = \"***\""));
+    /// ```
+    pub fn dump(&self, format: FileFormat, redact: bool) -> Result<String> {
+        let mut value = tree::to_value(&self.config.read().unwrap());
+        if redact {
+            export::redact(&mut value, &self.sensitive_keys.read().unwrap());
+        }
+        export::serialize(&value, format)
+    }
+
+    /// Export the configuration as TOML, with sensitive values redacted
+    pub fn to_toml(&self) -> Result<String> {
+        self.dump(FileFormat::Toml, true)
+    }
+
+    /// Export the configuration as YAML, with sensitive values redacted
+    pub fn to_yaml(&self) -> Result<String> {
+        self.dump(FileFormat::Yaml, true)
+    }
+
+    /// Export the configuration as pretty-printed JSON, with sensitive values
+    /// redacted
+    pub fn to_json_pretty(&self) -> Result<String> {
+        self.dump(FileFormat::Json, true)
+    }
+
+    /// Replace the key patterns redacted on export
+    ///
+    /// A key is sensitive if its name contains any pattern, ignoring case;
+    /// redacting a table hides everything in it. Defaults to
+    /// [`DEFAULT_SENSITIVE_KEYS`].
+    pub fn set_sensitive_keys<I, S>(&self, patterns: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        *self.sensitive_keys.write().unwrap() = patterns
+            .into_iter()
+            .map(|p| p.into().to_lowercase())
+            .collect();
+    }
+
+    /// Add a key pattern to redact on export
+    pub fn add_sensitive_key(&self, pattern: impl Into<String>) {
+        self.sensitive_keys
+            .write()
+            .unwrap()
+            .push(pattern.into().to_lowercase());
+    }
 }
 
 impl Default for ConfigManager {
@@ -314,11 +831,13 @@ impl Default for ConfigManager {
 /// ```
 pub mod prelude {
     pub use crate::ConfigManager;
+    pub use crate::builder::ConfigBuilder;
     pub use crate::config_service::ConfigService;
     pub use crate::env::EnvLoader;
     pub use crate::error::{ConfigError, Result};
     pub use crate::loader::{ConfigLoader, FileFormat};
-    pub use crate::validation::{ConfigValidator, Validate};
+    pub use crate::source::Source;
+    pub use crate::validation::{ConfigValidator, FieldError, Validate, ValidationContext};
 }
 
 #[cfg(test)]
@@ -405,6 +924,180 @@ mod tests {
         assert_eq!(manager.get_int("database.port").unwrap(), 5432);
     }
 
+    #[test]
+    fn test_nested_tree() {
+        let manager = ConfigManager::new();
+        manager.set("a.b.c", 1i64).unwrap();
+        manager.set("a.d", "kept").unwrap();
+
+        let a: serde_json::Value = manager.get("a").unwrap();
+        assert_eq!(a, serde_json::json!({"b": {"c": 1}, "d": "kept"}));
+
+        // Overwriting a.b leaves a.d alone
+        manager.set("a.b", "replaced").unwrap();
+        assert_eq!(manager.get_string("a.b").unwrap(), "replaced");
+        assert_eq!(manager.get_string("a.d").unwrap(), "kept");
+        assert!(!manager.has("a.b.c"));
+
+        manager.remove("a.d");
+        assert!(!manager.has("a.d"));
+        assert!(manager.has("a"));
+    }
+
+    #[test]
+    fn test_nested_struct_deserialization() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct DatabaseConfig {
+            host: String,
+            port: u16,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct AppConfig {
+            name: String,
+            database: DatabaseConfig,
+        }
+
+        impl Validate for AppConfig {
+            fn validate(&self) -> Result<()> {
+                ConfigValidator::not_empty(&self.name, "name")
+            }
+        }
+
+        let manager = ConfigManager::new();
+        manager.set("name", "app").unwrap();
+        manager.set("database.host", "localhost").unwrap();
+        manager.set("database.port", 5432).unwrap();
+
+        let database: DatabaseConfig = manager.get("database").unwrap();
+        assert_eq!(
+            database,
+            DatabaseConfig {
+                host: "localhost".to_string(),
+                port: 5432
+            }
+        );
+
+        let config: AppConfig = manager.load_validated().unwrap();
+        assert_eq!(config.name, "app");
+        assert_eq!(config.database, database);
+    }
+
+    #[test]
+    fn test_fallback_defaults() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct DatabaseConfig {
+            host: String,
+            port: u16,
+            pool: u32,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct AppConfig {
+            database: DatabaseConfig,
+        }
+
+        impl Validate for AppConfig {
+            fn validate(&self) -> Result<()> {
+                ConfigValidator::in_range(self.database.pool, 1, 100, "database.pool")
+            }
+        }
+
+        let manager = ConfigManager::new().with_defaults(HashMap::from([
+            ("database.port".to_string(), serde_json::json!(5432)),
+            ("database.pool".to_string(), serde_json::json!(10)),
+        ]));
+        manager.set("database.host", "db.internal").unwrap();
+        manager.set("database.pool", 20).unwrap();
+
+        let config: AppConfig = manager.load_validated().unwrap();
+        assert_eq!(
+            config.database,
+            DatabaseConfig {
+                host: "db.internal".to_string(),
+                port: 5432,
+                pool: 20
+            }
+        );
+        assert_eq!(manager.get_int("database.port").unwrap(), 5432);
+
+        assert!(!manager.has("database.port"));
+        assert!(manager.has("database.pool"));
+        assert!(!manager.keys().is_empty());
+        assert_eq!(manager.source_of("database.port"), Some(Source::Default));
+        assert_eq!(manager.source_of("database.pool"), Some(Source::Set));
+        assert_eq!(
+            manager.effective_value("database"),
+            Some(serde_json::json!({"host": "db.internal", "port": 5432, "pool": 20}))
+        );
+        assert_eq!(manager.effective_value("missing"), None);
+
+        // Defaults survive clearing the configuration
+        manager.clear();
+        assert_eq!(manager.get_int("database.pool").unwrap(), 10);
+        assert!(manager.get_string("database.host").is_err());
+    }
+
+    #[derive(Default)]
+    struct CountingResolver(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl SecretResolver for Arc<CountingResolver> {
+        async fn resolve(&self, key: &str) -> Result<String> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            match key {
+                "db_password" => Ok(format!("hunter{}", n)),
+                _ => Err(ConfigError::SecretUnresolved(key.to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_secret_references() {
+        let resolver = Arc::new(CountingResolver::default());
+        let manager = ConfigManager::new();
+        manager
+            .set("database.password", "${secret:db_password}")
+            .unwrap();
+        manager
+            .set("database.url", "pg://app:${secret:db_password}@db")
+            .unwrap();
+        manager.set("api.token", "${secret:missing}").unwrap();
+
+        // Without a resolver, references are plain strings
+        assert_eq!(
+            manager.get_string("database.password").unwrap(),
+            "${secret:db_password}"
+        );
+
+        manager.set_secret_resolver(resolver.clone());
+        assert!(matches!(
+            manager.get_string("database.password"),
+            Err(ConfigError::SecretUnresolved(_))
+        ));
+
+        tokio_test::block_on(async {
+            let password: String = manager.get_resolved("database.password").await.unwrap();
+            assert_eq!(password, "hunter1");
+
+            // Cached: the sync path and other keys reuse the resolved value
+            let url: String = manager.get_resolved("database.url").await.unwrap();
+            assert_eq!(url, "pg://app:hunter1@db");
+            assert_eq!(manager.get_string("database.password").unwrap(), "hunter1");
+            assert_eq!(resolver.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+            manager.invalidate_secret("db_password");
+            let password: String = manager.get_resolved("database.password").await.unwrap();
+            assert_eq!(password, "hunter2");
+
+            assert!(matches!(
+                manager.get_resolved::<String>("api.token").await,
+                Err(ConfigError::SecretUnresolved(_))
+            ));
+            assert!(manager.resolve_secrets().await.is_err());
+        });
+    }
+
     #[test]
     fn test_empty_string() {
         let manager = ConfigManager::new();
@@ -535,6 +1228,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_is_atomic() {
+        use std::sync::atomic::AtomicBool;
+        use std::thread;
+
+        let manager = ConfigManager::new();
+        manager
+            .update(|tx| {
+                tx.set("pair.a", 0)?;
+                tx.set("pair.b", 0)
+            })
+            .unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let observers: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.clone();
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut observed = 0;
+                    while !done.load(Ordering::Acquire) {
+                        let pair: HashMap<String, i64> = manager.get("pair").unwrap();
+                        assert_eq!(pair["a"], pair["b"], "observed a partial batch");
+                        observed += 1;
+                    }
+                    observed
+                })
+            })
+            .collect();
+
+        for i in 1..=2000 {
+            manager
+                .update(|tx| {
+                    tx.set("pair.a", i)?;
+                    tx.set("pair.b", i)
+                })
+                .unwrap();
+        }
+        done.store(true, Ordering::Release);
+
+        for observer in observers {
+            assert!(observer.join().unwrap() > 0);
+        }
+        assert_eq!(manager.get_int("pair.b").unwrap(), 2000);
+    }
+
+    #[test]
+    fn test_update_rolls_back_on_error() {
+        let manager = ConfigManager::new();
+        manager.set("a", 1).unwrap();
+        manager.set("b", 2).unwrap();
+        let version = manager.version();
+
+        let result: Result<()> = manager.update(|tx| {
+            tx.set("a", 10)?;
+            tx.remove("b");
+            assert_eq!(tx.get::<i64>("a")?, 10);
+            assert!(!tx.has("b"));
+            Err(ConfigError::ValidationError("abort".to_string()))
+        });
+
+        assert!(matches!(result, Err(ConfigError::ValidationError(_))));
+        assert_eq!(manager.get_int("a").unwrap(), 1);
+        assert_eq!(manager.get_int("b").unwrap(), 2);
+        assert_eq!(manager.version(), version);
+
+        manager
+            .update(|tx| {
+                tx.set("a", 10)?;
+                tx.remove("b");
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(manager.version(), version + 1);
+        assert_eq!(manager.source_of("a"), Some(Source::Set));
+        assert!(!manager.has("b"));
+    }
+
     #[test]
     fn test_case_sensitive_keys() {
         let manager = ConfigManager::new();
@@ -10,6 +10,7 @@ use std::path::Path;
 pub enum FileFormat {
     Json,
     Toml,
+    Yaml,
     Env,
 }
 
@@ -18,6 +19,7 @@ impl FileFormat {
         match ext.to_lowercase().as_str() {
             "json" => Some(FileFormat::Json),
             "toml" => Some(FileFormat::Toml),
+            "yaml" | "yml" => Some(FileFormat::Yaml),
             "env" => Some(FileFormat::Env),
             _ => None,
         }
@@ -61,6 +63,7 @@ impl ConfigLoader {
         match self.format {
             FileFormat::Json => self.parse_json(content),
             FileFormat::Toml => self.parse_toml(content),
+            FileFormat::Yaml => self.parse_yaml(content),
             FileFormat::Env => self.parse_env(content),
         }
     }
@@ -82,6 +85,11 @@ impl ConfigLoader {
             .map_err(|e| ConfigError::ParseError(format!("TOML to JSON conversion error: {}", e)))
     }
 
+    fn parse_yaml(&self, content: &str) -> Result<Value> {
+        serde_yaml::from_str(content)
+            .map_err(|e| ConfigError::ParseError(format!("YAML parse error: {}", e)))
+    }
+
     fn parse_env(&self, content: &str) -> Result<Value> {
         let mut map = serde_json::Map::new();
 
@@ -127,6 +135,15 @@ mod tests {
         assert!(result.is_object());
     }
 
+    #[test]
+    fn test_parse_yaml() {
+        let loader = ConfigLoader::new(FileFormat::Yaml);
+        let yaml = "key: value\nnested:\n  number: 42\n";
+
+        let result = loader.parse(yaml).unwrap();
+        assert_eq!(result["nested"]["number"], 42);
+    }
+
     #[test]
     fn test_parse_env() {
         let loader = ConfigLoader::new(FileFormat::Env);
@@ -145,6 +162,7 @@ mod tests {
     fn test_format_detection() {
         assert_eq!(FileFormat::from_extension("json"), Some(FileFormat::Json));
         assert_eq!(FileFormat::from_extension("toml"), Some(FileFormat::Toml));
+        assert_eq!(FileFormat::from_extension("yml"), Some(FileFormat::Yaml));
         assert_eq!(FileFormat::from_extension("env"), Some(FileFormat::Env));
         assert_eq!(FileFormat::from_extension("unknown"), None);
     }
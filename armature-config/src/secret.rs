@@ -0,0 +1,292 @@
+// Secret references resolved through external providers
+
+use crate::{ConfigError, EnvLoader, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+const SECRET_PREFIX: &str = "${secret:";
+
+/// Resolves secret references such as `${secret:db_password}`
+///
+/// Implementations receive the name inside the reference (`db_password`).
+#[async_trait]
+pub trait SecretResolver: Send + Sync {
+    /// Look up the value of a secret
+    async fn resolve(&self, key: &str) -> Result<String>;
+}
+
+/// Reads secrets from files in a directory, one secret per file
+///
+/// This matches how Docker and Kubernetes mount secrets: `${secret:db_password}`
+/// reads `<dir>/db_password`. A single trailing newline is stripped.
+#[derive(Debug, Clone)]
+pub struct FileSecretResolver {
+    dir: PathBuf,
+}
+
+impl FileSecretResolver {
+    /// Resolve secrets from files in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl SecretResolver for FileSecretResolver {
+    async fn resolve(&self, key: &str) -> Result<String> {
+        // Only plain file names, so a reference cannot escape the directory
+        let mut components = Path::new(key).components();
+        if !matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        ) {
+            return Err(ConfigError::SecretUnresolved(format!(
+                "{}: invalid secret name",
+                key
+            )));
+        }
+
+        let mut value = std::fs::read_to_string(self.dir.join(key))
+            .map_err(|e| ConfigError::SecretUnresolved(format!("{}: {}", key, e)))?;
+        if value.ends_with('\n') {
+            value.pop();
+            if value.ends_with('\r') {
+                value.pop();
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Reads secrets from environment variables
+///
+/// `${secret:db_password}` reads `DB_PASSWORD`, or `{PREFIX}_DB_PASSWORD`
+/// with a prefix.
+#[derive(Debug, Default)]
+pub struct EnvSecretResolver {
+    loader: EnvLoader,
+}
+
+impl EnvSecretResolver {
+    /// Resolve secrets from environment variables with an optional prefix
+    pub fn new(prefix: Option<String>) -> Self {
+        Self {
+            loader: EnvLoader::new(prefix),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretResolver for EnvSecretResolver {
+    async fn resolve(&self, key: &str) -> Result<String> {
+        self.loader
+            .load_var(key)
+            .map_err(|e| ConfigError::SecretUnresolved(format!("{}: {}", key, e)))
+    }
+}
+
+/// Registered resolver and resolved values, shared by clones of a manager
+#[derive(Default)]
+pub(crate) struct Secrets {
+    resolver: RwLock<Option<Arc<dyn SecretResolver>>>,
+    cache: RwLock<HashMap<String, String>>,
+}
+
+impl Secrets {
+    pub(crate) fn set_resolver(&self, resolver: Arc<dyn SecretResolver>) {
+        *self.resolver.write().unwrap() = Some(resolver);
+        self.invalidate_all();
+    }
+
+    pub(crate) fn has_resolver(&self) -> bool {
+        self.resolver.read().unwrap().is_some()
+    }
+
+    pub(crate) fn invalidate(&self, key: &str) {
+        self.cache.write().unwrap().remove(key);
+    }
+
+    pub(crate) fn invalidate_all(&self) {
+        self.cache.write().unwrap().clear();
+    }
+
+    /// Resolve every reference in `value` that is not cached yet.
+    ///
+    /// Returns whether any secret was added to the cache.
+    pub(crate) async fn fetch(&self, value: &Value) -> Result<bool> {
+        let mut names = Vec::new();
+        collect_references(value, &mut names);
+        {
+            let cache = self.cache.read().unwrap();
+            names.retain(|name| !cache.contains_key(name));
+        }
+        if names.is_empty() {
+            return Ok(false);
+        }
+
+        let resolver = self.resolver.read().unwrap().clone().ok_or_else(|| {
+            ConfigError::SecretUnresolved(format!("{}: no secret resolver registered", names[0]))
+        })?;
+
+        for name in names {
+            let secret = resolver.resolve(&name).await.map_err(|e| match e {
+                ConfigError::SecretUnresolved(_) => e,
+                other => ConfigError::SecretUnresolved(format!("{}: {}", name, other)),
+            })?;
+            self.cache.write().unwrap().insert(name, secret);
+        }
+        Ok(true)
+    }
+
+    /// Replace references with cached values.
+    ///
+    /// Fails with [`ConfigError::SecretUnresolved`] for references that have
+    /// not been resolved yet.
+    pub(crate) fn substitute(&self, value: &mut Value) -> Result<()> {
+        let cache = self.cache.read().unwrap();
+        substitute(value, &cache)
+    }
+}
+
+/// Find the names of all secret references in a value.
+fn collect_references(value: &Value, names: &mut Vec<String>) {
+    match value {
+        Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some((name, after)) = next_reference(rest) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                rest = after;
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_references(v, names)),
+        Value::Object(map) => map.values().for_each(|v| collect_references(v, names)),
+        _ => {}
+    }
+}
+
+fn substitute(value: &mut Value, cache: &HashMap<String, String>) -> Result<()> {
+    match value {
+        Value::String(s) if s.contains(SECRET_PREFIX) => {
+            let mut resolved = String::with_capacity(s.len());
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find(SECRET_PREFIX) {
+                let Some((name, after)) = next_reference(&rest[start..]) else {
+                    break;
+                };
+                let secret = cache.get(name).ok_or_else(|| {
+                    ConfigError::SecretUnresolved(format!(
+                        "{}: not resolved yet, use get_resolved",
+                        name
+                    ))
+                })?;
+                resolved.push_str(&rest[..start]);
+                resolved.push_str(secret);
+                rest = after;
+            }
+            resolved.push_str(rest);
+            *s = resolved;
+        }
+        Value::Array(items) => {
+            for item in items {
+                substitute(item, cache)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                substitute(item, cache)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Find the next `${secret:name}` in `s`, returning the name and the text
+/// after the reference.
+fn next_reference(s: &str) -> Option<(&str, &str)> {
+    let start = s.find(SECRET_PREFIX)? + SECRET_PREFIX.len();
+    let len = s[start..].find('}')?;
+    Some((&s[start..start + len], &s[start + len + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collect_references() {
+        let mut names = Vec::new();
+        collect_references(
+            &json!({
+                "url": "postgres://app:${secret:db_password}@db/${secret:db_name}",
+                "nested": {"token": "${secret:api_token}"},
+                "list": ["${secret:db_password}", "plain"],
+                "broken": "${secret:unterminated",
+            }),
+            &mut names,
+        );
+        names.sort();
+
+        assert_eq!(names, vec!["api_token", "db_name", "db_password"]);
+    }
+
+    #[test]
+    fn test_substitute() {
+        let cache = HashMap::from([
+            ("user".to_string(), "app".to_string()),
+            ("pass".to_string(), "s3cret".to_string()),
+        ]);
+        let mut value = json!({"url": "pg://${secret:user}:${secret:pass}@db", "port": 5432});
+        substitute(&mut value, &cache).unwrap();
+        assert_eq!(value, json!({"url": "pg://app:s3cret@db", "port": 5432}));
+
+        let mut missing = json!("${secret:other}");
+        assert!(matches!(
+            substitute(&mut missing, &cache),
+            Err(ConfigError::SecretUnresolved(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_resolver() {
+        let dir = std::env::temp_dir().join(format!(
+            "armature-config-secrets-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("db_password"), "hunter2\n").unwrap();
+
+        let resolver = FileSecretResolver::new(&dir);
+        tokio_test::block_on(async {
+            assert_eq!(resolver.resolve("db_password").await.unwrap(), "hunter2");
+            assert!(matches!(
+                resolver.resolve("missing").await,
+                Err(ConfigError::SecretUnresolved(_))
+            ));
+            assert!(matches!(
+                resolver.resolve("../db_password").await,
+                Err(ConfigError::SecretUnresolved(_))
+            ));
+        });
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_env_resolver_missing() {
+        let resolver = EnvSecretResolver::new(Some("ARMATURE_SECRET_TEST".to_string()));
+        let result = tokio_test::block_on(resolver.resolve("missing_12345"));
+
+        assert!(matches!(result, Err(ConfigError::SecretUnresolved(_))));
+    }
+}
@@ -0,0 +1,109 @@
+// Origin tracking for configuration values
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Where a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Defaults registered with [`ConfigBuilder::defaults`](crate::ConfigBuilder::defaults)
+    Default,
+    /// A configuration file
+    File(PathBuf),
+    /// An environment variable, by name
+    Env(String),
+    /// Overrides registered with [`ConfigBuilder::overrides`](crate::ConfigBuilder::overrides)
+    Override,
+    /// Set at runtime with [`ConfigManager::set`](crate::ConfigManager::set)
+    Set,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "defaults"),
+            Source::File(path) => write!(f, "file {}", path.display()),
+            Source::Env(name) => write!(f, "environment variable {}", name),
+            Source::Override => write!(f, "overrides"),
+            Source::Set => write!(f, "set at runtime"),
+        }
+    }
+}
+
+/// Sources recorded per dotted key
+///
+/// A key's source applies to everything below it unless a more specific key
+/// was recorded, so loading a `database` table from a file and then setting
+/// `database.port` reports the file for `database.host` and [`Source::Set`]
+/// for `database.port`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Sources(HashMap<String, Source>);
+
+impl Sources {
+    /// Record the source of a key, replacing anything recorded below it.
+    pub(crate) fn record(&mut self, key: &str, source: Source) {
+        self.forget(key);
+        self.0.insert(key.to_string(), source);
+    }
+
+    /// Forget a key and everything recorded below it.
+    pub(crate) fn forget(&mut self, key: &str) {
+        let prefix = format!("{}.", key);
+        self.0.retain(|k, _| k != key && !k.starts_with(&prefix));
+    }
+
+    /// Find the source of a key, falling back to its nearest recorded parent.
+    pub(crate) fn lookup(&self, key: &str) -> Option<Source> {
+        let mut key = key;
+        loop {
+            if let Some(source) = self.0.get(key) {
+                return Some(source.clone());
+            }
+            key = &key[..key.rfind('.')?];
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub(crate) fn extend(&mut self, other: &Sources) {
+        for (key, source) in &other.0 {
+            self.record(key, source.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_falls_back_to_parent() {
+        let mut sources = Sources::default();
+        sources.record("database", Source::File(PathBuf::from("app.toml")));
+        sources.record("database.port", Source::Env("APP_DATABASE__PORT".into()));
+
+        assert_eq!(
+            sources.lookup("database.host"),
+            Some(Source::File(PathBuf::from("app.toml")))
+        );
+        assert_eq!(
+            sources.lookup("database.port"),
+            Some(Source::Env("APP_DATABASE__PORT".into()))
+        );
+        assert_eq!(sources.lookup("cache"), None);
+    }
+
+    #[test]
+    fn test_record_replaces_children() {
+        let mut sources = Sources::default();
+        sources.record("database.port", Source::Set);
+        sources.record("database_url", Source::Set);
+        sources.record("database", Source::Default);
+
+        assert_eq!(sources.lookup("database.port"), Some(Source::Default));
+        assert_eq!(sources.lookup("database_url"), Some(Source::Set));
+    }
+}
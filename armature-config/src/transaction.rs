@@ -0,0 +1,67 @@
+// Atomic multi-key updates
+
+use crate::source::Source;
+use crate::{ConfigError, ConfigManager, Result, tree};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A staged change: a key set from a source, or removed
+pub(crate) type Change = (String, Option<Source>);
+
+/// A staged batch of changes, created by [`ConfigManager::update`]
+///
+/// Changes apply to a copy of the configuration and become visible to other
+/// readers all at once when the update commits.
+pub struct Transaction<'a> {
+    manager: &'a ConfigManager,
+    staged: HashMap<String, Value>,
+    changes: Vec<Change>,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(manager: &'a ConfigManager, staged: HashMap<String, Value>) -> Self {
+        Self {
+            manager,
+            staged,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Get a value from the staged configuration
+    ///
+    /// Sees changes made earlier in the transaction and otherwise behaves
+    /// like [`ConfigManager::get`].
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<T> {
+        let value = self
+            .manager
+            .lookup(&self.staged, key)
+            .ok_or_else(|| ConfigError::KeyNotFound(key.to_string()))?;
+        self.manager.decode(value)
+    }
+
+    /// Check if a key is explicitly set in the staged configuration
+    pub fn has(&self, key: &str) -> bool {
+        tree::get(&self.staged, key).is_some()
+    }
+
+    /// Stage a value
+    pub fn set<T: serde::Serialize>(&mut self, key: &str, value: T) -> Result<()> {
+        let value = serde_json::to_value(value)
+            .map_err(|e| ConfigError::SerializationError(e.to_string()))?;
+
+        tree::insert(&mut self.staged, key, value);
+        self.changes.push((key.to_string(), Some(Source::Set)));
+        Ok(())
+    }
+
+    /// Stage the removal of a key
+    pub fn remove(&mut self, key: &str) {
+        tree::remove(&mut self.staged, key);
+        self.changes.push((key.to_string(), None));
+    }
+
+    pub(crate) fn into_parts(self) -> (HashMap<String, Value>, Vec<Change>) {
+        (self.staged, self.changes)
+    }
+}
@@ -0,0 +1,214 @@
+// Dot-notation access into the nested configuration tree
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Split a dotted key into path segments.
+///
+/// Returns `None` for keys that cannot be treated as a path (no dot, or an
+/// empty segment such as `"a..b"`), which are only ever stored literally.
+fn segments(key: &str) -> Option<Vec<&str>> {
+    if !key.contains('.') {
+        return None;
+    }
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return None;
+    }
+    Some(segments)
+}
+
+/// Look up a key, preferring a literal entry over a nested path.
+pub(crate) fn get<'a>(root: &'a HashMap<String, Value>, key: &str) -> Option<&'a Value> {
+    if let Some(value) = root.get(key) {
+        return Some(value);
+    }
+
+    let segments = segments(key)?;
+    let mut current = root.get(segments[0])?;
+    for segment in &segments[1..] {
+        current = current.as_object()?.get(*segment)?;
+    }
+    Some(current)
+}
+
+/// Insert a value at a dotted path, creating intermediate objects.
+///
+/// Intermediate values that are not objects are replaced. A literal entry
+/// with the same dotted key is removed so it cannot shadow the new value.
+pub(crate) fn insert(root: &mut HashMap<String, Value>, key: &str, value: Value) {
+    let Some(segments) = segments(key) else {
+        root.insert(key.to_string(), value);
+        return;
+    };
+    root.remove(key);
+
+    let (last, parents) = segments.split_last().expect("segments are never empty");
+    let first = root
+        .entry(parents[0].to_string())
+        .or_insert_with(|| Value::Object(Map::new()));
+    let mut current = as_object_mut(first);
+    for segment in &parents[1..] {
+        let next = current
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        current = as_object_mut(next);
+    }
+    current.insert(last.to_string(), value);
+}
+
+/// Remove a key, preferring a literal entry over a nested path.
+pub(crate) fn remove(root: &mut HashMap<String, Value>, key: &str) -> Option<Value> {
+    if let Some(value) = root.remove(key) {
+        return Some(value);
+    }
+
+    let segments = segments(key)?;
+    let (last, parents) = segments.split_last()?;
+    let mut current = root.get_mut(parents[0])?.as_object_mut()?;
+    for segment in &parents[1..] {
+        current = current.get_mut(*segment)?.as_object_mut()?;
+    }
+    current.remove(*last)
+}
+
+/// Build a single object from the root map.
+pub(crate) fn to_value(root: &HashMap<String, Value>) -> Value {
+    Value::Object(root.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+}
+
+/// Flatten a value into dotted paths to its leaves.
+///
+/// Non-empty objects are descended into; every other value, including an
+/// empty object, is a leaf.
+pub(crate) fn leaves(key: String, value: Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (child, value) in map {
+                leaves(format!("{}.{}", key, child), value, out);
+            }
+        }
+        value => out.push((key, value)),
+    }
+}
+
+/// Deep-merge `overlay` into `base`; objects merge key by key and anything
+/// else in `overlay` replaces what is in `base`.
+pub(crate) fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn as_object_mut(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!("value was just replaced with an object"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_insert_builds_tree() {
+        let mut root = HashMap::new();
+        insert(&mut root, "a.b.c", json!(1));
+
+        assert_eq!(root.get("a"), Some(&json!({"b": {"c": 1}})));
+        assert_eq!(get(&root, "a.b.c"), Some(&json!(1)));
+        assert_eq!(get(&root, "a.b"), Some(&json!({"c": 1})));
+    }
+
+    #[test]
+    fn test_insert_replaces_scalar_parent() {
+        let mut root = HashMap::new();
+        insert(&mut root, "a", json!("scalar"));
+        insert(&mut root, "a.b", json!(true));
+
+        assert_eq!(root.get("a"), Some(&json!({"b": true})));
+    }
+
+    #[test]
+    fn test_literal_key_takes_precedence() {
+        let mut root = HashMap::new();
+        root.insert("a.b".to_string(), json!("literal"));
+        root.insert("a".to_string(), json!({"b": "nested"}));
+
+        assert_eq!(get(&root, "a.b"), Some(&json!("literal")));
+
+        // Setting the path replaces the literal entry
+        insert(&mut root, "a.b", json!("new"));
+        assert!(!root.contains_key("a.b"));
+        assert_eq!(get(&root, "a.b"), Some(&json!("new")));
+    }
+
+    #[test]
+    fn test_remove_nested() {
+        let mut root = HashMap::new();
+        insert(&mut root, "a.b", json!(1));
+        insert(&mut root, "a.c", json!(2));
+
+        assert_eq!(remove(&mut root, "a.b"), Some(json!(1)));
+        assert_eq!(root.get("a"), Some(&json!({"c": 2})));
+        assert_eq!(remove(&mut root, "a.missing"), None);
+    }
+
+    #[test]
+    fn test_leaves() {
+        let mut out = Vec::new();
+        leaves(
+            "db".to_string(),
+            json!({"host": "localhost", "pool": {"max": 10}, "tags": {}}),
+            &mut out,
+        );
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            out,
+            vec![
+                ("db.host".to_string(), json!("localhost")),
+                ("db.pool.max".to_string(), json!(10)),
+                ("db.tags".to_string(), json!({})),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut base = json!({"db": {"host": "default", "port": 5432}, "list": [1, 2]});
+        merge(
+            &mut base,
+            json!({"db": {"host": "db.internal"}, "list": [3], "debug": true}),
+        );
+
+        assert_eq!(
+            base,
+            json!({"db": {"host": "db.internal", "port": 5432}, "list": [3], "debug": true})
+        );
+    }
+
+    #[test]
+    fn test_empty_segments_are_literal() {
+        let mut root = HashMap::new();
+        insert(&mut root, "a..b", json!(1));
+
+        assert_eq!(root.get("a..b"), Some(&json!(1)));
+        assert!(!root.contains_key("a"));
+    }
+}
@@ -0,0 +1,154 @@
+// Cached typed access to configuration sections
+
+use crate::{ConfigManager, Result};
+use arc_swap::ArcSwap;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A typed view of a configuration key that deserializes only on change
+///
+/// Created by [`ConfigManager::typed`]. [`get`](Self::get) compares the
+/// manager's version with the version the cached value was derived from and
+/// returns the cached `Arc<T>` when nothing changed, so it is cheap enough
+/// for per-request code. Clones share the cache.
+///
+/// If the key is removed or no longer deserializes into `T` after a change,
+/// the handle keeps returning the last good value.
+pub struct ConfigHandle<T> {
+    manager: ConfigManager,
+    key: Arc<str>,
+    state: Arc<HandleState<T>>,
+}
+
+struct HandleState<T> {
+    value: ArcSwap<T>,
+    version: AtomicU64,
+}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> ConfigHandle<T> {
+    pub(crate) fn new(manager: &ConfigManager, key: &str) -> Result<Self> {
+        let version = manager.version();
+        let value: T = manager.get(key)?;
+
+        Ok(Self {
+            manager: manager.clone(),
+            key: Arc::from(key),
+            state: Arc::new(HandleState {
+                value: ArcSwap::from_pointee(value),
+                version: AtomicU64::new(version),
+            }),
+        })
+    }
+
+    /// Get the current value
+    pub fn get(&self) -> Arc<T> {
+        let version = self.manager.version();
+        if self.state.version.load(Ordering::Acquire) != version {
+            // Reading the version first means a change made while we
+            // deserialize is picked up on the next call.
+            if let Ok(value) = self.manager.get::<T>(&self.key) {
+                self.state.value.store(Arc::new(value));
+            }
+            self.state.version.store(version, Ordering::Release);
+        }
+        self.state.value.load_full()
+    }
+
+    /// Get the configuration key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl<T> Clone for ConfigHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            manager: self.manager.clone(),
+            key: Arc::clone(&self.key),
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ConfigHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigHandle")
+            .field("key", &self.key)
+            .field("version", &self.state.version.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::sync::atomic::AtomicUsize;
+
+    static DESERIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    impl<'de> Deserialize<'de> for Server {
+        fn deserialize<D: serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            #[derive(Deserialize)]
+            struct Raw {
+                host: String,
+                port: u16,
+            }
+
+            DESERIALIZED.fetch_add(1, Ordering::SeqCst);
+            let raw = Raw::deserialize(deserializer)?;
+            Ok(Server {
+                host: raw.host,
+                port: raw.port,
+            })
+        }
+    }
+
+    #[test]
+    fn test_get_does_not_redeserialize() {
+        let manager = ConfigManager::new();
+        manager.set("server.host", "localhost").unwrap();
+        manager.set("server.port", 8080).unwrap();
+
+        let handle = manager.typed::<Server>("server").unwrap();
+        let start = DESERIALIZED.load(Ordering::SeqCst);
+
+        for _ in 0..1_000 {
+            assert_eq!(handle.get().port, 8080);
+        }
+        assert_eq!(DESERIALIZED.load(Ordering::SeqCst), start);
+
+        // A change to any key bumps the version and re-derives once
+        manager.set("server.port", 9090).unwrap();
+        let clone = handle.clone();
+        assert_eq!(handle.get().port, 9090);
+        assert_eq!(clone.get().port, 9090);
+        assert_eq!(DESERIALIZED.load(Ordering::SeqCst), start + 1);
+
+        // Invalid data keeps the last good value
+        manager.set("server.port", "not a port").unwrap();
+        assert_eq!(
+            *handle.get(),
+            Server {
+                host: "localhost".to_string(),
+                port: 9090
+            }
+        );
+    }
+
+    #[test]
+    fn test_missing_key() {
+        let manager = ConfigManager::new();
+        assert!(manager.typed::<u16>("missing").is_err());
+    }
+}
@@ -1,10 +1,218 @@
 // Configuration validation
 
 use crate::{ConfigError, Result};
+use regex::Regex;
+use std::fmt::{self, Display};
 
 /// Trait for validating configuration
+///
+/// Implement [`validate`](Self::validate) for a simple pass/fail check, or
+/// [`validate_fields`](Self::validate_fields) to report every problem with
+/// its path. Each method has a default in terms of the other, so at least
+/// one of them must be implemented.
 pub trait Validate {
-    fn validate(&self) -> Result<()>;
+    /// Validate the whole value
+    fn validate(&self) -> Result<()> {
+        let mut ctx = ValidationContext::new();
+        self.validate_fields(&mut ctx);
+        ctx.finish()
+    }
+
+    /// Record every validation error in `ctx`
+    fn validate_fields(&self, ctx: &mut ValidationContext) {
+        if let Err(e) = self.validate() {
+            ctx.push(e);
+        }
+    }
+}
+
+/// Items are validated with their index in the path (`/replicas/0/host`).
+impl<T: Validate> Validate for [T] {
+    fn validate_fields(&self, ctx: &mut ValidationContext) {
+        for (i, item) in self.iter().enumerate() {
+            ctx.nested(&i.to_string(), item);
+        }
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_fields(&self, ctx: &mut ValidationContext) {
+        self.as_slice().validate_fields(ctx);
+    }
+}
+
+/// `None` is always valid.
+impl<T: Validate> Validate for Option<T> {
+    fn validate_fields(&self, ctx: &mut ValidationContext) {
+        if let Some(value) = self {
+            value.validate_fields(ctx);
+        }
+    }
+}
+
+/// A validation failure at a JSON-pointer path such as `/database/port`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Collects validation errors from nested structs in one pass
+///
+/// # Example
+///
+/// ```
+/// use armature_config::{ConfigError, Validate, ValidationContext};
+///
+/// struct Database {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// impl Validate for Database {
+///     fn validate_fields(&self, ctx: &mut ValidationContext) {
+///         ctx.not_empty("host", &self.host);
+///         ctx.range("port", self.port, 1, 65535);
+///     }
+/// }
+///
+/// struct AppConfig {
+///     database: Database,
+/// }
+///
+/// impl Validate for AppConfig {
+///     fn validate_fields(&self, ctx: &mut ValidationContext) {
+///         ctx.nested("database", &self.database);
+///     }
+/// }
+///
+/// let config = AppConfig {
+///     database: Database { host: String::new(), port: 0 },
+/// };
+///
+/// let Err(ConfigError::ValidationErrors(errors)) = config.validate() else {
+///     panic!("expected validation errors");
+/// };
+/// assert_eq!(errors[0].to_string(), "/database/host: cannot be empty");
+/// assert_eq!(errors[1].to_string(), "/database/port: must be between 1 and 65535");
+/// ```
+#[derive(Debug, Default)]
+pub struct ValidationContext {
+    path: String,
+    errors: Vec<FieldError>,
+}
+
+impl ValidationContext {
+    /// Create an empty context at the root path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an error for a field at the current path
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError {
+            path: self.path_of(field),
+            message: message.into(),
+        });
+    }
+
+    /// Record an existing error at the current path
+    ///
+    /// Paths of [`ConfigError::ValidationErrors`] are nested under the
+    /// current path; any other error is recorded with its message.
+    pub fn push(&mut self, error: ConfigError) {
+        match error {
+            ConfigError::ValidationErrors(errors) => {
+                for error in errors {
+                    self.errors.push(FieldError {
+                        path: format!("{}{}", self.path, error.path),
+                        message: error.message,
+                    });
+                }
+            }
+            ConfigError::ValidationError(message) => self.error("", message),
+            other => self.error("", other.to_string()),
+        }
+    }
+
+    /// Validate a nested value with its errors under `field`
+    pub fn nested<T: Validate + ?Sized>(&mut self, field: &str, value: &T) {
+        let path = self.path_of(field);
+        let parent = std::mem::replace(&mut self.path, path);
+        value.validate_fields(self);
+        self.path = parent;
+    }
+
+    /// Check that a string is not empty
+    pub fn not_empty(&mut self, field: &str, value: &str) {
+        if value.is_empty() {
+            self.error(field, "cannot be empty");
+        }
+    }
+
+    /// Check that a value is within `min..=max`
+    pub fn range<T: PartialOrd + Display>(&mut self, field: &str, value: T, min: T, max: T) {
+        if value < min || value > max {
+            self.error(field, format!("must be between {} and {}", min, max));
+        }
+    }
+
+    /// Check that a string matches a regular expression
+    pub fn matches(&mut self, field: &str, value: &str, pattern: &Regex) {
+        if !pattern.is_match(value) {
+            self.error(field, format!("must match {}", pattern.as_str()));
+        }
+    }
+
+    /// Check that a value is one of the allowed values
+    pub fn one_of<T: PartialEq + Display>(&mut self, field: &str, value: &T, allowed: &[T]) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(ToString::to_string).collect();
+            self.error(field, format!("must be one of {}", allowed.join(", ")));
+        }
+    }
+
+    /// Get the errors recorded so far
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Check if no errors were recorded
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Return all recorded errors as [`ConfigError::ValidationErrors`]
+    pub fn finish(self) -> Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::ValidationErrors(self.errors))
+        }
+    }
+
+    fn path_of(&self, field: &str) -> String {
+        if field.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}{}", self.path, pointer_segment(field))
+        }
+    }
+}
+
+/// Encode a field name as a JSON-pointer segment.
+fn pointer_segment(field: &str) -> String {
+    format!("/{}", field.replace('~', "~0").replace('/', "~1"))
 }
 
 /// Configuration validator with rules
@@ -33,6 +241,18 @@ impl ConfigValidator {
         Ok(())
     }
 
+    /// Validate that a string matches a regular expression
+    pub fn matches(value: &str, pattern: &Regex, field: &str) -> Result<()> {
+        if !pattern.is_match(value) {
+            return Err(ConfigError::ValidationError(format!(
+                "{} must match {}",
+                field,
+                pattern.as_str()
+            )));
+        }
+        Ok(())
+    }
+
     /// Validate that a value is in a list of allowed values
     pub fn one_of<T: PartialEq>(value: &T, allowed: &[T], field: &str) -> Result<()> {
         if !allowed.contains(value) {
@@ -102,6 +322,107 @@ mod tests {
         assert!(ConfigValidator::one_of(&"d", &allowed, "field").is_err());
     }
 
+    #[test]
+    fn test_matches_validation() {
+        let pattern = Regex::new(r"^[a-z]+$").unwrap();
+        assert!(ConfigValidator::matches("abc", &pattern, "field").is_ok());
+        assert!(ConfigValidator::matches("ABC", &pattern, "field").is_err());
+    }
+
+    struct Database {
+        host: String,
+        port: u32,
+    }
+
+    impl Validate for Database {
+        fn validate_fields(&self, ctx: &mut ValidationContext) {
+            ctx.not_empty("host", &self.host);
+            ctx.range("port", self.port, 1, 65535);
+        }
+    }
+
+    struct Legacy;
+
+    impl Validate for Legacy {
+        fn validate(&self) -> Result<()> {
+            ConfigValidator::is_url("ftp://example.com", "endpoint")
+        }
+    }
+
+    struct AppConfig {
+        name: String,
+        mode: &'static str,
+        database: Database,
+        replicas: Vec<Database>,
+        legacy: Legacy,
+    }
+
+    impl Validate for AppConfig {
+        fn validate_fields(&self, ctx: &mut ValidationContext) {
+            ctx.matches("name", &self.name, &Regex::new(r"^[a-z-]+$").unwrap());
+            ctx.one_of("mode", &self.mode, &["development", "production"]);
+            ctx.nested("database", &self.database);
+            ctx.nested("replicas", &self.replicas);
+            ctx.nested("legacy", &self.legacy);
+        }
+    }
+
+    #[test]
+    fn test_multiple_errors_with_paths() {
+        let config = AppConfig {
+            name: "My App".to_string(),
+            mode: "staging",
+            database: Database {
+                host: "localhost".to_string(),
+                port: 70000,
+            },
+            replicas: vec![Database {
+                host: String::new(),
+                port: 5432,
+            }],
+            legacy: Legacy,
+        };
+
+        let Err(ConfigError::ValidationErrors(errors)) = config.validate() else {
+            panic!("expected validation errors");
+        };
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "/name: must match ^[a-z-]+$",
+                "/mode: must be one of development, production",
+                "/database/port: must be between 1 and 65535",
+                "/replicas/0/host: cannot be empty",
+                "/legacy: endpoint must be a valid URL",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validation_errors_display() {
+        let mut ctx = ValidationContext::new();
+        ctx.error("port", "must be set");
+        ctx.nested("tls", &Legacy);
+
+        let error = ctx.finish().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Validation failed: /port: must be set; /tls: endpoint must be a valid URL"
+        );
+    }
+
+    #[test]
+    fn test_valid_config_passes() {
+        let database = Database {
+            host: "localhost".to_string(),
+            port: 5432,
+        };
+        assert!(database.validate().is_ok());
+        assert!(Legacy.validate().is_err());
+    }
+
     #[test]
     fn test_url_validation() {
         assert!(ConfigValidator::is_url("https://example.com", "field").is_ok());
@@ -0,0 +1,220 @@
+// File watching and hot reload
+
+use crate::{ConfigError, ConfigManager, FileFormat, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Quiet period after the last file event before a reload.
+///
+/// Editors and deploy tools usually write a file in several steps (truncate,
+/// write, rename); waiting for the events to settle avoids reloading a
+/// half-written file.
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Callback fired after a successful reload
+pub type ReloadCallback = Box<dyn Fn(&ConfigManager) + Send + Sync>;
+
+/// Stops watching a configuration file when dropped
+#[must_use = "the file is no longer watched once the guard is dropped"]
+pub struct WatchGuard {
+    path: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl WatchGuard {
+    /// Get the watched file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl std::fmt::Debug for WatchGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchGuard")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Load `path` into `manager`, then reload it whenever it changes.
+pub(crate) fn watch(manager: &ConfigManager, path: &str, format: FileFormat) -> Result<WatchGuard> {
+    let mut keys = manager.reload_file(path, format, &HashSet::new())?;
+
+    let file = PathBuf::from(path);
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| ConfigError::LoadError(format!("Not a file path: {}", path)))?
+        .to_os_string();
+
+    // Watch the directory rather than the file itself: editors commonly
+    // replace files by renaming over them, which would end a file watch.
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| ConfigError::LoadError(format!("Failed to watch {}: {}", path, e)))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| ConfigError::LoadError(format!("Failed to watch {}: {}", path, e)))?;
+
+    let manager = manager.clone();
+    let path = path.to_string();
+    std::thread::Builder::new()
+        .name("armature-config-watch".to_string())
+        .spawn(move || {
+            let is_target = |event: &Event| {
+                matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name() == Some(file_name.as_os_str()))
+            };
+
+            // Ends once the guard drops the watcher and closes the channel
+            while wait_for_change(&rx, WATCH_DEBOUNCE, is_target) {
+                // A file that fails to load (for example, while it is still
+                // being written) keeps the current values until the next change.
+                if let Ok(loaded) = manager.reload_file(&path, format, &keys) {
+                    keys = loaded;
+                    manager.fire_reload_callbacks();
+                }
+            }
+        })?;
+
+    Ok(WatchGuard {
+        path: file,
+        _watcher: watcher,
+    })
+}
+
+/// Block until a matching event arrives and no further events follow
+/// within `debounce`.
+///
+/// Returns `false` once the channel is closed.
+fn wait_for_change<T>(
+    rx: &Receiver<notify::Result<T>>,
+    debounce: Duration,
+    is_target: impl Fn(&T) -> bool,
+) -> bool {
+    loop {
+        match rx.recv() {
+            Ok(Ok(event)) if is_target(&event) => break,
+            Ok(_) => continue,
+            Err(_) => return false,
+        }
+    }
+
+    loop {
+        match rx.recv_timeout(debounce) {
+            Ok(_) => continue,
+            Err(RecvTimeoutError::Timeout) => return true,
+            Err(RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "armature-config-{}-{}-{}",
+            name,
+            std::process::id(),
+            nanos
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("app.json")
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        false
+    }
+
+    #[test]
+    fn test_debounce_collapses_bursts() {
+        let (tx, rx) = mpsc::channel::<notify::Result<u32>>();
+        for i in 0..5 {
+            tx.send(Ok(i)).unwrap();
+        }
+
+        assert!(wait_for_change(&rx, Duration::from_millis(20), |_| true));
+
+        // The whole burst was consumed by the first change
+        drop(tx);
+        assert!(!wait_for_change(&rx, Duration::from_millis(20), |_| true));
+    }
+
+    #[test]
+    fn test_debounce_ignores_other_events() {
+        let (tx, rx) = mpsc::channel::<notify::Result<u32>>();
+        tx.send(Ok(1)).unwrap();
+        tx.send(Ok(2)).unwrap();
+        drop(tx);
+
+        assert!(!wait_for_change(&rx, Duration::from_millis(20), |e| *e == 3));
+    }
+
+    #[test]
+    fn test_watch_file_reloads() {
+        let path = temp_file("watch");
+        fs::write(&path, r#"{"port": 3000, "stale": true}"#).unwrap();
+
+        let manager = ConfigManager::new();
+        manager.set("env_only", "kept").unwrap();
+
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reloads);
+        manager.on_reload(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let guard = manager
+            .watch_file(path.to_str().unwrap(), FileFormat::Json)
+            .unwrap();
+        assert_eq!(manager.get_int("port").unwrap(), 3000);
+
+        fs::write(&path, r#"{"port": 4000}"#).unwrap();
+        assert!(wait_until(|| reloads.load(Ordering::SeqCst) > 0));
+        assert_eq!(manager.get_int("port").unwrap(), 4000);
+        assert!(!manager.has("stale"));
+        assert_eq!(manager.get_string("env_only").unwrap(), "kept");
+
+        // Invalid content keeps the current values
+        let count = reloads.load(Ordering::SeqCst);
+        fs::write(&path, "{ not json").unwrap();
+        std::thread::sleep(WATCH_DEBOUNCE * 5);
+        assert_eq!(reloads.load(Ordering::SeqCst), count);
+        assert_eq!(manager.get_int("port").unwrap(), 4000);
+
+        drop(guard);
+        std::thread::sleep(WATCH_DEBOUNCE * 2);
+        fs::write(&path, r#"{"port": 5000}"#).unwrap();
+        std::thread::sleep(WATCH_DEBOUNCE * 5);
+        assert_eq!(manager.get_int("port").unwrap(), 4000);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
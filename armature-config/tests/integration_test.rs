@@ -115,3 +115,42 @@ fn test_config_manager_clear() {
     assert!(!manager.has("key1"));
     assert!(!manager.has("key2"));
 }
+
+#[test]
+fn test_export_round_trip() {
+    let manager = ConfigManager::new();
+    manager.set("name", "armature").unwrap();
+    manager.set("debug", false).unwrap();
+    manager.set("database.host", "localhost").unwrap();
+    manager.set("database.port", 5432).unwrap();
+    manager.set("database.replicas", vec!["a", "b"]).unwrap();
+    manager.set("database.pool.max", 10).unwrap();
+    manager.set("limits.ratio", 0.75).unwrap();
+    let original = manager.dump(FileFormat::Json, false).unwrap();
+
+    for format in [FileFormat::Toml, FileFormat::Yaml, FileFormat::Json] {
+        let exported = manager.dump(format, false).unwrap();
+        let parsed = ConfigLoader::new(format).parse(&exported).unwrap();
+        let reparsed = ConfigLoader::new(FileFormat::Json)
+            .parse(&original)
+            .unwrap();
+        assert_eq!(parsed, reparsed, "{:?} round trip", format);
+    }
+}
+
+#[test]
+fn test_export_redacts_sensitive_keys() {
+    let manager = ConfigManager::new();
+    manager.set("database.password", "hunter2").unwrap();
+    manager.set("session.cookie", "abc").unwrap();
+
+    let json = manager.to_json_pretty().unwrap();
+    assert!(!json.contains("hunter2"));
+    assert!(json.contains("abc"));
+
+    manager.add_sensitive_key("Cookie");
+    assert!(!manager.to_yaml().unwrap().contains("abc"));
+
+    manager.set_sensitive_keys(Vec::<String>::new());
+    assert!(manager.to_toml().unwrap().contains("hunter2"));
+}
@@ -4,7 +4,7 @@ use crate::logging::{debug, error, info, trace, warn};
 use crate::pipeline::{PipelineConfig, PipelineStats, PipelinedHttp1Builder};
 use crate::{
     Container, Error, HttpRequest, HttpResponse, HttpsConfig, LifecycleManager, Module, Router,
-    TlsConfig,
+    Scheme, TlsConfig,
 };
 use http_body_util::{BodyExt, Full};
 use hyper::server::conn::http1;
@@ -418,7 +418,7 @@ impl Application {
                     let stats = Arc::clone(&stats);
                     async move {
                         stats.request_processed();
-                        handle_request(req, router).await
+                        handle_request(req, router, Scheme::Http).await
                     }
                 });
 
@@ -505,7 +505,7 @@ impl Application {
                             let stats = Arc::clone(&stats);
                             async move {
                                 stats.request_processed();
-                                handle_request(req, router).await
+                                handle_request(req, router, Scheme::Https).await
                             }
                         });
 
@@ -590,7 +590,7 @@ impl Application {
 
                         let service = service_fn(move |req: Request<IncomingBody>| {
                             let router = router.clone();
-                            async move { handle_request(req, router).await }
+                            async move { handle_request(req, router, Scheme::Https).await }
                         });
 
                         if let Err(err) = http1::Builder::new().serve_connection(io, service).await
@@ -669,6 +669,7 @@ async fn start_http_redirect_server(addr: &str, https_port: u16) -> Result<(), E
 async fn handle_request(
     req: Request<IncomingBody>,
     router: Arc<Router>,
+    scheme: Scheme,
 ) -> Result<Response<Full<bytes::Bytes>>, hyper::Error> {
     use std::time::Instant;
 
@@ -681,6 +682,7 @@ async fn handle_request(
     trace!(method = %method, path = %path, "Incoming request");
 
     let mut armature_req = HttpRequest::new(method.clone(), path.clone());
+    armature_req.insert_extension(scheme);
 
     // Copy headers
     let header_count = req.headers().len();
@@ -7,6 +7,32 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// URI scheme a request was received over
+///
+/// The server stores it in the request extensions, see [`HttpRequest::scheme`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// Plain HTTP
+    Http,
+    /// HTTP over TLS
+    Https,
+}
+
+impl Scheme {
+    /// Parse a URI scheme name, ignoring case.
+    ///
+    /// Returns `None` for anything other than `http` or `https`.
+    pub fn parse(scheme: &str) -> Option<Self> {
+        if scheme.eq_ignore_ascii_case("https") {
+            Some(Self::Https)
+        } else if scheme.eq_ignore_ascii_case("http") {
+            Some(Self::Http)
+        } else {
+            None
+        }
+    }
+}
+
 /// HTTP request wrapper
 ///
 /// The body is stored as `Vec<u8>` for backwards compatibility.
@@ -193,6 +219,16 @@ impl HttpRequest {
         self.extensions.get_arc::<T>()
     }
 
+    /// Get the scheme the server received this request over.
+    ///
+    /// Returns `None` for requests that did not come from the server, such as
+    /// those built in tests. Behind a TLS-terminating proxy this is the scheme
+    /// between the proxy and the server.
+    #[inline]
+    pub fn scheme(&self) -> Option<Scheme> {
+        self.extension::<Scheme>().copied()
+    }
+
     /// Parse the request body as JSON.
     ///
     /// With the `simd-json` feature enabled, this uses SIMD-accelerated parsing
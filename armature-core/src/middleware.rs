@@ -48,6 +48,11 @@ impl MiddlewareChain {
         self.middlewares = Arc::new(mws);
     }
 
+    /// Check whether the chain has no middleware
+    pub fn is_empty(&self) -> bool {
+        self.middlewares.is_empty()
+    }
+
     /// Execute the middleware chain with a handler
     pub async fn apply(&self, req: HttpRequest, handler: HandlerFn) -> Result<HttpResponse, Error> {
         debug!(
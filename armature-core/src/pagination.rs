@@ -130,6 +130,33 @@ impl OffsetPagination {
     pub fn has_prev(&self) -> bool {
         self.page > 1
     }
+
+    /// Build links to the first, previous, next, and last pages
+    ///
+    /// Links are query strings, relative to the current path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use armature_core::*;
+    ///
+    /// let links = OffsetPagination::new(2, 10).links(25);
+    /// assert_eq!(links.first, "?page=1&per_page=10");
+    /// assert_eq!(links.prev.as_deref(), Some("?page=1&per_page=10"));
+    /// assert_eq!(links.next.as_deref(), Some("?page=3&per_page=10"));
+    /// assert_eq!(links.last, "?page=3&per_page=10");
+    /// ```
+    pub fn links(&self, total_items: usize) -> PaginationLinks {
+        let link = |page: usize| format!("?page={}&per_page={}", page, self.per_page);
+        let last = self.total_pages(total_items).max(1);
+
+        PaginationLinks {
+            first: link(1),
+            prev: self.has_prev().then(|| link((self.page - 1).min(last))),
+            next: self.has_next(total_items).then(|| link(self.page + 1)),
+            last: link(last),
+        }
+    }
 }
 
 impl Default for OffsetPagination {
@@ -215,6 +242,24 @@ pub struct PaginationMeta {
     pub next_cursor: Option<String>,
 }
 
+/// Links to neighbouring pages of an offset-paginated response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaginationLinks {
+    /// First page
+    pub first: String,
+
+    /// Previous page, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+
+    /// Next page, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+
+    /// Last page
+    pub last: String,
+}
+
 /// Paginated response wrapper
 ///
 /// Generic wrapper for paginated API responses.
@@ -246,6 +291,10 @@ pub struct PaginatedResponse<T> {
 
     /// Pagination metadata
     pub meta: PaginationMeta,
+
+    /// Page links (offset pagination)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<PaginationLinks>,
 }
 
 impl<T> PaginatedResponse<T> {
@@ -262,6 +311,7 @@ impl<T> PaginatedResponse<T> {
                 has_prev: pagination.has_prev(),
                 next_cursor: None,
             },
+            links: Some(pagination.links(total)),
         }
     }
 
@@ -283,6 +333,7 @@ impl<T> PaginatedResponse<T> {
                 has_prev: false, // Cursor pagination typically doesn't track prev
                 next_cursor,
             },
+            links: None,
         }
     }
 }
@@ -867,6 +918,32 @@ mod tests {
         assert_eq!(p.total_pages(101), 6);
     }
 
+    #[test]
+    fn test_offset_pagination_links() {
+        let links = OffsetPagination::new(1, 20).links(0);
+        assert_eq!(links.first, "?page=1&per_page=20");
+        assert_eq!(links.prev, None);
+        assert_eq!(links.next, None);
+        assert_eq!(links.last, "?page=1&per_page=20");
+
+        // Past the end: prev points at the last page
+        let links = OffsetPagination::new(9, 20).links(50);
+        assert_eq!(links.prev.as_deref(), Some("?page=3&per_page=20"));
+        assert_eq!(links.next, None);
+    }
+
+    #[test]
+    fn test_paginated_response_partial_last_page() {
+        let response = PaginatedResponse::new(vec![1, 2, 3], OffsetPagination::new(3, 10), 23);
+        assert_eq!(response.meta.total_pages, Some(3));
+        assert!(!response.meta.has_next);
+        assert!(response.meta.has_prev);
+
+        let links = response.links.unwrap();
+        assert_eq!(links.prev.as_deref(), Some("?page=2&per_page=10"));
+        assert_eq!(links.next, None);
+    }
+
     #[test]
     fn test_sort_field_from_str() {
         let f: SortField = "name".parse().unwrap();
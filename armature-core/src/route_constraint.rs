@@ -6,6 +6,7 @@
 //! # Features
 //!
 //! - **Built-in Constraints**: Int, UInt, Alpha, AlphaNum, UUID, Email, Regex
+//! - **Typed Constraints**: `ParseConstraint<T>` for any `FromStr` type
 //! - **Custom Constraints**: Implement `RouteConstraint` trait
 //! - **Composable**: Combine multiple constraints
 //! - **Type-safe**: Validate parameters match expected types
@@ -27,6 +28,8 @@
 use crate::Error;
 use regex::Regex;
 use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::str::FromStr;
 use std::sync::Arc;
 
 /// Trait for validating route parameters
@@ -416,6 +419,42 @@ impl RouteConstraint for EnumConstraint {
     }
 }
 
+/// Parse constraint - validates that a parameter parses as `T`
+///
+/// Used by the `routes!` macro for typed parameters such as `:id<i64>`, so
+/// the handler only runs when the value converts.
+pub struct ParseConstraint<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: FromStr> ParseConstraint<T> {
+    /// Create a new parse constraint
+    pub fn new() -> Self {
+        Self {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: FromStr> Default for ParseConstraint<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FromStr> RouteConstraint for ParseConstraint<T> {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        value
+            .parse::<T>()
+            .map(|_| ())
+            .map_err(|_| format!("'{}' is not a valid {}", value, self.description()))
+    }
+
+    fn description(&self) -> &str {
+        std::any::type_name::<T>()
+    }
+}
+
 /// Collection of route constraints for a route
 ///
 /// Maps parameter names to their constraints.
@@ -601,6 +640,15 @@ mod tests {
         assert!(constraint.validate("unknown").is_err());
     }
 
+    #[test]
+    fn test_parse_constraint() {
+        let constraint = ParseConstraint::<u8>::new();
+        assert!(constraint.validate("255").is_ok());
+        assert!(constraint.validate("256").is_err());
+        assert!(constraint.validate("abc").is_err());
+        assert_eq!(constraint.description(), "u8");
+    }
+
     #[test]
     fn test_route_constraints() {
         let constraints = RouteConstraints::new()
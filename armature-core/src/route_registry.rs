@@ -9,7 +9,7 @@
 //! that enables monomorphization and inlining of handler code.
 
 use crate::handler::{BoxedHandler, IntoHandler};
-use crate::{Error, HttpRequest, HttpResponse};
+use crate::{Error, Guard, GuardContext, HttpRequest, HttpResponse, MiddlewareChain};
 use std::any::TypeId;
 use std::future::Future;
 use std::pin::Pin;
@@ -122,6 +122,50 @@ impl RouteEntry {
     }
 }
 
+/// Wrap a route handler with guards and middleware
+///
+/// Used by `#[routes]` to apply controller-level and per-method guards and
+/// controller middleware. The middleware chain runs first, then each guard in
+/// order; a guard returning `false` rejects the request with 403 Forbidden and
+/// a guard error is returned as-is. The handler is returned unchanged when
+/// there is nothing to apply.
+pub fn guarded_handler(
+    handler: RouteHandlerFn,
+    guards: Vec<Box<dyn Guard>>,
+    middleware: MiddlewareChain,
+) -> RouteHandlerFn {
+    if guards.is_empty() && middleware.is_empty() {
+        return handler;
+    }
+
+    let guards = Arc::new(guards);
+    let guarded: RouteHandlerFn = Arc::new(move |req| {
+        let handler = handler.clone();
+        let guards = guards.clone();
+        Box::pin(async move {
+            if !guards.is_empty() {
+                let context = GuardContext::new(req.clone());
+                for guard in guards.iter() {
+                    if !guard.can_activate(&context).await? {
+                        return Err(Error::Forbidden("Access denied by guard".to_string()));
+                    }
+                }
+            }
+            handler(req).await
+        })
+    });
+
+    if middleware.is_empty() {
+        return guarded;
+    }
+
+    Arc::new(move |req| {
+        let middleware = middleware.clone();
+        let guarded = guarded.clone();
+        Box::pin(async move { middleware.apply(req, guarded).await })
+    })
+}
+
 /// Get all registered routes for a specific controller type
 pub fn get_routes_for_controller<C: 'static>() -> Vec<&'static RouteEntry> {
     let target_type_id = TypeId::of::<C>();
@@ -28,7 +28,7 @@
 //! ```
 
 use crate::headers::HeaderMap as ArmatureHeaderMap;
-use crate::http::{HttpRequest, HttpResponse};
+use crate::http::{HttpRequest, HttpResponse, Scheme};
 use bytes::Bytes;
 use http::{HeaderMap, HeaderName, HeaderValue, Request, Response, StatusCode};
 use http_body_util::Full;
@@ -85,6 +85,8 @@ impl FromHttpRequest for HttpRequest {
     fn from_http_request(req: Request<Bytes>) -> Self {
         let method = req.method().as_str().to_string();
         let path = req.uri().path().to_string();
+        // Only absolute-form URIs carry a scheme
+        let scheme = req.uri().scheme_str().and_then(Scheme::parse);
 
         // Parse query params
         let query_params: HashMap<String, String> = req
@@ -103,9 +105,13 @@ impl FromHttpRequest for HttpRequest {
 
         let body = req.into_body();
 
-        HttpRequest::with_bytes_body(method, path, body)
+        let mut request = HttpRequest::with_bytes_body(method, path, body)
             .with_headers_map(headers)
-            .with_query_params(query_params)
+            .with_query_params(query_params);
+        if let Some(scheme) = scheme {
+            request.insert_extension(scheme);
+        }
+        request
     }
 }
 
@@ -533,6 +539,19 @@ mod tests {
         let armature_req = <HttpRequest as FromHttpRequest>::from_http_request(http_req);
         assert_eq!(armature_req.method, "POST");
         assert_eq!(armature_req.path, "/api/users");
+        assert_eq!(armature_req.scheme(), None);
+    }
+
+    #[test]
+    fn test_http_request_scheme() {
+        let http_req = Request::builder()
+            .uri("https://example.com/api/users")
+            .body(Bytes::new())
+            .unwrap();
+
+        let armature_req = <HttpRequest as FromHttpRequest>::from_http_request(http_req);
+        assert_eq!(armature_req.scheme(), Some(Scheme::Https));
+        assert_eq!(armature_req.path, "/api/users");
     }
 
     #[test]
@@ -24,6 +24,15 @@ pub enum JwtError {
 
     #[error("Serialization error: {0}")]
     SerializationError(String),
+
+    #[error("Refresh token reuse detected; token family revoked")]
+    TokenReuseDetected,
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("JWKS error: {0}")]
+    JwksError(String),
 }
 
 pub type Result<T> = std::result::Result<T, JwtError>;
@@ -0,0 +1,294 @@
+// JWKS key fetching for verifying third-party tokens
+
+use crate::{JwtConfig, JwtError, Result};
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, TokenData, decode, decode_header};
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Client for a JSON Web Key Set published by an identity provider
+///
+/// Keys are cached for `ttl` (default: 1 hour). A token signed with a key id
+/// missing from the cache triggers a refetch, since the provider may have
+/// rotated keys, but at most once per `min_refresh_interval` (default: 30
+/// seconds) so tokens with made-up key ids can't flood the provider.
+pub struct JwksClient {
+    url: String,
+    http: reqwest::Client,
+    ttl: Duration,
+    min_refresh_interval: Duration,
+    cache: RwLock<Option<CachedJwks>>,
+}
+
+struct CachedJwks {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+impl JwksClient {
+    /// Create a client for the JWKS at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::Client::new(),
+            ttl: Duration::from_secs(3600),
+            min_refresh_interval: Duration::from_secs(30),
+            cache: RwLock::new(None),
+        }
+    }
+
+    /// Set how long fetched keys are trusted before refetching
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the minimum time between refetches for unknown key ids
+    pub fn with_min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    /// Seed the cache with known keys, as if just fetched
+    pub fn with_keys(self, keys: JwkSet) -> Self {
+        self.store(keys);
+        self
+    }
+
+    /// Get the JWKS URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Fetch the key set, replacing the cache
+    pub async fn refresh(&self) -> Result<()> {
+        let keys = self
+            .http
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| JwtError::JwksError(e.to_string()))?
+            .json::<JwkSet>()
+            .await
+            .map_err(|e| JwtError::JwksError(e.to_string()))?;
+
+        self.store(keys);
+        Ok(())
+    }
+
+    /// Get a key by id, fetching the key set if needed
+    pub async fn key(&self, kid: &str) -> Result<Jwk> {
+        if let Some(key) = self.cached_key(kid) {
+            return Ok(key);
+        }
+
+        if self.should_refresh() {
+            self.refresh().await?;
+        }
+
+        self.cached_key(kid)
+            .ok_or_else(|| JwtError::InvalidToken(format!("Unknown key id: {}", kid)))
+    }
+
+    /// Get a key by id from the cache, if it has not expired
+    pub fn cached_key(&self, kid: &str) -> Option<Jwk> {
+        let cache = self.cache.read().ok()?;
+        cache
+            .as_ref()
+            .filter(|cached| cached.fetched_at.elapsed() < self.ttl)
+            .and_then(|cached| cached.keys.find(kid).cloned())
+    }
+
+    /// Verify a token against the cached keys only
+    pub fn verify_cached<T: DeserializeOwned>(&self, token: &str, config: &JwtConfig) -> Result<T> {
+        let kid = key_id(token)?;
+        let key = self
+            .cached_key(&kid)
+            .ok_or_else(|| JwtError::InvalidToken(format!("Unknown key id: {}", kid)))?;
+        verify_with_jwk(token, &key, config)
+    }
+
+    /// Verify a token, fetching keys as needed
+    pub async fn verify<T: DeserializeOwned>(&self, token: &str, config: &JwtConfig) -> Result<T> {
+        let kid = key_id(token)?;
+        let key = self.key(&kid).await?;
+        verify_with_jwk(token, &key, config)
+    }
+
+    fn store(&self, keys: JwkSet) {
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        *cache = Some(CachedJwks {
+            keys,
+            fetched_at: Instant::now(),
+        });
+    }
+
+    /// Whether a cache miss may refetch, given the last fetch time
+    fn should_refresh(&self) -> bool {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        cache.as_ref().is_none_or(|cached| {
+            cached.fetched_at.elapsed() >= self.min_refresh_interval.min(self.ttl)
+        })
+    }
+}
+
+/// Read the `kid` header of a token
+fn key_id(token: &str) -> Result<String> {
+    decode_header(token)
+        .map_err(JwtError::from)?
+        .kid
+        .ok_or_else(|| JwtError::InvalidToken("Token has no key id".to_string()))
+}
+
+/// Verify a token with a public JWK
+///
+/// The algorithm comes from the key when it declares one, and must match the
+/// token header. Issuer, audience and expiry checks come from `config`.
+fn verify_with_jwk<T: DeserializeOwned>(token: &str, key: &Jwk, config: &JwtConfig) -> Result<T> {
+    if let AlgorithmParameters::OctetKey(_) = key.algorithm {
+        return Err(JwtError::InvalidToken(
+            "Symmetric keys are not accepted from a JWKS".to_string(),
+        ));
+    }
+
+    let header_alg = decode_header(token).map_err(JwtError::from)?.alg;
+    let algorithm = match key.common.key_algorithm {
+        Some(key_alg) => Algorithm::from_str(&key_alg.to_string()).map_err(|_| {
+            JwtError::InvalidToken(format!("Unsupported key algorithm: {}", key_alg))
+        })?,
+        None => header_alg,
+    };
+    if algorithm != header_alg {
+        return Err(JwtError::InvalidToken(format!(
+            "Token algorithm {:?} does not match key algorithm {:?}",
+            header_alg, algorithm
+        )));
+    }
+
+    let decoding_key = DecodingKey::from_jwk(key).map_err(JwtError::from)?;
+    let mut validation = config.validation();
+    validation.algorithms = vec![algorithm];
+
+    let token_data: TokenData<T> =
+        decode(token, &decoding_key, &validation).map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::ExpiredSignature => JwtError::TokenExpired,
+            jsonwebtoken::errors::ErrorKind::InvalidSignature => JwtError::InvalidSignature,
+            _ => JwtError::EncodingError(e),
+        })?;
+
+    Ok(token_data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StandardClaims;
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    const JWKS: &str = include_str!("../tests/fixtures/jwks.json");
+    const JWKS_ROTATED: &str = include_str!("../tests/fixtures/jwks-rotated.json");
+    const KEY_1: &str = include_str!("../tests/fixtures/rsa-key-1.pem");
+    const KEY_2: &str = include_str!("../tests/fixtures/rsa-key-2.pem");
+
+    fn sign(kid: &str, pem: &str) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(kid.to_string());
+        let claims = StandardClaims::new()
+            .with_subject("user123".to_string())
+            .with_expiration(3600);
+        encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// Serve `body` as the JWKS for each of `requests` connections
+    fn serve_jwks(body: &'static str, requests: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/.well-known/jwks.json",
+            listener.local_addr().unwrap()
+        );
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_verify_with_static_jwks() {
+        let keys: JwkSet = serde_json::from_str(JWKS).unwrap();
+        let client = JwksClient::new("https://idp.example.com/jwks").with_keys(keys);
+        let config = JwtConfig::default();
+
+        let claims: StandardClaims = client
+            .verify_cached(&sign("key-1", KEY_1), &config)
+            .unwrap();
+        assert_eq!(claims.sub, Some("user123".to_string()));
+
+        // Right kid, wrong key
+        let result: Result<StandardClaims> = client.verify_cached(&sign("key-1", KEY_2), &config);
+        assert!(matches!(result, Err(JwtError::InvalidSignature)));
+
+        let result: Result<StandardClaims> = client.verify_cached(&sign("key-2", KEY_2), &config);
+        assert!(matches!(result, Err(JwtError::InvalidToken(_))));
+    }
+
+    #[test]
+    fn test_jwks_manager() {
+        let keys: JwkSet = serde_json::from_str(JWKS).unwrap();
+        let client = JwksClient::new("https://idp.example.com/jwks").with_keys(keys);
+        let manager = crate::JwtManager::from_jwks(client, JwtConfig::default());
+
+        let claims: StandardClaims = manager.verify(&sign("key-1", KEY_1)).unwrap();
+        assert_eq!(claims.sub, Some("user123".to_string()));
+
+        // Verify only: there is no local key to sign with
+        let result = manager.sign(&claims);
+        assert!(matches!(result, Err(JwtError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_refetch_on_unknown_kid() {
+        let url = serve_jwks(JWKS_ROTATED, 1);
+        let keys: JwkSet = serde_json::from_str(JWKS).unwrap();
+        let client = JwksClient::new(url)
+            .with_keys(keys)
+            .with_min_refresh_interval(Duration::ZERO);
+        let config = JwtConfig::default();
+
+        // key-2 was rotated in after the cache was filled
+        let claims: StandardClaims =
+            tokio_test::block_on(client.verify(&sign("key-2", KEY_2), &config)).unwrap();
+        assert_eq!(claims.sub, Some("user123".to_string()));
+        assert!(client.cached_key("key-2").is_some());
+    }
+
+    #[test]
+    fn test_unknown_kid_refetch_is_rate_limited() {
+        let keys: JwkSet = serde_json::from_str(JWKS).unwrap();
+        // Unreachable URL: a refetch would fail with a JWKS error
+        let client = JwksClient::new("http://127.0.0.1:1/jwks").with_keys(keys);
+
+        let result: Result<StandardClaims> =
+            tokio_test::block_on(client.verify(&sign("key-2", KEY_2), &JwtConfig::default()));
+        assert!(matches!(result, Err(JwtError::InvalidToken(_))));
+    }
+}
@@ -63,24 +63,47 @@
 pub mod claims;
 pub mod config;
 pub mod error;
+#[cfg(feature = "jwks")]
+pub mod jwks;
+pub mod refresh;
 pub mod service;
 pub mod token;
 
 pub use claims::{Claims, StandardClaims};
 pub use config::JwtConfig;
 pub use error::{JwtError, Result};
+#[cfg(feature = "jwks")]
+pub use jwks::JwksClient;
+pub use refresh::{
+    InMemoryRefreshTokenRegistry, RefreshClaims, RefreshTokenRegistry, RefreshTokenStatus,
+};
 pub use service::JwtService;
 pub use token::{Token, TokenPair};
 
 // Re-export jsonwebtoken types
 pub use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 
-use armature_log::{debug, info, trace};
+use armature_log::{debug, info, trace, warn};
+use std::sync::Arc;
 
 /// JWT service for token management
 #[derive(Clone)]
 pub struct JwtManager {
-    service: JwtService,
+    keys: Keys,
+    refresh_registry: Option<Arc<dyn RefreshTokenRegistry>>,
+}
+
+/// Where a manager's keys come from
+#[derive(Clone)]
+enum Keys {
+    /// Secret or key pair from the config
+    Local(Box<JwtService>),
+    /// Public keys published by a third party (verify only)
+    #[cfg(feature = "jwks")]
+    Jwks {
+        config: JwtConfig,
+        client: Arc<JwksClient>,
+    },
 }
 
 impl JwtManager {
@@ -101,7 +124,72 @@ impl JwtManager {
         info!("Initializing JWT manager");
         debug!("JWT algorithm: {:?}", config.algorithm);
         let service = JwtService::new(config)?;
-        Ok(Self { service })
+        Ok(Self {
+            keys: Keys::Local(Box::new(service)),
+            refresh_registry: None,
+        })
+    }
+
+    /// Create a manager verifying tokens against a JWKS URL
+    ///
+    /// For tokens signed by an external identity provider. Keys are selected
+    /// by the token's `kid` header and fetched on first use; see
+    /// [`JwksClient`] for caching. The manager can only verify: signing
+    /// fails with [`JwtError::ConfigError`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use armature_jwt::{JwtManager, StandardClaims};
+    ///
+    /// # async fn example(token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// let manager = JwtManager::from_jwks_url("https://idp.example.com/.well-known/jwks.json");
+    /// let claims: StandardClaims = manager.verify_async(token).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "jwks")]
+    pub fn from_jwks_url(url: impl Into<String>) -> Self {
+        Self::from_jwks(JwksClient::new(url), JwtConfig::default())
+    }
+
+    /// Create a manager verifying tokens with a configured JWKS client
+    ///
+    /// The config's issuer, audience, expiry and leeway settings are
+    /// enforced; its keys and algorithm are ignored.
+    #[cfg(feature = "jwks")]
+    pub fn from_jwks(client: JwksClient, config: JwtConfig) -> Self {
+        info!("Initializing JWT manager for JWKS at {}", client.url());
+        Self {
+            keys: Keys::Jwks {
+                config,
+                client: Arc::new(client),
+            },
+            refresh_registry: None,
+        }
+    }
+
+    /// Rotate refresh tokens through a registry
+    ///
+    /// Refresh tokens become single-use [`RefreshClaims`] tokens. Presenting
+    /// one that was already used revokes every token rotated from the same
+    /// login and fails with [`JwtError::TokenReuseDetected`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use armature_jwt::{InMemoryRefreshTokenRegistry, JwtConfig, JwtManager};
+    /// use std::sync::Arc;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let manager = JwtManager::new(JwtConfig::new("secret".to_string()))?
+    ///     .with_refresh_registry(Arc::new(InMemoryRefreshTokenRegistry::new()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_refresh_registry(mut self, registry: Arc<dyn RefreshTokenRegistry>) -> Self {
+        self.refresh_registry = Some(registry);
+        self
     }
 
     /// Sign a token with claims
@@ -121,11 +209,14 @@ impl JwtManager {
     /// ```
     pub fn sign<T: serde::Serialize>(&self, claims: &T) -> Result<String> {
         trace!("Signing JWT token");
-        self.service.sign(claims)
+        self.service()?.sign(claims)
     }
 
     /// Verify and decode a token
     ///
+    /// JWKS managers only use keys that are already cached; use
+    /// [`verify_async`](Self::verify_async) to fetch them.
+    ///
     /// # Example
     ///
     /// ```
@@ -144,7 +235,12 @@ impl JwtManager {
     /// ```
     pub fn verify<T: serde::de::DeserializeOwned>(&self, token: &str) -> Result<T> {
         trace!("Verifying JWT token");
-        match self.service.verify(token) {
+        let result = match &self.keys {
+            Keys::Local(service) => service.verify(token),
+            #[cfg(feature = "jwks")]
+            Keys::Jwks { config, client } => client.verify_cached(token, config),
+        };
+        match result {
             Ok(claims) => {
                 trace!("JWT token verified successfully");
                 Ok(claims)
@@ -156,32 +252,127 @@ impl JwtManager {
         }
     }
 
+    /// Verify and decode a token, fetching JWKS keys as needed
+    ///
+    /// For managers with local keys, this is the same as [`verify`](Self::verify).
+    #[cfg(feature = "jwks")]
+    pub async fn verify_async<T: serde::de::DeserializeOwned>(&self, token: &str) -> Result<T> {
+        match &self.keys {
+            Keys::Local(_) => self.verify(token),
+            Keys::Jwks { config, client } => {
+                trace!("Verifying JWT token against JWKS");
+                client.verify(token, config).await.inspect_err(|e| {
+                    debug!("JWT verification failed: {}", e);
+                })
+            }
+        }
+    }
+
     /// Generate a token pair (access + refresh)
     pub fn generate_token_pair<T: serde::Serialize + Clone>(
         &self,
         claims: &T,
     ) -> Result<TokenPair> {
         debug!("Generating JWT token pair");
-        self.service.generate_token_pair(claims)
+        match &self.refresh_registry {
+            Some(registry) => {
+                let family = uuid::Uuid::new_v4().to_string();
+                self.generate_rotating_pair(registry.as_ref(), claims, family)
+            }
+            None => self.service()?.generate_token_pair(claims),
+        }
     }
 
     /// Refresh an access token using a refresh token
-    #[allow(dead_code)]
+    ///
+    /// With a refresh registry, the presented token is consumed and the new
+    /// pair continues its family. Reusing a consumed token revokes the family
+    /// and fails with [`JwtError::TokenReuseDetected`].
     pub fn refresh_token<T: serde::de::DeserializeOwned + serde::Serialize + Clone>(
         &self,
         refresh_token: &str,
     ) -> Result<TokenPair> {
-        self.service.refresh_token::<T>(refresh_token)
+        let Some(registry) = &self.refresh_registry else {
+            return self.service()?.refresh_token::<T>(refresh_token);
+        };
+
+        let refresh: RefreshClaims<T> = self.verify(refresh_token)?;
+        match registry.consume(&refresh.jti)? {
+            RefreshTokenStatus::Active => {
+                self.generate_rotating_pair(registry.as_ref(), &refresh.claims, refresh.family)
+            }
+            RefreshTokenStatus::Consumed => {
+                warn!("Refresh token reuse detected, revoking token family");
+                registry.revoke_family(&refresh.family)?;
+                Err(JwtError::TokenReuseDetected)
+            }
+            RefreshTokenStatus::Revoked | RefreshTokenStatus::Unknown => Err(
+                JwtError::InvalidToken("Refresh token has been revoked".to_string()),
+            ),
+        }
+    }
+
+    /// Sign an access token and a registered refresh token in `family`
+    fn generate_rotating_pair<T: serde::Serialize + Clone>(
+        &self,
+        registry: &dyn RefreshTokenRegistry,
+        claims: &T,
+        family: String,
+    ) -> Result<TokenPair> {
+        let service = self.service()?;
+        let config = service.config();
+        let now = chrono::Utc::now();
+        let expires_at = now
+            + chrono::Duration::from_std(config.refresh_expires_in)
+                .map_err(|e| JwtError::ConfigError(e.to_string()))?;
+
+        let refresh = RefreshClaims {
+            jti: uuid::Uuid::new_v4().to_string(),
+            family,
+            exp: expires_at.timestamp(),
+            iat: now.timestamp(),
+            iss: config.issuer.clone(),
+            aud: config.audience.clone(),
+            claims: claims.clone(),
+        };
+
+        let access_token = service.sign(claims)?;
+        let refresh_token = service.sign(&refresh)?;
+        registry.issue(&refresh.jti, &refresh.family, expires_at)?;
+
+        Ok(TokenPair::new(
+            access_token,
+            refresh_token,
+            config.expires_in.as_secs() as i64,
+            config.refresh_expires_in.as_secs() as i64,
+        ))
     }
 
     /// Decode a token without verification (useful for inspecting expired tokens)
     pub fn decode_unverified<T: serde::de::DeserializeOwned>(&self, token: &str) -> Result<T> {
-        self.service.decode_unverified(token)
+        let token_data: jsonwebtoken::TokenData<T> =
+            jsonwebtoken::dangerous::insecure_decode(token)?;
+        Ok(token_data.claims)
     }
 
     /// Get the configuration
     pub fn config(&self) -> &JwtConfig {
-        self.service.config()
+        match &self.keys {
+            Keys::Local(service) => service.config(),
+            #[cfg(feature = "jwks")]
+            Keys::Jwks { config, .. } => config,
+        }
+    }
+
+    /// Local signing service, unavailable for JWKS managers
+    fn service(&self) -> Result<&JwtService> {
+        match &self.keys {
+            Keys::Local(service) => Ok(service),
+            #[cfg(feature = "jwks")]
+            Keys::Jwks { .. } => Err(JwtError::ConfigError(
+                "JWKS managers can only verify tokens".to_string(),
+            )),
+        }
     }
 }
 
@@ -201,6 +392,9 @@ pub mod prelude {
     pub use crate::claims::{Claims, StandardClaims};
     pub use crate::config::JwtConfig;
     pub use crate::error::{JwtError, Result};
+    #[cfg(feature = "jwks")]
+    pub use crate::jwks::JwksClient;
+    pub use crate::refresh::{InMemoryRefreshTokenRegistry, RefreshTokenRegistry};
     pub use crate::service::JwtService;
     pub use crate::token::{Token, TokenPair};
     pub use jsonwebtoken::Algorithm;
@@ -211,7 +405,7 @@ mod tests {
     use super::*;
     use serde::{Deserialize, Serialize};
 
-    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
     struct TestClaims {
         sub: String,
         name: String,
@@ -246,4 +440,66 @@ mod tests {
         let result: Result<TestClaims> = manager.verify("invalid.token.here");
         assert!(result.is_err());
     }
+
+    fn rotating_manager() -> JwtManager {
+        JwtManager::new(JwtConfig::new("test-secret".to_string()))
+            .unwrap()
+            .with_refresh_registry(Arc::new(InMemoryRefreshTokenRegistry::new()))
+    }
+
+    fn test_claims() -> TestClaims {
+        TestClaims {
+            sub: "123".to_string(),
+            name: "John Doe".to_string(),
+            exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp(),
+        }
+    }
+
+    #[test]
+    fn test_refresh_token_rotation() {
+        let manager = rotating_manager();
+
+        let first = manager.generate_token_pair(&test_claims()).unwrap();
+        let second = manager
+            .refresh_token::<TestClaims>(&first.refresh_token)
+            .unwrap();
+        let third = manager
+            .refresh_token::<TestClaims>(&second.refresh_token)
+            .unwrap();
+
+        let access: TestClaims = manager.verify(&third.access_token).unwrap();
+        assert_eq!(access.sub, "123");
+
+        let first: RefreshClaims<TestClaims> = manager.verify(&first.refresh_token).unwrap();
+        let third: RefreshClaims<TestClaims> = manager.verify(&third.refresh_token).unwrap();
+        assert_eq!(first.family, third.family);
+        assert_ne!(first.jti, third.jti);
+    }
+
+    #[test]
+    fn test_refresh_token_reuse_revokes_family() {
+        let manager = rotating_manager();
+        let other = manager.generate_token_pair(&test_claims()).unwrap();
+
+        // An attacker copies the refresh token, then the user rotates it
+        let stolen = manager.generate_token_pair(&test_claims()).unwrap();
+        let user = manager
+            .refresh_token::<TestClaims>(&stolen.refresh_token)
+            .unwrap();
+
+        // Replaying the stolen token is detected...
+        let result = manager.refresh_token::<TestClaims>(&stolen.refresh_token);
+        assert!(matches!(result, Err(JwtError::TokenReuseDetected)));
+
+        // ...and the legitimate session's current token is revoked with it
+        let result = manager.refresh_token::<TestClaims>(&user.refresh_token);
+        assert!(matches!(result, Err(JwtError::InvalidToken(_))));
+
+        // Other sessions are unaffected
+        assert!(
+            manager
+                .refresh_token::<TestClaims>(&other.refresh_token)
+                .is_ok()
+        );
+    }
 }
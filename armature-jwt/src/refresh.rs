@@ -0,0 +1,154 @@
+// Refresh token rotation with reuse detection
+
+use crate::{JwtError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Claims carried by a rotating refresh token
+///
+/// Issued by [`JwtManager`](crate::JwtManager) when it has a
+/// [`RefreshTokenRegistry`]. The application claims are nested under
+/// `claims` so they can't collide with the rotation fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RefreshClaims<T> {
+    /// Unique token ID
+    pub jti: String,
+
+    /// Token family: every token rotated from the same login
+    #[serde(rename = "fam")]
+    pub family: String,
+
+    /// Expiration time (Unix timestamp)
+    pub exp: i64,
+
+    /// Issued at (Unix timestamp)
+    pub iat: i64,
+
+    /// Issuer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+
+    /// Audience
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Vec<String>>,
+
+    /// Application claims, copied into the next token pair
+    pub claims: T,
+}
+
+/// State of a refresh token in a [`RefreshTokenRegistry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshTokenStatus {
+    /// Issued and not yet used
+    Active,
+    /// Already exchanged for a new token pair
+    Consumed,
+    /// Revoked along with its family
+    Revoked,
+    /// Never issued, or expired and forgotten
+    Unknown,
+}
+
+/// Registry of issued refresh tokens (implement with your database)
+///
+/// Refresh tokens are single-use: presenting one marks it consumed. A consumed
+/// token presented again means it was copied, so its whole family is revoked.
+pub trait RefreshTokenRegistry: Send + Sync {
+    /// Record a newly issued refresh token
+    fn issue(&self, jti: &str, family: &str, expires_at: DateTime<Utc>) -> Result<()>;
+
+    /// Atomically mark a token consumed, returning its status beforehand
+    fn consume(&self, jti: &str) -> Result<RefreshTokenStatus>;
+
+    /// Revoke every token in a family
+    fn revoke_family(&self, family: &str) -> Result<()>;
+}
+
+/// In-memory refresh token registry
+///
+/// Suitable for a single instance. Expired tokens are purged on each issue.
+#[derive(Debug, Default)]
+pub struct InMemoryRefreshTokenRegistry {
+    tokens: Mutex<HashMap<String, IssuedToken>>,
+}
+
+#[derive(Debug)]
+struct IssuedToken {
+    family: String,
+    expires_at: DateTime<Utc>,
+    status: RefreshTokenStatus,
+}
+
+impl InMemoryRefreshTokenRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RefreshTokenRegistry for InMemoryRefreshTokenRegistry {
+    fn issue(&self, jti: &str, family: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        let mut tokens = self.tokens.lock().map_err(poisoned)?;
+        let now = Utc::now();
+        tokens.retain(|_, token| token.expires_at > now);
+        tokens.insert(
+            jti.to_string(),
+            IssuedToken {
+                family: family.to_string(),
+                expires_at,
+                status: RefreshTokenStatus::Active,
+            },
+        );
+        Ok(())
+    }
+
+    fn consume(&self, jti: &str) -> Result<RefreshTokenStatus> {
+        let mut tokens = self.tokens.lock().map_err(poisoned)?;
+        let Some(token) = tokens.get_mut(jti) else {
+            return Ok(RefreshTokenStatus::Unknown);
+        };
+
+        let status = token.status;
+        if status == RefreshTokenStatus::Active {
+            token.status = RefreshTokenStatus::Consumed;
+        }
+        Ok(status)
+    }
+
+    fn revoke_family(&self, family: &str) -> Result<()> {
+        let mut tokens = self.tokens.lock().map_err(poisoned)?;
+        for token in tokens.values_mut().filter(|token| token.family == family) {
+            token.status = RefreshTokenStatus::Revoked;
+        }
+        Ok(())
+    }
+}
+
+fn poisoned<T>(_: std::sync::PoisonError<T>) -> JwtError {
+    JwtError::Storage("refresh token registry lock poisoned".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_registry() {
+        let registry = InMemoryRefreshTokenRegistry::new();
+        let expires_at = Utc::now() + chrono::Duration::hours(1);
+
+        registry.issue("a", "family", expires_at).unwrap();
+        registry.issue("b", "family", expires_at).unwrap();
+        registry.issue("c", "other", expires_at).unwrap();
+
+        assert_eq!(registry.consume("a").unwrap(), RefreshTokenStatus::Active);
+        assert_eq!(registry.consume("a").unwrap(), RefreshTokenStatus::Consumed);
+        assert_eq!(registry.consume("x").unwrap(), RefreshTokenStatus::Unknown);
+
+        registry.revoke_family("family").unwrap();
+        assert_eq!(registry.consume("b").unwrap(), RefreshTokenStatus::Revoked);
+        assert_eq!(registry.consume("c").unwrap(), RefreshTokenStatus::Active);
+    }
+}
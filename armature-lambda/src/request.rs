@@ -1,5 +1,6 @@
 //! Lambda request conversion.
 
+use armature_core::Scheme;
 use bytes::Bytes;
 use lambda_http::Request;
 use std::collections::HashMap;
@@ -10,6 +11,8 @@ pub struct LambdaRequest {
     pub method: http::Method,
     /// Request path.
     pub path: String,
+    /// URI scheme, if the request URI carried one.
+    pub scheme: Option<Scheme>,
     /// Query string.
     pub query_string: Option<String>,
     /// Headers.
@@ -111,6 +114,7 @@ impl LambdaRequest {
         Self {
             method: parts.method,
             path: parts.uri.path().to_string(),
+            scheme: parts.uri.scheme_str().and_then(Scheme::parse),
             query_string,
             headers,
             body: body_bytes,
@@ -0,0 +1,442 @@
+//! Rotating file output.
+//!
+//! A [`FileSink`] appends formatted log lines to a file through a buffered
+//! writer. A background thread flushes the buffer periodically so that each
+//! log line does not cost a syscall. When the configured [`Rotation`] condition
+//! is met, the current file is renamed with a timestamp suffix and a fresh file
+//! is opened; only the newest `max_files` rotated files are kept.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Default interval between background flushes.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default number of rotated files to keep.
+pub const DEFAULT_MAX_FILES: usize = 7;
+
+/// How long to wait before retrying a rotation that failed.
+const ROTATE_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// When to rotate a log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// Never rotate
+    #[default]
+    Never,
+    /// Rotate when the local date changes
+    Daily,
+    /// Rotate before a write would grow the file beyond this many bytes
+    SizeBytes(u64),
+}
+
+/// File output with buffering and rotation.
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+    rotation: Rotation,
+    max_files: usize,
+    state: Mutex<FileState>,
+    /// Timestamp suffix and sequence number of the last rotated file
+    last_rotated: Mutex<(String, u32)>,
+}
+
+#[derive(Debug)]
+struct FileState {
+    writer: BufWriter<File>,
+    size: u64,
+    opened_on: chrono::NaiveDate,
+    /// Set after a failed rotation; no rotation is attempted before then
+    rotate_retry_at: Option<Instant>,
+}
+
+impl FileSink {
+    /// Open (or create) a log file for appending.
+    ///
+    /// The returned sink is flushed every `flush_interval` by a background
+    /// thread, which exits once the sink is dropped.
+    pub fn open(
+        path: impl Into<PathBuf>,
+        rotation: Rotation,
+        max_files: usize,
+        flush_interval: Duration,
+    ) -> io::Result<Arc<Self>> {
+        let path = path.into();
+        let state = FileState::open(&path)?;
+
+        let sink = Arc::new(Self {
+            path,
+            rotation,
+            max_files,
+            state: Mutex::new(state),
+            last_rotated: Mutex::new((String::new(), 0)),
+        });
+
+        spawn_flusher(Arc::downgrade(&sink), flush_interval);
+        Ok(sink)
+    }
+
+    /// Get the path of the active log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the rotation policy.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Append a line to the file, rotating first if needed.
+    pub fn write_line(&self, line: &str) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let len = line.len() as u64 + 1;
+        let now = Instant::now();
+        if self.should_rotate(&state, len)
+            && state.rotate_retry_at.is_none_or(|at| now >= at)
+            && let Err(e) = self.rotate(&mut state)
+        {
+            // Report once per failure streak and keep appending to the
+            // current file until the retry interval has passed
+            if state.rotate_retry_at.is_none() {
+                eprintln!(
+                    "armature-log: failed to rotate {}: {}",
+                    self.path.display(),
+                    e
+                );
+            }
+            state.rotate_retry_at = Some(now + ROTATE_RETRY_INTERVAL);
+        }
+
+        if writeln!(state.writer, "{}", line).is_ok() {
+            state.size += len;
+        }
+    }
+
+    /// Flush buffered lines to disk.
+    pub fn flush(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            let _ = state.writer.flush();
+        }
+    }
+
+    fn should_rotate(&self, state: &FileState, incoming: u64) -> bool {
+        match self.rotation {
+            Rotation::Never => false,
+            Rotation::Daily => chrono::Local::now().date_naive() != state.opened_on,
+            Rotation::SizeBytes(max) => state.size > 0 && state.size + incoming > max,
+        }
+    }
+
+    fn rotate(&self, state: &mut FileState) -> io::Result<()> {
+        state.writer.flush()?;
+
+        let suffix = chrono::Local::now().format("%Y%m%dT%H%M%S%.3f").to_string();
+        let mut last = self.last_rotated.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Rotations within the same millisecond get an increasing, zero-padded
+        // sequence number. Names are never reused, even after pruning, so that
+        // they keep sorting in the order the files were rotated.
+        let mut seq = if last.0 == suffix { last.1 + 1 } else { 0 };
+        let rotated = loop {
+            let name = if seq == 0 {
+                format!(".{}", suffix)
+            } else {
+                format!(".{}-{:04}", suffix, seq)
+            };
+            let rotated = append_to_file_name(&self.path, &name);
+            if !rotated.exists() {
+                break rotated;
+            }
+            seq += 1;
+        };
+        *last = (suffix, seq);
+        drop(last);
+
+        fs::rename(&self.path, &rotated)?;
+        *state = FileState::open(&self.path)?;
+        self.prune()
+    }
+
+    /// Remove the oldest rotated files beyond `max_files`.
+    fn prune(&self) -> io::Result<()> {
+        let Some(file_name) = self.path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(());
+        };
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let mut rotated: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix(file_name))
+                    .and_then(|n| n.strip_prefix('.'))
+                    .is_some_and(is_rotation_suffix)
+            })
+            .collect();
+
+        if rotated.len() <= self.max_files {
+            return Ok(());
+        }
+
+        // Timestamp suffixes sort chronologically
+        rotated.sort();
+        let excess = rotated.len() - self.max_files;
+        for path in rotated.into_iter().take(excess) {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl FileState {
+    fn open(path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+
+        // A reopened file belongs to the day it was last written, so that
+        // daily rotation still happens after a restart on a later day
+        let opened_on = metadata
+            .modified()
+            .map(|mtime| chrono::DateTime::<chrono::Local>::from(mtime).date_naive())
+            .unwrap_or_else(|_| chrono::Local::now().date_naive());
+
+        Ok(Self {
+            writer: BufWriter::new(file),
+            size: metadata.len(),
+            opened_on,
+            rotate_retry_at: None,
+        })
+    }
+}
+
+/// Check whether `suffix` is one this sink gives rotated files:
+/// `YYYYmmddTHHMMSS.fff`, optionally followed by `-` and a sequence number.
+fn is_rotation_suffix(suffix: &str) -> bool {
+    let (timestamp, seq) = match suffix.split_once('-') {
+        Some((timestamp, seq)) => (timestamp, Some(seq)),
+        None => (suffix, None),
+    };
+
+    let timestamp_ok = timestamp.len() == 19
+        && timestamp.bytes().enumerate().all(|(i, b)| match i {
+            8 => b == b'T',
+            15 => b == b'.',
+            _ => b.is_ascii_digit(),
+        });
+    let seq_ok = seq.is_none_or(|seq| seq.len() >= 4 && seq.bytes().all(|b| b.is_ascii_digit()));
+
+    timestamp_ok && seq_ok
+}
+
+fn append_to_file_name(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn spawn_flusher(sink: Weak<FileSink>, interval: Duration) {
+    let _ = std::thread::Builder::new()
+        .name("armature-log-flush".to_string())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(interval);
+                match sink.upgrade() {
+                    Some(sink) => sink.flush(),
+                    None => break,
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "armature-log-{}-{}-{}",
+            name,
+            std::process::id(),
+            nanos
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rotated_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.file_name().unwrap().to_str().unwrap() != "app.log")
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_write_and_flush() {
+        let dir = temp_dir("write");
+        let path = dir.join("app.log");
+        let sink = FileSink::open(&path, Rotation::Never, 3, Duration::from_secs(60)).unwrap();
+
+        sink.write_line("first");
+        sink.write_line("second");
+        sink.flush();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_size_rotation_keeps_max_files() {
+        let dir = temp_dir("size");
+        let path = dir.join("app.log");
+        let sink =
+            FileSink::open(&path, Rotation::SizeBytes(10), 2, Duration::from_secs(60)).unwrap();
+
+        for i in 0..5 {
+            sink.write_line(&format!("line-{}", i));
+        }
+        sink.flush();
+
+        // Each 7-byte line fills the 10-byte budget, so every write rotates
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line-4\n");
+
+        let rotated = rotated_files(&dir);
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(fs::read_to_string(&rotated[1]).unwrap(), "line-3\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_daily_rotation_uses_mtime_on_reopen() {
+        let dir = temp_dir("daily");
+        let path = dir.join("app.log");
+        fs::write(&path, "yesterday\n").unwrap();
+
+        let two_days_ago = std::time::SystemTime::now() - Duration::from_secs(2 * 86_400);
+        File::options()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(two_days_ago)
+            .unwrap();
+
+        let sink = FileSink::open(&path, Rotation::Daily, 3, Duration::from_secs(60)).unwrap();
+        sink.write_line("today");
+        sink.flush();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "today\n");
+        let rotated = rotated_files(&dir);
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(fs::read_to_string(&rotated[0]).unwrap(), "yesterday\n");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_prune_ignores_unrelated_files() {
+        let dir = temp_dir("prune");
+        let path = dir.join("app.log");
+        for name in [
+            "app.log.bak",
+            "app.log.gz",
+            "app.log.20240101T000000.000.gz",
+        ] {
+            fs::write(dir.join(name), "keep\n").unwrap();
+        }
+
+        let sink =
+            FileSink::open(&path, Rotation::SizeBytes(10), 1, Duration::from_secs(60)).unwrap();
+        for i in 0..4 {
+            sink.write_line(&format!("line-{}", i));
+        }
+        sink.flush();
+
+        for name in [
+            "app.log.bak",
+            "app.log.gz",
+            "app.log.20240101T000000.000.gz",
+        ] {
+            assert!(dir.join(name).exists(), "{} was pruned", name);
+        }
+        // The three unrelated files plus the one kept rotation
+        assert_eq!(rotated_files(&dir).len(), 4);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_failed_rotation_backs_off() {
+        let dir = temp_dir("backoff");
+        let path = dir.join("app.log");
+        let sink =
+            FileSink::open(&path, Rotation::SizeBytes(10), 2, Duration::from_secs(60)).unwrap();
+
+        sink.write_line("line-0");
+        // Renaming a file that no longer exists fails
+        fs::remove_file(&path).unwrap();
+
+        sink.write_line("line-1");
+        let retry_at = sink.state.lock().unwrap().rotate_retry_at;
+        assert!(retry_at.is_some());
+
+        // Further writes don't retry until the interval has passed
+        sink.write_line("line-2");
+        assert_eq!(sink.state.lock().unwrap().rotate_retry_at, retry_at);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_rotation_suffix() {
+        assert!(is_rotation_suffix("20240101T120000.123"));
+        assert!(is_rotation_suffix("20240101T120000.123-0001"));
+        assert!(is_rotation_suffix("20240101T120000.123-12345"));
+
+        assert!(!is_rotation_suffix("bak"));
+        assert!(!is_rotation_suffix("gz"));
+        assert!(!is_rotation_suffix("20240101T120000.123.gz"));
+        assert!(!is_rotation_suffix("20240101T120000.123-1"));
+        assert!(!is_rotation_suffix("20240101-120000.123"));
+    }
+
+    #[test]
+    fn test_background_flush() {
+        let dir = temp_dir("timer");
+        let path = dir.join("app.log");
+        let sink = FileSink::open(&path, Rotation::Never, 1, Duration::from_millis(10)).unwrap();
+
+        sink.write_line("buffered");
+        std::thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "buffered\n");
+        drop(sink);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
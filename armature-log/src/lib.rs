@@ -10,6 +10,11 @@
 //! - **Environment-controlled**: Configure via environment variables
 //! - **Zero-cost when disabled**: Debug macros compile to no-ops in release
 //! - **Runtime configurable**: Change format/level at runtime
+//! - **File output**: Buffered, rotating log files
+//! - **Redaction**: Mask sensitive fields and message patterns
+//! - **Sampling**: Rate-limit noisy targets
+//! - **Multiple sinks**: Independent format and level per destination
+//! - **OpenTelemetry**: OTLP log export (`otel` feature)
 //!
 //! # Quick Start
 //!
@@ -56,6 +61,52 @@
 //! armature_log::preset_production();   // JSON + Info
 //! ```
 //!
+//! ## File Output
+//!
+//! ```rust,no_run
+//! use armature_log::{configure, Rotation};
+//!
+//! configure()
+//!     .file("/var/log/app.log")
+//!     .rotation(Rotation::Daily)
+//!     .max_files(7)
+//!     .apply();
+//! ```
+//!
+//! ## Redaction
+//!
+//! ```rust,no_run
+//! use armature_log::{configure, Regex};
+//!
+//! configure()
+//!     .redact(vec!["password", "authorization", "token"])
+//!     .redact_pattern(Regex::new(r"Bearer\s+\S+").unwrap())
+//!     .apply();
+//!
+//! // {"...","message":"login","fields":{"user":"alice","password":"***"}}
+//! armature_log::log_kv(
+//!     armature_log::Level::Info,
+//!     "auth",
+//!     "login",
+//!     &[("user", &"alice"), ("password", &"hunter2")],
+//! );
+//! ```
+//!
+//! ## Multiple Sinks
+//!
+//! ```rust,no_run
+//! use armature_log::{configure, FileSink, Format, FormatSink, Rotation};
+//! use std::time::Duration;
+//!
+//! let file = FileSink::open("app.log", Rotation::Daily, 7, Duration::from_secs(1)).unwrap();
+//!
+//! // Pretty, colored console output and JSON in a file
+//! configure()
+//!     .sink(FormatSink::stderr(Format::Pretty).color(true))
+//!     .sink(FormatSink::file(file, Format::Json))
+//!     .apply();
+//! ```
+//!
 //! # Environment Variables
 //!
 //! | Variable | Values | Default | Description |
@@ -86,8 +137,30 @@
 
 use once_cell::sync::Lazy;
 use std::env;
+use std::fmt::Write as _;
 use std::io::Write;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use sample::Sampler;
+use sink::SinkRecord;
+
+mod file;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod redact;
+mod sample;
+mod sink;
+mod writer;
+
+pub use file::{DEFAULT_FLUSH_INTERVAL, DEFAULT_MAX_FILES, FileSink, Rotation};
+pub use redact::{REDACTED, Redactor};
+pub use regex::Regex;
+pub use sample::{SAMPLE_SUMMARY_INTERVAL, SampleRate};
+pub use sink::{FormatSink, Sink};
+pub use writer::{AsyncWriter, OverflowPolicy};
 
 // ============================================================================
 // Log Levels
@@ -137,6 +210,17 @@ impl Level {
         }
     }
 
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Level::Trace,
+            1 => Level::Debug,
+            2 => Level::Info,
+            3 => Level::Warn,
+            4 => Level::Error,
+            _ => Level::Off,
+        }
+    }
+
     /// Get colored level name (if color feature enabled).
     #[cfg(feature = "color")]
     pub fn colored(&self) -> colored::ColoredString {
@@ -192,6 +276,14 @@ impl Format {
             _ => None,
         }
     }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Format::Pretty,
+            1 => Format::Compact,
+            _ => Format::Json,
+        }
+    }
 }
 
 impl std::str::FromStr for Format {
@@ -206,11 +298,96 @@ impl std::str::FromStr for Format {
 // Global Configuration
 // ============================================================================
 
-/// Global debug flag - checked by macros.
-static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
+/// Runtime settings packed into one atomic, so that a reload (or any other
+/// multi-setting change) is observed by log calls all at once.
+static SETTINGS: AtomicU32 = AtomicU32::new(Settings::DEFAULT.pack());
+
+const LEVEL_MASK: u32 = 0xff;
+const FORMAT_SHIFT: u32 = 8;
+const COLOR_BIT: u32 = 1 << 16;
+const TIMESTAMPS_BIT: u32 = 1 << 17;
+const MODULE_PATH_BIT: u32 = 1 << 18;
+const DEBUG_BIT: u32 = 1 << 19;
+
+/// Snapshot of the runtime settings stored in [`SETTINGS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Settings {
+    level: Level,
+    format: Format,
+    color: bool,
+    timestamps: bool,
+    module_path: bool,
+    debug: bool,
+}
+
+impl Settings {
+    const DEFAULT: Self = Self {
+        level: Level::Info,
+        format: Format::Json,
+        color: false,
+        timestamps: true,
+        module_path: true,
+        debug: false,
+    };
+
+    const fn pack(self) -> u32 {
+        let mut bits = (self.level as u32) | ((self.format as u32) << FORMAT_SHIFT);
+        if self.color {
+            bits |= COLOR_BIT;
+        }
+        if self.timestamps {
+            bits |= TIMESTAMPS_BIT;
+        }
+        if self.module_path {
+            bits |= MODULE_PATH_BIT;
+        }
+        if self.debug {
+            bits |= DEBUG_BIT;
+        }
+        bits
+    }
+
+    fn unpack(bits: u32) -> Self {
+        Self {
+            level: Level::from_u8((bits & LEVEL_MASK) as u8),
+            format: Format::from_u8(((bits >> FORMAT_SHIFT) & LEVEL_MASK) as u8),
+            color: bits & COLOR_BIT != 0,
+            timestamps: bits & TIMESTAMPS_BIT != 0,
+            module_path: bits & MODULE_PATH_BIT != 0,
+            debug: bits & DEBUG_BIT != 0,
+        }
+    }
+
+    fn load() -> Self {
+        Self::unpack(SETTINGS.load(Ordering::Relaxed))
+    }
+
+    fn store(self) {
+        SETTINGS.store(self.pack(), Ordering::SeqCst);
+    }
+
+    /// Atomically modify the settings.
+    fn update(f: impl Fn(&mut Self)) {
+        let _ = SETTINGS.fetch_update(Ordering::SeqCst, Ordering::Relaxed, |bits| {
+            let mut settings = Self::unpack(bits);
+            f(&mut settings);
+            Some(settings.pack())
+        });
+    }
+}
 
-/// Global log level.
-static LOG_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+impl From<&LogConfig> for Settings {
+    fn from(config: &LogConfig) -> Self {
+        Self {
+            level: config.level,
+            format: config.format,
+            color: config.color,
+            timestamps: config.timestamps,
+            module_path: config.module_path,
+            debug: config.debug,
+        }
+    }
+}
 
 /// Global configuration (lazy initialized).
 static CONFIG: Lazy<LogConfig> = Lazy::new(LogConfig::from_env);
@@ -274,10 +451,6 @@ impl LogConfig {
             .map(|v| v == "1" || v.to_lowercase() == "true")
             .unwrap_or(true);
 
-        // Update global atomics
-        DEBUG_ENABLED.store(debug, Ordering::SeqCst);
-        LOG_LEVEL.store(level as u8, Ordering::SeqCst);
-
         Self {
             debug,
             level,
@@ -310,49 +483,113 @@ mod atty {
 /// This is called automatically when first log macro is used,
 /// but can be called explicitly for eager initialization.
 pub fn init() {
-    let config = Lazy::force(&CONFIG);
-    // Initialize runtime atomics from config
-    LOG_FORMAT.store(config.format as u8, Ordering::SeqCst);
-    LOG_COLOR.store(config.color, Ordering::SeqCst);
-    LOG_TIMESTAMPS.store(config.timestamps, Ordering::SeqCst);
-    LOG_MODULE_PATH.store(config.module_path, Ordering::SeqCst);
+    // Initialize runtime settings from config
+    Settings::from(Lazy::force(&CONFIG)).store();
+}
+
+/// Re-read the `ARMATURE_*` environment variables and apply them.
+///
+/// Level, format, color, timestamps, module path, and debug mode are replaced
+/// in a single atomic store, so concurrent log calls see either the old or
+/// the new settings, never a mix. Per-target levels, sampling, redaction, and
+/// sinks are left unchanged. Returns the applied configuration.
+///
+/// Useful from an admin endpoint or signal handler; see
+/// [`install_sighup_reload`].
+pub fn apply_from_env() -> LogConfig {
+    let config = LogConfig::from_env();
+    Settings::from(&config).store();
+    config
+}
+
+/// Reload settings from the environment whenever the process receives SIGHUP.
+///
+/// Spawns a thread that calls [`apply_from_env`] on each SIGHUP. Calling this
+/// more than once has no further effect.
+///
+/// A process's environment can only be changed from inside the process, so
+/// this is useful when something in the process (a config loader, an admin
+/// handler) updates the `ARMATURE_LOG_*` variables before the signal is sent.
+#[cfg(unix)]
+pub fn install_sighup_reload() -> std::io::Result<()> {
+    use signal_hook::consts::SIGHUP;
+    use signal_hook::iterator::Signals;
+
+    static INSTALLED: AtomicBool = AtomicBool::new(false);
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let result = Signals::new([SIGHUP]).and_then(|mut signals| {
+        std::thread::Builder::new()
+            .name("armature-log-reload".to_string())
+            .spawn(move || {
+                for _ in signals.forever() {
+                    let config = apply_from_env();
+                    log(
+                        Level::Info,
+                        "armature_log",
+                        &format!(
+                            "Reloaded log settings: level={} format={:?}",
+                            config.level.as_str(),
+                            config.format
+                        ),
+                    );
+                }
+            })
+    });
+
+    if result.is_err() {
+        INSTALLED.store(false, Ordering::SeqCst);
+    }
+    result.map(|_| ())
 }
 
 /// Check if debug logging is enabled.
 #[inline]
 pub fn is_debug_enabled() -> bool {
-    DEBUG_ENABLED.load(Ordering::Relaxed)
+    SETTINGS.load(Ordering::Relaxed) & DEBUG_BIT != 0
 }
 
 /// Check if a log level is enabled.
 #[inline]
 pub fn is_level_enabled(level: Level) -> bool {
-    level as u8 >= LOG_LEVEL.load(Ordering::Relaxed)
+    level as u32 >= (SETTINGS.load(Ordering::Relaxed) & LEVEL_MASK)
+}
+
+/// Check if a log level is enabled for a target.
+///
+/// Per-target overrides registered with [`set_target_level`] take precedence;
+/// targets without an override fall back to the global level. When no
+/// overrides are registered this is a single extra atomic load.
+#[inline]
+pub fn is_level_enabled_for(level: Level, target: &str) -> bool {
+    if HAS_TARGET_LEVELS.load(Ordering::Relaxed)
+        && let Some(min) = target_level(target)
+    {
+        return level >= min;
+    }
+    is_level_enabled(level)
 }
 
 /// Get current log level.
 pub fn current_level() -> Level {
-    match LOG_LEVEL.load(Ordering::Relaxed) {
-        0 => Level::Trace,
-        1 => Level::Debug,
-        2 => Level::Info,
-        3 => Level::Warn,
-        4 => Level::Error,
-        _ => Level::Off,
-    }
+    Settings::load().level
 }
 
 /// Set log level at runtime.
 pub fn set_level(level: Level) {
-    LOG_LEVEL.store(level as u8, Ordering::SeqCst);
+    Settings::update(|settings| settings.level = level);
 }
 
 /// Enable or disable debug mode at runtime.
 pub fn set_debug(enabled: bool) {
-    DEBUG_ENABLED.store(enabled, Ordering::SeqCst);
-    if enabled && current_level() > Level::Debug {
-        set_level(Level::Debug);
-    }
+    Settings::update(|settings| {
+        settings.debug = enabled;
+        if enabled && settings.level > Level::Debug {
+            settings.level = Level::Debug;
+        }
+    });
 }
 
 /// Get the global configuration.
@@ -361,30 +598,204 @@ pub fn config() -> &'static LogConfig {
 }
 
 // ============================================================================
-// Runtime Configuration
+// Per-Target Levels
 // ============================================================================
 
-use std::sync::atomic::AtomicU8 as AtomicFormat;
+/// Whether any per-target overrides are registered (fast path for the macros).
+static HAS_TARGET_LEVELS: AtomicBool = AtomicBool::new(false);
+
+/// Per-target level overrides, sorted by descending prefix length so that the
+/// most specific prefix is found first.
+static TARGET_LEVELS: Lazy<RwLock<Vec<(String, Level)>>> = Lazy::new(|| RwLock::new(Vec::new()));
 
-/// Global format setting (can be changed at runtime).
-static LOG_FORMAT: AtomicFormat = AtomicFormat::new(Format::Json as u8);
+/// Override the log level for a target and its submodules.
+///
+/// The override applies to `target` itself and to any target nested under it
+/// (`armature::router` also covers `armature::router::trie`). When several
+/// overrides match, the longest prefix wins.
+///
+/// # Example
+///
+/// ```rust
+/// use armature_log::{set_target_level, Level};
+///
+/// // Debug logs from the router, info everywhere else
+/// set_target_level("armature::router", Level::Debug);
+/// ```
+pub fn set_target_level(target: &str, level: Level) {
+    let mut levels = TARGET_LEVELS.write().unwrap_or_else(|e| e.into_inner());
+    match levels.iter_mut().find(|(prefix, _)| prefix == target) {
+        Some(entry) => entry.1 = level,
+        None => {
+            levels.push((target.to_string(), level));
+            levels.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        }
+    }
+    HAS_TARGET_LEVELS.store(true, Ordering::SeqCst);
+}
+
+/// Remove the level override for a target.
+pub fn clear_target_level(target: &str) {
+    let mut levels = TARGET_LEVELS.write().unwrap_or_else(|e| e.into_inner());
+    levels.retain(|(prefix, _)| prefix != target);
+    HAS_TARGET_LEVELS.store(!levels.is_empty(), Ordering::SeqCst);
+}
+
+/// Remove all per-target level overrides.
+pub fn clear_target_levels() {
+    let mut levels = TARGET_LEVELS.write().unwrap_or_else(|e| e.into_inner());
+    levels.clear();
+    HAS_TARGET_LEVELS.store(false, Ordering::SeqCst);
+}
 
-/// Global color setting.
-static LOG_COLOR: AtomicBool = AtomicBool::new(false);
+/// Get the level override that applies to a target, if any.
+pub fn target_level(target: &str) -> Option<Level> {
+    let levels = TARGET_LEVELS.read().ok()?;
+    levels
+        .iter()
+        .find(|(prefix, _)| target_matches(prefix, target))
+        .map(|(_, level)| *level)
+}
+
+/// Check if `target` is `prefix` or a module nested under it.
+fn target_matches(prefix: &str, target: &str) -> bool {
+    match target.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
+
+// ============================================================================
+// Sampling
+// ============================================================================
 
-/// Global timestamps setting.
-static LOG_TIMESTAMPS: AtomicBool = AtomicBool::new(true);
+/// Whether any target is sampled (fast path for the macros).
+static HAS_SAMPLERS: AtomicBool = AtomicBool::new(false);
 
-/// Global module path setting.
-static LOG_MODULE_PATH: AtomicBool = AtomicBool::new(true);
+/// Samplers keyed by target prefix.
+type Samplers = Vec<(String, Arc<Sampler>)>;
+
+/// Per-target samplers, sorted by descending prefix length.
+static SAMPLERS: Lazy<RwLock<Samplers>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Reference point for sampling clocks.
+static SAMPLE_EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// Sample records from a target and its submodules.
+///
+/// Records that pass the level check are kept or dropped according to `rate`
+/// before their message is formatted. The number of dropped records is
+/// reported at most every [`SAMPLE_SUMMARY_INTERVAL`] as an info line on the
+/// sampled target with a `dropped` field, and on [`flush`]. Replacing the rate
+/// for a target resets its counters.
+///
+/// # Example
+///
+/// ```rust
+/// use armature_log::{set_sample_rate, SampleRate};
+///
+/// // Keep 1% of the cache's debug chatter
+/// set_sample_rate("my_app::cache", SampleRate::OneIn(100));
+/// ```
+pub fn set_sample_rate(target: &str, rate: SampleRate) {
+    let mut samplers = SAMPLERS.write().unwrap_or_else(|e| e.into_inner());
+    let sampler = Arc::new(Sampler::new(rate));
+    match samplers.iter_mut().find(|(prefix, _)| prefix == target) {
+        Some(entry) => entry.1 = sampler,
+        None => {
+            samplers.push((target.to_string(), sampler));
+            samplers.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        }
+    }
+    HAS_SAMPLERS.store(true, Ordering::SeqCst);
+}
+
+/// Stop sampling a target.
+pub fn clear_sample_rate(target: &str) {
+    let mut samplers = SAMPLERS.write().unwrap_or_else(|e| e.into_inner());
+    samplers.retain(|(prefix, _)| prefix != target);
+    HAS_SAMPLERS.store(!samplers.is_empty(), Ordering::SeqCst);
+}
+
+/// Stop sampling all targets.
+pub fn clear_sample_rates() {
+    let mut samplers = SAMPLERS.write().unwrap_or_else(|e| e.into_inner());
+    samplers.clear();
+    HAS_SAMPLERS.store(false, Ordering::SeqCst);
+}
+
+/// Get the sample rate that applies to a target, if any.
+pub fn sample_rate(target: &str) -> Option<SampleRate> {
+    sampler_for(target).map(|(_, sampler)| sampler.rate())
+}
+
+/// Check if a record from `target` is kept by sampling.
+///
+/// Always `true` for targets that are not sampled. When no target is sampled
+/// this is a single atomic load.
+#[doc(hidden)]
+#[inline]
+pub fn is_sampled_in(target: &str) -> bool {
+    !HAS_SAMPLERS.load(Ordering::Relaxed) || sample_slow(target)
+}
+
+#[cold]
+fn sample_slow(target: &str) -> bool {
+    let Some((prefix, sampler)) = sampler_for(target) else {
+        return true;
+    };
+
+    let now_ms = SAMPLE_EPOCH.elapsed().as_millis() as u64;
+    let keep = sampler.sample(now_ms);
+    if let Some(dropped) = sampler.take_summary(now_ms, false) {
+        log_sampling_summary(&prefix, dropped);
+    }
+    keep
+}
+
+fn sampler_for(target: &str) -> Option<(String, Arc<Sampler>)> {
+    let samplers = SAMPLERS.read().ok()?;
+    samplers
+        .iter()
+        .find(|(prefix, _)| target_matches(prefix, target))
+        .map(|(prefix, sampler)| (prefix.clone(), sampler.clone()))
+}
+
+/// Report dropped counts for every sampled target, regardless of interval.
+fn flush_sampling_summaries() {
+    if !HAS_SAMPLERS.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let samplers: Vec<(String, Arc<Sampler>)> = match SAMPLERS.read() {
+        Ok(samplers) => samplers.clone(),
+        Err(_) => return,
+    };
+    let now_ms = SAMPLE_EPOCH.elapsed().as_millis() as u64;
+    for (prefix, sampler) in samplers {
+        if let Some(dropped) = sampler.take_summary(now_ms, true) {
+            log_sampling_summary(&prefix, dropped);
+        }
+    }
+}
+
+fn log_sampling_summary(target: &str, dropped: u64) {
+    // Bypasses the level check: the summary is the only trace of the dropped records
+    write_record(
+        Level::Info,
+        target,
+        "log records dropped by sampling",
+        &[("dropped", &dropped)],
+    );
+}
+
+// ============================================================================
+// Runtime Configuration
+// ============================================================================
 
 /// Get the current log format.
 pub fn current_format() -> Format {
-    match LOG_FORMAT.load(Ordering::Relaxed) {
-        0 => Format::Pretty,
-        1 => Format::Compact,
-        _ => Format::Json,
-    }
+    Settings::load().format
 }
 
 /// Set log format at runtime.
@@ -401,28 +812,31 @@ pub fn current_format() -> Format {
 /// set_format(Format::Json);
 /// ```
 pub fn set_format(format: Format) {
-    LOG_FORMAT.store(format as u8, Ordering::SeqCst);
-    // Also update color based on format
-    if format == Format::Pretty {
-        LOG_COLOR.store(atty::is(atty::Stream::Stderr), Ordering::SeqCst);
-    } else if format == Format::Json {
-        LOG_COLOR.store(false, Ordering::SeqCst);
-    }
+    let tty = atty::is(atty::Stream::Stderr);
+    Settings::update(|settings| {
+        settings.format = format;
+        // Also update color based on format
+        if format == Format::Pretty {
+            settings.color = tty;
+        } else if format == Format::Json {
+            settings.color = false;
+        }
+    });
 }
 
 /// Set whether colors are enabled.
 pub fn set_color(enabled: bool) {
-    LOG_COLOR.store(enabled, Ordering::SeqCst);
+    Settings::update(|settings| settings.color = enabled);
 }
 
 /// Set whether timestamps are included.
 pub fn set_timestamps(enabled: bool) {
-    LOG_TIMESTAMPS.store(enabled, Ordering::SeqCst);
+    Settings::update(|settings| settings.timestamps = enabled);
 }
 
 /// Set whether module path is included.
 pub fn set_module_path(enabled: bool) {
-    LOG_MODULE_PATH.store(enabled, Ordering::SeqCst);
+    Settings::update(|settings| settings.module_path = enabled);
 }
 
 /// Configuration builder for fluent API.
@@ -447,6 +861,18 @@ pub struct ConfigBuilder {
     timestamps: Option<bool>,
     module_path: Option<bool>,
     debug: Option<bool>,
+    target_levels: Vec<(String, Level)>,
+    stderr: Option<bool>,
+    file: Option<PathBuf>,
+    rotation: Rotation,
+    max_files: usize,
+    flush_interval: Duration,
+    async_capacity: Option<usize>,
+    overflow_policy: OverflowPolicy,
+    redact: Vec<String>,
+    redact_patterns: Vec<Regex>,
+    sample_rates: Vec<(String, SampleRate)>,
+    sinks: Vec<Arc<dyn Sink>>,
 }
 
 impl ConfigBuilder {
@@ -459,6 +885,18 @@ impl ConfigBuilder {
             timestamps: None,
             module_path: None,
             debug: None,
+            target_levels: Vec::new(),
+            stderr: None,
+            file: None,
+            rotation: Rotation::Never,
+            max_files: DEFAULT_MAX_FILES,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            async_capacity: None,
+            overflow_policy: OverflowPolicy::Block,
+            redact: Vec::new(),
+            redact_patterns: Vec::new(),
+            sample_rates: Vec::new(),
+            sinks: Vec::new(),
         }
     }
 
@@ -498,8 +936,141 @@ impl ConfigBuilder {
         self
     }
 
+    /// Override the log level for a target and its submodules.
+    ///
+    /// See [`set_target_level`].
+    pub fn target_level(mut self, target: impl Into<String>, level: Level) -> Self {
+        self.target_levels.push((target.into(), level));
+        self
+    }
+
+    /// Enable or disable writing to stderr.
+    ///
+    /// Disable this to log only to a file.
+    pub fn stderr(mut self, enabled: bool) -> Self {
+        self.stderr = Some(enabled);
+        self
+    }
+
+    /// Also write logs to a file.
+    ///
+    /// File output uses the same format and level as stderr, without colors.
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    /// Set when the log file is rotated.
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Set how many rotated log files are kept.
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Set how often buffered file output is flushed.
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Write log output from a dedicated background thread.
+    ///
+    /// Log calls queue formatted lines (up to `capacity`) and return without
+    /// waiting on stderr or file I/O. Call [`flush`] (or hold a [`FlushGuard`])
+    /// before exit so queued lines are written.
+    pub fn async_writer(mut self, capacity: usize) -> Self {
+        self.async_capacity = Some(capacity);
+        self
+    }
+
+    /// Set what the async writer does when its queue is full.
+    pub fn overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Mask the values of fields whose name contains any of these patterns.
+    ///
+    /// See [`redact_fields`].
+    pub fn redact<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redact.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// Mask every match of a regex in messages and field values.
+    pub fn redact_pattern(mut self, pattern: Regex) -> Self {
+        self.redact_patterns.push(pattern);
+        self
+    }
+
+    /// Sample records from a target and its submodules.
+    ///
+    /// See [`set_sample_rate`].
+    pub fn sample(mut self, target: impl Into<String>, rate: SampleRate) -> Self {
+        self.sample_rates.push((target.into(), rate));
+        self
+    }
+
+    /// Register a sink.
+    ///
+    /// When any sink is registered, records go only to the registered sinks
+    /// (replacing those from an earlier configuration) instead of the default
+    /// stderr and file output. See [`set_sinks`].
+    pub fn sink(mut self, sink: impl Sink + 'static) -> Self {
+        self.sinks.push(Arc::new(sink));
+        self
+    }
+
     /// Apply the configuration.
+    ///
+    /// If a log file cannot be opened or the writer thread cannot be started,
+    /// an error is printed to stderr and the remaining settings are still applied.
     pub fn apply(self) {
+        if let Err(e) = self.try_apply() {
+            eprintln!("armature-log: failed to apply configuration: {}", e);
+        }
+    }
+
+    /// Apply the configuration, returning an error if the log file cannot be
+    /// opened or the writer thread cannot be started.
+    pub fn try_apply(self) -> std::io::Result<()> {
+        let file = match &self.file {
+            Some(path) => Some(FileSink::open(
+                path,
+                self.rotation,
+                self.max_files,
+                self.flush_interval,
+            )),
+            None => None,
+        };
+        let writer = self
+            .async_capacity
+            .map(|capacity| AsyncWriter::spawn(capacity, self.overflow_policy));
+
+        self.apply_settings();
+
+        if let Some(writer) = writer {
+            set_async_writer(Some(writer?));
+        }
+        if let Some(file) = file {
+            set_file_sink(Some(file?));
+        }
+        Ok(())
+    }
+
+    fn apply_settings(&self) {
+        if let Some(stderr) = self.stderr {
+            set_stderr(stderr);
+        }
         if let Some(format) = self.format {
             set_format(format);
         }
@@ -518,6 +1089,25 @@ impl ConfigBuilder {
         if let Some(debug) = self.debug {
             set_debug(debug);
         }
+        for (target, level) in &self.target_levels {
+            set_target_level(target, *level);
+        }
+        if !self.sinks.is_empty() {
+            set_sinks(self.sinks.clone());
+        }
+        for (target, rate) in &self.sample_rates {
+            set_sample_rate(target, *rate);
+        }
+        if !self.redact.is_empty() || !self.redact_patterns.is_empty() {
+            update_redactor(|redactor| {
+                for field in &self.redact {
+                    redactor.add_field(field);
+                }
+                for pattern in &self.redact_patterns {
+                    redactor.add_pattern(pattern.clone());
+                }
+            });
+        }
     }
 }
 
@@ -590,85 +1180,497 @@ pub fn preset_quiet() {
         .apply();
 }
 
+// ============================================================================
+// Redaction
+// ============================================================================
+
+/// Whether any redaction is configured (checked before taking the lock).
+static REDACT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Installed redaction rules.
+static REDACTOR: Lazy<RwLock<Option<Arc<Redactor>>>> = Lazy::new(|| RwLock::new(None));
+
+fn update_redactor(update: impl FnOnce(&mut Redactor)) {
+    let mut current = REDACTOR.write().unwrap_or_else(|e| e.into_inner());
+    let mut redactor = current.as_deref().cloned().unwrap_or_default();
+    update(&mut redactor);
+    REDACT_ENABLED.store(!redactor.is_empty(), Ordering::SeqCst);
+    *current = Some(Arc::new(redactor));
+}
+
+/// Mask the values of structured fields whose name contains any of `fields`.
+///
+/// Matching is a case-insensitive substring match, so `"token"` also masks
+/// `access_token` and `X-Token`.
+pub fn redact_fields<I, S>(fields: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    update_redactor(|redactor| {
+        for field in fields {
+            redactor.add_field(field.as_ref());
+        }
+    });
+}
+
+/// Mask every match of `pattern` in log messages and field values.
+pub fn redact_pattern(pattern: Regex) {
+    update_redactor(|redactor| redactor.add_pattern(pattern));
+}
+
+/// Remove all redaction rules.
+pub fn clear_redactions() {
+    let mut current = REDACTOR.write().unwrap_or_else(|e| e.into_inner());
+    REDACT_ENABLED.store(false, Ordering::SeqCst);
+    *current = None;
+}
+
+/// Get the installed redaction rules.
+pub fn redactor() -> Option<Arc<Redactor>> {
+    if !REDACT_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    REDACTOR.read().ok().and_then(|redactor| redactor.clone())
+}
+
 // ============================================================================
 // Log Output
 // ============================================================================
 
+/// Whether log lines are written to stderr.
+static STDERR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Whether a file sink is installed (checked before taking the lock).
+static FILE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Installed file sink.
+static FILE_SINK: Lazy<RwLock<Option<Arc<FileSink>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Set whether log lines are written to stderr.
+pub fn set_stderr(enabled: bool) {
+    STDERR_ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+/// Install a file sink, or remove it with `None`.
+///
+/// The previously installed sink (if any) is flushed.
+pub fn set_file_sink(sink: Option<Arc<FileSink>>) {
+    let mut current = FILE_SINK.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(old) = current.take() {
+        old.flush();
+    }
+    FILE_ENABLED.store(sink.is_some(), Ordering::SeqCst);
+    *current = sink;
+}
+
+/// Get the installed file sink.
+pub fn file_sink() -> Option<Arc<FileSink>> {
+    if !FILE_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    FILE_SINK.read().ok().and_then(|sink| sink.clone())
+}
+
+/// Whether any sinks are registered (checked before taking the lock).
+static SINKS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// A shared snapshot of the registered sinks.
+type Sinks = Arc<Vec<Arc<dyn Sink>>>;
+
+/// Registered sinks.
+static SINKS: Lazy<RwLock<Option<Sinks>>> = Lazy::new(|| RwLock::new(None));
+
+/// Replace the registered sinks.
+///
+/// While any sink is registered, records are written only to the registered
+/// sinks; the default stderr and file output (with the global format) is
+/// used again once the list is empty. Replaced sinks are flushed.
+pub fn set_sinks(sinks: Vec<Arc<dyn Sink>>) {
+    let sinks = (!sinks.is_empty()).then(|| Arc::new(sinks));
+    let old = {
+        let mut current = SINKS.write().unwrap_or_else(|e| e.into_inner());
+        SINKS_ENABLED.store(sinks.is_some(), Ordering::SeqCst);
+        std::mem::replace(&mut *current, sinks)
+    };
+    for sink in old.iter().flat_map(|sinks| sinks.iter()) {
+        sink.flush();
+    }
+}
+
+/// Register an additional sink.
+pub fn add_sink(sink: Arc<dyn Sink>) {
+    let mut current = SINKS.write().unwrap_or_else(|e| e.into_inner());
+    let mut sinks: Vec<Arc<dyn Sink>> = current.as_deref().cloned().unwrap_or_default();
+    sinks.push(sink);
+    SINKS_ENABLED.store(true, Ordering::SeqCst);
+    *current = Some(Arc::new(sinks));
+}
+
+/// Remove all registered sinks, restoring the default output.
+pub fn clear_sinks() {
+    set_sinks(Vec::new());
+}
+
+/// Get the registered sinks.
+pub fn sinks() -> Vec<Arc<dyn Sink>> {
+    registered_sinks()
+        .map(|sinks| sinks.to_vec())
+        .unwrap_or_default()
+}
+
+/// Sinks equivalent to the default stderr and file output.
+///
+/// Used when a sink is added on top of the default output, so that
+/// registering it does not silence stderr or the log file.
+#[cfg_attr(not(feature = "otel"), allow(dead_code))]
+pub(crate) fn default_sinks() -> Vec<Arc<dyn Sink>> {
+    let settings = Settings::load();
+    let mut sinks: Vec<Arc<dyn Sink>> = Vec::new();
+
+    if STDERR_ENABLED.load(Ordering::Relaxed) {
+        sinks.push(Arc::new(
+            FormatSink::stderr(settings.format)
+                .color(settings.color)
+                .timestamps(settings.timestamps)
+                .module_path(settings.module_path),
+        ));
+    }
+    if let Some(file) = file_sink() {
+        sinks.push(Arc::new(
+            FormatSink::file(file, settings.format)
+                .timestamps(settings.timestamps)
+                .module_path(settings.module_path),
+        ));
+    }
+    sinks
+}
+
+fn registered_sinks() -> Option<Sinks> {
+    if !SINKS_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    SINKS.read().ok().and_then(|sinks| sinks.clone())
+}
+
+/// Whether the async writer is installed (checked before taking the lock).
+static ASYNC_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Installed async writer.
+static ASYNC_WRITER: Lazy<RwLock<Option<Arc<AsyncWriter>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Install a background writer, or remove it with `None`.
+///
+/// Lines queued on the previous writer (if any) are written before it is replaced.
+pub fn set_async_writer(writer: Option<Arc<AsyncWriter>>) {
+    let old = {
+        let mut current = ASYNC_WRITER.write().unwrap_or_else(|e| e.into_inner());
+        ASYNC_ENABLED.store(writer.is_some(), Ordering::SeqCst);
+        std::mem::replace(&mut *current, writer)
+    };
+    if let Some(old) = old {
+        old.flush();
+    }
+}
+
+/// Get the installed async writer.
+pub fn async_writer() -> Option<Arc<AsyncWriter>> {
+    if !ASYNC_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    ASYNC_WRITER.read().ok().and_then(|writer| writer.clone())
+}
+
+/// Flush buffered log output.
+///
+/// Reports pending sampling summaries, waits for the async writer (if
+/// enabled) to drain its queue, then flushes registered sinks and the file
+/// sink. Call this before shutdown to make sure no lines are lost.
+pub fn flush() {
+    flush_sampling_summaries();
+    if let Some(writer) = async_writer() {
+        writer.flush();
+    }
+    for sink in registered_sinks().iter().flat_map(|sinks| sinks.iter()) {
+        sink.flush();
+    }
+    if let Some(sink) = file_sink() {
+        sink.flush();
+    }
+    let _ = std::io::stderr().flush();
+}
+
+/// Guard that flushes all log output when dropped.
+///
+/// Hold one for the lifetime of `main` so queued lines are written at exit,
+/// including on early return or panic unwinding.
+///
+/// # Example
+///
+/// ```rust
+/// let _guard = armature_log::flush_guard();
+/// armature_log::configure().async_writer(8192).apply();
+///
+/// armature_log::info!("Server started");
+/// ```
+#[derive(Debug)]
+#[must_use = "the guard flushes log output when dropped"]
+pub struct FlushGuard {
+    _private: (),
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        flush();
+    }
+}
+
+/// Create a guard that flushes all log output when dropped.
+pub fn flush_guard() -> FlushGuard {
+    FlushGuard { _private: () }
+}
+
+/// Output for one log record, ready to be written.
+#[derive(Debug)]
+pub(crate) enum Output {
+    /// Formatted lines for the default stderr and file output
+    Lines {
+        stderr: Option<String>,
+        file: Option<(Arc<FileSink>, String)>,
+    },
+    /// A record for the registered sinks
+    Record(SinkRecord),
+}
+
+impl Output {
+    pub(crate) fn write(self) {
+        match self {
+            Output::Lines { stderr, file } => {
+                if let Some(line) = stderr {
+                    write_stderr(&line);
+                }
+                if let Some((sink, line)) = file {
+                    sink.write_line(&line);
+                }
+            }
+            Output::Record(record) => record.write(),
+        }
+    }
+}
+
 /// Log a message with the given level.
+///
+/// Called by the macros once [`is_sampled_in`] has kept the record, so this
+/// does not sample again.
 #[doc(hidden)]
 pub fn log(level: Level, target: &str, message: &str) {
-    if !is_level_enabled(level) {
+    if is_level_enabled_for(level, target) {
+        write_record(level, target, message, &[]);
+    }
+}
+
+/// Log a message with structured key-value fields.
+///
+/// Fields appear as a nested `"fields"` object in JSON output and as
+/// `key=value` pairs in the pretty and compact formats. Redaction is applied
+/// before anything is formatted, so masked values never reach a sink.
+/// Records from sampled targets are subject to [`set_sample_rate`].
+///
+/// # Example
+///
+/// ```rust
+/// use armature_log::{log_kv, Level};
+///
+/// log_kv(Level::Info, "http", "request", &[("method", &"GET"), ("status", &200)]);
+/// ```
+pub fn log_kv(
+    level: Level,
+    target: &str,
+    message: &str,
+    fields: &[(&str, &dyn std::fmt::Display)],
+) {
+    if is_level_enabled_for(level, target) && is_sampled_in(target) {
+        write_record(level, target, message, fields);
+    }
+}
+
+/// Redact, format, and write a record that passed the level and sampling checks.
+fn write_record(
+    level: Level,
+    target: &str,
+    message: &str,
+    fields: &[(&str, &dyn std::fmt::Display)],
+) {
+    let sinks = registered_sinks();
+    let stderr = STDERR_ENABLED.load(Ordering::Relaxed);
+    let file = file_sink();
+
+    if sinks.is_none() && !stderr && file.is_none() {
         return;
     }
 
-    // Use runtime-configurable format instead of static config
-    let format = current_format();
-    let color = LOG_COLOR.load(Ordering::Relaxed);
-    let timestamps = LOG_TIMESTAMPS.load(Ordering::Relaxed);
-    let module_path = LOG_MODULE_PATH.load(Ordering::Relaxed);
+    let redactor = redactor();
+    let (message, fields): (std::borrow::Cow<'_, str>, Vec<(&str, String)>) = match &redactor {
+        Some(redactor) => (
+            redactor.redact_message(message),
+            fields
+                .iter()
+                .map(|(key, value)| (*key, redactor.redact_field(key, *value)))
+                .collect(),
+        ),
+        None => (
+            message.into(),
+            fields
+                .iter()
+                .map(|(key, value)| (*key, value.to_string()))
+                .collect(),
+        ),
+    };
 
-    match format {
-        Format::Pretty => {
-            log_pretty_runtime(level, target, message, color, timestamps, module_path)
+    if let Some(sinks) = sinks {
+        match async_writer() {
+            Some(writer) => writer.send(Output::Record(SinkRecord {
+                sinks,
+                level,
+                target: target.to_string(),
+                message: message.into_owned(),
+                fields: fields
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+            })),
+            None => sink::write_to_sinks(&sinks, level, target, &message, &fields),
         }
-        Format::Compact => log_compact_runtime(level, target, message, timestamps, module_path),
-        Format::Json => log_json(level, target, message),
+        return;
+    }
+
+    // Use runtime-configurable settings (one coherent snapshot) instead of static config
+    let Settings {
+        format,
+        color,
+        timestamps,
+        module_path,
+        ..
+    } = Settings::load();
+    let color = stderr && color;
+
+    let record = Record {
+        level,
+        target,
+        message: &message,
+        fields: &fields,
+    };
+
+    let line = format_line(&record, format, color, timestamps, module_path);
+
+    let file = file.map(|file| {
+        let file_line = if color {
+            // Files never contain ANSI escape codes
+            format_line(&record, format, false, timestamps, module_path)
+        } else {
+            line.clone()
+        };
+        (file, file_line)
+    });
+
+    let output = Output::Lines {
+        stderr: stderr.then_some(line),
+        file,
+    };
+
+    match async_writer() {
+        Some(writer) => writer.send(output),
+        None => output.write(),
+    }
+}
+
+/// A log record after redaction, ready to be formatted.
+struct Record<'a> {
+    level: Level,
+    target: &'a str,
+    message: &'a str,
+    fields: &'a [(&'a str, String)],
+}
+
+fn write_stderr(line: &str) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = writeln!(stderr, "{}", line);
+}
+
+fn format_line(
+    record: &Record<'_>,
+    format: Format,
+    color: bool,
+    timestamps: bool,
+    module_path: bool,
+) -> String {
+    match format {
+        Format::Pretty => format_pretty(record, color, timestamps, module_path),
+        Format::Compact => format_compact(record, timestamps, module_path),
+        Format::Json => format_json(record),
     }
 }
 
 #[allow(dead_code)]
 fn log_pretty(level: Level, target: &str, message: &str, config: &LogConfig) {
-    log_pretty_runtime(
+    let record = Record {
         level,
         target,
         message,
+        fields: &[],
+    };
+    write_stderr(&format_pretty(
+        &record,
         config.color,
         config.timestamps,
         config.module_path,
-    );
+    ));
 }
 
 #[allow(dead_code)]
 fn log_compact(level: Level, target: &str, message: &str, config: &LogConfig) {
-    log_compact_runtime(
+    let record = Record {
         level,
         target,
         message,
+        fields: &[],
+    };
+    write_stderr(&format_compact(
+        &record,
         config.timestamps,
         config.module_path,
-    );
+    ));
 }
 
-// Runtime-configurable versions
+// Runtime-configurable formatters
 
-fn log_pretty_runtime(
-    level: Level,
-    target: &str,
-    message: &str,
-    color: bool,
-    timestamps: bool,
-    module_path: bool,
-) {
-    let mut stderr = std::io::stderr().lock();
+fn format_pretty(record: &Record<'_>, color: bool, timestamps: bool, module_path: bool) -> String {
+    let Record {
+        level,
+        target,
+        message,
+        ..
+    } = *record;
+    let mut out = String::with_capacity(message.len() + 64);
 
     // Timestamp
     if timestamps {
         let now = chrono::Local::now();
-        let _ = write!(stderr, "{} ", now.format("%Y-%m-%d %H:%M:%S%.3f"));
+        let _ = write!(out, "{} ", now.format("%Y-%m-%d %H:%M:%S%.3f"));
     }
 
     // Level
     #[cfg(feature = "color")]
     if color {
-        let _ = write!(stderr, "{:5} ", level.colored());
+        let _ = write!(out, "{:5} ", level.colored());
     } else {
-        let _ = write!(stderr, "{:5} ", level.as_str());
+        let _ = write!(out, "{:5} ", level.as_str());
     }
 
     #[cfg(not(feature = "color"))]
     {
         let _ = color; // suppress warning
-        let _ = write!(stderr, "{:5} ", level.as_str());
+        let _ = write!(out, "{:5} ", level.as_str());
     }
 
     // Target
@@ -676,45 +1678,69 @@ fn log_pretty_runtime(
         #[cfg(feature = "color")]
         if color {
             use colored::Colorize;
-            let _ = write!(stderr, "{} ", target.dimmed());
+            let _ = write!(out, "{} ", target.dimmed());
         } else {
-            let _ = write!(stderr, "[{}] ", target);
+            let _ = write!(out, "[{}] ", target);
         }
 
         #[cfg(not(feature = "color"))]
-        let _ = write!(stderr, "[{}] ", target);
+        let _ = write!(out, "[{}] ", target);
     }
 
     // Message
-    let _ = writeln!(stderr, "{}", message);
+    out.push_str(message);
+    push_fields(&mut out, record.fields);
+    out
 }
 
-fn log_compact_runtime(
-    level: Level,
-    target: &str,
-    message: &str,
-    timestamps: bool,
-    module_path: bool,
-) {
-    let mut stderr = std::io::stderr().lock();
+fn format_compact(record: &Record<'_>, timestamps: bool, module_path: bool) -> String {
+    let Record {
+        level,
+        target,
+        message,
+        ..
+    } = *record;
+    let mut out = String::with_capacity(message.len() + 32);
 
     if timestamps {
         let now = chrono::Local::now();
-        let _ = write!(stderr, "{} ", now.format("%H:%M:%S"));
+        let _ = write!(out, "{} ", now.format("%H:%M:%S"));
     }
 
-    let _ = write!(stderr, "{} ", level.as_str().chars().next().unwrap_or('?'));
+    let _ = write!(out, "{} ", level.as_str().chars().next().unwrap_or('?'));
 
     if module_path && !target.is_empty() {
-        let _ = write!(stderr, "{}: ", target);
+        let _ = write!(out, "{}: ", target);
     }
 
-    let _ = writeln!(stderr, "{}", message);
+    out.push_str(message);
+    push_fields(&mut out, record.fields);
+    out
+}
+
+/// Append fields as ` key=value` pairs.
+fn push_fields(out: &mut String, fields: &[(&str, String)]) {
+    for (key, value) in fields {
+        let _ = write!(out, " {}={}", key, value);
+    }
 }
 
 #[cfg(feature = "json")]
-fn log_json(level: Level, target: &str, message: &str) {
+fn format_json(record: &Record<'_>) -> String {
     use serde::Serialize;
+    use serde::ser::SerializeMap;
+
+    struct Fields<'a>(&'a [(&'a str, String)]);
+
+    impl Serialize for Fields<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (key, value) in self.0 {
+                map.serialize_entry(key, value)?;
+            }
+            map.end()
+        }
+    }
 
     #[derive(Serialize)]
     struct LogEntry<'a> {
@@ -722,31 +1748,44 @@ fn log_json(level: Level, target: &str, message: &str) {
         level: &'a str,
         target: &'a str,
         message: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fields: Option<Fields<'a>>,
     }
 
     let entry = LogEntry {
         timestamp: chrono::Utc::now().to_rfc3339(),
-        level: level.as_str(),
-        target,
-        message,
+        level: record.level.as_str(),
+        target: record.target,
+        message: record.message,
+        fields: (!record.fields.is_empty()).then_some(Fields(record.fields)),
     };
 
-    if let Ok(json) = serde_json::to_string(&entry) {
-        eprintln!("{}", json);
-    }
+    serde_json::to_string(&entry).unwrap_or_default()
 }
 
 #[cfg(not(feature = "json"))]
-fn log_json(level: Level, target: &str, message: &str) {
+fn format_json(record: &Record<'_>) -> String {
     // Fallback without serde - manually escape JSON strings
     let timestamp = chrono::Utc::now().to_rfc3339();
-    eprintln!(
-        r#"{{"timestamp":"{}","level":"{}","target":"{}","message":"{}"}}"#,
+    let mut out = format!(
+        r#"{{"timestamp":"{}","level":"{}","target":"{}","message":"{}""#,
         timestamp,
-        level.as_str(),
-        escape_json(target),
-        escape_json(message)
+        record.level.as_str(),
+        escape_json(record.target),
+        escape_json(record.message)
     );
+    if !record.fields.is_empty() {
+        out.push_str(r#","fields":{"#);
+        for (i, (key, value)) in record.fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, r#""{}":"{}""#, escape_json(key), escape_json(value));
+        }
+        out.push('}');
+    }
+    out.push('}');
+    out
 }
 
 #[cfg(not(feature = "json"))]
@@ -777,13 +1816,18 @@ fn escape_json(s: &str) -> String {
 /// Only enabled when `ARMATURE_DEBUG=1` or `ARMATURE_LOG_LEVEL=trace`.
 #[macro_export]
 macro_rules! trace {
-    (target: $target:expr, $($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Trace) {
-            $crate::log($crate::Level::Trace, $target, &format!($($arg)+));
+    (target: $target:expr, $($arg:tt)+) => {{
+        let target: &str = $target;
+        if $crate::is_level_enabled_for($crate::Level::Trace, target)
+            && $crate::is_sampled_in(target)
+        {
+            $crate::log($crate::Level::Trace, target, &format!($($arg)+));
         }
-    };
+    }};
     ($($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Trace) {
+        if $crate::is_level_enabled_for($crate::Level::Trace, module_path!())
+            && $crate::is_sampled_in(module_path!())
+        {
             $crate::log($crate::Level::Trace, module_path!(), &format!($($arg)+));
         }
     };
@@ -806,13 +1850,20 @@ macro_rules! trace {
 /// ```
 #[macro_export]
 macro_rules! debug {
-    (target: $target:expr, $($arg:tt)+) => {
-        if $crate::is_debug_enabled() || $crate::is_level_enabled($crate::Level::Debug) {
-            $crate::log($crate::Level::Debug, $target, &format!($($arg)+));
+    (target: $target:expr, $($arg:tt)+) => {{
+        let target: &str = $target;
+        if ($crate::is_debug_enabled()
+            || $crate::is_level_enabled_for($crate::Level::Debug, target))
+            && $crate::is_sampled_in(target)
+        {
+            $crate::log($crate::Level::Debug, target, &format!($($arg)+));
         }
-    };
+    }};
     ($($arg:tt)+) => {
-        if $crate::is_debug_enabled() || $crate::is_level_enabled($crate::Level::Debug) {
+        if ($crate::is_debug_enabled()
+            || $crate::is_level_enabled_for($crate::Level::Debug, module_path!()))
+            && $crate::is_sampled_in(module_path!())
+        {
             $crate::log($crate::Level::Debug, module_path!(), &format!($($arg)+));
         }
     };
@@ -821,13 +1872,18 @@ macro_rules! debug {
 /// Log an info message.
 #[macro_export]
 macro_rules! info {
-    (target: $target:expr, $($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Info) {
-            $crate::log($crate::Level::Info, $target, &format!($($arg)+));
+    (target: $target:expr, $($arg:tt)+) => {{
+        let target: &str = $target;
+        if $crate::is_level_enabled_for($crate::Level::Info, target)
+            && $crate::is_sampled_in(target)
+        {
+            $crate::log($crate::Level::Info, target, &format!($($arg)+));
         }
-    };
+    }};
     ($($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Info) {
+        if $crate::is_level_enabled_for($crate::Level::Info, module_path!())
+            && $crate::is_sampled_in(module_path!())
+        {
             $crate::log($crate::Level::Info, module_path!(), &format!($($arg)+));
         }
     };
@@ -836,13 +1892,18 @@ macro_rules! info {
 /// Log a warning message.
 #[macro_export]
 macro_rules! warn {
-    (target: $target:expr, $($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Warn) {
-            $crate::log($crate::Level::Warn, $target, &format!($($arg)+));
+    (target: $target:expr, $($arg:tt)+) => {{
+        let target: &str = $target;
+        if $crate::is_level_enabled_for($crate::Level::Warn, target)
+            && $crate::is_sampled_in(target)
+        {
+            $crate::log($crate::Level::Warn, target, &format!($($arg)+));
         }
-    };
+    }};
     ($($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Warn) {
+        if $crate::is_level_enabled_for($crate::Level::Warn, module_path!())
+            && $crate::is_sampled_in(module_path!())
+        {
             $crate::log($crate::Level::Warn, module_path!(), &format!($($arg)+));
         }
     };
@@ -851,13 +1912,18 @@ macro_rules! warn {
 /// Log an error message.
 #[macro_export]
 macro_rules! error {
-    (target: $target:expr, $($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Error) {
-            $crate::log($crate::Level::Error, $target, &format!($($arg)+));
+    (target: $target:expr, $($arg:tt)+) => {{
+        let target: &str = $target;
+        if $crate::is_level_enabled_for($crate::Level::Error, target)
+            && $crate::is_sampled_in(target)
+        {
+            $crate::log($crate::Level::Error, target, &format!($($arg)+));
         }
-    };
+    }};
     ($($arg:tt)+) => {
-        if $crate::is_level_enabled($crate::Level::Error) {
+        if $crate::is_level_enabled_for($crate::Level::Error, module_path!())
+            && $crate::is_sampled_in(module_path!())
+        {
             $crate::log($crate::Level::Error, module_path!(), &format!($($arg)+));
         }
     };
@@ -872,7 +1938,9 @@ pub mod tracing_compat {
     //! Tracing compatibility layer.
     //!
     //! When the `tracing` feature is enabled, this module provides
-    //! a subscriber that respects `ARMATURE_DEBUG`.
+    //! a subscriber that respects `ARMATURE_DEBUG`. With the `otel` feature,
+    //! the subscriber also forwards events to the collector configured with
+    //! [`otel::init`](crate::otel::init).
 
     use super::*;
 
@@ -893,9 +1961,14 @@ pub mod tracing_compat {
 
         let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
 
-        tracing_subscriber::registry()
+        let registry = tracing_subscriber::registry()
             .with(filter)
-            .with(fmt::layer().with_ansi(config.color))
+            .with(fmt::layer().with_ansi(config.color));
+
+        #[cfg(feature = "otel")]
+        let registry = registry.with(crate::otel::tracing_layer());
+
+        registry
     }
 }
 
@@ -935,6 +2008,7 @@ mod tests {
 
     #[test]
     fn test_set_level() {
+        let _lock = GLOBAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let original = current_level();
 
         set_level(Level::Error);
@@ -959,6 +2033,259 @@ mod tests {
         set_debug(original);
     }
 
+    fn record<'a>(
+        level: Level,
+        target: &'a str,
+        message: &'a str,
+        fields: &'a [(&'a str, String)],
+    ) -> Record<'a> {
+        Record {
+            level,
+            target,
+            message,
+            fields,
+        }
+    }
+
+    #[test]
+    fn test_settings_pack_roundtrip() {
+        let settings = Settings {
+            level: Level::Warn,
+            format: Format::Compact,
+            color: true,
+            timestamps: false,
+            module_path: true,
+            debug: true,
+        };
+        assert_eq!(Settings::unpack(settings.pack()), settings);
+        assert_eq!(
+            Settings::unpack(Settings::DEFAULT.pack()),
+            Settings::DEFAULT
+        );
+    }
+
+    #[test]
+    fn test_apply_from_env() {
+        let _lock = GLOBAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let original = Settings::load();
+
+        let config = apply_from_env();
+        let settings = Settings::load();
+        assert_eq!(settings, Settings::from(&config));
+        assert_eq!(current_level(), config.level);
+        assert_eq!(current_format(), config.format);
+
+        original.store();
+    }
+
+    #[test]
+    fn test_format_line_plain() {
+        let line = format_line(
+            &record(Level::Warn, "app", "disk low", &[]),
+            Format::Compact,
+            false,
+            false,
+            true,
+        );
+        assert_eq!(line, "W app: disk low");
+
+        let line = format_line(
+            &record(Level::Info, "app", "ready", &[]),
+            Format::Pretty,
+            false,
+            false,
+            true,
+        );
+        assert_eq!(line, "INFO  [app] ready");
+    }
+
+    #[test]
+    fn test_format_line_fields() {
+        let fields = [("user", "alice".to_string()), ("status", "200".to_string())];
+
+        let line = format_line(
+            &record(Level::Info, "app", "login", &fields),
+            Format::Compact,
+            false,
+            false,
+            true,
+        );
+        assert_eq!(line, "I app: login user=alice status=200");
+
+        let line = format_line(
+            &record(Level::Info, "app", "login", &fields),
+            Format::Json,
+            false,
+            false,
+            true,
+        );
+        assert!(line.contains(r#""fields":{"user":"alice","status":"200"}"#));
+    }
+
+    /// Serializes tests that install global sinks or redaction rules.
+    static GLOBAL_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn temp_log(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "armature-log-{}-{}-{}.log",
+            name,
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+    }
+
+    #[test]
+    fn test_file_sink_receives_logs() {
+        let _lock = GLOBAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = temp_log("lib");
+
+        let sink = FileSink::open(&path, Rotation::Never, 1, Duration::from_secs(60)).unwrap();
+        set_file_sink(Some(sink));
+
+        log(Level::Error, "file_test", "written to file");
+        flush();
+        set_file_sink(None);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("written to file"));
+        assert!(!contents.contains('\u{1b}'));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_redaction_before_output() {
+        let _lock = GLOBAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = temp_log("redact");
+
+        let sink = FileSink::open(&path, Rotation::Never, 1, Duration::from_secs(60)).unwrap();
+        set_file_sink(Some(sink));
+        configure()
+            .redact(vec!["password", "authorization", "token"])
+            .redact_pattern(Regex::new(r"Bearer\s+[A-Za-z0-9._~+/-]+=*").unwrap())
+            .apply();
+
+        log_kv(
+            Level::Error,
+            "redact_test",
+            "auth header was Bearer eyJhbGciOiJIUzI1NiJ9.e30.sig",
+            &[("user", &"alice"), ("password", &"hunter2")],
+        );
+        flush();
+        set_file_sink(None);
+        clear_redactions();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("alice"));
+        assert!(!contents.contains("hunter2"));
+        assert!(!contents.contains("eyJhbGciOiJIUzI1NiJ9"));
+        assert!(contents.contains("auth header was ***"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_sampling_drops_and_reports() {
+        let _lock = GLOBAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = temp_log("sample");
+
+        let sink = FileSink::open(&path, Rotation::Never, 1, Duration::from_secs(60)).unwrap();
+        set_file_sink(Some(sink));
+        configure()
+            .sample("sample_test", SampleRate::OneIn(10))
+            .apply();
+
+        assert_eq!(
+            sample_rate("sample_test::inner"),
+            Some(SampleRate::OneIn(10))
+        );
+        assert_eq!(sample_rate("sample_test_other"), None);
+
+        for i in 0..100 {
+            error!(target: "sample_test::inner", "noisy {}", i);
+        }
+        error!(target: "sample_test_other", "not sampled");
+        flush();
+        clear_sample_rate("sample_test");
+        set_file_sink(None);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.matches("noisy").count(), 10);
+        assert!(contents.contains("not sampled"));
+        let summary = contents
+            .lines()
+            .find(|line| line.contains("dropped by sampling"))
+            .unwrap();
+        assert!(summary.contains("90"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct Capture(Arc<std::sync::Mutex<Vec<String>>>);
+
+    impl Sink for Capture {
+        fn write(&self, level: Level, _target: &str, message: &str, fields: &[(&str, String)]) {
+            let mut line = format!("{} {}", level.as_str(), message);
+            for (key, value) in fields {
+                let _ = write!(line, " {}={}", key, value);
+            }
+            self.0.lock().unwrap().push(line);
+        }
+    }
+
+    #[test]
+    fn test_multiple_sinks() {
+        let _lock = GLOBAL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let path = temp_log("sinks");
+
+        let file = FileSink::open(&path, Rotation::Never, 1, Duration::from_secs(60)).unwrap();
+        let capture = Capture::default();
+        configure()
+            .sink(capture.clone())
+            .sink(FormatSink::file(file, Format::Json).level(Level::Error))
+            .redact(vec!["secret"])
+            .apply();
+        assert_eq!(sinks().len(), 2);
+
+        log_kv(Level::Warn, "sink_test", "warned", &[("secret", &"s3cr3t")]);
+        log_kv(Level::Error, "sink_test", "failed", &[("code", &500)]);
+        flush();
+        clear_sinks();
+        clear_redactions();
+
+        assert_eq!(
+            *capture.0.lock().unwrap(),
+            vec!["WARN warned secret=***", "ERROR failed code=500"]
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 1);
+        assert!(contents.contains(r#""message":"failed""#));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_target_level_overrides() {
+        set_target_level("override_test::router", Level::Trace);
+        set_target_level("override_test", Level::Off);
+
+        // Longest prefix wins
+        assert_eq!(
+            target_level("override_test::router::trie"),
+            Some(Level::Trace)
+        );
+        assert_eq!(target_level("override_test::db"), Some(Level::Off));
+        assert!(is_level_enabled_for(Level::Trace, "override_test::router"));
+        assert!(!is_level_enabled_for(Level::Error, "override_test::db"));
+
+        // Prefixes only match whole path segments
+        assert_eq!(target_level("override_test_other"), None);
+        assert!(is_level_enabled_for(Level::Error, "override_test_other"));
+
+        clear_target_level("override_test");
+        assert_eq!(target_level("override_test::db"), None);
+        clear_target_level("override_test::router");
+        assert_eq!(target_level("override_test::router"), None);
+    }
+
     #[test]
     fn test_macros_compile() {
         // Just verify macros compile correctly
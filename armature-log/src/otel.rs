@@ -0,0 +1,223 @@
+//! OpenTelemetry log export.
+//!
+//! With the `otel` feature, [`OtelSink`] turns each Armature log record into an
+//! OpenTelemetry `LogRecord`: the level becomes the severity, the message the
+//! body, and structured fields become attributes. Records are batched by the
+//! SDK and exported over OTLP/gRPC from a background thread; if the collector
+//! is unreachable, failed batches are dropped and logging carries on.
+//!
+//! The exporter uses tonic, so [`init`] must be called from within a Tokio
+//! runtime.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # #[tokio::main]
+//! # async fn main() {
+//! let _otel = armature_log::otel::init("http://localhost:4317").unwrap();
+//!
+//! armature_log::info!("exported to the collector and still written to stderr");
+//! # }
+//! ```
+
+use crate::{Level, LogConfig, Sink, add_sink, default_sinks, set_sinks, sinks};
+use once_cell::sync::Lazy;
+use opentelemetry::Key;
+use opentelemetry::logs::{AnyValue, LogRecord as _, Logger as _, LoggerProvider as _, Severity};
+use opentelemetry_otlp::{ExporterBuildError, LogExporter, WithExportConfig};
+use opentelemetry_sdk::logs::{SdkLogger, SdkLoggerProvider};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+/// Instrumentation scope name reported with exported records.
+pub const SCOPE_NAME: &str = "armature-log";
+
+/// A sink that emits records through an OpenTelemetry logger provider.
+pub struct OtelSink {
+    provider: SdkLoggerProvider,
+    logger: SdkLogger,
+    level: Level,
+}
+
+impl OtelSink {
+    /// Create a sink that emits records at or above `level` through `provider`.
+    pub fn new(provider: &SdkLoggerProvider, level: Level) -> Self {
+        Self {
+            provider: provider.clone(),
+            logger: provider.logger(SCOPE_NAME),
+            level,
+        }
+    }
+
+    /// Get the minimum exported level.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+}
+
+impl std::fmt::Debug for OtelSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtelSink")
+            .field("level", &self.level)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sink for OtelSink {
+    fn write(&self, level: Level, target: &str, message: &str, fields: &[(&str, String)]) {
+        if level < self.level {
+            return;
+        }
+        let Some(severity) = severity(level) else {
+            return;
+        };
+
+        let mut record = self.logger.create_log_record();
+        record.set_timestamp(SystemTime::now());
+        record.set_observed_timestamp(SystemTime::now());
+        record.set_severity_number(severity);
+        record.set_severity_text(level.as_str());
+        record.set_target(target.to_string());
+        record.set_body(AnyValue::from(message.to_string()));
+        for (key, value) in fields {
+            record.add_attribute(Key::new(key.to_string()), AnyValue::from(value.clone()));
+        }
+
+        // The batch processor drops records when its queue is full or the
+        // collector rejects them; neither is reported back to the caller.
+        self.logger.emit(record);
+    }
+
+    fn flush(&self) {
+        let _ = self.provider.force_flush();
+    }
+}
+
+/// Map an Armature level to an OpenTelemetry severity.
+pub fn severity(level: Level) -> Option<Severity> {
+    match level {
+        Level::Trace => Some(Severity::Trace),
+        Level::Debug => Some(Severity::Debug),
+        Level::Info => Some(Severity::Info),
+        Level::Warn => Some(Severity::Warn),
+        Level::Error => Some(Severity::Error),
+        Level::Off => None,
+    }
+}
+
+/// Provider installed by [`init`], used by the tracing bridge.
+static PROVIDER: Lazy<RwLock<Option<SdkLoggerProvider>>> = Lazy::new(|| RwLock::new(None));
+
+/// Export logs to an OTLP/gRPC collector at `endpoint`.
+///
+/// Registers an [`OtelSink`] whose level comes from `ARMATURE_LOG_LEVEL`
+/// (default `info`). If no sinks were registered yet, the current stderr and
+/// file output are kept as [`FormatSink`](crate::FormatSink)s alongside it.
+/// Records emitted through `tracing` reach the collector as well when the
+/// subscriber from [`tracing_compat::subscriber`](crate::tracing_compat::subscriber)
+/// is installed after this call.
+///
+/// Hold the returned guard until shutdown; dropping it flushes pending
+/// batches and stops the exporter.
+pub fn init(endpoint: impl Into<String>) -> Result<OtelGuard, ExporterBuildError> {
+    let exporter = LogExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()?;
+
+    let provider = SdkLoggerProvider::builder()
+        .with_batch_exporter(exporter)
+        .build();
+
+    let level = LogConfig::from_env().level;
+    if sinks().is_empty() {
+        set_sinks(default_sinks());
+    }
+    add_sink(Arc::new(OtelSink::new(&provider, level)));
+
+    *PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = Some(provider.clone());
+    Ok(OtelGuard { provider })
+}
+
+/// Get the logger provider installed by [`init`].
+pub fn provider() -> Option<SdkLoggerProvider> {
+    PROVIDER.read().ok().and_then(|provider| provider.clone())
+}
+
+/// A `tracing` layer that forwards events to the provider installed by [`init`].
+pub fn tracing_layer() -> Option<
+    opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge<SdkLoggerProvider, SdkLogger>,
+> {
+    provider().map(|provider| {
+        opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(&provider)
+    })
+}
+
+/// Flushes and shuts down the OpenTelemetry exporter when dropped.
+#[derive(Debug)]
+#[must_use = "the exporter shuts down when the guard is dropped"]
+pub struct OtelGuard {
+    provider: SdkLoggerProvider,
+}
+
+impl OtelGuard {
+    /// Get the logger provider.
+    pub fn provider(&self) -> &SdkLoggerProvider {
+        &self.provider
+    }
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        crate::flush();
+        let _ = self.provider.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_sdk::logs::InMemoryLogExporter;
+
+    #[test]
+    fn test_severity_mapping() {
+        assert_eq!(severity(Level::Trace), Some(Severity::Trace));
+        assert_eq!(severity(Level::Warn), Some(Severity::Warn));
+        assert_eq!(severity(Level::Error), Some(Severity::Error));
+        assert_eq!(severity(Level::Off), None);
+    }
+
+    #[test]
+    fn test_sink_exports_record() {
+        let exporter = InMemoryLogExporter::default();
+        let provider = SdkLoggerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let sink = OtelSink::new(&provider, Level::Info);
+
+        sink.write(Level::Debug, "app", "below level", &[]);
+        sink.write(
+            Level::Warn,
+            "app::db",
+            "slow query",
+            &[("duration_ms", "250".to_string())],
+        );
+
+        let logs = exporter.get_emitted_logs().unwrap();
+        assert_eq!(logs.len(), 1);
+
+        let record = &logs[0].record;
+        assert_eq!(record.severity_number(), Some(Severity::Warn));
+        assert_eq!(record.target().map(|t| t.as_ref()), Some("app::db"));
+        assert_eq!(
+            record.body(),
+            Some(&AnyValue::from("slow query".to_string()))
+        );
+        assert!(
+            record
+                .attributes_iter()
+                .any(|(key, value)| key.as_str() == "duration_ms"
+                    && *value == AnyValue::from("250".to_string()))
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Redaction of sensitive values.
+//!
+//! Field redaction masks the value of any structured field whose name contains
+//! a registered pattern (case-insensitive), e.g. `password` also covers
+//! `db_password`. Pattern redaction masks every regex match in the message and
+//! in the values of fields that were not already masked. Redaction runs before
+//! a record is formatted, so raw secrets never reach a sink.
+
+use regex::Regex;
+use std::borrow::Cow;
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "***";
+
+/// Set of field names and message patterns to redact.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    fields: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Create an empty redactor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mask fields whose name contains `pattern` (case-insensitive).
+    pub fn add_field(&mut self, pattern: &str) {
+        let pattern = pattern.to_lowercase();
+        if !self.fields.contains(&pattern) {
+            self.fields.push(pattern);
+        }
+    }
+
+    /// Mask every match of `pattern` in messages and field values.
+    pub fn add_pattern(&mut self, pattern: Regex) {
+        self.patterns.push(pattern);
+    }
+
+    /// Check if nothing is configured for redaction.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Check if a field's value should be masked based on its name.
+    pub fn is_sensitive_field(&self, name: &str) -> bool {
+        if self.fields.is_empty() {
+            return false;
+        }
+        let name = name.to_lowercase();
+        self.fields.iter().any(|pattern| name.contains(pattern))
+    }
+
+    /// Mask pattern matches in a message.
+    pub fn redact_message<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        let mut result = Cow::Borrowed(message);
+        for pattern in &self.patterns {
+            if let Cow::Owned(replaced) = pattern.replace_all(&result, REDACTED) {
+                result = Cow::Owned(replaced);
+            }
+        }
+        result
+    }
+
+    /// Produce the value to log for a field.
+    ///
+    /// Sensitive fields are masked without formatting their value.
+    pub fn redact_field(&self, name: &str, value: &dyn std::fmt::Display) -> String {
+        if self.is_sensitive_field(name) {
+            return REDACTED.to_string();
+        }
+        let value = value.to_string();
+        match self.redact_message(&value) {
+            Cow::Borrowed(_) => value,
+            Cow::Owned(redacted) => redacted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_redaction() {
+        let mut redactor = Redactor::new();
+        redactor.add_field("password");
+        redactor.add_field("Authorization");
+
+        assert!(redactor.is_sensitive_field("password"));
+        assert!(redactor.is_sensitive_field("db_Password"));
+        assert!(redactor.is_sensitive_field("authorization"));
+        assert!(!redactor.is_sensitive_field("username"));
+
+        assert_eq!(redactor.redact_field("password", &"hunter2"), REDACTED);
+        assert_eq!(redactor.redact_field("user_id", &42), "42");
+    }
+
+    #[test]
+    fn test_pattern_redaction() {
+        let mut redactor = Redactor::new();
+        redactor.add_pattern(Regex::new(r"Bearer\s+[A-Za-z0-9._~+/-]+=*").unwrap());
+
+        let message = "calling api with Bearer eyJhbGciOi.abc.def";
+        assert_eq!(redactor.redact_message(message), "calling api with ***");
+        assert!(matches!(
+            redactor.redact_message("no secrets"),
+            Cow::Borrowed(_)
+        ));
+
+        assert_eq!(
+            redactor.redact_field("header", &"Bearer abc123"),
+            REDACTED.to_string()
+        );
+    }
+}
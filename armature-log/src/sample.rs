@@ -0,0 +1,156 @@
+//! Log sampling.
+//!
+//! A [`Sampler`] decides whether a record from a noisy target is kept, using
+//! only atomic counters so the decision is cheap enough to make before the
+//! message is formatted. Dropped records are counted and reported in periodic
+//! summary lines.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Minimum interval between sampling summary lines for a target.
+pub const SAMPLE_SUMMARY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How many records to keep from a sampled target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleRate {
+    /// Keep one record out of every `n` (the first, then every `n`th)
+    OneIn(u64),
+    /// Keep at most `n` records per second
+    PerSecond(u64),
+}
+
+/// Sampling state for one target.
+#[derive(Debug)]
+pub(crate) struct Sampler {
+    rate: SampleRate,
+    /// Records seen (`OneIn`) or kept in the current window (`PerSecond`)
+    count: AtomicU64,
+    /// Current one-second window (`PerSecond` only)
+    window: AtomicU64,
+    dropped: AtomicU64,
+    last_summary_ms: AtomicU64,
+}
+
+impl Sampler {
+    pub(crate) fn new(rate: SampleRate) -> Self {
+        Self {
+            rate,
+            count: AtomicU64::new(0),
+            window: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            last_summary_ms: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn rate(&self) -> SampleRate {
+        self.rate
+    }
+
+    /// Decide whether to keep a record seen at `now_ms`.
+    pub(crate) fn sample(&self, now_ms: u64) -> bool {
+        let keep = match self.rate {
+            SampleRate::OneIn(n) => {
+                n <= 1 || self.count.fetch_add(1, Ordering::Relaxed).is_multiple_of(n)
+            }
+            SampleRate::PerSecond(n) => {
+                let window = now_ms / 1000;
+                let current = self.window.load(Ordering::Relaxed);
+                // Whichever thread moves the window forward resets the count;
+                // a few records around the boundary may be counted either way.
+                if window > current
+                    && self
+                        .window
+                        .compare_exchange(current, window, Ordering::Relaxed, Ordering::Relaxed)
+                        .is_ok()
+                {
+                    self.count.store(0, Ordering::Relaxed);
+                }
+                self.count.fetch_add(1, Ordering::Relaxed) < n
+            }
+        };
+
+        if !keep {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        keep
+    }
+
+    /// Get the number of records dropped since the last summary.
+    #[cfg(test)]
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Take the dropped count if a summary is due at `now_ms`.
+    ///
+    /// Returns `None` when nothing was dropped or another summary was taken
+    /// within [`SAMPLE_SUMMARY_INTERVAL`], unless `force` is set.
+    pub(crate) fn take_summary(&self, now_ms: u64, force: bool) -> Option<u64> {
+        if self.dropped.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+
+        if !force {
+            let last = self.last_summary_ms.load(Ordering::Relaxed);
+            if now_ms.saturating_sub(last) < SAMPLE_SUMMARY_INTERVAL.as_millis() as u64
+                || self
+                    .last_summary_ms
+                    .compare_exchange(last, now_ms, Ordering::Relaxed, Ordering::Relaxed)
+                    .is_err()
+            {
+                return None;
+            }
+        }
+
+        match self.dropped.swap(0, Ordering::Relaxed) {
+            0 => None,
+            dropped => Some(dropped),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_in() {
+        let sampler = Sampler::new(SampleRate::OneIn(10));
+        let kept = (0..100).filter(|_| sampler.sample(0)).count();
+
+        assert_eq!(kept, 10);
+        assert_eq!(sampler.dropped(), 90);
+    }
+
+    #[test]
+    fn test_per_second() {
+        let sampler = Sampler::new(SampleRate::PerSecond(5));
+
+        let kept = (0..20).filter(|_| sampler.sample(1_000)).count();
+        assert_eq!(kept, 5);
+
+        // A new window starts a new budget
+        let kept = (0..20).filter(|_| sampler.sample(2_500)).count();
+        assert_eq!(kept, 5);
+        assert_eq!(sampler.dropped(), 30);
+    }
+
+    #[test]
+    fn test_summary_interval() {
+        let sampler = Sampler::new(SampleRate::OneIn(2));
+        let interval = SAMPLE_SUMMARY_INTERVAL.as_millis() as u64;
+
+        for _ in 0..4 {
+            sampler.sample(0);
+        }
+        assert_eq!(sampler.take_summary(interval - 1, false), None);
+        assert_eq!(sampler.take_summary(interval, false), Some(2));
+        assert_eq!(sampler.take_summary(interval, false), None);
+
+        sampler.sample(interval);
+        sampler.sample(interval);
+        assert_eq!(sampler.take_summary(interval + 1, false), None);
+        assert_eq!(sampler.take_summary(interval + 1, true), Some(1));
+    }
+}
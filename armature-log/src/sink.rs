@@ -0,0 +1,255 @@
+//! Pluggable log sinks.
+//!
+//! By default records go to stderr (and the optional file sink) using the
+//! global format. Registering one or more [`Sink`]s replaces that output:
+//! every record that passes the level, sampling, and redaction steps is
+//! handed to each registered sink, which decides how to filter and format it.
+
+use crate::{FileSink, Format, Level, Record, format_line, write_stderr};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Destination for log records.
+///
+/// Records are redacted before they reach a sink. Implementations must not
+/// log through `armature_log` themselves.
+pub trait Sink: Send + Sync + Debug {
+    /// Write one record.
+    fn write(&self, level: Level, target: &str, message: &str, fields: &[(&str, String)]);
+
+    /// Flush buffered output.
+    fn flush(&self) {}
+}
+
+/// Where a [`FormatSink`] writes its lines.
+#[derive(Debug, Clone)]
+enum Destination {
+    Stderr,
+    File(Arc<FileSink>),
+}
+
+/// A sink that formats records with its own settings.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use armature_log::{configure, FileSink, Format, FormatSink, Level, Rotation};
+/// use std::time::Duration;
+///
+/// let file = FileSink::open("app.log", Rotation::Daily, 7, Duration::from_secs(1)).unwrap();
+///
+/// configure()
+///     .level(Level::Debug)
+///     .sink(FormatSink::stderr(Format::Pretty).color(true).level(Level::Info))
+///     .sink(FormatSink::file(file, Format::Json))
+///     .apply();
+/// ```
+#[derive(Debug, Clone)]
+pub struct FormatSink {
+    destination: Destination,
+    format: Format,
+    level: Option<Level>,
+    color: bool,
+    timestamps: bool,
+    module_path: bool,
+}
+
+impl FormatSink {
+    /// Write to stderr in the given format.
+    pub fn stderr(format: Format) -> Self {
+        Self::new(Destination::Stderr, format)
+    }
+
+    /// Write to a file in the given format.
+    pub fn file(file: Arc<FileSink>, format: Format) -> Self {
+        Self::new(Destination::File(file), format)
+    }
+
+    fn new(destination: Destination, format: Format) -> Self {
+        Self {
+            destination,
+            format,
+            level: None,
+            color: false,
+            timestamps: true,
+            module_path: true,
+        }
+    }
+
+    /// Only write records at or above this level.
+    ///
+    /// Records are filtered by the global level (and per-target levels)
+    /// first, so a sink level can only narrow what is written.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Enable or disable ANSI colors (pretty format only).
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Enable or disable timestamps.
+    pub fn timestamps(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    /// Enable or disable the target in output.
+    pub fn module_path(mut self, enabled: bool) -> Self {
+        self.module_path = enabled;
+        self
+    }
+
+    /// Get the output format.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+}
+
+impl Sink for FormatSink {
+    fn write(&self, level: Level, target: &str, message: &str, fields: &[(&str, String)]) {
+        if self.level.is_some_and(|min| level < min) {
+            return;
+        }
+
+        let record = Record {
+            level,
+            target,
+            message,
+            fields,
+        };
+        let line = format_line(
+            &record,
+            self.format,
+            self.color,
+            self.timestamps,
+            self.module_path,
+        );
+
+        match &self.destination {
+            Destination::Stderr => write_stderr(&line),
+            Destination::File(file) => file.write_line(&line),
+        }
+    }
+
+    fn flush(&self) {
+        match &self.destination {
+            Destination::Stderr => {
+                use std::io::Write;
+                let _ = std::io::stderr().flush();
+            }
+            Destination::File(file) => file.flush(),
+        }
+    }
+}
+
+/// An owned, redacted record queued for registered sinks.
+#[derive(Debug)]
+pub(crate) struct SinkRecord {
+    pub(crate) sinks: Arc<Vec<Arc<dyn Sink>>>,
+    pub(crate) level: Level,
+    pub(crate) target: String,
+    pub(crate) message: String,
+    pub(crate) fields: Vec<(String, String)>,
+}
+
+impl SinkRecord {
+    pub(crate) fn write(self) {
+        let fields: Vec<(&str, String)> = self
+            .fields
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.clone()))
+            .collect();
+        write_to_sinks(
+            &self.sinks,
+            self.level,
+            &self.target,
+            &self.message,
+            &fields,
+        );
+    }
+}
+
+/// Hand a record to every sink.
+pub(crate) fn write_to_sinks(
+    sinks: &[Arc<dyn Sink>],
+    level: Level,
+    target: &str,
+    message: &str,
+    fields: &[(&str, String)],
+) {
+    for sink in sinks {
+        sink.write(level, target, message, fields);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Capture(Mutex<Vec<String>>);
+
+    impl Sink for Capture {
+        fn write(&self, level: Level, target: &str, message: &str, fields: &[(&str, String)]) {
+            let fields: Vec<String> = fields.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            self.0.lock().unwrap().push(format!(
+                "{} {} {} {}",
+                level.as_str(),
+                target,
+                message,
+                fields.join(",")
+            ));
+        }
+    }
+
+    #[test]
+    fn test_sink_record_reaches_every_sink() {
+        let first = Arc::new(Capture::default());
+        let second = Arc::new(Capture::default());
+        let sinks: Vec<Arc<dyn Sink>> = vec![first.clone(), second.clone()];
+
+        SinkRecord {
+            sinks: Arc::new(sinks),
+            level: Level::Info,
+            target: "app".to_string(),
+            message: "hello".to_string(),
+            fields: vec![("user".to_string(), "alice".to_string())],
+        }
+        .write();
+
+        assert_eq!(*first.0.lock().unwrap(), vec!["INFO app hello user=alice"]);
+        assert_eq!(*second.0.lock().unwrap(), vec!["INFO app hello user=alice"]);
+    }
+
+    #[test]
+    fn test_format_sink_level_and_format() {
+        let path = std::env::temp_dir().join(format!(
+            "armature-log-sink-{}-{}.log",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let file = FileSink::open(
+            &path,
+            crate::Rotation::Never,
+            1,
+            std::time::Duration::from_secs(60),
+        )
+        .unwrap();
+        let sink = FormatSink::file(file, Format::Compact)
+            .level(Level::Warn)
+            .timestamps(false);
+
+        sink.write(Level::Info, "app", "skipped", &[]);
+        sink.write(Level::Error, "app", "kept", &[("code", "500".to_string())]);
+        sink.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, "E app: kept code=500\n");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,220 @@
+//! Background log writer.
+//!
+//! With the async writer enabled, [`log`](crate::log) formats each record (or,
+//! with registered sinks, copies it) and hands it to a dedicated thread
+//! through a bounded channel instead of taking the stderr lock (or file lock)
+//! on the calling thread.
+
+use crate::Output;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// What to do when the async writer's queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for space in the queue (no lines are lost)
+    #[default]
+    Block,
+    /// Discard the line and count it in [`AsyncWriter::dropped`]
+    Drop,
+}
+
+pub(crate) enum Message {
+    Write(Output),
+    Flush(SyncSender<()>),
+}
+
+/// A dedicated thread that writes queued log output.
+///
+/// Dropping the writer closes the queue and waits for the worker thread to
+/// write everything still queued.
+#[derive(Debug)]
+pub struct AsyncWriter {
+    sender: Option<SyncSender<Message>>,
+    policy: OverflowPolicy,
+    capacity: usize,
+    dropped: AtomicU64,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AsyncWriter {
+    /// Spawn a writer thread with a queue of `capacity` lines.
+    pub fn spawn(capacity: usize, policy: OverflowPolicy) -> io::Result<Arc<Self>> {
+        let (writer, receiver) = Self::new(capacity, policy);
+
+        let handle = std::thread::Builder::new()
+            .name("armature-log-writer".to_string())
+            .spawn(move || run(receiver))?;
+
+        *writer.worker.lock().unwrap_or_else(|e| e.into_inner()) = Some(handle);
+        Ok(Arc::new(writer))
+    }
+
+    fn new(capacity: usize, policy: OverflowPolicy) -> (Self, Receiver<Message>) {
+        let capacity = capacity.max(1);
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+
+        let writer = Self {
+            sender: Some(sender),
+            policy,
+            capacity,
+            dropped: AtomicU64::new(0),
+            worker: Mutex::new(None),
+        };
+        (writer, receiver)
+    }
+
+    /// Get the queue capacity.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the overflow policy.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Get the number of lines discarded because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queue output for the worker thread.
+    pub(crate) fn send(&self, output: Output) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        let result = match self.policy {
+            OverflowPolicy::Block => sender.send(Message::Write(output)).map_err(|e| e.0),
+            OverflowPolicy::Drop => match sender.try_send(Message::Write(output)) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    Ok(())
+                }
+                Err(TrySendError::Disconnected(message)) => Err(message),
+            },
+        };
+
+        // The worker is gone; write on the calling thread rather than lose the line
+        if let Err(Message::Write(output)) = result {
+            output.write();
+        }
+    }
+
+    /// Block until every line queued so far has been written.
+    pub fn flush(&self) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        let (ack, done) = mpsc::sync_channel(1);
+        if sender.send(Message::Flush(ack)).is_ok() {
+            let _ = done.recv();
+        }
+    }
+}
+
+impl Drop for AsyncWriter {
+    fn drop(&mut self) {
+        // Closing the channel lets the worker drain the queue and exit
+        self.sender.take();
+
+        let worker = self
+            .worker
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(worker) = worker {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run(receiver: Receiver<Message>) {
+    for message in receiver {
+        match message {
+            Message::Write(output) => output.write(),
+            Message::Flush(ack) => {
+                let _ = ack.send(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FileSink, Rotation};
+    use std::time::Duration;
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "armature-log-writer-{}-{}-{}.log",
+            name,
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+    }
+
+    fn file_output(sink: &Arc<FileSink>, line: &str) -> Output {
+        Output::Lines {
+            stderr: None,
+            file: Some((sink.clone(), line.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_flush_drains_queue() {
+        let path = temp_file("flush");
+        let sink = FileSink::open(&path, Rotation::Never, 1, Duration::from_secs(60)).unwrap();
+        let writer = AsyncWriter::spawn(16, OverflowPolicy::Block).unwrap();
+
+        for i in 0..100 {
+            writer.send(file_output(&sink, &format!("line {}", i)));
+        }
+        writer.flush();
+        sink.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 100);
+        assert_eq!(writer.dropped(), 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_drop_writes_remaining_lines() {
+        let path = temp_file("drop");
+        let sink = FileSink::open(&path, Rotation::Never, 1, Duration::from_secs(60)).unwrap();
+        let writer = AsyncWriter::spawn(16, OverflowPolicy::Block).unwrap();
+
+        for i in 0..10 {
+            writer.send(file_output(&sink, &format!("line {}", i)));
+        }
+        drop(writer);
+        sink.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 10);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_overflow_drop_counts_lines() {
+        // No worker is attached, so the queue fills up after `capacity` lines
+        let (writer, _receiver) = AsyncWriter::new(2, OverflowPolicy::Drop);
+
+        for _ in 0..5 {
+            writer.send(Output::Lines {
+                stderr: None,
+                file: None,
+            });
+        }
+
+        assert_eq!(writer.dropped(), 3);
+    }
+}
@@ -10,6 +10,7 @@
 //! json_response!({ "message": "Success" })
 //! ok_json!({ "id": 123 })
 //! error_json!("User not found")
+//! respond!(req, 200, user) // JSON, TOON or form, per the Accept header (`negotiate` feature)
 //! ```
 //!
 //! # Route Grouping
@@ -18,7 +19,7 @@
 //! routes! {
 //!     GET "/users" => list_users,
 //!     POST "/users" => create_user,
-//!     GET "/users/:id" => get_user,
+//!     GET "/users/:id<i64>" => get_user,
 //! }
 //! ```
 
@@ -72,6 +73,58 @@ macro_rules! created_json {
     }};
 }
 
+/// Create a response in the format the request's `Accept` header prefers
+///
+/// Serializes `value` as JSON, TOON (`application/toon`) or a URL-encoded
+/// form and sets the matching `Content-Type`. Higher `q` values win, then
+/// earlier entries; anything else, including `*/*` or no `Accept` header at
+/// all, gets JSON. If serialization fails, evaluates to `internal_error!`
+/// with the error message instead.
+///
+/// Requires the `negotiate` feature.
+///
+/// # Examples
+///
+/// ```
+/// use armature_core::HttpRequest;
+/// use armature_macros::respond;
+///
+/// let user = serde_json::json!({ "id": 1, "name": "Ada" });
+///
+/// // No preference: JSON
+/// let req = HttpRequest::new("GET".to_string(), "/users/1".to_string());
+/// let response = respond!(req, 200, user).unwrap();
+/// assert_eq!(response.headers.get("Content-Type").unwrap(), "application/json");
+/// assert_eq!(&response.body_bytes()[..], br#"{"id":1,"name":"Ada"}"#);
+/// ```
+///
+/// ```
+/// use armature_core::HttpRequest;
+/// use armature_macros::respond;
+///
+/// let user = serde_json::json!({ "id": 1, "name": "Ada" });
+///
+/// // TOON preferred over JSON
+/// let mut req = HttpRequest::new("GET".to_string(), "/users/1".to_string());
+/// req.headers.insert(
+///     "Accept".to_string(),
+///     "application/json;q=0.5, application/toon".to_string(),
+/// );
+/// let response = respond!(req, 200, user).unwrap();
+/// assert_eq!(response.headers.get("Content-Type").unwrap(), "application/toon");
+/// assert_eq!(&response.body_bytes()[..], b"id: 1\nname: Ada");
+/// ```
+#[cfg(feature = "negotiate")]
+#[macro_export]
+macro_rules! respond {
+    ($req:expr, $status:expr, $value:expr) => {
+        match $crate::negotiate::respond(&$req, $status, &$value) {
+            Ok(response) => Ok(response),
+            Err(e) => $crate::internal_error!(e),
+        }
+    };
+}
+
 /// Create a 400 Bad Request JSON error response
 ///
 /// # Examples
@@ -216,23 +269,46 @@ macro_rules! validation_error {
 
 /// Guard a condition, returning an error if false
 ///
+/// Returns `Error::Forbidden` by default. Name an `armature_core::Error`
+/// variant to return that instead; any variant carrying a message works,
+/// such as `BadRequest`, `Unauthorized`, `NotFound`, `Conflict`,
+/// `Validation` or `TooManyRequests`. Messages may use `format!` syntax.
+///
 /// # Examples
 ///
 /// ```ignore
 /// guard!(user.is_admin(), "Admin access required");
-/// guard!(age >= 18, "Must be 18 or older");
+/// guard!(age >= 18, "Must be {} or older", 18);
+///
+/// guard!(session.is_some(), Unauthorized, "Login required");
+/// guard!(name.len() <= 64, BadRequest, "Field '{}' is too long", "name");
+/// guard!(post.is_some(), NotFound, "Post {} not found", id);
 /// ```
 #[macro_export]
 macro_rules! guard {
     ($cond:expr, $msg:expr) => {
+        $crate::guard!($cond, Forbidden, $msg)
+    };
+    ($cond:expr, $variant:ident, $msg:expr) => {
         if !($cond) {
-            return Err(armature_core::Error::Forbidden($msg.to_string()));
+            return Err(armature_core::Error::$variant($msg.to_string()));
         }
     };
+    ($cond:expr, $variant:ident, $fmt:expr, $($arg:tt)*) => {
+        $crate::guard!($cond, $variant, format!($fmt, $($arg)*))
+    };
+    ($cond:expr, $fmt:literal, $($arg:tt)*) => {
+        $crate::guard!($cond, Forbidden, format!($fmt, $($arg)*))
+    };
 }
 
 /// Define multiple routes concisely
 ///
+/// Path parameters may declare a type, as in `:id<i64>`. The route then only
+/// dispatches when the value parses as that type; otherwise the request is
+/// rejected with 400 Bad Request before the handler runs. The type must
+/// implement `FromStr`, which is checked at compile time.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -243,18 +319,24 @@ macro_rules! guard {
 ///     PUT "/users/:id" => update_user,
 ///     DELETE "/users/:id" => delete_user,
 /// }
+///
+/// // Typed parameters: `/posts/abc` never reaches `get_post`
+/// routes! {
+///     GET "/posts/:id<i64>" => get_post,
+///     GET "/posts/:id<i64>/comments/:n<u32>" => get_comment,
+/// }
+///
+/// async fn get_post(req: HttpRequest) -> Result<HttpResponse, Error> {
+///     let id: i64 = path_param!(req, "id")?;
+///     // ...
+/// }
 /// ```
 #[macro_export]
 macro_rules! routes {
     ($($method:ident $path:literal => $handler:expr),* $(,)?) => {
         vec![
             $(
-                armature_core::Route {
-                    method: armature_core::HttpMethod::from_str(stringify!($method)).unwrap(),
-                    path: $path.to_string(),
-                    handler: std::sync::Arc::new($handler),
-                    constraints: None,
-                },
+                $crate::__route_entry!($method $path => $handler),
             )*
         ]
     };
@@ -262,6 +344,12 @@ macro_rules! routes {
 
 /// Build JSON objects with type safety
 ///
+/// The `StructName { field => expr, ... }` form builds the object from a
+/// `Serialize` struct, so every key is checked against the struct's fields
+/// at compile time: a typo or a renamed field fails the build. As with a
+/// struct literal, every field must be given. Serde attributes on the struct
+/// (such as `rename`) apply to the resulting keys.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -270,6 +358,13 @@ macro_rules! routes {
 ///     "name" => user.name,
 ///     "email" => user.email,
 /// };
+///
+/// // Checked against `UserResponse`
+/// let response = json_object!(UserResponse {
+///     id => user.id,
+///     name => format!("{} {}", user.first_name, user.last_name),
+///     email => user.email,
+/// });
 /// ```
 #[macro_export]
 macro_rules! json_object {
@@ -282,6 +377,10 @@ macro_rules! json_object {
             serde_json::Value::Object(map)
         }
     };
+    ($($name:ident)::+ { $($field:ident => $value:expr),* $(,)? }) => {
+        serde_json::to_value(&$($name)::+ { $($field: $value),* })
+            .expect(concat!("failed to serialize ", stringify!($($name)::+)))
+    };
 }
 
 /// Extract multiple path params at once
@@ -335,30 +434,70 @@ macro_rules! log_error {
 
 /// Create a paginated response
 ///
+/// Wraps one page of `data` in the [`PaginatedResponse`] envelope from
+/// `armature_core::pagination`, computing `total_pages`, `has_next`,
+/// `has_prev` and the page links from `page` (1-indexed), `per_page` and the
+/// `total` item count. `data` may be anything iterable, such as a `Vec`.
+///
+/// The three-argument form `(data, page, total)` is deprecated: it takes
+/// `per_page` from `data.len()`, which is wrong on a partial last page.
+///
+/// [`PaginatedResponse`]: armature_core::PaginatedResponse
+///
 /// # Examples
 ///
 /// ```ignore
-/// paginated_response!(users, page, total_count)
+/// paginated_response!(users, page, per_page, total_count)
+/// ```
+///
+/// Produces:
+///
+/// ```json
+/// {
+///   "data": [...],
+///   "meta": { "page": 3, "per_page": 10, "total": 23, "total_pages": 3,
+///             "has_next": false, "has_prev": true },
+///   "links": { "first": "?page=1&per_page=10", "prev": "?page=2&per_page=10",
+///              "last": "?page=3&per_page=10" }
+/// }
 /// ```
 #[macro_export]
 macro_rules! paginated_response {
-    ($data:expr, $page:expr, $total:expr) => {
-        {
-            use armature_core::HttpResponse;
-            HttpResponse::ok().with_json(&serde_json::json!({
-                "data": $data,
-                "pagination": {
-                    "page": $page,
-                    "total": $total,
-                    "per_page": $data.len(),
-                }
-            }))
-        }
-    };
+    ($data:expr, $page:expr, $per_page:expr, $total:expr) => {{
+        use armature_core::HttpResponse;
+        let pagination = armature_core::OffsetPagination {
+            page: ($page as usize).max(1),
+            per_page: ($per_page as usize).max(1),
+        };
+        HttpResponse::ok().with_json(&armature_core::PaginatedResponse::new(
+            $data.into_iter().collect::<Vec<_>>(),
+            pagination,
+            $total as usize,
+        ))
+    }};
+    ($data:expr, $page:expr, $total:expr) => {{
+        $crate::paginated_response_without_per_page();
+        let data = $data;
+        let per_page = data.len();
+        $crate::paginated_response!(data, $page, per_page, $total)
+    }};
 }
 
+/// Marks uses of the deprecated three-argument `paginated_response!`
+#[doc(hidden)]
+#[deprecated(
+    note = "`per_page` is taken from the data length; use `paginated_response!(data, page, per_page, total)`"
+)]
+pub fn paginated_response_without_per_page() {}
+
+#[cfg(feature = "negotiate")]
+#[doc(hidden)]
+pub mod negotiate;
 pub mod prelude;
 
+#[doc(hidden)]
+pub use armature_proc_macro::route_entry as __route_entry;
+
 #[cfg(test)]
 mod tests {
     #[test]
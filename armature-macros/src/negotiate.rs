@@ -0,0 +1,147 @@
+//! Content negotiation behind the `respond!` macro
+
+use armature_core::{HttpRequest, HttpResponse};
+use armature_toon::ToonContentNegotiator;
+use serde::Serialize;
+
+/// Body formats `respond!` can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toon,
+    Form,
+}
+
+impl Format {
+    /// Candidates in tie-break order: JSON wins ties, e.g. under `*/*`
+    const ALL: [Format; 3] = [Format::Json, Format::Toon, Format::Form];
+
+    /// The `Content-Type` for this format
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Toon => armature_toon::TOON_CONTENT_TYPE,
+            Format::Form => "application/x-www-form-urlencoded",
+        }
+    }
+
+    /// Pick the format an `Accept` header ranks highest
+    ///
+    /// Higher quality values win, then earlier positions in the header.
+    /// Falls back to JSON when the header is missing or accepts none of the
+    /// formats.
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        let Some(accept) = accept else {
+            return Format::Json;
+        };
+
+        let mut best: Option<(Format, f32, usize)> = None;
+        for format in Self::ALL {
+            let Some((quality, position)) =
+                ToonContentNegotiator::preference(accept, format.content_type())
+            else {
+                continue;
+            };
+            if quality <= 0.0 {
+                continue;
+            }
+            if best.is_none_or(|(_, best_quality, best_position)| {
+                quality > best_quality || (quality == best_quality && position < best_position)
+            }) {
+                best = Some((format, quality, position));
+            }
+        }
+
+        best.map_or(Format::Json, |(format, _, _)| format)
+    }
+
+    /// Serialize `value` in this format
+    pub fn serialize<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Format::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Format::Toon => armature_toon::to_vec(value).map_err(|e| e.to_string()),
+            Format::Form => serde_urlencoded::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Build a response in the format the request's `Accept` header prefers
+///
+/// Returns the serialization error message on failure, for `respond!` to
+/// turn into a 500 response.
+pub fn respond<T: Serialize + ?Sized>(
+    req: &HttpRequest,
+    status: u16,
+    value: &T,
+) -> Result<HttpResponse, String> {
+    let accept = req
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("accept"))
+        .map(|(_, value)| value.as_str());
+
+    let format = Format::from_accept(accept);
+    let body = format.serialize(value)?;
+    Ok(HttpResponse::new(status).with_body(body).with_header(
+        "Content-Type".to_string(),
+        format.content_type().to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_accept() {
+        assert_eq!(Format::from_accept(None), Format::Json);
+        assert_eq!(Format::from_accept(Some("*/*")), Format::Json);
+        assert_eq!(Format::from_accept(Some("text/html")), Format::Json);
+        assert_eq!(Format::from_accept(Some("application/toon")), Format::Toon);
+        assert_eq!(
+            Format::from_accept(Some("application/x-www-form-urlencoded")),
+            Format::Form
+        );
+        assert_eq!(
+            Format::from_accept(Some("application/json, application/toon")),
+            Format::Json
+        );
+        assert_eq!(
+            Format::from_accept(Some("application/json;q=0.5, application/toon")),
+            Format::Toon
+        );
+        assert_eq!(
+            Format::from_accept(Some("application/toon;q=0.5, */*")),
+            Format::Json
+        );
+        assert_eq!(
+            Format::from_accept(Some("application/json;q=0, application/*")),
+            Format::Toon
+        );
+    }
+
+    #[test]
+    fn test_respond_form() {
+        let mut req = HttpRequest::new("POST".to_string(), "/users".to_string());
+        req.headers.insert(
+            "accept".to_string(),
+            "application/x-www-form-urlencoded".to_string(),
+        );
+
+        let response =
+            crate::respond!(req, 201, serde_json::json!({ "id": 1, "name": "Ada" })).unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(
+            response.headers.get("Content-Type").unwrap(),
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(&response.body_bytes()[..], b"id=1&name=Ada");
+
+        // Nested values can't be form-encoded
+        let response =
+            crate::respond!(req, 200, serde_json::json!({ "user": { "id": 1 } })).unwrap();
+        assert_eq!(response.status, 500);
+    }
+}
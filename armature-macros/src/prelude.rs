@@ -11,3 +11,6 @@ pub use crate::{
     log_error, not_found, ok_json, paginated_response, path_param, path_params, query_param,
     validation_error,
 };
+
+#[cfg(feature = "negotiate")]
+pub use crate::respond;
@@ -0,0 +1,50 @@
+use armature_core::Error;
+use armature_macros::guard;
+
+fn check(allowed: bool) -> Result<(), Error> {
+    guard!(allowed, "Admin access required");
+    Ok(())
+}
+
+fn check_variant(allowed: bool) -> Result<(), Error> {
+    guard!(allowed, Unauthorized, "Login required");
+    Ok(())
+}
+
+fn check_formatted(name: &str) -> Result<(), Error> {
+    guard!(name.len() <= 4, BadRequest, "Field '{}' is too long", name);
+    Ok(())
+}
+
+#[test]
+fn test_guard_defaults_to_forbidden() {
+    assert!(check(true).is_ok());
+
+    let err = check(false).unwrap_err();
+    assert!(matches!(err, Error::Forbidden(ref msg) if msg == "Admin access required"));
+    assert_eq!(err.status_code(), 403);
+}
+
+#[test]
+fn test_guard_variant() {
+    assert!(check_variant(true).is_ok());
+
+    let err = check_variant(false).unwrap_err();
+    assert!(matches!(err, Error::Unauthorized(ref msg) if msg == "Login required"));
+    assert_eq!(err.status_code(), 401);
+}
+
+#[test]
+fn test_guard_formatted_message() {
+    assert!(check_formatted("ada").is_ok());
+
+    let err = check_formatted("grace").unwrap_err();
+    assert!(matches!(err, Error::BadRequest(ref msg) if msg == "Field 'grace' is too long"));
+}
+
+#[test]
+fn test_guard_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/guard-variants.rs");
+    t.compile_fail("tests/ui/guard-unknown-variant.rs");
+}
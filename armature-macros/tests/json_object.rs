@@ -0,0 +1,41 @@
+use armature_macros::json_object;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct UserResponse {
+    id: i64,
+    name: String,
+    #[serde(rename = "emailAddress")]
+    email: Option<String>,
+}
+
+#[test]
+fn test_json_object_from_struct() {
+    let first = "Ada";
+    let value = json_object!(UserResponse {
+        id => 40 + 2,
+        name => format!("{} Lovelace", first),
+        email => None,
+    });
+
+    assert_eq!(
+        value,
+        serde_json::json!({ "id": 42, "name": "Ada Lovelace", "emailAddress": null })
+    );
+}
+
+#[test]
+fn test_json_object_from_literals() {
+    let value = json_object! {
+        "id" => 42,
+        "tags" => vec!["a", "b"],
+    };
+
+    assert_eq!(value, serde_json::json!({ "id": 42, "tags": ["a", "b"] }));
+}
+
+#[test]
+fn test_json_object_ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/json-object-unknown-field.rs");
+}
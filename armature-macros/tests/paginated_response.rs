@@ -0,0 +1,57 @@
+use armature_core::HttpResponse;
+use armature_macros::paginated_response;
+use serde_json::{Value, json};
+
+fn body(response: HttpResponse) -> Value {
+    serde_json::from_slice(&response.body_bytes()).unwrap()
+}
+
+#[test]
+fn test_partial_last_page() {
+    let users = vec!["ada", "grace", "linus"];
+    let page: u32 = 3;
+    let per_page: u32 = 10;
+
+    let body = body(paginated_response!(users, page, per_page, 23).unwrap());
+
+    assert_eq!(body["data"], json!(["ada", "grace", "linus"]));
+    assert_eq!(
+        body["meta"],
+        json!({
+            "page": 3,
+            "per_page": 10,
+            "total": 23,
+            "total_pages": 3,
+            "has_next": false,
+            "has_prev": true,
+        })
+    );
+    assert_eq!(
+        body["links"],
+        json!({
+            "first": "?page=1&per_page=10",
+            "prev": "?page=2&per_page=10",
+            "last": "?page=3&per_page=10",
+        })
+    );
+}
+
+#[test]
+fn test_first_page() {
+    let body = body(paginated_response!(vec![1, 2], 1, 2, 5).unwrap());
+
+    assert_eq!(body["meta"]["total_pages"], 3);
+    assert_eq!(body["meta"]["has_next"], true);
+    assert_eq!(body["meta"]["has_prev"], false);
+    assert_eq!(body["links"]["next"], "?page=2&per_page=2");
+    assert!(body["links"].get("prev").is_none());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_per_page_from_data() {
+    let body = body(paginated_response!(vec![1, 2], 1, 5).unwrap());
+
+    assert_eq!(body["meta"]["per_page"], 2);
+    assert_eq!(body["meta"]["total_pages"], 3);
+}
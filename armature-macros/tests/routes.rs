@@ -0,0 +1,69 @@
+use armature_core::{Error, HttpRequest, HttpResponse, Router};
+use armature_macros::{ok_json, path_param, routes};
+
+async fn get_user(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let id: i64 = path_param!(req, "id")?;
+    ok_json!(serde_json::json!({ "id": id }))
+}
+
+async fn get_by_slug(req: HttpRequest) -> Result<HttpResponse, Error> {
+    let slug: String = path_param!(req, "slug")?;
+    ok_json!(serde_json::json!({ "slug": slug }))
+}
+
+fn router() -> Router {
+    Router {
+        routes: routes! {
+            GET "/users/:id<i64>" => get_user,
+            GET "/pages/:slug" => get_by_slug,
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_typed_param_parses() {
+    let response = router()
+        .route(HttpRequest::new("GET".to_string(), "/users/42".to_string()))
+        .await
+        .unwrap();
+
+    let body: serde_json::Value = serde_json::from_slice(&response.body_bytes()).unwrap();
+    assert_eq!(body["id"], 42);
+}
+
+#[tokio::test]
+async fn test_typed_param_rejects_before_handler() {
+    let result = router()
+        .route(HttpRequest::new(
+            "GET".to_string(),
+            "/users/abc".to_string(),
+        ))
+        .await;
+
+    let Err(err) = result else {
+        panic!("expected /users/abc to be rejected");
+    };
+    assert!(matches!(err, Error::BadRequest(_)));
+    assert_eq!(err.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_untyped_param() {
+    let response = router()
+        .route(HttpRequest::new(
+            "GET".to_string(),
+            "/pages/about".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let body: serde_json::Value = serde_json::from_slice(&response.body_bytes()).unwrap();
+    assert_eq!(body["slug"], "about");
+}
+
+#[test]
+fn test_routes_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/routes-typed-param.rs");
+    t.compile_fail("tests/ui/routes-unparseable-param.rs");
+}
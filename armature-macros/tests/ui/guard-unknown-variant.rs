@@ -0,0 +1,11 @@
+use armature_core::Error;
+use armature_macros::guard;
+
+fn check(allowed: bool) -> Result<(), Error> {
+    guard!(allowed, Denied, "not allowed");
+    Ok(())
+}
+
+fn main() {
+    let _ = check(true);
+}
@@ -0,0 +1,74 @@
+use armature_core::Error;
+use armature_macros::guard;
+
+fn forbidden() -> Result<(), Error> {
+    guard!(false, "no");
+    Ok(())
+}
+
+fn forbidden_formatted(id: i64) -> Result<(), Error> {
+    guard!(false, "no access to {}", id);
+    Ok(())
+}
+
+fn variants(variant: &str) -> Result<(), Error> {
+    guard!(variant != "bad_request", BadRequest, "no");
+    guard!(variant != "unauthorized", Unauthorized, "no");
+    guard!(variant != "forbidden", Forbidden, "no");
+    guard!(variant != "not_found", NotFound, "no");
+    guard!(variant != "conflict", Conflict, "no");
+    guard!(variant != "internal", Internal, "no");
+    guard!(variant != "validation", Validation, "no");
+    guard!(variant != "timeout", RequestTimeout, "no");
+    guard!(variant != "rate_limited", TooManyRequests, "no");
+    guard!(variant != "unavailable", ServiceUnavailable, "no");
+    Ok(())
+}
+
+fn formatted(variant: &str, id: i64) -> Result<(), Error> {
+    guard!(variant != "not_found", NotFound, "user {} not found", id);
+    guard!(
+        variant != "conflict",
+        Conflict,
+        "user {} already {}",
+        id,
+        "exists"
+    );
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(forbidden().unwrap_err().status_code(), 403);
+    assert_eq!(
+        forbidden_formatted(7).unwrap_err().to_string(),
+        Error::forbidden("no access to 7").to_string()
+    );
+
+    let expected = [
+        ("bad_request", Error::bad_request("no")),
+        ("unauthorized", Error::unauthorized("no")),
+        ("forbidden", Error::forbidden("no")),
+        ("not_found", Error::not_found("no")),
+        ("conflict", Error::conflict("no")),
+        ("internal", Error::internal("no")),
+        ("validation", Error::validation("no")),
+        ("timeout", Error::timeout("no")),
+        ("rate_limited", Error::rate_limited("no")),
+        ("unavailable", Error::unavailable("no")),
+    ];
+    for (variant, error) in expected {
+        let err = variants(variant).unwrap_err();
+        assert_eq!(err.status_code(), error.status_code(), "{}", variant);
+        assert_eq!(err.to_string(), error.to_string(), "{}", variant);
+    }
+    assert!(variants("none").is_ok());
+
+    assert_eq!(
+        formatted("not_found", 7).unwrap_err().to_string(),
+        Error::not_found("user 7 not found").to_string()
+    );
+    assert_eq!(
+        formatted("conflict", 7).unwrap_err().to_string(),
+        Error::conflict("user 7 already exists").to_string()
+    );
+}
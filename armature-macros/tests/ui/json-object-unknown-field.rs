@@ -0,0 +1,15 @@
+use armature_macros::json_object;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct UserResponse {
+    id: i64,
+    name: String,
+}
+
+fn main() {
+    let _value = json_object!(UserResponse {
+        id => 1,
+        nmae => "Ada".to_string(),
+    });
+}
@@ -0,0 +1,26 @@
+use armature_core::{Error, HttpMethod, HttpRequest, HttpResponse};
+use armature_macros::routes;
+use std::collections::HashMap;
+
+async fn handler(_req: HttpRequest) -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::ok())
+}
+
+fn main() {
+    let routes = routes! {
+        GET "/users/:id<i64>" => handler,
+        post "/users/:id<u32>/posts/:slug" => handler,
+        DELETE "/users" => handler,
+    };
+
+    assert_eq!(routes[0].path, "/users/:id");
+    assert_eq!(routes[1].method, HttpMethod::POST);
+    assert_eq!(routes[1].path, "/users/:id/posts/:slug");
+    assert!(routes[2].constraints.is_none());
+
+    let constraints = routes[1].constraints.as_ref().unwrap();
+    let params = |id: &str| HashMap::from([("id".to_string(), id.to_string())]);
+    assert!(constraints.validate(&params("7")).is_ok());
+    assert!(constraints.validate(&params("-7")).is_err());
+    assert!(constraints.validate(&params("abc")).is_err());
+}
@@ -0,0 +1,14 @@
+use armature_core::{Error, HttpRequest, HttpResponse};
+use armature_macros::routes;
+
+struct UserId(i64);
+
+async fn handler(_req: HttpRequest) -> Result<HttpResponse, Error> {
+    Ok(HttpResponse::ok())
+}
+
+fn main() {
+    let _routes = routes! {
+        GET "/users/:id<UserId>" => handler,
+    };
+}
@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use aws_sdk_sns::Client as SnsClient;
@@ -11,8 +12,9 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    Message, MessageBroker, MessageHandler, MessagingError, ProcessingResult, PublishOptions,
-    SubscribeOptions, Subscription, config::AwsConfig,
+    ConcurrentDispatcher, DeadLetterQueue, DeadLetterReason, InFlight, InFlightGuard, Message,
+    MessageBroker, MessageHandler, MessagingError, ProcessingResult, PublishOptions,
+    SubscribeOptions, Subscription, await_confirm, config::AwsConfig, forward_to_dead_letter,
 };
 
 /// AWS SQS/SNS message broker
@@ -215,6 +217,17 @@ impl AwsBroker {
             );
         }
 
+        if let Some(ref schema_version) = message.schema_version {
+            attrs.insert(
+                "schema_version".to_string(),
+                aws_sdk_sqs::types::MessageAttributeValue::builder()
+                    .data_type("String")
+                    .string_value(schema_version)
+                    .build()
+                    .unwrap(),
+            );
+        }
+
         for (key, value) in &message.headers {
             attrs.insert(
                 key.clone(),
@@ -244,72 +257,74 @@ impl MessageBroker for AwsBroker {
         message: Message,
         options: PublishOptions,
     ) -> Result<(), MessagingError> {
-        let body = String::from_utf8_lossy(&message.payload).to_string();
-
-        // Determine if publishing to SNS topic or SQS queue
-        if message.topic.starts_with("arn:aws:sns") || options.exchange.is_some() {
-            // Publish to SNS
-            let topic_arn = options
-                .exchange
-                .as_ref()
-                .map(|e| self.get_topic_arn(e))
-                .unwrap_or_else(|| message.topic.clone());
-
-            debug!(topic_arn = %topic_arn, message_id = %message.id, "Publishing to SNS");
-
-            let mut request = self
-                .sns_client
-                .publish()
-                .topic_arn(&topic_arn)
-                .message(&body);
-
-            // Add message attributes
-            for (key, value) in &message.headers {
-                request = request.message_attributes(
-                    key,
-                    aws_sdk_sns::types::MessageAttributeValue::builder()
-                        .data_type("String")
-                        .string_value(value)
-                        .build()
-                        .unwrap(),
-                );
-            }
+        // SQS and SNS have no confirms; the timeout bounds the API calls
+        await_confirm(&options, async {
+            let body = String::from_utf8_lossy(&message.payload).to_string();
+
+            // Determine if publishing to SNS topic or SQS queue
+            if message.topic.starts_with("arn:aws:sns") || options.exchange.is_some() {
+                // Publish to SNS
+                let topic_arn = options
+                    .exchange
+                    .as_ref()
+                    .map(|e| self.get_topic_arn(e))
+                    .unwrap_or_else(|| message.topic.clone());
+
+                debug!(topic_arn = %topic_arn, message_id = %message.id, "Publishing to SNS");
+
+                let mut request = self
+                    .sns_client
+                    .publish()
+                    .topic_arn(&topic_arn)
+                    .message(&body);
+
+                // Add message attributes
+                for (key, value) in &message.headers {
+                    request = request.message_attributes(
+                        key,
+                        aws_sdk_sns::types::MessageAttributeValue::builder()
+                            .data_type("String")
+                            .string_value(value)
+                            .build()
+                            .unwrap(),
+                    );
+                }
 
-            request
-                .send()
-                .await
-                .map_err(|e| MessagingError::Publish(format!("Failed to publish to SNS: {}", e)))?;
-        } else {
-            // Publish to SQS
-            let queue_url = self.get_queue_url(&message.topic).await?;
-
-            debug!(queue_url = %queue_url, message_id = %message.id, "Publishing to SQS");
-
-            let mut request = self
-                .sqs_client
-                .send_message()
-                .queue_url(&queue_url)
-                .message_body(&body);
-
-            // Add message attributes
-            let attrs = Self::build_message_attributes(&message);
-            for (key, value) in attrs {
-                request = request.message_attributes(key, value);
-            }
+                request.send().await.map_err(|e| {
+                    MessagingError::Publish(format!("Failed to publish to SNS: {}", e))
+                })?;
+            } else {
+                // Publish to SQS
+                let queue_url = self.get_queue_url(&message.topic).await?;
+
+                debug!(queue_url = %queue_url, message_id = %message.id, "Publishing to SQS");
+
+                let mut request = self
+                    .sqs_client
+                    .send_message()
+                    .queue_url(&queue_url)
+                    .message_body(&body);
+
+                // Add message attributes
+                let attrs = Self::build_message_attributes(&message);
+                for (key, value) in attrs {
+                    request = request.message_attributes(key, value);
+                }
 
-            // Add delay if TTL is set (using delay seconds)
-            if let Some(ttl) = message.ttl {
-                let delay_seconds = (ttl / 1000).min(900) as i32; // Max 15 minutes
-                request = request.delay_seconds(delay_seconds);
-            }
+                // Add delay if TTL is set (using delay seconds)
+                if let Some(ttl) = message.ttl {
+                    let delay_seconds = (ttl / 1000).min(900) as i32; // Max 15 minutes
+                    request = request.delay_seconds(delay_seconds);
+                }
 
-            request
-                .send()
-                .await
-                .map_err(|e| MessagingError::Publish(format!("Failed to publish to SQS: {}", e)))?;
-        }
+                request.send().await.map_err(|e| {
+                    MessagingError::Publish(format!("Failed to publish to SQS: {}", e))
+                })?;
+            }
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     async fn subscribe(
@@ -325,26 +340,47 @@ impl MessageBroker for AwsBroker {
         &self,
         topic: &str,
         handler: Arc<dyn MessageHandler>,
-        _options: SubscribeOptions,
+        options: SubscribeOptions,
     ) -> Result<Self::Subscription, MessagingError> {
+        let handler = options.wrap_handler(handler);
+        let dispatcher = ConcurrentDispatcher::from_options(&options);
+
         let queue_url = self.get_queue_url(topic).await?;
+
+        let dead_letter = match (options.dead_letter_queue, options.dead_letter_topic) {
+            (Some(queue), _) => Some(queue),
+            (None, Some(dead_letter_topic)) => Some(Arc::new(SqsDeadLetterQueue {
+                client: self.sqs_client.clone(),
+                queue_url: self.get_queue_url(&dead_letter_topic).await?,
+            }) as Arc<dyn DeadLetterQueue>),
+            (None, None) => None,
+        };
+
         let active = Arc::new(AtomicBool::new(true));
+        let in_flight = InFlight::new();
 
         let subscription = AwsSubscription {
             queue_url: queue_url.clone(),
             active: active.clone(),
+            in_flight: in_flight.clone(),
         };
 
         // Store active flag for cleanup
         self.active_consumers.write().await.push(active.clone());
 
         // Spawn consumer task
-        let sqs_client = self.sqs_client.clone();
+        let context = DeliveryContext {
+            client: self.sqs_client.clone(),
+            queue_url,
+            handler,
+            dead_letter,
+            in_flight,
+        };
         let config = self.config.clone();
         let topic_owned = topic.to_string();
 
         tokio::spawn(async move {
-            poll_messages(sqs_client, queue_url, handler, config, &topic_owned, active).await;
+            poll_messages(context, config, &topic_owned, active, dispatcher).await;
         });
 
         info!(queue = topic, "Subscribed to SQS queue");
@@ -369,15 +405,31 @@ impl MessageBroker for AwsBroker {
     }
 }
 
-async fn poll_messages(
+/// State shared by the polling loop and the handlers it runs
+#[derive(Clone)]
+struct DeliveryContext {
     client: SqsClient,
     queue_url: String,
     handler: Arc<dyn MessageHandler>,
+    dead_letter: Option<Arc<dyn DeadLetterQueue>>,
+    in_flight: InFlight,
+}
+
+async fn poll_messages(
+    context: DeliveryContext,
     config: AwsConfig,
     topic: &str,
     active: Arc<AtomicBool>,
+    dispatcher: Option<ConcurrentDispatcher>,
 ) {
-    while active.load(Ordering::SeqCst) {
+    let DeliveryContext {
+        client,
+        queue_url,
+        in_flight,
+        ..
+    } = context.clone();
+
+    while active.load(Ordering::SeqCst) && !in_flight.is_aborted() {
         let result = client
             .receive_message()
             .queue_url(&queue_url)
@@ -385,6 +437,9 @@ async fn poll_messages(
             .wait_time_seconds(config.long_poll_wait_seconds)
             .visibility_timeout(config.visibility_timeout)
             .message_attribute_names("All")
+            .message_system_attribute_names(
+                aws_sdk_sqs::types::MessageSystemAttributeName::ApproximateReceiveCount,
+            )
             .send()
             .await;
 
@@ -392,56 +447,25 @@ async fn poll_messages(
             Ok(output) => {
                 if let Some(messages) = output.messages {
                     for sqs_message in messages {
-                        let message = sqs_message_to_message(&sqs_message, topic);
-                        let receipt_handle = sqs_message.receipt_handle.clone();
-
-                        match handler.handle(message).await {
-                            Ok(result) => match result {
-                                ProcessingResult::Success => {
-                                    // Delete the message
-                                    if let Some(handle) = receipt_handle
-                                        && let Err(e) = client
-                                            .delete_message()
-                                            .queue_url(&queue_url)
-                                            .receipt_handle(&handle)
-                                            .send()
-                                            .await
-                                    {
-                                        error!(error = %e, "Failed to delete message");
-                                    }
-                                }
-                                ProcessingResult::Retry => {
-                                    // Change visibility timeout to make it available again
-                                    if let Some(handle) = receipt_handle
-                                        && let Err(e) = client
-                                            .change_message_visibility()
-                                            .queue_url(&queue_url)
-                                            .receipt_handle(&handle)
-                                            .visibility_timeout(0)
-                                            .send()
-                                            .await
-                                    {
-                                        warn!(error = %e, "Failed to change message visibility");
-                                    }
-                                }
-                                ProcessingResult::DeadLetter | ProcessingResult::Reject => {
-                                    // Delete the message (it should go to DLQ if configured)
-                                    if let Some(handle) = receipt_handle
-                                        && let Err(e) = client
-                                            .delete_message()
-                                            .queue_url(&queue_url)
-                                            .receipt_handle(&handle)
-                                            .send()
-                                            .await
-                                    {
-                                        error!(error = %e, "Failed to delete rejected message");
-                                    }
+                        if !active.load(Ordering::SeqCst) || in_flight.is_aborted() {
+                            // Hand the rest of the batch back once draining starts
+                            release(&client, &queue_url, sqs_message.receipt_handle).await;
+                            continue;
+                        }
+
+                        let job = handle_message(
+                            context.clone(),
+                            sqs_message,
+                            topic.to_string(),
+                            in_flight.start(),
+                        );
+                        match &dispatcher {
+                            Some(dispatcher) => {
+                                if dispatcher.dispatch(job).await.is_err() {
+                                    return;
                                 }
-                            },
-                            Err(e) => {
-                                error!(error = %e, "Message handler error");
-                                // Message will become visible again after visibility timeout
                             }
+                            None => job.await,
                         }
                     }
                 }
@@ -455,6 +479,111 @@ async fn poll_messages(
     }
 }
 
+/// Handle one message received from SQS and delete or release it.
+async fn handle_message(
+    context: DeliveryContext,
+    sqs_message: aws_sdk_sqs::types::Message,
+    topic: String,
+    _in_flight: InFlightGuard,
+) {
+    let DeliveryContext {
+        client,
+        queue_url,
+        handler,
+        dead_letter,
+        in_flight,
+    } = context;
+    let receipt_handle = sqs_message.receipt_handle.clone();
+    let message = sqs_message_to_message(&sqs_message, &topic);
+    let original = dead_letter.as_ref().map(|_| message.clone());
+
+    let Some(outcome) = in_flight.run(handler.handle(message)).await else {
+        // The drain timed out; make the message visible again
+        release(&client, &queue_url, receipt_handle).await;
+        return;
+    };
+
+    match outcome {
+        Ok(result) => match result {
+            ProcessingResult::Success => {
+                // Delete the message
+                if let Some(handle) = receipt_handle
+                    && let Err(e) = client
+                        .delete_message()
+                        .queue_url(&queue_url)
+                        .receipt_handle(&handle)
+                        .send()
+                        .await
+                {
+                    error!(error = %e, "Failed to delete message");
+                }
+            }
+            ProcessingResult::Retry => {
+                release(&client, &queue_url, receipt_handle).await;
+            }
+            ProcessingResult::DeadLetter | ProcessingResult::Reject => {
+                if let (Some(queue), Some(message), Some(reason)) = (
+                    &dead_letter,
+                    original,
+                    DeadLetterReason::from_result(&result),
+                ) {
+                    let attempts = receive_count(&sqs_message);
+                    if forward_to_dead_letter(queue.as_ref(), message, reason, attempts)
+                        .await
+                        .is_err()
+                    {
+                        // Leave it to become visible again
+                        return;
+                    }
+                }
+
+                // Delete the message; it was forwarded above if a DLQ is configured
+                if let Some(handle) = receipt_handle
+                    && let Err(e) = client
+                        .delete_message()
+                        .queue_url(&queue_url)
+                        .receipt_handle(&handle)
+                        .send()
+                        .await
+                {
+                    error!(error = %e, "Failed to delete rejected message");
+                }
+            }
+        },
+        Err(e) => {
+            error!(error = %e, "Message handler error");
+            // Message will become visible again after visibility timeout
+        }
+    }
+}
+
+/// Make a received message visible again so it is redelivered.
+async fn release(client: &SqsClient, queue_url: &str, receipt_handle: Option<String>) {
+    if let Some(handle) = receipt_handle
+        && let Err(e) = client
+            .change_message_visibility()
+            .queue_url(queue_url)
+            .receipt_handle(&handle)
+            .visibility_timeout(0)
+            .send()
+            .await
+    {
+        warn!(error = %e, "Failed to change message visibility");
+    }
+}
+
+/// How many times SQS has delivered a message, including this delivery.
+fn receive_count(sqs_msg: &aws_sdk_sqs::types::Message) -> u32 {
+    sqs_msg
+        .attributes
+        .as_ref()
+        .and_then(|attrs| {
+            attrs.get(&aws_sdk_sqs::types::MessageSystemAttributeName::ApproximateReceiveCount)
+        })
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(1)
+}
+
 fn sqs_message_to_message(sqs_msg: &aws_sdk_sqs::types::Message, topic: &str) -> Message {
     let payload = sqs_msg
         .body
@@ -466,6 +595,7 @@ fn sqs_message_to_message(sqs_msg: &aws_sdk_sqs::types::Message, topic: &str) ->
     let mut message_id = None;
     let mut correlation_id = None;
     let mut content_type = None;
+    let mut schema_version = None;
     let mut timestamp = chrono::Utc::now();
 
     if let Some(attrs) = &sqs_msg.message_attributes {
@@ -475,6 +605,7 @@ fn sqs_message_to_message(sqs_msg: &aws_sdk_sqs::types::Message, topic: &str) ->
                     "message_id" => message_id = Some(string_value.clone()),
                     "correlation_id" => correlation_id = Some(string_value.clone()),
                     "content_type" => content_type = Some(string_value.clone()),
+                    "schema_version" => schema_version = Some(string_value.clone()),
                     "timestamp" => {
                         if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(string_value) {
                             timestamp = ts.with_timezone(&chrono::Utc);
@@ -504,6 +635,37 @@ fn sqs_message_to_message(sqs_msg: &aws_sdk_sqs::types::Message, topic: &str) ->
         content_type,
         priority: None,
         ttl: None,
+        schema_version,
+    }
+}
+
+/// Sends dead-lettered messages to an SQS queue
+struct SqsDeadLetterQueue {
+    client: SqsClient,
+    queue_url: String,
+}
+
+#[async_trait]
+impl DeadLetterQueue for SqsDeadLetterQueue {
+    async fn send(
+        &self,
+        message: Message,
+        _reason: DeadLetterReason,
+    ) -> Result<(), MessagingError> {
+        let mut request = self
+            .client
+            .send_message()
+            .queue_url(&self.queue_url)
+            .message_body(String::from_utf8_lossy(&message.payload));
+
+        for (key, value) in AwsBroker::build_message_attributes(&message) {
+            request = request.message_attributes(key, value);
+        }
+
+        request.send().await.map_err(|e| {
+            MessagingError::Publish(format!("Failed to send to dead-letter queue: {}", e))
+        })?;
+        Ok(())
     }
 }
 
@@ -511,6 +673,7 @@ fn sqs_message_to_message(sqs_msg: &aws_sdk_sqs::types::Message, topic: &str) ->
 pub struct AwsSubscription {
     queue_url: String,
     active: Arc<AtomicBool>,
+    in_flight: InFlight,
 }
 
 #[async_trait]
@@ -528,6 +691,15 @@ impl Subscription for AwsSubscription {
     fn topic(&self) -> &str {
         &self.queue_url
     }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        self.unsubscribe().await?;
+        self.in_flight.drain(timeout).await
+    }
 }
 
 #[cfg(test)]
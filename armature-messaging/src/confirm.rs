@@ -0,0 +1,56 @@
+//! Bounding publishes by their confirmation timeout
+
+use std::future::Future;
+
+use crate::{MessagingError, PublishOptions};
+
+/// Await a publish and its confirmation within [`PublishOptions::timeout`]
+///
+/// Broker implementations wrap everything `publish_with_options` waits on,
+/// including the broker's confirmation when [`PublishOptions::confirm`] is
+/// set. Without a timeout the future is awaited as is.
+///
+/// Returns [`MessagingError::ConfirmTimeout`] if the timeout expires. The
+/// broker may still have accepted the message, so a retried publish can
+/// deliver it twice.
+pub async fn await_confirm<F, T>(options: &PublishOptions, confirm: F) -> Result<T, MessagingError>
+where
+    F: Future<Output = Result<T, MessagingError>>,
+{
+    let Some(timeout) = options.timeout else {
+        return confirm.await;
+    };
+
+    tokio::time::timeout(timeout, confirm)
+        .await
+        .unwrap_or_else(|_| {
+            Err(MessagingError::ConfirmTimeout(format!(
+                "not confirmed within {:?}",
+                timeout
+            )))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_confirmed_in_time() {
+        let options = PublishOptions::default().with_confirm(Duration::from_millis(100));
+        let result = await_confirm(&options, async { Ok(1) }).await;
+        assert_eq!(result.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_timeout() {
+        let options = PublishOptions::default().with_confirm(Duration::from_millis(10));
+        let result = await_confirm(&options, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+        assert!(matches!(result, Err(MessagingError::ConfirmTimeout(_))));
+    }
+}
@@ -0,0 +1,189 @@
+//! Dead-letter routing for messages that handlers give up on
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use tracing::{debug, error};
+
+use crate::{Message, MessagingError, ProcessingResult};
+
+/// Header recording the topic a dead-lettered message was received from
+pub const DEAD_LETTER_TOPIC_HEADER: &str = "x-dead-letter-original-topic";
+
+/// Header recording why a message was dead-lettered
+pub const DEAD_LETTER_REASON_HEADER: &str = "x-dead-letter-reason";
+
+/// Header recording how many times a message was attempted
+pub const DEAD_LETTER_ATTEMPTS_HEADER: &str = "x-dead-letter-attempts";
+
+/// Why a message was sent to a dead-letter queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// The handler returned [`ProcessingResult::DeadLetter`](crate::ProcessingResult::DeadLetter)
+    DeadLettered,
+    /// The handler returned [`ProcessingResult::Reject`](crate::ProcessingResult::Reject)
+    Rejected,
+    /// The handler kept failing until its retries ran out
+    RetriesExhausted,
+    /// The payload could not be deserialized
+    Deserialization(String),
+}
+
+impl DeadLetterReason {
+    /// The reason for a handler result that gives up on the message
+    pub fn from_result(result: &ProcessingResult) -> Option<Self> {
+        match result {
+            ProcessingResult::DeadLetter => Some(DeadLetterReason::DeadLettered),
+            ProcessingResult::Reject => Some(DeadLetterReason::Rejected),
+            ProcessingResult::Success | ProcessingResult::Retry => None,
+        }
+    }
+}
+
+impl fmt::Display for DeadLetterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeadLetterReason::DeadLettered => write!(f, "dead-lettered"),
+            DeadLetterReason::Rejected => write!(f, "rejected"),
+            DeadLetterReason::RetriesExhausted => write!(f, "retries exhausted"),
+            DeadLetterReason::Deserialization(e) => write!(f, "deserialization failed: {}", e),
+        }
+    }
+}
+
+/// Destination for messages that could not be processed
+#[async_trait]
+pub trait DeadLetterQueue: Send + Sync {
+    /// Store or forward a dead-lettered message
+    ///
+    /// The message already carries the [`DEAD_LETTER_TOPIC_HEADER`],
+    /// [`DEAD_LETTER_REASON_HEADER`] and [`DEAD_LETTER_ATTEMPTS_HEADER`]
+    /// headers.
+    async fn send(&self, message: Message, reason: DeadLetterReason) -> Result<(), MessagingError>;
+}
+
+/// Dead-letter queue that keeps messages in memory
+///
+/// Useful in tests and for inspecting failures in development.
+#[derive(Debug, Default)]
+pub struct InMemoryDeadLetterQueue {
+    messages: Mutex<Vec<(Message, DeadLetterReason)>>,
+}
+
+impl InMemoryDeadLetterQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the dead-lettered messages and their reasons, oldest first
+    pub fn messages(&self) -> Vec<(Message, DeadLetterReason)> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Get the number of dead-lettered messages
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    /// Check if no messages were dead-lettered
+    pub fn is_empty(&self) -> bool {
+        self.messages.lock().unwrap().is_empty()
+    }
+
+    /// Remove all messages
+    pub fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl DeadLetterQueue for InMemoryDeadLetterQueue {
+    async fn send(&self, message: Message, reason: DeadLetterReason) -> Result<(), MessagingError> {
+        self.messages.lock().unwrap().push((message, reason));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T: DeadLetterQueue + ?Sized> DeadLetterQueue for Arc<T> {
+    async fn send(&self, message: Message, reason: DeadLetterReason) -> Result<(), MessagingError> {
+        (**self).send(message, reason).await
+    }
+}
+
+/// Record the original topic, reason and attempt count on a message.
+pub fn annotate_dead_letter(message: Message, reason: &DeadLetterReason, attempts: u32) -> Message {
+    let topic = message.topic.clone();
+    message
+        .with_header(DEAD_LETTER_TOPIC_HEADER, topic)
+        .with_header(DEAD_LETTER_REASON_HEADER, reason.to_string())
+        .with_header(DEAD_LETTER_ATTEMPTS_HEADER, attempts.to_string())
+}
+
+/// Annotate a message and hand it to a dead-letter queue
+///
+/// Broker implementations call this when a handler gives up on a message.
+/// On error the message should stay on the broker rather than be dropped.
+pub async fn forward_to_dead_letter(
+    queue: &dyn DeadLetterQueue,
+    message: Message,
+    reason: DeadLetterReason,
+    attempts: u32,
+) -> Result<(), MessagingError> {
+    let message_id = message.id.clone();
+    let message = annotate_dead_letter(message, &reason, attempts);
+
+    match queue.send(message, reason).await {
+        Ok(()) => {
+            debug!(message_id = %message_id, "Message sent to dead-letter queue");
+            Ok(())
+        }
+        Err(e) => {
+            error!(error = %e, message_id = %message_id, "Failed to dead-letter message");
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forward_records_headers() {
+        let queue = InMemoryDeadLetterQueue::new();
+        let message = Message::new("orders", b"{}".to_vec());
+
+        forward_to_dead_letter(&queue, message.clone(), DeadLetterReason::Rejected, 3)
+            .await
+            .unwrap();
+
+        let messages = queue.messages();
+        assert_eq!(messages.len(), 1);
+        let (forwarded, reason) = &messages[0];
+        assert_eq!(*reason, DeadLetterReason::Rejected);
+        assert_eq!(forwarded.id, message.id);
+        assert_eq!(forwarded.headers[DEAD_LETTER_TOPIC_HEADER], "orders");
+        assert_eq!(forwarded.headers[DEAD_LETTER_REASON_HEADER], "rejected");
+        assert_eq!(forwarded.headers[DEAD_LETTER_ATTEMPTS_HEADER], "3");
+    }
+
+    #[tokio::test]
+    async fn test_forward_reports_failure() {
+        struct Failing;
+
+        #[async_trait]
+        impl DeadLetterQueue for Failing {
+            async fn send(&self, _: Message, _: DeadLetterReason) -> Result<(), MessagingError> {
+                Err(MessagingError::Publish("unavailable".to_string()))
+            }
+        }
+
+        let message = Message::new("orders", b"{}".to_vec());
+        let result =
+            forward_to_dead_letter(&Failing, message, DeadLetterReason::DeadLettered, 1).await;
+        assert!(matches!(result, Err(MessagingError::Publish(_))));
+    }
+}
@@ -0,0 +1,264 @@
+//! Concurrent handler dispatch for subscriptions
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Semaphore, mpsc};
+
+use crate::{MessagingError, SubscribeOptions};
+
+/// A unit of work: handling one message and acknowledging it
+pub type DispatchJob = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Callback receiving dispatcher statistics whenever they change
+pub type DispatchMetricsHook = Arc<dyn Fn(DispatcherStats) + Send + Sync>;
+
+/// Snapshot of a dispatcher's load
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DispatcherStats {
+    /// Handlers currently running
+    pub active: usize,
+    /// Messages waiting for a free handler slot
+    pub queued: usize,
+}
+
+#[derive(Clone, Default)]
+struct Counters {
+    active: Arc<AtomicUsize>,
+    queued: Arc<AtomicUsize>,
+    hook: Arc<Mutex<Option<DispatchMetricsHook>>>,
+}
+
+impl Counters {
+    fn stats(&self) -> DispatcherStats {
+        DispatcherStats {
+            active: self.active.load(Ordering::SeqCst),
+            queued: self.queued.load(Ordering::SeqCst),
+        }
+    }
+
+    fn changed(&self) {
+        let hook = self.hook.lock().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(self.stats());
+        }
+    }
+}
+
+/// Decrements the active count when a job finishes, even by panicking
+struct ActiveGuard(Counters);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::SeqCst);
+        self.0.changed();
+    }
+}
+
+/// Runs message handlers concurrently, up to a limit
+///
+/// Jobs wait in a bounded queue until one of `concurrency` slots is free.
+/// When the queue is full, [`dispatch`](Self::dispatch) waits, so a consumer
+/// loop stops pulling messages from the broker instead of buffering without
+/// bound.
+///
+/// Each job handles and acknowledges a single message, which keeps
+/// acknowledgments tied to the right message however jobs interleave.
+pub struct ConcurrentDispatcher {
+    sender: mpsc::Sender<DispatchJob>,
+    counters: Counters,
+    concurrency: usize,
+}
+
+impl ConcurrentDispatcher {
+    /// Create a dispatcher running up to `concurrency` jobs with `buffer`
+    /// more waiting
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn new(concurrency: usize, buffer: usize) -> Self {
+        let concurrency = concurrency.max(1);
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        let counters = Counters::default();
+
+        tokio::spawn(run_jobs(
+            receiver,
+            Arc::new(Semaphore::new(concurrency)),
+            counters.clone(),
+        ));
+
+        Self {
+            sender,
+            counters,
+            concurrency,
+        }
+    }
+
+    /// Create a dispatcher for a subscription if it asks for concurrency
+    ///
+    /// Returns `None` unless [`SubscribeOptions::concurrency`] is above one.
+    /// The prefetch count, or else the concurrency, sets the queue size.
+    pub fn from_options(options: &SubscribeOptions) -> Option<Self> {
+        let concurrency = options.concurrency.filter(|&c| c > 1)?;
+        let buffer = options
+            .prefetch_count
+            .map(usize::from)
+            .unwrap_or(concurrency);
+
+        let dispatcher = Self::new(concurrency, buffer);
+        Some(match &options.dispatch_metrics {
+            Some(hook) => dispatcher.with_metrics_hook(hook.clone()),
+            None => dispatcher,
+        })
+    }
+
+    /// Report statistics to a callback whenever they change
+    pub fn with_metrics_hook(self, hook: DispatchMetricsHook) -> Self {
+        *self.counters.hook.lock().unwrap() = Some(hook);
+        self
+    }
+
+    /// Queue a job, waiting while the queue is full
+    pub async fn dispatch<F>(&self, job: F) -> Result<(), MessagingError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.counters.queued.fetch_add(1, Ordering::SeqCst);
+        self.counters.changed();
+
+        if self.sender.send(Box::pin(job)).await.is_err() {
+            self.counters.queued.fetch_sub(1, Ordering::SeqCst);
+            self.counters.changed();
+            return Err(MessagingError::ChannelClosed(
+                "dispatcher stopped".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the maximum number of concurrent jobs
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Get the number of running jobs
+    pub fn active(&self) -> usize {
+        self.counters.active.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of jobs waiting for a slot
+    pub fn queued(&self) -> usize {
+        self.counters.queued.load(Ordering::SeqCst)
+    }
+
+    /// Get a snapshot of the dispatcher's load
+    pub fn stats(&self) -> DispatcherStats {
+        self.counters.stats()
+    }
+}
+
+impl fmt::Debug for ConcurrentDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrentDispatcher")
+            .field("concurrency", &self.concurrency)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+async fn run_jobs(
+    mut receiver: mpsc::Receiver<DispatchJob>,
+    slots: Arc<Semaphore>,
+    counters: Counters,
+) {
+    while let Some(job) = receiver.recv().await {
+        let Ok(slot) = slots.clone().acquire_owned().await else {
+            break;
+        };
+
+        counters.queued.fetch_sub(1, Ordering::SeqCst);
+        counters.active.fetch_add(1, Ordering::SeqCst);
+        counters.changed();
+
+        let active = ActiveGuard(counters.clone());
+        tokio::spawn(async move {
+            job.await;
+            drop(slot);
+            drop(active);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_limits_concurrent_jobs() {
+        let dispatcher = ConcurrentDispatcher::new(3, 2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..12 {
+            let running = running.clone();
+            let peak = peak.clone();
+            let done = done.clone();
+            dispatcher
+                .dispatch(async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    done.fetch_add(1, Ordering::SeqCst);
+                })
+                .await
+                .unwrap();
+            assert!(dispatcher.active() <= 3);
+        }
+
+        while done.load(Ordering::SeqCst) < 12 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(dispatcher.stats(), DispatcherStats::default());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_hook() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let dispatcher = ConcurrentDispatcher::new(1, 1)
+            .with_metrics_hook(Arc::new(move |stats| recorder.lock().unwrap().push(stats)));
+
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        dispatcher
+            .dispatch(async move {
+                let _ = released.await;
+            })
+            .await
+            .unwrap();
+        while dispatcher.active() == 0 {
+            tokio::task::yield_now().await;
+        }
+        dispatcher.dispatch(async {}).await.unwrap();
+        assert_eq!(
+            dispatcher.stats(),
+            DispatcherStats {
+                active: 1,
+                queued: 1
+            }
+        );
+
+        release.send(()).unwrap();
+        while dispatcher.stats() != DispatcherStats::default() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let seen = seen.lock().unwrap();
+        assert!(seen.iter().any(|s| s.queued == 1 && s.active == 1));
+        assert!(seen.iter().all(|s| s.active <= 1));
+    }
+}
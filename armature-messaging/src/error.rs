@@ -33,6 +33,10 @@ pub enum MessagingError {
     #[error("Operation timed out: {0}")]
     Timeout(String),
 
+    /// The broker did not confirm a publish in time
+    #[error("Publish confirm timed out: {0}")]
+    ConfirmTimeout(String),
+
     /// Authentication failed
     #[error("Authentication failed: {0}")]
     Authentication(String),
@@ -77,6 +81,10 @@ pub enum MessagingError {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// Payload does not match its schema
+    #[error("Schema validation failed: {0}")]
+    Schema(String),
+
     /// Other errors
     #[error("{0}")]
     Other(String),
@@ -89,6 +97,7 @@ impl MessagingError {
             self,
             MessagingError::Connection(_)
                 | MessagingError::Timeout(_)
+                | MessagingError::ConfirmTimeout(_)
                 | MessagingError::ChannelClosed(_)
                 | MessagingError::ResourceExhausted(_)
                 | MessagingError::BrokerError(_)
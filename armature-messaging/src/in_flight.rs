@@ -0,0 +1,184 @@
+//! In-flight message tracking for draining subscriptions
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tracing::warn;
+
+use crate::MessagingError;
+
+/// How long abandoned handlers get to release their messages
+const ABORT_GRACE: Duration = Duration::from_secs(1);
+
+/// Counts the messages a subscription is handling
+///
+/// Broker implementations hold an [`InFlightGuard`] from receiving a message
+/// until it is acknowledged, and run the handler through [`run`](Self::run)
+/// so a drain that times out can abandon it.
+#[derive(Debug, Clone, Default)]
+pub struct InFlight {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    count: AtomicUsize,
+    idle: Notify,
+    aborted: AtomicBool,
+    abort: Notify,
+}
+
+impl InFlight {
+    /// Create a tracker with nothing in flight
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of messages in flight
+    pub fn count(&self) -> usize {
+        self.inner.count.load(Ordering::SeqCst)
+    }
+
+    /// Mark a message as in flight until the guard is dropped
+    pub fn start(&self) -> InFlightGuard {
+        self.inner.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard {
+            in_flight: self.clone(),
+        }
+    }
+
+    /// Wait until nothing is in flight
+    ///
+    /// Returns `false` if messages were still in flight when the timeout
+    /// expired.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let idle = async {
+            loop {
+                let notified = self.inner.idle.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if self.count() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(timeout, idle).await.is_ok()
+    }
+
+    /// Wait for in-flight messages, abandoning them once the timeout expires
+    ///
+    /// After abandoning, waits briefly for consumers to release the messages
+    /// back to the broker.
+    pub async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        if self.wait_idle(timeout).await {
+            return Ok(());
+        }
+
+        let abandoned = self.count();
+        warn!(abandoned, "Drain timed out, abandoning in-flight messages");
+        self.abort();
+        self.wait_idle(ABORT_GRACE).await;
+
+        Err(MessagingError::Timeout(format!(
+            "{} message(s) still in flight after {:?}",
+            abandoned, timeout
+        )))
+    }
+
+    /// Abandon handlers still running through [`run`](Self::run)
+    pub fn abort(&self) {
+        self.inner.aborted.store(true, Ordering::SeqCst);
+        self.inner.abort.notify_waiters();
+    }
+
+    /// Check if [`abort`](Self::abort) was called
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Run a handler future, or return `None` once the tracker is aborted
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        let aborted = self.inner.abort.notified();
+        tokio::pin!(aborted);
+        aborted.as_mut().enable();
+        if self.is_aborted() {
+            return None;
+        }
+
+        tokio::select! {
+            output = future => Some(output),
+            _ = aborted => None,
+        }
+    }
+}
+
+/// Keeps a message counted as in flight, see [`InFlight::start`]
+#[derive(Debug)]
+pub struct InFlightGuard {
+    in_flight: InFlight,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.in_flight.inner.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.in_flight.inner.idle.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_idle() {
+        let in_flight = InFlight::new();
+        assert!(in_flight.wait_idle(Duration::from_millis(10)).await);
+
+        let guard = in_flight.start();
+        assert_eq!(in_flight.count(), 1);
+        assert!(!in_flight.wait_idle(Duration::from_millis(10)).await);
+
+        let waiter = in_flight.clone();
+        let wait = tokio::spawn(async move { waiter.wait_idle(Duration::from_secs(5)).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(guard);
+        assert!(wait.await.unwrap());
+        assert_eq!(in_flight.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_abort_abandons_running_handler() {
+        let in_flight = InFlight::new();
+        assert_eq!(in_flight.run(async { 1 }).await, Some(1));
+
+        let runner = in_flight.clone();
+        let task = tokio::spawn(async move { runner.run(std::future::pending::<()>()).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        in_flight.abort();
+
+        assert_eq!(task.await.unwrap(), None);
+        assert_eq!(in_flight.run(async { 1 }).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_drain_times_out() {
+        let in_flight = InFlight::new();
+        let runner = in_flight.clone();
+        let task = tokio::spawn(async move {
+            let _guard = runner.start();
+            runner.run(std::future::pending::<()>()).await
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let result = in_flight.drain(Duration::from_millis(10)).await;
+
+        assert!(matches!(result, Err(MessagingError::Timeout(_))));
+        assert_eq!(task.await.unwrap(), None);
+        assert_eq!(in_flight.count(), 0);
+    }
+}
@@ -15,8 +15,10 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    AckMode, Message, MessageBroker, MessageHandler, MessagingConfig, MessagingError,
-    ProcessingResult, PublishOptions, SubscribeOptions, Subscription, config::KafkaConfig,
+    AckMode, ConcurrentDispatcher, DeadLetterQueue, DeadLetterReason, InFlight, InFlightGuard,
+    Message, MessageBroker, MessageHandler, MessagingConfig, MessagingError, ProcessingResult,
+    PublishOptions, SubscribeOptions, Subscription, await_confirm, config::KafkaConfig,
+    forward_to_dead_letter,
 };
 
 /// Apache Kafka message broker
@@ -124,6 +126,13 @@ impl KafkaBroker {
             });
         }
 
+        if let Some(ref schema_version) = message.schema_version {
+            headers = headers.insert(Header {
+                key: "schema_version",
+                value: Some(schema_version.as_bytes()),
+            });
+        }
+
         for (key, value) in &message.headers {
             headers = headers.insert(Header {
                 key,
@@ -165,12 +174,14 @@ impl MessageBroker for KafkaBroker {
 
         let timeout = options.timeout.unwrap_or(Duration::from_secs(5));
 
-        self.producer
-            .send(record, timeout)
-            .await
-            .map_err(|(e, _)| MessagingError::Publish(e.to_string()))?;
-
-        Ok(())
+        await_confirm(&options, async {
+            self.producer
+                .send(record, timeout)
+                .await
+                .map_err(|(e, _)| MessagingError::Publish(e.to_string()))?;
+            Ok(())
+        })
+        .await
     }
 
     async fn subscribe(
@@ -188,6 +199,9 @@ impl MessageBroker for KafkaBroker {
         handler: Arc<dyn MessageHandler>,
         options: SubscribeOptions,
     ) -> Result<Self::Subscription, MessagingError> {
+        let handler = options.wrap_handler(handler);
+        let dispatcher = ConcurrentDispatcher::from_options(&options);
+
         let mut client_config = ClientConfig::new();
         client_config.set("bootstrap.servers", &self.config.base.url);
 
@@ -272,22 +286,38 @@ impl MessageBroker for KafkaBroker {
             .subscribe(&[topic])
             .map_err(|e| MessagingError::Subscribe(e.to_string()))?;
 
+        let dead_letter = options.dead_letter_queue.clone().or_else(|| {
+            options.dead_letter_topic.clone().map(|topic| {
+                Arc::new(KafkaDeadLetterQueue {
+                    producer: self.producer.clone(),
+                    topic,
+                }) as Arc<dyn DeadLetterQueue>
+            })
+        });
+
         let consumer = Arc::new(consumer);
         let active = Arc::new(AtomicBool::new(true));
+        let in_flight = InFlight::new();
 
         let subscription = KafkaSubscription {
             topic: topic.to_string(),
             group_id: group_id.clone(),
             active: active.clone(),
+            in_flight: in_flight.clone(),
         };
 
         // Store consumer for cleanup
         self.consumers.write().await.push(consumer.clone());
 
         // Spawn consumer task
+        let context = DeliveryContext {
+            handler,
+            dead_letter,
+            in_flight,
+        };
         let topic_owned = topic.to_string();
         tokio::spawn(async move {
-            consume_messages(consumer, handler, &topic_owned, active).await;
+            consume_messages(consumer, context, &topic_owned, active, dispatcher).await;
         });
 
         info!(topic = topic, group_id = %group_id, "Subscribed to Kafka topic");
@@ -306,40 +336,43 @@ impl MessageBroker for KafkaBroker {
     }
 }
 
+/// State shared by the consumer loop and the handlers it runs
+#[derive(Clone)]
+struct DeliveryContext {
+    handler: Arc<dyn MessageHandler>,
+    dead_letter: Option<Arc<dyn DeadLetterQueue>>,
+    in_flight: InFlight,
+}
+
 async fn consume_messages(
     consumer: Arc<StreamConsumer>,
-    handler: Arc<dyn MessageHandler>,
+    context: DeliveryContext,
     topic: &str,
     active: Arc<AtomicBool>,
+    dispatcher: Option<ConcurrentDispatcher>,
 ) {
     use futures_util::StreamExt;
 
     let mut stream = consumer.stream();
+    let in_flight = context.in_flight.clone();
 
-    while active.load(Ordering::SeqCst) {
+    while active.load(Ordering::SeqCst) && !in_flight.is_aborted() {
         match stream.next().await {
             Some(Ok(borrowed_message)) => {
-                let message = kafka_message_to_message(&borrowed_message, topic);
+                // Do not handle messages received after a drain started
+                if !active.load(Ordering::SeqCst) {
+                    break;
+                }
 
-                match handler.handle(message).await {
-                    Ok(result) => {
-                        match result {
-                            ProcessingResult::Success => {
-                                // Message processed successfully
-                            }
-                            ProcessingResult::Retry => {
-                                warn!(
-                                    "Kafka does not support message retry - message will be lost"
-                                );
-                            }
-                            ProcessingResult::DeadLetter | ProcessingResult::Reject => {
-                                debug!("Message rejected");
-                            }
+                let message = kafka_message_to_message(&borrowed_message, topic);
+                let job = handle_message(context.clone(), message, in_flight.start());
+                match &dispatcher {
+                    Some(dispatcher) => {
+                        if dispatcher.dispatch(job).await.is_err() {
+                            break;
                         }
                     }
-                    Err(e) => {
-                        error!(error = %e, "Message handler error");
-                    }
+                    None => job.await,
                 }
             }
             Some(Err(e)) => {
@@ -353,6 +386,47 @@ async fn consume_messages(
     }
 }
 
+/// Handle one message received from Kafka.
+async fn handle_message(context: DeliveryContext, message: Message, _in_flight: InFlightGuard) {
+    let DeliveryContext {
+        handler,
+        dead_letter,
+        in_flight,
+    } = context;
+    let original = dead_letter.as_ref().map(|_| message.clone());
+
+    let Some(outcome) = in_flight.run(handler.handle(message)).await else {
+        return;
+    };
+
+    match outcome {
+        Ok(result) => {
+            match result {
+                ProcessingResult::Success => {
+                    // Message processed successfully
+                }
+                ProcessingResult::Retry => {
+                    warn!("Kafka does not support message retry - message will be lost");
+                }
+                ProcessingResult::DeadLetter | ProcessingResult::Reject => {
+                    if let (Some(queue), Some(message), Some(reason)) = (
+                        &dead_letter,
+                        original,
+                        DeadLetterReason::from_result(&result),
+                    ) {
+                        let _ = forward_to_dead_letter(queue.as_ref(), message, reason, 1).await;
+                    } else {
+                        debug!("Message rejected");
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            error!(error = %e, "Message handler error");
+        }
+    }
+}
+
 fn kafka_message_to_message<M: KafkaMessage>(kafka_msg: &M, topic: &str) -> Message {
     let payload = kafka_msg.payload().map(|p| p.to_vec()).unwrap_or_default();
 
@@ -360,6 +434,7 @@ fn kafka_message_to_message<M: KafkaMessage>(kafka_msg: &M, topic: &str) -> Mess
     let mut message_id = None;
     let mut correlation_id = None;
     let mut content_type = None;
+    let mut schema_version = None;
     let mut timestamp = chrono::Utc::now();
 
     if let Some(kafka_headers) = kafka_msg.headers() {
@@ -370,6 +445,7 @@ fn kafka_message_to_message<M: KafkaMessage>(kafka_msg: &M, topic: &str) -> Mess
                     "message_id" => message_id = Some(value_str),
                     "correlation_id" => correlation_id = Some(value_str),
                     "content_type" => content_type = Some(value_str),
+                    "schema_version" => schema_version = Some(value_str),
                     "timestamp" => {
                         if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(&value_str) {
                             timestamp = ts.with_timezone(&chrono::Utc);
@@ -401,6 +477,32 @@ fn kafka_message_to_message<M: KafkaMessage>(kafka_msg: &M, topic: &str) -> Mess
         content_type,
         priority: None,
         ttl: None,
+        schema_version,
+    }
+}
+
+/// Produces dead-lettered messages to a Kafka topic
+struct KafkaDeadLetterQueue {
+    producer: FutureProducer,
+    topic: String,
+}
+
+#[async_trait]
+impl DeadLetterQueue for KafkaDeadLetterQueue {
+    async fn send(
+        &self,
+        message: Message,
+        _reason: DeadLetterReason,
+    ) -> Result<(), MessagingError> {
+        let record = FutureRecord::<(), _>::to(&self.topic)
+            .payload(&message.payload)
+            .headers(KafkaBroker::build_headers(&message));
+
+        self.producer
+            .send(record, Duration::from_secs(5))
+            .await
+            .map_err(|(e, _)| MessagingError::Publish(e.to_string()))?;
+        Ok(())
     }
 }
 
@@ -409,6 +511,7 @@ pub struct KafkaSubscription {
     topic: String,
     group_id: String,
     active: Arc<AtomicBool>,
+    in_flight: InFlight,
 }
 
 #[async_trait]
@@ -426,4 +529,13 @@ impl Subscription for KafkaSubscription {
     fn topic(&self) -> &str {
         &self.topic
     }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        self.unsubscribe().await?;
+        self.in_flight.drain(timeout).await
+    }
 }
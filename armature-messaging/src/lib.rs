@@ -15,6 +15,8 @@
 //! - `kafka` - Apache Kafka support
 //! - `nats` - NATS support
 //! - `aws` - AWS SQS/SNS support
+//! - `memory` - In-memory broker for tests (enabled by default)
+//! - `json-schema` - JSON Schema validation of message payloads
 //! - `full` - All backends
 //!
 //! ## Example
@@ -53,7 +55,19 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub mod config;
+pub mod confirm;
+pub mod dead_letter;
+pub mod dispatch;
 pub mod error;
+pub mod in_flight;
+pub mod outbox;
+pub mod priority;
+pub mod retry;
+pub mod schema;
+pub mod typed;
+
+#[cfg(feature = "memory")]
+pub mod memory;
 
 #[cfg(feature = "rabbitmq")]
 pub mod rabbitmq;
@@ -68,7 +82,18 @@ pub mod nats;
 pub mod aws;
 
 pub use config::*;
+pub use confirm::*;
+pub use dead_letter::*;
+pub use dispatch::*;
 pub use error::*;
+pub use in_flight::*;
+#[cfg(feature = "memory")]
+pub use memory::*;
+pub use outbox::*;
+pub use priority::*;
+pub use retry::*;
+pub use schema::*;
+pub use typed::*;
 
 /// A message to be sent or received from a message broker
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,6 +118,9 @@ pub struct Message {
     pub priority: Option<u8>,
     /// Time-to-live in milliseconds
     pub ttl: Option<u64>,
+    /// Version of the payload schema, see [`SchemaRegistry`]
+    #[serde(default)]
+    pub schema_version: Option<String>,
 }
 
 impl Message {
@@ -109,6 +137,7 @@ impl Message {
             content_type: None,
             priority: None,
             ttl: None,
+            schema_version: None,
         }
     }
 
@@ -169,6 +198,12 @@ impl Message {
         self
     }
 
+    /// Set the payload schema version
+    pub fn with_schema_version(mut self, version: impl Into<String>) -> Self {
+        self.schema_version = Some(version.into());
+        self
+    }
+
     /// Set the time-to-live from a Duration
     pub fn with_ttl_duration(mut self, ttl: Duration) -> Self {
         self.ttl = Some(ttl.as_millis() as u64);
@@ -243,8 +278,15 @@ where
 #[derive(Debug, Clone, Default)]
 pub struct PublishOptions {
     /// Whether to wait for confirmation from the broker
+    ///
+    /// RabbitMQ waits for a publisher confirm when the channel has confirms
+    /// enabled. Kafka always waits for the delivery report. NATS and AWS have
+    /// no confirms, so this is a no-op there.
     pub confirm: bool,
-    /// Timeout for confirmation
+    /// Upper bound on a publish, including its confirmation
+    ///
+    /// Applies to every broker, whether or not it confirms. Exceeding it
+    /// fails with [`MessagingError::ConfirmTimeout`], see [`await_confirm`].
     pub timeout: Option<Duration>,
     /// Delivery mode (persistent or transient)
     pub persistent: bool,
@@ -293,7 +335,7 @@ impl PublishOptions {
 }
 
 /// Options for subscribing to messages
-#[derive(Debug, Clone, Default)]
+#[derive(Clone, Default)]
 pub struct SubscribeOptions {
     /// Consumer group/tag
     pub consumer_group: Option<String>,
@@ -305,8 +347,33 @@ pub struct SubscribeOptions {
     pub from_beginning: bool,
     /// Filter expression (for some brokers)
     pub filter: Option<String>,
-    /// Maximum concurrent handlers
+    /// Maximum concurrent handlers, see [`ConcurrentDispatcher`]
     pub concurrency: Option<usize>,
+    /// Topic/queue that dead-lettered and rejected messages are published to
+    pub dead_letter_topic: Option<String>,
+    /// Dead-letter queue to use instead of publishing to `dead_letter_topic`
+    pub dead_letter_queue: Option<Arc<dyn DeadLetterQueue>>,
+    /// Window for delivering buffered messages by priority, see [`PriorityBuffer`]
+    pub priority_window: Option<Duration>,
+    /// Callback for concurrent dispatch statistics
+    pub dispatch_metrics: Option<DispatchMetricsHook>,
+}
+
+impl fmt::Debug for SubscribeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubscribeOptions")
+            .field("consumer_group", &self.consumer_group)
+            .field("prefetch_count", &self.prefetch_count)
+            .field("ack_mode", &self.ack_mode)
+            .field("from_beginning", &self.from_beginning)
+            .field("filter", &self.filter)
+            .field("concurrency", &self.concurrency)
+            .field("dead_letter_topic", &self.dead_letter_topic)
+            .field("dead_letter_queue", &self.dead_letter_queue.is_some())
+            .field("priority_window", &self.priority_window)
+            .field("dispatch_metrics", &self.dispatch_metrics.is_some())
+            .finish()
+    }
 }
 
 impl SubscribeOptions {
@@ -335,10 +402,58 @@ impl SubscribeOptions {
     }
 
     /// Set the concurrency level
+    ///
+    /// Above one, messages are handled concurrently by a
+    /// [`ConcurrentDispatcher`] that buffers up to the prefetch count.
     pub fn with_concurrency(mut self, concurrency: usize) -> Self {
         self.concurrency = Some(concurrency);
         self
     }
+
+    /// Report active and queued handler counts to a callback
+    pub fn with_dispatch_metrics(
+        mut self,
+        hook: impl Fn(DispatcherStats) + Send + Sync + 'static,
+    ) -> Self {
+        self.dispatch_metrics = Some(Arc::new(hook));
+        self
+    }
+
+    /// Publish messages the handler dead-letters or rejects to a topic/queue
+    ///
+    /// Forwarded messages carry the original topic, the reason, and the
+    /// attempt count in the `x-dead-letter-*` headers.
+    pub fn with_dead_letter_topic(mut self, topic: impl Into<String>) -> Self {
+        self.dead_letter_topic = Some(topic.into());
+        self
+    }
+
+    /// Send messages the handler dead-letters or rejects to a custom queue
+    ///
+    /// Takes precedence over [`with_dead_letter_topic`](Self::with_dead_letter_topic).
+    pub fn with_dead_letter_queue(mut self, queue: Arc<dyn DeadLetterQueue>) -> Self {
+        self.dead_letter_queue = Some(queue);
+        self
+    }
+
+    /// Deliver messages received within `window` highest-priority-first
+    ///
+    /// For brokers without native priorities. Every message is delayed by up
+    /// to the window, trading latency for ordering; see [`PriorityBuffer`].
+    pub fn priority_buffer(mut self, window: Duration) -> Self {
+        self.priority_window = Some(window);
+        self
+    }
+
+    /// Wrap a handler in the client-side behavior these options ask for
+    ///
+    /// Broker implementations call this before consuming.
+    pub fn wrap_handler(&self, handler: Arc<dyn MessageHandler>) -> Arc<dyn MessageHandler> {
+        match self.priority_window {
+            Some(window) => Arc::new(PriorityBuffer::new(handler, window)),
+            None => handler,
+        }
+    }
 }
 
 /// Subscription handle for managing a subscription
@@ -352,6 +467,29 @@ pub trait Subscription: Send + Sync {
 
     /// Get the topic/queue name
     fn topic(&self) -> &str;
+
+    /// Get the number of messages currently being handled
+    fn in_flight(&self) -> usize {
+        0
+    }
+
+    /// Stop receiving messages and wait for in-flight ones to finish
+    ///
+    /// No new messages are handled once this is called. Handlers that are
+    /// already running get up to `timeout` to complete and have their result
+    /// acknowledged as usual. After that they are abandoned and a
+    /// [`MessagingError::Timeout`] is returned.
+    ///
+    /// With [`AckMode::Auto`] or [`AckMode::Manual`], abandoned messages are
+    /// handed back for redelivery instead of being lost: RabbitMQ nacks them
+    /// with requeue and SQS makes them visible again. Kafka leaves their offset
+    /// uncommitted, which only prevents loss with `Manual`, since `Auto`
+    /// commits in the background. With [`AckMode::None`], and on core NATS,
+    /// the broker already considers them delivered.
+    async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        let _ = timeout;
+        self.unsubscribe().await
+    }
 }
 
 /// Core trait for message brokers
@@ -497,5 +635,12 @@ mod tests {
         assert_eq!(opts.prefetch_count, Some(10));
         assert_eq!(opts.ack_mode, AckMode::Manual);
         assert_eq!(opts.concurrency, Some(4));
+        assert!(opts.dead_letter_topic.is_none());
+
+        let opts = opts
+            .with_dead_letter_topic("orders.dlq")
+            .with_dead_letter_queue(Arc::new(InMemoryDeadLetterQueue::new()));
+        assert_eq!(opts.dead_letter_topic, Some("orders.dlq".to_string()));
+        assert!(opts.dead_letter_queue.is_some());
     }
 }
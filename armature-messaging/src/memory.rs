@@ -0,0 +1,698 @@
+//! In-memory message broker for testing
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+use tracing::{debug, error};
+
+use crate::{
+    AckMode, ConcurrentDispatcher, DeadLetterQueue, DeadLetterReason, InFlight, Message,
+    MessageBroker, MessageHandler, MessagingError, ProcessingResult, PublishOptions,
+    SubscribeOptions, Subscription, await_confirm, forward_to_dead_letter,
+};
+
+/// Default number of deliveries before a retried message is given up on
+const DEFAULT_MAX_DELIVERIES: u32 = 5;
+
+/// Message broker that keeps everything in process
+///
+/// Lets [`MessageHandler`] implementations be tested without an external
+/// service. Publishing dispatches the message to every matching subscription
+/// before it returns, so assertions can follow the `publish` call directly.
+///
+/// - Messages are routed by `PublishOptions::routing_key`, falling back to the
+///   message topic. Subscriptions match routing keys exactly or with `*` (one
+///   `.`-separated segment) and `#` (any number of segments).
+/// - Subscriptions sharing a consumer group take turns receiving messages.
+/// - With [`AckMode::Auto`] or [`AckMode::Manual`], a handler error or
+///   [`ProcessingResult::Retry`] redelivers the message, up to
+///   [`with_max_deliveries`](Self::with_max_deliveries) times. Dead-lettered
+///   and rejected messages go to the subscription's dead-letter queue or
+///   topic. With [`AckMode::None`], every message is delivered once.
+/// - A subscription with [`concurrency`](SubscribeOptions::concurrency) above
+///   one handles messages from concurrent publishers in parallel, up to that
+///   limit; further publishers wait for a free slot.
+/// - Publishing with [`PublishOptions::confirm`] waits for the
+///   [`confirm delay`](Self::set_confirm_delay), bounded by
+///   [`PublishOptions::timeout`]. A message that is not confirmed in time is
+///   neither recorded nor delivered.
+/// - Messages abandoned by a [`drain`](Subscription::drain) that times out are
+///   dropped, since there is no queue to return them to.
+///
+/// ```
+/// use armature_messaging::{FnHandler, InMemoryBroker, Message, MessageBroker, ProcessingResult};
+/// use std::sync::Arc;
+///
+/// # tokio_test::block_on(async {
+/// let broker = InMemoryBroker::new();
+/// let handler = FnHandler(|message: Message| async move {
+///     assert_eq!(message.payload_str()?, "created");
+///     Ok(ProcessingResult::Success)
+/// });
+/// broker.subscribe("orders.*", Arc::new(handler)).await.unwrap();
+///
+/// broker.publish(Message::new("orders.new", "created")).await.unwrap();
+/// assert_eq!(broker.published("orders.new").len(), 1);
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct InMemoryBroker {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    published: Mutex<HashMap<String, Vec<Message>>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+    group_turns: Mutex<HashMap<(String, String), usize>>,
+    max_deliveries: u32,
+    confirm_delay: Mutex<Option<Duration>>,
+    connected: AtomicBool,
+}
+
+#[derive(Clone)]
+struct Subscriber {
+    pattern: String,
+    group: Option<String>,
+    handler: Arc<dyn MessageHandler>,
+    ack_mode: AckMode,
+    dead_letter_queue: Option<Arc<dyn DeadLetterQueue>>,
+    dead_letter_topic: Option<String>,
+    active: Arc<AtomicBool>,
+    in_flight: InFlight,
+    dispatcher: Option<Arc<ConcurrentDispatcher>>,
+}
+
+impl InMemoryBroker {
+    /// Create an empty broker
+    pub fn new() -> Self {
+        Self::with_max_deliveries(DEFAULT_MAX_DELIVERIES)
+    }
+
+    /// Create a broker that gives up on a retried message after `max` deliveries
+    pub fn with_max_deliveries(max: u32) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                published: Mutex::new(HashMap::new()),
+                subscribers: Mutex::new(Vec::new()),
+                group_turns: Mutex::new(HashMap::new()),
+                max_deliveries: max.max(1),
+                confirm_delay: Mutex::new(None),
+                connected: AtomicBool::new(true),
+            }),
+        }
+    }
+
+    /// Get the messages published with a routing key, oldest first
+    pub fn published(&self, topic: &str) -> Vec<Message> {
+        self.inner
+            .published
+            .lock()
+            .unwrap()
+            .get(topic)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Make confirmed publishes wait before the broker accepts them
+    ///
+    /// Simulates a slow broker for testing publish timeouts.
+    pub fn set_confirm_delay(&self, delay: Duration) {
+        *self.inner.confirm_delay.lock().unwrap() = Some(delay);
+    }
+
+    /// Forget all published messages
+    pub fn clear(&self) {
+        self.inner.published.lock().unwrap().clear();
+    }
+
+    /// Pick the subscriptions that receive a message routed with `key`.
+    fn recipients(&self, key: &str) -> Vec<Subscriber> {
+        let mut subscribers = self.inner.subscribers.lock().unwrap();
+        subscribers.retain(|s| s.active.load(Ordering::SeqCst));
+
+        let mut recipients = Vec::new();
+        let mut groups: Vec<(String, String)> = Vec::new();
+        for subscriber in subscribers.iter() {
+            if !topic_matches(&subscriber.pattern, key) {
+                continue;
+            }
+            match &subscriber.group {
+                None => recipients.push(subscriber.clone()),
+                Some(group) => {
+                    let group = (subscriber.pattern.clone(), group.clone());
+                    if !groups.contains(&group) {
+                        groups.push(group);
+                    }
+                }
+            }
+        }
+
+        let mut turns = self.inner.group_turns.lock().unwrap();
+        for (pattern, group) in groups {
+            let members: Vec<&Subscriber> = subscribers
+                .iter()
+                .filter(|s| s.pattern == pattern && s.group.as_ref() == Some(&group))
+                .collect();
+            let turn = turns.entry((pattern, group)).or_default();
+            recipients.push(members[*turn % members.len()].clone());
+            *turn = turn.wrapping_add(1);
+        }
+
+        recipients
+    }
+
+    async fn deliver(&self, subscriber: &Subscriber, message: Message) {
+        let _guard = subscriber.in_flight.start();
+        let mut attempt = 1;
+        let reason = loop {
+            let handled = subscriber.handler.handle(message.clone());
+            let Some(result) = subscriber.in_flight.run(handled).await else {
+                debug!(message_id = %message.id, "Message abandoned by draining subscription");
+                return;
+            };
+            if subscriber.ack_mode == AckMode::None {
+                if let Err(e) = result {
+                    error!(error = %e, "Message handler error");
+                }
+                return;
+            }
+
+            match result {
+                Ok(ProcessingResult::Success) => return,
+                Ok(ProcessingResult::Retry) | Err(_) if attempt < self.inner.max_deliveries => {
+                    debug!(message_id = %message.id, attempt, "Redelivering message");
+                    attempt += 1;
+                }
+                Ok(ProcessingResult::Retry) | Err(_) => break DeadLetterReason::RetriesExhausted,
+                Ok(ProcessingResult::DeadLetter) => break DeadLetterReason::DeadLettered,
+                Ok(ProcessingResult::Reject) => break DeadLetterReason::Rejected,
+            }
+        };
+
+        let result = match (&subscriber.dead_letter_queue, &subscriber.dead_letter_topic) {
+            (Some(queue), _) => {
+                forward_to_dead_letter(queue.as_ref(), message, reason, attempt).await
+            }
+            (None, Some(topic)) => {
+                let queue = TopicDeadLetterQueue {
+                    broker: self,
+                    topic,
+                };
+                forward_to_dead_letter(&queue, message, reason, attempt).await
+            }
+            (None, None) => {
+                debug!(message_id = %message.id, reason = %reason, "Message dropped");
+                Ok(())
+            }
+        };
+        // There is nothing to requeue to; forward_to_dead_letter already logged it
+        let _ = result;
+    }
+}
+
+impl Default for InMemoryBroker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MessageBroker for InMemoryBroker {
+    type Subscription = InMemorySubscription;
+
+    async fn publish(&self, message: Message) -> Result<(), MessagingError> {
+        self.publish_with_options(message, PublishOptions::default())
+            .await
+    }
+
+    async fn publish_with_options(
+        &self,
+        mut message: Message,
+        options: PublishOptions,
+    ) -> Result<(), MessagingError> {
+        if !self.is_connected() {
+            return Err(MessagingError::ChannelClosed(
+                "in-memory broker is closed".to_string(),
+            ));
+        }
+
+        if options.confirm {
+            let delay = *self.inner.confirm_delay.lock().unwrap();
+            await_confirm(&options, async {
+                if let Some(delay) = delay {
+                    tokio::time::sleep(delay).await;
+                }
+                Ok(())
+            })
+            .await?;
+        }
+
+        if let Some(key) = options.routing_key {
+            message.topic = key;
+        }
+
+        debug!(topic = %message.topic, message_id = %message.id, "Publishing message in memory");
+
+        self.inner
+            .published
+            .lock()
+            .unwrap()
+            .entry(message.topic.clone())
+            .or_default()
+            .push(message.clone());
+
+        for subscriber in self.recipients(&message.topic) {
+            let Some(dispatcher) = subscriber.dispatcher.clone() else {
+                self.deliver(&subscriber, message.clone()).await;
+                continue;
+            };
+
+            // Still wait for the handler so publishing stays synchronous
+            let (done, delivered) = oneshot::channel();
+            let broker = self.clone();
+            let message = message.clone();
+            dispatcher
+                .dispatch(async move {
+                    broker.deliver(&subscriber, message).await;
+                    let _ = done.send(());
+                })
+                .await?;
+            let _ = delivered.await;
+        }
+
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        topic: &str,
+        handler: Arc<dyn MessageHandler>,
+    ) -> Result<Self::Subscription, MessagingError> {
+        self.subscribe_with_options(topic, handler, SubscribeOptions::default())
+            .await
+    }
+
+    async fn subscribe_with_options(
+        &self,
+        topic: &str,
+        handler: Arc<dyn MessageHandler>,
+        options: SubscribeOptions,
+    ) -> Result<Self::Subscription, MessagingError> {
+        let handler = options.wrap_handler(handler);
+        let dispatcher = ConcurrentDispatcher::from_options(&options).map(Arc::new);
+
+        if !self.is_connected() {
+            return Err(MessagingError::ChannelClosed(
+                "in-memory broker is closed".to_string(),
+            ));
+        }
+
+        let active = Arc::new(AtomicBool::new(true));
+        let in_flight = InFlight::new();
+        self.inner.subscribers.lock().unwrap().push(Subscriber {
+            pattern: topic.to_string(),
+            group: options.consumer_group,
+            handler,
+            ack_mode: options.ack_mode,
+            dead_letter_queue: options.dead_letter_queue,
+            dead_letter_topic: options.dead_letter_topic,
+            active: active.clone(),
+            in_flight: in_flight.clone(),
+            dispatcher,
+        });
+
+        debug!(topic = topic, "Subscribed in memory");
+        Ok(InMemorySubscription {
+            topic: topic.to_string(),
+            active,
+            in_flight,
+        })
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.connected.load(Ordering::SeqCst)
+    }
+
+    async fn close(&self) -> Result<(), MessagingError> {
+        self.inner.connected.store(false, Ordering::SeqCst);
+        for subscriber in self.inner.subscribers.lock().unwrap().drain(..) {
+            subscriber.active.store(false, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+/// Publishes dead-lettered messages back into the broker
+struct TopicDeadLetterQueue<'a> {
+    broker: &'a InMemoryBroker,
+    topic: &'a str,
+}
+
+#[async_trait]
+impl DeadLetterQueue for TopicDeadLetterQueue<'_> {
+    async fn send(
+        &self,
+        mut message: Message,
+        _reason: DeadLetterReason,
+    ) -> Result<(), MessagingError> {
+        message.topic = self.topic.to_string();
+        self.broker.publish(message).await
+    }
+}
+
+/// In-memory subscription handle
+pub struct InMemorySubscription {
+    topic: String,
+    active: Arc<AtomicBool>,
+    in_flight: InFlight,
+}
+
+#[async_trait]
+impl Subscription for InMemorySubscription {
+    async fn unsubscribe(&self) -> Result<(), MessagingError> {
+        self.active.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        self.unsubscribe().await?;
+        self.in_flight.drain(timeout).await
+    }
+}
+
+/// Match a routing key against a pattern with `*` and `#` wildcards.
+fn topic_matches(pattern: &str, topic: &str) -> bool {
+    fn matches(pattern: &[&str], topic: &[&str]) -> bool {
+        match (pattern.split_first(), topic.split_first()) {
+            (None, None) => true,
+            (Some((&"#", rest)), _) => {
+                matches(rest, topic) || (!topic.is_empty() && matches(pattern, &topic[1..]))
+            }
+            (Some((&"*", rest)), Some((_, topic_rest))) => matches(rest, topic_rest),
+            (Some((segment, rest)), Some((topic_segment, topic_rest))) => {
+                segment == topic_segment && matches(rest, topic_rest)
+            }
+            _ => false,
+        }
+    }
+
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let topic: Vec<&str> = topic.split('.').collect();
+    matches(&pattern, &topic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DEAD_LETTER_REASON_HEADER, FnHandler, InMemoryDeadLetterQueue};
+    use std::sync::atomic::AtomicUsize;
+
+    fn counting(result: ProcessingResult) -> (Arc<AtomicUsize>, Arc<dyn MessageHandler>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let handler = FnHandler(move |_message: Message| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let result = result.clone();
+            async move { Ok(result) }
+        });
+        (calls, Arc::new(handler))
+    }
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("orders", "orders"));
+        assert!(topic_matches("orders.*", "orders.created"));
+        assert!(!topic_matches("orders.*", "orders.created.eu"));
+        assert!(topic_matches("orders.#", "orders.created.eu"));
+        assert!(topic_matches("orders.#", "orders"));
+        assert!(topic_matches("#.eu", "orders.created.eu"));
+        assert!(!topic_matches("orders", "payments"));
+    }
+
+    #[tokio::test]
+    async fn test_routing_key_and_published() {
+        let broker = InMemoryBroker::new();
+        let (calls, handler) = counting(ProcessingResult::Success);
+        broker.subscribe("orders.eu", handler).await.unwrap();
+
+        let options = PublishOptions::default().with_routing_key("orders.eu");
+        broker
+            .publish_with_options(Message::new("orders", "a"), options)
+            .await
+            .unwrap();
+        broker.publish(Message::new("orders", "b")).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(broker.published("orders.eu").len(), 1);
+        assert_eq!(broker.published("orders").len(), 1);
+
+        broker.clear();
+        assert!(broker.published("orders").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_consumer_group_takes_turns() {
+        let broker = InMemoryBroker::new();
+        let (first, handler) = counting(ProcessingResult::Success);
+        let options = SubscribeOptions::default().with_consumer_group("workers");
+        broker
+            .subscribe_with_options("jobs", handler, options.clone())
+            .await
+            .unwrap();
+        let (second, handler) = counting(ProcessingResult::Success);
+        broker
+            .subscribe_with_options("jobs", handler, options)
+            .await
+            .unwrap();
+        let (observer, handler) = counting(ProcessingResult::Success);
+        broker.subscribe("jobs", handler).await.unwrap();
+
+        for _ in 0..4 {
+            broker.publish(Message::new("jobs", "job")).await.unwrap();
+        }
+
+        assert_eq!(first.load(Ordering::SeqCst), 2);
+        assert_eq!(second.load(Ordering::SeqCst), 2);
+        assert_eq!(observer.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_retry_then_dead_letter_topic() {
+        let broker = InMemoryBroker::with_max_deliveries(3);
+        let (calls, handler) = counting(ProcessingResult::Retry);
+        let options = SubscribeOptions::default().with_dead_letter_topic("orders.dlq");
+        broker
+            .subscribe_with_options("orders", handler, options)
+            .await
+            .unwrap();
+
+        broker.publish(Message::new("orders", "a")).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        let dead = broker.published("orders.dlq");
+        assert_eq!(dead.len(), 1);
+        assert_eq!(
+            dead[0].headers[DEAD_LETTER_REASON_HEADER],
+            "retries exhausted"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ack_mode_none_delivers_once() {
+        let broker = InMemoryBroker::new();
+        let dlq = Arc::new(InMemoryDeadLetterQueue::new());
+        let (calls, handler) = counting(ProcessingResult::Retry);
+        let options = SubscribeOptions::default()
+            .with_ack_mode(AckMode::None)
+            .with_dead_letter_queue(dlq.clone());
+        broker
+            .subscribe_with_options("orders", handler, options)
+            .await
+            .unwrap();
+
+        broker.publish(Message::new("orders", "a")).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(dlq.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reject_goes_to_dead_letter_queue() {
+        let broker = InMemoryBroker::new();
+        let dlq = Arc::new(InMemoryDeadLetterQueue::new());
+        let (_, handler) = counting(ProcessingResult::Reject);
+        let options = SubscribeOptions::default().with_dead_letter_queue(dlq.clone());
+        broker
+            .subscribe_with_options("orders", handler, options)
+            .await
+            .unwrap();
+
+        broker.publish(Message::new("orders", "a")).await.unwrap();
+
+        assert_eq!(dlq.messages()[0].1, DeadLetterReason::Rejected);
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_in_flight() {
+        let broker = InMemoryBroker::new();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let released = Arc::new(tokio::sync::Mutex::new(Some(released)));
+        let handler = FnHandler(move |_message: Message| {
+            let released = released.clone();
+            async move {
+                if let Some(released) = released.lock().await.take() {
+                    let _ = released.await;
+                }
+                Ok(ProcessingResult::Success)
+            }
+        });
+        let subscription = broker.subscribe("orders", Arc::new(handler)).await.unwrap();
+
+        let publisher = broker.clone();
+        let publish =
+            tokio::spawn(async move { publisher.publish(Message::new("orders", "a")).await });
+        while subscription.in_flight() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let drain = tokio::spawn(async move {
+            let result = subscription.drain(Duration::from_secs(5)).await;
+            (result, subscription)
+        });
+        release.send(()).unwrap();
+
+        let (result, subscription) = drain.await.unwrap();
+        assert!(result.is_ok());
+        assert!(publish.await.unwrap().is_ok());
+        assert!(!subscription.is_active());
+        assert_eq!(subscription.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_drain_abandons_on_timeout() {
+        let broker = InMemoryBroker::new();
+        let handler = FnHandler(|_message: Message| async {
+            std::future::pending::<()>().await;
+            Ok(ProcessingResult::Success)
+        });
+        let subscription = broker.subscribe("orders", Arc::new(handler)).await.unwrap();
+
+        let publisher = broker.clone();
+        let publish =
+            tokio::spawn(async move { publisher.publish(Message::new("orders", "a")).await });
+        while subscription.in_flight() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let result = subscription.drain(Duration::from_millis(10)).await;
+
+        assert!(matches!(result, Err(MessagingError::Timeout(_))));
+        assert!(publish.await.unwrap().is_ok());
+        assert_eq!(subscription.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limits_parallel_handlers() {
+        let broker = InMemoryBroker::new();
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let handler = FnHandler({
+            let running = running.clone();
+            let peak = peak.clone();
+            move |_message: Message| {
+                let running = running.clone();
+                let peak = peak.clone();
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(ProcessingResult::Success)
+                }
+            }
+        });
+        let options = SubscribeOptions::default().with_concurrency(2);
+        broker
+            .subscribe_with_options("orders", Arc::new(handler), options)
+            .await
+            .unwrap();
+
+        let publishes: Vec<_> = (0..6)
+            .map(|i| {
+                let broker = broker.clone();
+                tokio::spawn(
+                    async move { broker.publish(Message::new("orders", i.to_string())).await },
+                )
+            })
+            .collect();
+        for publish in publishes {
+            publish.await.unwrap().unwrap();
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(running.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_confirm_times_out() {
+        let broker = InMemoryBroker::new();
+        let (calls, handler) = counting(ProcessingResult::Success);
+        broker.subscribe("orders", handler).await.unwrap();
+        broker.set_confirm_delay(Duration::from_millis(200));
+
+        let result = broker
+            .publish_with_options(
+                Message::new("orders", "a"),
+                PublishOptions::default().with_confirm(Duration::from_millis(10)),
+            )
+            .await;
+        assert!(matches!(result, Err(MessagingError::ConfirmTimeout(_))));
+        assert!(broker.published("orders").is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        broker
+            .publish_with_options(
+                Message::new("orders", "b"),
+                PublishOptions::default().with_confirm(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(broker.published("orders").len(), 1);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_and_close() {
+        let broker = InMemoryBroker::new();
+        let (calls, handler) = counting(ProcessingResult::Success);
+        let subscription = broker.subscribe("orders", handler).await.unwrap();
+
+        subscription.unsubscribe().await.unwrap();
+        assert!(!subscription.is_active());
+        broker.publish(Message::new("orders", "a")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        broker.close().await.unwrap();
+        assert!(!broker.is_connected());
+        assert!(matches!(
+            broker.publish(Message::new("orders", "a")).await,
+            Err(MessagingError::ChannelClosed(_))
+        ));
+    }
+}
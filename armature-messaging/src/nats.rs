@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use async_nats::Client;
 use async_trait::async_trait;
@@ -11,8 +12,10 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info};
 
 use crate::{
-    Message, MessageBroker, MessageHandler, MessagingConfig, MessagingError, ProcessingResult,
-    PublishOptions, SubscribeOptions, Subscription, config::NatsConfig,
+    ConcurrentDispatcher, DeadLetterQueue, DeadLetterReason, InFlight, InFlightGuard, Message,
+    MessageBroker, MessageHandler, MessagingConfig, MessagingError, ProcessingResult,
+    PublishOptions, SubscribeOptions, Subscription, await_confirm, config::NatsConfig,
+    forward_to_dead_letter,
 };
 
 /// NATS message broker
@@ -90,6 +93,10 @@ impl NatsBroker {
             headers.insert("reply-to", reply_to.as_str());
         }
 
+        if let Some(ref schema_version) = message.schema_version {
+            headers.insert("schema-version", schema_version.as_str());
+        }
+
         for (key, value) in &message.headers {
             headers.insert(key.as_str(), value.as_str());
         }
@@ -110,19 +117,21 @@ impl MessageBroker for NatsBroker {
     async fn publish_with_options(
         &self,
         message: Message,
-        _options: PublishOptions,
+        options: PublishOptions,
     ) -> Result<(), MessagingError> {
         let subject = &message.topic;
         let headers = Self::build_headers(&message);
 
         debug!(subject = subject, message_id = %message.id, "Publishing message to NATS");
 
-        self.client
-            .publish_with_headers(subject.clone(), headers, message.payload.into())
-            .await
-            .map_err(MessagingError::from)?;
-
-        Ok(())
+        // Core NATS has no confirms; the timeout bounds handing off to the client
+        await_confirm(&options, async {
+            self.client
+                .publish_with_headers(subject.clone(), headers, message.payload.into())
+                .await
+                .map_err(MessagingError::from)
+        })
+        .await
     }
 
     async fn subscribe(
@@ -140,6 +149,9 @@ impl MessageBroker for NatsBroker {
         handler: Arc<dyn MessageHandler>,
         options: SubscribeOptions,
     ) -> Result<Self::Subscription, MessagingError> {
+        let handler = options.wrap_handler(handler);
+        let dispatcher = ConcurrentDispatcher::from_options(&options);
+
         let subscriber = if let Some(ref group) = options.consumer_group {
             // Queue group subscription for load balancing
             self.client
@@ -153,20 +165,36 @@ impl MessageBroker for NatsBroker {
                 .map_err(MessagingError::from)?
         };
 
+        let dead_letter = options.dead_letter_queue.clone().or_else(|| {
+            options.dead_letter_topic.clone().map(|subject| {
+                Arc::new(NatsDeadLetterQueue {
+                    client: self.client.clone(),
+                    subject,
+                }) as Arc<dyn DeadLetterQueue>
+            })
+        });
+
         let active = Arc::new(AtomicBool::new(true));
+        let in_flight = InFlight::new();
 
         let subscription = NatsSubscription {
             topic: topic.to_string(),
             active: active.clone(),
+            in_flight: in_flight.clone(),
         };
 
         // Store active flag for cleanup
         self.active_flags.write().await.push(active.clone());
 
         // Spawn consumer task
-        let topic_owned = topic.to_string();
+        let context = DeliveryContext {
+            handler,
+            dead_letter,
+            topic: topic.to_string(),
+            in_flight,
+        };
         tokio::spawn(async move {
-            consume_messages(subscriber, handler, &topic_owned, active).await;
+            consume_messages(subscriber, context, active, dispatcher).await;
         });
 
         info!(subject = topic, "Subscribed to NATS subject");
@@ -197,34 +225,39 @@ impl MessageBroker for NatsBroker {
     }
 }
 
+/// State shared by the consumer loop and the handlers it runs
+#[derive(Clone)]
+struct DeliveryContext {
+    handler: Arc<dyn MessageHandler>,
+    dead_letter: Option<Arc<dyn DeadLetterQueue>>,
+    topic: String,
+    in_flight: InFlight,
+}
+
 async fn consume_messages(
     mut subscriber: async_nats::Subscriber,
-    handler: Arc<dyn MessageHandler>,
-    topic: &str,
+    context: DeliveryContext,
     active: Arc<AtomicBool>,
+    dispatcher: Option<ConcurrentDispatcher>,
 ) {
-    while active.load(Ordering::SeqCst) {
+    let in_flight = context.in_flight.clone();
+
+    while active.load(Ordering::SeqCst) && !in_flight.is_aborted() {
         match subscriber.next().await {
             Some(nats_msg) => {
-                let message = nats_message_to_message(&nats_msg, topic);
+                // Core NATS cannot redeliver, so messages after a drain are dropped
+                if !active.load(Ordering::SeqCst) {
+                    break;
+                }
 
-                match handler.handle(message).await {
-                    Ok(result) => match result {
-                        ProcessingResult::Success => {
-                            debug!("Message processed successfully");
-                        }
-                        ProcessingResult::Retry => {
-                            debug!(
-                                "Message retry requested (NATS does not support built-in retry)"
-                            );
+                let job = handle_message(context.clone(), nats_msg, in_flight.start());
+                match &dispatcher {
+                    Some(dispatcher) => {
+                        if dispatcher.dispatch(job).await.is_err() {
+                            break;
                         }
-                        ProcessingResult::DeadLetter | ProcessingResult::Reject => {
-                            debug!("Message rejected");
-                        }
-                    },
-                    Err(e) => {
-                        error!(error = %e, "Message handler error");
                     }
+                    None => job.await,
                 }
             }
             None => {
@@ -235,12 +268,60 @@ async fn consume_messages(
     }
 }
 
+/// Handle one message received from NATS.
+async fn handle_message(
+    context: DeliveryContext,
+    nats_msg: async_nats::Message,
+    _in_flight: InFlightGuard,
+) {
+    let DeliveryContext {
+        handler,
+        dead_letter,
+        topic,
+        in_flight,
+    } = context;
+
+    let message = nats_message_to_message(&nats_msg, &topic);
+    let original = dead_letter.as_ref().map(|_| message.clone());
+
+    let Some(outcome) = in_flight.run(handler.handle(message)).await else {
+        return;
+    };
+
+    match outcome {
+        Ok(result) => match result {
+            ProcessingResult::Success => {
+                debug!("Message processed successfully");
+            }
+            ProcessingResult::Retry => {
+                debug!("Message retry requested (NATS does not support built-in retry)");
+            }
+            ProcessingResult::DeadLetter | ProcessingResult::Reject => {
+                if let (Some(queue), Some(message), Some(reason)) = (
+                    &dead_letter,
+                    original,
+                    DeadLetterReason::from_result(&result),
+                ) {
+                    // Core NATS has no redelivery, so a failure is only logged
+                    let _ = forward_to_dead_letter(queue.as_ref(), message, reason, 1).await;
+                } else {
+                    debug!("Message rejected");
+                }
+            }
+        },
+        Err(e) => {
+            error!(error = %e, "Message handler error");
+        }
+    }
+}
+
 fn nats_message_to_message(nats_msg: &async_nats::Message, topic: &str) -> Message {
     let headers = HashMap::new();
     let mut message_id = None;
     let mut correlation_id = None;
     let mut content_type = None;
     let mut reply_to = None;
+    let mut schema_version = None;
     let mut timestamp = chrono::Utc::now();
 
     if let Some(nats_headers) = nats_msg.headers.as_ref() {
@@ -257,6 +338,9 @@ fn nats_message_to_message(nats_msg: &async_nats::Message, topic: &str) -> Messa
         if let Some(value) = nats_headers.get("reply-to") {
             reply_to = Some(AsRef::<str>::as_ref(&value).to_string());
         }
+        if let Some(value) = nats_headers.get("schema-version") {
+            schema_version = Some(AsRef::<str>::as_ref(&value).to_string());
+        }
         if let Some(value) = nats_headers.get("timestamp") {
             let ts_str: &str = AsRef::<str>::as_ref(&value);
             if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(ts_str) {
@@ -283,6 +367,29 @@ fn nats_message_to_message(nats_msg: &async_nats::Message, topic: &str) -> Messa
         content_type,
         priority: None,
         ttl: None,
+        schema_version,
+    }
+}
+
+/// Publishes dead-lettered messages to a NATS subject
+struct NatsDeadLetterQueue {
+    client: Client,
+    subject: String,
+}
+
+#[async_trait]
+impl DeadLetterQueue for NatsDeadLetterQueue {
+    async fn send(
+        &self,
+        message: Message,
+        _reason: DeadLetterReason,
+    ) -> Result<(), MessagingError> {
+        let headers = NatsBroker::build_headers(&message);
+        self.client
+            .publish_with_headers(self.subject.clone(), headers, message.payload.into())
+            .await
+            .map_err(MessagingError::from)?;
+        Ok(())
     }
 }
 
@@ -290,6 +397,7 @@ fn nats_message_to_message(nats_msg: &async_nats::Message, topic: &str) -> Messa
 pub struct NatsSubscription {
     topic: String,
     active: Arc<AtomicBool>,
+    in_flight: InFlight,
 }
 
 #[async_trait]
@@ -307,6 +415,15 @@ impl Subscription for NatsSubscription {
     fn topic(&self) -> &str {
         &self.topic
     }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        self.unsubscribe().await?;
+        self.in_flight.drain(timeout).await
+    }
 }
 
 #[cfg(test)]
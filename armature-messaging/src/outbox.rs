@@ -0,0 +1,347 @@
+//! Transactional outbox and relay
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tracing::{debug, error, warn};
+
+use crate::{Message, MessageBroker, MessagingError};
+
+/// A message waiting in an outbox
+#[derive(Debug, Clone)]
+pub struct OutboxRecord {
+    /// Record identifier, the ID of the message
+    pub id: String,
+    /// The message to publish
+    pub message: Message,
+    /// When the message was enqueued
+    pub created_at: DateTime<Utc>,
+}
+
+impl OutboxRecord {
+    /// Create a record for a message
+    pub fn new(message: Message) -> Self {
+        Self {
+            id: message.id.clone(),
+            message,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Storage for messages that must be published once a transaction commits
+///
+/// Implementations write to the same database as the business data, so
+/// [`enqueue`](Self::enqueue) should join the caller's transaction.
+/// [`OutboxRelay`] then publishes the pending records.
+#[async_trait]
+pub trait Outbox: Send + Sync {
+    /// Store a message for publishing
+    async fn enqueue(&self, message: Message) -> Result<(), MessagingError>;
+
+    /// Get up to `limit` unpublished records, oldest first
+    async fn poll_pending(&self, limit: usize) -> Result<Vec<OutboxRecord>, MessagingError>;
+
+    /// Mark a record as published so it is not polled again
+    async fn mark_published(&self, id: &str) -> Result<(), MessagingError>;
+}
+
+/// Outbox that keeps records in memory
+///
+/// Useful in tests; it offers none of the transactional guarantees of a
+/// database-backed outbox.
+#[derive(Debug, Default)]
+pub struct InMemoryOutbox {
+    pending: Mutex<Vec<OutboxRecord>>,
+    published: Mutex<Vec<OutboxRecord>>,
+}
+
+impl InMemoryOutbox {
+    /// Create an empty outbox
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the unpublished records, oldest first
+    pub fn pending(&self) -> Vec<OutboxRecord> {
+        self.pending.lock().unwrap().clone()
+    }
+
+    /// Get the published records in the order they were marked
+    pub fn published(&self) -> Vec<OutboxRecord> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Outbox for InMemoryOutbox {
+    async fn enqueue(&self, message: Message) -> Result<(), MessagingError> {
+        self.pending
+            .lock()
+            .unwrap()
+            .push(OutboxRecord::new(message));
+        Ok(())
+    }
+
+    async fn poll_pending(&self, limit: usize) -> Result<Vec<OutboxRecord>, MessagingError> {
+        Ok(self
+            .pending
+            .lock()
+            .unwrap()
+            .iter()
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn mark_published(&self, id: &str) -> Result<(), MessagingError> {
+        let mut pending = self.pending.lock().unwrap();
+        let index = pending
+            .iter()
+            .position(|record| record.id == id)
+            .ok_or_else(|| MessagingError::NotFound(format!("outbox record {}", id)))?;
+        let record = pending.remove(index);
+        self.published.lock().unwrap().push(record);
+        Ok(())
+    }
+}
+
+/// Publishes pending outbox records through a broker
+///
+/// Records are marked published only after the broker accepts them. A record
+/// that fails to publish stays pending and is retried on the next poll, while
+/// the rest of the batch carries on. Delivery is at least once: if marking
+/// fails after publishing, the message is published again, so consumers
+/// should deduplicate by message ID.
+///
+/// ```rust,ignore
+/// let relay = Arc::new(OutboxRelay::new(broker, outbox));
+/// tokio::spawn({
+///     let relay = relay.clone();
+///     async move { relay.run().await }
+/// });
+/// // ...
+/// relay.stop();
+/// ```
+pub struct OutboxRelay<B, O> {
+    broker: Arc<B>,
+    outbox: Arc<O>,
+    batch_size: usize,
+    poll_interval: Duration,
+    running: AtomicBool,
+}
+
+impl<B: MessageBroker, O: Outbox> OutboxRelay<B, O> {
+    /// Create a relay polling 100 records every second
+    pub fn new(broker: Arc<B>, outbox: Arc<O>) -> Self {
+        Self {
+            broker,
+            outbox,
+            batch_size: 100,
+            poll_interval: Duration::from_secs(1),
+            running: AtomicBool::new(false),
+        }
+    }
+
+    /// Set the maximum number of records published per poll
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set how long to wait between polls when the outbox has been emptied
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Publish one batch of pending records
+    ///
+    /// Returns the number of records published and marked.
+    pub async fn relay_once(&self) -> Result<usize, MessagingError> {
+        let records = self.outbox.poll_pending(self.batch_size).await?;
+        let mut relayed = 0;
+
+        for record in records {
+            if let Err(e) = self.broker.publish(record.message).await {
+                warn!(error = %e, record_id = %record.id, "Failed to publish outbox record");
+                continue;
+            }
+
+            match self.outbox.mark_published(&record.id).await {
+                Ok(()) => relayed += 1,
+                Err(e) => {
+                    error!(error = %e, record_id = %record.id, "Failed to mark outbox record published");
+                }
+            }
+        }
+
+        if relayed > 0 {
+            debug!(relayed, "Relayed outbox records");
+        }
+        Ok(relayed)
+    }
+
+    /// Relay records until [`stop`](Self::stop) is called
+    ///
+    /// Full batches are followed immediately by the next poll; otherwise the
+    /// relay waits for the poll interval.
+    pub async fn run(&self) {
+        self.running.store(true, Ordering::SeqCst);
+
+        while self.running.load(Ordering::SeqCst) {
+            match self.relay_once().await {
+                Ok(relayed) if relayed >= self.batch_size => continue,
+                Ok(_) => {}
+                Err(e) => error!(error = %e, "Failed to poll outbox"),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Stop a running relay after its current poll
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// Check if the relay is running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::*;
+    use crate::{
+        InMemoryBroker, InMemorySubscription, MessageHandler, PublishOptions, SubscribeOptions,
+    };
+
+    /// Fails to publish to topics in `failing`
+    struct FlakyBroker {
+        inner: InMemoryBroker,
+        failing: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl MessageBroker for FlakyBroker {
+        type Subscription = InMemorySubscription;
+
+        async fn publish(&self, message: Message) -> Result<(), MessagingError> {
+            self.publish_with_options(message, PublishOptions::default())
+                .await
+        }
+
+        async fn publish_with_options(
+            &self,
+            message: Message,
+            options: PublishOptions,
+        ) -> Result<(), MessagingError> {
+            if self.failing.lock().unwrap().contains(&message.topic) {
+                return Err(MessagingError::Publish("unavailable".to_string()));
+            }
+            self.inner.publish_with_options(message, options).await
+        }
+
+        async fn subscribe(
+            &self,
+            topic: &str,
+            handler: Arc<dyn MessageHandler>,
+        ) -> Result<Self::Subscription, MessagingError> {
+            self.inner.subscribe(topic, handler).await
+        }
+
+        async fn subscribe_with_options(
+            &self,
+            topic: &str,
+            handler: Arc<dyn MessageHandler>,
+            options: SubscribeOptions,
+        ) -> Result<Self::Subscription, MessagingError> {
+            self.inner
+                .subscribe_with_options(topic, handler, options)
+                .await
+        }
+
+        fn is_connected(&self) -> bool {
+            self.inner.is_connected()
+        }
+
+        async fn close(&self) -> Result<(), MessagingError> {
+            self.inner.close().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_publishes_and_marks() {
+        let broker = Arc::new(InMemoryBroker::new());
+        let outbox = Arc::new(InMemoryOutbox::new());
+        outbox.enqueue(Message::new("orders", "a")).await.unwrap();
+        outbox.enqueue(Message::new("orders", "b")).await.unwrap();
+        outbox.enqueue(Message::new("orders", "c")).await.unwrap();
+
+        let relay = OutboxRelay::new(broker.clone(), outbox.clone()).with_batch_size(2);
+
+        assert_eq!(relay.relay_once().await.unwrap(), 2);
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert_eq!(relay.relay_once().await.unwrap(), 0);
+
+        let payloads: Vec<Vec<u8>> = broker
+            .published("orders")
+            .into_iter()
+            .map(|m| m.payload)
+            .collect();
+        assert_eq!(payloads, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+        assert!(outbox.pending().is_empty());
+        assert_eq!(outbox.published().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_relay_leaves_failed_records_pending() {
+        let broker = Arc::new(FlakyBroker {
+            inner: InMemoryBroker::new(),
+            failing: Mutex::new(vec!["payments".to_string()]),
+        });
+        let outbox = Arc::new(InMemoryOutbox::new());
+        outbox.enqueue(Message::new("orders", "a")).await.unwrap();
+        outbox.enqueue(Message::new("payments", "b")).await.unwrap();
+        outbox.enqueue(Message::new("orders", "c")).await.unwrap();
+
+        let relay = OutboxRelay::new(broker.clone(), outbox.clone());
+
+        assert_eq!(relay.relay_once().await.unwrap(), 2);
+        let pending = outbox.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].message.topic, "payments");
+
+        broker.failing.lock().unwrap().clear();
+        assert_eq!(relay.relay_once().await.unwrap(), 1);
+        assert!(outbox.pending().is_empty());
+        assert_eq!(broker.inner.published("payments").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_until_stopped() {
+        let broker = Arc::new(InMemoryBroker::new());
+        let outbox = Arc::new(InMemoryOutbox::new());
+        let relay = Arc::new(
+            OutboxRelay::new(broker.clone(), outbox.clone())
+                .with_poll_interval(Duration::from_millis(5)),
+        );
+
+        let runner = relay.clone();
+        let task = tokio::spawn(async move { runner.run().await });
+
+        outbox.enqueue(Message::new("orders", "a")).await.unwrap();
+        while !outbox.pending().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        relay.stop();
+        task.await.unwrap();
+        assert!(!relay.is_running());
+        assert_eq!(broker.published("orders").len(), 1);
+    }
+}
@@ -0,0 +1,245 @@
+//! Client-side priority ordering for brokers without native priorities
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::oneshot;
+
+use crate::{Message, MessageHandler, MessagingError, ProcessingResult};
+
+/// Handler wrapper that delivers buffered messages highest-priority-first
+///
+/// The first message to arrive opens a window. Messages received before the
+/// window closes are queued with it, then all are handed to the inner handler
+/// one at a time by [`Message::priority`] (9 first, no priority counting as
+/// 0), oldest first within a priority. Messages arriving while the queue is
+/// being worked through join it in priority order.
+///
+/// Each message's result is still returned to the broker that delivered it,
+/// so acknowledgments are unaffected. Only messages delivered concurrently can
+/// be reordered, which needs a subscription with
+/// [`concurrency`](crate::SubscribeOptions::concurrency) above one.
+///
+/// Every message waits up to the window before it is handled, so this trades
+/// latency for ordering. Enable it with
+/// [`SubscribeOptions::priority_buffer`](crate::SubscribeOptions::priority_buffer).
+pub struct PriorityBuffer {
+    inner: Arc<dyn MessageHandler>,
+    window: Duration,
+    state: Arc<Mutex<BufferState>>,
+}
+
+#[derive(Default)]
+struct BufferState {
+    queue: BinaryHeap<Pending>,
+    dispatching: bool,
+    next_seq: u64,
+}
+
+type Reply = oneshot::Sender<Result<ProcessingResult, MessagingError>>;
+
+struct Pending {
+    priority: u8,
+    seq: u64,
+    message: Message,
+    reply: Reply,
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Max-heap: higher priority first, then lower sequence number
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PriorityBuffer {
+    /// Wrap a handler with a buffering window
+    pub fn new(inner: Arc<dyn MessageHandler>, window: Duration) -> Self {
+        Self {
+            inner,
+            window,
+            state: Arc::new(Mutex::new(BufferState::default())),
+        }
+    }
+
+    /// Get the buffering window
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    async fn dispatch(
+        inner: Arc<dyn MessageHandler>,
+        state: Arc<Mutex<BufferState>>,
+        window: Duration,
+    ) {
+        tokio::time::sleep(window).await;
+
+        loop {
+            let next = {
+                let mut state = state.lock().unwrap();
+                let next = state.queue.pop();
+                if next.is_none() {
+                    state.dispatching = false;
+                }
+                next
+            };
+            let Some(pending) = next else {
+                return;
+            };
+
+            let result = inner.handle(pending.message).await;
+            let _ = pending.reply.send(result);
+        }
+    }
+}
+
+#[async_trait]
+impl MessageHandler for PriorityBuffer {
+    async fn handle(&self, message: Message) -> Result<ProcessingResult, MessagingError> {
+        let (reply, result) = oneshot::channel();
+
+        let start_dispatch = {
+            let mut state = self.state.lock().unwrap();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.queue.push(Pending {
+                priority: message.priority.unwrap_or(0),
+                seq,
+                message,
+                reply,
+            });
+            !std::mem::replace(&mut state.dispatching, true)
+        };
+
+        if start_dispatch {
+            tokio::spawn(Self::dispatch(
+                self.inner.clone(),
+                self.state.clone(),
+                self.window,
+            ));
+        }
+
+        result.await.unwrap_or_else(|_| {
+            Err(MessagingError::Other(
+                "priority buffer dropped the message".to_string(),
+            ))
+        })
+    }
+
+    async fn on_deserialize_error(&self, error: &MessagingError) -> ProcessingResult {
+        self.inner.on_deserialize_error(error).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FnHandler;
+
+    fn recording() -> (Arc<Mutex<Vec<u8>>>, Arc<dyn MessageHandler>) {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let recorder = order.clone();
+        let handler = FnHandler(move |message: Message| {
+            recorder.lock().unwrap().push(message.priority.unwrap_or(0));
+            async { Ok(ProcessingResult::Success) }
+        });
+        (order, Arc::new(handler))
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_first_within_window() {
+        let (order, handler) = recording();
+        let buffer = Arc::new(PriorityBuffer::new(handler, Duration::from_millis(50)));
+
+        let low = tokio::spawn({
+            let buffer = buffer.clone();
+            async move {
+                buffer
+                    .handle(Message::new("jobs", "low").with_priority(1))
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let high = tokio::spawn({
+            let buffer = buffer.clone();
+            async move {
+                buffer
+                    .handle(Message::new("jobs", "high").with_priority(9))
+                    .await
+            }
+        });
+
+        assert!(matches!(low.await.unwrap(), Ok(ProcessingResult::Success)));
+        assert!(matches!(high.await.unwrap(), Ok(ProcessingResult::Success)));
+        assert_eq!(*order.lock().unwrap(), vec![9, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_returns_each_result_to_its_caller() {
+        let handler = FnHandler(|message: Message| async move {
+            Ok(match message.priority {
+                Some(9) => ProcessingResult::Reject,
+                _ => ProcessingResult::Success,
+            })
+        });
+        let buffer = PriorityBuffer::new(Arc::new(handler), Duration::from_millis(10));
+
+        let (first, second) = tokio::join!(
+            buffer.handle(Message::new("jobs", "a").with_priority(1)),
+            buffer.handle(Message::new("jobs", "b").with_priority(9)),
+        );
+
+        assert!(matches!(first, Ok(ProcessingResult::Success)));
+        assert!(matches!(second, Ok(ProcessingResult::Reject)));
+    }
+
+    #[cfg(feature = "memory")]
+    #[tokio::test]
+    async fn test_subscribe_option() {
+        use crate::{InMemoryBroker, MessageBroker, SubscribeOptions};
+
+        let broker = InMemoryBroker::new();
+        let (order, handler) = recording();
+        let options = SubscribeOptions::default().priority_buffer(Duration::from_millis(50));
+        broker
+            .subscribe_with_options("jobs", handler, options)
+            .await
+            .unwrap();
+
+        let low = tokio::spawn({
+            let broker = broker.clone();
+            async move {
+                broker
+                    .publish(Message::new("jobs", "low").with_priority(1))
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        broker
+            .publish(Message::new("jobs", "high").with_priority(9))
+            .await
+            .unwrap();
+        low.await.unwrap().unwrap();
+
+        assert_eq!(*order.lock().unwrap(), vec![9, 1]);
+    }
+}
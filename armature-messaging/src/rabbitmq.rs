@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures_util::StreamExt;
@@ -14,10 +15,14 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    AckMode, Message, MessageBroker, MessageHandler, MessagingConfig, MessagingError,
-    ProcessingResult, PublishOptions, SubscribeOptions, Subscription,
+    AckMode, ConcurrentDispatcher, DeadLetterQueue, DeadLetterReason, InFlight, InFlightGuard,
+    Message, MessageBroker, MessageHandler, MessagingConfig, MessagingError, ProcessingResult,
+    PublishOptions, SubscribeOptions, Subscription, await_confirm, forward_to_dead_letter,
 };
 
+/// AMQP header carrying [`Message::schema_version`]
+const SCHEMA_VERSION_HEADER: &str = "x-schema-version";
+
 /// RabbitMQ message broker
 pub struct RabbitMqBroker {
     connection: Arc<Connection>,
@@ -127,8 +132,8 @@ impl RabbitMqBroker {
             props = props.with_expiration(ttl.to_string().into());
         }
 
-        // Add headers
-        if !message.headers.is_empty() {
+        // Add headers; AMQP has no property for the schema version
+        if !message.headers.is_empty() || message.schema_version.is_some() {
             let mut headers = FieldTable::default();
             for (key, value) in &message.headers {
                 headers.insert(
@@ -136,6 +141,12 @@ impl RabbitMqBroker {
                     lapin::types::AMQPValue::LongString(value.clone().into()),
                 );
             }
+            if let Some(ref schema_version) = message.schema_version {
+                headers.insert(
+                    SCHEMA_VERSION_HEADER.into(),
+                    lapin::types::AMQPValue::LongString(schema_version.clone().into()),
+                );
+            }
             props = props.with_headers(headers);
         }
 
@@ -173,25 +184,28 @@ impl MessageBroker for RabbitMqBroker {
             "Publishing message"
         );
 
-        let confirm = self
-            .publish_channel
-            .basic_publish(
-                exchange,
-                routing_key,
-                BasicPublishOptions::default(),
-                &message.payload,
-                props,
-            )
-            .await?;
+        await_confirm(&options, async {
+            let confirm = self
+                .publish_channel
+                .basic_publish(
+                    exchange,
+                    routing_key,
+                    BasicPublishOptions::default(),
+                    &message.payload,
+                    props,
+                )
+                .await?;
 
-        if options.confirm {
-            confirm.await.map_err(|e| {
-                error!(error = %e, "Publisher confirm failed");
-                MessagingError::Publish(format!("Publisher confirm failed: {}", e))
-            })?;
-        }
+            if options.confirm {
+                confirm.await.map_err(|e| {
+                    error!(error = %e, "Publisher confirm failed");
+                    MessagingError::Publish(format!("Publisher confirm failed: {}", e))
+                })?;
+            }
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     async fn subscribe(
@@ -209,6 +223,9 @@ impl MessageBroker for RabbitMqBroker {
         handler: Arc<dyn MessageHandler>,
         options: SubscribeOptions,
     ) -> Result<Self::Subscription, MessagingError> {
+        let handler = options.wrap_handler(handler);
+        let dispatcher = ConcurrentDispatcher::from_options(&options);
+
         let channel = self.connection.create_channel().await?;
 
         // Set prefetch count if specified
@@ -230,6 +247,28 @@ impl MessageBroker for RabbitMqBroker {
             )
             .await?;
 
+        // Declare the dead-letter queue so forwarded messages are not dropped
+        let dead_letter = match (&options.dead_letter_queue, &options.dead_letter_topic) {
+            (Some(queue), _) => Some(queue.clone()),
+            (None, Some(dead_letter_topic)) => {
+                channel
+                    .queue_declare(
+                        dead_letter_topic,
+                        QueueDeclareOptions {
+                            durable: true,
+                            ..Default::default()
+                        },
+                        FieldTable::default(),
+                    )
+                    .await?;
+                Some(Arc::new(RabbitMqDeadLetterQueue {
+                    channel: channel.clone(),
+                    queue: dead_letter_topic.clone(),
+                }) as Arc<dyn DeadLetterQueue>)
+            }
+            (None, None) => None,
+        };
+
         let consumer_tag = options
             .consumer_group
             .unwrap_or_else(|| format!("armature-{}", uuid::Uuid::new_v4()));
@@ -246,22 +285,26 @@ impl MessageBroker for RabbitMqBroker {
             )
             .await?;
 
-        let active = Arc::new(AtomicBool::new(true));
         let subscription = RabbitMqSubscription {
             topic: topic.to_string(),
             consumer_tag: consumer_tag.clone(),
             channel: channel.clone(),
-            active: active.clone(),
+            active: Arc::new(AtomicBool::new(true)),
+            in_flight: InFlight::new(),
         };
 
         // Store channel for cleanup
-        self.channels.write().await.push(channel.clone());
-
-        // Spawn consumer task
-        let topic_owned = topic.to_string();
-        let ack_mode = options.ack_mode;
+        self.channels.write().await.push(channel);
+
+        // Spawn consumer task; it shares the subscription's state
+        let context = DeliveryContext {
+            handler,
+            dead_letter,
+            ack_mode: options.ack_mode,
+            subscription: subscription.clone(),
+        };
         tokio::spawn(async move {
-            consume_messages(consumer, handler, channel, &topic_owned, ack_mode, active).await;
+            consume_messages(consumer, context, dispatcher).await;
         });
 
         info!(queue = topic, consumer_tag = %consumer_tag, "Subscribed to queue");
@@ -294,74 +337,46 @@ impl MessageBroker for RabbitMqBroker {
     }
 }
 
-async fn consume_messages(
-    mut consumer: Consumer,
+/// State shared by the consumer loop and the handlers it runs
+#[derive(Clone)]
+struct DeliveryContext {
     handler: Arc<dyn MessageHandler>,
-    channel: Channel,
-    topic: &str,
+    dead_letter: Option<Arc<dyn DeadLetterQueue>>,
     ack_mode: AckMode,
-    active: Arc<AtomicBool>,
+    subscription: RabbitMqSubscription,
+}
+
+async fn consume_messages(
+    mut consumer: Consumer,
+    context: DeliveryContext,
+    dispatcher: Option<ConcurrentDispatcher>,
 ) {
-    while active.load(Ordering::SeqCst) {
+    let RabbitMqSubscription {
+        channel,
+        active,
+        in_flight,
+        ..
+    } = context.subscription.clone();
+
+    while active.load(Ordering::SeqCst) && !in_flight.is_aborted() {
         match consumer.next().await {
             Some(Ok(delivery)) => {
-                let message = delivery_to_message(&delivery, topic);
-                let delivery_tag = delivery.delivery_tag;
-
-                match handler.handle(message).await {
-                    Ok(result) => {
-                        if ack_mode == AckMode::Auto || ack_mode == AckMode::Manual {
-                            match result {
-                                ProcessingResult::Success => {
-                                    if let Err(e) = channel
-                                        .basic_ack(delivery_tag, BasicAckOptions::default())
-                                        .await
-                                    {
-                                        error!(error = %e, "Failed to ack message");
-                                    }
-                                }
-                                ProcessingResult::Retry => {
-                                    if let Err(e) = channel
-                                        .basic_nack(
-                                            delivery_tag,
-                                            BasicNackOptions {
-                                                requeue: true,
-                                                ..Default::default()
-                                            },
-                                        )
-                                        .await
-                                    {
-                                        error!(error = %e, "Failed to nack message for retry");
-                                    }
-                                }
-                                ProcessingResult::DeadLetter | ProcessingResult::Reject => {
-                                    if let Err(e) = channel
-                                        .basic_reject(
-                                            delivery_tag,
-                                            BasicRejectOptions { requeue: false },
-                                        )
-                                        .await
-                                    {
-                                        error!(error = %e, "Failed to reject message");
-                                    }
-                                }
-                            }
-                        }
+                if !active.load(Ordering::SeqCst) {
+                    // Delivered after the subscription started draining
+                    if context.ack_mode != AckMode::None {
+                        requeue(&channel, delivery.delivery_tag).await;
                     }
-                    Err(e) => {
-                        error!(error = %e, "Message handler error");
-                        if ack_mode != AckMode::None {
-                            let _ = channel
-                                .basic_nack(
-                                    delivery_tag,
-                                    BasicNackOptions {
-                                        requeue: true,
-                                        ..Default::default()
-                                    },
-                                )
-                                .await;
+                    break;
+                }
+
+                let job = handle_delivery(context.clone(), delivery, in_flight.start());
+                match &dispatcher {
+                    Some(dispatcher) => {
+                        if dispatcher.dispatch(job).await.is_err() {
+                            break;
                         }
                     }
+                    None => job.await,
                 }
             }
             Some(Err(e)) => {
@@ -376,6 +391,116 @@ async fn consume_messages(
     }
 }
 
+/// Handle one delivery and settle it with the broker.
+async fn handle_delivery(
+    context: DeliveryContext,
+    delivery: lapin::message::Delivery,
+    _in_flight: InFlightGuard,
+) {
+    let DeliveryContext {
+        handler,
+        dead_letter,
+        ack_mode,
+        subscription,
+    } = context;
+    let channel = &subscription.channel;
+    let delivery_tag = delivery.delivery_tag;
+
+    let message = delivery_to_message(&delivery, &subscription.topic);
+    let original = dead_letter.as_ref().map(|_| message.clone());
+
+    let Some(outcome) = subscription.in_flight.run(handler.handle(message)).await else {
+        // The drain timed out; hand the message back to the broker
+        if ack_mode != AckMode::None {
+            requeue(channel, delivery_tag).await;
+        }
+        return;
+    };
+
+    match outcome {
+        Ok(result) => {
+            // Forward before rejecting so a failed forward can requeue instead
+            let mut requeue = false;
+            if let (Some(queue), Some(message), Some(reason)) = (
+                &dead_letter,
+                original,
+                DeadLetterReason::from_result(&result),
+            ) {
+                // AMQP only says whether this is a redelivery, not how many
+                let attempts = if delivery.redelivered { 2 } else { 1 };
+                requeue = forward_to_dead_letter(queue.as_ref(), message, reason, attempts)
+                    .await
+                    .is_err();
+            }
+
+            if ack_mode == AckMode::Auto || ack_mode == AckMode::Manual {
+                match result {
+                    ProcessingResult::Success => {
+                        if let Err(e) = channel
+                            .basic_ack(delivery_tag, BasicAckOptions::default())
+                            .await
+                        {
+                            error!(error = %e, "Failed to ack message");
+                        }
+                    }
+                    ProcessingResult::Retry => {
+                        if let Err(e) = channel
+                            .basic_nack(
+                                delivery_tag,
+                                BasicNackOptions {
+                                    requeue: true,
+                                    ..Default::default()
+                                },
+                            )
+                            .await
+                        {
+                            error!(error = %e, "Failed to nack message for retry");
+                        }
+                    }
+                    ProcessingResult::DeadLetter | ProcessingResult::Reject => {
+                        if let Err(e) = channel
+                            .basic_reject(delivery_tag, BasicRejectOptions { requeue })
+                            .await
+                        {
+                            error!(error = %e, "Failed to reject message");
+                        }
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            error!(error = %e, "Message handler error");
+            if ack_mode != AckMode::None {
+                let _ = channel
+                    .basic_nack(
+                        delivery_tag,
+                        BasicNackOptions {
+                            requeue: true,
+                            ..Default::default()
+                        },
+                    )
+                    .await;
+            }
+        }
+    }
+}
+
+/// Nack a delivery so the broker redelivers it.
+async fn requeue(channel: &Channel, delivery_tag: u64) {
+    if let Err(e) = channel
+        .basic_nack(
+            delivery_tag,
+            BasicNackOptions {
+                requeue: true,
+                ..Default::default()
+            },
+        )
+        .await
+    {
+        error!(error = %e, "Failed to requeue message");
+    }
+}
+
 fn delivery_to_message(delivery: &lapin::message::Delivery, topic: &str) -> Message {
     let props = &delivery.properties;
     let mut headers = HashMap::new();
@@ -387,6 +512,7 @@ fn delivery_to_message(delivery: &lapin::message::Delivery, topic: &str) -> Mess
             }
         }
     }
+    let schema_version = headers.remove(SCHEMA_VERSION_HEADER);
 
     Message {
         id: props
@@ -411,15 +537,45 @@ fn delivery_to_message(delivery: &lapin::message::Delivery, topic: &str) -> Mess
             .expiration()
             .as_ref()
             .and_then(|s| s.to_string().parse().ok()),
+        schema_version,
+    }
+}
+
+/// Publishes dead-lettered messages to a queue through the default exchange
+struct RabbitMqDeadLetterQueue {
+    channel: Channel,
+    queue: String,
+}
+
+#[async_trait]
+impl DeadLetterQueue for RabbitMqDeadLetterQueue {
+    async fn send(
+        &self,
+        message: Message,
+        _reason: DeadLetterReason,
+    ) -> Result<(), MessagingError> {
+        let props = RabbitMqBroker::build_properties(&message).with_delivery_mode(2);
+        self.channel
+            .basic_publish(
+                "",
+                &self.queue,
+                BasicPublishOptions::default(),
+                &message.payload,
+                props,
+            )
+            .await?;
+        Ok(())
     }
 }
 
 /// RabbitMQ subscription handle
+#[derive(Clone)]
 pub struct RabbitMqSubscription {
     topic: String,
     consumer_tag: String,
     channel: Channel,
     active: Arc<AtomicBool>,
+    in_flight: InFlight,
 }
 
 #[async_trait]
@@ -440,4 +596,14 @@ impl Subscription for RabbitMqSubscription {
     fn topic(&self) -> &str {
         &self.topic
     }
+
+    fn in_flight(&self) -> usize {
+        self.in_flight.count()
+    }
+
+    async fn drain(&self, timeout: Duration) -> Result<(), MessagingError> {
+        // Cancelling the consumer stops new deliveries
+        self.unsubscribe().await?;
+        self.in_flight.drain(timeout).await
+    }
 }
@@ -0,0 +1,272 @@
+//! Retry with backoff around message handlers
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::{
+    DeadLetterQueue, DeadLetterReason, Message, MessageHandler, MessagingError, ProcessingResult,
+    forward_to_dead_letter,
+};
+
+/// Retry configuration for [`RetryingHandler`]
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum number of attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Maximum delay between retries
+    pub max_delay: Duration,
+    /// Randomize delays so consumers that failed together do not retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Create a configuration with the given number of attempts
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the maximum delay between retries
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Enable or disable jitter
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Calculate the delay after a failed attempt (starting at 1)
+    ///
+    /// The delay doubles with every attempt and is capped at `max_delay`.
+    /// With jitter, it is half the computed delay plus a random share of the
+    /// other half.
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        if attempt == 0 {
+            return Duration::ZERO;
+        }
+
+        let delay = self.base_delay.as_secs_f64() * 2f64.powi(attempt as i32 - 1);
+        let delay = delay.min(self.max_delay.as_secs_f64());
+
+        if self.jitter {
+            Duration::from_secs_f64(delay / 2.0 + delay / 2.0 * rand_jitter())
+        } else {
+            Duration::from_secs_f64(delay)
+        }
+    }
+}
+
+/// Generate a random factor between 0.0 and 1.0.
+fn rand_jitter() -> f64 {
+    use std::time::SystemTime;
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    (nanos % 1000) as f64 / 1000.0
+}
+
+/// Handler decorator that retries failed messages with exponential backoff
+///
+/// The inner handler is invoked again when it returns
+/// [`ProcessingResult::Retry`] or an error, up to
+/// [`RetryConfig::max_attempts`] times in total. Other results are returned
+/// as they are. When attempts run out the message is sent to the configured
+/// dead-letter queue and acknowledged, or, without one, the handler returns
+/// [`ProcessingResult::DeadLetter`] for the broker to handle.
+///
+/// Retries happen in place, so the message is held by this consumer while it
+/// waits.
+///
+/// ```rust,ignore
+/// let handler = RetryingHandler::new(MyHandler, RetryConfig::new(5));
+/// broker.subscribe("orders", Arc::new(handler)).await?;
+/// ```
+pub struct RetryingHandler<H> {
+    inner: H,
+    config: RetryConfig,
+    dead_letter: Option<Arc<dyn DeadLetterQueue>>,
+}
+
+impl<H: MessageHandler> RetryingHandler<H> {
+    /// Wrap a handler
+    pub fn new(inner: H, config: RetryConfig) -> Self {
+        Self {
+            inner,
+            config,
+            dead_letter: None,
+        }
+    }
+
+    /// Send messages to a dead-letter queue once attempts run out
+    ///
+    /// Forwarded messages record [`DeadLetterReason::RetriesExhausted`] and
+    /// the number of attempts made.
+    pub fn with_dead_letter_queue(mut self, queue: Arc<dyn DeadLetterQueue>) -> Self {
+        self.dead_letter = Some(queue);
+        self
+    }
+
+    /// Get the retry configuration
+    pub fn config(&self) -> &RetryConfig {
+        &self.config
+    }
+}
+
+#[async_trait]
+impl<H: MessageHandler> MessageHandler for RetryingHandler<H> {
+    async fn handle(&self, message: Message) -> Result<ProcessingResult, MessagingError> {
+        let max_attempts = self.config.max_attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            match self.inner.handle(message.clone()).await {
+                Ok(ProcessingResult::Retry) => {
+                    debug!(message_id = %message.id, attempt, "Handler requested retry");
+                }
+                Err(e) => {
+                    debug!(message_id = %message.id, attempt, error = %e, "Handler failed");
+                }
+                result => return result,
+            }
+
+            if attempt >= max_attempts {
+                break;
+            }
+            tokio::time::sleep(self.config.delay_for_attempt(attempt)).await;
+            attempt += 1;
+        }
+
+        warn!(message_id = %message.id, attempts = attempt, "Message retries exhausted");
+
+        match &self.dead_letter {
+            Some(queue) => {
+                forward_to_dead_letter(
+                    queue.as_ref(),
+                    message,
+                    DeadLetterReason::RetriesExhausted,
+                    attempt,
+                )
+                .await?;
+                Ok(ProcessingResult::Success)
+            }
+            None => Ok(ProcessingResult::DeadLetter),
+        }
+    }
+
+    async fn on_deserialize_error(&self, error: &MessagingError) -> ProcessingResult {
+        self.inner.on_deserialize_error(error).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DEAD_LETTER_ATTEMPTS_HEADER, FnHandler, InMemoryDeadLetterQueue};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast(max_attempts: u32) -> RetryConfig {
+        RetryConfig::new(max_attempts)
+            .with_base_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[test]
+    fn test_delay_for_attempt() {
+        let config = RetryConfig::new(5)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(300))
+            .with_jitter(false);
+
+        assert_eq!(config.delay_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(config.delay_for_attempt(2), Duration::from_millis(200));
+        assert_eq!(config.delay_for_attempt(3), Duration::from_millis(300));
+
+        let jittered = config.with_jitter(true).delay_for_attempt(2);
+        assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_succeeds_after_failures() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        // Fails twice, then succeeds
+        let inner = FnHandler(move |_message: Message| {
+            let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if call <= 2 {
+                    Err(MessagingError::Other(format!("failure {}", call)))
+                } else {
+                    Ok(ProcessingResult::Success)
+                }
+            }
+        });
+        let handler = RetryingHandler::new(inner, fast(3));
+
+        let result = handler.handle(Message::new("orders", b"{}".to_vec())).await;
+
+        assert!(matches!(result, Ok(ProcessingResult::Success)));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_returns_dead_letter() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let inner = FnHandler(move |_message: Message| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Err(MessagingError::Other("always fails".to_string())) }
+        });
+        let handler = RetryingHandler::new(inner, fast(2));
+
+        let result = handler.handle(Message::new("orders", b"{}".to_vec())).await;
+
+        assert!(matches!(result, Ok(ProcessingResult::DeadLetter)));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_forwards_to_dead_letter_queue() {
+        let dlq = Arc::new(InMemoryDeadLetterQueue::new());
+        let handler = RetryingHandler::new(
+            FnHandler(|_message: Message| async { Ok(ProcessingResult::Retry) }),
+            fast(3),
+        )
+        .with_dead_letter_queue(dlq.clone());
+
+        let result = handler.handle(Message::new("orders", b"{}".to_vec())).await;
+
+        assert!(matches!(result, Ok(ProcessingResult::Success)));
+        let messages = dlq.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].1, DeadLetterReason::RetriesExhausted);
+        assert_eq!(messages[0].0.headers[DEAD_LETTER_ATTEMPTS_HEADER], "3");
+    }
+}
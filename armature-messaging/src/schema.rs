@@ -0,0 +1,167 @@
+//! Payload schema validation by topic and version
+
+use async_trait::async_trait;
+
+use crate::{Message, MessagingError};
+
+#[cfg(feature = "json-schema")]
+pub use json_schema::JsonSchemaRegistry;
+
+/// Validates message payloads against registered schemas
+///
+/// Schemas are looked up by the message topic and its
+/// [`schema_version`](Message::schema_version). Typed subscriptions with a
+/// registry pass messages that fail validation to the handler's
+/// `on_deserialize_error` instead of deserializing them.
+#[async_trait]
+pub trait SchemaRegistry: Send + Sync {
+    /// Check a message against the schema registered for its topic and version
+    ///
+    /// Returns [`MessagingError::Schema`] if the message has no version, no
+    /// schema is registered for it, or the payload does not match.
+    async fn validate(&self, message: &Message) -> Result<(), MessagingError>;
+}
+
+/// Get a message's schema version or fail validation.
+pub fn require_schema_version(message: &Message) -> Result<&str, MessagingError> {
+    message.schema_version.as_deref().ok_or_else(|| {
+        MessagingError::Schema(format!(
+            "message {} on {} has no schema version",
+            message.id, message.topic
+        ))
+    })
+}
+
+#[cfg(feature = "json-schema")]
+mod json_schema {
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+
+    use async_trait::async_trait;
+    use jsonschema::Validator;
+    use serde_json::Value;
+
+    use super::{SchemaRegistry, require_schema_version};
+    use crate::{Message, MessagingError};
+
+    /// Schema registry backed by JSON Schema documents
+    ///
+    /// ```rust,ignore
+    /// let registry = JsonSchemaRegistry::new();
+    /// registry.register("orders", "1", &json!({
+    ///     "type": "object",
+    ///     "required": ["id"],
+    /// }))?;
+    /// ```
+    #[derive(Default)]
+    pub struct JsonSchemaRegistry {
+        validators: RwLock<HashMap<(String, String), Validator>>,
+    }
+
+    impl JsonSchemaRegistry {
+        /// Create an empty registry
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Register a schema for a topic and version, replacing any existing one
+        pub fn register(
+            &self,
+            topic: impl Into<String>,
+            version: impl Into<String>,
+            schema: &Value,
+        ) -> Result<(), MessagingError> {
+            let validator = jsonschema::validator_for(schema)
+                .map_err(|e| MessagingError::Configuration(format!("invalid schema: {}", e)))?;
+            self.validators
+                .write()
+                .unwrap()
+                .insert((topic.into(), version.into()), validator);
+            Ok(())
+        }
+
+        /// Check if a schema is registered for a topic and version
+        pub fn contains(&self, topic: &str, version: &str) -> bool {
+            self.validators
+                .read()
+                .unwrap()
+                .contains_key(&(topic.to_string(), version.to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl SchemaRegistry for JsonSchemaRegistry {
+        async fn validate(&self, message: &Message) -> Result<(), MessagingError> {
+            let version = require_schema_version(message)?;
+            let payload: Value = message.parse_json()?;
+
+            let validators = self.validators.read().unwrap();
+            let validator = validators
+                .get(&(message.topic.clone(), version.to_string()))
+                .ok_or_else(|| {
+                    MessagingError::Schema(format!(
+                        "no schema registered for {} version {}",
+                        message.topic, version
+                    ))
+                })?;
+
+            validator.validate(&payload).map_err(|e| {
+                MessagingError::Schema(format!("{} version {}: {}", message.topic, version, e))
+            })
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use serde_json::json;
+
+        fn registry() -> JsonSchemaRegistry {
+            let registry = JsonSchemaRegistry::new();
+            registry
+                .register(
+                    "orders",
+                    "1",
+                    &json!({
+                        "type": "object",
+                        "properties": {"id": {"type": "integer"}},
+                        "required": ["id"],
+                    }),
+                )
+                .unwrap();
+            registry
+        }
+
+        #[tokio::test]
+        async fn test_accepts_known_version() {
+            let message = Message::json("orders", &json!({"id": 1}))
+                .unwrap()
+                .with_schema_version("1");
+            assert!(registry().validate(&message).await.is_ok());
+        }
+
+        #[tokio::test]
+        async fn test_rejects_unknown_version() {
+            let message = Message::json("orders", &json!({"id": 1}))
+                .unwrap()
+                .with_schema_version("2");
+            let result = registry().validate(&message).await;
+            assert!(matches!(result, Err(MessagingError::Schema(_))));
+        }
+
+        #[tokio::test]
+        async fn test_rejects_invalid_payload() {
+            let message = Message::json("orders", &json!({"id": "x"}))
+                .unwrap()
+                .with_schema_version("1");
+            let result = registry().validate(&message).await;
+            assert!(matches!(result, Err(MessagingError::Schema(_))));
+        }
+
+        #[test]
+        fn test_register_rejects_invalid_schema() {
+            let result = JsonSchemaRegistry::new().register("orders", "1", &json!({"type": 5}));
+            assert!(matches!(result, Err(MessagingError::Configuration(_))));
+        }
+    }
+}
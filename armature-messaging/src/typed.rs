@@ -0,0 +1,333 @@
+//! Typed publish/subscribe over JSON messages
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::{
+    Message, MessageBroker, MessageHandler, MessagingError, ProcessingResult, PublishOptions,
+    SchemaRegistry, SubscribeOptions,
+};
+
+/// Content type of typed messages
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Trait for handling deserialized message payloads
+#[async_trait]
+pub trait TypedHandler<T: Send + 'static>: Send + Sync + 'static {
+    /// Handle a received value along with the message it came from
+    async fn handle(&self, value: T, raw: &Message) -> Result<ProcessingResult, MessagingError>;
+
+    /// Called when a message is not JSON or does not deserialize into `T`
+    async fn on_deserialize_error(&self, _error: &MessagingError) -> ProcessingResult {
+        ProcessingResult::DeadLetter
+    }
+}
+
+/// Adapts a [`TypedHandler`] into a [`MessageHandler`]
+///
+/// Only messages with an `application/json` content type, and that pass the
+/// schema registry if one is set, are deserialized; anything else is passed to
+/// [`TypedHandler::on_deserialize_error`].
+pub struct TypedMessageHandler<T, H> {
+    handler: H,
+    registry: Option<Arc<dyn SchemaRegistry>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, H> TypedMessageHandler<T, H>
+where
+    T: DeserializeOwned + Send + 'static,
+    H: TypedHandler<T>,
+{
+    /// Wrap a typed handler
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            registry: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Validate messages against a schema registry before deserializing
+    pub fn with_schema_registry(mut self, registry: Arc<dyn SchemaRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    async fn decode(&self, message: &Message) -> Result<T, MessagingError> {
+        let is_json = message.content_type.as_deref().is_some_and(|content_type| {
+            let media_type = content_type.split(';').next().unwrap_or_default();
+            media_type.trim().eq_ignore_ascii_case(JSON_CONTENT_TYPE)
+        });
+        if !is_json {
+            return Err(MessagingError::Deserialization(format!(
+                "expected content type {}, got {}",
+                JSON_CONTENT_TYPE,
+                message.content_type.as_deref().unwrap_or("none")
+            )));
+        }
+
+        if let Some(registry) = &self.registry {
+            registry.validate(message).await?;
+        }
+
+        message.parse_json()
+    }
+}
+
+#[async_trait]
+impl<T, H> MessageHandler for TypedMessageHandler<T, H>
+where
+    T: DeserializeOwned + Send + 'static,
+    H: TypedHandler<T>,
+{
+    async fn handle(&self, message: Message) -> Result<ProcessingResult, MessagingError> {
+        match self.decode(&message).await {
+            Ok(value) => self.handler.handle(value, &message).await,
+            Err(e) => {
+                debug!(message_id = %message.id, error = %e, "Failed to decode typed message");
+                Ok(self.on_deserialize_error(&e).await)
+            }
+        }
+    }
+
+    async fn on_deserialize_error(&self, error: &MessagingError) -> ProcessingResult {
+        self.handler.on_deserialize_error(error).await
+    }
+}
+
+/// Broker wrapper that serializes and deserializes payloads as JSON
+///
+/// ```rust,ignore
+/// let broker = TypedBroker::new(broker);
+/// broker.subscribe_typed::<Order, _>("orders", OrderHandler).await?;
+/// broker.publish_typed("orders", &order).await?;
+/// ```
+pub struct TypedBroker<B> {
+    broker: B,
+    registry: Option<Arc<dyn SchemaRegistry>>,
+}
+
+impl<B: MessageBroker> TypedBroker<B> {
+    /// Wrap a broker
+    pub fn new(broker: B) -> Self {
+        Self {
+            broker,
+            registry: None,
+        }
+    }
+
+    /// Validate received messages against a schema registry
+    ///
+    /// Applies to subscriptions made after this call.
+    pub fn with_schema_registry(mut self, registry: Arc<dyn SchemaRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Get the wrapped broker
+    pub fn inner(&self) -> &B {
+        &self.broker
+    }
+
+    /// Unwrap the broker
+    pub fn into_inner(self) -> B {
+        self.broker
+    }
+
+    /// Publish a value as a JSON message
+    pub async fn publish_typed<T: Serialize>(
+        &self,
+        topic: &str,
+        value: &T,
+    ) -> Result<(), MessagingError> {
+        self.broker.publish(Message::json(topic, value)?).await
+    }
+
+    /// Publish a value as a JSON message with options
+    pub async fn publish_typed_with_options<T: Serialize>(
+        &self,
+        topic: &str,
+        value: &T,
+        options: PublishOptions,
+    ) -> Result<(), MessagingError> {
+        self.broker
+            .publish_with_options(Message::json(topic, value)?, options)
+            .await
+    }
+
+    /// Subscribe with a handler that receives deserialized values
+    pub async fn subscribe_typed<T, H>(
+        &self,
+        topic: &str,
+        handler: H,
+    ) -> Result<B::Subscription, MessagingError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        H: TypedHandler<T>,
+    {
+        self.broker
+            .subscribe(topic, Arc::new(self.typed_handler(handler)))
+            .await
+    }
+
+    /// Subscribe with options and a handler that receives deserialized values
+    pub async fn subscribe_typed_with_options<T, H>(
+        &self,
+        topic: &str,
+        handler: H,
+        options: SubscribeOptions,
+    ) -> Result<B::Subscription, MessagingError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        H: TypedHandler<T>,
+    {
+        self.broker
+            .subscribe_with_options(topic, Arc::new(self.typed_handler(handler)), options)
+            .await
+    }
+
+    fn typed_handler<T, H>(&self, handler: H) -> TypedMessageHandler<T, H>
+    where
+        T: DeserializeOwned + Send + 'static,
+        H: TypedHandler<T>,
+    {
+        let handler = TypedMessageHandler::new(handler);
+        match &self.registry {
+            Some(registry) => handler.with_schema_registry(registry.clone()),
+            None => handler,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u64,
+        item: String,
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        orders: Mutex<Vec<Order>>,
+        errors: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl TypedHandler<Order> for Arc<Recorder> {
+        async fn handle(
+            &self,
+            value: Order,
+            raw: &Message,
+        ) -> Result<ProcessingResult, MessagingError> {
+            assert_eq!(raw.content_type.as_deref(), Some(JSON_CONTENT_TYPE));
+            self.orders.lock().unwrap().push(value);
+            Ok(ProcessingResult::Success)
+        }
+
+        async fn on_deserialize_error(&self, error: &MessagingError) -> ProcessingResult {
+            self.errors.lock().unwrap().push(error.to_string());
+            ProcessingResult::Reject
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    #[tokio::test]
+    async fn test_round_trip() {
+        let broker = TypedBroker::new(crate::InMemoryBroker::new());
+        let recorder = Arc::new(Recorder::default());
+        broker
+            .subscribe_typed::<Order, _>("orders", recorder.clone())
+            .await
+            .unwrap();
+
+        let order = Order {
+            id: 7,
+            item: "widget".to_string(),
+        };
+        broker.publish_typed("orders", &order).await.unwrap();
+
+        assert_eq!(*recorder.orders.lock().unwrap(), vec![order]);
+        assert_eq!(broker.inner().published("orders").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_non_json_content_type() {
+        let recorder = Arc::new(Recorder::default());
+        let handler = TypedMessageHandler::<Order, _>::new(recorder.clone());
+
+        let message =
+            Message::new("orders", r#"{"id":1,"item":"a"}"#).with_content_type("text/plain");
+        let result = handler.handle(message).await.unwrap();
+
+        assert!(matches!(result, ProcessingResult::Reject));
+        assert!(recorder.orders.lock().unwrap().is_empty());
+        assert!(recorder.errors.lock().unwrap()[0].contains("text/plain"));
+    }
+
+    /// Accepts version "1" of any topic
+    struct VersionOne;
+
+    #[async_trait]
+    impl SchemaRegistry for VersionOne {
+        async fn validate(&self, message: &Message) -> Result<(), MessagingError> {
+            match crate::require_schema_version(message)? {
+                "1" => Ok(()),
+                other => Err(MessagingError::Schema(format!("unknown version {}", other))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schema_mismatch_calls_error_hook() {
+        let recorder = Arc::new(Recorder::default());
+        let handler = TypedMessageHandler::<Order, _>::new(recorder.clone())
+            .with_schema_registry(Arc::new(VersionOne));
+        let order = Order {
+            id: 1,
+            item: "a".to_string(),
+        };
+
+        let known = Message::json("orders", &order)
+            .unwrap()
+            .with_schema_version("1");
+        assert!(matches!(
+            handler.handle(known).await,
+            Ok(ProcessingResult::Success)
+        ));
+
+        let unknown = Message::json("orders", &order)
+            .unwrap()
+            .with_schema_version("2");
+        assert!(matches!(
+            handler.handle(unknown).await,
+            Ok(ProcessingResult::Reject)
+        ));
+
+        assert_eq!(*recorder.orders.lock().unwrap(), vec![order]);
+        assert!(recorder.errors.lock().unwrap()[0].contains("unknown version 2"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_payload_calls_error_hook() {
+        let recorder = Arc::new(Recorder::default());
+        let handler = TypedMessageHandler::<Order, _>::new(recorder.clone());
+
+        let message = Message::new("orders", r#"{"id":"x"}"#)
+            .with_content_type("application/json; charset=utf-8");
+        let result = handler.handle(message).await.unwrap();
+
+        assert!(matches!(result, ProcessingResult::Reject));
+        assert_eq!(recorder.errors.lock().unwrap().len(), 1);
+    }
+}
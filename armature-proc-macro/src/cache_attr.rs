@@ -1,8 +1,106 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{ItemFn, parse_macro_input};
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    Error, FnArg, Ident, ItemFn, LitInt, LitStr, Pat, Token, parse::Parse, parse::ParseStream,
+    parse_macro_input,
+};
 
-pub fn cache_impl(_attr: TokenStream, item: TokenStream) -> TokenStream {
+/// Arguments for the cache attribute
+/// Parses: #[cache] or #[cache(key = "user:{id}", ttl = 600, tag = "users")]
+pub struct CacheArgs {
+    pub key: Option<LitStr>,
+    pub ttl_secs: u64,
+    pub tags: Vec<LitStr>,
+}
+
+impl Parse for CacheArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self {
+            key: None,
+            ttl_secs: 3600, // Default 1 hour
+            tags: Vec::new(),
+        };
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            let _eq: Token![=] = input.parse()?;
+
+            match ident.to_string().as_str() {
+                "key" => args.key = Some(input.parse()?),
+                "ttl" => args.ttl_secs = input.parse::<LitInt>()?.base10_parse()?,
+                "tag" => args.tags.push(input.parse()?),
+                _ => {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!(
+                            "unknown cache option '{}'\n\
+                             hint: expected 'key', 'ttl' or 'tag'",
+                            ident
+                        ),
+                    ));
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Names of the `{placeholders}` in a key template, in order of first use
+///
+/// Format specs are allowed (`{id:?}`) and `{{`/`}}` are escapes. Positional
+/// placeholders (`{}`, `{0}`) are rejected since they can't name an argument.
+fn template_placeholders(template: &str) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err("unclosed '{' in cache key template".to_string()),
+                    }
+                }
+
+                let name = placeholder.split(':').next().unwrap_or_default();
+                if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+                    return Err("cache key placeholders must name an argument\n\
+                         hint: use '{user_id}' instead of '{}'"
+                        .to_string());
+                }
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(names)
+}
+
+/// Implementation of the `#[cache(...)]` attribute macro.
+///
+/// With a `key` template, the key is formatted from the named arguments,
+/// which must implement `Display` (or `Debug` with `{arg:?}`). Without one,
+/// the key is `fn_name:[args]`, the JSON array of every argument's
+/// serialization, so all arguments must implement `Serialize`. A `self`
+/// receiver is not part of the key.
+pub fn cache_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as CacheArgs);
     let input_fn = parse_macro_input!(item as ItemFn);
 
     let fn_name = &input_fn.sig.ident;
@@ -11,83 +109,180 @@ pub fn cache_impl(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let fn_block = &input_fn.block;
     let fn_attrs = &input_fn.attrs;
 
-    // Default configuration (attribute parsing to be added later)
-    let key_template = format!("{}:{{:?}}", fn_name);
-    let ttl_secs = 3600_u64; // Default 1 hour
-    let tags: Vec<String> = Vec::new();
+    let ttl_secs = args.ttl_secs;
+    let tags = &args.tags;
 
-    let cache_code = if tags.is_empty() {
-        // Simple cache without tags
-        quote! {
-            #(#fn_attrs)*
-            #fn_vis #fn_sig {
-                use std::time::Duration;
+    // Arguments that can take part in the key (everything but the receiver)
+    let mut key_args = Vec::new();
+    for arg in &input_fn.sig.inputs {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        match pat_type.pat.as_ref() {
+            Pat::Ident(pat_ident) => key_args.push((&pat_ident.ident, &pat_type.ty)),
+            pat if args.key.is_none() => {
+                return Error::new(
+                    pat.span(),
+                    "#[cache] can't derive a key from a destructured argument\n\
+                     hint: bind it to a name or give the key explicitly with key = \"...\"",
+                )
+                .to_compile_error()
+                .into();
+            }
+            _ => {}
+        }
+    }
 
-                // Generate cache key from function arguments
-                let cache_key = format!(#key_template, stringify!(#fn_name));
+    let cache_key = if let Some(template) = &args.key {
+        let placeholders = match template_placeholders(&template.value()) {
+            Ok(placeholders) => placeholders,
+            Err(message) => {
+                return Error::new(template.span(), message)
+                    .to_compile_error()
+                    .into();
+            }
+        };
 
-                // Try to get from cache
-                if let Ok(Some(cached)) = __cache.get_json(&cache_key).await {
-                    if let Ok(result) = serde_json::from_str(&cached) {
-                        return result;
-                    }
+        let mut names = Vec::new();
+        for name in &placeholders {
+            match key_args.iter().find(|(ident, _)| *ident == name) {
+                Some((ident, _)) => names.push(*ident),
+                None => {
+                    return Error::new(
+                        template.span(),
+                        format!(
+                            "'{{{}}}' in the cache key template is not an argument of '{}'",
+                            name, fn_name
+                        ),
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            }
+        }
+
+        quote! { Some(format!(#template, #(#names = #names),*)) }
+    } else {
+        let fn_name_str = fn_name.to_string();
+        if key_args.is_empty() {
+            quote! { Some(#fn_name_str.to_string()) }
+        } else {
+            // Span each argument's JSON on its type so a non-serializable
+            // argument is reported where it's declared
+            let arg_json = key_args.iter().map(|(ident, ty)| {
+                quote_spanned! {ty.span()=>
+                    __CacheKeyArg::__cache_key_json(&#ident)
+                }
+            });
+
+            quote! {{
+                #[diagnostic::on_unimplemented(
+                    message = "#[cache] can't derive a key from `{Self}` because it doesn't implement `serde::Serialize`",
+                    label = "not serializable",
+                    note = "derive `Serialize` for it, or give the key explicitly: #[cache(key = \"...{arg}...\")]"
+                )]
+                trait __CacheKeyArg {
+                    fn __cache_key_json(&self) -> Option<String>;
                 }
 
-                // Execute function
-                let result = (|| async #fn_block)().await;
-
-                // Cache successful results
-                if let Ok(ref success_result) = result {
-                    if let Ok(json) = serde_json::to_string(success_result) {
-                        let _ = __cache.set_json(
-                            &cache_key,
-                            json,
-                            Some(Duration::from_secs(#ttl_secs))
-                        ).await;
+                impl<T: serde::Serialize + ?Sized> __CacheKeyArg for T {
+                    fn __cache_key_json(&self) -> Option<String> {
+                        serde_json::to_string(self).ok()
                     }
                 }
 
-                result
-            }
+                // The arguments as a JSON array, so distinct arguments never
+                // share a key
+                let mut __cache_key_args: Option<Vec<String>> = Some(Vec::new());
+                #(
+                    __cache_key_args = __cache_key_args.zip(#arg_json).map(|(mut args, json)| {
+                        args.push(json);
+                        args
+                    });
+                )*
+                __cache_key_args.map(|args| format!("{}:[{}]", #fn_name_str, args.join(",")))
+            }}
+        }
+    };
+
+    let store = if tags.is_empty() {
+        quote! {
+            let _ = __cache.set_json(
+                cache_key,
+                json,
+                Some(Duration::from_secs(#ttl_secs))
+            ).await;
         }
     } else {
-        // Tagged cache
-        let tags_slice = tags.join("\", \"");
         quote! {
-            #(#fn_attrs)*
-            #fn_vis #fn_sig {
-                use std::time::Duration;
+            let tags: &[&str] = &[#(#tags),*];
+            let _ = __tagged_cache.set_with_tags(
+                cache_key,
+                json,
+                tags,
+                Some(Duration::from_secs(#ttl_secs))
+            ).await;
+        }
+    };
+    let cache = if tags.is_empty() {
+        Ident::new("__cache", Span::call_site())
+    } else {
+        Ident::new("__tagged_cache", Span::call_site())
+    };
 
-                // Generate cache key from function arguments
-                let cache_key = format!(#key_template, stringify!(#fn_name));
+    let cache_code = quote! {
+        #(#fn_attrs)*
+        #fn_vis #fn_sig {
+            use std::time::Duration;
 
-                // Try to get from cache
-                if let Ok(Some(cached)) = __tagged_cache.get(&cache_key).await {
-                    if let Ok(result) = serde_json::from_str(&cached) {
-                        return result;
-                    }
-                }
+            // Generate cache key from function arguments; None when an
+            // argument fails to serialize, which bypasses the cache
+            let __cache_key: Option<String> = #cache_key;
 
-                // Execute function
-                let result = (|| async #fn_block)().await;
-
-                // Cache successful results with tags
-                if let Ok(ref success_result) = result {
-                    if let Ok(json) = serde_json::to_string(success_result) {
-                        let tags: &[&str] = &[#tags_slice];
-                        let _ = __tagged_cache.set_with_tags(
-                            &cache_key,
-                            json,
-                            tags,
-                            Some(Duration::from_secs(#ttl_secs))
-                        ).await;
+            // Try to get from cache
+            if let Some(cache_key) = &__cache_key {
+                if let Ok(Some(cached)) = #cache.get_json(cache_key).await {
+                    if let Ok(value) = serde_json::from_str(&cached) {
+                        return Ok(value);
                     }
                 }
+            }
+
+            // Execute function
+            let result = (|| async #fn_block)().await;
 
-                result
+            // Cache successful results
+            if let (Some(cache_key), Ok(success_result)) = (&__cache_key, &result) {
+                if let Ok(json) = serde_json::to_string(success_result) {
+                    #store
+                }
             }
+
+            result
         }
     };
 
     TokenStream::from(cache_code)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_placeholders() {
+        assert_eq!(
+            template_placeholders("user:{id}:posts:{page:?}:{id}").unwrap(),
+            vec!["id".to_string(), "page".to_string()]
+        );
+        assert_eq!(
+            template_placeholders("{{literal}}:{id}").unwrap(),
+            vec!["id".to_string()]
+        );
+        assert!(template_placeholders("static").unwrap().is_empty());
+
+        assert!(template_placeholders("user:{}").is_err());
+        assert!(template_placeholders("user:{0}").is_err());
+        assert!(template_placeholders("user:{id").is_err());
+    }
+}
@@ -1,13 +1,71 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Fields, ItemStruct, LitStr, parse_macro_input};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Error, Expr, Fields, Ident, ItemStruct, LitStr, Token, bracketed, parse_macro_input};
 
 use crate::route_validation::validate_controller_path;
 
+/// Arguments for the controller attribute
+/// Parses: #[controller("/admin", guards = [AuthGuard], middleware = [RateLimit])]
+struct ControllerArgs {
+    base_path: LitStr,
+    guards: Vec<Expr>,
+    middleware: Vec<Expr>,
+}
+
+impl Parse for ControllerArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let base_path = input.parse()?;
+        let mut guards = Vec::new();
+        let mut middleware = Vec::new();
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let content;
+            bracketed!(content in input);
+            let values: Vec<Expr> = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?
+                .into_iter()
+                .collect();
+
+            match key.to_string().as_str() {
+                "guards" => guards.extend(values),
+                "middleware" => middleware.extend(values),
+                _ => {
+                    return Err(Error::new(
+                        key.span(),
+                        format!(
+                            "unknown controller option '{}'\n\
+                             hint: expected 'guards = [...]' or 'middleware = [...]'",
+                            key
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            base_path,
+            guards,
+            middleware,
+        })
+    }
+}
+
 pub fn controller_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemStruct);
     let struct_name = &input.ident;
-    let base_path = parse_macro_input!(attr as LitStr);
+    let ControllerArgs {
+        base_path,
+        guards,
+        middleware,
+    } = parse_macro_input!(attr as ControllerArgs);
     let base_path_value = base_path.value();
 
     // Validate controller base path at compile time
@@ -65,6 +123,20 @@ pub fn controller_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
 
             #constructor
 
+            /// Guards applied to every route of this controller.
+            /// Per-method `#[guard(...)]` guards run after these.
+            pub fn __controller_guards() -> Vec<Box<dyn armature_core::Guard>> {
+                vec![#(Box::new(#guards) as Box<dyn armature_core::Guard>),*]
+            }
+
+            /// Middleware applied to every route of this controller.
+            #[allow(unused_mut)]
+            pub fn __controller_middleware() -> armature_core::MiddlewareChain {
+                let mut chain = armature_core::MiddlewareChain::new();
+                #(chain.use_middleware(#middleware);)*
+                chain
+            }
+
             /// Collect all routes defined on this controller.
             /// This method is called by the Controller trait implementation.
             /// Routes are added via the route registration macros.
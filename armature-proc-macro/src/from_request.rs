@@ -0,0 +1,124 @@
+use proc_macro::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Error, Fields, LitStr, Type, parse_macro_input};
+
+/// How a field is extracted from the request
+enum FieldSource {
+    /// `FromRequest::from_request(request)`
+    Request,
+    /// `FromRequestNamed::from_request(request, name)`
+    Named(String),
+}
+
+/// Work out how to extract a field from its attributes and type
+///
+/// `#[param("name")]` and `#[header("name")]` extract by name, defaulting to
+/// the field name when no name is given. Fields typed `Path<T>` or `Header`
+/// are extracted by their field name without an attribute.
+fn field_source(field: &syn::Field) -> syn::Result<FieldSource> {
+    let field_name = field
+        .ident
+        .as_ref()
+        .map(|ident| ident.to_string())
+        .unwrap_or_default();
+
+    for attr in &field.attrs {
+        if attr.path().is_ident("param") || attr.path().is_ident("header") {
+            if matches!(attr.meta, syn::Meta::Path(_)) {
+                return Ok(FieldSource::Named(field_name));
+            }
+            let name: LitStr = attr.parse_args()?;
+            return Ok(FieldSource::Named(name.value()));
+        }
+    }
+
+    if let Type::Path(type_path) = &field.ty
+        && let Some(segment) = type_path.path.segments.last()
+        && (segment.ident == "Path" || segment.ident == "Header")
+    {
+        return Ok(FieldSource::Named(field_name));
+    }
+
+    Ok(FieldSource::Request)
+}
+
+/// Derive macro for aggregate extractors
+///
+/// Implements `FromRequest` for a struct whose fields are extractors, filling
+/// every field from the same request. Fields are extracted in declaration
+/// order and the first failure is returned.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(FromRequest)]
+/// struct UpdatePost {
+///     id: Path<i64>,
+///     filter: Query<PostFilter>,
+///     body: Body<PostUpdate>,
+///     #[header("X-Request-Id")]
+///     request_id: Header,
+/// }
+///
+/// async fn update_post(req: HttpRequest) -> Result<HttpResponse, Error> {
+///     let UpdatePost { id, filter, body, request_id } = UpdatePost::from_request(&req)?;
+///     // ...
+/// }
+/// ```
+pub fn from_request_derive_impl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Error::new_spanned(
+                    &input.ident,
+                    "FromRequest can only be derived for structs with named fields",
+                )
+                .to_compile_error()
+                .into();
+            }
+        },
+        _ => {
+            return Error::new_spanned(
+                &input.ident,
+                "FromRequest can only be derived for structs with named fields",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let mut extractions = Vec::new();
+    for field in fields {
+        let field_name = &field.ident;
+        let ty = &field.ty;
+        // Span on the field type so a non-extractor field is reported there
+        let extraction = match field_source(field) {
+            Ok(FieldSource::Request) => quote_spanned! {ty.span()=>
+                <#ty as armature_core::extractors::FromRequest>::from_request(request)?
+            },
+            Ok(FieldSource::Named(param)) => quote_spanned! {ty.span()=>
+                <#ty as armature_core::extractors::FromRequestNamed>::from_request(request, #param)?
+            },
+            Err(e) => return e.to_compile_error().into(),
+        };
+        extractions.push(quote! { #field_name: #extraction });
+    }
+
+    let expanded = quote! {
+        impl #impl_generics armature_core::extractors::FromRequest for #name #ty_generics #where_clause {
+            fn from_request(request: &armature_core::HttpRequest) -> Result<Self, armature_core::Error> {
+                Ok(Self {
+                    #(#extractions),*
+                })
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
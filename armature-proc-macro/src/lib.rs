@@ -6,9 +6,11 @@ use proc_macro::TokenStream;
 mod body_limit_attr;
 mod cache_attr;
 mod controller;
+mod from_request;
 mod injectable;
 mod module;
 mod params;
+mod route_entry;
 mod route_validation;
 mod routes;
 mod routes_impl;
@@ -21,6 +23,27 @@ pub fn injectable(attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 /// Marks a struct as a controller with a base path
+///
+/// Optional `guards` and `middleware` lists apply to every route in the
+/// controller's `#[routes]` impl. Per-method `#[guard(...)]` guards run after
+/// the controller's guards.
+///
+/// # Usage
+///
+/// ```ignore
+/// #[controller("/admin", guards = [AuthenticationGuard], middleware = [LoggerMiddleware::new()])]
+/// #[derive(Default, Clone)]
+/// struct AdminController;
+///
+/// #[routes]
+/// impl AdminController {
+///     #[guard(RolesGuard::new(vec!["admin".to_string()]))]
+///     #[delete("/users/:id")]
+///     async fn delete_user(req: HttpRequest) -> Result<HttpResponse, Error> {
+///         Ok(HttpResponse::no_content())
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn controller(attr: TokenStream, item: TokenStream) -> TokenStream {
     controller::controller_impl(attr, item)
@@ -96,6 +119,18 @@ pub fn routes(attr: TokenStream, item: TokenStream) -> TokenStream {
     routes_impl::routes_impl(attr, item)
 }
 
+/// Builds one entry of the declarative `routes!` macro
+///
+/// Expands `GET "/users/:id<i64>" => get_user` into an `armature_core::Route`.
+/// Typed parameters are stripped from the path and registered as
+/// `ParseConstraint`s, so values that don't parse are rejected with
+/// 400 Bad Request before the handler runs.
+#[doc(hidden)]
+#[proc_macro]
+pub fn route_entry(input: TokenStream) -> TokenStream {
+    route_entry::route_entry_impl(input)
+}
+
 /// Extracts and deserializes the request body
 #[proc_macro_derive(Body)]
 pub fn body_derive(input: TokenStream) -> TokenStream {
@@ -114,6 +149,17 @@ pub fn query_derive(input: TokenStream) -> TokenStream {
     params::query_derive_impl(input)
 }
 
+/// Composes several extractors into one
+///
+/// Implements `FromRequest` for a struct whose fields are extractors, filling
+/// every field from the same request and returning the first extraction error.
+/// Fields typed `Path<T>` or `Header` are looked up by field name; use
+/// `#[param("name")]` or `#[header("name")]` to extract under another name.
+#[proc_macro_derive(FromRequest, attributes(param, header))]
+pub fn from_request_derive(input: TokenStream) -> TokenStream {
+    from_request::from_request_derive_impl(input)
+}
+
 /// Request timeout decorator
 ///
 /// Applies a timeout to the decorated route handler. If the handler doesn't
@@ -202,15 +248,15 @@ pub fn body_limit(attr: TokenStream, item: TokenStream) -> TokenStream {
 
 /// Cache method decorator
 ///
-/// Automatically caches the result of a method. The cache key is generated from
-/// the function name and arguments, and successful results are stored with a TTL.
+/// Automatically caches the result of a method. Successful results are stored
+/// with a TTL under a key built from the function name and arguments.
 ///
 /// # Usage
 ///
 /// ```ignore
 /// use armature::cache;
 ///
-/// // Basic caching with default TTL (1 hour)
+/// // Basic caching with default TTL (1 hour); the key is derived from `id`
 /// #[cache]
 /// async fn get_user(id: i64) -> Result<User, Error> {
 ///     // Expensive operation
@@ -218,14 +264,14 @@ pub fn body_limit(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// // Custom TTL (in seconds)
 /// #[cache(ttl = 300)]
-/// async fn get_posts(user_id: i64) -> Result<Vec<Post>, Error> {
+/// async fn get_posts(user_id: i64, filter: PostFilter) -> Result<Vec<Post>, Error> {
 ///     // Cached for 5 minutes
 /// }
 ///
 /// // Custom cache key template
-/// #[cache(key = "user:profile:{}", ttl = 600)]
-/// async fn get_profile(user_id: i64) -> Result<Profile, Error> {
-///     // Cached with specific key format
+/// #[cache(key = "user:profile:{user_id}", ttl = 600)]
+/// async fn get_profile(db: &Database, user_id: i64) -> Result<Profile, Error> {
+///     // Cached under "user:profile:42"
 /// }
 ///
 /// // With tags for invalidation
@@ -235,16 +281,31 @@ pub fn body_limit(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// # Cache Keys
+///
+/// - With `key = "..."`, `{arg}` placeholders are replaced by the named
+///   arguments using `Display` (or `Debug` with `{arg:?}`). Only the named
+///   arguments need to be formattable.
+/// - Without a template, the key is `fn_name:[args]`, the arguments
+///   serialized as a `serde_json` array, e.g. `search:[1,{"tag":"rust"}]`.
+///   Every argument must implement `Serialize`; one that doesn't is a
+///   compile error pointing at the argument. A `self` receiver is not part
+///   of the key.
+/// - Map arguments must serialize in a deterministic order: use a
+///   `BTreeMap` rather than a `HashMap`, whose iteration order differs
+///   between instances and would give the same arguments different keys.
+/// - If an argument fails to serialize at runtime the cache is bypassed.
+///
 /// # Requirements
 ///
 /// - The function must be async
 /// - The return type must be `Result<T, E>` where `T: Serialize + DeserializeOwned`
-/// - Requires a `__cache` or `__tagged_cache` variable in scope
+/// - Requires a `__cache` (or, with `tag`, `__tagged_cache`) in scope at the
+///   function's definition, such as a static
 ///
 /// # Notes
 ///
 /// - Only successful results (`Ok` variants) are cached
-/// - Cache keys are generated from the function name and arguments
 /// - Default TTL is 3600 seconds (1 hour)
 #[proc_macro_attribute]
 pub fn cache(attr: TokenStream, item: TokenStream) -> TokenStream {
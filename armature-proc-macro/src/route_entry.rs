@@ -0,0 +1,170 @@
+//! Route entries with typed path parameters
+//!
+//! Expands one `METHOD "/path" => handler` entry of the declarative
+//! `routes!` macro into an `armature_core::Route`. Parameters may declare a
+//! type inline (`:id<i64>`): the type is stripped from the registered path and
+//! becomes a `ParseConstraint`, so a request whose value doesn't parse is
+//! rejected with 400 Bad Request before the handler runs.
+
+use proc_macro::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, Expr, Ident, LitStr, Token, Type, parse_macro_input};
+
+use crate::route_validation::validate_route_path;
+
+const METHODS: [&str; 7] = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS"];
+
+/// `METHOD "/path" => handler`
+struct RouteEntry {
+    method: Ident,
+    path: LitStr,
+    handler: Expr,
+}
+
+impl Parse for RouteEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let method = input.parse()?;
+        let path = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let handler = input.parse()?;
+        Ok(Self {
+            method,
+            path,
+            handler,
+        })
+    }
+}
+
+/// A route path with the parameter types stripped out
+#[derive(Debug, PartialEq)]
+struct TypedPath {
+    /// The path as registered with the router (e.g. "/users/:id")
+    path: String,
+    /// Declared parameter types as (name, type) pairs
+    types: Vec<(String, String)>,
+}
+
+/// Split `:name<Type>` parameters into the plain path and their types
+fn split_typed_params(path: &str) -> Result<TypedPath, String> {
+    let mut types = Vec::new();
+    let segments = path
+        .split('/')
+        .map(|segment| {
+            let Some((name, ty)) = segment
+                .strip_prefix(':')
+                .and_then(|param| param.split_once('<'))
+            else {
+                return Ok(segment.to_string());
+            };
+
+            let ty = ty
+                .strip_suffix('>')
+                .filter(|ty| !ty.trim().is_empty())
+                .ok_or_else(|| {
+                    format!(
+                        "invalid typed path parameter '{}'\n\
+                         hint: declare the type as ':{}<Type>'",
+                        segment, name
+                    )
+                })?;
+            types.push((name.to_string(), ty.to_string()));
+            Ok(format!(":{}", name))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(TypedPath {
+        path: segments.join("/"),
+        types,
+    })
+}
+
+pub fn route_entry_impl(input: TokenStream) -> TokenStream {
+    let RouteEntry {
+        method,
+        path,
+        handler,
+    } = parse_macro_input!(input as RouteEntry);
+
+    let method_name = method.to_string().to_uppercase();
+    if !METHODS.contains(&method_name.as_str()) {
+        return Error::new(
+            method.span(),
+            format!(
+                "unknown HTTP method '{}'\n\
+                 hint: use one of {}",
+                method,
+                METHODS.join(", ")
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+    let method = Ident::new(&method_name, method.span());
+
+    let typed = match split_typed_params(&path.value()) {
+        Ok(typed) => typed,
+        Err(message) => return Error::new(path.span(), message).to_compile_error().into(),
+    };
+
+    if let Err(e) = validate_route_path(&typed.path, path.span()) {
+        return e.to_compile_error().into();
+    }
+
+    let mut constraints = Vec::new();
+    for (name, ty) in &typed.types {
+        // Parse with the literal's span so type errors point at the route
+        let ty = match LitStr::new(ty, path.span()).parse::<Type>() {
+            Ok(ty) => ty,
+            Err(_) => {
+                return Error::new(
+                    path.span(),
+                    format!("invalid type '{}' for path parameter '{}'", ty, name),
+                )
+                .to_compile_error()
+                .into();
+            }
+        };
+        constraints.push(quote_spanned! {path.span()=>
+            .add(#name, Box::new(armature_core::ParseConstraint::<#ty>::new()))
+        });
+    }
+
+    let route_path = &typed.path;
+    let route = quote! {
+        armature_core::Route::new(armature_core::HttpMethod::#method, #route_path, #handler)
+    };
+
+    let expanded = if constraints.is_empty() {
+        route
+    } else {
+        quote! {
+            #route.with_constraints(armature_core::RouteConstraints::new() #(#constraints)*)
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_typed_params() {
+        let typed = split_typed_params("/users/:id<i64>/posts/:slug").unwrap();
+        assert_eq!(typed.path, "/users/:id/posts/:slug");
+        assert_eq!(typed.types, vec![("id".to_string(), "i64".to_string())]);
+
+        let typed = split_typed_params("/tags/:ids<Vec<u8>>").unwrap();
+        assert_eq!(typed.path, "/tags/:ids");
+        assert_eq!(typed.types[0].1, "Vec<u8>");
+
+        let typed = split_typed_params("/users/:id").unwrap();
+        assert_eq!(typed.path, "/users/:id");
+        assert!(typed.types.is_empty());
+
+        assert!(split_typed_params("/users/:id<i64").is_err());
+        assert!(split_typed_params("/users/:id<>").is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
+use syn::punctuated::Punctuated;
 use syn::{
-    Attribute, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, LitStr, PatType, Type,
+    Attribute, Expr, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, LitStr, PatType, Token, Type,
     parse_macro_input,
 };
 
@@ -71,14 +72,29 @@ fn extract_route_info(method: &ImplItemFn) -> Option<RouteInfo> {
     None
 }
 
-/// Remove route attributes from a method (get, post, put, delete, patch)
+/// Collect the guards from a method's `#[guard(...)]` attributes
+///
+/// These run after the controller-level guards from `#[controller]`.
+fn extract_method_guards(attrs: &[Attribute]) -> syn::Result<Vec<Expr>> {
+    let mut guards = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("guard")) {
+        let parsed = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+        guards.extend(parsed);
+    }
+    Ok(guards)
+}
+
+/// Remove route attributes from a method (get, post, put, delete, patch, guard)
 fn strip_route_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
     attrs
         .iter()
         .filter(|attr| {
             if let Some(ident) = attr.path().get_ident() {
                 let name = ident.to_string();
-                !matches!(name.as_str(), "get" | "post" | "put" | "delete" | "patch")
+                !matches!(
+                    name.as_str(),
+                    "get" | "post" | "put" | "delete" | "patch" | "guard"
+                )
             } else {
                 true
             }
@@ -210,7 +226,32 @@ pub fn routes_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                     },
                 };
 
-                route_handlers.push(handler);
+                let method_guards = match extract_method_guards(&method.attrs) {
+                    Ok(guards) => guards,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+                let guards = if method_guards.is_empty() {
+                    quote! { Self::__controller_guards() }
+                } else {
+                    quote! {{
+                        let mut guards = Self::__controller_guards();
+                        #(guards.push(Box::new(#method_guards) as Box<dyn armature_core::Guard>);)*
+                        guards
+                    }}
+                };
+
+                route_handlers.push(quote! {{
+                    let (method, path, handler) = #handler;
+                    (
+                        method,
+                        path,
+                        armature_core::route_registry::guarded_handler(
+                            handler,
+                            #guards,
+                            middleware.clone(),
+                        ),
+                    )
+                }});
 
                 // Create modified method without route attributes
                 let mut modified_method = method.clone();
@@ -240,9 +281,12 @@ pub fn routes_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             #(#modified_items)*
 
             /// Returns the route handlers for this controller.
-            /// Generated by the #[routes] macro.
+            /// Generated by the #[routes] macro. Each handler is wrapped with
+            /// the controller's guards and middleware.
             #[allow(clippy::redundant_clone)]
             pub fn __route_handlers(controller: std::sync::Arc<Self>) -> Vec<(&'static str, &'static str, armature_core::route_registry::RouteHandlerFn)> {
+                let middleware = Self::__controller_middleware();
+
                 // Create individual clones for each route handler closure
                 let mut handlers = Vec::new();
                 #({
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use armature_proc_macro::cache;
+use serde::Serialize;
+
+/// In-memory stand-in for a `CacheStore`
+#[derive(Default)]
+struct MemoryCache {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryCache {
+    async fn get_json(&self, key: &str) -> Result<Option<String>, ()> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set_json(&self, key: &str, value: String, _ttl: Option<Duration>) -> Result<(), ()> {
+        self.entries.lock().unwrap().insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut keys: Vec<_> = self
+            .entries
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[allow(non_upper_case_globals)]
+static __cache: LazyLock<MemoryCache> = LazyLock::new(MemoryCache::default);
+
+static SEARCH_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PROFILE_CALLS: AtomicUsize = AtomicUsize::new(0);
+static PAIR_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Serialize)]
+struct Filter {
+    tag: String,
+    limit: u32,
+}
+
+#[cache(ttl = 60)]
+async fn search(user_id: i64, filter: &Filter) -> Result<Vec<String>, String> {
+    SEARCH_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(vec![format!("{}:{}:{}", user_id, filter.tag, filter.limit)])
+}
+
+#[cache]
+async fn pair(first: &str, second: &str) -> Result<String, String> {
+    PAIR_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(format!("{}|{}", first, second))
+}
+
+#[cache(key = "profile:{user_id}:{lang:?}")]
+async fn profile(user_id: i64, lang: Option<&str>) -> Result<String, String> {
+    PROFILE_CALLS.fetch_add(1, Ordering::SeqCst);
+    Ok(format!("{}:{}", user_id, lang.unwrap_or("en")))
+}
+
+#[tokio::test]
+async fn test_cache_derived_key() {
+    let filter = Filter {
+        tag: "rust".to_string(),
+        limit: 10,
+    };
+
+    let first = search(1, &filter).await.unwrap();
+    let second = search(1, &filter).await.unwrap();
+    assert_eq!(first, second);
+    assert_eq!(SEARCH_CALLS.load(Ordering::SeqCst), 1);
+
+    // Any change to a serialized argument gives a new key
+    let other = Filter {
+        tag: "rust".to_string(),
+        limit: 20,
+    };
+    search(1, &other).await.unwrap();
+    search(2, &filter).await.unwrap();
+    assert_eq!(SEARCH_CALLS.load(Ordering::SeqCst), 3);
+
+    assert_eq!(
+        __cache.keys_with_prefix("search:"),
+        vec![
+            r#"search:[1,{"tag":"rust","limit":10}]"#.to_string(),
+            r#"search:[1,{"tag":"rust","limit":20}]"#.to_string(),
+            r#"search:[2,{"tag":"rust","limit":10}]"#.to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_cache_derived_key_distinct_arguments() {
+    // Argument sets whose concatenated text is identical
+    pair("a", "b,c").await.unwrap();
+    pair("a,b", "c").await.unwrap();
+    pair("a\",\"b", "c").await.unwrap();
+    assert_eq!(PAIR_CALLS.load(Ordering::SeqCst), 3);
+    assert_eq!(__cache.keys_with_prefix("pair:").len(), 3);
+
+    pair("a", "b,c").await.unwrap();
+    assert_eq!(PAIR_CALLS.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_cache_template_key() {
+    profile(7, Some("fr")).await.unwrap();
+    profile(7, Some("fr")).await.unwrap();
+    profile(7, None).await.unwrap();
+    assert_eq!(PROFILE_CALLS.load(Ordering::SeqCst), 2);
+
+    assert_eq!(
+        __cache.keys_with_prefix("profile:"),
+        vec![
+            "profile:7:None".to_string(),
+            "profile:7:Some(\"fr\")".to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_cache_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/cache-derived-key.rs");
+    t.pass("tests/ui/cache-template-key.rs");
+    t.compile_fail("tests/ui/cache-not-serializable.rs");
+    t.compile_fail("tests/ui/cache-unknown-placeholder.rs");
+}
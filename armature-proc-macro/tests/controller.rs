@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use armature_core::{
+    AuthenticationGuard, Error, Guard, GuardContext, HttpRequest, HttpResponse, Middleware, Next,
+};
+use armature_proc_macro::{controller, routes};
+
+/// Rejects requests without an `x-role: admin` header
+struct AdminRoleGuard;
+
+#[async_trait::async_trait]
+impl Guard for AdminRoleGuard {
+    async fn can_activate(&self, context: &GuardContext) -> Result<bool, Error> {
+        Ok(context
+            .get_header("x-role")
+            .is_some_and(|role| role == "admin"))
+    }
+}
+
+/// Tags every response so tests can see the middleware ran
+struct ServedBy;
+
+#[async_trait::async_trait]
+impl Middleware for ServedBy {
+    async fn handle(&self, req: HttpRequest, next: Next) -> Result<HttpResponse, Error> {
+        let response = next(req).await?;
+        Ok(response.with_header("X-Served-By".to_string(), "admin".to_string()))
+    }
+}
+
+#[controller("/admin", guards = [AuthenticationGuard], middleware = [ServedBy])]
+#[derive(Default, Clone)]
+struct AdminController;
+
+#[routes]
+impl AdminController {
+    #[get("/stats")]
+    async fn stats(&self) -> Result<HttpResponse, Error> {
+        Ok(HttpResponse::ok())
+    }
+
+    #[get("/users/:id")]
+    async fn user(req: HttpRequest) -> Result<HttpResponse, Error> {
+        Ok(HttpResponse::ok().with_body(req.param("id").cloned().unwrap_or_default().into_bytes()))
+    }
+
+    #[guard(AdminRoleGuard)]
+    #[post("/users")]
+    async fn create_user(_req: HttpRequest) -> Result<HttpResponse, Error> {
+        Ok(HttpResponse::created())
+    }
+}
+
+fn handlers() -> Vec<(
+    &'static str,
+    &'static str,
+    armature_core::route_registry::RouteHandlerFn,
+)> {
+    AdminController::__route_handlers(Arc::new(AdminController))
+}
+
+fn request(method: &str, path: &str, headers: &[(&str, &str)]) -> HttpRequest {
+    let mut req = HttpRequest::new(method.to_string(), path.to_string());
+    for (name, value) in headers {
+        req.headers.insert(name.to_string(), value.to_string());
+    }
+    req
+}
+
+#[tokio::test]
+async fn test_unauthenticated_requests_are_rejected() {
+    let handlers = handlers();
+    assert_eq!(handlers.len(), 3);
+
+    for (method, path, handler) in handlers {
+        let err = handler(request(method, path, &[])).await.unwrap_err();
+        assert!(
+            matches!(err, Error::Forbidden(_)),
+            "{} {} should be rejected",
+            method,
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_authenticated_requests_pass_through_middleware() {
+    for (method, path, handler) in handlers() {
+        if method != "GET" {
+            continue;
+        }
+        let response = handler(request(method, path, &[("authorization", "Bearer token")]))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.headers.get("X-Served-By").unwrap(), "admin");
+    }
+}
+
+#[tokio::test]
+async fn test_method_guards_compose_with_controller_guards() {
+    let (_, _, create_user) = handlers()
+        .into_iter()
+        .find(|(method, _, _)| *method == "POST")
+        .unwrap();
+
+    // The method guard doesn't replace the controller guard
+    let err = create_user(request("POST", "/users", &[("x-role", "admin")]))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Forbidden(ref msg) if msg.contains("authorization")));
+
+    let err = create_user(request(
+        "POST",
+        "/users",
+        &[("authorization", "Bearer token")],
+    ))
+    .await
+    .unwrap_err();
+    assert!(matches!(err, Error::Forbidden(ref msg) if msg == "Access denied by guard"));
+
+    let response = create_user(request(
+        "POST",
+        "/users",
+        &[("authorization", "Bearer token"), ("x-role", "admin")],
+    ))
+    .await
+    .unwrap();
+    assert_eq!(response.status, 201);
+}
@@ -0,0 +1,74 @@
+use armature_core::extractors::{Body, FromRequest, Header, Path, Query};
+use armature_core::{Error, HttpRequest};
+use armature_proc_macro::FromRequest;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct PostFilter {
+    draft: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostUpdate {
+    title: String,
+}
+
+#[derive(FromRequest)]
+struct UpdatePost {
+    id: Path<i64>,
+    filter: Query<PostFilter>,
+    body: Body<PostUpdate>,
+    #[header("X-Request-Id")]
+    request_id: Header,
+}
+
+fn request() -> HttpRequest {
+    let mut req = HttpRequest::new("PUT".to_string(), "/posts/7?draft=true".to_string());
+    req.path_params.insert("id".to_string(), "7".to_string());
+    req.query_params
+        .insert("draft".to_string(), "true".to_string());
+    req.headers
+        .insert("x-request-id".to_string(), "abc123".to_string());
+    req.set_body(serde_json::to_vec(&serde_json::json!({ "title": "Hello" })).unwrap());
+    req
+}
+
+#[test]
+fn test_from_request_aggregate() {
+    let UpdatePost {
+        id,
+        filter,
+        body,
+        request_id,
+    } = UpdatePost::from_request(&request()).unwrap();
+
+    assert_eq!(*id, 7);
+    assert!(filter.draft);
+    assert_eq!(body.title, "Hello");
+    assert_eq!(request_id.value(), "abc123");
+}
+
+#[test]
+fn test_from_request_first_error() {
+    // Both the path parameter and the body are invalid; the path comes first
+    let mut req = request();
+    req.path_params
+        .insert("id".to_string(), "seven".to_string());
+    req.set_body(b"not json".to_vec());
+
+    let err = UpdatePost::from_request(&req).err().unwrap();
+    assert!(matches!(err, Error::Validation(ref msg) if msg.contains("'id'")));
+
+    let mut req = request();
+    req.set_body(b"not json".to_vec());
+    let err = UpdatePost::from_request(&req).err().unwrap();
+    assert!(matches!(err, Error::Deserialization(_)));
+}
+
+#[test]
+fn test_from_request_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/from-request-aggregate.rs");
+    t.compile_fail("tests/ui/from-request-not-extractor.rs");
+    t.compile_fail("tests/ui/from-request-tuple-struct.rs");
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use armature_proc_macro::cache;
+use serde::Serialize;
+
+struct Store;
+
+impl Store {
+    async fn get_json(&self, _key: &str) -> Result<Option<String>, ()> {
+        Ok(None)
+    }
+
+    async fn set_json(&self, _key: &str, _value: String, _ttl: Option<Duration>) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+#[allow(non_upper_case_globals)]
+static __cache: Store = Store;
+
+#[derive(Serialize)]
+enum Sort {
+    Newest,
+    Oldest,
+}
+
+#[derive(Serialize)]
+struct Page {
+    number: u32,
+    size: u32,
+}
+
+// The key serializes every argument
+#[cache(ttl = 300)]
+async fn list_posts(author: String, tags: Vec<&str>, sort: Sort, page: &Page) -> Result<Vec<u64>, String> {
+    let _ = (author, tags, sort, page);
+    Ok(vec![])
+}
+
+#[cache]
+async fn count_posts() -> Result<u64, String> {
+    Ok(0)
+}
+
+fn main() {
+    let _ = list_posts(
+        "ada".to_string(),
+        vec!["rust"],
+        Sort::Newest,
+        &Page { number: 1, size: 20 },
+    );
+    let _ = Sort::Oldest;
+    let _ = count_posts();
+}
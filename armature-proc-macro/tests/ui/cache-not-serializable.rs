@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use armature_proc_macro::cache;
+
+struct Store;
+
+impl Store {
+    async fn get_json(&self, _key: &str) -> Result<Option<String>, ()> {
+        Ok(None)
+    }
+
+    async fn set_json(&self, _key: &str, _value: String, _ttl: Option<Duration>) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+#[allow(non_upper_case_globals)]
+static __cache: Store = Store;
+
+struct Connection;
+
+#[cache]
+async fn user_posts(conn: &Connection, user_id: i64) -> Result<Vec<u64>, String> {
+    let _ = conn;
+    Ok(vec![user_id as u64])
+}
+
+fn main() {}
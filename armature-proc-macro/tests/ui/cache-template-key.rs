@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use armature_proc_macro::cache;
+
+struct Store;
+
+impl Store {
+    async fn get_json(&self, _key: &str) -> Result<Option<String>, ()> {
+        Ok(None)
+    }
+
+    async fn set_json(&self, _key: &str, _value: String, _ttl: Option<Duration>) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+#[allow(non_upper_case_globals)]
+static __cache: Store = Store;
+
+// Not `Serialize`, so it can only appear in an explicit key
+struct Connection;
+
+#[cache(key = "user:{user_id}:posts:{{all}}", ttl = 600)]
+async fn user_posts(conn: &Connection, user_id: i64) -> Result<Vec<u64>, String> {
+    let _ = conn;
+    Ok(vec![user_id as u64])
+}
+
+fn main() {
+    let _ = user_posts(&Connection, 1);
+}
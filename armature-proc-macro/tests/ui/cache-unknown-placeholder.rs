@@ -0,0 +1,13 @@
+use armature_proc_macro::cache;
+
+#[cache(key = "user:{id}")]
+async fn user_posts(user_id: i64) -> Result<Vec<u64>, String> {
+    Ok(vec![user_id as u64])
+}
+
+#[cache(key = "user:{}")]
+async fn user_profile(user_id: i64) -> Result<String, String> {
+    Ok(user_id.to_string())
+}
+
+fn main() {}
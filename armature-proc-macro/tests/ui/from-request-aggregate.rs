@@ -0,0 +1,31 @@
+use armature_core::extractors::{Body, FromRequest, Path, Query};
+use armature_core::{Error, HttpRequest};
+use armature_proc_macro::FromRequest;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Filter {
+    tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NewComment {
+    text: String,
+}
+
+#[derive(FromRequest)]
+struct CreateComment {
+    #[param("post_id")]
+    post: Path<i64>,
+    filter: Query<Filter>,
+    body: Body<NewComment>,
+}
+
+fn handle(req: &HttpRequest) -> Result<String, Error> {
+    let CreateComment { post, filter, body } = CreateComment::from_request(req)?;
+    Ok(format!("{} {:?} {}", *post, filter.tag, body.text))
+}
+
+fn main() {
+    let _ = handle;
+}
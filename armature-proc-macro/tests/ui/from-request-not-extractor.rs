@@ -0,0 +1,10 @@
+use armature_core::extractors::Path;
+use armature_proc_macro::FromRequest;
+
+#[derive(FromRequest)]
+struct GetUser {
+    id: Path<i64>,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use armature_core::extractors::Path;
+use armature_proc_macro::FromRequest;
+
+#[derive(FromRequest)]
+struct GetUser(Path<i64>);
+
+fn main() {}
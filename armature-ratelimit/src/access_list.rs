@@ -0,0 +1,284 @@
+//! Allowlists and denylists
+//!
+//! Allowlisted keys skip rate limiting entirely, while denylisted keys are
+//! always rejected. Entries are either exact keys or, for IP keys, CIDR ranges
+//! such as `10.0.0.0/8` or `fd00::/8`.
+//!
+//! Both lists can be replaced at runtime without a restart:
+//!
+//! ```
+//! use armature_ratelimit::access_list::AccessLists;
+//!
+//! let lists = AccessLists::new();
+//! lists.set_allowlist(["10.0.0.0/8", "health-checker"]);
+//! assert!(lists.is_allowlisted("10.1.2.3"));
+//! assert!(lists.is_allowlisted("health-checker"));
+//!
+//! lists.set_denylist(["203.0.113.7"]);
+//! assert!(lists.is_denylisted("203.0.113.7"));
+//! ```
+//!
+//! CIDR ranges only match keys that are bare IP addresses, as produced by
+//! `KeyExtractor::Ip`.
+
+use arc_swap::ArcSwap;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// An IP network in CIDR notation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    /// Create a network, masking off host bits
+    ///
+    /// Returns None if the prefix length is too long for the address family.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Self> {
+        let addr = addr.to_canonical();
+        let network = match addr {
+            IpAddr::V4(v4) if prefix_len <= 32 => {
+                IpAddr::V4((u32::from(v4) & v4_mask(prefix_len)).into())
+            }
+            IpAddr::V6(v6) if prefix_len <= 128 => {
+                IpAddr::V6((u128::from(v6) & v6_mask(prefix_len)).into())
+            }
+            _ => return None,
+        };
+        Some(Self {
+            network,
+            prefix_len,
+        })
+    }
+
+    /// Get the network address
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    /// Get the prefix length
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Check if an address is in the network
+    ///
+    /// IPv4-mapped IPv6 addresses match IPv4 networks.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.network, addr.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => {
+                u32::from(addr) & v4_mask(self.prefix_len) == u32::from(network)
+            }
+            (IpAddr::V6(network), IpAddr::V6(addr)) => {
+                u128::from(addr) & v6_mask(self.prefix_len) == u128::from(network)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn v4_mask(prefix_len: u8) -> u32 {
+    u32::MAX
+        .checked_shl(32 - u32::from(prefix_len))
+        .unwrap_or(0)
+}
+
+fn v6_mask(prefix_len: u8) -> u128 {
+    u128::MAX
+        .checked_shl(128 - u32::from(prefix_len))
+        .unwrap_or(0)
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    /// Parse `addr/len`, or a bare address as a single-host network
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (s, None),
+        };
+
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid IP address in '{}'", s))?;
+        let addr = addr.to_canonical();
+        let max_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(len) => len
+                .trim()
+                .parse::<u8>()
+                .map_err(|_| format!("Invalid prefix length in '{}'", s))?,
+            None => max_len,
+        };
+
+        Self::new(addr, prefix_len)
+            .ok_or_else(|| format!("Prefix length in '{}' exceeds {}", s, max_len))
+    }
+}
+
+impl std::fmt::Display for Cidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix_len)
+    }
+}
+
+/// A list of exact keys and IP ranges
+#[derive(Debug, Clone, Default)]
+pub struct KeyList {
+    keys: Vec<String>,
+    networks: Vec<Cidr>,
+}
+
+impl KeyList {
+    /// Create a list from entries
+    ///
+    /// Entries that parse as an IP address or CIDR range match IP keys in
+    /// that range; anything else matches keys exactly.
+    pub fn new(entries: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut list = Self::default();
+        for entry in entries {
+            let entry = entry.into();
+            match entry.parse::<Cidr>() {
+                Ok(network) => list.networks.push(network),
+                Err(_) => list.keys.push(entry),
+            }
+        }
+        list
+    }
+
+    /// Check if a key is in the list
+    pub fn contains(&self, key: &str) -> bool {
+        if self.keys.iter().any(|k| k == key) {
+            return true;
+        }
+
+        match key.parse::<IpAddr>() {
+            Ok(addr) => self.networks.iter().any(|network| network.contains(addr)),
+            Err(_) => false,
+        }
+    }
+
+    /// Check if the list has no entries
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.networks.is_empty()
+    }
+}
+
+/// Allowlist and denylist, swappable at runtime
+///
+/// Share one instance between middleware through an `Arc`; updates are seen
+/// by the next request without locking the request path.
+#[derive(Debug, Default)]
+pub struct AccessLists {
+    allowlist: ArcSwap<KeyList>,
+    denylist: ArcSwap<KeyList>,
+}
+
+impl AccessLists {
+    /// Create empty lists
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create from an allowlist and a denylist
+    pub fn with_lists(allowlist: KeyList, denylist: KeyList) -> Self {
+        Self {
+            allowlist: ArcSwap::from_pointee(allowlist),
+            denylist: ArcSwap::from_pointee(denylist),
+        }
+    }
+
+    /// Replace the allowlist
+    pub fn set_allowlist(&self, entries: impl IntoIterator<Item = impl Into<String>>) {
+        self.allowlist.store(Arc::new(KeyList::new(entries)));
+    }
+
+    /// Replace the denylist
+    pub fn set_denylist(&self, entries: impl IntoIterator<Item = impl Into<String>>) {
+        self.denylist.store(Arc::new(KeyList::new(entries)));
+    }
+
+    /// Get the current allowlist
+    pub fn allowlist(&self) -> Arc<KeyList> {
+        self.allowlist.load_full()
+    }
+
+    /// Get the current denylist
+    pub fn denylist(&self) -> Arc<KeyList> {
+        self.denylist.load_full()
+    }
+
+    /// Check if a key skips rate limiting
+    pub fn is_allowlisted(&self, key: &str) -> bool {
+        self.allowlist.load().contains(key)
+    }
+
+    /// Check if a key is always rejected
+    pub fn is_denylisted(&self, key: &str) -> bool {
+        self.denylist.load().contains(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cidr_parse() {
+        let cidr: Cidr = "192.168.1.77/24".parse().unwrap();
+        assert_eq!(cidr.to_string(), "192.168.1.0/24");
+
+        let host: Cidr = "2001:db8::1".parse().unwrap();
+        assert_eq!(host.prefix_len(), 128);
+
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("10.0.0.0/x".parse::<Cidr>().is_err());
+        assert!("not-an-ip/8".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn test_cidr_contains() {
+        let v4: Cidr = "10.0.0.0/8".parse().unwrap();
+        assert!(v4.contains("10.255.0.1".parse().unwrap()));
+        assert!(!v4.contains("11.0.0.1".parse().unwrap()));
+        assert!(v4.contains("::ffff:10.1.2.3".parse().unwrap()));
+
+        let v6: Cidr = "fd00::/8".parse().unwrap();
+        assert!(v6.contains("fd12:3456::1".parse().unwrap()));
+        assert!(!v6.contains("fe80::1".parse().unwrap()));
+        assert!(!v6.contains("10.0.0.1".parse().unwrap()));
+
+        let all: Cidr = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains("203.0.113.7".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_key_list() {
+        let list = KeyList::new(["127.0.0.1", "10.0.0.0/8", "admin-key"]);
+
+        assert!(list.contains("127.0.0.1"));
+        assert!(list.contains("10.20.30.40"));
+        assert!(list.contains("admin-key"));
+        assert!(!list.contains("127.0.0.2"));
+        assert!(!list.contains("user-key"));
+        assert!(KeyList::default().is_empty());
+    }
+
+    #[test]
+    fn test_swap_lists() {
+        let lists = AccessLists::new();
+        assert!(!lists.is_denylisted("203.0.113.7"));
+
+        lists.set_denylist(["203.0.113.0/24"]);
+        assert!(lists.is_denylisted("203.0.113.7"));
+
+        lists.set_denylist(Vec::<String>::new());
+        assert!(!lists.is_denylisted("203.0.113.7"));
+        assert!(lists.denylist().is_empty());
+    }
+}
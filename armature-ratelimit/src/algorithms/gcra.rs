@@ -0,0 +1,226 @@
+//! Generic Cell Rate Algorithm (GCRA)
+//!
+//! GCRA is a leaky bucket variant that paces requests evenly instead of
+//! letting a full bucket drain at once. It stores a single timestamp per key:
+//! the theoretical arrival time (TAT) of the next request.
+//!
+//! ## How It Works
+//!
+//! 1. Each admitted request moves the TAT forward by one `period`
+//! 2. A request is admitted if the TAT would be at most `burst` periods ahead
+//! 3. Otherwise it is denied, and the exact wait until it would be admitted
+//!    is known from the TAT
+//!
+//! With `burst: 1` requests are spaced at least `period` apart. Larger bursts
+//! behave like a token bucket of that capacity refilling one token per period,
+//! but the retry time of a denied request is precise.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use armature_ratelimit::algorithms::Gcra;
+//! use armature_ratelimit::algorithms::RateLimitAlgorithm;
+//! use std::time::Duration;
+//!
+//! let limiter = Gcra::new(Duration::from_secs(1), 3); // 1/sec, bursts of 3
+//!
+//! // First 3 requests succeed (burst)
+//! for _ in 0..3 {
+//!     assert!(limiter.check("user1").0);
+//! }
+//!
+//! // 4th request fails until a period has passed
+//! assert!(!limiter.check("user1").0);
+//! ```
+
+use super::RateLimitAlgorithm;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+/// Length of `n` periods, saturating instead of overflowing
+pub(crate) fn gcra_periods(period: Duration, n: u64) -> Duration {
+    period
+        .checked_mul(u32::try_from(n).unwrap_or(u32::MAX))
+        .unwrap_or(Duration::MAX)
+}
+
+/// Apply one GCRA check at `now` against the stored theoretical arrival time
+///
+/// A request costing `cost` moves the TAT forward by that many periods.
+/// Returns the new TAT and the remaining burst if admitted, or the exact wait
+/// until the request would be admitted if denied.
+pub(crate) fn gcra_step(
+    tat: Instant,
+    now: Instant,
+    period: Duration,
+    burst: u64,
+    cost: u64,
+) -> Result<(Instant, u64), Duration> {
+    // How far ahead of now the TAT may be for a request to be admitted
+    let tolerance = gcra_periods(period, burst);
+    let new_tat = tat
+        .max(now)
+        .checked_add(gcra_periods(period, cost))
+        .ok_or(Duration::MAX)?;
+    let ahead = new_tat - now;
+
+    if ahead > tolerance {
+        return Err(ahead - tolerance);
+    }
+
+    let remaining = (tolerance - ahead).as_nanos() / period.as_nanos().max(1);
+    Ok((new_tat, remaining as u64))
+}
+
+/// GCRA rate limiter
+pub struct Gcra {
+    /// Interval between requests at the sustained rate
+    period: Duration,
+    /// Requests that may be admitted back to back
+    burst: u64,
+    /// Theoretical arrival time per key
+    tats: DashMap<String, Instant>,
+}
+
+impl Gcra {
+    /// Create a new GCRA rate limiter
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - Interval between requests at the sustained rate
+    /// * `burst` - Requests that may be admitted back to back
+    ///
+    /// # Panics
+    ///
+    /// Panics if period is zero or burst is 0
+    pub fn new(period: Duration, burst: u64) -> Self {
+        assert!(!period.is_zero(), "Period must be non-zero");
+        assert!(burst > 0, "Burst must be greater than 0");
+
+        Self {
+            period,
+            burst,
+            tats: DashMap::new(),
+        }
+    }
+
+    /// Try to admit a request
+    ///
+    /// Returns whether it was admitted, the remaining burst, and how long to
+    /// wait before retrying if it was not.
+    pub fn try_acquire(&self, key: &str) -> (bool, u64, Option<Duration>) {
+        let now = Instant::now();
+        let mut tat = self.tats.entry(key.to_string()).or_insert(now);
+
+        match gcra_step(*tat, now, self.period, self.burst, 1) {
+            Ok((new_tat, remaining)) => {
+                *tat = new_tat;
+                (true, remaining, None)
+            }
+            Err(retry_after) => (false, 0, Some(retry_after)),
+        }
+    }
+
+    /// Get the period
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Get the burst size
+    pub fn burst(&self) -> u64 {
+        self.burst
+    }
+}
+
+impl RateLimitAlgorithm for Gcra {
+    fn check(&self, key: &str) -> (bool, u64) {
+        let (allowed, remaining, _) = self.try_acquire(key);
+        (allowed, remaining)
+    }
+
+    fn reset(&self, key: &str) {
+        self.tats.remove(key);
+    }
+
+    fn remaining(&self, key: &str) -> u64 {
+        let Some(tat) = self.tats.get(key) else {
+            return self.burst;
+        };
+        let ahead = tat.saturating_duration_since(Instant::now());
+        let free = gcra_periods(self.period, self.burst).saturating_sub(ahead);
+        (free.as_nanos() / self.period.as_nanos()) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_burst_then_deny() {
+        let limiter = Gcra::new(Duration::from_secs(1), 3);
+
+        for i in (0..3).rev() {
+            let (allowed, remaining, _) = limiter.try_acquire("test");
+            assert!(allowed);
+            assert_eq!(remaining, i);
+        }
+
+        let (allowed, remaining, retry_after) = limiter.try_acquire("test");
+        assert!(!allowed);
+        assert_eq!(remaining, 0);
+        let retry_after = retry_after.unwrap();
+        assert!(retry_after > Duration::from_millis(900) && retry_after <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_pacing() {
+        let limiter = Gcra::new(Duration::from_millis(50), 1);
+
+        assert!(limiter.check("test").0);
+        assert!(!limiter.check("test").0);
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(limiter.check("test").0);
+        assert!(!limiter.check("test").0);
+    }
+
+    #[test]
+    fn test_step_exact_retry_after() {
+        let now = Instant::now();
+        let period = Duration::from_millis(100);
+
+        // TAT two periods ahead with a burst of two: the next slot opens in one period
+        let tat = now + period * 2;
+        assert_eq!(gcra_step(tat, now, period, 2, 1), Err(period));
+
+        // Half a period later only half a period is left
+        let later = now + period / 2;
+        assert_eq!(gcra_step(tat, later, period, 2, 1), Err(period / 2));
+
+        // A request costing two periods needs two free slots
+        let tat = now + period;
+        assert_eq!(gcra_step(tat, now, period, 2, 2), Err(period));
+        assert_eq!(gcra_step(tat, now, period, 3, 2), Ok((now + period * 3, 0)));
+    }
+
+    #[test]
+    fn test_reset_and_remaining() {
+        let limiter = Gcra::new(Duration::from_secs(10), 3);
+        assert_eq!(limiter.remaining("test"), 3);
+
+        limiter.check("test");
+        limiter.check("test");
+        assert_eq!(limiter.remaining("test"), 1);
+
+        limiter.reset("test");
+        assert_eq!(limiter.remaining("test"), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Period must be non-zero")]
+    fn test_zero_period() {
+        Gcra::new(Duration::ZERO, 1);
+    }
+}
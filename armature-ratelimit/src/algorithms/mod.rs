@@ -4,14 +4,22 @@
 //!
 //! - **Token Bucket**: Smooth rate limiting with burst capacity
 //! - **Sliding Window Log**: Precise rate limiting with individual request tracking
+//! - **Sliding Window Counter**: Approximate sliding window with O(1) memory per key
 //! - **Fixed Window**: Simple rate limiting with fixed time windows
+//! - **GCRA**: Evenly paced rate limiting with one timestamp per key
 
 mod fixed_window;
+mod gcra;
 mod sliding_window;
+mod sliding_window_counter;
 mod token_bucket;
 
 pub use fixed_window::FixedWindow;
+pub use gcra::Gcra;
+pub(crate) use gcra::{gcra_periods, gcra_step};
 pub use sliding_window::SlidingWindowLog;
+pub(crate) use sliding_window_counter::CounterState;
+pub use sliding_window_counter::SlidingWindowCounter;
 pub use token_bucket::TokenBucket;
 
 use std::time::Duration;
@@ -41,6 +49,20 @@ pub enum Algorithm {
         window: Duration,
     },
 
+    /// Sliding window counter algorithm
+    ///
+    /// Keeps counts for the current and previous fixed windows and weights the
+    /// previous count by its overlap with the sliding window. Close to the
+    /// sliding window log's accuracy with constant storage per key; the
+    /// estimate can be off slightly when requests were unevenly spread over
+    /// the previous window.
+    SlidingWindowCounter {
+        /// Maximum requests allowed in the window
+        max_requests: u64,
+        /// Window duration
+        window: Duration,
+    },
+
     /// Fixed window algorithm
     ///
     /// Divides time into fixed windows and counts requests per window.
@@ -51,6 +73,17 @@ pub enum Algorithm {
         /// Window duration
         window: Duration,
     },
+
+    /// Generic Cell Rate Algorithm
+    ///
+    /// Admits one request per `period` on average, allowing up to `burst`
+    /// back to back. Paces requests evenly and stores one timestamp per key.
+    Gcra {
+        /// Interval between requests at the sustained rate
+        period: Duration,
+        /// Requests that may be admitted back to back
+        burst: u64,
+    },
 }
 
 impl Algorithm {
@@ -83,7 +116,9 @@ impl Algorithm {
         match self {
             Algorithm::TokenBucket { capacity, .. } => *capacity,
             Algorithm::SlidingWindowLog { max_requests, .. } => *max_requests,
+            Algorithm::SlidingWindowCounter { max_requests, .. } => *max_requests,
             Algorithm::FixedWindow { max_requests, .. } => *max_requests,
+            Algorithm::Gcra { burst, .. } => *burst,
         }
     }
 
@@ -101,10 +136,20 @@ impl Algorithm {
                 max_requests,
                 window,
             } => format!("Sliding window: {} requests per {:?}", max_requests, window),
+            Algorithm::SlidingWindowCounter {
+                max_requests,
+                window,
+            } => format!(
+                "Sliding window counter: {} requests per {:?}",
+                max_requests, window
+            ),
             Algorithm::FixedWindow {
                 max_requests,
                 window,
             } => format!("Fixed window: {} requests per {:?}", max_requests, window),
+            Algorithm::Gcra { period, burst } => {
+                format!("GCRA: 1 request per {:?}, burst of {}", period, burst)
+            }
         }
     }
 }
@@ -152,6 +197,14 @@ mod tests {
             .limit(),
             200
         );
+        assert_eq!(
+            Algorithm::Gcra {
+                period: Duration::from_millis(100),
+                burst: 10
+            }
+            .limit(),
+            10
+        );
     }
 
     #[test]
@@ -0,0 +1,320 @@
+//! Sliding Window Counter Algorithm
+//!
+//! Approximates a sliding window with two fixed-window counters, the current
+//! one and the previous one. The previous count is weighted by how much of the
+//! previous window still overlaps the sliding window:
+//!
+//! ```text
+//! estimate = previous * (1 - elapsed / window) + current
+//! ```
+//!
+//! ## Pros
+//!
+//! - O(1) memory per key, unlike the sliding window log
+//! - No doubled bursts at window boundaries, unlike the fixed window
+//!
+//! ## Cons
+//!
+//! - Approximate: it assumes requests in the previous window were evenly
+//!   spread. If they were bunched at its start the estimate over-counts, and
+//!   if bunched at its end it under-counts, by at most the previous window's
+//!   count times the weight. In practice the error is a few percent.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use armature_ratelimit::algorithms::SlidingWindowCounter;
+//! use armature_ratelimit::algorithms::RateLimitAlgorithm;
+//! use std::time::Duration;
+//!
+//! let limiter = SlidingWindowCounter::new(5, Duration::from_secs(60)); // 5 requests per minute
+//!
+//! // First 5 requests succeed
+//! for _ in 0..5 {
+//!     assert!(limiter.check("user1").0);
+//! }
+//!
+//! // 6th request fails
+//! assert!(!limiter.check("user1").0);
+//! ```
+
+use super::RateLimitAlgorithm;
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+
+/// Counters for the current and previous fixed windows
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CounterState {
+    /// Start of the current window
+    window_start: Instant,
+    /// Requests in the previous window
+    previous: u64,
+    /// Requests in the current window
+    current: u64,
+}
+
+impl CounterState {
+    /// Create state whose first window starts at `now`
+    pub(crate) fn new(now: Instant) -> Self {
+        Self {
+            window_start: now,
+            previous: 0,
+            current: 0,
+        }
+    }
+
+    /// Get the start of the current window
+    pub(crate) fn window_start(&self) -> Instant {
+        self.window_start
+    }
+
+    /// Move to the window containing `now`
+    fn advance(&mut self, now: Instant, window: Duration) {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        if elapsed < window {
+            return;
+        }
+
+        let windows = elapsed.as_nanos() / window.as_nanos();
+        self.previous = if windows == 1 { self.current } else { 0 };
+        self.current = 0;
+        self.window_start = u32::try_from(windows)
+            .ok()
+            .and_then(|windows| window.checked_mul(windows))
+            .map_or(now, |skipped| self.window_start + skipped);
+    }
+
+    /// Estimated requests in the sliding window ending at `now`
+    fn estimate(&self, now: Instant, window: Duration) -> f64 {
+        let elapsed = now.saturating_duration_since(self.window_start);
+        let weight = 1.0 - (elapsed.as_secs_f64() / window.as_secs_f64()).min(1.0);
+        self.previous as f64 * weight + self.current as f64
+    }
+
+    /// Record a request costing `cost` slots at `now` if the estimate leaves
+    /// room for it
+    ///
+    /// Returns (allowed, remaining_requests, retry_after); retry_after is the
+    /// estimated wait until a denied request would be admitted.
+    pub(crate) fn check(
+        &mut self,
+        now: Instant,
+        max_requests: u64,
+        window: Duration,
+        cost: u64,
+    ) -> (bool, u64, Duration) {
+        self.advance(now, window);
+
+        let estimate = self.estimate(now, window);
+        if estimate + cost as f64 <= max_requests as f64 {
+            self.current += cost;
+            let remaining = (max_requests as f64 - estimate - cost as f64).floor() as u64;
+            return (true, remaining, Duration::ZERO);
+        }
+
+        (false, 0, self.retry_after(now, max_requests, window, cost))
+    }
+
+    /// Give back `cost` slots recorded in the current window
+    pub(crate) fn refund(&mut self, cost: u64) {
+        self.current = self.current.saturating_sub(cost);
+    }
+
+    /// Time until the estimate drops enough to admit `cost` more slots
+    fn retry_after(
+        &self,
+        now: Instant,
+        max_requests: u64,
+        window: Duration,
+        cost: u64,
+    ) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.window_start)
+            .as_secs_f64();
+        let window = window.as_secs_f64();
+        let room = max_requests as f64 - cost as f64;
+        if room < 0.0 {
+            // Never fits
+            return Duration::MAX;
+        }
+        let current = self.current as f64;
+
+        let wait = if current <= room && self.previous > 0 {
+            // Wait for the previous window's weight to decay
+            window * (1.0 - (room - current) / self.previous as f64) - elapsed
+        } else if current > 0.0 {
+            // The current window is full: wait until it is the previous one
+            // and has decayed in turn
+            (window - elapsed) + window * (1.0 - room / current).max(0.0)
+        } else {
+            window - elapsed
+        };
+
+        Duration::from_secs_f64(wait.max(0.0))
+    }
+}
+
+/// Sliding window counter rate limiter
+pub struct SlidingWindowCounter {
+    /// Maximum requests allowed in the window
+    max_requests: u64,
+    /// Window duration
+    window: Duration,
+    /// Counters per key
+    counters: DashMap<String, CounterState>,
+}
+
+impl SlidingWindowCounter {
+    /// Create a new sliding window counter rate limiter
+    ///
+    /// # Arguments
+    ///
+    /// * `max_requests` - Maximum requests allowed in the window
+    /// * `window` - Window duration
+    ///
+    /// # Panics
+    ///
+    /// Panics if max_requests is 0 or window is zero duration
+    pub fn new(max_requests: u64, window: Duration) -> Self {
+        assert!(max_requests > 0, "Max requests must be greater than 0");
+        assert!(!window.is_zero(), "Window must be non-zero");
+
+        Self {
+            max_requests,
+            window,
+            counters: DashMap::new(),
+        }
+    }
+
+    /// Try to record a request
+    pub fn try_acquire(&self, key: &str) -> (bool, u64) {
+        let now = Instant::now();
+        let mut state = self
+            .counters
+            .entry(key.to_string())
+            .or_insert_with(|| CounterState::new(now));
+
+        let (allowed, remaining, _) = state.check(now, self.max_requests, self.window, 1);
+        (allowed, remaining)
+    }
+
+    /// Get the max requests setting
+    pub fn max_requests(&self) -> u64 {
+        self.max_requests
+    }
+
+    /// Get the window duration
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+impl RateLimitAlgorithm for SlidingWindowCounter {
+    fn check(&self, key: &str) -> (bool, u64) {
+        self.try_acquire(key)
+    }
+
+    fn reset(&self, key: &str) {
+        self.counters.remove(key);
+    }
+
+    fn remaining(&self, key: &str) -> u64 {
+        let Some(state) = self.counters.get(key) else {
+            return self.max_requests;
+        };
+        let now = Instant::now();
+        let mut state = *state;
+        state.advance(now, self.window);
+        (self.max_requests as f64 - state.estimate(now, self.window)).max(0.0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_basic_limit() {
+        let limiter = SlidingWindowCounter::new(5, WINDOW);
+
+        for i in (0..5).rev() {
+            let (allowed, remaining) = limiter.check("test");
+            assert!(allowed);
+            assert_eq!(remaining, i as u64);
+        }
+
+        let (allowed, remaining) = limiter.check("test");
+        assert!(!allowed);
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_window_boundary() {
+        let start = Instant::now();
+        let mut state = CounterState::new(start);
+
+        // Fill the first window
+        for _ in 0..4 {
+            assert!(state.check(start, 4, WINDOW, 1).0);
+        }
+        assert!(!state.check(start, 4, WINDOW, 1).0);
+
+        // A fixed window would allow 4 more right after the boundary; the
+        // previous window still weighs 7/8 of its count here
+        let after_boundary = start + WINDOW + WINDOW / 8;
+        let (allowed, remaining, _) = state.check(after_boundary, 4, WINDOW, 1);
+        assert!(!allowed);
+        assert_eq!(remaining, 0);
+
+        // Halfway through, the previous window counts for 2
+        let halfway = start + WINDOW + WINDOW / 2;
+        assert_eq!(
+            state.check(halfway, 4, WINDOW, 1),
+            (true, 1, Duration::ZERO)
+        );
+        assert!(state.check(halfway, 4, WINDOW, 1).0);
+        assert!(!state.check(halfway, 4, WINDOW, 1).0);
+
+        // Two windows later everything has expired
+        let later = start + WINDOW * 3;
+        assert_eq!(state.check(later, 4, WINDOW, 1), (true, 3, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let start = Instant::now();
+        let mut state = CounterState::new(start);
+        for _ in 0..4 {
+            state.check(start, 4, WINDOW, 1);
+        }
+
+        // Full current window: wait for the boundary, then for the count to
+        // decay to 3 (a quarter of the next window)
+        let (_, _, retry_after) = state.check(start, 4, WINDOW, 1);
+        assert_eq!(retry_after.as_secs(), 75);
+
+        // A quarter into the next window the previous count has decayed to 3
+        let at = start + WINDOW + WINDOW / 4;
+        assert!(state.check(at, 4, WINDOW, 1).0);
+    }
+
+    #[test]
+    fn test_reset_and_remaining() {
+        let limiter = SlidingWindowCounter::new(3, WINDOW);
+        assert_eq!(limiter.remaining("test"), 3);
+
+        limiter.check("test");
+        assert_eq!(limiter.remaining("test"), 2);
+
+        limiter.reset("test");
+        assert_eq!(limiter.remaining("test"), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Window must be non-zero")]
+    fn test_zero_window() {
+        SlidingWindowCounter::new(10, Duration::ZERO);
+    }
+}
@@ -1,13 +1,25 @@
 //! Rate limiter configuration and builder
 
 use crate::RateLimiter;
+use crate::access_list::{AccessLists, KeyList};
 use crate::algorithms::Algorithm;
 use crate::error::{RateLimitError, RateLimitResult};
+use crate::middleware::OnRejected;
 use crate::stores::{MemoryStore, RateLimitStore, StoreType};
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::debug;
 
+/// What to do with a request when the store fails or times out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailMode {
+    /// Allow the request, favoring availability
+    #[default]
+    FailOpen,
+    /// Reject the request, favoring protection of the backend
+    FailClosed,
+}
+
 /// Configuration for the rate limiter
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
@@ -19,15 +31,32 @@ pub struct RateLimitConfig {
     pub key_prefix: String,
     /// Include rate limit headers in responses
     pub include_headers: bool,
-    /// Skip rate limiting for certain conditions
+    /// Also include the draft combined `RateLimit` header
+    pub rate_limit_header: bool,
+    /// Builds the response for rate limited requests
+    pub on_rejected: OnRejected,
+    /// How requests are decided when the store fails or times out
+    pub fail_mode: FailMode,
+    /// Skip rate limiting on store errors
+    ///
+    /// Setting this to false rejects requests on store errors, like
+    /// [`FailMode::FailClosed`]; see [`store_error_mode`](Self::store_error_mode).
+    #[deprecated(note = "use `fail_mode` instead")]
     pub skip_on_error: bool,
+    /// Maximum time to wait for the store on each check
+    pub store_timeout: Duration,
     /// Custom error message when rate limited
     pub error_message: Option<String>,
     /// Bypass keys (these keys will never be rate limited)
     pub bypass_keys: Vec<String>,
+    /// Allowlist and denylist consulted before every check
+    pub access_lists: Arc<AccessLists>,
+    /// Retry-After reported to denylisted keys
+    pub denylist_retry_after: Duration,
 }
 
 impl Default for RateLimitConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             algorithm: Algorithm::TokenBucket {
@@ -37,9 +66,15 @@ impl Default for RateLimitConfig {
             store_type: StoreType::Memory,
             key_prefix: "ratelimit".to_string(),
             include_headers: true,
+            rate_limit_header: false,
+            on_rejected: OnRejected::default(),
+            fail_mode: FailMode::FailOpen,
             skip_on_error: true,
+            store_timeout: Duration::from_millis(250),
             error_message: None,
             bypass_keys: Vec::new(),
+            access_lists: Arc::new(AccessLists::new()),
+            denylist_retry_after: Duration::from_secs(3600),
         }
     }
 }
@@ -50,9 +85,74 @@ impl RateLimitConfig {
         RateLimiterBuilder::new()
     }
 
+    /// Set whether to include `X-RateLimit-*` headers in responses
+    pub fn headers(mut self, include: bool) -> Self {
+        self.include_headers = include;
+        self
+    }
+
+    /// Set whether to also include the draft combined `RateLimit` header
+    pub fn rate_limit_header(mut self, include: bool) -> Self {
+        self.rate_limit_header = include;
+        self
+    }
+
+    /// Set how the response for rate limited requests is built
+    pub fn on_rejected(mut self, on_rejected: impl Into<OnRejected>) -> Self {
+        self.on_rejected = on_rejected.into();
+        self
+    }
+
+    /// Set how requests are decided when the store fails or times out
+    #[allow(deprecated)]
+    pub fn on_store_error(mut self, mode: FailMode) -> Self {
+        self.fail_mode = mode;
+        self.skip_on_error = mode == FailMode::FailOpen;
+        self
+    }
+
+    /// Get how requests are decided when the store fails or times out
+    ///
+    /// Requests fail closed if either `fail_mode` or the deprecated
+    /// `skip_on_error` says so.
+    #[allow(deprecated)]
+    pub fn store_error_mode(&self) -> FailMode {
+        if self.skip_on_error {
+            self.fail_mode
+        } else {
+            FailMode::FailClosed
+        }
+    }
+
+    /// Set the maximum time to wait for the store on each check
+    pub fn store_timeout(mut self, timeout: Duration) -> Self {
+        self.store_timeout = timeout;
+        self
+    }
+
+    /// Set the keys that skip rate limiting
+    ///
+    /// Entries are exact keys or, for IP keys, CIDR ranges like `10.0.0.0/8`.
+    /// Replaces the lists with new ones, so other configs sharing them are
+    /// not affected; use [`AccessLists`] to update them at runtime.
+    pub fn allowlist(mut self, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let denylist = KeyList::clone(&self.access_lists.denylist());
+        self.access_lists = Arc::new(AccessLists::with_lists(KeyList::new(keys), denylist));
+        self
+    }
+
+    /// Set the keys that are always rejected
+    ///
+    /// Entries are exact keys or, for IP keys, CIDR ranges like `10.0.0.0/8`.
+    pub fn denylist(mut self, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let allowlist = KeyList::clone(&self.access_lists.allowlist());
+        self.access_lists = Arc::new(AccessLists::with_lists(allowlist, KeyList::new(keys)));
+        self
+    }
+
     /// Check if a key should bypass rate limiting
     pub fn should_bypass(&self, key: &str) -> bool {
-        self.bypass_keys.iter().any(|k| k == key)
+        self.bypass_keys.iter().any(|k| k == key) || self.access_lists.is_allowlisted(key)
     }
 }
 
@@ -62,9 +162,16 @@ pub struct RateLimiterBuilder {
     store_type: StoreType,
     key_prefix: String,
     include_headers: bool,
-    skip_on_error: bool,
+    rate_limit_header: bool,
+    on_rejected: OnRejected,
+    fail_mode: FailMode,
+    store_timeout: Duration,
     error_message: Option<String>,
     bypass_keys: Vec<String>,
+    allowlist: KeyList,
+    denylist: KeyList,
+    denylist_retry_after: Duration,
+    sweep: Option<(Duration, Duration)>,
     #[cfg(feature = "redis")]
     redis_url: Option<String>,
 }
@@ -77,9 +184,16 @@ impl RateLimiterBuilder {
             store_type: StoreType::Memory,
             key_prefix: "ratelimit".to_string(),
             include_headers: true,
-            skip_on_error: true,
+            rate_limit_header: false,
+            on_rejected: OnRejected::default(),
+            fail_mode: FailMode::FailOpen,
+            store_timeout: Duration::from_millis(250),
             error_message: None,
             bypass_keys: Vec::new(),
+            allowlist: KeyList::default(),
+            denylist: KeyList::default(),
+            denylist_retry_after: Duration::from_secs(3600),
+            sweep: None,
             #[cfg(feature = "redis")]
             redis_url: None,
         }
@@ -109,6 +223,15 @@ impl RateLimiterBuilder {
         self
     }
 
+    /// Use sliding window counter algorithm
+    pub fn sliding_window_counter(mut self, max_requests: u64, window: Duration) -> Self {
+        self.algorithm = Some(Algorithm::SlidingWindowCounter {
+            max_requests,
+            window,
+        });
+        self
+    }
+
     /// Use fixed window algorithm
     pub fn fixed_window(mut self, max_requests: u64, window: Duration) -> Self {
         self.algorithm = Some(Algorithm::FixedWindow {
@@ -118,12 +241,27 @@ impl RateLimiterBuilder {
         self
     }
 
+    /// Use GCRA algorithm
+    pub fn gcra(mut self, period: Duration, burst: u64) -> Self {
+        self.algorithm = Some(Algorithm::Gcra { period, burst });
+        self
+    }
+
     /// Use in-memory store (default)
     pub fn memory_store(mut self) -> Self {
         self.store_type = StoreType::Memory;
         self
     }
 
+    /// Sweep idle keys from the in-memory store every `interval`
+    ///
+    /// Keys are removed once their limit has fully reset, or after `max_age`
+    /// without requests. Ignored for the Redis store, which expires keys itself.
+    pub fn memory_sweeper(mut self, interval: Duration, max_age: Duration) -> Self {
+        self.sweep = Some((interval, max_age));
+        self
+    }
+
     /// Use Redis store for distributed rate limiting
     #[cfg(feature = "redis")]
     pub fn redis_store(mut self, url: &str) -> Self {
@@ -144,9 +282,52 @@ impl RateLimiterBuilder {
         self
     }
 
+    /// Include `X-RateLimit-*` headers in responses
+    ///
+    /// Same as [`include_headers`](Self::include_headers).
+    pub fn headers(self, include: bool) -> Self {
+        self.include_headers(include)
+    }
+
+    /// Also include the draft combined `RateLimit` header
+    pub fn rate_limit_header(mut self, include: bool) -> Self {
+        self.rate_limit_header = include;
+        self
+    }
+
+    /// Set how the response for rate limited requests is built
+    ///
+    /// Defaults to a JSON body; see [`OnRejected`].
+    pub fn on_rejected(mut self, on_rejected: impl Into<OnRejected>) -> Self {
+        self.on_rejected = on_rejected.into();
+        self
+    }
+
+    /// Set how requests are decided when the store fails or times out
+    ///
+    /// Defaults to [`FailMode::FailOpen`].
+    pub fn on_store_error(mut self, mode: FailMode) -> Self {
+        self.fail_mode = mode;
+        self
+    }
+
     /// Skip rate limiting on store errors
-    pub fn skip_on_error(mut self, skip: bool) -> Self {
-        self.skip_on_error = skip;
+    ///
+    /// Shorthand for `on_store_error(FailMode::FailOpen)` when true and
+    /// `on_store_error(FailMode::FailClosed)` when false.
+    pub fn skip_on_error(self, skip: bool) -> Self {
+        self.on_store_error(if skip {
+            FailMode::FailOpen
+        } else {
+            FailMode::FailClosed
+        })
+    }
+
+    /// Set the maximum time to wait for the store on each check (default 250ms)
+    ///
+    /// A check that takes longer is treated as a store error.
+    pub fn store_timeout(mut self, timeout: Duration) -> Self {
+        self.store_timeout = timeout;
         self
     }
 
@@ -168,6 +349,28 @@ impl RateLimiterBuilder {
         self
     }
 
+    /// Set the keys that skip rate limiting
+    ///
+    /// Entries are exact keys or, for IP keys, CIDR ranges like `10.0.0.0/8`.
+    pub fn allowlist(mut self, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allowlist = KeyList::new(keys);
+        self
+    }
+
+    /// Set the keys that are always rejected
+    ///
+    /// Entries are exact keys or, for IP keys, CIDR ranges like `10.0.0.0/8`.
+    pub fn denylist(mut self, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.denylist = KeyList::new(keys);
+        self
+    }
+
+    /// Set the Retry-After reported to denylisted keys (default 1 hour)
+    pub fn denylist_retry_after(mut self, retry_after: Duration) -> Self {
+        self.denylist_retry_after = retry_after;
+        self
+    }
+
     /// Build the rate limiter
     pub async fn build(self) -> RateLimitResult<RateLimiter> {
         let algorithm = self
@@ -180,18 +383,32 @@ impl RateLimiterBuilder {
             "Building rate limiter"
         );
 
+        #[allow(deprecated)]
         let config = RateLimitConfig {
             algorithm: algorithm.clone(),
             store_type: self.store_type.clone(),
             key_prefix: self.key_prefix.clone(),
             include_headers: self.include_headers,
-            skip_on_error: self.skip_on_error,
+            rate_limit_header: self.rate_limit_header,
+            on_rejected: self.on_rejected,
+            fail_mode: self.fail_mode,
+            skip_on_error: self.fail_mode == FailMode::FailOpen,
+            store_timeout: self.store_timeout,
             error_message: self.error_message,
             bypass_keys: self.bypass_keys,
+            access_lists: Arc::new(AccessLists::with_lists(self.allowlist, self.denylist)),
+            denylist_retry_after: self.denylist_retry_after,
         };
 
         let store: Arc<dyn RateLimitStore> = match self.store_type {
-            StoreType::Memory => Arc::new(MemoryStore::new()),
+            StoreType::Memory => match self.sweep {
+                Some((interval, max_age)) => {
+                    let store = Arc::new(MemoryStore::new().with_max_age(max_age));
+                    store.spawn_sweeper(interval);
+                    store
+                }
+                None => Arc::new(MemoryStore::new()),
+            },
             #[cfg(feature = "redis")]
             StoreType::Redis => {
                 let url = self.redis_url.ok_or_else(|| {
@@ -222,6 +439,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_default_config() {
         let config = RateLimitConfig::default();
         assert!(matches!(config.algorithm, Algorithm::TokenBucket { .. }));
@@ -230,6 +448,23 @@ mod tests {
         assert!(config.skip_on_error);
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_skip_on_error_maps_to_fail_mode() {
+        let config = RateLimitConfig::default();
+        assert_eq!(config.store_error_mode(), FailMode::FailOpen);
+
+        let config = RateLimitConfig {
+            skip_on_error: false,
+            ..Default::default()
+        };
+        assert_eq!(config.store_error_mode(), FailMode::FailClosed);
+
+        let config = RateLimitConfig::default().on_store_error(FailMode::FailClosed);
+        assert!(!config.skip_on_error);
+        assert_eq!(config.store_error_mode(), FailMode::FailClosed);
+    }
+
     #[test]
     fn test_bypass_keys() {
         let config = RateLimitConfig {
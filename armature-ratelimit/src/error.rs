@@ -26,6 +26,10 @@ pub enum RateLimitError {
     #[error("Rate limit store error: {0}")]
     StoreError(String),
 
+    /// Store did not answer in time
+    #[error("Rate limit store timed out after {0:?}")]
+    StoreTimeout(Duration),
+
     /// Configuration error
     #[error("Rate limit configuration error: {0}")]
     ConfigError(String),
@@ -150,6 +154,23 @@ impl RateLimitHeaders {
 
         headers
     }
+
+    /// Get the value of the combined `RateLimit` header
+    ///
+    /// Follows draft-ietf-httpapi-ratelimit-headers-07, e.g.
+    /// `limit=100, remaining=50, reset=30`, where `reset` is in seconds from now.
+    pub fn rate_limit_header(&self) -> String {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        format!(
+            "limit={}, remaining={}, reset={}",
+            self.limit,
+            self.remaining,
+            self.reset.saturating_sub(now)
+        )
+    }
 }
 
 #[cfg(test)]
@@ -196,4 +217,25 @@ mod tests {
         );
         assert!(pairs.iter().any(|(k, v)| *k == "Retry-After" && v == "30"));
     }
+
+    #[test]
+    fn test_rate_limit_header() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let headers = RateLimitHeaders::allowed(100, 50, now + 30);
+        let value = headers.rate_limit_header();
+        assert!(value.starts_with("limit=100, remaining=50, reset="));
+        let reset: u64 = value.rsplit('=').next().unwrap().parse().unwrap();
+        assert!((29..=30).contains(&reset));
+
+        // A reset in the past is reported as now
+        let headers = RateLimitHeaders::allowed(100, 50, now - 5);
+        assert_eq!(
+            headers.rate_limit_header(),
+            "limit=100, remaining=50, reset=0"
+        );
+    }
 }
@@ -4,10 +4,14 @@
 //! from incoming requests.
 
 use std::net::IpAddr;
+use std::sync::Arc;
 
 /// Type alias for key extractor function
 pub type KeyExtractorFn = Box<dyn Fn(&RequestInfo) -> Option<String> + Send + Sync>;
 
+/// Type alias for cost extractor function
+pub type CostExtractorFn = Arc<dyn Fn(&RequestInfo) -> u64 + Send + Sync>;
+
 /// Information about an incoming request used for key extraction
 #[derive(Debug, Clone)]
 pub struct RequestInfo {
@@ -94,6 +98,10 @@ pub enum KeyExtractor {
     IpAndPath,
     /// Combine user ID and path for per-endpoint limiting
     UserIdAndPath,
+    /// Request path, usually combined with other extractors
+    Path,
+    /// Combine several extractors into one key
+    Composite(CompositeKeyExtractor),
     /// Custom extractor function
     Custom(String), // Store description for Debug
 }
@@ -131,6 +139,16 @@ impl KeyExtractor {
         Self::UserIdAndPath
     }
 
+    /// Create a path extractor
+    pub fn path() -> Self {
+        Self::Path
+    }
+
+    /// Create a composite extractor from several extractors
+    pub fn composite(extractors: impl IntoIterator<Item = KeyExtractor>) -> Self {
+        Self::Composite(CompositeKeyExtractor::new(extractors))
+    }
+
     /// Extract the key from request info
     pub fn extract(&self, info: &RequestInfo) -> Option<String> {
         match self {
@@ -143,6 +161,8 @@ impl KeyExtractor {
                 .user_id
                 .as_ref()
                 .map(|uid| format!("{}:{}", uid, info.path)),
+            Self::Path => Some(info.path.clone()),
+            Self::Composite(composite) => composite.extract(info),
             Self::Custom(_) => None, // Custom extractors use the function directly
         }
     }
@@ -156,11 +176,71 @@ impl KeyExtractor {
             Self::Header { .. } => "Custom header",
             Self::IpAndPath => "IP + Path",
             Self::UserIdAndPath => "User ID + Path",
+            Self::Path => "Path",
+            Self::Composite(_) => "Composite",
             Self::Custom(desc) => desc,
         }
     }
 }
 
+/// Combines several extractors into one delimited key
+///
+/// Every extractor must produce a value, so limiting per (user, endpoint)
+/// skips anonymous requests rather than lumping them together.
+///
+/// ```
+/// use armature_ratelimit::extractor::{CompositeKeyExtractor, KeyExtractor, RequestInfo};
+///
+/// let extractor = CompositeKeyExtractor::new([KeyExtractor::UserId, KeyExtractor::Path]);
+///
+/// let info = RequestInfo::new("/api/orders", "GET").with_user_id("u1");
+/// assert_eq!(extractor.extract(&info).as_deref(), Some("u1|/api/orders"));
+/// assert_eq!(extractor.extract(&RequestInfo::new("/api/orders", "GET")), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompositeKeyExtractor {
+    extractors: Vec<KeyExtractor>,
+    delimiter: String,
+}
+
+impl CompositeKeyExtractor {
+    /// Create a composite extractor joining keys with `|`
+    pub fn new(extractors: impl IntoIterator<Item = KeyExtractor>) -> Self {
+        Self {
+            extractors: extractors.into_iter().collect(),
+            delimiter: "|".to_string(),
+        }
+    }
+
+    /// Set the delimiter between keys
+    pub fn delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = delimiter.into();
+        self
+    }
+
+    /// Add an extractor
+    pub fn with(mut self, extractor: KeyExtractor) -> Self {
+        self.extractors.push(extractor);
+        self
+    }
+
+    /// Extract the combined key, or None if any part is missing
+    pub fn extract(&self, info: &RequestInfo) -> Option<String> {
+        let parts = self
+            .extractors
+            .iter()
+            .map(|extractor| extractor.extract(info))
+            .collect::<Option<Vec<_>>>()?;
+        Some(parts.join(&self.delimiter))
+    }
+}
+
+impl From<CompositeKeyExtractor> for KeyExtractor {
+    fn from(composite: CompositeKeyExtractor) -> Self {
+        Self::Composite(composite)
+    }
+}
+
 /// Builder for creating complex key extractors
 pub struct KeyExtractorBuilder {
     extractors: Vec<KeyExtractor>,
@@ -229,6 +309,109 @@ impl Default for KeyExtractorBuilder {
     }
 }
 
+/// Request cost for cost-weighted rate limiting
+///
+/// Expensive routes can consume several tokens or window slots per request.
+/// A custom function takes precedence over route costs; otherwise the most
+/// specific matching route applies, falling back to the default cost.
+///
+/// ```
+/// use armature_ratelimit::extractor::{CostExtractor, RequestInfo};
+///
+/// let costs = CostExtractor::new()
+///     .route("/export", 10)
+///     .route("/search/*", 3);
+///
+/// assert_eq!(costs.cost(&RequestInfo::new("/export", "POST")), 10);
+/// assert_eq!(costs.cost(&RequestInfo::new("/search/users", "GET")), 3);
+/// assert_eq!(costs.cost(&RequestInfo::new("/users", "GET")), 1);
+/// ```
+#[derive(Clone)]
+pub struct CostExtractor {
+    default_cost: u64,
+    routes: Vec<(String, u64)>,
+    custom: Option<CostExtractorFn>,
+}
+
+impl CostExtractor {
+    /// Create a cost extractor where every request costs 1
+    pub fn new() -> Self {
+        Self {
+            default_cost: 1,
+            routes: Vec::new(),
+            custom: None,
+        }
+    }
+
+    /// Create a cost extractor where every request costs the same
+    pub fn fixed(cost: u64) -> Self {
+        Self::new().default_cost(cost)
+    }
+
+    /// Set the cost of requests that match no route
+    pub fn default_cost(mut self, cost: u64) -> Self {
+        self.default_cost = cost;
+        self
+    }
+
+    /// Set the cost of a route
+    ///
+    /// The pattern is an exact path, or a prefix ending in `/*` that matches
+    /// the prefix and everything below it.
+    pub fn route(mut self, pattern: impl Into<String>, cost: u64) -> Self {
+        self.routes.push((pattern.into(), cost));
+        self
+    }
+
+    /// Compute the cost with a custom function
+    pub fn custom<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RequestInfo) -> u64 + Send + Sync + 'static,
+    {
+        self.custom = Some(Arc::new(f));
+        self
+    }
+
+    /// Get the cost of a request
+    pub fn cost(&self, info: &RequestInfo) -> u64 {
+        if let Some(custom) = &self.custom {
+            return custom(info);
+        }
+
+        self.routes
+            .iter()
+            .filter(|(pattern, _)| route_matches(pattern, &info.path))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map_or(self.default_cost, |(_, cost)| *cost)
+    }
+}
+
+fn route_matches(pattern: &str, path: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(prefix) => match path.strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        },
+        None => pattern == path,
+    }
+}
+
+impl Default for CostExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CostExtractor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CostExtractor")
+            .field("default_cost", &self.default_cost)
+            .field("routes", &self.routes)
+            .field("custom", &self.custom.is_some())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,4 +534,48 @@ mod tests {
         assert_eq!(info.user_id, Some("u123".to_string()));
         assert_eq!(info.get_header("content-type"), Some("application/json"));
     }
+
+    #[test]
+    fn test_composite_extractor() {
+        let extractor =
+            KeyExtractor::composite([KeyExtractor::Ip, KeyExtractor::UserId, KeyExtractor::Path]);
+        assert_eq!(
+            extractor.extract(&sample_request()),
+            Some("192.168.1.1|user_123|/api/users".to_string())
+        );
+
+        let extractor = CompositeKeyExtractor::new([KeyExtractor::UserId])
+            .with(KeyExtractor::Path)
+            .delimiter("::");
+        assert_eq!(
+            extractor.extract(&sample_request()),
+            Some("user_123::/api/users".to_string())
+        );
+
+        // Missing parts yield no key
+        let anonymous = RequestInfo::new("/api/users", "GET");
+        assert_eq!(extractor.extract(&anonymous), None);
+    }
+
+    #[test]
+    fn test_cost_extractor_routes() {
+        let costs = CostExtractor::fixed(2)
+            .route("/api/*", 3)
+            .route("/api/export", 10);
+
+        assert_eq!(costs.cost(&RequestInfo::new("/api/export", "POST")), 10);
+        assert_eq!(costs.cost(&RequestInfo::new("/api/users", "GET")), 3);
+        assert_eq!(costs.cost(&RequestInfo::new("/api", "GET")), 3);
+        assert_eq!(costs.cost(&RequestInfo::new("/apix", "GET")), 2);
+    }
+
+    #[test]
+    fn test_cost_extractor_custom() {
+        let costs = CostExtractor::new()
+            .route("/upload", 10)
+            .custom(|info| if info.method == "GET" { 1 } else { 5 });
+
+        assert_eq!(costs.cost(&RequestInfo::new("/upload", "GET")), 1);
+        assert_eq!(costs.cost(&RequestInfo::new("/upload", "PUT")), 5);
+    }
 }
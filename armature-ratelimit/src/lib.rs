@@ -5,7 +5,8 @@
 //!
 //! ## Features
 //!
-//! - **Multiple Algorithms**: Token bucket, sliding window log, and fixed window
+//! - **Multiple Algorithms**: Token bucket, sliding window log and counter, fixed
+//!   window, and GCRA
 //! - **Storage Backends**: In-memory (DashMap) and Redis for distributed deployments
 //! - **Flexible Key Extraction**: By IP, user ID, API key, or custom function
 //! - **Standard Headers**: `X-RateLimit-Limit`, `X-RateLimit-Remaining`, `X-RateLimit-Reset`
@@ -68,6 +69,23 @@
 //! };
 //! ```
 //!
+//! ### Sliding Window Counter
+//!
+//! Approximates the sliding window log using only the counts of the current
+//! and previous fixed windows, so memory per key is constant. Assumes the
+//! previous window's requests were evenly spread, so it may over- or
+//! under-count slightly when they were bunched together.
+//!
+//! ```rust
+//! use armature_ratelimit::Algorithm;
+//! use std::time::Duration;
+//!
+//! let algo = Algorithm::SlidingWindowCounter {
+//!     max_requests: 100,
+//!     window: Duration::from_secs(60),
+//! };
+//! ```
+//!
 //! ### Fixed Window
 //!
 //! Simple rate limiting with fixed time windows.
@@ -82,19 +100,41 @@
 //!     window: Duration::from_secs(60),
 //! };
 //! ```
+//!
+//! ### GCRA
+//!
+//! Generic Cell Rate Algorithm. Paces requests evenly at one per `period`
+//! while allowing short bursts, and reports the exact time until the next
+//! request would be admitted. Stores one timestamp per key.
+//!
+//! ```rust
+//! use armature_ratelimit::Algorithm;
+//! use std::time::Duration;
+//!
+//! let algo = Algorithm::Gcra {
+//!     period: Duration::from_millis(100), // 10 requests per second
+//!     burst: 5,                           // At most 5 back to back
+//! };
+//! ```
 
+pub mod access_list;
 pub mod algorithms;
 pub mod config;
 pub mod error;
 pub mod extractor;
 pub mod middleware;
+pub mod rules;
 pub mod stores;
 
+pub use access_list::{AccessLists, Cidr, KeyList};
 pub use algorithms::{Algorithm, RateLimitAlgorithm};
-pub use config::{RateLimitConfig, RateLimiterBuilder};
+pub use config::{FailMode, RateLimitConfig, RateLimiterBuilder};
 pub use error::{RateLimitError, RateLimitResult};
-pub use extractor::{KeyExtractor, KeyExtractorFn};
-pub use middleware::RateLimitMiddleware;
+pub use extractor::{
+    CompositeKeyExtractor, CostExtractor, CostExtractorFn, KeyExtractor, KeyExtractorFn,
+};
+pub use middleware::{OnRejected, RateLimitMiddleware, Rejection};
+pub use rules::{RouteRule, RouteRuleSet, RuleLimit};
 pub use stores::{MemoryStore, RateLimitStore, StoreType};
 
 #[cfg(feature = "redis")]
@@ -175,21 +215,91 @@ impl RateLimiter {
 
     /// Check if a request with the given key is allowed
     pub async fn check(&self, key: &str) -> RateLimitResult<RateLimitCheckResult> {
-        trace!(key = %key, "Checking rate limit");
+        self.check_n(key, 1).await
+    }
+
+    /// Check if a request costing `cost` tokens or slots is allowed
+    ///
+    /// An admitted request consumes `cost` units at once; a denied one
+    /// consumes nothing. A cost above the limit is always denied, with no
+    /// `retry_after` since waiting would not help.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store fails or does not answer within the
+    /// configured store timeout.
+    pub async fn check_n(&self, key: &str, cost: u64) -> RateLimitResult<RateLimitCheckResult> {
+        let timeout = self.config.store_timeout;
+        tokio::time::timeout(timeout, self.check_store(key, cost))
+            .await
+            .map_err(|_| RateLimitError::StoreTimeout(timeout))?
+    }
+
+    /// Give back `cost` units consumed by an allowed [`check_n`](Self::check_n)
+    ///
+    /// Use this when a request this limiter admitted is rejected anyway, such
+    /// as by another limit, so it does not count against this one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store fails or does not answer within the
+    /// configured store timeout.
+    pub async fn refund_n(&self, key: &str, cost: u64) -> RateLimitResult<()> {
+        let timeout = self.config.store_timeout;
+        tokio::time::timeout(timeout, self.store.refund(key, &self.algorithm, cost))
+            .await
+            .map_err(|_| RateLimitError::StoreTimeout(timeout))?
+    }
+
+    async fn check_store(&self, key: &str, cost: u64) -> RateLimitResult<RateLimitCheckResult> {
+        trace!(key = %key, cost = cost, "Checking rate limit");
+
+        let limit = self.algorithm.limit();
+        if cost > limit {
+            warn!(key = %key, cost = cost, limit = limit, "Request cost exceeds the limit");
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            return Ok(RateLimitCheckResult {
+                allowed: false,
+                remaining: 0,
+                limit,
+                reset_at: now,
+                retry_after: None,
+            });
+        }
 
         match &self.algorithm {
             Algorithm::TokenBucket {
                 capacity,
                 refill_rate,
-            } => self.check_token_bucket(key, *capacity, *refill_rate).await,
+            } => {
+                self.check_token_bucket(key, *capacity, *refill_rate, cost)
+                    .await
+            }
             Algorithm::SlidingWindowLog {
                 max_requests,
                 window,
-            } => self.check_sliding_window(key, *max_requests, *window).await,
+            } => {
+                self.check_sliding_window(key, *max_requests, *window, cost)
+                    .await
+            }
+            Algorithm::SlidingWindowCounter {
+                max_requests,
+                window,
+            } => {
+                self.check_sliding_window_counter(key, *max_requests, *window, cost)
+                    .await
+            }
             Algorithm::FixedWindow {
                 max_requests,
                 window,
-            } => self.check_fixed_window(key, *max_requests, *window).await,
+            } => {
+                self.check_fixed_window(key, *max_requests, *window, cost)
+                    .await
+            }
+            Algorithm::Gcra { period, burst } => self.check_gcra(key, *period, *burst, cost).await,
         }
     }
 
@@ -199,29 +309,21 @@ impl RateLimiter {
         key: &str,
         capacity: u64,
         refill_rate: f64,
+        cost: u64,
     ) -> RateLimitResult<RateLimitCheckResult> {
-        let result = self
+        let (allowed, remaining, ttl) = self
             .store
-            .token_bucket_check(key, capacity, refill_rate)
+            .token_bucket_check(key, capacity, refill_rate, cost)
             .await?;
 
-        let reset_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + (capacity as f64 / refill_rate) as u64;
+        let reset_at = unix_time_after(ttl);
 
-        if result.0 {
-            debug!(key = %key, remaining = result.1, "Token bucket: request allowed");
-            Ok(RateLimitCheckResult::allowed(result.1, capacity, reset_at))
+        if allowed {
+            debug!(key = %key, remaining = remaining, "Token bucket: request allowed");
+            Ok(RateLimitCheckResult::allowed(remaining, capacity, reset_at))
         } else {
-            let retry_after = Duration::from_secs_f64(1.0 / refill_rate);
-            warn!(key = %key, retry_after = ?retry_after, "Token bucket: request denied");
-            Ok(RateLimitCheckResult::denied(
-                capacity,
-                reset_at,
-                retry_after,
-            ))
+            warn!(key = %key, retry_after = ?ttl, "Token bucket: request denied");
+            Ok(RateLimitCheckResult::denied(capacity, reset_at, ttl))
         }
     }
 
@@ -231,10 +333,39 @@ impl RateLimiter {
         key: &str,
         max_requests: u64,
         window: Duration,
+        cost: u64,
     ) -> RateLimitResult<RateLimitCheckResult> {
-        let result = self
+        let (allowed, remaining, ttl) = self
             .store
-            .sliding_window_check(key, max_requests, window)
+            .sliding_window_check(key, max_requests, window, cost)
+            .await?;
+
+        let reset_at = unix_time_after(ttl);
+
+        if allowed {
+            debug!(key = %key, remaining = remaining, "Sliding window: request allowed");
+            Ok(RateLimitCheckResult::allowed(
+                remaining,
+                max_requests,
+                reset_at,
+            ))
+        } else {
+            warn!(key = %key, retry_after = ?ttl, "Sliding window: request denied");
+            Ok(RateLimitCheckResult::denied(max_requests, reset_at, ttl))
+        }
+    }
+
+    /// Check using sliding window counter algorithm
+    async fn check_sliding_window_counter(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window: Duration,
+        cost: u64,
+    ) -> RateLimitResult<RateLimitCheckResult> {
+        let (allowed, remaining, retry_after) = self
+            .store
+            .sliding_window_counter_check(key, max_requests, window, cost)
             .await?;
 
         let reset_at = std::time::SystemTime::now()
@@ -243,16 +374,15 @@ impl RateLimiter {
             .as_secs()
             + window.as_secs();
 
-        if result.0 {
-            debug!(key = %key, remaining = result.1, "Sliding window: request allowed");
+        if allowed {
+            debug!(key = %key, remaining = remaining, "Sliding window counter: request allowed");
             Ok(RateLimitCheckResult::allowed(
-                result.1,
+                remaining,
                 max_requests,
                 reset_at,
             ))
         } else {
-            let retry_after = Duration::from_secs(1);
-            warn!(key = %key, retry_after = ?retry_after, "Sliding window: request denied");
+            warn!(key = %key, retry_after = ?retry_after, "Sliding window counter: request denied");
             Ok(RateLimitCheckResult::denied(
                 max_requests,
                 reset_at,
@@ -267,34 +397,49 @@ impl RateLimiter {
         key: &str,
         max_requests: u64,
         window: Duration,
+        cost: u64,
     ) -> RateLimitResult<RateLimitCheckResult> {
-        let result = self
+        let (allowed, remaining, ttl) = self
             .store
-            .fixed_window_check(key, max_requests, window)
+            .fixed_window_check(key, max_requests, window, cost)
             .await?;
 
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let window_secs = window.as_secs();
-        let reset_at = ((now / window_secs) + 1) * window_secs;
+        let reset_at = unix_time_after(ttl);
 
-        if result.0 {
-            debug!(key = %key, remaining = result.1, "Fixed window: request allowed");
+        if allowed {
+            debug!(key = %key, remaining = remaining, "Fixed window: request allowed");
             Ok(RateLimitCheckResult::allowed(
-                result.1,
+                remaining,
                 max_requests,
                 reset_at,
             ))
         } else {
-            let retry_after = Duration::from_secs(reset_at - now);
-            warn!(key = %key, retry_after = ?retry_after, "Fixed window: request denied");
-            Ok(RateLimitCheckResult::denied(
-                max_requests,
-                reset_at,
-                retry_after,
-            ))
+            warn!(key = %key, retry_after = ?ttl, "Fixed window: request denied");
+            Ok(RateLimitCheckResult::denied(max_requests, reset_at, ttl))
+        }
+    }
+
+    /// Check using GCRA algorithm
+    async fn check_gcra(
+        &self,
+        key: &str,
+        period: Duration,
+        burst: u64,
+        cost: u64,
+    ) -> RateLimitResult<RateLimitCheckResult> {
+        let (allowed, remaining, retry_after) =
+            self.store.gcra_check(key, period, burst, cost).await?;
+
+        // The burst is fully restored once every used slot's period has passed
+        let restored_in = algorithms::gcra_periods(period, burst - remaining);
+        let reset_at = unix_time_after(restored_in);
+
+        if allowed {
+            debug!(key = %key, remaining = remaining, "GCRA: request allowed");
+            Ok(RateLimitCheckResult::allowed(remaining, burst, reset_at))
+        } else {
+            warn!(key = %key, retry_after = ?retry_after, "GCRA: request denied");
+            Ok(RateLimitCheckResult::denied(burst, reset_at, retry_after))
         }
     }
 
@@ -324,6 +469,16 @@ impl std::fmt::Debug for RateLimiter {
     }
 }
 
+/// Unix timestamp `after` from now, in seconds rounded up
+fn unix_time_after(after: Duration) -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        .saturating_add(after.as_secs())
+        .saturating_add(u64::from(after.subsec_nanos() > 0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,6 +527,89 @@ mod tests {
         assert!(!result.allowed, "4th request should be denied");
     }
 
+    #[tokio::test]
+    async fn test_gcra_burst_compared_to_token_bucket() {
+        let token_bucket = RateLimiter::builder()
+            .token_bucket(5, 1.0)
+            .build()
+            .await
+            .unwrap();
+        let gcra = RateLimiter::builder()
+            .gcra(Duration::from_secs(1), 5)
+            .build()
+            .await
+            .unwrap();
+        let paced = RateLimiter::builder()
+            .gcra(Duration::from_secs(1), 1)
+            .build()
+            .await
+            .unwrap();
+
+        // Same rate and burst: both admit the burst, then deny
+        for i in 0..5 {
+            assert!(token_bucket.check("key").await.unwrap().allowed, "{}", i);
+            assert!(gcra.check("key").await.unwrap().allowed, "{}", i);
+        }
+        assert!(!token_bucket.check("key").await.unwrap().allowed);
+        let denied = gcra.check("key").await.unwrap();
+        assert!(!denied.allowed);
+
+        // GCRA knows exactly when the next slot opens
+        let retry_after = denied.retry_after.unwrap();
+        assert!(retry_after > Duration::from_millis(900) && retry_after <= Duration::from_secs(1));
+
+        // Without a burst, GCRA spaces every request a full period apart
+        assert!(paced.check("key").await.unwrap().allowed);
+        let denied = paced.check("key").await.unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(denied.limit, 1);
+    }
+
+    #[tokio::test]
+    async fn test_gcra_admits_after_retry_after() {
+        let limiter = RateLimiter::builder()
+            .gcra(Duration::from_millis(50), 2)
+            .build()
+            .await
+            .unwrap();
+
+        assert!(limiter.check("key").await.unwrap().allowed);
+        assert!(limiter.check("key").await.unwrap().allowed);
+        let denied = limiter.check("key").await.unwrap();
+        assert!(!denied.allowed);
+
+        tokio::time::sleep(denied.retry_after.unwrap()).await;
+        let result = limiter.check("key").await.unwrap();
+        assert!(result.allowed);
+        assert_eq!(result.remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_counter_across_boundary() {
+        let window = Duration::from_millis(200);
+        let limiter = RateLimiter::builder()
+            .sliding_window_counter(4, window)
+            .build()
+            .await
+            .unwrap();
+
+        for _ in 0..4 {
+            assert!(limiter.check("key").await.unwrap().allowed);
+        }
+        assert!(!limiter.check("key").await.unwrap().allowed);
+
+        // Early in the next window most of the previous count still applies,
+        // where a fixed window would have reset completely
+        tokio::time::sleep(window + window / 10).await;
+        assert!(!limiter.check("key").await.unwrap().allowed);
+
+        // Past the middle of it, the previous count has decayed to under 2
+        tokio::time::sleep(window / 2).await;
+        assert!(limiter.check("key").await.unwrap().allowed);
+        assert!(limiter.check("key").await.unwrap().allowed);
+        assert!(!limiter.check("key").await.unwrap().allowed);
+    }
+
     #[tokio::test]
     async fn test_different_keys() {
         let limiter = RateLimiter::builder()
@@ -415,4 +653,49 @@ mod tests {
         let result = limiter.check("test_key").await.unwrap();
         assert!(result.allowed);
     }
+
+    #[tokio::test]
+    async fn test_check_n_partial_consumption() {
+        let limiters = [
+            RateLimiter::builder().token_bucket(10, 0.001),
+            RateLimiter::builder().sliding_window(10, Duration::from_secs(60)),
+            RateLimiter::builder().sliding_window_counter(10, Duration::from_secs(60)),
+            RateLimiter::builder().fixed_window(10, Duration::from_secs(60)),
+            RateLimiter::builder().gcra(Duration::from_secs(60), 10),
+        ];
+
+        for builder in limiters {
+            let limiter = builder.build().await.unwrap();
+            let name = format!("{:?}", limiter.algorithm());
+
+            let result = limiter.check_n("key", 4).await.unwrap();
+            assert!(result.allowed, "{}", name);
+            assert_eq!(result.remaining, 6, "{}", name);
+
+            // Too expensive for what is left, and consumes nothing
+            let result = limiter.check_n("key", 7).await.unwrap();
+            assert!(!result.allowed, "{}", name);
+
+            let result = limiter.check_n("key", 6).await.unwrap();
+            assert!(result.allowed, "{}", name);
+            assert_eq!(result.remaining, 0, "{}", name);
+            assert!(!limiter.check("key").await.unwrap().allowed, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_n_cost_above_limit() {
+        let limiter = RateLimiter::builder()
+            .token_bucket(5, 1.0)
+            .build()
+            .await
+            .unwrap();
+
+        let result = limiter.check_n("key", 6).await.unwrap();
+        assert!(!result.allowed);
+        assert!(result.retry_after.is_none());
+
+        // Nothing was consumed
+        assert_eq!(limiter.check_n("key", 5).await.unwrap().remaining, 0);
+    }
 }
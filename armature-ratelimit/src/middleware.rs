@@ -3,39 +3,166 @@
 //! This module provides middleware that can be used with the Armature framework
 //! to add rate limiting to your application.
 
-use crate::RateLimiter;
+use crate::access_list::AccessLists;
+use crate::config::FailMode;
 use crate::error::RateLimitHeaders;
-use crate::extractor::{KeyExtractor, RequestInfo};
+use crate::extractor::{CostExtractor, KeyExtractor, RequestInfo};
+use crate::rules::RouteRuleSet;
+use crate::{RateLimitCheckResult, RateLimiter};
+use armature_core::{HttpResponse, ResponseBuilder};
 use std::net::IpAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::{debug, info, trace, warn};
 
+/// A rejected request, passed to [`OnRejected`] to build the 429 response
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    /// Configured error message
+    pub message: String,
+    /// Seconds until the client should retry, if waiting would help
+    pub retry_after: Option<u64>,
+    /// Rate limit headers, if enabled
+    pub headers: Option<RateLimitHeaders>,
+}
+
+/// Type alias for rejected response builder function
+pub type OnRejectedFn = Arc<dyn Fn(&Rejection) -> ResponseBuilder + Send + Sync>;
+
+/// Builds the response sent when a request is rate limited
+///
+/// The rate limit headers and `Retry-After` are added to whatever response
+/// is built, so the builder only needs to provide the status and body.
+///
+/// ```
+/// use armature_core::ResponseBuilder;
+/// use armature_ratelimit::middleware::OnRejected;
+///
+/// let on_rejected = OnRejected::new(|rejection| {
+///     ResponseBuilder::new()
+///         .status(429)
+///         .content_type("text/html")
+///         .body_str(&format!("<p>{}</p>", rejection.message))
+/// });
+/// ```
+#[derive(Clone)]
+pub struct OnRejected(OnRejectedFn);
+
+impl OnRejected {
+    /// Build the response with a custom function
+    pub fn new<F>(f: F) -> Self
+    where
+        F: Fn(&Rejection) -> ResponseBuilder + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    /// Respond with a JSON body (the default)
+    ///
+    /// `{"error": "Too Many Requests", "message": "..."}`
+    pub fn json() -> Self {
+        Self::new(|rejection| {
+            ResponseBuilder::new()
+                .status(429)
+                .content_type("application/json")
+                .body_str(
+                    &serde_json::json!({
+                        "error": "Too Many Requests",
+                        "message": rejection.message
+                    })
+                    .to_string(),
+                )
+        })
+    }
+
+    /// Respond with the message as a plain text body
+    pub fn text() -> Self {
+        Self::new(|rejection| {
+            ResponseBuilder::new()
+                .status(429)
+                .content_type("text/plain; charset=utf-8")
+                .body_str(&rejection.message)
+        })
+    }
+
+    /// Build the response for a rejected request
+    pub fn build(&self, rejection: &Rejection) -> HttpResponse {
+        (self.0)(rejection).build()
+    }
+}
+
+impl Default for OnRejected {
+    fn default() -> Self {
+        Self::json()
+    }
+}
+
+impl std::fmt::Debug for OnRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OnRejected").field(&"<fn>").finish()
+    }
+}
+
+impl<F> From<F> for OnRejected
+where
+    F: Fn(&Rejection) -> ResponseBuilder + Send + Sync + 'static,
+{
+    fn from(f: F) -> Self {
+        Self::new(f)
+    }
+}
+
 /// Rate limiting middleware for Armature applications
 pub struct RateLimitMiddleware {
     /// The rate limiter instance
     limiter: Arc<RateLimiter>,
     /// Key extraction strategy
     key_extractor: KeyExtractor,
+    /// Cost of each request
+    cost_extractor: CostExtractor,
+    /// Per-route rules, used instead of the limiter when any match
+    route_rules: RouteRuleSet,
     /// Whether to add rate limit headers to responses
     include_headers: bool,
+    /// Whether to add the draft combined `RateLimit` header
+    rate_limit_header: bool,
+    /// Builds the response for rate limited requests
+    on_rejected: OnRejected,
     /// Custom message for rate limit exceeded responses
     error_message: String,
     /// Keys that bypass rate limiting
     bypass_keys: Vec<String>,
+    /// Allowlist and denylist, consulted before the limiter
+    access_lists: Arc<AccessLists>,
+    /// Retry-After reported to denylisted keys
+    denylist_retry_after: std::time::Duration,
+    /// How requests are decided when the store fails or times out
+    fail_mode: FailMode,
+    /// Decisions made by the fail mode instead of the store
+    degraded_decisions: AtomicU64,
 }
 
 impl RateLimitMiddleware {
     /// Create a new rate limit middleware
     pub fn new(limiter: Arc<RateLimiter>) -> Self {
         let config = limiter.config().clone();
+        let fail_mode = config.store_error_mode();
         Self {
             limiter,
             key_extractor: KeyExtractor::Ip,
+            cost_extractor: CostExtractor::new(),
+            route_rules: RouteRuleSet::new(),
             include_headers: config.include_headers,
+            rate_limit_header: config.rate_limit_header,
+            on_rejected: config.on_rejected,
             error_message: config
                 .error_message
                 .unwrap_or_else(|| "Rate limit exceeded".to_string()),
             bypass_keys: config.bypass_keys,
+            access_lists: config.access_lists,
+            denylist_retry_after: config.denylist_retry_after,
+            fail_mode,
+            degraded_decisions: AtomicU64::new(0),
         }
     }
 
@@ -45,12 +172,42 @@ impl RateLimitMiddleware {
         self
     }
 
+    /// Set how much each request costs
+    pub fn with_cost_extractor(mut self, extractor: CostExtractor) -> Self {
+        self.cost_extractor = extractor;
+        self
+    }
+
+    /// Set per-route rules
+    ///
+    /// Requests matching one or more rules are checked against all of them
+    /// and must pass every one. Requests matching none fall back to the
+    /// middleware's limiter and key extractor.
+    pub fn with_route_rules(mut self, rules: RouteRuleSet) -> Self {
+        self.route_rules = rules;
+        self
+    }
+
     /// Set whether to include rate limit headers
     pub fn with_headers(mut self, include: bool) -> Self {
         self.include_headers = include;
         self
     }
 
+    /// Set whether to include the draft combined `RateLimit` header
+    ///
+    /// Sent alongside the `X-RateLimit-*` headers when those are enabled.
+    pub fn with_rate_limit_header(mut self, include: bool) -> Self {
+        self.rate_limit_header = include;
+        self
+    }
+
+    /// Set how the response for rate limited requests is built
+    pub fn with_on_rejected(mut self, on_rejected: impl Into<OnRejected>) -> Self {
+        self.on_rejected = on_rejected.into();
+        self
+    }
+
     /// Set custom error message
     pub fn with_error_message(mut self, message: impl Into<String>) -> Self {
         self.error_message = message.into();
@@ -63,68 +220,215 @@ impl RateLimitMiddleware {
         self
     }
 
+    /// Set how requests are decided when the store fails or times out
+    pub fn with_on_store_error(mut self, mode: FailMode) -> Self {
+        self.fail_mode = mode;
+        self
+    }
+
+    /// Get the number of requests decided by the fail mode because the store
+    /// failed or timed out
+    pub fn degraded_decisions(&self) -> u64 {
+        self.degraded_decisions.load(Ordering::Relaxed)
+    }
+
+    /// Share allowlist and denylist with other middleware
+    pub fn with_access_lists(mut self, lists: Arc<AccessLists>) -> Self {
+        self.access_lists = lists;
+        self
+    }
+
+    /// Get the allowlist and denylist, e.g. to replace them at runtime
+    pub fn access_lists(&self) -> &Arc<AccessLists> {
+        &self.access_lists
+    }
+
     /// Check if a request should be rate limited
     ///
     /// Returns Ok with headers if allowed, Err with response if rate limited.
     pub async fn check(&self, info: &RequestInfo) -> RateLimitCheckResponse {
-        // Extract key
-        let key = match self.key_extractor.extract(info) {
-            Some(k) => k,
-            None => {
-                warn!("Could not extract rate limit key, allowing request");
-                return RateLimitCheckResponse::Allowed { headers: None };
+        let cost = self.cost_extractor.cost(info);
+
+        let mut rules = self.route_rules.matching(&info.path).peekable();
+        if rules.peek().is_none() {
+            let key = self.key_extractor.extract(info);
+            return match self.check_limiter(&self.limiter, key, None, cost).await {
+                Some(result) => self.response(result),
+                None => RateLimitCheckResponse::Allowed { headers: None },
+            };
+        }
+
+        // Every limit of every matching rule must pass. All are checked so a
+        // denial reports the longest wait; otherwise report the one closest to
+        // its limit.
+        let mut denied: Option<RateLimitCheckResult> = None;
+        let mut most_restrictive: Option<RateLimitCheckResult> = None;
+        let mut charged = Vec::new();
+        for rule in rules {
+            trace!(pattern = %rule.pattern(), "Applying route rule");
+            for limit in rule.limits() {
+                let key = limit.key_extractor().extract(info);
+                let Some(result) = self
+                    .check_limiter(limit.limiter(), key.clone(), Some(limit.namespace()), cost)
+                    .await
+                else {
+                    continue;
+                };
+
+                if !result.allowed {
+                    // No retry_after means the cost can never fit, which beats any wait
+                    let longer = |current: &RateLimitCheckResult| match (
+                        result.retry_after,
+                        current.retry_after,
+                    ) {
+                        (_, None) => false,
+                        (None, Some(_)) => true,
+                        (Some(new), Some(old)) => new > old,
+                    };
+                    if denied.as_ref().is_none_or(longer) {
+                        denied = Some(result);
+                    }
+                } else {
+                    // Only allowed checks consumed anything
+                    if let Some(key) = key {
+                        charged.push((limit, namespaced_key(key, Some(limit.namespace()))));
+                    }
+                    if most_restrictive
+                        .as_ref()
+                        .is_none_or(|current| result.remaining < current.remaining)
+                    {
+                        most_restrictive = Some(result);
+                    }
+                }
             }
+        }
+
+        // A denied request doesn't count against the limits that allowed it
+        if denied.is_some() {
+            for (limit, key) in charged {
+                if let Err(e) = limit.limiter().refund_n(&key, cost).await {
+                    warn!(key = %key, error = %e, "Could not refund rate limit cost");
+                }
+            }
+        }
+
+        match denied.or(most_restrictive) {
+            Some(result) => self.response(result),
+            None => RateLimitCheckResponse::Allowed { headers: None },
+        }
+    }
+
+    /// Check one limiter, returning None if the request is not limited by it
+    ///
+    /// Rule limits store their keys under the limit's namespace.
+    async fn check_limiter(
+        &self,
+        limiter: &RateLimiter,
+        key: Option<String>,
+        namespace: Option<&str>,
+        cost: u64,
+    ) -> Option<RateLimitCheckResult> {
+        let Some(key) = key else {
+            warn!("Could not extract rate limit key, allowing request");
+            return None;
         };
 
         trace!(key = %key, "Checking rate limit");
 
+        // Denylisted keys are rejected without consuming anything
+        if self.access_lists.is_denylisted(&key) {
+            info!(key = %key, "Key is denylisted, rejecting request");
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            return Some(RateLimitCheckResult::denied(
+                limiter.algorithm().limit(),
+                now + self.denylist_retry_after.as_secs(),
+                self.denylist_retry_after,
+            ));
+        }
+
         // Check bypass
-        if self.bypass_keys.contains(&key) {
+        if self.bypass_keys.contains(&key) || self.access_lists.is_allowlisted(&key) {
             debug!(key = %key, "Key is in bypass list, allowing request");
-            return RateLimitCheckResponse::Allowed { headers: None };
+            return None;
         }
 
+        let key = namespaced_key(key, namespace);
+
         // Check rate limit
-        match self.limiter.check(&key).await {
+        match limiter.check_n(&key, cost).await {
             Ok(result) => {
-                let headers = if self.include_headers {
-                    Some(RateLimitHeaders::allowed(
-                        result.limit,
-                        result.remaining,
-                        result.reset_at,
-                    ))
-                } else {
-                    None
-                };
-
                 if result.allowed {
                     trace!(key = %key, remaining = result.remaining, "Request allowed");
-                    RateLimitCheckResponse::Allowed { headers }
                 } else {
                     info!(key = %key, retry_after = ?result.retry_after, "Rate limit exceeded");
-                    RateLimitCheckResponse::Limited {
-                        headers: if self.include_headers {
-                            Some(RateLimitHeaders::denied(
-                                result.limit,
-                                result.reset_at,
-                                result.retry_after.map(|d| d.as_secs()).unwrap_or(1),
-                            ))
-                        } else {
-                            None
-                        },
-                        message: self.error_message.clone(),
-                        retry_after: result.retry_after.map(|d| d.as_secs()),
-                    }
                 }
+                Some(result)
             }
             Err(e) => {
-                warn!(error = %e, "Rate limit check failed");
-                // On error, allow the request (fail open)
-                RateLimitCheckResponse::Allowed { headers: None }
+                self.degraded_decisions.fetch_add(1, Ordering::Relaxed);
+                match self.fail_mode {
+                    FailMode::FailOpen => {
+                        warn!(key = %key, error = %e, "Rate limit check failed, allowing request");
+                        None
+                    }
+                    FailMode::FailClosed => {
+                        warn!(key = %key, error = %e, "Rate limit check failed, rejecting request");
+                        let now = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap()
+                            .as_secs();
+                        Some(RateLimitCheckResult::denied(
+                            limiter.algorithm().limit(),
+                            now + 1,
+                            std::time::Duration::from_secs(1),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Build the response for a check result
+    fn response(&self, result: RateLimitCheckResult) -> RateLimitCheckResponse {
+        if result.allowed {
+            RateLimitCheckResponse::Allowed {
+                headers: self.include_headers.then(|| {
+                    RateLimitHeaders::allowed(result.limit, result.remaining, result.reset_at)
+                }),
+            }
+        } else {
+            // Round up so clients never retry too early
+            let retry_after = result
+                .retry_after
+                .map(|d| d.as_secs() + u64::from(d.subsec_nanos() > 0));
+            RateLimitCheckResponse::Limited {
+                headers: self.include_headers.then_some(RateLimitHeaders {
+                    limit: result.limit,
+                    remaining: 0,
+                    reset: result.reset_at,
+                    retry_after,
+                }),
+                message: self.error_message.clone(),
+                retry_after,
             }
         }
     }
 
+    /// Add rate limit headers to a response
+    fn apply_headers(&self, response: &mut HttpResponse, headers: &RateLimitHeaders) {
+        for (name, value) in headers.to_header_pairs() {
+            response.headers.insert(name.to_string(), value);
+        }
+        if self.rate_limit_header {
+            response
+                .headers
+                .insert("RateLimit".to_string(), headers.rate_limit_header());
+        }
+    }
+
     /// Extract request info from common HTTP request types
     pub fn extract_request_info(
         ip: Option<IpAddr>,
@@ -165,6 +469,14 @@ impl RateLimitMiddleware {
     }
 }
 
+/// Prefix a key with the namespace of the rule limit it belongs to
+fn namespaced_key(key: String, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) => format!("{}:{}", namespace, key),
+        None => key,
+    }
+}
+
 /// Response from rate limit check
 #[derive(Debug)]
 pub enum RateLimitCheckResponse {
@@ -224,6 +536,8 @@ impl RateLimitCheckResponse {
 pub struct RateLimitMiddlewareBuilder {
     limiter: Option<Arc<RateLimiter>>,
     key_extractor: KeyExtractor,
+    cost_extractor: CostExtractor,
+    route_rules: RouteRuleSet,
     include_headers: bool,
     error_message: Option<String>,
     bypass_keys: Vec<String>,
@@ -235,6 +549,8 @@ impl RateLimitMiddlewareBuilder {
         Self {
             limiter: None,
             key_extractor: KeyExtractor::Ip,
+            cost_extractor: CostExtractor::new(),
+            route_rules: RouteRuleSet::new(),
             include_headers: true,
             error_message: None,
             bypass_keys: Vec::new(),
@@ -253,6 +569,18 @@ impl RateLimitMiddlewareBuilder {
         self
     }
 
+    /// Set the cost extractor
+    pub fn cost_extractor(mut self, extractor: CostExtractor) -> Self {
+        self.cost_extractor = extractor;
+        self
+    }
+
+    /// Set per-route rules
+    pub fn route_rules(mut self, rules: RouteRuleSet) -> Self {
+        self.route_rules = rules;
+        self
+    }
+
     /// Extract key from IP address
     pub fn by_ip(mut self) -> Self {
         self.key_extractor = KeyExtractor::Ip;
@@ -303,6 +631,8 @@ impl RateLimitMiddlewareBuilder {
 
         let mut middleware = RateLimitMiddleware::new(limiter)
             .with_extractor(self.key_extractor)
+            .with_cost_extractor(self.cost_extractor)
+            .with_route_rules(self.route_rules)
             .with_headers(self.include_headers)
             .with_bypass_keys(self.bypass_keys);
 
@@ -364,15 +694,7 @@ impl armature_core::Middleware for RateLimitMiddleware {
 
                 // Add rate limit headers if present
                 if let Some(h) = headers {
-                    response
-                        .headers
-                        .insert("X-RateLimit-Limit".to_string(), h.limit.to_string());
-                    response
-                        .headers
-                        .insert("X-RateLimit-Remaining".to_string(), h.remaining.to_string());
-                    response
-                        .headers
-                        .insert("X-RateLimit-Reset".to_string(), h.reset.to_string());
+                    self.apply_headers(&mut response, &h);
                 }
 
                 Ok(response)
@@ -383,37 +705,25 @@ impl armature_core::Middleware for RateLimitMiddleware {
                 retry_after,
             } => {
                 // Request is rate limited
-                let mut response = armature_core::HttpResponse::new(429).with_body(
-                    serde_json::json!({
-                        "error": "Too Many Requests",
-                        "message": message
-                    })
-                    .to_string()
-                    .into_bytes(),
-                );
+                let rejection = Rejection {
+                    message,
+                    retry_after,
+                    headers,
+                };
+                let mut response = self.on_rejected.build(&rejection);
 
-                response
-                    .headers
-                    .insert("Content-Type".to_string(), "application/json".to_string());
+                if let Some(h) = &rejection.headers {
+                    self.apply_headers(&mut response, h);
+                }
 
+                // Retry-After is standard on 429 responses, even without the
+                // rate limit headers
                 if let Some(retry) = retry_after {
                     response
                         .headers
                         .insert("Retry-After".to_string(), retry.to_string());
                 }
 
-                if let Some(h) = headers {
-                    response
-                        .headers
-                        .insert("X-RateLimit-Limit".to_string(), h.limit.to_string());
-                    response
-                        .headers
-                        .insert("X-RateLimit-Remaining".to_string(), "0".to_string());
-                    response
-                        .headers
-                        .insert("X-RateLimit-Reset".to_string(), h.reset.to_string());
-                }
-
                 Ok(response)
             }
         }
@@ -529,6 +839,580 @@ mod tests {
         assert_eq!(response.message(), Some("Custom rate limit message"));
     }
 
+    #[tokio::test]
+    async fn test_cost_extractor() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .token_bucket(6, 0.001)
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddlewareBuilder::new()
+            .limiter(limiter)
+            .cost_extractor(CostExtractor::new().route("/export", 5))
+            .build()
+            .unwrap();
+
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let export = RequestInfo::new("/export", "POST").with_ip(ip);
+        let cheap = RequestInfo::new("/api/test", "GET").with_ip(ip);
+
+        let response = middleware.check(&export).await;
+        assert!(response.is_allowed());
+        assert_eq!(response.headers().unwrap().remaining, 1);
+
+        // A second export does not fit, but a cheap request still does
+        assert!(middleware.check(&export).await.is_limited());
+        assert!(middleware.check(&cheap).await.is_allowed());
+        assert!(middleware.check(&cheap).await.is_limited());
+    }
+
+    #[tokio::test]
+    async fn test_route_rules() {
+        let per_minute = |max_requests| Algorithm::FixedWindow {
+            max_requests,
+            window: std::time::Duration::from_secs(60),
+        };
+        let rules = RouteRuleSet::new()
+            .rule("/api/*", per_minute(100), KeyExtractor::Ip)
+            .rule("/auth/login", per_minute(5), KeyExtractor::Ip);
+        let middleware =
+            RateLimitMiddleware::new(create_test_limiter().await).with_route_rules(rules);
+
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let login = RequestInfo::new("/auth/login", "POST").with_ip(ip);
+        let api = RequestInfo::new("/api/users", "GET").with_ip(ip);
+
+        for _ in 0..5 {
+            assert!(middleware.check(&login).await.is_allowed());
+        }
+        let response = middleware.check(&login).await;
+        assert!(response.is_limited());
+        assert_eq!(response.headers().unwrap().limit, 5);
+
+        // The same client still has its API allowance
+        for _ in 0..20 {
+            let response = middleware.check(&api).await;
+            assert!(response.is_allowed());
+            assert_eq!(response.headers().unwrap().limit, 100);
+        }
+
+        // Unmatched routes use the default limiter (capacity 5)
+        let other = RequestInfo::new("/health", "GET").with_ip(ip);
+        for _ in 0..5 {
+            assert!(middleware.check(&other).await.is_allowed());
+        }
+        assert!(middleware.check(&other).await.is_limited());
+    }
+
+    #[tokio::test]
+    async fn test_route_rules_compose() {
+        let per_minute = |max_requests| Algorithm::FixedWindow {
+            max_requests,
+            window: std::time::Duration::from_secs(60),
+        };
+        let rules = RouteRuleSet::new()
+            .rule("/*", per_minute(8), KeyExtractor::Ip)
+            .rule("/auth/login", per_minute(3), KeyExtractor::Ip);
+        let middleware =
+            RateLimitMiddleware::new(create_test_limiter().await).with_route_rules(rules);
+
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let login = RequestInfo::new("/auth/login", "POST").with_ip(ip);
+        let page = RequestInfo::new("/page", "GET").with_ip(ip);
+
+        // The stricter login rule applies on top of the global one
+        for remaining in (0..3).rev() {
+            let response = middleware.check(&login).await;
+            assert!(response.is_allowed());
+            assert_eq!(response.headers().unwrap().remaining, remaining);
+        }
+        assert!(middleware.check(&login).await.is_limited());
+
+        // Only the allowed logins counted against the global limit
+        for _ in 0..5 {
+            assert!(middleware.check(&page).await.is_allowed());
+        }
+        assert!(middleware.check(&page).await.is_limited());
+    }
+
+    #[tokio::test]
+    async fn test_layered_rule_limits() {
+        let rules = RouteRuleSet::new().layered(
+            "/api/*",
+            [
+                (
+                    Algorithm::FixedWindow {
+                        max_requests: 3,
+                        window: std::time::Duration::from_secs(60),
+                    },
+                    KeyExtractor::Ip,
+                ),
+                (
+                    Algorithm::TokenBucket {
+                        capacity: 2,
+                        refill_rate: 1.0,
+                    },
+                    KeyExtractor::composite([KeyExtractor::UserId, KeyExtractor::Path]),
+                ),
+            ],
+        );
+        let middleware =
+            RateLimitMiddleware::new(create_test_limiter().await).with_route_rules(rules);
+
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let alice = RequestInfo::new("/api/orders", "GET")
+            .with_ip(ip)
+            .with_user_id("alice");
+        let bob = RequestInfo::new("/api/orders", "GET")
+            .with_ip(ip)
+            .with_user_id("bob");
+
+        let retry_after = |response| match response {
+            RateLimitCheckResponse::Limited { retry_after, .. } => retry_after.unwrap(),
+            RateLimitCheckResponse::Allowed { .. } => panic!("expected limited"),
+        };
+
+        // Exceeding the per-user limit denies even though the IP has room
+        assert!(middleware.check(&alice).await.is_allowed());
+        assert!(middleware.check(&alice).await.is_allowed());
+        assert_eq!(retry_after(middleware.check(&alice).await), 1);
+
+        // The denied request was refunded to the IP limit, which has room left
+        assert!(middleware.check(&bob).await.is_allowed());
+        assert!(retry_after(middleware.check(&bob).await) > 1);
+
+        // With both exceeded, the longer wait is reported
+        assert!(retry_after(middleware.check(&alice).await) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_denied_request_refunds_allowing_limits() {
+        use crate::RateLimitStore;
+
+        let window = std::time::Duration::from_secs(60);
+        let store = Arc::new(crate::MemoryStore::new());
+        let rules = RouteRuleSet::with_store(store.clone()).layered(
+            "/api/*",
+            [
+                (
+                    Algorithm::FixedWindow {
+                        max_requests: 10,
+                        window,
+                    },
+                    KeyExtractor::Ip,
+                ),
+                (
+                    Algorithm::FixedWindow {
+                        max_requests: 1,
+                        window,
+                    },
+                    KeyExtractor::UserId,
+                ),
+            ],
+        );
+        let middleware =
+            RateLimitMiddleware::new(create_test_limiter().await).with_route_rules(rules);
+
+        let info = RequestInfo::new("/api/orders", "GET")
+            .with_ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)))
+            .with_user_id("alice");
+        // A zero-cost check reads the IP limit's count without changing it
+        let ip_remaining = || async {
+            store
+                .fixed_window_check("/api/*:127.0.0.1", 10, window, 0)
+                .await
+                .unwrap()
+                .1
+        };
+
+        assert!(middleware.check(&info).await.is_allowed());
+        assert_eq!(ip_remaining().await, 9);
+
+        // The user limit denies; the IP limit's remaining count is unchanged
+        for _ in 0..3 {
+            assert!(middleware.check(&info).await.is_limited());
+            assert_eq!(ip_remaining().await, 9);
+        }
+    }
+
+    /// Run a request from 127.0.0.1 through the middleware
+    async fn handle(middleware: &RateLimitMiddleware) -> HttpResponse {
+        use armature_core::{HttpRequest, Middleware};
+
+        let mut req = HttpRequest::new("GET".to_string(), "/api/test".to_string());
+        req.headers
+            .insert("x-real-ip".to_string(), "127.0.0.1".to_string());
+        middleware
+            .handle(
+                req,
+                Box::new(|_req: HttpRequest| Box::pin(async { Ok(HttpResponse::ok()) })),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_headers_on_allowed_and_denied_responses() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .fixed_window(2, std::time::Duration::from_secs(60))
+                .headers(true)
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddleware::new(limiter);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let response = handle(&middleware).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.headers.get("X-RateLimit-Limit").unwrap(), "2");
+        assert_eq!(response.headers.get("X-RateLimit-Remaining").unwrap(), "1");
+        let reset: u64 = response
+            .headers
+            .get("X-RateLimit-Reset")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(reset > now && reset <= now + 61);
+        assert!(response.headers.get("Retry-After").is_none());
+        assert!(response.headers.get("RateLimit").is_none());
+
+        handle(&middleware).await;
+        let response = handle(&middleware).await;
+        assert_eq!(response.status, 429);
+        assert_eq!(response.headers.get("X-RateLimit-Limit").unwrap(), "2");
+        assert_eq!(response.headers.get("X-RateLimit-Remaining").unwrap(), "0");
+        assert_eq!(
+            response.headers.get("X-RateLimit-Reset").unwrap(),
+            &reset.to_string()
+        );
+        let retry_after: u64 = response
+            .headers
+            .get("Retry-After")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(retry_after >= 1 && retry_after <= reset - now);
+        assert_eq!(
+            response.headers.get("Content-Type").unwrap(),
+            "application/json"
+        );
+        let body: serde_json::Value = serde_json::from_slice(response.body_ref()).unwrap();
+        assert_eq!(body["message"], "Rate limit exceeded");
+    }
+
+    #[tokio::test]
+    async fn test_headers_disabled() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .token_bucket(1, 0.5)
+                .headers(false)
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddleware::new(limiter);
+
+        let response = handle(&middleware).await;
+        assert!(response.headers.get("X-RateLimit-Limit").is_none());
+
+        // Retry-After is still sent with the 429
+        let response = handle(&middleware).await;
+        assert_eq!(response.status, 429);
+        assert!(response.headers.get("X-RateLimit-Limit").is_none());
+        assert_eq!(response.headers.get("Retry-After").unwrap(), "2");
+    }
+
+    #[tokio::test]
+    async fn test_draft_rate_limit_header() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .fixed_window(2, std::time::Duration::from_secs(60))
+                .rate_limit_header(true)
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddleware::new(limiter);
+
+        let response = handle(&middleware).await;
+        let value = response.headers.get("RateLimit").unwrap();
+        assert!(
+            value.starts_with("limit=2, remaining=1, reset="),
+            "{}",
+            value
+        );
+
+        handle(&middleware).await;
+        let response = handle(&middleware).await;
+        let value = response.headers.get("RateLimit").unwrap();
+        assert!(
+            value.starts_with("limit=2, remaining=0, reset="),
+            "{}",
+            value
+        );
+    }
+
+    #[tokio::test]
+    async fn test_on_rejected_text_body() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .token_bucket(1, 0.001)
+                .error_message("Slow down")
+                .on_rejected(OnRejected::text())
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddleware::new(limiter);
+
+        handle(&middleware).await;
+        let response = handle(&middleware).await;
+        assert_eq!(response.status, 429);
+        assert_eq!(
+            response.headers.get("Content-Type").unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(response.body_ref(), b"Slow down");
+        assert_eq!(response.headers.get("X-RateLimit-Remaining").unwrap(), "0");
+    }
+
+    #[tokio::test]
+    async fn test_on_rejected_custom_builder() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .token_bucket(1, 0.001)
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware =
+            RateLimitMiddleware::new(limiter).with_on_rejected(|rejection: &Rejection| {
+                ResponseBuilder::new()
+                    .status(503)
+                    .body_str(&format!("retry in {}s", rejection.retry_after.unwrap()))
+            });
+
+        handle(&middleware).await;
+        let response = handle(&middleware).await;
+        assert_eq!(response.status, 503);
+        assert!(String::from_utf8_lossy(response.body_ref()).starts_with("retry in "));
+        assert!(response.headers.get("Retry-After").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_skips_limiting() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .token_bucket(1, 0.001)
+                .allowlist(["127.0.0.0/8", "health-checker"])
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddleware::new(limiter);
+
+        let internal =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 9)));
+        for _ in 0..10 {
+            let response = middleware.check(&internal).await;
+            assert!(response.is_allowed());
+            assert!(response.headers().is_none());
+        }
+
+        // Other clients are still limited
+        let external =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)));
+        assert!(middleware.check(&external).await.is_allowed());
+        assert!(middleware.check(&external).await.is_limited());
+    }
+
+    #[tokio::test]
+    async fn test_denylist_rejects() {
+        let limiter = Arc::new(
+            RateLimiter::builder()
+                .token_bucket(100, 10.0)
+                .denylist(["203.0.113.0/24"])
+                .build()
+                .await
+                .unwrap(),
+        );
+        let middleware = RateLimitMiddleware::new(limiter);
+
+        let abuser =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)));
+        let response = middleware.check(&abuser).await;
+        assert!(response.is_limited());
+        assert_eq!(response.retry_after(), Some(3600));
+
+        // Nothing was consumed from the limiter
+        let remaining = middleware
+            .limiter()
+            .check("203.0.113.7")
+            .await
+            .unwrap()
+            .remaining;
+        assert_eq!(remaining, 99);
+    }
+
+    #[tokio::test]
+    async fn test_access_lists_swap_at_runtime() {
+        let middleware = RateLimitMiddleware::new(create_test_limiter().await);
+        let info =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)));
+        assert!(middleware.check(&info).await.is_allowed());
+
+        middleware.access_lists().set_denylist(["10.0.0.0/8"]);
+        assert!(middleware.check(&info).await.is_limited());
+
+        // The denylist wins over the allowlist
+        middleware.access_lists().set_allowlist(["10.1.2.3"]);
+        assert!(middleware.check(&info).await.is_limited());
+
+        middleware.access_lists().set_denylist(Vec::<String>::new());
+        let response = middleware.check(&info).await;
+        assert!(response.is_allowed());
+        assert!(response.headers().is_none());
+    }
+
+    /// A store that is down: every check fails, or never answers
+    struct FailingStore {
+        hang: bool,
+    }
+
+    impl FailingStore {
+        async fn fail<T>(&self) -> crate::RateLimitResult<T> {
+            if self.hang {
+                std::future::pending::<()>().await;
+            }
+            Err(crate::RateLimitError::store("connection refused"))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::RateLimitStore for FailingStore {
+        async fn token_bucket_check(
+            &self,
+            _key: &str,
+            _capacity: u64,
+            _refill_rate: f64,
+            _cost: u64,
+        ) -> crate::RateLimitResult<(bool, u64, std::time::Duration)> {
+            self.fail().await
+        }
+
+        async fn sliding_window_check(
+            &self,
+            _key: &str,
+            _max_requests: u64,
+            _window: std::time::Duration,
+            _cost: u64,
+        ) -> crate::RateLimitResult<(bool, u64, std::time::Duration)> {
+            self.fail().await
+        }
+
+        async fn sliding_window_counter_check(
+            &self,
+            _key: &str,
+            _max_requests: u64,
+            _window: std::time::Duration,
+            _cost: u64,
+        ) -> crate::RateLimitResult<(bool, u64, std::time::Duration)> {
+            self.fail().await
+        }
+
+        async fn fixed_window_check(
+            &self,
+            _key: &str,
+            _max_requests: u64,
+            _window: std::time::Duration,
+            _cost: u64,
+        ) -> crate::RateLimitResult<(bool, u64, std::time::Duration)> {
+            self.fail().await
+        }
+
+        async fn gcra_check(
+            &self,
+            _key: &str,
+            _period: std::time::Duration,
+            _burst: u64,
+            _cost: u64,
+        ) -> crate::RateLimitResult<(bool, u64, std::time::Duration)> {
+            self.fail().await
+        }
+
+        async fn reset(&self, _key: &str) -> crate::RateLimitResult<()> {
+            self.fail().await
+        }
+
+        async fn remaining(&self, _key: &str) -> crate::RateLimitResult<u64> {
+            self.fail().await
+        }
+
+        fn store_type(&self) -> &'static str {
+            "failing"
+        }
+    }
+
+    fn failing_middleware(hang: bool, mode: FailMode) -> RateLimitMiddleware {
+        let config = crate::RateLimitConfig::default()
+            .on_store_error(mode)
+            .store_timeout(std::time::Duration::from_millis(20));
+        let limiter = RateLimiter::new(
+            Arc::new(FailingStore { hang }),
+            config.algorithm.clone(),
+            config,
+        );
+        RateLimitMiddleware::new(Arc::new(limiter))
+    }
+
+    #[tokio::test]
+    async fn test_store_error_fail_open() {
+        let middleware = failing_middleware(false, FailMode::FailOpen);
+        let info =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+
+        assert!(middleware.check(&info).await.is_allowed());
+        assert!(middleware.check(&info).await.is_allowed());
+        assert_eq!(middleware.degraded_decisions(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_store_error_fail_closed() {
+        let middleware = failing_middleware(false, FailMode::FailClosed);
+        let info =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+
+        let response = middleware.check(&info).await;
+        assert!(response.is_limited());
+        assert_eq!(response.retry_after(), Some(1));
+        assert_eq!(middleware.degraded_decisions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_store_timeout_uses_fail_mode() {
+        let info =
+            RequestInfo::new("/api/test", "GET").with_ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+
+        let middleware = failing_middleware(true, FailMode::FailOpen);
+        assert!(middleware.check(&info).await.is_allowed());
+        assert_eq!(middleware.degraded_decisions(), 1);
+
+        let middleware = failing_middleware(true, FailMode::FailClosed);
+        assert!(middleware.check(&info).await.is_limited());
+        assert_eq!(middleware.degraded_decisions(), 1);
+
+        // The limiter itself reports the timeout
+        let error = middleware.limiter().check("key").await.unwrap_err();
+        assert!(matches!(error, crate::RateLimitError::StoreTimeout(_)));
+    }
+
     #[tokio::test]
     async fn test_extract_request_info() {
         let headers = vec![
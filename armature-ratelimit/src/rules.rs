@@ -0,0 +1,292 @@
+//! Per-route rate limit rules
+//!
+//! A [`RouteRuleSet`] maps route patterns to their own algorithm and key
+//! extractor. Every rule whose pattern matches a request applies, so a strict
+//! limit on `/auth/login` and a looser one on `/*` can both be enforced.
+//!
+//! Patterns use the router's syntax, segment by segment:
+//!
+//! - `/users` matches that path exactly
+//! - `/users/:id` or `/users/{id}` matches any single segment
+//! - `/api/*` or `/api/{*rest}` matches `/api` and everything below it
+//!
+//! ```
+//! use armature_ratelimit::{Algorithm, KeyExtractor, RouteRuleSet};
+//! use std::time::Duration;
+//!
+//! let rules = RouteRuleSet::new()
+//!     .rule(
+//!         "/api/*",
+//!         Algorithm::FixedWindow { max_requests: 100, window: Duration::from_secs(60) },
+//!         KeyExtractor::Ip,
+//!     )
+//!     .rule(
+//!         "/auth/login",
+//!         Algorithm::FixedWindow { max_requests: 5, window: Duration::from_secs(60) },
+//!         KeyExtractor::Ip,
+//!     );
+//!
+//! assert_eq!(rules.matching("/auth/login").count(), 1);
+//! assert_eq!(rules.matching("/api/users/1").count(), 1);
+//! assert_eq!(rules.matching("/health").count(), 0);
+//! ```
+//!
+//! A rule can also layer several limits, each with its own key, such as a
+//! global per-IP cap and a tighter per-(user, endpoint) cap:
+//!
+//! ```
+//! use armature_ratelimit::{Algorithm, KeyExtractor, RouteRuleSet};
+//! use std::time::Duration;
+//!
+//! let rules = RouteRuleSet::new().layered(
+//!     "/api/*",
+//!     [
+//!         (
+//!             Algorithm::FixedWindow { max_requests: 1000, window: Duration::from_secs(60) },
+//!             KeyExtractor::Ip,
+//!         ),
+//!         (
+//!             Algorithm::FixedWindow { max_requests: 100, window: Duration::from_secs(60) },
+//!             KeyExtractor::composite([KeyExtractor::UserId, KeyExtractor::Path]),
+//!         ),
+//!     ],
+//! );
+//! assert_eq!(rules.rules()[0].limits().len(), 2);
+//! ```
+//!
+//! A request must pass every limit of every matching rule. All of them are
+//! checked, so limits that pass are charged even when another denies, and a
+//! denied request reports the longest `retry_after`. Keys are stored under the
+//! rule's pattern, so rules sharing a store never share counters.
+
+use crate::RateLimiter;
+use crate::algorithms::Algorithm;
+use crate::config::RateLimitConfig;
+use crate::extractor::KeyExtractor;
+use crate::stores::{MemoryStore, RateLimitStore};
+use std::sync::Arc;
+
+/// One limit of a rule: an algorithm applied per extracted key
+pub struct RuleLimit {
+    limiter: RateLimiter,
+    key_extractor: KeyExtractor,
+    namespace: String,
+}
+
+impl RuleLimit {
+    /// Get the rate limiter
+    pub fn limiter(&self) -> &RateLimiter {
+        &self.limiter
+    }
+
+    /// Get the key extractor
+    pub fn key_extractor(&self) -> &KeyExtractor {
+        &self.key_extractor
+    }
+
+    /// Get the prefix that keeps this limit's keys apart from other limits
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+}
+
+impl std::fmt::Debug for RuleLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuleLimit")
+            .field("algorithm", self.limiter.algorithm())
+            .field("key_extractor", &self.key_extractor)
+            .finish()
+    }
+}
+
+/// Rate limits that apply to requests matching a route pattern
+#[derive(Debug)]
+pub struct RouteRule {
+    pattern: String,
+    limits: Vec<RuleLimit>,
+}
+
+impl RouteRule {
+    /// Get the route pattern
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Get the limits, all of which a request must pass
+    pub fn limits(&self) -> &[RuleLimit] {
+        &self.limits
+    }
+
+    /// Check if the rule applies to a request path
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or(path);
+        let mut path_parts = path.split('/').filter(|s| !s.is_empty());
+
+        for pattern_part in self.pattern.split('/').filter(|s| !s.is_empty()) {
+            if pattern_part == "*" || pattern_part.starts_with("{*") {
+                return true;
+            }
+
+            let Some(path_part) = path_parts.next() else {
+                return false;
+            };
+
+            let is_param = pattern_part.starts_with(':')
+                || (pattern_part.starts_with('{') && pattern_part.ends_with('}'));
+            if !is_param && pattern_part != path_part {
+                return false;
+            }
+        }
+
+        path_parts.next().is_none()
+    }
+}
+
+/// A set of per-route rate limit rules
+pub struct RouteRuleSet {
+    store: Arc<dyn RateLimitStore>,
+    rules: Vec<RouteRule>,
+}
+
+impl RouteRuleSet {
+    /// Create an empty rule set backed by an in-memory store
+    pub fn new() -> Self {
+        Self::with_store(Arc::new(MemoryStore::new()))
+    }
+
+    /// Create an empty rule set backed by the given store
+    ///
+    /// Use a shared store such as Redis to enforce the rules across instances.
+    pub fn with_store(store: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            store,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Add a rule limiting requests that match `pattern`
+    pub fn rule(
+        self,
+        pattern: impl Into<String>,
+        algorithm: Algorithm,
+        key_extractor: impl Into<KeyExtractor>,
+    ) -> Self {
+        self.layered(pattern, [(algorithm, key_extractor.into())])
+    }
+
+    /// Add a rule with several limits that must all pass
+    pub fn layered(
+        mut self,
+        pattern: impl Into<String>,
+        limits: impl IntoIterator<Item = (Algorithm, KeyExtractor)>,
+    ) -> Self {
+        let pattern = pattern.into();
+        let limits = limits
+            .into_iter()
+            .enumerate()
+            .map(|(i, (algorithm, key_extractor))| {
+                let config = RateLimitConfig {
+                    algorithm: algorithm.clone(),
+                    ..Default::default()
+                };
+                RuleLimit {
+                    limiter: RateLimiter::new(self.store.clone(), algorithm, config),
+                    key_extractor,
+                    namespace: match i {
+                        0 => pattern.clone(),
+                        _ => format!("{}#{}", pattern, i),
+                    },
+                }
+            })
+            .collect();
+        self.rules.push(RouteRule { pattern, limits });
+        self
+    }
+
+    /// Get the rules that apply to a request path, in the order they were added
+    pub fn matching<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a RouteRule> + 'a {
+        self.rules.iter().filter(move |rule| rule.matches(path))
+    }
+
+    /// Get all rules
+    pub fn rules(&self) -> &[RouteRule] {
+        &self.rules
+    }
+
+    /// Get the number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Check if there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl Default for RouteRuleSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for RouteRuleSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RouteRuleSet")
+            .field("rules", &self.rules)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn rules(patterns: &[&str]) -> RouteRuleSet {
+        patterns.iter().fold(RouteRuleSet::new(), |set, pattern| {
+            set.rule(
+                *pattern,
+                Algorithm::FixedWindow {
+                    max_requests: 1,
+                    window: Duration::from_secs(60),
+                },
+                KeyExtractor::Ip,
+            )
+        })
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let set = rules(&[
+            "/users",
+            "/users/:id",
+            "/files/{name}",
+            "/api/*",
+            "/static/{*rest}",
+        ]);
+        let matched = |path| {
+            set.matching(path)
+                .map(|rule| rule.pattern().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matched("/users"), ["/users"]);
+        assert_eq!(matched("/users/42?full=1"), ["/users/:id"]);
+        assert_eq!(matched("/users/42/posts"), Vec::<String>::new());
+        assert_eq!(matched("/files/a.txt"), ["/files/{name}"]);
+        assert_eq!(matched("/api"), ["/api/*"]);
+        assert_eq!(matched("/api/v1/users"), ["/api/*"]);
+        assert_eq!(matched("/apix"), Vec::<String>::new());
+        assert_eq!(matched("/static/css/app.css"), ["/static/{*rest}"]);
+    }
+
+    #[test]
+    fn test_overlapping_rules_all_match() {
+        let set = rules(&["/*", "/auth/login"]);
+
+        assert_eq!(set.matching("/auth/login").count(), 2);
+        assert_eq!(set.matching("/anything").count(), 1);
+        assert_eq!(set.len(), 2);
+    }
+}
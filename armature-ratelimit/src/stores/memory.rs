@@ -2,13 +2,32 @@
 //!
 //! Uses DashMap for thread-safe concurrent access. Suitable for single-instance
 //! deployments or testing. For distributed deployments, use the Redis store.
-
+//!
+//! Keys are kept until swept. Call [`MemoryStore::sweep_now`] yourself or
+//! start a background sweeper, which removes keys whose limit has fully reset
+//! and keys idle for longer than the store's max age:
+//!
+//! ```
+//! use armature_ratelimit::MemoryStore;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let store = Arc::new(MemoryStore::new().with_max_age(Duration::from_secs(600)));
+//! store.spawn_sweeper(Duration::from_secs(30));
+//! # }
+//! ```
+
+use crate::algorithms::{Algorithm, CounterState, gcra_periods, gcra_step};
 use crate::error::RateLimitResult;
 use crate::stores::RateLimitStore;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use std::collections::VecDeque;
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tracing::{debug, trace};
 
 /// Token bucket state
@@ -25,14 +44,49 @@ struct FixedWindowState {
     window_start: Instant,
 }
 
+/// Algorithm state with the bookkeeping needed to sweep it
+#[derive(Debug, Clone)]
+struct Tracked<T> {
+    state: T,
+    /// Last time the key was checked
+    last_seen: Instant,
+    /// When the key becomes indistinguishable from a fresh one
+    reset_at: Instant,
+}
+
+impl<T> Tracked<T> {
+    fn new(state: T, now: Instant) -> Self {
+        Self {
+            state,
+            last_seen: now,
+            reset_at: now,
+        }
+    }
+
+    /// Check if the entry can be dropped without changing any decision, or
+    /// has been idle for longer than `max_age`
+    fn is_stale(&self, now: Instant, max_age: Duration) -> bool {
+        now >= self.reset_at || now.saturating_duration_since(self.last_seen) >= max_age
+    }
+}
+
+/// Default time after which idle keys are swept even if not fully reset
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(3600);
+
 /// In-memory rate limit store
 pub struct MemoryStore {
     /// Token bucket states
-    token_buckets: DashMap<String, TokenBucketState>,
+    token_buckets: DashMap<String, Tracked<TokenBucketState>>,
     /// Sliding window logs
-    sliding_logs: DashMap<String, VecDeque<Instant>>,
+    sliding_logs: DashMap<String, Tracked<VecDeque<Instant>>>,
+    /// Sliding window counters
+    window_counters: DashMap<String, Tracked<CounterState>>,
     /// Fixed window states
-    fixed_windows: DashMap<String, FixedWindowState>,
+    fixed_windows: DashMap<String, Tracked<FixedWindowState>>,
+    /// GCRA theoretical arrival times
+    gcra_tats: DashMap<String, Tracked<Instant>>,
+    /// Idle time after which a key is swept even if not fully reset
+    max_age: Duration,
 }
 
 impl MemoryStore {
@@ -42,13 +96,89 @@ impl MemoryStore {
         Self {
             token_buckets: DashMap::new(),
             sliding_logs: DashMap::new(),
+            window_counters: DashMap::new(),
             fixed_windows: DashMap::new(),
+            gcra_tats: DashMap::new(),
+            max_age: DEFAULT_MAX_AGE,
         }
     }
 
+    /// Set how long a key may sit idle before it is swept, even if its limit
+    /// has not fully reset (default: 1 hour)
+    ///
+    /// Sweeping such a key forgets its usage, so keep this well above the
+    /// longest window or refill time in use.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Get the number of tracked keys
+    pub fn len(&self) -> usize {
+        self.token_buckets.len()
+            + self.sliding_logs.len()
+            + self.window_counters.len()
+            + self.fixed_windows.len()
+            + self.gcra_tats.len()
+    }
+
+    /// Check if no keys are tracked
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Get the number of tracked keys (for monitoring)
     pub fn key_count(&self) -> usize {
-        self.token_buckets.len() + self.sliding_logs.len() + self.fixed_windows.len()
+        self.len()
+    }
+
+    /// Remove keys whose limit has fully reset or that have been idle for
+    /// longer than the max age, returning how many were removed
+    ///
+    /// Each map is swept one shard at a time, so checks on other shards are
+    /// never blocked.
+    pub fn sweep_now(&self) -> usize {
+        let before = self.len();
+        let now = Instant::now();
+        let max_age = self.max_age;
+
+        self.token_buckets
+            .retain(|_, entry| !entry.is_stale(now, max_age));
+        self.sliding_logs
+            .retain(|_, entry| !entry.is_stale(now, max_age));
+        self.window_counters
+            .retain(|_, entry| !entry.is_stale(now, max_age));
+        self.fixed_windows
+            .retain(|_, entry| !entry.is_stale(now, max_age));
+        self.gcra_tats
+            .retain(|_, entry| !entry.is_stale(now, max_age));
+
+        let removed = before.saturating_sub(self.len());
+        debug!(removed = removed, key_count = self.len(), "Swept idle keys");
+        removed
+    }
+
+    /// Sweep idle keys every `interval` on a background task
+    ///
+    /// The task holds a weak reference and stops once the store is dropped.
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_sweeper(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        debug!(interval = ?interval, "Starting memory store sweeper");
+        let store: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(store) = store.upgrade() else {
+                    debug!("Memory store dropped, stopping sweeper");
+                    return;
+                };
+                store.sweep_now();
+            }
+        })
     }
 }
 
@@ -65,33 +195,53 @@ impl RateLimitStore for MemoryStore {
         key: &str,
         capacity: u64,
         refill_rate: f64,
-    ) -> RateLimitResult<(bool, u64)> {
-        trace!(key = %key, capacity = capacity, refill_rate = refill_rate, "Token bucket check");
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, capacity = capacity, refill_rate = refill_rate, cost = cost, "Token bucket check");
 
         let now = Instant::now();
 
         let mut entry = self
             .token_buckets
             .entry(key.to_string())
-            .or_insert_with(|| TokenBucketState {
-                tokens: capacity as f64,
-                last_refill: now,
+            .or_insert_with(|| {
+                Tracked::new(
+                    TokenBucketState {
+                        tokens: capacity as f64,
+                        last_refill: now,
+                    },
+                    now,
+                )
             });
+        entry.last_seen = now;
 
         // Refill based on elapsed time
-        let elapsed = now.duration_since(entry.last_refill).as_secs_f64();
+        let elapsed = now.duration_since(entry.state.last_refill).as_secs_f64();
         let new_tokens = elapsed * refill_rate;
-        entry.tokens = (entry.tokens + new_tokens).min(capacity as f64);
-        entry.last_refill = now;
+        entry.state.tokens = (entry.state.tokens + new_tokens).min(capacity as f64);
+        entry.state.last_refill = now;
+
+        let allowed = entry.state.tokens >= cost as f64;
+        if allowed {
+            entry.state.tokens -= cost as f64;
+        }
+
+        // The bucket is as good as new once it has refilled; a bucket that
+        // never refills is left to the max age
+        let refill_time = |tokens: f64| {
+            Duration::try_from_secs_f64(tokens / refill_rate).unwrap_or(Duration::MAX)
+        };
+        let full_in = refill_time(capacity as f64 - entry.state.tokens);
+        entry.reset_at = now.checked_add(full_in).unwrap_or(now + self.max_age);
 
-        if entry.tokens >= 1.0 {
-            entry.tokens -= 1.0;
-            let remaining = entry.tokens as u64;
+        if allowed {
+            let remaining = entry.state.tokens as u64;
             trace!(key = %key, remaining = remaining, "Token bucket: allowed");
-            Ok((true, remaining))
+            Ok((true, remaining, full_in))
         } else {
-            trace!(key = %key, "Token bucket: denied");
-            Ok((false, 0))
+            let wait = refill_time(cost as f64 - entry.state.tokens);
+            trace!(key = %key, wait = ?wait, "Token bucket: denied");
+            Ok((false, 0, wait))
         }
     }
 
@@ -100,77 +250,203 @@ impl RateLimitStore for MemoryStore {
         key: &str,
         max_requests: u64,
         window: Duration,
-    ) -> RateLimitResult<(bool, u64)> {
-        trace!(key = %key, max_requests = max_requests, window = ?window, "Sliding window check");
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, max_requests = max_requests, window = ?window, cost = cost, "Sliding window check");
 
         let now = Instant::now();
         let cutoff = now - window;
 
-        let mut entry = self.sliding_logs.entry(key.to_string()).or_default();
+        let mut entry = self
+            .sliding_logs
+            .entry(key.to_string())
+            .or_insert_with(|| Tracked::new(VecDeque::new(), now));
+        entry.last_seen = now;
 
         // Remove old timestamps
-        while let Some(front) = entry.front() {
+        while let Some(front) = entry.state.front() {
             if *front < cutoff {
-                entry.pop_front();
+                entry.state.pop_front();
             } else {
                 break;
             }
         }
 
-        let current_count = entry.len() as u64;
+        let current_count = entry.state.len() as u64;
 
-        if current_count < max_requests {
-            entry.push_back(now);
-            let remaining = max_requests - current_count - 1;
+        if current_count.saturating_add(cost) <= max_requests {
+            entry.state.extend(std::iter::repeat_n(now, cost as usize));
+            // The log empties once the newest request leaves the window
+            entry.reset_at = now + window;
+            let remaining = max_requests - current_count - cost;
             trace!(key = %key, remaining = remaining, "Sliding window: allowed");
-            Ok((true, remaining))
+            Ok((true, remaining, window))
         } else {
-            trace!(key = %key, "Sliding window: denied");
-            Ok((false, 0))
+            // Wait for the entry whose expiry makes room for `cost` more
+            let excess = current_count.saturating_add(cost) - max_requests;
+            let wait = (excess.min(current_count) as usize)
+                .checked_sub(1)
+                .and_then(|index| entry.state.get(index))
+                .map_or(window, |logged| {
+                    (*logged + window).saturating_duration_since(now)
+                });
+            trace!(key = %key, wait = ?wait, "Sliding window: denied");
+            Ok((false, 0, wait))
         }
     }
 
+    async fn sliding_window_counter_check(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window: Duration,
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, max_requests = max_requests, window = ?window, cost = cost, "Sliding window counter check");
+
+        let now = Instant::now();
+
+        let mut entry = self
+            .window_counters
+            .entry(key.to_string())
+            .or_insert_with(|| Tracked::new(CounterState::new(now), now));
+        entry.last_seen = now;
+
+        let result = entry.state.check(now, max_requests, window, cost);
+        // Both counters are zero once the window after the current one ends
+        entry.reset_at = entry.state.window_start() + window * 2;
+        if result.0 {
+            trace!(key = %key, remaining = result.1, "Sliding window counter: allowed");
+        } else {
+            trace!(key = %key, "Sliding window counter: denied");
+        }
+        Ok(result)
+    }
+
     async fn fixed_window_check(
         &self,
         key: &str,
         max_requests: u64,
         window: Duration,
-    ) -> RateLimitResult<(bool, u64)> {
-        trace!(key = %key, max_requests = max_requests, window = ?window, "Fixed window check");
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, max_requests = max_requests, window = ?window, cost = cost, "Fixed window check");
 
         let now = Instant::now();
 
         let mut entry = self
             .fixed_windows
             .entry(key.to_string())
-            .or_insert_with(|| FixedWindowState {
-                count: 0,
-                window_start: now,
+            .or_insert_with(|| {
+                Tracked::new(
+                    FixedWindowState {
+                        count: 0,
+                        window_start: now,
+                    },
+                    now,
+                )
             });
+        entry.last_seen = now;
 
         // Check if we're in a new window
-        let elapsed = now.duration_since(entry.window_start);
+        let elapsed = now.duration_since(entry.state.window_start);
         if elapsed >= window {
-            entry.count = 0;
-            entry.window_start = now;
+            entry.state.count = 0;
+            entry.state.window_start = now;
         }
+        entry.reset_at = entry.state.window_start + window;
+        let ttl = entry.reset_at.saturating_duration_since(now);
 
-        if entry.count < max_requests {
-            entry.count += 1;
-            let remaining = max_requests - entry.count;
+        if entry.state.count.saturating_add(cost) <= max_requests {
+            entry.state.count += cost;
+            let remaining = max_requests - entry.state.count;
             trace!(key = %key, remaining = remaining, "Fixed window: allowed");
-            Ok((true, remaining))
+            Ok((true, remaining, ttl))
         } else {
             trace!(key = %key, "Fixed window: denied");
-            Ok((false, 0))
+            Ok((false, 0, ttl))
+        }
+    }
+
+    async fn gcra_check(
+        &self,
+        key: &str,
+        period: Duration,
+        burst: u64,
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, period = ?period, burst = burst, cost = cost, "GCRA check");
+
+        let now = Instant::now();
+
+        let mut tat = self
+            .gcra_tats
+            .entry(key.to_string())
+            .or_insert_with(|| Tracked::new(now, now));
+        tat.last_seen = now;
+
+        match gcra_step(tat.state, now, period, burst, cost) {
+            Ok((new_tat, remaining)) => {
+                // A TAT in the past means the key is back to its full burst
+                tat.state = new_tat;
+                tat.reset_at = new_tat;
+                trace!(key = %key, remaining = remaining, "GCRA: allowed");
+                Ok((true, remaining, Duration::ZERO))
+            }
+            Err(retry_after) => {
+                trace!(key = %key, retry_after = ?retry_after, "GCRA: denied");
+                Ok((false, 0, retry_after))
+            }
         }
     }
 
+    async fn refund(&self, key: &str, algorithm: &Algorithm, cost: u64) -> RateLimitResult<()> {
+        trace!(key = %key, cost = cost, "Refunding rate limit cost");
+
+        match algorithm {
+            Algorithm::TokenBucket { capacity, .. } => {
+                if let Some(mut entry) = self.token_buckets.get_mut(key) {
+                    entry.state.tokens = (entry.state.tokens + cost as f64).min(*capacity as f64);
+                }
+            }
+            Algorithm::SlidingWindowLog { .. } => {
+                if let Some(mut entry) = self.sliding_logs.get_mut(key) {
+                    // The charged entries are the newest ones
+                    let kept = entry.state.len().saturating_sub(cost as usize);
+                    entry.state.truncate(kept);
+                }
+            }
+            Algorithm::SlidingWindowCounter { .. } => {
+                if let Some(mut entry) = self.window_counters.get_mut(key) {
+                    entry.state.refund(cost);
+                }
+            }
+            Algorithm::FixedWindow { .. } => {
+                if let Some(mut entry) = self.fixed_windows.get_mut(key) {
+                    entry.state.count = entry.state.count.saturating_sub(cost);
+                }
+            }
+            Algorithm::Gcra { period, .. } => {
+                if let Some(mut tat) = self.gcra_tats.get_mut(key) {
+                    let now = Instant::now();
+                    tat.state = tat
+                        .state
+                        .checked_sub(gcra_periods(*period, cost))
+                        .map_or(now, |refunded| refunded.max(now));
+                    tat.reset_at = tat.state;
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn reset(&self, key: &str) -> RateLimitResult<()> {
         debug!(key = %key, "Resetting rate limit state");
         self.token_buckets.remove(key);
         self.sliding_logs.remove(key);
+        self.window_counters.remove(key);
         self.fixed_windows.remove(key);
+        self.gcra_tats.remove(key);
         Ok(())
     }
 
@@ -178,34 +454,14 @@ impl RateLimitStore for MemoryStore {
         // This is algorithm-specific; return 0 as a default
         // In practice, callers should use the algorithm-specific methods
         if let Some(entry) = self.token_buckets.get(key) {
-            return Ok(entry.tokens as u64);
+            return Ok(entry.state.tokens as u64);
         }
         Ok(0)
     }
 
     async fn cleanup(&self) -> RateLimitResult<()> {
         debug!("Cleaning up expired entries");
-
-        // For token buckets, we keep them indefinitely (they self-refill)
-
-        // For sliding window, clean entries with no recent requests
-        let now = Instant::now();
-        let old_cutoff = now - Duration::from_secs(3600); // 1 hour
-
-        self.sliding_logs.retain(|_, logs| {
-            if let Some(last) = logs.back() {
-                *last > old_cutoff
-            } else {
-                false
-            }
-        });
-
-        // For fixed window, clean old windows
-        self.fixed_windows
-            .retain(|_, state| now.duration_since(state.window_start) < Duration::from_secs(3600));
-
-        debug!(key_count = self.key_count(), "Cleanup complete");
-
+        self.sweep_now();
         Ok(())
     }
 
@@ -218,20 +474,27 @@ impl RateLimitStore for MemoryStore {
 mod tests {
     use super::*;
 
+    /// Drop the TTL from a check result
+    fn decision(result: (bool, u64, Duration)) -> (bool, u64) {
+        (result.0, result.1)
+    }
+
     #[tokio::test]
     async fn test_token_bucket() {
         let store = MemoryStore::new();
 
         // First 5 requests should be allowed
         for i in (0..5).rev() {
-            let (allowed, remaining) = store.token_bucket_check("test", 5, 1.0).await.unwrap();
+            let (allowed, remaining, _) =
+                store.token_bucket_check("test", 5, 1.0, 1).await.unwrap();
             assert!(allowed);
             assert_eq!(remaining, i);
         }
 
-        // 6th should be denied
-        let (allowed, _) = store.token_bucket_check("test", 5, 1.0).await.unwrap();
+        // 6th should be denied until a token has refilled
+        let (allowed, _, wait) = store.token_bucket_check("test", 5, 1.0, 1).await.unwrap();
         assert!(!allowed);
+        assert!(wait > Duration::ZERO && wait <= Duration::from_secs(1));
     }
 
     #[tokio::test]
@@ -241,14 +504,21 @@ mod tests {
 
         // First 3 requests should be allowed
         for i in (0..3).rev() {
-            let (allowed, remaining) = store.sliding_window_check("test", 3, window).await.unwrap();
+            let (allowed, remaining, _) = store
+                .sliding_window_check("test", 3, window, 1)
+                .await
+                .unwrap();
             assert!(allowed);
             assert_eq!(remaining, i);
         }
 
-        // 4th should be denied
-        let (allowed, _) = store.sliding_window_check("test", 3, window).await.unwrap();
+        // 4th should be denied until the first request leaves the window
+        let (allowed, _, wait) = store
+            .sliding_window_check("test", 3, window, 1)
+            .await
+            .unwrap();
         assert!(!allowed);
+        assert!(wait > Duration::ZERO && wait <= window);
     }
 
     #[tokio::test]
@@ -258,14 +528,148 @@ mod tests {
 
         // First 3 requests should be allowed
         for i in (0..3).rev() {
-            let (allowed, remaining) = store.fixed_window_check("test", 3, window).await.unwrap();
+            let (allowed, remaining, _) = store
+                .fixed_window_check("test", 3, window, 1)
+                .await
+                .unwrap();
+            assert!(allowed);
+            assert_eq!(remaining, i);
+        }
+
+        // 4th should be denied until the window ends
+        let (allowed, _, ttl) = store
+            .fixed_window_check("test", 3, window, 1)
+            .await
+            .unwrap();
+        assert!(!allowed);
+        assert!(ttl > Duration::ZERO && ttl <= window);
+    }
+
+    #[tokio::test]
+    async fn test_sliding_window_counter() {
+        let store = MemoryStore::new();
+        let window = Duration::from_secs(60);
+
+        for i in (0..3).rev() {
+            let (allowed, remaining, _) = store
+                .sliding_window_counter_check("test", 3, window, 1)
+                .await
+                .unwrap();
+            assert!(allowed);
+            assert_eq!(remaining, i);
+        }
+
+        let (allowed, _, retry_after) = store
+            .sliding_window_counter_check("test", 3, window, 1)
+            .await
+            .unwrap();
+        assert!(!allowed);
+        assert!(retry_after > window);
+    }
+
+    #[tokio::test]
+    async fn test_gcra() {
+        let store = MemoryStore::new();
+        let period = Duration::from_secs(1);
+
+        for i in (0..3).rev() {
+            let (allowed, remaining, _) = store.gcra_check("test", period, 3, 1).await.unwrap();
             assert!(allowed);
             assert_eq!(remaining, i);
         }
 
-        // 4th should be denied
-        let (allowed, _) = store.fixed_window_check("test", 3, window).await.unwrap();
+        let (allowed, _, retry_after) = store.gcra_check("test", period, 3, 1).await.unwrap();
         assert!(!allowed);
+        assert!(retry_after > Duration::ZERO && retry_after <= period);
+    }
+
+    #[tokio::test]
+    async fn test_partial_consumption() {
+        let store = MemoryStore::new();
+        let window = Duration::from_secs(60);
+
+        // A request costing more than what's left is denied without consuming
+        assert_eq!(
+            decision(store.token_bucket_check("tb", 10, 0.001, 4).await.unwrap()),
+            (true, 6)
+        );
+        assert_eq!(
+            decision(store.token_bucket_check("tb", 10, 0.001, 7).await.unwrap()),
+            (false, 0)
+        );
+        assert_eq!(
+            decision(store.token_bucket_check("tb", 10, 0.001, 6).await.unwrap()),
+            (true, 0)
+        );
+
+        assert_eq!(
+            decision(
+                store
+                    .sliding_window_check("sw", 10, window, 4)
+                    .await
+                    .unwrap()
+            ),
+            (true, 6)
+        );
+        assert_eq!(
+            decision(
+                store
+                    .sliding_window_check("sw", 10, window, 7)
+                    .await
+                    .unwrap()
+            ),
+            (false, 0)
+        );
+        assert_eq!(
+            decision(
+                store
+                    .sliding_window_check("sw", 10, window, 6)
+                    .await
+                    .unwrap()
+            ),
+            (true, 0)
+        );
+
+        assert_eq!(
+            decision(store.fixed_window_check("fw", 10, window, 4).await.unwrap()),
+            (true, 6)
+        );
+        assert_eq!(
+            decision(store.fixed_window_check("fw", 10, window, 7).await.unwrap()),
+            (false, 0)
+        );
+        assert_eq!(
+            decision(store.fixed_window_check("fw", 10, window, 6).await.unwrap()),
+            (true, 0)
+        );
+
+        let (allowed, remaining, _) = store
+            .sliding_window_counter_check("swc", 10, window, 4)
+            .await
+            .unwrap();
+        assert!(allowed);
+        assert_eq!(remaining, 6);
+        assert!(
+            !store
+                .sliding_window_counter_check("swc", 10, window, 7)
+                .await
+                .unwrap()
+                .0
+        );
+        assert!(
+            store
+                .sliding_window_counter_check("swc", 10, window, 6)
+                .await
+                .unwrap()
+                .0
+        );
+
+        let period = Duration::from_secs(1);
+        let (allowed, remaining, _) = store.gcra_check("gcra", period, 10, 4).await.unwrap();
+        assert!(allowed);
+        assert_eq!(remaining, 6);
+        assert!(!store.gcra_check("gcra", period, 10, 7).await.unwrap().0);
+        assert!(store.gcra_check("gcra", period, 10, 6).await.unwrap().0);
     }
 
     #[tokio::test]
@@ -273,15 +677,15 @@ mod tests {
         let store = MemoryStore::new();
 
         // Use up token bucket
-        store.token_bucket_check("test", 1, 0.001).await.unwrap();
-        let (allowed, _) = store.token_bucket_check("test", 1, 0.001).await.unwrap();
+        store.token_bucket_check("test", 1, 0.001, 1).await.unwrap();
+        let (allowed, _, _) = store.token_bucket_check("test", 1, 0.001, 1).await.unwrap();
         assert!(!allowed);
 
         // Reset
         store.reset("test").await.unwrap();
 
         // Should be allowed again
-        let (allowed, _) = store.token_bucket_check("test", 1, 0.001).await.unwrap();
+        let (allowed, _, _) = store.token_bucket_check("test", 1, 0.001, 1).await.unwrap();
         assert!(allowed);
     }
 
@@ -290,12 +694,12 @@ mod tests {
         let store = MemoryStore::new();
 
         // Exhaust key1
-        store.token_bucket_check("key1", 1, 0.001).await.unwrap();
-        let (allowed, _) = store.token_bucket_check("key1", 1, 0.001).await.unwrap();
+        store.token_bucket_check("key1", 1, 0.001, 1).await.unwrap();
+        let (allowed, _, _) = store.token_bucket_check("key1", 1, 0.001, 1).await.unwrap();
         assert!(!allowed);
 
         // key2 should still work
-        let (allowed, _) = store.token_bucket_check("key2", 1, 0.001).await.unwrap();
+        let (allowed, _, _) = store.token_bucket_check("key2", 1, 0.001, 1).await.unwrap();
         assert!(allowed);
     }
 
@@ -304,13 +708,13 @@ mod tests {
         let store = MemoryStore::new();
 
         // Add some entries
-        store.token_bucket_check("test", 10, 1.0).await.unwrap();
+        store.token_bucket_check("test", 10, 1.0, 1).await.unwrap();
         store
-            .sliding_window_check("test", 10, Duration::from_secs(60))
+            .sliding_window_check("test", 10, Duration::from_secs(60), 1)
             .await
             .unwrap();
         store
-            .fixed_window_check("test", 10, Duration::from_secs(60))
+            .fixed_window_check("test", 10, Duration::from_secs(60), 1)
             .await
             .unwrap();
 
@@ -323,6 +727,79 @@ mod tests {
         assert!(store.key_count() > 0);
     }
 
+    #[tokio::test]
+    async fn test_sweep_now() {
+        let store = MemoryStore::new();
+        let short = Duration::from_millis(20);
+        let long = Duration::from_secs(60);
+
+        store
+            .fixed_window_check("idle", 10, short, 1)
+            .await
+            .unwrap();
+        store
+            .fixed_window_check("active", 10, long, 1)
+            .await
+            .unwrap();
+        store.gcra_check("idle", short, 1, 1).await.unwrap();
+        store
+            .token_bucket_check("idle", 10, 100.0, 1)
+            .await
+            .unwrap();
+        store
+            .token_bucket_check("active", 10, 0.001, 1)
+            .await
+            .unwrap();
+        assert_eq!(store.len(), 5);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(store.sweep_now(), 3);
+        assert_eq!(store.len(), 2);
+
+        // Keys idle past the max age go even if they have not reset
+        let store = MemoryStore::new().with_max_age(Duration::from_millis(20));
+        store
+            .fixed_window_check("active", 10, long, 1)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(store.sweep_now(), 1);
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sweeper_removes_idle_keys() {
+        let store = Arc::new(MemoryStore::new());
+        let window = Duration::from_millis(50);
+        let interval = Duration::from_millis(20);
+        let sweeper = store.spawn_sweeper(interval);
+
+        store
+            .sliding_window_check("idle", 10, window, 1)
+            .await
+            .unwrap();
+        store
+            .sliding_window_counter_check("idle", 10, window, 1)
+            .await
+            .unwrap();
+        store
+            .fixed_window_check("active", 10, Duration::from_secs(60), 1)
+            .await
+            .unwrap();
+        assert_eq!(store.len(), 3);
+
+        // The counter needs two windows to forget its previous count
+        tokio::time::sleep(window * 2 + interval * 3).await;
+        assert_eq!(store.len(), 1);
+
+        // The sweeper stops once the store is dropped
+        drop(store);
+        tokio::time::timeout(interval * 5, sweeper)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
     #[test]
     fn test_store_type() {
         let store = MemoryStore::new();
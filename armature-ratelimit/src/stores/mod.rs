@@ -13,6 +13,7 @@ pub use memory::MemoryStore;
 #[cfg(feature = "redis")]
 pub use redis::RedisStore;
 
+use crate::algorithms::Algorithm;
 use crate::error::RateLimitResult;
 use async_trait::async_trait;
 use std::time::Duration;
@@ -28,34 +29,77 @@ pub enum StoreType {
 }
 
 /// Trait for rate limit storage backends
+///
+/// Every check consumes `cost` tokens or window slots (1 for an ordinary
+/// request). A denied check consumes nothing.
 #[async_trait]
 pub trait RateLimitStore: Send + Sync {
-    /// Check and consume a token using token bucket algorithm
-    /// Returns (allowed, remaining_tokens)
+    /// Check and consume `cost` tokens using token bucket algorithm
+    /// Returns (allowed, remaining_tokens, ttl); ttl is the time until the
+    /// bucket is full again, or for a denied request until `cost` tokens are
+    /// available
     async fn token_bucket_check(
         &self,
         key: &str,
         capacity: u64,
         refill_rate: f64,
-    ) -> RateLimitResult<(bool, u64)>;
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)>;
 
-    /// Check and record a request using sliding window log algorithm
-    /// Returns (allowed, remaining_requests)
+    /// Check and record `cost` requests using sliding window log algorithm
+    /// Returns (allowed, remaining_requests, ttl); ttl is the time until the
+    /// log is empty again, or for a denied request until enough entries have
+    /// left the window to admit it
     async fn sliding_window_check(
         &self,
         key: &str,
         max_requests: u64,
         window: Duration,
-    ) -> RateLimitResult<(bool, u64)>;
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)>;
 
-    /// Check and increment counter using fixed window algorithm
-    /// Returns (allowed, remaining_requests)
+    /// Check and record `cost` requests using sliding window counter algorithm
+    /// Returns (allowed, remaining_requests, retry_after); retry_after is the
+    /// estimated wait until a denied request would be admitted
+    async fn sliding_window_counter_check(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window: Duration,
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)>;
+
+    /// Check and increment counter by `cost` using fixed window algorithm
+    /// Returns (allowed, remaining_requests, ttl); ttl is the time until the
+    /// current window ends
     async fn fixed_window_check(
         &self,
         key: &str,
         max_requests: u64,
         window: Duration,
-    ) -> RateLimitResult<(bool, u64)>;
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)>;
+
+    /// Check and record a request costing `cost` periods using the GCRA algorithm
+    /// Returns (allowed, remaining_burst, retry_after); retry_after is the
+    /// exact wait until a denied request would be admitted
+    async fn gcra_check(
+        &self,
+        key: &str,
+        period: Duration,
+        burst: u64,
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)>;
+
+    /// Give back `cost` that an allowed check with `algorithm` consumed
+    ///
+    /// Used when another limit denies the same request, so the limits that
+    /// allowed it are not charged. Stores that don't support refunds keep the
+    /// charge.
+    async fn refund(&self, key: &str, algorithm: &Algorithm, cost: u64) -> RateLimitResult<()> {
+        let _ = (key, algorithm, cost);
+        Ok(())
+    }
 
     /// Reset rate limit state for a key
     async fn reset(&self, key: &str) -> RateLimitResult<()>;
@@ -2,12 +2,20 @@
 //!
 //! Uses Redis for distributed rate limiting across multiple instances.
 //! Requires the `redis` feature to be enabled.
+//!
+//! Every check is a single Lua script that reads, updates, and writes the
+//! state in one round trip, so concurrent checks from any number of
+//! instances are atomic. Scripts read the Redis server clock rather than the
+//! caller's, so clock skew between instances does not affect limits. This
+//! needs Redis 5 or later.
 
+use crate::algorithms::Algorithm;
 use crate::error::{RateLimitError, RateLimitResult};
 use crate::stores::RateLimitStore;
 use async_trait::async_trait;
 use redis::AsyncCommands;
 use redis::aio::ConnectionManager;
+use std::sync::LazyLock;
 use std::time::Duration;
 use tracing::{debug, trace};
 
@@ -57,6 +65,247 @@ impl RedisStore {
     }
 }
 
+/// Token bucket: refill from elapsed time, then take `cost` tokens
+///
+/// Returns {allowed, remaining, ttl_ms}, where ttl_ms is the time until the
+/// bucket is full again, or for a denied request until `cost` tokens are
+/// available. The key expires a second after the bucket is full.
+static TOKEN_BUCKET_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r#"
+        local key = KEYS[1]
+        local capacity = tonumber(ARGV[1])
+        local refill_rate = tonumber(ARGV[2])
+        local cost = tonumber(ARGV[3])
+        local time = redis.call('TIME')
+        local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+
+        local data = redis.call('HMGET', key, 'tokens', 'last_refill')
+        local tokens = tonumber(data[1]) or capacity
+        local last_refill = tonumber(data[2]) or now
+
+        -- Refill tokens
+        local elapsed = math.max(0, now - last_refill)
+        tokens = math.min(capacity, tokens + elapsed * refill_rate)
+
+        -- Try to consume the tokens
+        local allowed = 0
+        if tokens >= cost then
+            tokens = tokens - cost
+            allowed = 1
+        end
+
+        local full_in = math.ceil((capacity - tokens) / refill_rate * 1000)
+        redis.call('HSET', key, 'tokens', tokens, 'last_refill', now)
+        redis.call('PEXPIRE', key, full_in + 1000)
+        if allowed == 0 then
+            return {0, math.floor(tokens), math.ceil((cost - tokens) / refill_rate * 1000)}
+        end
+        return {1, math.floor(tokens), full_in}
+        "#,
+    )
+});
+
+/// Sliding window log: drop expired entries, then add `cost` entries
+///
+/// Returns {allowed, remaining, ttl_ms}, where ttl_ms is the time until the
+/// log is empty, or for a denied request until enough entries have left the
+/// window to admit it.
+static SLIDING_WINDOW_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r#"
+        local key = KEYS[1]
+        local seq_key = KEYS[2]
+        local max_requests = tonumber(ARGV[1])
+        local window = tonumber(ARGV[2])
+        local cost = tonumber(ARGV[3])
+        local time = redis.call('TIME')
+        local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+        -- Remove old entries
+        redis.call('ZREMRANGEBYSCORE', key, 0, now - window)
+
+        -- Count current entries
+        local count = redis.call('ZCARD', key)
+        if count + cost > max_requests then
+            -- Wait for the entry whose expiry makes room for `cost` more
+            local index = math.min(count + cost - max_requests, count) - 1
+            if index < 0 then
+                return {0, 0, window}
+            end
+            local entry = redis.call('ZRANGE', key, index, index, 'WITHSCORES')
+            return {0, 0, math.max(0, tonumber(entry[2]) + window - now)}
+        end
+
+        -- Add one entry per unit of cost; members must be unique
+        local seq = redis.call('INCRBY', seq_key, cost)
+        for i = 1, cost do
+            redis.call('ZADD', key, now, now .. ':' .. (seq - i))
+        end
+        redis.call('PEXPIRE', key, window)
+        redis.call('PEXPIRE', seq_key, window)
+        return {1, max_requests - count - cost, window}
+        "#,
+    )
+});
+
+/// Sliding window counter: roll the counters into the current window, then
+/// weigh the previous count by its overlap with the sliding window
+///
+/// Returns {allowed, remaining, retry_after_ms}.
+static SLIDING_WINDOW_COUNTER_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r#"
+        local key = KEYS[1]
+        local max_requests = tonumber(ARGV[1])
+        local window = tonumber(ARGV[2])
+        local cost = tonumber(ARGV[3])
+        local time = redis.call('TIME')
+        local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+        local window_id = math.floor(now / window)
+        local elapsed = now - window_id * window
+
+        local data = redis.call('HMGET', key, 'window', 'previous', 'current')
+        local stored_id = tonumber(data[1]) or window_id
+        local previous = tonumber(data[2]) or 0
+        local current = tonumber(data[3]) or 0
+        if stored_id == window_id - 1 then
+            previous = current
+            current = 0
+        elseif stored_id ~= window_id then
+            previous = 0
+            current = 0
+        end
+
+        local estimate = previous * (window - elapsed) / window + current
+        if estimate + cost <= max_requests then
+            current = current + cost
+            redis.call('HSET', key, 'window', window_id, 'previous', previous, 'current', current)
+            redis.call('PEXPIRE', key, window * 2)
+            return {1, math.floor(max_requests - estimate - cost), 0}
+        end
+
+        -- Estimate when the count will have decayed enough
+        local room = max_requests - cost
+        local wait
+        if current <= room and previous > 0 then
+            wait = window * (1 - (room - current) / previous) - elapsed
+        elseif current > 0 then
+            wait = (window - elapsed) + window * math.max(0, 1 - room / current)
+        else
+            wait = window - elapsed
+        end
+        return {0, 0, math.ceil(math.max(0, wait))}
+        "#,
+    )
+});
+
+/// Fixed window: count `cost` against the window containing now
+///
+/// The key expires at the end of the window, so a new window starts from
+/// zero. Returns {allowed, remaining, ttl_ms}, where ttl_ms is the time until
+/// the window ends.
+static FIXED_WINDOW_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r#"
+        local key = KEYS[1]
+        local max_requests = tonumber(ARGV[1])
+        local window = tonumber(ARGV[2])
+        local cost = tonumber(ARGV[3])
+        local time = redis.call('TIME')
+        local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+        local window_end = (math.floor(now / window) + 1) * window
+
+        local count = tonumber(redis.call('GET', key)) or 0
+        if count + cost > max_requests then
+            return {0, 0, window_end - now}
+        end
+
+        count = redis.call('INCRBY', key, cost)
+        if count == cost then
+            redis.call('PEXPIREAT', key, window_end)
+        end
+        return {1, max_requests - count, window_end - now}
+        "#,
+    )
+});
+
+/// GCRA: advance the theoretical arrival time by `cost` periods; times are in
+/// microseconds
+///
+/// Returns {allowed, remaining, retry_after_us}.
+static GCRA_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r#"
+        local key = KEYS[1]
+        local period = tonumber(ARGV[1])
+        local burst = tonumber(ARGV[2])
+        local cost = tonumber(ARGV[3])
+        local time = redis.call('TIME')
+        local now = tonumber(time[1]) * 1000000 + tonumber(time[2])
+        local tolerance = period * burst
+
+        local tat = tonumber(redis.call('GET', key)) or now
+        if tat < now then
+            tat = now
+        end
+
+        local new_tat = tat + period * cost
+        local ahead = new_tat - now
+
+        if ahead > tolerance then
+            return {0, 0, ahead - tolerance}
+        end
+
+        redis.call('SET', key, new_tat, 'PX', math.max(1, math.ceil(ahead / 1000)))
+        return {1, math.floor((tolerance - ahead) / period), 0}
+        "#,
+    )
+});
+
+/// Refund: give back `cost` that an allowed check consumed
+///
+/// ARGV[1] names the algorithm by its key prefix. ARGV[3] is the bucket
+/// capacity for token buckets and the period in microseconds for GCRA. Keys
+/// keep their expiry. Returns 1 if the key existed.
+static REFUND_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r#"
+        local key = KEYS[1]
+        local kind = ARGV[1]
+        local cost = tonumber(ARGV[2])
+        if redis.call('EXISTS', key) == 0 then
+            return 0
+        end
+
+        if kind == 'tb' then
+            local capacity = tonumber(ARGV[3])
+            local tokens = tonumber(redis.call('HGET', key, 'tokens')) or capacity
+            redis.call('HSET', key, 'tokens', math.min(capacity, tokens + cost))
+        elseif kind == 'sw' then
+            -- The charged entries are the newest ones
+            redis.call('ZPOPMAX', key, cost)
+        elseif kind == 'swc' then
+            local current = tonumber(redis.call('HGET', key, 'current')) or 0
+            redis.call('HSET', key, 'current', math.max(0, current - cost))
+        elseif kind == 'fw' then
+            local count = tonumber(redis.call('GET', key)) or 0
+            redis.call('DECRBY', key, math.min(cost, count))
+        elseif kind == 'gcra' then
+            local period = tonumber(ARGV[3])
+            local time = redis.call('TIME')
+            local now = tonumber(time[1]) * 1000000 + tonumber(time[2])
+            local tat = math.max(now, tonumber(redis.call('GET', key)) - period * cost)
+            local ttl = redis.call('PTTL', key)
+            if ttl > 0 then
+                redis.call('SET', key, tat, 'PX', ttl)
+            end
+        end
+        return 1
+        "#,
+    )
+});
+
 #[async_trait]
 impl RateLimitStore for RedisStore {
     async fn token_bucket_check(
@@ -64,66 +313,33 @@ impl RateLimitStore for RedisStore {
         key: &str,
         capacity: u64,
         refill_rate: f64,
-    ) -> RateLimitResult<(bool, u64)> {
-        trace!(key = %key, capacity = capacity, refill_rate = refill_rate, "Redis token bucket check");
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, capacity = capacity, refill_rate = refill_rate, cost = cost, "Redis token bucket check");
 
         let full_key = self.key(&format!("tb:{}", key));
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64();
-
-        // Lua script for atomic token bucket operation
-        let script = redis::Script::new(
-            r#"
-            local key = KEYS[1]
-            local capacity = tonumber(ARGV[1])
-            local refill_rate = tonumber(ARGV[2])
-            local now = tonumber(ARGV[3])
-            local ttl = math.ceil(capacity / refill_rate) + 10
-
-            local data = redis.call('HMGET', key, 'tokens', 'last_refill')
-            local tokens = tonumber(data[1]) or capacity
-            local last_refill = tonumber(data[2]) or now
-
-            -- Refill tokens
-            local elapsed = now - last_refill
-            tokens = math.min(capacity, tokens + elapsed * refill_rate)
-
-            -- Try to consume a token
-            if tokens >= 1 then
-                tokens = tokens - 1
-                redis.call('HMSET', key, 'tokens', tokens, 'last_refill', now)
-                redis.call('EXPIRE', key, ttl)
-                return {1, math.floor(tokens)}
-            else
-                redis.call('HMSET', key, 'tokens', tokens, 'last_refill', now)
-                redis.call('EXPIRE', key, ttl)
-                return {0, 0}
-            end
-            "#,
-        );
 
         let mut conn = self.conn.clone();
-        let result: (i32, i64) = script
+        let (allowed, remaining, ttl_ms): (i32, i64, i64) = TOKEN_BUCKET_SCRIPT
             .key(&full_key)
             .arg(capacity)
             .arg(refill_rate)
-            .arg(now)
+            .arg(cost)
             .invoke_async(&mut conn)
             .await
             .map_err(|e| RateLimitError::store(e.to_string()))?;
 
-        let allowed = result.0 == 1;
-        let remaining = result.1 as u64;
+        let allowed = allowed == 1;
+        let remaining = remaining as u64;
+        let ttl = Duration::from_millis(ttl_ms.max(0) as u64);
 
         if allowed {
-            trace!(key = %key, remaining = remaining, "Redis token bucket: allowed");
+            trace!(key = %key, remaining = remaining, ttl = ?ttl, "Redis token bucket: allowed");
         } else {
-            trace!(key = %key, "Redis token bucket: denied");
+            trace!(key = %key, ttl = ?ttl, "Redis token bucket: denied");
         }
 
-        Ok((allowed, remaining))
+        Ok((allowed, remaining, ttl))
     }
 
     async fn sliding_window_check(
@@ -131,108 +347,165 @@ impl RateLimitStore for RedisStore {
         key: &str,
         max_requests: u64,
         window: Duration,
-    ) -> RateLimitResult<(bool, u64)> {
-        trace!(key = %key, max_requests = max_requests, window = ?window, "Redis sliding window check");
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, max_requests = max_requests, window = ?window, cost = cost, "Redis sliding window check");
 
         let full_key = self.key(&format!("sw:{}", key));
-        let window_ms = window.as_millis() as i64;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as i64;
-        let cutoff = now - window_ms;
-
-        // Lua script for atomic sliding window operation
-        let script = redis::Script::new(
-            r#"
-            local key = KEYS[1]
-            local max_requests = tonumber(ARGV[1])
-            local now = tonumber(ARGV[2])
-            local cutoff = tonumber(ARGV[3])
-            local window_secs = tonumber(ARGV[4])
-
-            -- Remove old entries
-            redis.call('ZREMRANGEBYSCORE', key, 0, cutoff)
-
-            -- Count current entries
-            local count = redis.call('ZCARD', key)
-
-            if count < max_requests then
-                -- Add new entry
-                redis.call('ZADD', key, now, now)
-                redis.call('EXPIRE', key, window_secs + 10)
-                return {1, max_requests - count - 1}
-            else
-                return {0, 0}
-            end
-            "#,
-        );
+        let seq_key = self.key(&format!("sw:{}:seq", key));
 
         let mut conn = self.conn.clone();
-        let result: (i32, i64) = script
+        let (allowed, remaining, ttl_ms): (i32, i64, i64) = SLIDING_WINDOW_SCRIPT
             .key(&full_key)
+            .key(&seq_key)
             .arg(max_requests)
-            .arg(now)
-            .arg(cutoff)
-            .arg(window.as_secs())
+            .arg(window.as_millis().max(1) as u64)
+            .arg(cost)
             .invoke_async(&mut conn)
             .await
             .map_err(|e| RateLimitError::store(e.to_string()))?;
 
-        let allowed = result.0 == 1;
-        let remaining = result.1 as u64;
+        let allowed = allowed == 1;
+        let remaining = remaining as u64;
+        let ttl = Duration::from_millis(ttl_ms.max(0) as u64);
 
         if allowed {
-            trace!(key = %key, remaining = remaining, "Redis sliding window: allowed");
+            trace!(key = %key, remaining = remaining, ttl = ?ttl, "Redis sliding window: allowed");
         } else {
-            trace!(key = %key, "Redis sliding window: denied");
+            trace!(key = %key, ttl = ?ttl, "Redis sliding window: denied");
         }
 
-        Ok((allowed, remaining))
+        Ok((allowed, remaining, ttl))
     }
 
-    async fn fixed_window_check(
+    async fn sliding_window_counter_check(
         &self,
         key: &str,
         max_requests: u64,
         window: Duration,
-    ) -> RateLimitResult<(bool, u64)> {
-        trace!(key = %key, max_requests = max_requests, window = ?window, "Redis fixed window check");
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, max_requests = max_requests, window = ?window, cost = cost, "Redis sliding window counter check");
 
-        let window_secs = window.as_secs();
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let window_id = now / window_secs;
-        let full_key = self.key(&format!("fw:{}:{}", key, window_id));
+        let full_key = self.key(&format!("swc:{}", key));
 
         let mut conn = self.conn.clone();
+        let (allowed, remaining, retry_after_ms): (i32, i64, i64) = SLIDING_WINDOW_COUNTER_SCRIPT
+            .key(&full_key)
+            .arg(max_requests)
+            .arg(window.as_millis().max(1) as u64)
+            .arg(cost)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| RateLimitError::store(e.to_string()))?;
 
-        // Increment and get current count
-        let count: i64 = conn
-            .incr(&full_key, 1)
+        let allowed = allowed == 1;
+        let remaining = remaining as u64;
+
+        if allowed {
+            trace!(key = %key, remaining = remaining, "Redis sliding window counter: allowed");
+        } else {
+            trace!(key = %key, "Redis sliding window counter: denied");
+        }
+
+        Ok((
+            allowed,
+            remaining,
+            Duration::from_millis(retry_after_ms as u64),
+        ))
+    }
+
+    async fn fixed_window_check(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window: Duration,
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, max_requests = max_requests, window = ?window, cost = cost, "Redis fixed window check");
+
+        let full_key = self.key(&format!("fw:{}", key));
+
+        let mut conn = self.conn.clone();
+        let (allowed, remaining, ttl_ms): (i32, i64, i64) = FIXED_WINDOW_SCRIPT
+            .key(&full_key)
+            .arg(max_requests)
+            .arg(window.as_millis().max(1) as u64)
+            .arg(cost)
+            .invoke_async(&mut conn)
             .await
             .map_err(|e| RateLimitError::store(e.to_string()))?;
 
-        // Set expiry on first request
-        if count == 1 {
-            let _: () = conn
-                .expire(&full_key, window_secs as i64 + 10)
-                .await
-                .map_err(|e| RateLimitError::store(e.to_string()))?;
+        let allowed = allowed == 1;
+        let remaining = remaining as u64;
+        let ttl = Duration::from_millis(ttl_ms.max(0) as u64);
+
+        if allowed {
+            trace!(key = %key, remaining = remaining, ttl = ?ttl, "Redis fixed window: allowed");
+        } else {
+            trace!(key = %key, ttl = ?ttl, "Redis fixed window: denied");
         }
 
-        let count = count as u64;
+        Ok((allowed, remaining, ttl))
+    }
+
+    async fn gcra_check(
+        &self,
+        key: &str,
+        period: Duration,
+        burst: u64,
+        cost: u64,
+    ) -> RateLimitResult<(bool, u64, Duration)> {
+        trace!(key = %key, period = ?period, burst = burst, cost = cost, "Redis GCRA check");
 
-        if count <= max_requests {
-            let remaining = max_requests - count;
-            trace!(key = %key, remaining = remaining, "Redis fixed window: allowed");
-            Ok((true, remaining))
+        let full_key = self.key(&format!("gcra:{}", key));
+
+        let mut conn = self.conn.clone();
+        let (allowed, remaining, retry_after_us): (i32, i64, i64) = GCRA_SCRIPT
+            .key(&full_key)
+            .arg(period.as_micros().max(1) as u64)
+            .arg(burst)
+            .arg(cost)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| RateLimitError::store(e.to_string()))?;
+
+        let allowed = allowed == 1;
+        let remaining = remaining as u64;
+        let retry_after = Duration::from_micros(retry_after_us as u64);
+
+        if allowed {
+            trace!(key = %key, remaining = remaining, "Redis GCRA: allowed");
         } else {
-            trace!(key = %key, "Redis fixed window: denied");
-            Ok((false, 0))
+            trace!(key = %key, retry_after = ?retry_after, "Redis GCRA: denied");
         }
+
+        Ok((allowed, remaining, retry_after))
+    }
+
+    async fn refund(&self, key: &str, algorithm: &Algorithm, cost: u64) -> RateLimitResult<()> {
+        trace!(key = %key, cost = cost, "Redis refund");
+
+        let (kind, arg) = match algorithm {
+            Algorithm::TokenBucket { capacity, .. } => ("tb", *capacity),
+            Algorithm::SlidingWindowLog { .. } => ("sw", 0),
+            Algorithm::SlidingWindowCounter { .. } => ("swc", 0),
+            Algorithm::FixedWindow { .. } => ("fw", 0),
+            Algorithm::Gcra { period, .. } => ("gcra", period.as_micros().max(1) as u64),
+        };
+        let full_key = self.key(&format!("{}:{}", kind, key));
+
+        let mut conn = self.conn.clone();
+        let _: i32 = REFUND_SCRIPT
+            .key(&full_key)
+            .arg(kind)
+            .arg(cost)
+            .arg(arg)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| RateLimitError::store(e.to_string()))?;
+
+        Ok(())
     }
 
     async fn reset(&self, key: &str) -> RateLimitResult<()> {
@@ -241,34 +514,19 @@ impl RateLimitStore for RedisStore {
         let mut conn = self.conn.clone();
 
         // Delete all keys for this rate limit key
-        let patterns = [
+        let keys = [
             self.key(&format!("tb:{}", key)),
             self.key(&format!("sw:{}", key)),
-            self.key(&format!("fw:{}:*", key)),
+            self.key(&format!("sw:{}:seq", key)),
+            self.key(&format!("swc:{}", key)),
+            self.key(&format!("fw:{}", key)),
+            self.key(&format!("gcra:{}", key)),
         ];
 
-        for pattern in &patterns {
-            if pattern.contains('*') {
-                // Use SCAN for pattern matching
-                let keys: Vec<String> = redis::cmd("KEYS")
-                    .arg(pattern)
-                    .query_async(&mut conn)
-                    .await
-                    .map_err(|e| RateLimitError::store(e.to_string()))?;
-
-                for key in keys {
-                    let _: () = conn
-                        .del(&key)
-                        .await
-                        .map_err(|e| RateLimitError::store(e.to_string()))?;
-                }
-            } else {
-                let _: () = conn
-                    .del(pattern)
-                    .await
-                    .map_err(|e| RateLimitError::store(e.to_string()))?;
-            }
-        }
+        let _: () = conn
+            .del(&keys)
+            .await
+            .map_err(|e| RateLimitError::store(e.to_string()))?;
 
         Ok(())
     }
@@ -326,6 +584,7 @@ mod tests {
     // Run with: cargo test --features redis -- --ignored
 
     use super::*;
+    use std::sync::Arc;
 
     #[tokio::test]
     #[ignore = "Requires running Redis instance"]
@@ -337,14 +596,16 @@ mod tests {
 
         // First 5 requests should be allowed
         for i in (0..5).rev() {
-            let (allowed, remaining) = store.token_bucket_check("test", 5, 1.0).await.unwrap();
+            let (allowed, remaining, _) =
+                store.token_bucket_check("test", 5, 1.0, 1).await.unwrap();
             assert!(allowed);
             assert_eq!(remaining, i);
         }
 
-        // 6th should be denied
-        let (allowed, _) = store.token_bucket_check("test", 5, 1.0).await.unwrap();
+        // 6th should be denied until a token has refilled
+        let (allowed, _, ttl) = store.token_bucket_check("test", 5, 1.0, 1).await.unwrap();
         assert!(!allowed);
+        assert!(ttl > Duration::ZERO && ttl <= Duration::from_secs(1));
     }
 
     #[tokio::test]
@@ -359,14 +620,21 @@ mod tests {
 
         // First 3 requests should be allowed
         for i in (0..3).rev() {
-            let (allowed, remaining) = store.sliding_window_check("test", 3, window).await.unwrap();
+            let (allowed, remaining, _) = store
+                .sliding_window_check("test", 3, window, 1)
+                .await
+                .unwrap();
             assert!(allowed);
             assert_eq!(remaining, i);
         }
 
-        // 4th should be denied
-        let (allowed, _) = store.sliding_window_check("test", 3, window).await.unwrap();
+        // 4th should be denied until the first request leaves the window
+        let (allowed, _, ttl) = store
+            .sliding_window_check("test", 3, window, 1)
+            .await
+            .unwrap();
         assert!(!allowed);
+        assert!(ttl > Duration::ZERO && ttl <= window);
     }
 
     #[tokio::test]
@@ -381,13 +649,65 @@ mod tests {
 
         // First 3 requests should be allowed
         for i in (0..3).rev() {
-            let (allowed, remaining) = store.fixed_window_check("test", 3, window).await.unwrap();
+            let (allowed, remaining, _) = store
+                .fixed_window_check("test", 3, window, 1)
+                .await
+                .unwrap();
             assert!(allowed);
             assert_eq!(remaining, i);
         }
 
-        // 4th should be denied
-        let (allowed, _) = store.fixed_window_check("test", 3, window).await.unwrap();
+        // 4th should be denied until the window ends
+        let (allowed, _, ttl) = store
+            .fixed_window_check("test", 3, window, 1)
+            .await
+            .unwrap();
         assert!(!allowed);
+        assert!(ttl > Duration::ZERO && ttl <= window);
+    }
+
+    #[tokio::test]
+    #[ignore = "Requires running Redis instance"]
+    async fn test_redis_concurrent_instances_never_exceed_capacity() {
+        // Two stores stand in for two application instances
+        let stores = [
+            Arc::new(RedisStore::new("redis://localhost:6379").await.unwrap()),
+            Arc::new(RedisStore::new("redis://localhost:6379").await.unwrap()),
+        ];
+        stores[0].reset("concurrent").await.unwrap();
+
+        let window = Duration::from_secs(60);
+        let mut tasks = Vec::new();
+        for i in 0..200 {
+            let store = stores[i % 2].clone();
+            tasks.push(tokio::spawn(async move {
+                let bucket = store
+                    .token_bucket_check("concurrent", 50, 0.001, 1)
+                    .await
+                    .unwrap()
+                    .0;
+                let fixed = store
+                    .fixed_window_check("concurrent", 30, window, 1)
+                    .await
+                    .unwrap()
+                    .0;
+                let gcra = store
+                    .gcra_check("concurrent", window, 20, 1)
+                    .await
+                    .unwrap()
+                    .0;
+                (bucket, fixed, gcra)
+            }));
+        }
+
+        let mut allowed = (0, 0, 0);
+        for task in tasks {
+            let (bucket, fixed, gcra) = task.await.unwrap();
+            allowed.0 += u64::from(bucket);
+            allowed.1 += u64::from(fixed);
+            allowed.2 += u64::from(gcra);
+        }
+
+        assert_eq!(allowed, (50, 30, 20));
     }
 }
@@ -2,7 +2,45 @@
 //!
 //! CSP helps prevent XSS attacks by declaring which dynamic resources are allowed to load.
 
+use armature_core::HttpRequest;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rand::Rng;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::fmt;
+
+/// Per-request nonce that lets inline scripts run under a strict CSP
+///
+/// When the policy uses [`CspConfig::script_src_nonce`], the security
+/// middleware stores a fresh nonce in the request extensions. Templates render
+/// it as `<script nonce="...">`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(String);
+
+impl CspNonce {
+    /// Generate a nonce from 16 cryptographically random bytes
+    pub fn generate() -> Self {
+        let bytes: [u8; 16] = rand::rng().random();
+        Self(STANDARD.encode(bytes))
+    }
+
+    /// Get the nonce value
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the nonce the security middleware stored for a request
+    pub fn from_request(req: &HttpRequest) -> Option<&str> {
+        req.extension::<CspNonce>().map(CspNonce::as_str)
+    }
+}
+
+impl fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
 
 /// Content Security Policy configuration
 #[derive(Debug, Clone)]
@@ -12,6 +50,9 @@ pub struct CspConfig {
 
     /// Report violations only (doesn't enforce)
     pub report_only: bool,
+
+    /// Add a per-request nonce to script-src
+    pub script_nonce: bool,
 }
 
 impl CspConfig {
@@ -20,6 +61,7 @@ impl CspConfig {
         Self {
             directives: HashMap::new(),
             report_only: false,
+            script_nonce: false,
         }
     }
 
@@ -75,11 +117,129 @@ impl CspConfig {
     }
 
     /// Enable report-only mode
+    ///
+    /// Violations are reported but not blocked, so a new policy can be
+    /// observed before it is enforced.
     pub fn report_only(mut self, enabled: bool) -> Self {
         self.report_only = enabled;
         self
     }
 
+    /// Set report-uri directive, where browsers POST violation reports
+    ///
+    /// See [`csp_report_handler`](crate::csp_report::csp_report_handler) for
+    /// an endpoint that receives them.
+    pub fn report_uri(self, uri: impl Into<String>) -> Self {
+        self.directive("report-uri", vec![uri.into()])
+    }
+
+    /// Set report-to directive, naming a Reporting API endpoint
+    ///
+    /// The name must be declared in the `Reporting-Endpoints` header, see
+    /// [`ReportingEndpoints`](crate::reporting_endpoints::ReportingEndpoints)
+    /// and [`SecurityMiddleware::report_csp_to`](crate::SecurityMiddleware::report_csp_to).
+    pub fn report_to(self, group: impl Into<String>) -> Self {
+        self.directive("report-to", vec![group.into()])
+    }
+
+    /// Get the header this policy is sent in, depending on report-only mode
+    pub fn header_name(&self) -> &'static str {
+        if self.report_only {
+            "Content-Security-Policy-Report-Only"
+        } else {
+            "Content-Security-Policy"
+        }
+    }
+
+    /// Allow inline scripts carrying a per-request nonce
+    ///
+    /// Browsers ignore `'unsafe-inline'` once a nonce is present, so this is
+    /// the way to keep inline scripts without it. The nonce is only added when
+    /// the policy is applied through the middleware, see [`CspNonce`].
+    pub fn script_src_nonce(mut self) -> Self {
+        self.script_nonce = true;
+        self
+    }
+
+    /// Allow an inline script by the hash of its content
+    ///
+    /// `content` is the exact text between `<script>` and `</script>`,
+    /// including whitespace. Adds `'sha256-...'` to script-src, which falls
+    /// back to a copy of default-src if unset.
+    ///
+    /// Hashes only match `<script>` elements. Inline event handlers such as
+    /// `onclick="..."` stay blocked unless
+    /// [`allow_inline_event_handlers`](Self::allow_inline_event_handlers) is
+    /// enabled; leaving it off and moving handlers into scripts is safer.
+    pub fn script_hash(self, content: &str) -> Self {
+        self.add_source("script-src", sha256_source(content))
+    }
+
+    /// Allow an inline style by the hash of its content
+    ///
+    /// Adds `'sha256-...'` to style-src, see [`script_hash`](Self::script_hash).
+    pub fn style_hash(self, content: &str) -> Self {
+        self.add_source("style-src", sha256_source(content))
+    }
+
+    /// Let script hashes also match inline event handler attributes
+    ///
+    /// Adds `'unsafe-hashes'` to script-src when enabled. Disabled by default,
+    /// so `onclick` and similar attributes are blocked under a hash or nonce
+    /// policy.
+    pub fn allow_inline_event_handlers(self, allow: bool) -> Self {
+        if allow {
+            self.add_source("script-src", "'unsafe-hashes'".to_string())
+        } else {
+            self.remove_source("script-src", "'unsafe-hashes'")
+        }
+    }
+
+    /// Append a source to a fetch directive, starting from default-src
+    fn add_source(mut self, directive: &str, source: String) -> Self {
+        let mut sources = self
+            .directives
+            .get(directive)
+            .or_else(|| self.directives.get("default-src"))
+            .cloned()
+            .unwrap_or_default();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+        self.directives.insert(directive.to_string(), sources);
+        self
+    }
+
+    fn remove_source(mut self, directive: &str, source: &str) -> Self {
+        if let Some(sources) = self.directives.get_mut(directive) {
+            sources.retain(|existing| existing != source);
+        }
+        self
+    }
+
+    /// Convert to header value, allowing scripts with `nonce`
+    ///
+    /// Without a script-src directive the nonce is added to a copy of
+    /// default-src, which script-src would otherwise fall back to.
+    pub fn to_header_value_with_nonce(&self, nonce: Option<&CspNonce>) -> String {
+        let Some(nonce) = nonce else {
+            return self.to_header_value();
+        };
+
+        let mut config = self.clone();
+        let mut script_src = config
+            .directives
+            .get("script-src")
+            .or_else(|| config.directives.get("default-src"))
+            .cloned()
+            .unwrap_or_default();
+        script_src.push(format!("'nonce-{}'", nonce));
+        config
+            .directives
+            .insert("script-src".to_string(), script_src);
+        config.to_header_value()
+    }
+
     /// Convert to header value
     pub fn to_header_value(&self) -> String {
         let mut parts = Vec::new();
@@ -93,6 +253,11 @@ impl CspConfig {
     }
 }
 
+/// Hash source expression for inline content, as browsers compute it
+fn sha256_source(content: &str) -> String {
+    format!("'sha256-{}'", STANDARD.encode(Sha256::digest(content)))
+}
+
 impl Default for CspConfig {
     fn default() -> Self {
         Self::new()
@@ -137,9 +302,75 @@ mod tests {
         assert!(header.contains("script-src 'self' https://cdn.example.com"));
     }
 
+    #[test]
+    fn test_csp_nonce_added_to_script_src() {
+        let csp = CspConfig::new()
+            .default_src(vec!["'self'".to_string()])
+            .script_src_nonce();
+        let nonce = CspNonce::generate();
+
+        let header = csp.to_header_value_with_nonce(Some(&nonce));
+        assert!(header.contains(&format!("script-src 'self' 'nonce-{}'", nonce)));
+        assert!(!csp.to_header_value().contains("nonce"));
+        assert_ne!(nonce, CspNonce::generate());
+    }
+
     #[test]
     fn test_csp_report_only() {
         let csp = CspConfig::default().report_only(true);
         assert!(csp.report_only);
+        assert_eq!(csp.header_name(), "Content-Security-Policy-Report-Only");
+        assert_eq!(
+            CspConfig::default().header_name(),
+            "Content-Security-Policy"
+        );
+    }
+
+    #[test]
+    fn test_csp_report_directives() {
+        let csp = CspConfig::new()
+            .report_uri("/csp-report")
+            .report_to("csp-endpoint");
+
+        let header = csp.to_header_value();
+        assert!(header.contains("report-uri /csp-report"));
+        assert!(header.contains("report-to csp-endpoint"));
+    }
+
+    #[test]
+    fn test_csp_script_hash() {
+        // Example from MDN's script-src documentation
+        let csp = CspConfig::new()
+            .script_src(vec!["'self'".to_string()])
+            .script_hash("alert('Hello, world.');");
+
+        assert_eq!(
+            csp.to_header_value(),
+            "script-src 'self' 'sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng='"
+        );
+    }
+
+    #[test]
+    fn test_csp_style_hash_falls_back_to_default_src() {
+        let csp = CspConfig::new()
+            .default_src(vec!["'self'".to_string()])
+            .style_hash("body { color: red; }")
+            .style_hash("body { color: red; }");
+
+        let style_src = &csp.directives["style-src"];
+        assert_eq!(style_src.len(), 2);
+        assert_eq!(style_src[0], "'self'");
+        assert!(style_src[1].starts_with("'sha256-"));
+    }
+
+    #[test]
+    fn test_csp_inline_event_handlers() {
+        let csp = CspConfig::new()
+            .script_hash("doSomething();")
+            .allow_inline_event_handlers(true);
+        assert!(csp.to_header_value().contains("'unsafe-hashes'"));
+
+        let csp = csp.allow_inline_event_handlers(false);
+        assert!(!csp.to_header_value().contains("'unsafe-hashes'"));
     }
 }
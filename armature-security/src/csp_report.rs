@@ -0,0 +1,191 @@
+//! CSP violation reports
+//!
+//! Browsers POST a report to the policy's `report-uri` whenever it is
+//! violated. [`csp_report_handler`] parses them for logging or metrics.
+
+use armature_core::handler::{BoxedHandler, from_legacy_handler};
+use armature_core::{Error, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Content type browsers use for `report-uri` reports
+pub const CSP_REPORT_CONTENT_TYPE: &str = "application/csp-report";
+
+/// A violation report sent to a CSP `report-uri`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct CspReport {
+    /// Page on which the violation occurred
+    pub document_uri: String,
+
+    /// Referrer of that page
+    pub referrer: Option<String>,
+
+    /// Directive that was violated
+    pub violated_directive: String,
+
+    /// Directive whose enforcement caused the violation
+    pub effective_directive: Option<String>,
+
+    /// The policy as sent in the header
+    pub original_policy: String,
+
+    /// Resource that was blocked
+    pub blocked_uri: Option<String>,
+
+    /// `enforce` or `report`
+    pub disposition: Option<String>,
+
+    /// Script or stylesheet in which the violation occurred
+    pub source_file: Option<String>,
+
+    /// Line in the source file
+    pub line_number: Option<u32>,
+
+    /// Column in the source file
+    pub column_number: Option<u32>,
+
+    /// HTTP status of the page
+    pub status_code: Option<u16>,
+
+    /// First characters of the blocked inline script or style
+    pub script_sample: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReportBody {
+    #[serde(rename = "csp-report")]
+    csp_report: CspReport,
+}
+
+impl CspReport {
+    /// Parse a `{"csp-report": {...}}` request body
+    pub fn parse(body: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice::<ReportBody>(body).map(|body| body.csp_report)
+    }
+}
+
+/// Handle a CSP report request, passing the parsed report to `callback`
+///
+/// Responds with 204 No Content on success, 405 for methods other than POST,
+/// 415 for bodies that are not `application/csp-report` or JSON, and 400 for
+/// reports that cannot be parsed.
+pub async fn handle_csp_report<F>(req: HttpRequest, callback: &F) -> Result<HttpResponse, Error>
+where
+    F: Fn(CspReport) + ?Sized,
+{
+    if !req.method.eq_ignore_ascii_case("POST") {
+        return Ok(HttpResponse::new(405).with_header("Allow".to_string(), "POST".to_string()));
+    }
+
+    let content_type = req
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_ascii_lowercase()
+        });
+    if !matches!(
+        content_type.as_deref(),
+        Some(CSP_REPORT_CONTENT_TYPE | "application/json")
+    ) {
+        return Ok(HttpResponse::new(415));
+    }
+
+    match CspReport::parse(&req.body) {
+        Ok(report) => {
+            callback(report);
+            Ok(HttpResponse::no_content())
+        }
+        Err(_) => Ok(HttpResponse::bad_request()),
+    }
+}
+
+/// Create a handler for a CSP `report-uri` endpoint
+///
+/// Also available as [`SecurityMiddleware::csp_report_handler`](crate::SecurityMiddleware::csp_report_handler).
+pub fn csp_report_handler<F>(callback: F) -> BoxedHandler
+where
+    F: Fn(CspReport) + Send + Sync + 'static,
+{
+    let callback = Arc::new(callback);
+    from_legacy_handler(Arc::new(move |req: HttpRequest| {
+        let callback = callback.clone();
+        Box::pin(async move { handle_csp_report(req, callback.as_ref()).await })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    const REPORT: &str = r#"{
+        "csp-report": {
+            "document-uri": "https://example.com/page",
+            "referrer": "",
+            "violated-directive": "script-src-elem",
+            "effective-directive": "script-src-elem",
+            "original-policy": "script-src 'self'; report-uri /csp-report",
+            "disposition": "report",
+            "blocked-uri": "https://evil.example.com/x.js",
+            "line-number": 12,
+            "status-code": 200
+        }
+    }"#;
+
+    fn report_request(content_type: &str, body: &str) -> HttpRequest {
+        let mut req = HttpRequest::new("POST".to_string(), "/csp-report".to_string());
+        req.headers
+            .insert("content-type".to_string(), content_type.to_string());
+        req.body = body.as_bytes().to_vec();
+        req
+    }
+
+    #[test]
+    fn test_parse_report() {
+        let report = CspReport::parse(REPORT.as_bytes()).unwrap();
+        assert_eq!(report.document_uri, "https://example.com/page");
+        assert_eq!(report.violated_directive, "script-src-elem");
+        assert_eq!(
+            report.blocked_uri.as_deref(),
+            Some("https://evil.example.com/x.js")
+        );
+        assert_eq!(report.line_number, Some(12));
+    }
+
+    #[tokio::test]
+    async fn test_handler_forwards_report() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let seen = reports.clone();
+        let handler = csp_report_handler(move |report| seen.lock().unwrap().push(report));
+
+        let response = handler
+            .call(report_request(CSP_REPORT_CONTENT_TYPE, REPORT))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, 204);
+        assert_eq!(reports.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handler_rejects_bad_requests() {
+        let callback = |_report: CspReport| panic!("unexpected report");
+
+        let req = report_request("text/plain", REPORT);
+        assert_eq!(handle_csp_report(req, &callback).await.unwrap().status, 415);
+
+        let req = report_request(CSP_REPORT_CONTENT_TYPE, "not json");
+        assert_eq!(handle_csp_report(req, &callback).await.unwrap().status, 400);
+
+        let mut req = report_request(CSP_REPORT_CONTENT_TYPE, REPORT);
+        req.method = "GET".to_string();
+        assert_eq!(handle_csp_report(req, &callback).await.unwrap().status, 405);
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Forces browsers to use HTTPS.
 
+use armature_core::{HttpRequest, Scheme};
+
 /// HSTS configuration
 #[derive(Debug, Clone)]
 pub struct HstsConfig {
@@ -13,6 +15,12 @@ pub struct HstsConfig {
 
     /// Preload (submit to browser preload list)
     pub preload: bool,
+
+    /// Only send the header on secure requests
+    pub only_https: bool,
+
+    /// Treat `X-Forwarded-Proto` as the request scheme
+    pub trust_forwarded_proto: bool,
 }
 
 impl HstsConfig {
@@ -22,6 +30,8 @@ impl HstsConfig {
             max_age,
             include_subdomains: true,
             preload: false,
+            only_https: true,
+            trust_forwarded_proto: false,
         }
     }
 
@@ -37,6 +47,42 @@ impl HstsConfig {
         self
     }
 
+    /// Only send the header on secure requests (the default)
+    ///
+    /// Browsers ignore HSTS received over plain HTTP, and the spec forbids
+    /// sending it there. The header is still sent when the scheme is unknown,
+    /// i.e. the request carries no [`Scheme`] extension. Only the middleware
+    /// path sees the request; `apply` always adds the header.
+    pub fn only_https(mut self, only_https: bool) -> Self {
+        self.only_https = only_https;
+        self
+    }
+
+    /// Trust `X-Forwarded-Proto` to tell whether a request is secure
+    ///
+    /// Enable this behind a TLS-terminating proxy that sets the header. Without
+    /// such a proxy, clients could set it themselves.
+    pub fn trust_forwarded_proto(mut self, trust: bool) -> Self {
+        self.trust_forwarded_proto = trust;
+        self
+    }
+
+    /// Check if the header should be sent in response to a request
+    pub fn applies_to(&self, req: &HttpRequest) -> bool {
+        if !self.only_https {
+            return true;
+        }
+
+        if self.trust_forwarded_proto
+            && let Some(proto) = forwarded_proto(req)
+        {
+            return proto.eq_ignore_ascii_case("https");
+        }
+
+        // Omitting HSTS weakens security, so only skip it on known plain HTTP
+        req.scheme() != Some(Scheme::Http)
+    }
+
     /// Convert to header value
     pub fn to_header_value(&self) -> String {
         let mut parts = vec![format!("max-age={}", self.max_age)];
@@ -53,6 +99,16 @@ impl HstsConfig {
     }
 }
 
+/// Get the scheme the client used, as reported by the nearest proxy.
+fn forwarded_proto(req: &HttpRequest) -> Option<&str> {
+    let (_, value) = req
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("x-forwarded-proto"))?;
+    // Chained proxies append their hop, so the first entry is the client's
+    value.split(',').next().map(str::trim)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,6 +128,41 @@ mod tests {
         assert_eq!(config.to_header_value(), "max-age=31536000");
     }
 
+    fn request(scheme: Option<Scheme>, forwarded_proto: Option<&str>) -> HttpRequest {
+        let mut req = HttpRequest::new("GET".to_string(), "/".to_string());
+        if let Some(scheme) = scheme {
+            req.insert_extension(scheme);
+        }
+        if let Some(proto) = forwarded_proto {
+            req.headers
+                .insert("x-forwarded-proto".to_string(), proto.to_string());
+        }
+        req
+    }
+
+    #[test]
+    fn test_hsts_only_https() {
+        let config = HstsConfig::new(31536000);
+        assert!(config.applies_to(&request(Some(Scheme::Https), None)));
+        assert!(!config.applies_to(&request(Some(Scheme::Http), None)));
+        assert!(config.applies_to(&request(None, None)));
+
+        let config = config.only_https(false);
+        assert!(config.applies_to(&request(Some(Scheme::Http), None)));
+    }
+
+    #[test]
+    fn test_hsts_forwarded_proto() {
+        let untrusted = HstsConfig::new(31536000);
+        assert!(!untrusted.applies_to(&request(Some(Scheme::Http), Some("https"))));
+
+        let trusted = HstsConfig::new(31536000).trust_forwarded_proto(true);
+        assert!(trusted.applies_to(&request(Some(Scheme::Http), Some("https"))));
+        assert!(trusted.applies_to(&request(Some(Scheme::Http), Some("https, http"))));
+        assert!(!trusted.applies_to(&request(Some(Scheme::Https), Some("http"))));
+        assert!(trusted.applies_to(&request(Some(Scheme::Https), None)));
+    }
+
     #[test]
     fn test_hsts_preload() {
         let config = HstsConfig::new(31536000).preload(true);
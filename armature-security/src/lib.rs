@@ -108,15 +108,19 @@
 pub mod content_security_policy;
 pub mod content_type_options;
 pub mod cors;
+pub mod csp_report;
 pub mod dns_prefetch_control;
 pub mod download_options;
 pub mod expect_ct;
 pub mod frame_guard;
 pub mod hsts;
+pub mod permissions_policy;
 pub mod permitted_cross_domain_policies;
 pub mod powered_by;
 pub mod referrer_policy;
+pub mod reporting_endpoints;
 pub mod request_signing;
+pub mod sri;
 pub mod xss_filter;
 
 use armature_core::HttpResponse;
@@ -132,6 +136,8 @@ pub struct SecurityMiddleware {
     pub dns_prefetch_control: dns_prefetch_control::DnsPrefetchControl,
 
     /// Expect-CT configuration
+    ///
+    /// Expect-CT is deprecated and only sent when set explicitly.
     pub expect_ct: Option<expect_ct::ExpectCtConfig>,
 
     /// Frame Guard (X-Frame-Options)
@@ -158,6 +164,15 @@ pub struct SecurityMiddleware {
     /// X-Permitted-Cross-Domain-Policies
     pub permitted_cross_domain_policies:
         permitted_cross_domain_policies::PermittedCrossDomainPolicies,
+
+    /// Permissions-Policy configuration
+    pub permissions_policy: Option<permissions_policy::PermissionsPolicy>,
+
+    /// Reporting-Endpoints configuration
+    pub reporting_endpoints: Option<reporting_endpoints::ReportingEndpoints>,
+
+    /// Configurations used instead of this one for path prefixes
+    pub overrides: Vec<(String, SecurityMiddleware)>,
 }
 
 impl SecurityMiddleware {
@@ -176,6 +191,9 @@ impl SecurityMiddleware {
             download_options: download_options::DownloadOptions::NoOpen,
             permitted_cross_domain_policies:
                 permitted_cross_domain_policies::PermittedCrossDomainPolicies::None,
+            permissions_policy: None,
+            reporting_endpoints: None,
+            overrides: Vec::new(),
         }
     }
 
@@ -195,6 +213,10 @@ impl SecurityMiddleware {
     }
 
     /// Enable Expect-CT
+    ///
+    /// Expect-CT is deprecated and ignored by current browsers, which enforce
+    /// Certificate Transparency themselves. For violation reports use
+    /// [`with_reporting_endpoints`](Self::with_reporting_endpoints) instead.
     pub fn with_expect_ct(mut self, config: expect_ct::ExpectCtConfig) -> Self {
         self.expect_ct = Some(config);
         self
@@ -230,13 +252,112 @@ impl SecurityMiddleware {
         self
     }
 
+    /// Enable Permissions-Policy
+    pub fn with_permissions_policy(
+        mut self,
+        policy: permissions_policy::PermissionsPolicy,
+    ) -> Self {
+        self.permissions_policy = Some(policy);
+        self
+    }
+
+    /// Use `config` instead of this configuration for requests under `prefix`
+    ///
+    /// `prefix` matches whole path segments, so `/api` (or `/api/*`) covers
+    /// `/api` and `/api/users` but not `/apiary`. When several prefixes match,
+    /// the longest wins. The override replaces this configuration entirely;
+    /// start from a clone to change only some headers:
+    ///
+    /// ```
+    /// use armature_security::SecurityMiddleware;
+    ///
+    /// let base = SecurityMiddleware::default();
+    /// let api = SecurityMiddleware {
+    ///     csp: None,
+    ///     ..base.clone()
+    /// };
+    /// let security = base.override_for("/api", api);
+    /// assert!(security.config_for("/api/users").csp.is_none());
+    /// assert!(security.config_for("/").csp.is_some());
+    /// ```
+    pub fn override_for(mut self, prefix: impl Into<String>, config: SecurityMiddleware) -> Self {
+        let prefix = prefix.into();
+        let prefix = prefix.strip_suffix("/*").unwrap_or(&prefix);
+        let prefix = prefix.trim_end_matches('/').to_string();
+        self.overrides.retain(|(existing, _)| *existing != prefix);
+        self.overrides.push((prefix, config));
+        self
+    }
+
+    /// Get the configuration that applies to `path`
+    ///
+    /// Overrides of the selected configuration are not consulted again.
+    pub fn config_for(&self, path: &str) -> &SecurityMiddleware {
+        self.overrides
+            .iter()
+            .filter(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self, |(_, config)| config)
+    }
+
+    /// Set the Reporting API endpoints
+    pub fn with_reporting_endpoints(
+        mut self,
+        endpoints: reporting_endpoints::ReportingEndpoints,
+    ) -> Self {
+        self.reporting_endpoints = Some(endpoints);
+        self
+    }
+
+    /// Send CSP violation reports to `url` through the Reporting API
+    ///
+    /// Adds `url` to Reporting-Endpoints as `name` and points the CSP's
+    /// `report-to` directive at it. Call after [`with_csp`](Self::with_csp);
+    /// without a CSP only the endpoint is added.
+    pub fn report_csp_to(mut self, name: impl Into<String>, url: impl Into<String>) -> Self {
+        let name = name.into();
+        let endpoints = self.reporting_endpoints.take().unwrap_or_default();
+        self.reporting_endpoints = Some(endpoints.endpoint(name.clone(), url));
+        self.csp = self.csp.map(|csp| csp.report_to(name));
+        self
+    }
+
     /// Apply security headers to a response
-    pub fn apply(&self, mut response: HttpResponse) -> HttpResponse {
+    ///
+    /// A CSP using [`CspConfig::script_src_nonce`](content_security_policy::CspConfig::script_src_nonce)
+    /// only gets its nonce when applied through the [`Middleware`](armature_core::Middleware)
+    /// implementation, which generates one per request. Path overrides are
+    /// also only applied there; use [`config_for`](Self::config_for) here.
+    pub fn apply(&self, response: HttpResponse) -> HttpResponse {
+        self.apply_with_nonce(response, None)
+    }
+
+    /// Apply security headers to a response, allowing scripts with `nonce`
+    pub fn apply_with_nonce(
+        &self,
+        response: HttpResponse,
+        nonce: Option<&content_security_policy::CspNonce>,
+    ) -> HttpResponse {
+        self.apply_headers(response, nonce, true)
+    }
+
+    fn apply_headers(
+        &self,
+        mut response: HttpResponse,
+        nonce: Option<&content_security_policy::CspNonce>,
+        include_hsts: bool,
+    ) -> HttpResponse {
         let mut headers = HashMap::new();
 
         // Content Security Policy
         if let Some(ref csp) = self.csp {
-            headers.insert("Content-Security-Policy".to_string(), csp.to_header_value());
+            headers.insert(
+                csp.header_name().to_string(),
+                csp.to_header_value_with_nonce(nonce),
+            );
         }
 
         // DNS Prefetch Control
@@ -257,7 +378,9 @@ impl SecurityMiddleware {
         );
 
         // HSTS
-        if let Some(ref hsts) = self.hsts {
+        if let Some(ref hsts) = self.hsts
+            && include_hsts
+        {
             headers.insert(
                 "Strict-Transport-Security".to_string(),
                 hsts.to_header_value(),
@@ -294,6 +417,23 @@ impl SecurityMiddleware {
             self.permitted_cross_domain_policies.to_header_value(),
         );
 
+        // Permissions Policy
+        if let Some(ref policy) = self.permissions_policy
+            && !policy.is_empty()
+        {
+            headers.insert("Permissions-Policy".to_string(), policy.to_header_value());
+        }
+
+        // Reporting Endpoints
+        if let Some(ref endpoints) = self.reporting_endpoints
+            && !endpoints.is_empty()
+        {
+            headers.insert(
+                "Reporting-Endpoints".to_string(),
+                endpoints.to_header_value(),
+            );
+        }
+
         // Apply all headers
         for (key, value) in headers {
             response.headers.insert(key, value);
@@ -307,12 +447,38 @@ impl SecurityMiddleware {
         response
     }
 
+    /// Create a handler for a CSP `report-uri` endpoint
+    ///
+    /// Accepts `application/csp-report` POST bodies and passes each parsed
+    /// report to `callback`, e.g. for logging or metrics.
+    ///
+    /// ```no_run
+    /// use armature_core::*;
+    /// use armature_security::SecurityMiddleware;
+    ///
+    /// let mut router = Router::new();
+    /// router.add_route(Route {
+    ///     method: HttpMethod::POST,
+    ///     path: "/csp-report".to_string(),
+    ///     handler: SecurityMiddleware::csp_report_handler(|report| {
+    ///         eprintln!("CSP violation: {}", report.violated_directive);
+    ///     }),
+    ///     constraints: None,
+    /// });
+    /// ```
+    pub fn csp_report_handler<F>(callback: F) -> armature_core::BoxedHandler
+    where
+        F: Fn(csp_report::CspReport) + Send + Sync + 'static,
+    {
+        csp_report::csp_report_handler(callback)
+    }
+
     /// Convenience method to enable all common security features (recommended defaults)
     pub fn enable_all(max_age_seconds: u64) -> Self {
         Self {
             csp: Some(content_security_policy::CspConfig::default()),
             dns_prefetch_control: dns_prefetch_control::DnsPrefetchControl::Off,
-            expect_ct: Some(expect_ct::ExpectCtConfig::new(max_age_seconds)),
+            expect_ct: None,
             frame_guard: frame_guard::FrameGuard::Deny,
             hsts: Some(hsts::HstsConfig::new(max_age_seconds)),
             hide_powered_by: true,
@@ -322,6 +488,9 @@ impl SecurityMiddleware {
             download_options: download_options::DownloadOptions::NoOpen,
             permitted_cross_domain_policies:
                 permitted_cross_domain_policies::PermittedCrossDomainPolicies::None,
+            permissions_policy: Some(permissions_policy::PermissionsPolicy::restrictive()),
+            reporting_endpoints: None,
+            overrides: Vec::new(),
         }
     }
 }
@@ -339,12 +508,15 @@ impl Default for SecurityMiddleware {
 /// ```
 pub mod prelude {
     pub use crate::SecurityMiddleware;
-    pub use crate::content_security_policy::CspConfig;
+    pub use crate::content_security_policy::{CspConfig, CspNonce};
     pub use crate::cors::CorsConfig;
     pub use crate::frame_guard::FrameGuard;
     pub use crate::hsts::HstsConfig;
+    pub use crate::permissions_policy::{AllowList, PermissionsPolicy};
     pub use crate::referrer_policy::ReferrerPolicy;
+    pub use crate::reporting_endpoints::ReportingEndpoints;
     pub use crate::request_signing::{RequestSigner, RequestSigningMiddleware, RequestVerifier};
+    pub use crate::sri::{Algo, SriHelper};
 }
 
 /// Implement the core Middleware trait for SecurityMiddleware
@@ -353,7 +525,7 @@ pub mod prelude {
 impl armature_core::Middleware for SecurityMiddleware {
     async fn handle(
         &self,
-        req: armature_core::HttpRequest,
+        mut req: armature_core::HttpRequest,
         next: Box<
             dyn FnOnce(
                     armature_core::HttpRequest,
@@ -366,16 +538,61 @@ impl armature_core::Middleware for SecurityMiddleware {
                 > + Send,
         >,
     ) -> Result<armature_core::HttpResponse, armature_core::Error> {
+        let config = self.config_for(&req.path);
+
+        // Generate the CSP nonce up front so handlers can render it
+        let nonce = config
+            .csp
+            .as_ref()
+            .filter(|csp| csp.script_nonce)
+            .map(|_| content_security_policy::CspNonce::generate());
+        if let Some(ref nonce) = nonce {
+            req.insert_extension(nonce.clone());
+        }
+
+        // HSTS is only meaningful over HTTPS, so skip it on known plain HTTP
+        let include_hsts = config
+            .hsts
+            .as_ref()
+            .is_some_and(|hsts| hsts.applies_to(&req));
+
         // Call the next handler first
         let response = next(req).await?;
         // Apply security headers to the response
-        Ok(self.apply(response))
+        Ok(config.apply_headers(response, nonce.as_ref(), include_hsts))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use armature_core::{HttpRequest, Middleware, Scheme};
+    use content_security_policy::{CspConfig, CspNonce};
+
+    fn get(path: &str) -> HttpRequest {
+        HttpRequest::new("GET".to_string(), path.to_string())
+    }
+
+    /// Run a request through the middleware, echoing its CSP nonce back
+    async fn handle(middleware: &SecurityMiddleware, req: HttpRequest) -> HttpResponse {
+        middleware
+            .handle(
+                req,
+                Box::new(|req: HttpRequest| {
+                    Box::pin(async move {
+                        let mut response = HttpResponse::ok();
+                        if let Some(nonce) = CspNonce::from_request(&req) {
+                            response
+                                .headers
+                                .insert("X-Nonce".to_string(), nonce.to_string());
+                        }
+                        Ok(response)
+                    })
+                }),
+            )
+            .await
+            .unwrap()
+    }
 
     #[test]
     fn test_security_middleware_new() {
@@ -403,6 +620,150 @@ mod tests {
         assert!(secured.headers.contains_key("X-XSS-Protection"));
         assert!(secured.headers.contains_key("Strict-Transport-Security"));
         assert!(secured.headers.contains_key("Content-Security-Policy"));
+        assert_eq!(
+            secured.headers.get("Permissions-Policy"),
+            Some(&permissions_policy::PermissionsPolicy::restrictive().to_header_value())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_csp_nonce_per_request() {
+        let middleware = SecurityMiddleware::new().with_csp(
+            CspConfig::new()
+                .default_src(vec!["'self'".to_string()])
+                .script_src(vec!["'self'".to_string()])
+                .script_src_nonce(),
+        );
+
+        let first = handle(&middleware, get("/")).await;
+        let second = handle(&middleware, get("/")).await;
+
+        let nonce = first.headers.get("X-Nonce").unwrap();
+        assert_ne!(Some(nonce), second.headers.get("X-Nonce"));
+        assert!(
+            first
+                .headers
+                .get("Content-Security-Policy")
+                .unwrap()
+                .contains(&format!("script-src 'self' 'nonce-{}'", nonce))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_nonce_without_nonce_mode() {
+        let middleware = SecurityMiddleware::default();
+        let response = handle(&middleware, get("/")).await;
+
+        assert!(!response.headers.contains_key("X-Nonce"));
+        assert!(
+            !response
+                .headers
+                .get("Content-Security-Policy")
+                .unwrap()
+                .contains("nonce")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hsts_only_over_https() {
+        let middleware = SecurityMiddleware::new().with_hsts(hsts::HstsConfig::new(31536000));
+
+        let mut req = get("/");
+        req.insert_extension(Scheme::Http);
+        let response = handle(&middleware, req).await;
+        assert!(!response.headers.contains_key("Strict-Transport-Security"));
+
+        let mut req = get("/");
+        req.insert_extension(Scheme::Https);
+        let response = handle(&middleware, req).await;
+        assert!(response.headers.contains_key("Strict-Transport-Security"));
+
+        // An unknown scheme still gets the header
+        let response = handle(&middleware, get("/")).await;
+        assert!(response.headers.contains_key("Strict-Transport-Security"));
+    }
+
+    #[tokio::test]
+    async fn test_hsts_trusts_forwarded_proto() {
+        let middleware = SecurityMiddleware::new()
+            .with_hsts(hsts::HstsConfig::new(31536000).trust_forwarded_proto(true));
+
+        let mut req = get("/");
+        req.insert_extension(Scheme::Http);
+        req.headers
+            .insert("x-forwarded-proto".to_string(), "https".to_string());
+        let response = handle(&middleware, req).await;
+        assert!(response.headers.contains_key("Strict-Transport-Security"));
+    }
+
+    #[tokio::test]
+    async fn test_override_for_path_prefix() {
+        let base = SecurityMiddleware::new().with_csp(CspConfig::default());
+        let api = SecurityMiddleware {
+            csp: None,
+            ..base.clone()
+        };
+        let middleware = base
+            .override_for("/api/*", api)
+            .override_for("/api/admin", SecurityMiddleware::new());
+
+        let response = handle(&middleware, get("/")).await;
+        assert!(response.headers.contains_key("Content-Security-Policy"));
+
+        let response = handle(&middleware, get("/api/users")).await;
+        assert!(!response.headers.contains_key("Content-Security-Policy"));
+        assert!(response.headers.contains_key("X-Frame-Options"));
+
+        let response = handle(&middleware, get("/apiary")).await;
+        assert!(response.headers.contains_key("Content-Security-Policy"));
+
+        assert!(std::ptr::eq(
+            middleware.config_for("/api/admin/users"),
+            &middleware.overrides[1].1
+        ));
+    }
+
+    #[test]
+    fn test_report_csp_to_endpoint() {
+        let middleware = SecurityMiddleware::new()
+            .with_csp(CspConfig::new().default_src(vec!["'self'".to_string()]))
+            .report_csp_to("csp", "https://example.com/csp-reports");
+        let secured = middleware.apply(HttpResponse::ok());
+
+        assert_eq!(
+            secured.headers.get("Reporting-Endpoints").unwrap(),
+            "csp=\"https://example.com/csp-reports\""
+        );
+        let csp = secured.headers.get("Content-Security-Policy").unwrap();
+        assert!(csp.contains("report-to csp"));
+    }
+
+    #[test]
+    fn test_expect_ct_only_when_configured() {
+        let secured = SecurityMiddleware::default().apply(HttpResponse::ok());
+        assert!(!secured.headers.contains_key("Expect-CT"));
+
+        let secured = SecurityMiddleware::default()
+            .with_expect_ct(expect_ct::ExpectCtConfig::new(86400))
+            .apply(HttpResponse::ok());
+        assert!(secured.headers.contains_key("Expect-CT"));
+    }
+
+    #[test]
+    fn test_csp_report_only_header() {
+        let middleware = SecurityMiddleware::new().with_csp(
+            CspConfig::default()
+                .report_only(true)
+                .report_uri("/csp-report"),
+        );
+        let secured = middleware.apply(HttpResponse::ok());
+
+        assert!(!secured.headers.contains_key("Content-Security-Policy"));
+        let header = secured
+            .headers
+            .get("Content-Security-Policy-Report-Only")
+            .unwrap();
+        assert!(header.contains("report-uri /csp-report"));
     }
 
     #[test]
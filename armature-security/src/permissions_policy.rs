@@ -0,0 +1,240 @@
+//! Permissions-Policy (formerly Feature-Policy)
+//!
+//! Controls which browser features the page and its frames may use.
+
+/// Origins allowed to use a feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowList {
+    /// Any origin (`*`)
+    All,
+
+    /// The listed origins; an empty list disables the feature
+    Origins(Vec<String>),
+}
+
+impl AllowList {
+    /// Disable the feature everywhere
+    pub fn none() -> Self {
+        AllowList::Origins(Vec::new())
+    }
+
+    /// Allow only the page's own origin
+    pub fn self_only() -> Self {
+        AllowList::Origins(vec!["self".to_string()])
+    }
+
+    /// Convert to the structured header form, e.g. `(self "https://a.com")`
+    pub fn to_header_value(&self) -> String {
+        match self {
+            AllowList::All => "*".to_string(),
+            AllowList::Origins(origins) => {
+                let items: Vec<String> = origins.iter().map(|o| allowlist_item(o)).collect();
+                format!("({})", items.join(" "))
+            }
+        }
+    }
+}
+
+/// Accept CSP-style sources such as `'self'` as well as bare keywords
+fn allowlist_item(origin: &str) -> String {
+    match origin.trim_matches('\'') {
+        "*" => "*".to_string(),
+        "self" => "self".to_string(),
+        "src" => "src".to_string(),
+        origin => format!("\"{}\"", origin),
+    }
+}
+
+impl From<Vec<&str>> for AllowList {
+    fn from(origins: Vec<&str>) -> Self {
+        AllowList::Origins(origins.into_iter().map(String::from).collect())
+    }
+}
+
+/// Permissions-Policy configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionsPolicy {
+    /// Features and their allowlists, in header order
+    pub features: Vec<(String, AllowList)>,
+}
+
+impl PermissionsPolicy {
+    /// Create a policy with no features restricted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Disable features most sites never use
+    ///
+    /// Turns off sensors, camera, microphone, geolocation, payment, and USB.
+    pub fn restrictive() -> Self {
+        Self::new()
+            .accelerometer(AllowList::none())
+            .camera(AllowList::none())
+            .geolocation(AllowList::none())
+            .gyroscope(AllowList::none())
+            .magnetometer(AllowList::none())
+            .microphone(AllowList::none())
+            .payment(AllowList::none())
+            .usb(AllowList::none())
+    }
+
+    /// Set the allowlist for any feature, replacing an existing one
+    pub fn feature(mut self, name: impl Into<String>, allow: impl Into<AllowList>) -> Self {
+        let name = name.into();
+        let allow = allow.into();
+        match self
+            .features
+            .iter_mut()
+            .find(|(feature, _)| *feature == name)
+        {
+            Some((_, existing)) => *existing = allow,
+            None => self.features.push((name, allow)),
+        }
+        self
+    }
+
+    /// Set accelerometer
+    pub fn accelerometer(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("accelerometer", allow)
+    }
+
+    /// Set autoplay
+    pub fn autoplay(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("autoplay", allow)
+    }
+
+    /// Set camera
+    pub fn camera(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("camera", allow)
+    }
+
+    /// Set display-capture
+    pub fn display_capture(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("display-capture", allow)
+    }
+
+    /// Set encrypted-media
+    pub fn encrypted_media(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("encrypted-media", allow)
+    }
+
+    /// Set fullscreen
+    pub fn fullscreen(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("fullscreen", allow)
+    }
+
+    /// Set geolocation
+    pub fn geolocation(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("geolocation", allow)
+    }
+
+    /// Set gyroscope
+    pub fn gyroscope(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("gyroscope", allow)
+    }
+
+    /// Set magnetometer
+    pub fn magnetometer(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("magnetometer", allow)
+    }
+
+    /// Set microphone
+    pub fn microphone(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("microphone", allow)
+    }
+
+    /// Set midi
+    pub fn midi(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("midi", allow)
+    }
+
+    /// Set payment
+    pub fn payment(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("payment", allow)
+    }
+
+    /// Set picture-in-picture
+    pub fn picture_in_picture(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("picture-in-picture", allow)
+    }
+
+    /// Set publickey-credentials-get (WebAuthn)
+    pub fn publickey_credentials_get(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("publickey-credentials-get", allow)
+    }
+
+    /// Set screen-wake-lock
+    pub fn screen_wake_lock(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("screen-wake-lock", allow)
+    }
+
+    /// Set sync-xhr
+    pub fn sync_xhr(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("sync-xhr", allow)
+    }
+
+    /// Set usb
+    pub fn usb(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("usb", allow)
+    }
+
+    /// Set xr-spatial-tracking
+    pub fn xr_spatial_tracking(self, allow: impl Into<AllowList>) -> Self {
+        self.feature("xr-spatial-tracking", allow)
+    }
+
+    /// Check if no features are set
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Convert to header value
+    pub fn to_header_value(&self) -> String {
+        self.features
+            .iter()
+            .map(|(feature, allow)| format!("{}={}", feature, allow.to_header_value()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permissions_policy_directives() {
+        let policy = PermissionsPolicy::new()
+            .geolocation(vec!["'self'", "https://maps.example.com"])
+            .camera(vec![])
+            .fullscreen(AllowList::All);
+
+        assert_eq!(
+            policy.to_header_value(),
+            "geolocation=(self \"https://maps.example.com\"), camera=(), fullscreen=*"
+        );
+    }
+
+    #[test]
+    fn test_permissions_policy_custom_feature() {
+        let policy = PermissionsPolicy::new()
+            .feature("interest-cohort", AllowList::none())
+            .feature("bluetooth", AllowList::self_only())
+            .feature("interest-cohort", vec!["self"]);
+
+        assert_eq!(
+            policy.to_header_value(),
+            "interest-cohort=(self), bluetooth=(self)"
+        );
+    }
+
+    #[test]
+    fn test_permissions_policy_restrictive() {
+        let header = PermissionsPolicy::restrictive().to_header_value();
+        assert!(header.contains("camera=()"));
+        assert!(header.contains("microphone=()"));
+        assert!(header.contains("geolocation=()"));
+        assert!(PermissionsPolicy::new().is_empty());
+    }
+}
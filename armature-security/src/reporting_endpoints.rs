@@ -0,0 +1,87 @@
+//! Reporting-Endpoints (Reporting API)
+//!
+//! Names the URLs browsers send reports to. Policies such as CSP refer to an
+//! endpoint by name with their `report-to` directive.
+
+/// Reporting-Endpoints configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReportingEndpoints {
+    /// Endpoint names and URLs, in header order
+    pub endpoints: Vec<(String, String)>,
+}
+
+impl ReportingEndpoints {
+    /// Create an empty set of endpoints
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named endpoint, replacing one with the same name
+    ///
+    /// Names should be lowercase tokens such as `csp` or `default`. URLs must
+    /// be absolute `https` URLs (or same-origin paths) for browsers to use them.
+    pub fn endpoint(mut self, name: impl Into<String>, url: impl Into<String>) -> Self {
+        let name = name.into();
+        let url = url.into();
+        match self
+            .endpoints
+            .iter_mut()
+            .find(|(existing, _)| *existing == name)
+        {
+            Some((_, existing)) => *existing = url,
+            None => self.endpoints.push((name, url)),
+        }
+        self
+    }
+
+    /// Get the URL of a named endpoint
+    pub fn url(&self, name: &str) -> Option<&str> {
+        self.endpoints
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .map(|(_, url)| url.as_str())
+    }
+
+    /// Check if no endpoints are configured
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Convert to header value, e.g. `csp="https://example.com/csp"`
+    pub fn to_header_value(&self) -> String {
+        self.endpoints
+            .iter()
+            .map(|(name, url)| format!("{}=\"{}\"", name, url.replace(['"', '\\'], "")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reporting_endpoints_header() {
+        let endpoints = ReportingEndpoints::new()
+            .endpoint("csp", "https://example.com/csp-reports")
+            .endpoint("default", "https://example.com/reports");
+
+        assert_eq!(
+            endpoints.to_header_value(),
+            "csp=\"https://example.com/csp-reports\", default=\"https://example.com/reports\""
+        );
+    }
+
+    #[test]
+    fn test_reporting_endpoints_replace() {
+        let endpoints = ReportingEndpoints::new()
+            .endpoint("csp", "https://old.example.com")
+            .endpoint("csp", "https://example.com/csp");
+
+        assert_eq!(endpoints.endpoints.len(), 1);
+        assert_eq!(endpoints.url("csp"), Some("https://example.com/csp"));
+        assert_eq!(endpoints.url("default"), None);
+        assert!(ReportingEndpoints::new().is_empty());
+    }
+}
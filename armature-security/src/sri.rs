@@ -0,0 +1,138 @@
+//! Subresource Integrity (SRI)
+//!
+//! Lets browsers reject a script or stylesheet whose content differs from the
+//! hash in its tag, e.g. when a CDN serving a third-party bundle is compromised.
+//!
+//! Compute the integrity string once at startup or build time and pass the
+//! attributes to your templates:
+//!
+//! ```
+//! use armature_security::sri::{Algo, SriHelper};
+//!
+//! let bundle = b"console.log('app');";
+//! let attrs = SriHelper::attributes(bundle, Algo::Sha384);
+//! let tag = format!("<script src=\"https://cdn.example.com/app.js\" {attrs}></script>");
+//! assert!(tag.contains("integrity=\"sha384-"));
+//! ```
+//!
+//! With Handlebars or Tera, insert the snippet unescaped (`{{{sri}}}` or
+//! `{{ sri | safe }}`); it only contains base64 and fixed attribute names.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// Hash algorithm for integrity strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algo {
+    /// SHA-256
+    Sha256,
+    /// SHA-384, the recommended default
+    #[default]
+    Sha384,
+    /// SHA-512
+    Sha512,
+}
+
+impl Algo {
+    /// Get the algorithm prefix used in integrity strings
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Algo::Sha256 => "sha256",
+            Algo::Sha384 => "sha384",
+            Algo::Sha512 => "sha512",
+        }
+    }
+}
+
+/// Subresource Integrity helper
+pub struct SriHelper;
+
+impl SriHelper {
+    /// Compute an integrity string such as `sha384-...` for asset bytes
+    pub fn hash(bytes: &[u8], algo: Algo) -> String {
+        let digest = match algo {
+            Algo::Sha256 => STANDARD.encode(Sha256::digest(bytes)),
+            Algo::Sha384 => STANDARD.encode(Sha384::digest(bytes)),
+            Algo::Sha512 => STANDARD.encode(Sha512::digest(bytes)),
+        };
+        format!("{}-{}", algo.prefix(), digest)
+    }
+
+    /// Produce `integrity="..." crossorigin="anonymous"` for a tag
+    ///
+    /// `crossorigin` is required for cross-origin assets, since the browser
+    /// can only check the integrity of responses it is allowed to read.
+    pub fn attributes(bytes: &[u8], algo: Algo) -> String {
+        format!(
+            "integrity=\"{}\" crossorigin=\"anonymous\"",
+            Self::hash(bytes, algo)
+        )
+    }
+
+    /// Produce a `<script>` tag for an asset
+    pub fn script_tag(src: &str, bytes: &[u8], algo: Algo) -> String {
+        format!(
+            "<script src=\"{}\" {}></script>",
+            escape_attribute(src),
+            Self::attributes(bytes, algo)
+        )
+    }
+
+    /// Produce a `<link rel="stylesheet">` tag for an asset
+    pub fn stylesheet_tag(href: &str, bytes: &[u8], algo: Algo) -> String {
+        format!(
+            "<link rel=\"stylesheet\" href=\"{}\" {}>",
+            escape_attribute(href),
+            Self::attributes(bytes, algo)
+        )
+    }
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = b"alert('Hello, world.');";
+
+    #[test]
+    fn test_hash_fixture() {
+        assert_eq!(
+            SriHelper::hash(FIXTURE, Algo::Sha384),
+            "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO"
+        );
+        assert_eq!(
+            SriHelper::hash(FIXTURE, Algo::Sha256),
+            "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng="
+        );
+        assert!(SriHelper::hash(FIXTURE, Algo::Sha512).starts_with("sha512-Q2bFTOhEALkN8hOm"));
+    }
+
+    #[test]
+    fn test_tags() {
+        let integrity = SriHelper::hash(FIXTURE, Algo::Sha384);
+
+        assert_eq!(
+            SriHelper::script_tag("/app.js?v=1&x=2", FIXTURE, Algo::Sha384),
+            format!(
+                "<script src=\"/app.js?v=1&amp;x=2\" integrity=\"{}\" crossorigin=\"anonymous\"></script>",
+                integrity
+            )
+        );
+        assert_eq!(
+            SriHelper::stylesheet_tag("/app.css", FIXTURE, Algo::Sha384),
+            format!(
+                "<link rel=\"stylesheet\" href=\"/app.css\" integrity=\"{}\" crossorigin=\"anonymous\">",
+                integrity
+            )
+        );
+    }
+}
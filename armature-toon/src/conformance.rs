@@ -0,0 +1,153 @@
+//! Conformance with the TOON specification.
+//!
+//! Runs the encode and decode fixtures in `tests/fixtures`, and, with the
+//! `serde-toon-compat` feature, compares the codec with `serde_toon`, which
+//! this crate wrapped before it had its own.
+
+use crate::value::Value;
+use crate::{Delimiter, ToonSerializer, de};
+
+const ENCODE: &str = include_str!("../tests/fixtures/encode.json");
+const DECODE: &str = include_str!("../tests/fixtures/decode.json");
+
+/// One fixture case.
+struct Case {
+    name: String,
+    input: Value,
+    expected: Option<Value>,
+    delimiter: Delimiter,
+}
+
+/// Read the cases of a fixture file, keeping object keys in file order.
+fn cases(fixtures: &str) -> Vec<Case> {
+    let file: Value = serde_json::from_str(fixtures).unwrap();
+    let Some(Value::Array(tests)) = field(&file, "tests") else {
+        panic!("fixture file without tests");
+    };
+
+    tests
+        .iter()
+        .map(|test| {
+            let Some(Value::String(name)) = field(test, "name") else {
+                panic!("fixture without a name");
+            };
+            let delimiter = match field(test, "options").and_then(|o| field(o, "delimiter")) {
+                Some(Value::String(d)) if d == "\t" => Delimiter::Tab,
+                Some(Value::String(d)) if d == "|" => Delimiter::Pipe,
+                _ => Delimiter::Comma,
+            };
+            Case {
+                name: name.clone(),
+                input: field(test, "input").cloned().unwrap(),
+                expected: field(test, "expected").cloned(),
+                delimiter,
+            }
+        })
+        .collect()
+}
+
+fn field<'v>(value: &'v Value, key: &str) -> Option<&'v Value> {
+    match value {
+        Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+fn string(value: &Value) -> &str {
+    match value {
+        Value::String(s) => s,
+        other => panic!("expected a string, found {:?}", other),
+    }
+}
+
+/// Compare numbers by value, so `1500` matches `1.5e3`.
+fn numbers_as_f64(value: Value) -> Value {
+    match value {
+        Value::Number(n) => Value::Number(
+            serde_json::Number::from_f64(n.as_f64().unwrap()).unwrap_or_else(|| 0.into()),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(numbers_as_f64).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, numbers_as_f64(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Compare ignoring key order and number representation.
+fn same(a: &Value, b: &Value) -> bool {
+    serde_json::Value::from(numbers_as_f64(a.clone()))
+        == serde_json::Value::from(numbers_as_f64(b.clone()))
+}
+
+fn encoder(delimiter: Delimiter) -> ToonSerializer {
+    ToonSerializer::new().tabular(true).delimiter(delimiter)
+}
+
+#[test]
+fn test_encode_fixtures() {
+    for case in cases(ENCODE) {
+        let expected = string(case.expected.as_ref().unwrap());
+        let encoded = encoder(case.delimiter).serialize(&case.input).unwrap();
+        assert_eq!(encoded, expected, "{}", case.name);
+
+        // And the output reads back as the input; tables put every row's
+        // keys in header order
+        let decoded = de::parse(&encoded).unwrap();
+        assert!(same(&decoded, &case.input), "{}", case.name);
+    }
+}
+
+#[test]
+fn test_decode_fixtures() {
+    for case in cases(DECODE) {
+        let result = de::parse(string(&case.input));
+        match case.expected {
+            Some(expected) => assert_eq!(
+                numbers_as_f64(result.unwrap()),
+                numbers_as_f64(expected),
+                "{}",
+                case.name
+            ),
+            None => assert!(result.is_err(), "{}: {:?}", case.name, result),
+        }
+    }
+}
+
+/// Compare with the `serde_toon` encoder and decoder on every fixture.
+///
+/// Both codecs must read each other's output as the input value. Cases where
+/// the text differs are printed, since `serde_toon` may lay out some arrays
+/// differently.
+#[cfg(feature = "serde-toon-compat")]
+#[test]
+fn test_serde_toon_compat() {
+    let mut differences = Vec::new();
+    for case in cases(ENCODE) {
+        let delimiter = case.delimiter;
+        if delimiter != Delimiter::Comma {
+            continue;
+        }
+
+        let old = serde_toon::to_string(&case.input).unwrap();
+        let new = encoder(delimiter).serialize(&case.input).unwrap();
+        assert!(
+            same(&de::parse(&old).unwrap(), &case.input),
+            "{}",
+            case.name
+        );
+
+        let read_back: Value = serde_toon::from_str(&new).unwrap();
+        assert!(same(&read_back, &case.input), "{}", case.name);
+
+        if old != new {
+            differences.push(format!("{}:\n{}\n---\n{}", case.name, old, new));
+        }
+    }
+    for difference in differences {
+        eprintln!("{}\n", difference);
+    }
+}
@@ -0,0 +1,769 @@
+//! TOON decoder.
+//!
+//! Parses TOON text into a [`Value`] tree, one line at a time. Indentation is
+//! two spaces per level, matching the encoder. Lines are pulled from the
+//! input on demand, which lets `ToonArrayReader` decode a top-level array
+//! item by item.
+
+use crate::value::Value;
+use crate::{Result, ToonError};
+use serde::de::DeserializeOwned;
+use serde_json::Number;
+use std::borrow::Cow;
+#[cfg(feature = "streaming")]
+use std::io::BufRead;
+
+/// Spaces per nesting level.
+const INDENT: usize = 2;
+
+/// Longest input excerpt included in a parse error.
+const SNIPPET_LEN: usize = 40;
+
+/// Parse a complete TOON document.
+pub(crate) fn parse(input: &str) -> Result<Value> {
+    Parser::new(Source::Str(input.lines())).document()
+}
+
+/// Parse a document, accepting arrays whose item count differs from their
+/// `[N]` header.
+pub(crate) fn parse_lenient(input: &str) -> Result<Value> {
+    let mut parser = Parser::new(Source::Str(input.lines()));
+    parser.lenient = true;
+    parser.document()
+}
+
+/// Deserialize a parsed value.
+pub(crate) fn deserialize<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value.into()).map_err(|e| ToonError::DeserializeError(e.to_string()))
+}
+
+/// Where lines come from.
+pub(crate) enum Source<'a> {
+    Str(std::str::Lines<'a>),
+    #[cfg(feature = "streaming")]
+    Reader(Box<dyn BufRead + 'a>),
+}
+
+impl<'a> Source<'a> {
+    fn next_line(&mut self) -> Result<Option<Cow<'a, str>>> {
+        match self {
+            Source::Str(lines) => Ok(lines.next().map(Cow::Borrowed)),
+            #[cfg(feature = "streaming")]
+            Source::Reader(reader) => {
+                let mut line = String::new();
+                if reader.read_line(&mut line)? == 0 {
+                    return Ok(None);
+                }
+                Ok(Some(Cow::Owned(line)))
+            }
+        }
+    }
+}
+
+/// A non-blank input line.
+#[derive(Debug)]
+pub(crate) struct Line<'a> {
+    /// 1-based line number.
+    pub(crate) number: usize,
+    pub(crate) depth: usize,
+    /// The line without its indentation or trailing whitespace.
+    pub(crate) content: Cow<'a, str>,
+}
+
+/// The `[N]{fields}:` part of an array field.
+#[derive(Debug)]
+pub(crate) struct ArrayHeader {
+    pub(crate) len: usize,
+    /// Separator of inline values, rows, and fields, declared by a `\t` or
+    /// `|` after the length; a comma otherwise.
+    pub(crate) delimiter: char,
+    /// Column names of a tabular array.
+    pub(crate) fields: Option<Vec<String>>,
+}
+
+/// A field line split into its parts.
+#[derive(Debug)]
+pub(crate) struct Head<'a> {
+    /// None for a bare array header, as used at the root and in list items.
+    pub(crate) key: Option<String>,
+    pub(crate) array: Option<ArrayHeader>,
+    /// Everything after the colon, trimmed.
+    pub(crate) rest: &'a str,
+}
+
+pub(crate) struct Parser<'a> {
+    source: Source<'a>,
+    number: usize,
+    peeked: Option<Line<'a>>,
+    /// Ignore `[N]` counts that disagree with the items.
+    lenient: bool,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(source: Source<'a>) -> Self {
+        Self {
+            source,
+            number: 0,
+            peeked: None,
+            lenient: false,
+        }
+    }
+
+    /// Look at the next non-blank line.
+    pub(crate) fn peek(&mut self) -> Result<Option<&Line<'a>>> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_line()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    /// Take the next non-blank line.
+    pub(crate) fn next_line(&mut self) -> Result<Option<Line<'a>>> {
+        match self.peeked.take() {
+            Some(line) => Ok(Some(line)),
+            None => self.read_line(),
+        }
+    }
+
+    /// Take the next non-blank line if it matches `accept`.
+    fn next_if(&mut self, accept: impl FnOnce(&Line<'a>) -> bool) -> Result<Option<Line<'a>>> {
+        match self.peek()? {
+            Some(line) if accept(line) => self.next_line(),
+            _ => Ok(None),
+        }
+    }
+
+    fn read_line(&mut self) -> Result<Option<Line<'a>>> {
+        while let Some(raw) = self.source.next_line()? {
+            self.number += 1;
+            let content = raw.trim_start_matches(' ');
+            if content.trim().is_empty() {
+                continue;
+            }
+
+            let indent = raw.len() - content.len();
+            if content.starts_with('\t') {
+                return Err(parse_error(
+                    "tabs are not allowed in indentation",
+                    self.number,
+                    indent + 1,
+                    snippet(raw.trim(), 0),
+                ));
+            }
+            if indent % INDENT != 0 {
+                return Err(parse_error(
+                    format!("indentation must be a multiple of {} spaces", INDENT),
+                    self.number,
+                    indent + 1,
+                    snippet(raw.trim(), 0),
+                ));
+            }
+
+            let end = raw.trim_end().len();
+            let content = match raw {
+                Cow::Borrowed(raw) => Cow::Borrowed(&raw[indent..end]),
+                Cow::Owned(raw) => Cow::Owned(raw[indent..end].to_string()),
+            };
+            return Ok(Some(Line {
+                number: self.number,
+                depth: indent / INDENT,
+                content,
+            }));
+        }
+        Ok(None)
+    }
+
+    fn document(&mut self) -> Result<Value> {
+        let Some(first) = self.next_line()? else {
+            return Ok(Value::Object(Vec::new()));
+        };
+        if first.depth != 0 {
+            return Err(first.error("unexpected indentation"));
+        }
+
+        let value = match head(&first, &first.content)? {
+            Some(Head {
+                key: None,
+                array: Some(header),
+                rest,
+            }) => self.array(&first, header, rest, 1)?,
+            Some(head) => {
+                let mut fields = vec![self.field(&first, head, 0)?];
+                fields.extend(self.object(0)?);
+                Value::Object(fields)
+            }
+            None => primitive(&first, &first.content)?,
+        };
+
+        match self.peek()? {
+            Some(line) => Err(line.error("unexpected content after the document")),
+            None => Ok(value),
+        }
+    }
+
+    /// Parse the fields at `depth`.
+    fn object(&mut self, depth: usize) -> Result<Vec<(String, Value)>> {
+        let mut fields = Vec::new();
+        while let Some(line) = self.peek()? {
+            if line.depth < depth {
+                break;
+            }
+            if line.depth > depth {
+                return Err(line.error("unexpected indentation"));
+            }
+            if is_list_item(&line.content) {
+                return Err(line.error("list item outside of an array"));
+            }
+
+            let Some(line) = self.next_line()? else {
+                break;
+            };
+            match head(&line, &line.content)? {
+                Some(head) => fields.push(self.field(&line, head, depth)?),
+                None => return Err(line.error("expected `key: value`")),
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Parse a field whose nested content is at `depth + 1`.
+    fn field(&mut self, line: &Line<'a>, head: Head<'_>, depth: usize) -> Result<(String, Value)> {
+        let Head {
+            key: Some(key),
+            array,
+            rest,
+        } = head
+        else {
+            return Err(line.error("expected `key: value`"));
+        };
+
+        let value = match array {
+            Some(header) => self.array(line, header, rest, depth + 1)?,
+            None if rest.is_empty() => Value::Object(self.object(depth + 1)?),
+            None => primitive(line, rest)?,
+        };
+        Ok((key, value))
+    }
+
+    /// Parse an array's items, which are inline or at `depth`.
+    fn array(
+        &mut self,
+        line: &Line<'a>,
+        header: ArrayHeader,
+        rest: &str,
+        depth: usize,
+    ) -> Result<Value> {
+        let mut items = Vec::new();
+        if let Some(fields) = header.fields {
+            if !rest.is_empty() {
+                return Err(line.error_at(rest, "unexpected values after a table header"));
+            }
+            while let Some(row) = self.row(&fields, header.delimiter, depth, items.len() + 1)? {
+                items.push(row);
+            }
+        } else if !rest.is_empty() {
+            items = inline(line, rest, header.delimiter)?;
+        } else {
+            while let Some(item) = self.list_item(depth)? {
+                items.push(item);
+            }
+        }
+
+        if items.len() != header.len && !self.lenient {
+            return Err(count_error(line, header.len, items.len()));
+        }
+        Ok(Value::Array(items))
+    }
+
+    /// Parse the next row of a table whose rows are at `depth`, where `row` is
+    /// its 1-based position.
+    pub(crate) fn row(
+        &mut self,
+        fields: &[String],
+        delimiter: char,
+        depth: usize,
+        row: usize,
+    ) -> Result<Option<Value>> {
+        let Some(line) = self.next_if(|line| line.depth == depth)? else {
+            return Ok(None);
+        };
+
+        let values = split_values(&line.content, delimiter)
+            .map_err(|at| line.error_at(at, "unterminated string").in_row(row))?;
+        if values.len() != fields.len() {
+            let message = format!(
+                "row has {} values but the table has {} columns",
+                values.len(),
+                fields.len()
+            );
+            // Point at the first extra value, or the end of a short row
+            let at = values
+                .get(fields.len())
+                .copied()
+                .unwrap_or(&line.content[line.content.len()..]);
+            return Err(line.error_at(at, message).in_row(row));
+        }
+        let values = fields
+            .iter()
+            .zip(values)
+            .map(|(field, token)| Ok((field.clone(), primitive(&line, token)?)))
+            .collect::<Result<_>>()
+            .map_err(|e| e.in_row(row))?;
+        Ok(Some(Value::Object(values)))
+    }
+
+    /// Parse the next `- item` with its hyphen at `depth`.
+    pub(crate) fn list_item(&mut self, depth: usize) -> Result<Option<Value>> {
+        let Some(line) = self.next_if(|line| line.depth == depth && is_list_item(&line.content))?
+        else {
+            return Ok(None);
+        };
+
+        let content = line.content[1..].trim_start();
+        if content.is_empty() {
+            return Ok(Some(Value::Object(Vec::new())));
+        }
+        let item = match head(&line, content)? {
+            Some(Head {
+                key: None,
+                array: Some(header),
+                rest,
+            }) => self.array(&line, header, rest, depth + 1)?,
+            // An object whose first field shares the hyphen's line
+            Some(head) => {
+                let mut fields = vec![self.field(&line, head, depth + 1)?];
+                fields.extend(self.object(depth + 1)?);
+                Value::Object(fields)
+            }
+            None => primitive(&line, content)?,
+        };
+        Ok(Some(item))
+    }
+}
+
+impl Line<'_> {
+    /// An error at the start of the line.
+    pub(crate) fn error(&self, message: impl Into<String>) -> ToonError {
+        self.error_at(&self.content, message)
+    }
+
+    /// An error at `at`, a slice of the line's content.
+    pub(crate) fn error_at(&self, at: &str, message: impl Into<String>) -> ToonError {
+        let offset = (at.as_ptr() as usize)
+            .checked_sub(self.content.as_ptr() as usize)
+            .filter(|offset| *offset <= self.content.len())
+            .unwrap_or(0);
+        parse_error(
+            message,
+            self.number,
+            self.depth * INDENT + self.content[..offset].chars().count() + 1,
+            snippet(&self.content, offset),
+        )
+    }
+}
+
+impl ToonError {
+    /// Record the table row a parse error occurred in.
+    fn in_row(mut self, index: usize) -> Self {
+        if let ToonError::ParseError { row, .. } = &mut self {
+            *row = Some(index);
+        }
+        self
+    }
+
+    /// Shift the line of a parse error in a document that starts after
+    /// `lines` lines of a larger input.
+    pub(crate) fn offset_lines(mut self, lines: usize) -> Self {
+        if let ToonError::ParseError { line, .. } = &mut self {
+            *line += lines;
+        }
+        self
+    }
+}
+
+pub(crate) fn parse_error(
+    message: impl Into<String>,
+    line: usize,
+    column: usize,
+    snippet: String,
+) -> ToonError {
+    ToonError::ParseError {
+        message: message.into(),
+        line,
+        column,
+        snippet,
+        row: None,
+    }
+}
+
+/// Up to [`SNIPPET_LEN`] characters of `content` around byte `offset`.
+pub(crate) fn snippet(content: &str, offset: usize) -> String {
+    let start = content[..offset]
+        .chars()
+        .count()
+        .saturating_sub(SNIPPET_LEN / 4);
+    let mut snippet: String = content.chars().skip(start).take(SNIPPET_LEN).collect();
+    if start > 0 {
+        snippet.insert_str(0, "...");
+    }
+    if content.chars().count() > start + SNIPPET_LEN {
+        snippet.push_str("...");
+    }
+    snippet
+}
+
+pub(crate) fn count_error(line: &Line<'_>, declared: usize, found: usize) -> ToonError {
+    line.error(format!(
+        "array declares {} items but has {}",
+        declared, found
+    ))
+}
+
+/// Parse the values of an inline array.
+pub(crate) fn inline(line: &Line<'_>, rest: &str, delimiter: char) -> Result<Vec<Value>> {
+    split_values(rest, delimiter)
+        .map_err(|at| line.error_at(at, "unterminated string"))?
+        .into_iter()
+        .map(|token| primitive(line, token))
+        .collect()
+}
+
+fn is_list_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Split a line into key, array header, and value, or None if it is not a
+/// field.
+pub(crate) fn head<'s>(line: &Line<'_>, content: &'s str) -> Result<Option<Head<'s>>> {
+    let (key, after_key) = if content.starts_with('[') {
+        (None, content)
+    } else if content.starts_with('"') {
+        let (key, rest) = quoted(content).map_err(|(at, message)| line.error_at(at, message))?;
+        (Some(key), rest)
+    } else {
+        match content.find([':', '[']) {
+            Some(0) | None => return Ok(None),
+            Some(end) => (Some(content[..end].trim_end().to_string()), &content[end..]),
+        }
+    };
+
+    let (array, after_header) = match after_key.strip_prefix('[') {
+        Some(header) => match array_header(header) {
+            Some((header, rest)) => (Some(header), rest),
+            None => return Ok(None),
+        },
+        None => (None, after_key),
+    };
+
+    match after_header.strip_prefix(':') {
+        Some(rest) => Ok(Some(Head {
+            key,
+            array,
+            rest: rest.trim(),
+        })),
+        None => Ok(None),
+    }
+}
+
+/// Parse `N]` and an optional `{fields}` after the opening bracket.
+fn array_header(s: &str) -> Option<(ArrayHeader, &str)> {
+    let (len, rest) = s.split_once(']')?;
+    let (len, delimiter) = match len.strip_suffix(['\t', '|']) {
+        Some(digits) => (digits, len[digits.len()..].chars().next()?),
+        None => (len, ','),
+    };
+    let len = len.parse().ok()?;
+
+    let Some(fields) = rest.strip_prefix('{') else {
+        return Some((
+            ArrayHeader {
+                len,
+                delimiter,
+                fields: None,
+            },
+            rest,
+        ));
+    };
+    let (fields, rest) = fields.split_once('}')?;
+    let fields = split_values(fields, delimiter)
+        .ok()?
+        .into_iter()
+        .map(|field| match field.starts_with('"') {
+            true => quoted(field).ok().map(|(key, _)| key),
+            false => Some(field.to_string()),
+        })
+        .collect::<Option<_>>()?;
+
+    Some((
+        ArrayHeader {
+            len,
+            delimiter,
+            fields: Some(fields),
+        },
+        rest,
+    ))
+}
+
+/// Split delimited values, keeping delimiters inside quotes. Fails with the
+/// input from an unterminated opening quote onward.
+fn split_values(s: &str, delimiter: char) -> std::result::Result<Vec<&str>, &str> {
+    let mut values = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote.is_some() => escaped = true,
+            '"' if quote.is_some() => quote = None,
+            '"' => quote = Some(i),
+            c if c == delimiter && quote.is_none() => {
+                values.push(s[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    if let Some(quote) = quote {
+        return Err(&s[quote..]);
+    }
+
+    values.push(s[start..].trim());
+    Ok(values)
+}
+
+/// Parse a quoted string at the start of `s`, returning it and the rest. Fails
+/// with the offending input and a message.
+fn quoted(s: &str) -> std::result::Result<(String, &str), (&str, String)> {
+    let mut out = String::new();
+    let mut chars = s.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((out, &s[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => out.push('"'),
+                Some((_, '\\')) => out.push('\\'),
+                Some((_, 'n')) => out.push('\n'),
+                Some((_, 'r')) => out.push('\r'),
+                Some((_, 't')) => out.push('\t'),
+                Some((_, other)) => {
+                    return Err((&s[i..], format!("invalid escape `\\{}`", other)));
+                }
+                None => break,
+            },
+            c => out.push(c),
+        }
+    }
+    Err((s, "unterminated string".to_string()))
+}
+
+/// Parse a single primitive token from `line`.
+fn primitive(line: &Line<'_>, token: &str) -> Result<Value> {
+    let token = token.trim();
+    if token.starts_with('"') {
+        let (s, rest) = quoted(token).map_err(|(at, message)| line.error_at(at, message))?;
+        if !rest.trim().is_empty() {
+            return Err(line.error_at(rest.trim(), "unexpected characters after string"));
+        }
+        return Ok(Value::String(s));
+    }
+
+    Ok(match token {
+        "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => number(token).map_or_else(|| Value::String(token.to_string()), Value::Number),
+    })
+}
+
+/// Parse a number, or None if the token should be read as a string.
+fn number(token: &str) -> Option<Number> {
+    let digits = token.strip_prefix('-').unwrap_or(token);
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    // Leading zeros such as "007" mark a string
+    if digits.len() > 1
+        && digits.starts_with('0')
+        && digits[1..].starts_with(|c: char| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    if let Ok(n) = token.parse::<i64>() {
+        return Some(n.into());
+    }
+    if let Ok(n) = token.parse::<u64>() {
+        return Some(n.into());
+    }
+    // Reject forms like "1." or "inf" that Rust parses but JSON does not
+    if token.ends_with('.')
+        || !token
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+    {
+        return None;
+    }
+    token.parse::<f64>().ok().and_then(Number::from_f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obj(fields: Vec<(&str, Value)>) -> Value {
+        Value::Object(
+            fields
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    fn s(v: &str) -> Value {
+        Value::String(v.to_string())
+    }
+
+    fn n(v: i64) -> Value {
+        Value::Number(v.into())
+    }
+
+    #[test]
+    fn test_parse_object() {
+        let value =
+            parse("id: 1\nname: Ada Lovelace\ntags[2]: admin,ops\nmeta:\n  size: 1").unwrap();
+        assert_eq!(
+            value,
+            obj(vec![
+                ("id", n(1)),
+                ("name", s("Ada Lovelace")),
+                ("tags", Value::Array(vec![s("admin"), s("ops")])),
+                ("meta", obj(vec![("size", n(1))])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_list_items() {
+        let value = parse("members[2]:\n  - id: 1\n    tags[0]:\n  - [2]: a,b\nafter: x").unwrap();
+        assert_eq!(
+            value,
+            obj(vec![
+                (
+                    "members",
+                    Value::Array(vec![
+                        obj(vec![("id", n(1)), ("tags", Value::Array(vec![]))]),
+                        Value::Array(vec![s("a"), s("b")]),
+                    ])
+                ),
+                ("after", s("x")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let value = parse("[2]{id,name}:\n  1,Ada\n  2,\"Grace, Admiral\"").unwrap();
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                obj(vec![("id", n(1)), ("name", s("Ada"))]),
+                obj(vec![("id", n(2)), ("name", s("Grace, Admiral"))]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_primitives() {
+        assert_eq!(parse("42").unwrap(), n(42));
+        assert_eq!(
+            parse("-1.5").unwrap(),
+            Value::Number(Number::from_f64(-1.5).unwrap())
+        );
+        assert_eq!(parse("007").unwrap(), s("007"));
+        assert_eq!(parse("\"true\"").unwrap(), s("true"));
+        assert_eq!(parse("null").unwrap(), Value::Null);
+        assert_eq!(parse("").unwrap(), obj(vec![]));
+        assert_eq!(parse("\"a\\nb\"").unwrap(), s("a\nb"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("tags[3]: a,b").is_err());
+        assert!(parse("[1]{a,b}:\n  1").is_err());
+        assert!(parse("a: 1\n   b: 2").is_err());
+        assert!(parse("a: \"open").is_err());
+        assert!(parse("a:\n    b: 1").is_err());
+    }
+
+    #[test]
+    fn test_parse_delimiters() {
+        let expected = Value::Array(vec![
+            obj(vec![("id", n(1)), ("note", s("a,b"))]),
+            obj(vec![("id", n(2)), ("note", s("c|d"))]),
+        ]);
+        assert_eq!(
+            parse("[2\t]{id\tnote}:\n  1\ta,b\n  2\tc|d").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse("[2|]{id|note}:\n  1|a,b\n  2|\"c|d\"").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse("tags[2|]: a b|c,d").unwrap(),
+            obj(vec![("tags", Value::Array(vec![s("a b"), s("c,d")]))])
+        );
+    }
+
+    fn position(input: &str) -> (usize, usize, String, Option<usize>) {
+        match parse(input).unwrap_err() {
+            ToonError::ParseError {
+                line,
+                column,
+                snippet,
+                row,
+                ..
+            } => (line, column, snippet, row),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_error_positions() {
+        // A table row with an extra value
+        let input = "users[2]{id,name}:\n  1,Ada\n  2,Grace,admin";
+        assert_eq!(
+            position(input),
+            (3, 11, "2,Grace,admin".to_string(), Some(2))
+        );
+        let message = parse(input).unwrap_err().to_string();
+        assert!(message.contains("line 3, column 11 (row 2)"), "{}", message);
+        assert!(message.contains("has 3 values but the table has 2 columns"));
+
+        // An unterminated string in a nested field
+        assert_eq!(
+            position("user:\n  name: \"Ada"),
+            (2, 9, "name: \"Ada".to_string(), None)
+        );
+        // A bad escape, with the snippet starting shortly before it
+        assert_eq!(
+            position("tags[2]: a,b,c,\"d\\q\""),
+            (1, 18, "...: a,b,c,\"d\\q\"".to_string(), None)
+        );
+        // Indentation
+        assert_eq!(position("a:\n   b: 1").0, 2);
+        assert_eq!(position("a: 1\n  b: 2"), (2, 3, "b: 2".to_string(), None));
+        // A count mismatch points at the header
+        assert_eq!(position("x: 1\ntags[3]: a,b").0, 2);
+
+        // Long lines are shortened around the error
+        let long = format!("note: \"{}", "x".repeat(100));
+        let (_, column, snippet, _) = position(&long);
+        assert_eq!(column, 7);
+        assert!(snippet.starts_with("note: \"xx") && snippet.ends_with("..."));
+        assert!(snippet.len() < 50);
+    }
+}
@@ -13,6 +13,24 @@ pub enum ToonError {
     #[error("TOON deserialization error: {0}")]
     DeserializeError(String),
 
+    /// Malformed TOON input.
+    #[error(
+        "TOON parse error at line {line}, column {column}{}: {message} near `{snippet}`",
+        in_row(.row)
+    )]
+    ParseError {
+        /// What is wrong.
+        message: String,
+        /// 1-based line number.
+        line: usize,
+        /// 1-based column, in characters.
+        column: usize,
+        /// The input around the error, shortened to a few dozen characters.
+        snippet: String,
+        /// 1-based row within a table, for errors in a table row.
+        row: Option<usize>,
+    },
+
     /// UTF-8 encoding error.
     #[error("UTF-8 encoding error: {0}")]
     Utf8Error(String),
@@ -22,8 +40,12 @@ pub enum ToonError {
     IoError(#[from] std::io::Error),
 }
 
-impl From<serde_toon::Error> for ToonError {
-    fn from(err: serde_toon::Error) -> Self {
-        ToonError::SerializeError(err.to_string())
+fn in_row(row: &Option<usize>) -> String {
+    row.map(|row| format!(" (row {})", row)).unwrap_or_default()
+}
+
+impl serde::ser::Error for ToonError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ToonError::SerializeError(msg.to_string())
     }
 }
@@ -1,10 +1,19 @@
 //! HTTP integration for TOON responses.
 
-use crate::{TOON_CONTENT_TYPE, ToonError, to_string};
-use armature_core::http::HttpResponse;
-use bytes::Bytes;
+use crate::{TOON_CONTENT_TYPE, ToonError, to_writer};
+use armature_core::http::{HttpRequest, HttpResponse};
 use serde::Serialize;
 
+/// Serialize straight into the response body, without an intermediate string.
+fn toon_response<T: Serialize + ?Sized>(status: u16, value: &T) -> Result<HttpResponse, ToonError> {
+    let mut response = HttpResponse::new(status);
+    to_writer(&mut response.body, value)?;
+    response
+        .headers
+        .insert("Content-Type".to_string(), TOON_CONTENT_TYPE.to_string());
+    Ok(response)
+}
+
 /// TOON response wrapper for Armature HTTP responses.
 pub struct Toon<T>(pub T);
 
@@ -16,12 +25,7 @@ impl<T: Serialize> Toon<T> {
 
     /// Convert to HTTP response.
     pub fn into_response(self) -> Result<HttpResponse, ToonError> {
-        let body = to_string(&self.0)?;
-        let mut response = HttpResponse::new(200).with_bytes_body(Bytes::from(body));
-        response
-            .headers
-            .insert("Content-Type".to_string(), TOON_CONTENT_TYPE.to_string());
-        Ok(response)
+        toon_response(200, &self.0)
     }
 }
 
@@ -36,21 +40,42 @@ pub trait ToonResponseExt {
 
 impl ToonResponseExt for HttpResponse {
     fn toon<T: Serialize>(value: T) -> Result<HttpResponse, ToonError> {
-        let body = to_string(&value)?;
-        let mut response = HttpResponse::new(200).with_bytes_body(Bytes::from(body));
-        response
-            .headers
-            .insert("Content-Type".to_string(), TOON_CONTENT_TYPE.to_string());
-        Ok(response)
+        toon_response(200, &value)
     }
 
     fn toon_with_status<T: Serialize>(status: u16, value: T) -> Result<HttpResponse, ToonError> {
-        let body = to_string(&value)?;
-        let mut response = HttpResponse::new(status).with_bytes_body(Bytes::from(body));
-        response
-            .headers
-            .insert("Content-Type".to_string(), TOON_CONTENT_TYPE.to_string());
-        Ok(response)
+        toon_response(status, &value)
+    }
+}
+
+/// Respond with TOON or JSON, whichever the request's `Accept` header prefers.
+///
+/// TOON is only chosen when the client asks for it: it needs a higher quality
+/// value than JSON, or the same quality and an earlier position. Requests
+/// without an `Accept` header, or with only wildcards such as `*/*`, get JSON.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// async fn list_users(req: HttpRequest) -> Result<HttpResponse, Error> {
+///     let users = load_users().await?;
+///     Ok(negotiate_response(&req, &users)?)
+/// }
+/// ```
+pub fn negotiate_response<T: Serialize>(
+    req: &HttpRequest,
+    value: &T,
+) -> Result<HttpResponse, ToonError> {
+    let accept = req
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("accept"))
+        .map(|(_, value)| value.as_str());
+
+    if ToonContentNegotiator::prefers_toon(accept) {
+        toon_response(200, value)
+    } else {
+        HttpResponse::json(value).map_err(|e| ToonError::SerializeError(e.to_string()))
     }
 }
 
@@ -60,33 +85,70 @@ pub struct ToonContentNegotiator;
 impl ToonContentNegotiator {
     /// Check if the request accepts TOON format.
     pub fn accepts_toon(accept_header: Option<&str>) -> bool {
-        match accept_header {
-            Some(accept) => {
-                accept.contains(TOON_CONTENT_TYPE)
-                    || accept.contains("application/*")
-                    || accept.contains("*/*")
-            }
-            None => false,
-        }
+        accept_header
+            .and_then(|accept| Self::preference(accept, TOON_CONTENT_TYPE))
+            .is_some_and(|(quality, _)| quality > 0.0)
     }
 
     /// Check if the request prefers TOON over JSON.
+    ///
+    /// Compares quality values (`q=`), then position in the header. Ties
+    /// between wildcard matches go to JSON.
     pub fn prefers_toon(accept_header: Option<&str>) -> bool {
-        match accept_header {
-            Some(accept) => {
-                // Simple heuristic: TOON appears before JSON in Accept header
-                let toon_pos = accept.find(TOON_CONTENT_TYPE);
-                let json_pos = accept.find("application/json");
-
-                match (toon_pos, json_pos) {
-                    (Some(t), Some(j)) => t < j,
-                    (Some(_), None) => true,
-                    _ => false,
-                }
+        let Some(accept) = accept_header else {
+            return false;
+        };
+
+        match (
+            Self::preference(accept, TOON_CONTENT_TYPE),
+            Self::preference(accept, "application/json"),
+        ) {
+            (Some((toon_q, toon_pos)), Some((json_q, json_pos))) => {
+                toon_q > json_q || (toon_q == json_q && toon_q > 0.0 && toon_pos < json_pos)
             }
-            None => false,
+            (Some((toon_q, _)), None) => toon_q > 0.0,
+            _ => false,
         }
     }
+
+    /// Get the quality value and position of the most specific media range
+    /// in `accept` matching `media_type`.
+    ///
+    /// Returns `None` when no range matches, not even a wildcard.
+    pub fn preference(accept: &str, media_type: &str) -> Option<(f32, usize)> {
+        let (kind, _) = media_type.split_once('/')?;
+        let mut best: Option<(u8, f32, usize)> = None;
+
+        for (position, range) in accept.split(',').enumerate() {
+            let mut params = range.split(';');
+            let range = params.next().unwrap_or_default().trim();
+
+            let specificity = if range.eq_ignore_ascii_case(media_type) {
+                2
+            } else if range
+                .strip_suffix("/*")
+                .is_some_and(|range_kind| range_kind.eq_ignore_ascii_case(kind))
+            {
+                1
+            } else if range == "*/*" {
+                0
+            } else {
+                continue;
+            };
+
+            let quality = params
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if best.is_none_or(|(best_specificity, _, _)| specificity > best_specificity) {
+                best = Some((specificity, quality, position));
+            }
+        }
+
+        best.map(|(_, quality, position)| (quality, position))
+    }
 }
 
 #[cfg(test)]
@@ -142,6 +204,60 @@ mod tests {
         assert!(!ToonContentNegotiator::accepts_toon(None));
     }
 
+    fn negotiate(accept: Option<&str>) -> HttpResponse {
+        let mut req = HttpRequest::new("GET".to_string(), "/data".to_string());
+        if let Some(accept) = accept {
+            req.headers.insert("Accept".to_string(), accept.to_string());
+        }
+        let data = TestData {
+            name: "test".to_string(),
+            value: 42,
+        };
+        negotiate_response(&req, &data).unwrap()
+    }
+
+    fn content_type(response: &HttpResponse) -> &str {
+        response.headers.get("Content-Type").unwrap()
+    }
+
+    #[test]
+    fn test_negotiate_toon() {
+        let response = negotiate(Some("application/toon"));
+        assert_eq!(content_type(&response), TOON_CONTENT_TYPE);
+        assert_eq!(&response.body_bytes()[..], b"name: test\nvalue: 42");
+    }
+
+    #[test]
+    fn test_negotiate_json() {
+        let response = negotiate(Some("application/json"));
+        assert_eq!(content_type(&response), "application/json");
+        assert_eq!(&response.body_bytes()[..], br#"{"name":"test","value":42}"#);
+
+        assert_eq!(content_type(&negotiate(None)), "application/json");
+    }
+
+    #[test]
+    fn test_negotiate_quality_values() {
+        let toon = negotiate(Some("application/json;q=0.5, application/toon"));
+        assert_eq!(content_type(&toon), TOON_CONTENT_TYPE);
+
+        let json = negotiate(Some("application/toon;q=0.4, application/json;q=0.9"));
+        assert_eq!(content_type(&json), "application/json");
+
+        // TOON explicitly refused
+        let json = negotiate(Some("application/toon;q=0, */*"));
+        assert_eq!(content_type(&json), "application/json");
+    }
+
+    #[test]
+    fn test_negotiate_wildcard_defaults_to_json() {
+        assert_eq!(content_type(&negotiate(Some("*/*"))), "application/json");
+        assert_eq!(
+            content_type(&negotiate(Some("text/html, application/*;q=0.9"))),
+            "application/json"
+        );
+    }
+
     #[test]
     fn test_prefers_toon() {
         assert!(ToonContentNegotiator::prefers_toon(Some(
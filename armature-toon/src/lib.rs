@@ -7,8 +7,11 @@
 //!
 //! - **Token Efficiency**: Optimized format for LLM token reduction
 //! - **Serde Compatible**: Works with existing Rust types
+//! - **Streaming Output**: Write straight to `io::Write` or `AsyncWrite`
+//! - **Streaming Input**: Decode large arrays item by item with the `streaming` feature
 //! - **HTTP Integration**: Response helpers for TOON content
 //! - **Comparison Tools**: Token counting and format comparison
+//! - **Exact Token Counts**: Real BPE counts with the `tiktoken` feature
 //!
 //! ## Quick Start
 //!
@@ -59,18 +62,40 @@
 //! println!("Reduction: {:.1}%", comparison.reduction_percent);
 //! ```
 
+#[cfg(test)]
+mod conformance;
+mod de;
 mod error;
+mod repair;
+mod ser;
+mod value;
+mod writer;
+
+#[cfg(feature = "streaming")]
+mod stream;
+
+#[cfg(feature = "tiktoken")]
+mod tokens;
 
 #[cfg(feature = "http")]
 mod http;
 
 pub use error::ToonError;
-pub use serde_toon::{from_str, to_string};
+pub use repair::repair;
+pub use ser::{Delimiter, Quoting};
+
+#[cfg(feature = "streaming")]
+pub use stream::ToonArrayReader;
+
+#[cfg(feature = "tiktoken")]
+pub use tokens::{Encoding, count_tokens};
 
 #[cfg(feature = "http")]
 pub use http::*;
 
+use ser::Encoder;
 use serde::{Serialize, de::DeserializeOwned};
+use std::io;
 
 /// TOON content type for HTTP responses.
 pub const TOON_CONTENT_TYPE: &str = "application/toon";
@@ -78,22 +103,130 @@ pub const TOON_CONTENT_TYPE: &str = "application/toon";
 /// Result type for TOON operations.
 pub type Result<T> = std::result::Result<T, ToonError>;
 
+/// Serialize a value to a TOON string.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let bytes = to_vec(value)?;
+    String::from_utf8(bytes).map_err(|e| ToonError::Utf8Error(e.to_string()))
+}
+
 /// Serialize a value to a TOON byte vector.
-pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>> {
-    let s = to_string(value)?;
-    Ok(s.into_bytes())
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    to_writer(&mut bytes, value)?;
+    Ok(bytes)
+}
+
+/// Serialize a value as TOON into a writer.
+///
+/// The value is encoded as its `Serialize` impl visits it and written line by
+/// line, so neither the output nor a copy of the input is held in memory.
+/// Arrays are the exception while their layout is undecided: items that may
+/// still share the header line, or with [`ToonSerializer::tabular`] form a
+/// table, are held until an item rules that out. Writes are small; wrap
+/// unbuffered writers such as files or sockets in an [`io::BufWriter`].
+///
+/// # Example
+///
+/// ```rust
+/// let mut out = Vec::new();
+/// armature_toon::to_writer(&mut out, &vec![1, 2, 3]).unwrap();
+/// assert_eq!(out, b"[3]: 1,2,3");
+/// ```
+pub fn to_writer<W: io::Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<()> {
+    encode(writer, value, ser::Options::default())
+}
+
+/// Serialize a value to a TOON string, writing uniform arrays as tables.
+///
+/// Arrays whose items are objects with the same keys and only primitive
+/// values are written as a `key[N]{field,...}:` header followed by one row
+/// per item, so field names appear once instead of once per item. Other
+/// arrays use the standard form. Shorthand for
+/// `ToonSerializer::new().tabular(true).serialize(value)`.
+///
+/// # Example
+///
+/// ```rust
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User { id: u32, name: String }
+///
+/// let users = vec![
+///     User { id: 1, name: "Alice".to_string() },
+///     User { id: 2, name: "Bob".to_string() },
+/// ];
+/// let toon = armature_toon::to_string_tabular(&users).unwrap();
+/// assert_eq!(toon, "[2]{id,name}:\n  1,Alice\n  2,Bob");
+/// ```
+pub fn to_string_tabular<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    ToonSerializer::new().tabular(true).serialize(value)
+}
+
+fn encode<W: io::Write, T: Serialize + ?Sized>(
+    writer: W,
+    value: &T,
+    options: ser::Options,
+) -> Result<()> {
+    value.serialize(writer::Serializer::new(&mut Encoder::new(writer, options)))
+}
+
+/// Serialize a value as TOON into an async writer.
+///
+/// The value is encoded as by [`to_writer`] on the caller's task, into chunks
+/// of 8 KiB that are then written to `writer` in order. Encoding is
+/// synchronous and can't wait for `writer`, so the output is held in memory
+/// until it is written; it is not copied into one contiguous buffer. For
+/// output too large to hold, call [`to_writer`] from a blocking task.
+///
+/// # Example
+///
+/// ```rust
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut out = Vec::new();
+/// armature_toon::to_async_writer(&mut out, &vec![1, 2, 3]).await.unwrap();
+/// assert_eq!(out, b"[3]: 1,2,3");
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub async fn to_async_writer<W, T>(mut writer: W, value: &T) -> Result<()>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    T: Serialize + ?Sized,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let mut chunks = writer::ChunkWriter::default();
+    to_writer(&mut chunks, value)?;
+    for chunk in chunks.into_chunks() {
+        writer.write_all(&chunk).await?;
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Deserialize a value from a TOON string.
+///
+/// Accepts inline, list, and tabular arrays.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T> {
+    de::deserialize(de::parse(s)?)
 }
 
 /// Deserialize a value from TOON bytes.
 pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
     let s = std::str::from_utf8(bytes).map_err(|e| ToonError::Utf8Error(e.to_string()))?;
-    from_str(s).map_err(ToonError::from)
+    from_str(s)
 }
 
 /// Serialize a value to a pretty-printed TOON string.
-pub fn to_string_pretty<T: Serialize>(value: &T) -> Result<String> {
-    // TOON is already compact, but we can add some formatting
-    to_string(value).map_err(ToonError::from)
+///
+/// Every array item gets its own indented line, delimiters are followed by a
+/// space, and table columns are aligned. The output reads back with
+/// [`from_str`], but costs more tokens than [`to_string`]: use it for
+/// inspecting payloads while debugging, not for sending them to a model.
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    ToonSerializer::new().pretty().serialize(value)
 }
 
 /// Format comparison result.
@@ -107,12 +240,20 @@ pub struct FormatComparison {
     pub json_tokens_estimate: usize,
     /// Estimated TOON tokens (chars / 4 rough estimate).
     pub toon_tokens_estimate: usize,
-    /// Percentage reduction in characters.
+    /// JSON tokens: exact with the `tiktoken` feature, otherwise the estimate.
+    pub json_tokens: usize,
+    /// TOON tokens: exact with the `tiktoken` feature, otherwise the estimate.
+    pub toon_tokens: usize,
+    /// Whether `json_tokens` and `toon_tokens` are exact BPE counts.
+    pub tokens_exact: bool,
+    /// Percentage reduction in characters (negative if TOON is larger).
     pub reduction_percent: f64,
 }
 
 /// Compare JSON and TOON serialization for a value.
 ///
+/// With the `tiktoken` feature, token counts use the `cl100k_base` encoding.
+///
 /// # Example
 ///
 /// ```rust
@@ -127,19 +268,49 @@ pub struct FormatComparison {
 /// println!("Reduction: {:.1}%", comparison.reduction_percent);
 /// ```
 pub fn compare_formats<T: Serialize>(value: &T) -> Result<FormatComparison> {
+    compare_formats_with(value, &ToonSerializer::default())
+}
+
+/// Compare JSON with the TOON output of a configured serializer.
+///
+/// # Example
+///
+/// ```rust
+/// use armature_toon::{ToonSerializer, compare_formats_with};
+///
+/// let rows = vec![[("id", 1), ("rank", 2)], [("id", 2), ("rank", 1)]]
+///     .into_iter()
+///     .map(|row| row.into_iter().collect::<std::collections::BTreeMap<_, _>>())
+///     .collect::<Vec<_>>();
+/// let tabular = compare_formats_with(&rows, &ToonSerializer::new().tabular(true)).unwrap();
+/// assert!(tabular.reduction_percent > 0.0);
+/// ```
+pub fn compare_formats_with<T: Serialize>(
+    value: &T,
+    serializer: &ToonSerializer,
+) -> Result<FormatComparison> {
     let json_string =
         serde_json::to_string(value).map_err(|e| ToonError::SerializeError(e.to_string()))?;
-    let toon_string = to_string(value)?;
+    let toon_string = serializer.serialize(value)?;
 
     let json_chars = json_string.len();
     let toon_chars = toon_string.len();
 
-    // Rough token estimate (GPT models use ~4 chars per token on average)
-    let json_tokens_estimate = json_chars.div_ceil(4);
-    let toon_tokens_estimate = toon_chars.div_ceil(4);
+    let json_tokens_estimate = estimate_tokens(&json_string);
+    let toon_tokens_estimate = estimate_tokens(&toon_string);
+
+    #[cfg(feature = "tiktoken")]
+    let (json_tokens, toon_tokens, tokens_exact) = (
+        count_tokens(&json_string, Encoding::Cl100kBase),
+        count_tokens(&toon_string, Encoding::Cl100kBase),
+        true,
+    );
+    #[cfg(not(feature = "tiktoken"))]
+    let (json_tokens, toon_tokens, tokens_exact) =
+        (json_tokens_estimate, toon_tokens_estimate, false);
 
     let reduction_percent = if json_chars > 0 {
-        ((json_chars - toon_chars) as f64 / json_chars as f64) * 100.0
+        ((json_chars as f64 - toon_chars as f64) / json_chars as f64) * 100.0
     } else {
         0.0
     };
@@ -149,15 +320,26 @@ pub fn compare_formats<T: Serialize>(value: &T) -> Result<FormatComparison> {
         toon_chars,
         json_tokens_estimate,
         toon_tokens_estimate,
+        json_tokens,
+        toon_tokens,
+        tokens_exact,
         reduction_percent,
     })
 }
 
+/// Estimate tokens as chars / 4, a rough average for GPT models.
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
 /// Token counter for estimating LLM token usage.
 #[derive(Debug, Default)]
 pub struct TokenCounter {
     total_chars: usize,
     total_tokens_estimate: usize,
+    total_tokens: usize,
+    #[cfg(feature = "tiktoken")]
+    encoding: Option<Encoding>,
 }
 
 impl TokenCounter {
@@ -166,18 +348,35 @@ impl TokenCounter {
         Self::default()
     }
 
+    /// Create a token counter that counts exact tokens for an encoding.
+    #[cfg(feature = "tiktoken")]
+    pub fn with_encoding(encoding: Encoding) -> Self {
+        Self {
+            encoding: Some(encoding),
+            ..Self::default()
+        }
+    }
+
     /// Add a serialized value to the counter.
     pub fn add<T: Serialize>(&mut self, value: &T) -> Result<()> {
         let toon = to_string(value)?;
-        self.total_chars += toon.len();
-        self.total_tokens_estimate += toon.len().div_ceil(4);
+        self.add_raw(&toon);
         Ok(())
     }
 
     /// Add raw TOON string to the counter.
     pub fn add_raw(&mut self, toon: &str) {
         self.total_chars += toon.len();
-        self.total_tokens_estimate += toon.len().div_ceil(4);
+        self.total_tokens_estimate += estimate_tokens(toon);
+        self.total_tokens += self.count(toon);
+    }
+
+    fn count(&self, text: &str) -> usize {
+        #[cfg(feature = "tiktoken")]
+        if let Some(encoding) = self.encoding {
+            return count_tokens(text, encoding);
+        }
+        estimate_tokens(text)
     }
 
     /// Get total character count.
@@ -190,10 +389,17 @@ impl TokenCounter {
         self.total_tokens_estimate
     }
 
+    /// Get the token count: exact if created with an encoding, otherwise
+    /// the estimate.
+    pub fn total_tokens(&self) -> usize {
+        self.total_tokens
+    }
+
     /// Reset the counter.
     pub fn reset(&mut self) {
         self.total_chars = 0;
         self.total_tokens_estimate = 0;
+        self.total_tokens = 0;
     }
 }
 
@@ -204,6 +410,12 @@ pub struct ToonSerializer {
     pub include_type_hints: bool,
     /// Whether to use compact mode.
     pub compact: bool,
+    /// Whether to write uniform arrays of objects as tables.
+    pub tabular: bool,
+    /// Separator between array values and table columns.
+    pub delimiter: Delimiter,
+    /// When string values are quoted.
+    pub quoting: Quoting,
 }
 
 impl Default for ToonSerializer {
@@ -211,6 +423,9 @@ impl Default for ToonSerializer {
         Self {
             include_type_hints: false,
             compact: true,
+            tabular: false,
+            delimiter: Delimiter::default(),
+            quoting: Quoting::default(),
         }
     }
 }
@@ -227,28 +442,77 @@ impl ToonSerializer {
         self
     }
 
-    /// Disable compact mode.
+    /// Disable compact mode, for human-readable output while debugging.
+    ///
+    /// See [`to_string_pretty`]. Pretty output is not token-optimal.
     pub fn pretty(mut self) -> Self {
         self.compact = false;
         self
     }
 
+    /// Write arrays of objects with the same keys and only primitive values
+    /// as a header plus one row per item. See [`to_string_tabular`].
+    pub fn tabular(mut self, enabled: bool) -> Self {
+        self.tabular = enabled;
+        self
+    }
+
+    /// Set the separator between array values and table columns.
+    ///
+    /// The delimiter is declared in each array header, so [`from_str`] reads
+    /// any of them without configuration.
+    pub fn delimiter(mut self, delimiter: Delimiter) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set when string values are quoted.
+    ///
+    /// Values containing the delimiter are quoted under every policy.
+    pub fn quoting(mut self, quoting: Quoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
     /// Serialize a value to TOON string.
-    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String> {
-        to_string(value).map_err(ToonError::from)
+    pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<String> {
+        let bytes = self.serialize_bytes(value)?;
+        String::from_utf8(bytes).map_err(|e| ToonError::Utf8Error(e.to_string()))
     }
 
     /// Serialize a value to TOON bytes.
-    pub fn serialize_bytes<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
-        to_vec(value)
+    pub fn serialize_bytes<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.serialize_to_writer(&mut bytes, value)?;
+        Ok(bytes)
+    }
+
+    /// Serialize a value as TOON into a writer.
+    pub fn serialize_to_writer<W: io::Write, T: Serialize + ?Sized>(
+        &self,
+        writer: W,
+        value: &T,
+    ) -> Result<()> {
+        encode(writer, value, self.options())
+    }
+
+    fn options(&self) -> ser::Options {
+        ser::Options {
+            tabular: self.tabular,
+            delimiter: self.delimiter,
+            quoting: self.quoting,
+            pretty: !self.compact,
+        }
     }
 }
 
 /// TOON deserializer with configuration options.
 #[derive(Debug, Clone, Default)]
 pub struct ToonDeserializer {
-    /// Whether to be strict about unknown fields.
+    /// Whether to require exact TOON. Takes precedence over `lenient`.
     pub strict: bool,
+    /// Whether to accept common deviations in LLM-generated TOON.
+    pub lenient: bool,
 }
 
 impl ToonDeserializer {
@@ -257,47 +521,247 @@ impl ToonDeserializer {
         Self::default()
     }
 
-    /// Enable strict mode (fail on unknown fields).
+    /// Require exact TOON, as [`from_str`] does. This is the default.
     pub fn strict(mut self) -> Self {
         self.strict = true;
+        self.lenient = false;
+        self
+    }
+
+    /// Accept common mistakes in LLM-generated TOON.
+    ///
+    /// Input is cleaned with [`repair`] before parsing, and array lengths
+    /// that disagree with their `[N]` header are accepted.
+    pub fn lenient(mut self) -> Self {
+        self.strict = false;
+        self.lenient = true;
         self
     }
 
     /// Deserialize from TOON string.
     pub fn deserialize<T: DeserializeOwned>(&self, s: &str) -> Result<T> {
-        from_str(s).map_err(ToonError::from)
+        if self.lenient && !self.strict {
+            de::deserialize(de::parse_lenient(&repair(s))?)
+        } else {
+            from_str(s)
+        }
     }
 
     /// Deserialize from TOON bytes.
     pub fn deserialize_bytes<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
-        from_slice(bytes)
+        let s = std::str::from_utf8(bytes).map_err(|e| ToonError::Utf8Error(e.to_string()))?;
+        self.deserialize(s)
     }
 }
 
 /// Batch converter for converting between JSON and TOON.
+///
+/// The string conversions keep object keys in input order. The line-based
+/// conversions stream: they hold one document at a time, and skip documents
+/// that fail to convert, reporting them in a [`BatchReport`]. Use
+/// [`NdjsonConverter`] to stop at the first failure instead.
 pub struct BatchConverter;
 
+/// Line-based converter between NDJSON and TOON documents, with options.
+///
+/// TOON documents are separated by a blank line. An empty object has no TOON
+/// lines, so it is written as a `{}` line, which reads back as an empty
+/// object rather than a separator.
+#[derive(Debug, Clone, Default)]
+pub struct NdjsonConverter {
+    fail_fast: bool,
+}
+
+/// Outcome of a line-based conversion.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Number of documents converted.
+    pub converted: usize,
+    /// Documents that were skipped, as [`ToonError::ParseError`]s whose line
+    /// numbers refer to the whole input.
+    pub errors: Vec<ToonError>,
+}
+
 impl BatchConverter {
     /// Convert JSON string to TOON string.
     pub fn json_to_toon(json: &str) -> Result<String> {
-        let value: serde_json::Value =
+        let value: value::Value =
             serde_json::from_str(json).map_err(|e| ToonError::DeserializeError(e.to_string()))?;
-        to_string(&value).map_err(ToonError::from)
+        to_string(&value)
     }
 
     /// Convert TOON string to JSON string.
     pub fn toon_to_json(toon: &str) -> Result<String> {
-        let value: serde_json::Value = from_str(toon)?;
-        serde_json::to_string(&value).map_err(|e| ToonError::SerializeError(e.to_string()))
+        serde_json::to_string(&de::parse(toon)?)
+            .map_err(|e| ToonError::SerializeError(e.to_string()))
     }
 
     /// Convert TOON string to pretty JSON string.
     pub fn toon_to_json_pretty(toon: &str) -> Result<String> {
-        let value: serde_json::Value = from_str(toon)?;
-        serde_json::to_string_pretty(&value).map_err(|e| ToonError::SerializeError(e.to_string()))
+        serde_json::to_string_pretty(&de::parse(toon)?)
+            .map_err(|e| ToonError::SerializeError(e.to_string()))
+    }
+
+    /// Convert NDJSON to TOON, one document per input line, skipping lines
+    /// that fail to convert.
+    ///
+    /// Documents are separated by a blank line, the format
+    /// [`toon_lines_to_ndjson`](Self::toon_lines_to_ndjson) reads. Blank
+    /// input lines are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use armature_toon::BatchConverter;
+    ///
+    /// let ndjson = "{\"id\":1,\"tags\":[\"a\"]}\n{}\n{\"id\":2,\"tags\":[]}\n";
+    /// let mut toon = Vec::new();
+    /// let report = BatchConverter::ndjson_to_toon(ndjson.as_bytes(), &mut toon).unwrap();
+    /// assert_eq!(report.converted, 3);
+    /// assert_eq!(toon, b"id: 1\ntags[1]: a\n\n{}\n\nid: 2\ntags[0]:\n");
+    /// ```
+    pub fn ndjson_to_toon<R: io::BufRead, W: io::Write>(
+        reader: R,
+        writer: W,
+    ) -> Result<BatchReport> {
+        NdjsonConverter::new().ndjson_to_toon(reader, writer)
+    }
+
+    /// Convert blank-line separated TOON documents to NDJSON, one line per
+    /// document, skipping documents that fail to convert.
+    pub fn toon_lines_to_ndjson<R: io::BufRead, W: io::Write>(
+        reader: R,
+        writer: W,
+    ) -> Result<BatchReport> {
+        NdjsonConverter::new().toon_lines_to_ndjson(reader, writer)
     }
 }
 
+/// Stands in for an empty object, which has no TOON lines of its own.
+const EMPTY_DOCUMENT: &str = "{}";
+
+impl NdjsonConverter {
+    /// Create a converter that skips documents that fail to convert.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop at the first document that fails to convert, returning its error.
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Convert NDJSON to TOON. See [`BatchConverter::ndjson_to_toon`].
+    pub fn ndjson_to_toon<R: io::BufRead, W: io::Write>(
+        &self,
+        reader: R,
+        mut writer: W,
+    ) -> Result<BatchReport> {
+        let mut report = BatchReport::default();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let value = match serde_json::from_str::<value::Value>(&line) {
+                Ok(value) => value,
+                Err(e) => {
+                    self.skip(&mut report, json_error(&e, index + 1, &line))?;
+                    continue;
+                }
+            };
+            if report.converted > 0 {
+                writer.write_all(b"\n")?;
+            }
+            match &value {
+                value::Value::Object(fields) if fields.is_empty() => {
+                    writer.write_all(EMPTY_DOCUMENT.as_bytes())?
+                }
+                value => Encoder::new(&mut writer, ser::Options::default()).document(value)?,
+            }
+            writer.write_all(b"\n")?;
+            report.converted += 1;
+        }
+        writer.flush()?;
+        Ok(report)
+    }
+
+    /// Convert TOON documents to NDJSON. See
+    /// [`BatchConverter::toon_lines_to_ndjson`].
+    pub fn toon_lines_to_ndjson<R: io::BufRead, W: io::Write>(
+        &self,
+        reader: R,
+        mut writer: W,
+    ) -> Result<BatchReport> {
+        let mut report = BatchReport::default();
+        let mut document = String::new();
+        // Lines before the current document
+        let mut start = 0;
+
+        // A final `None` flushes the last document
+        let lines = reader.lines().map(Some).chain(std::iter::once(None));
+        for (index, line) in lines.enumerate() {
+            match line.transpose()? {
+                Some(line) if !line.trim().is_empty() => {
+                    if document.is_empty() {
+                        start = index;
+                    }
+                    document.push_str(&line);
+                    document.push('\n');
+                    continue;
+                }
+                _ if document.is_empty() => continue,
+                _ => {}
+            }
+
+            let parsed = if document.trim_end() == EMPTY_DOCUMENT {
+                Ok(value::Value::Object(Vec::new()))
+            } else {
+                de::parse(&document)
+            };
+            match parsed {
+                Ok(value) => {
+                    serde_json::to_writer(&mut writer, &value)
+                        .map_err(|e| ToonError::SerializeError(e.to_string()))?;
+                    writer.write_all(b"\n")?;
+                    report.converted += 1;
+                }
+                Err(e) => self.skip(&mut report, e.offset_lines(start))?,
+            }
+            document.clear();
+        }
+        writer.flush()?;
+        Ok(report)
+    }
+
+    /// Record a failed document, or return its error when failing fast.
+    fn skip(&self, report: &mut BatchReport, error: ToonError) -> Result<()> {
+        if self.fail_fast {
+            return Err(error);
+        }
+        report.errors.push(error);
+        Ok(())
+    }
+}
+
+/// Convert a JSON error on input line `line` to a [`ToonError::ParseError`].
+fn json_error(error: &serde_json::Error, line: usize, content: &str) -> ToonError {
+    let message = error.to_string();
+    // serde_json appends its own position, which is relative to `content`
+    let message = match message.rfind(" at line ") {
+        Some(end) => &message[..end],
+        None => &message,
+    };
+    let column = error.column().max(1);
+    let offset = content
+        .char_indices()
+        .nth(column - 1)
+        .map_or(content.len(), |(i, _)| i);
+    de::parse_error(message, line, column, de::snippet(content, offset))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -363,6 +827,8 @@ mod tests {
         let comparison = compare_formats(&data).unwrap();
         assert!(comparison.json_chars > 0);
         assert!(comparison.toon_chars > 0);
+        assert!(comparison.json_tokens > 0);
+        assert_eq!(comparison.tokens_exact, cfg!(feature = "tiktoken"));
         // TOON should generally be smaller
         println!(
             "JSON: {} chars, TOON: {} chars, Reduction: {:.1}%",
@@ -370,6 +836,130 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tabular_round_trip() {
+        let users: Vec<TestUser> = (0..3)
+            .map(|id| TestUser {
+                id,
+                name: format!("user {}", id),
+                active: id != 1,
+            })
+            .collect();
+
+        let toon = to_string_tabular(&users).unwrap();
+        assert_eq!(
+            toon,
+            "[3]{id,name,active}:\n  0,user 0,true\n  1,user 1,false\n  2,user 2,true"
+        );
+        let parsed: Vec<TestUser> = from_str(&toon).unwrap();
+        assert_eq!(parsed, users);
+
+        // Heterogeneous arrays fall back to list items and still round-trip
+        let mixed = serde_json::json!({ "items": [{ "id": 1 }, { "id": 2, "extra": [1] }] });
+        let toon = to_string_tabular(&mixed).unwrap();
+        assert!(toon.starts_with("items[2]:\n  - id: 1"));
+        assert_eq!(from_str::<serde_json::Value>(&toon).unwrap(), mixed);
+    }
+
+    #[test]
+    fn test_delimiter_round_trip() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Note {
+            id: u32,
+            text: String,
+            tags: Vec<String>,
+        }
+
+        let notes = vec![
+            Note {
+                id: 1,
+                text: "commas, tabs\tand | pipes".to_string(),
+                tags: vec!["a,b".to_string(), "c\td".to_string(), "e|f".to_string()],
+            },
+            Note {
+                id: 2,
+                text: "plain".to_string(),
+                tags: vec![],
+            },
+        ];
+        let table: Vec<std::collections::BTreeMap<String, String>> = vec![
+            [("k".into(), "a,b".into()), ("v".into(), "c|d".into())].into(),
+            [("k".into(), "e\tf".into()), ("v".into(), "plain".into())].into(),
+        ];
+
+        for delimiter in [Delimiter::Comma, Delimiter::Tab, Delimiter::Pipe] {
+            for quoting in [Quoting::Minimal, Quoting::Always, Quoting::Never] {
+                let serializer = ToonSerializer::new()
+                    .tabular(true)
+                    .delimiter(delimiter)
+                    .quoting(quoting);
+
+                let toon = serializer.serialize(&notes).unwrap();
+                assert_eq!(from_str::<Vec<Note>>(&toon).unwrap(), notes, "{}", toon);
+
+                let toon = serializer.serialize(&table).unwrap();
+                let header = format!("[2{}]{{k{}v}}:", delimiter.as_char(), delimiter.as_char())
+                    .replace("[2,]", "[2]");
+                assert!(toon.starts_with(&header), "{}", toon);
+                let parsed: Vec<std::collections::BTreeMap<String, String>> =
+                    from_str(&toon).unwrap();
+                assert_eq!(parsed, table, "{}", toon);
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_string_pretty() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq)]
+        struct Team {
+            name: String,
+            tags: Vec<String>,
+            members: Vec<TestUser>,
+        }
+
+        let team = Team {
+            name: "core".to_string(),
+            tags: vec!["rust".to_string(), "web".to_string()],
+            members: vec![TestUser {
+                id: 1,
+                name: "Ada".to_string(),
+                active: true,
+            }],
+        };
+
+        let pretty = to_string_pretty(&team).unwrap();
+        assert_ne!(pretty, to_string(&team).unwrap());
+        assert!(pretty.contains("tags[2]:\n  - rust\n  - web"));
+        assert!(pretty.contains("\n  - id: 1\n    name: Ada"));
+        assert_eq!(from_str::<Team>(&pretty).unwrap(), team);
+
+        let table = ToonSerializer::new()
+            .pretty()
+            .tabular(true)
+            .serialize(&team)
+            .unwrap();
+        assert!(table.contains("members[1]{id, name, active}:\n  1, Ada, true"));
+        assert_eq!(from_str::<Team>(&table).unwrap(), team);
+    }
+
+    #[test]
+    fn test_compare_formats_tabular() {
+        let users: Vec<TestUser> = (0..20)
+            .map(|id| TestUser {
+                id,
+                name: format!("user-{}", id),
+                active: true,
+            })
+            .collect();
+
+        let standard = compare_formats(&users).unwrap();
+        let tabular = compare_formats_with(&users, &ToonSerializer::new().tabular(true)).unwrap();
+        assert!(tabular.toon_chars < standard.toon_chars);
+        assert!(tabular.toon_chars < tabular.json_chars);
+        assert!(tabular.reduction_percent > standard.reduction_percent);
+        assert!(tabular.toon_tokens < standard.toon_tokens);
+    }
+
     #[test]
     fn test_token_counter() {
         let mut counter = TokenCounter::new();
@@ -415,6 +1005,211 @@ mod tests {
         assert_eq!(user, parsed);
     }
 
+    #[test]
+    fn test_lenient_deserializer() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Profile {
+            name: String,
+            tags: Vec<String>,
+            meta: Meta,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Meta {
+            rank: u32,
+        }
+
+        let expected = Profile {
+            name: "Ada, Countess".to_string(),
+            tags: vec!["math, logic".to_string(), "poetry".to_string()],
+            meta: Meta { rank: 1 },
+        };
+        let mistakes = [
+            // Wrapped in a Markdown code fence
+            "```toon\nname: \"Ada, Countess\"\ntags[2]: \"math, logic\",poetry\nmeta:\n  rank: 1\n```",
+            // Curly quotes
+            "name: \u{201C}Ada, Countess\u{201D}\ntags[2]: \u{201C}math, logic\u{201D},poetry\nmeta:\n  rank: 1",
+            // Trailing comma
+            "name: \"Ada, Countess\"\ntags[2]: \"math, logic\",poetry,\nmeta:\n  rank: 1",
+            // Tab indentation and Windows line endings
+            "name: \"Ada, Countess\"\r\ntags[2]: \"math, logic\",poetry\r\nmeta:\r\n\trank: 1\r\n",
+            // Miscounted array
+            "name: \"Ada, Countess\"\ntags[3]: \"math, logic\",poetry\nmeta:\n  rank: 1",
+        ];
+
+        let lenient = ToonDeserializer::new().lenient();
+        let strict = ToonDeserializer::new().strict();
+        // The public fields keep working, with `strict` taking precedence
+        let strict_fields = ToonDeserializer {
+            strict: true,
+            lenient: true,
+        };
+        assert!(strict.strict && !strict.lenient);
+        assert!(!lenient.strict && lenient.lenient);
+        for input in mistakes {
+            assert_eq!(
+                lenient.deserialize::<Profile>(input).unwrap(),
+                expected,
+                "{}",
+                input
+            );
+            assert!(strict.deserialize::<Profile>(input).is_err(), "{}", input);
+            assert!(
+                strict_fields.deserialize::<Profile>(input).is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    fn users(count: u32) -> Vec<TestUser> {
+        (0..count)
+            .map(|id| TestUser {
+                id,
+                name: format!("user-{}", id),
+                active: id % 2 == 0,
+            })
+            .collect()
+    }
+
+    /// The expected TOON for [`users`], built by hand.
+    fn expected_users(count: u32) -> String {
+        let mut expected = format!("[{}]:", count);
+        for id in 0..count {
+            expected.push_str(&format!(
+                "\n  - id: {}\n    name: user-{}\n    active: {}",
+                id,
+                id,
+                id % 2 == 0
+            ));
+        }
+        expected
+    }
+
+    #[test]
+    fn test_to_writer() {
+        let mut sink = Vec::new();
+        to_writer(&mut sink, &users(10_000)).unwrap();
+        assert_eq!(String::from_utf8(sink).unwrap(), expected_users(10_000));
+        assert_eq!(to_string(&users(10_000)).unwrap(), expected_users(10_000));
+    }
+
+    #[test]
+    fn test_to_writer_is_incremental() {
+        use std::cell::{Cell, RefCell};
+
+        /// Records the size of every write.
+        struct Recorder<'a> {
+            written: &'a Cell<usize>,
+            largest_write: usize,
+        }
+
+        impl io::Write for Recorder<'_> {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.written.set(self.written.get() + buf.len());
+                self.largest_write = self.largest_write.max(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        /// Notes how much output was written when it is serialized.
+        struct Probe<'a> {
+            user: TestUser,
+            written: &'a Cell<usize>,
+            seen: &'a RefCell<Vec<usize>>,
+        }
+
+        impl Serialize for Probe<'_> {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                self.seen.borrow_mut().push(self.written.get());
+                self.user.serialize(serializer)
+            }
+        }
+
+        let written = Cell::new(0);
+        let seen = RefCell::new(Vec::new());
+        let probes: Vec<Probe<'_>> = users(10_000)
+            .into_iter()
+            .map(|user| Probe {
+                user,
+                written: &written,
+                seen: &seen,
+            })
+            .collect();
+
+        let mut recorder = Recorder {
+            written: &written,
+            largest_write: 0,
+        };
+        to_writer(&mut recorder, &probes).unwrap();
+        assert_eq!(written.get(), expected_users(10_000).len());
+
+        // Every item is written before the next one is serialized, and
+        // nothing is collected into large writes
+        let seen = seen.into_inner();
+        assert_eq!(seen.len(), 10_000);
+        assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(recorder.largest_write <= 16, "{}", recorder.largest_write);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_to_async_writer() {
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        /// Records the size of every write.
+        #[derive(Default)]
+        struct Recorder {
+            out: Vec<u8>,
+            writes: Vec<usize>,
+        }
+
+        impl tokio::io::AsyncWrite for Recorder {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                self.out.extend_from_slice(buf);
+                self.writes.push(buf.len());
+                Poll::Ready(Ok(buf.len()))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        // The value is borrowed, so it need not be `'static`
+        let users = users(10_000);
+        let borrowed: Vec<&TestUser> = users.iter().collect();
+
+        let mut recorder = Recorder::default();
+        to_async_writer(&mut recorder, &borrowed).await.unwrap();
+        assert_eq!(
+            String::from_utf8(recorder.out).unwrap(),
+            expected_users(10_000)
+        );
+
+        // Output arrives in chunks of 8 KiB, not as one buffer
+        assert!(recorder.writes.len() > 10);
+        let (last, full) = recorder.writes.split_last().unwrap();
+        assert!(full.iter().all(|&len| len == 8 * 1024));
+        assert!(*last <= 8 * 1024);
+    }
+
     #[test]
     fn test_batch_converter_json_to_toon() {
         let json = r#"{"id":123,"name":"Alice","active":true}"#;
@@ -426,4 +1221,63 @@ mod tests {
         assert!(json_back.contains("123"));
         assert!(json_back.contains("Alice"));
     }
+
+    #[test]
+    fn test_batch_converter_ndjson() {
+        let ndjson = concat!(
+            r#"{"id":1,"name":"Ada","tags":["math","code"]}"#,
+            "\n",
+            r#"{"id":2,"name":"Grace, Admiral","tags":[]}"#,
+            "\n\n",
+            r#"{"users":[{"id":3,"active":true},{"id":4,"active":false}]}"#,
+            "\n",
+            "{}\n",
+            "[]\n",
+            "{\"empty\":{}}\n",
+            "[{}]\n",
+        );
+
+        let mut toon = Vec::new();
+        let report = BatchConverter::ndjson_to_toon(ndjson.as_bytes(), &mut toon).unwrap();
+        assert_eq!(report.converted, 7);
+        assert!(report.errors.is_empty());
+
+        let mut json = Vec::new();
+        let report = BatchConverter::toon_lines_to_ndjson(&toon[..], &mut json).unwrap();
+        assert_eq!(report.converted, 7);
+        assert!(report.errors.is_empty());
+
+        let expected: Vec<&str> = ndjson.lines().filter(|line| !line.is_empty()).collect();
+        let actual: Vec<&str> = std::str::from_utf8(&json).unwrap().lines().collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_batch_converter_errors() {
+        let ndjson = "{\"id\":1}\n{\"id\":}\n{\"id\":3}\n";
+
+        let mut toon = Vec::new();
+        let report = BatchConverter::ndjson_to_toon(ndjson.as_bytes(), &mut toon).unwrap();
+        assert_eq!(report.converted, 2);
+        assert!(matches!(
+            report.errors[..],
+            [ToonError::ParseError { line: 2, .. }]
+        ));
+        assert_eq!(toon, b"id: 1\n\nid: 3\n");
+
+        let result = NdjsonConverter::new()
+            .fail_fast(true)
+            .ndjson_to_toon(ndjson.as_bytes(), Vec::new());
+        assert!(matches!(result, Err(ToonError::ParseError { line: 2, .. })));
+
+        // Line numbers refer to the whole input
+        let toon = "id: 1\n\nid: 2\ntags[2]: a\n";
+        let mut json = Vec::new();
+        let report = BatchConverter::toon_lines_to_ndjson(toon.as_bytes(), &mut json).unwrap();
+        assert_eq!(json, b"{\"id\":1}\n");
+        assert!(matches!(
+            report.errors[..],
+            [ToonError::ParseError { line: 4, .. }]
+        ));
+    }
 }
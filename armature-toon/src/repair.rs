@@ -0,0 +1,96 @@
+//! Cleanup of near-TOON text produced by language models.
+
+/// Repair common deviations in LLM-generated TOON.
+///
+/// The returned text is what [`ToonDeserializer::lenient`] parses, so callers
+/// can log or diff it to see what was fixed. Repairs:
+///
+/// - A surrounding Markdown code fence, such as `` ```toon ``, is removed.
+/// - Curly quotes are replaced with their ASCII equivalents.
+/// - Tabs in indentation become two spaces each.
+/// - A trailing comma at the end of a line, outside quotes, is removed.
+/// - Line endings become `\n`, with exactly one at the end.
+///
+/// [`ToonDeserializer::lenient`]: crate::ToonDeserializer::lenient
+///
+/// # Example
+///
+/// ```rust
+/// let fixed = armature_toon::repair("tags[2]: \u{201C}a, b\u{201D},c,\r\n");
+/// assert_eq!(fixed, "tags[2]: \"a, b\",c\n");
+/// ```
+pub fn repair(input: &str) -> String {
+    let mut lines: Vec<&str> = input.lines().collect();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    while lines.first().is_some_and(|line| line.trim().is_empty()) {
+        lines.remove(0);
+    }
+    if lines.len() >= 2
+        && lines[0].trim_start().starts_with("```")
+        && lines[lines.len() - 1].trim() == "```"
+    {
+        lines = lines[1..lines.len() - 1].to_vec();
+    }
+
+    let mut out = String::with_capacity(input.len() + 1);
+    for line in lines {
+        let line = straighten_quotes(line);
+        let body = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - body.len()];
+        for c in indent.chars() {
+            out.push_str(if c == '\t' { "  " } else { " " });
+        }
+        out.push_str(strip_trailing_comma(body.trim_end()));
+        out.push('\n');
+    }
+    out
+}
+
+/// Replace curly quotes with ASCII quotes.
+fn straighten_quotes(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            c => c,
+        })
+        .collect()
+}
+
+/// Remove a comma ending the line, unless it is inside a quoted string.
+fn strip_trailing_comma(line: &str) -> &str {
+    let Some(body) = line.strip_suffix(',') else {
+        return line;
+    };
+
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for c in body.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ => {}
+        }
+    }
+    if in_quotes { line } else { body.trim_end() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair() {
+        let input = "```toon\r\nuser:\r\n\tname: \u{201C}Ada\u{201D}\r\n\ttags[2]: a,b,\r\n```";
+        assert_eq!(repair(input), "user:\n  name: \"Ada\"\n  tags[2]: a,b\n");
+
+        // Already valid text only gains a final newline
+        assert_eq!(repair("id: 1\nname: Ada"), "id: 1\nname: Ada\n");
+        // Commas inside quotes are kept
+        assert_eq!(repair("note: \"a,\n"), "note: \"a,\n");
+        assert_eq!(repair(""), "");
+    }
+}
@@ -0,0 +1,748 @@
+//! Streaming TOON encoder.
+//!
+//! The encoder writes TOON straight to an [`io::Write`], line by line, so the
+//! serialized text is never held in memory as a whole. Values come either from
+//! a [`Value`] tree or, through [`crate::writer::Serializer`], directly from a
+//! `Serialize` impl.
+
+use crate::value::Value;
+use std::io::{self, Write};
+
+/// Spaces per nesting level.
+const INDENT: usize = 2;
+
+/// Separator between array values and table columns.
+///
+/// Tokenizers split delimiters differently, so the cheapest choice depends on
+/// the model. Non-comma delimiters are declared in each array header, as in
+/// `[3\t]` or `[3|]`, so readers detect them automatically.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// `,`
+    #[default]
+    Comma,
+    /// A tab character.
+    Tab,
+    /// `|`
+    Pipe,
+}
+
+impl Delimiter {
+    /// The delimiter character.
+    pub fn as_char(self) -> char {
+        match self {
+            Delimiter::Comma => ',',
+            Delimiter::Tab => '\t',
+            Delimiter::Pipe => '|',
+        }
+    }
+
+    /// The marker written after an array length.
+    fn marker(self) -> &'static str {
+        match self {
+            Delimiter::Comma => "",
+            Delimiter::Tab => "\t",
+            Delimiter::Pipe => "|",
+        }
+    }
+}
+
+/// When string values are quoted.
+///
+/// Strings that contain the delimiter, a colon, a double quote, or control
+/// characters, or that are empty or padded with whitespace, are quoted under
+/// every policy; otherwise the output would not parse back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Quoting {
+    /// Quote strings that would otherwise read back as something else, such
+    /// as `"true"`, `"42"`, or `"null"`.
+    #[default]
+    Minimal,
+    /// Quote every string.
+    Always,
+    /// Quote only when required to parse. Strings that look like numbers,
+    /// booleans, or null read back as those types.
+    Never,
+}
+
+/// Encoder settings.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Options {
+    /// Write arrays of uniform, flat objects as tables.
+    pub(crate) tabular: bool,
+    pub(crate) delimiter: Delimiter,
+    pub(crate) quoting: Quoting,
+    /// Favor readability: one line per array item, spaces after delimiters,
+    /// and aligned table columns.
+    pub(crate) pretty: bool,
+}
+
+/// How a line starts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Line {
+    /// Indented to the given depth.
+    Indented(usize),
+    /// A list item whose hyphen is at the given depth.
+    ListItem(usize),
+}
+
+/// How the items of an array are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrayForm {
+    /// All items on the header line: `key[3]: a,b,c`
+    Inline,
+    /// One `- item` line per item.
+    List,
+    /// A `key[2]{id,name}:` header, then one row of values per item.
+    Tabular,
+}
+
+impl ArrayForm {
+    fn of(items: &[Value], options: Options) -> Self {
+        if items.iter().all(Value::is_primitive) && (items.is_empty() || options.inline_arrays()) {
+            ArrayForm::Inline
+        } else if options.tabular && table_fields(items).is_some() {
+            ArrayForm::Tabular
+        } else {
+            ArrayForm::List
+        }
+    }
+}
+
+impl Options {
+    /// Whether arrays of primitives go on their header line.
+    pub(crate) fn inline_arrays(self) -> bool {
+        !self.pretty
+    }
+}
+
+/// The shared keys of an array of objects that all have the same keys and
+/// only primitive values, in the first object's order.
+fn table_fields(items: &[Value]) -> Option<&[(String, Value)]> {
+    let first = items.first();
+    match first {
+        Some(Value::Object(fields)) if items.iter().all(|item| fits_table(first, item)) => {
+            Some(fields.as_slice())
+        }
+        _ => None,
+    }
+}
+
+/// Check whether `item` can be a table row in an array whose first item is
+/// `first`, or the first row if there is none yet. Rows may order their keys
+/// differently; cells are written in the header's order.
+pub(crate) fn fits_table(first: Option<&Value>, item: &Value) -> bool {
+    let Value::Object(fields) = item else {
+        return false;
+    };
+    match first {
+        None => !fields.is_empty() && fields.iter().all(|(_, value)| value.is_primitive()),
+        Some(Value::Object(first)) => {
+            !first.is_empty()
+                && fields.len() == first.len()
+                && fields.iter().all(|(_, value)| value.is_primitive())
+                && first.iter().all(|(key, _)| cell(fields, key).is_some())
+        }
+        Some(_) => false,
+    }
+}
+
+/// The value of `key` in a table row.
+fn cell<'v>(fields: &'v [(String, Value)], key: &str) -> Option<&'v Value> {
+    fields
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, value)| value)
+}
+
+/// Writes TOON text to an [`io::Write`].
+pub(crate) struct Encoder<W> {
+    writer: W,
+    options: Options,
+    /// Keys of the table being written, in header order.
+    columns: Vec<String>,
+    /// Column widths of the table being written, when aligning columns.
+    widths: Vec<usize>,
+    /// Whether any line has been written, so the next one needs a newline.
+    started: bool,
+}
+
+impl<W: Write> Encoder<W> {
+    pub(crate) fn new(writer: W, options: Options) -> Self {
+        Self {
+            writer,
+            options,
+            columns: Vec::new(),
+            widths: Vec::new(),
+            started: false,
+        }
+    }
+
+    pub(crate) fn options(&self) -> Options {
+        self.options
+    }
+
+    /// Write a complete document.
+    pub(crate) fn document(&mut self, value: &Value) -> io::Result<()> {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    self.field(key, value, Line::Indented(0), 0)?;
+                }
+                Ok(())
+            }
+            Value::Array(items) => self.array(None, items, Line::Indented(0), 1),
+            primitive => {
+                self.start_line(Line::Indented(0))?;
+                self.primitive(primitive)
+            }
+        }
+    }
+
+    /// Write an object field whose nested content belongs at `depth + 1`.
+    pub(crate) fn field(
+        &mut self,
+        key: &str,
+        value: &Value,
+        line: Line,
+        depth: usize,
+    ) -> io::Result<()> {
+        match value {
+            Value::Object(fields) => {
+                self.object_header(key, line)?;
+                for (key, value) in fields {
+                    self.field(key, value, Line::Indented(depth + 1), depth + 1)?;
+                }
+                Ok(())
+            }
+            Value::Array(items) => self.array(Some(key), items, line, depth + 1),
+            primitive => {
+                self.start_line(line)?;
+                self.key(key)?;
+                self.writer.write_all(b": ")?;
+                self.primitive(primitive)
+            }
+        }
+    }
+
+    /// Write the `key:` line that opens a nested object.
+    pub(crate) fn object_header(&mut self, key: &str, line: Line) -> io::Result<()> {
+        self.start_line(line)?;
+        self.key(key)?;
+        self.writer.write_all(b":")
+    }
+
+    /// Write an array whose list items belong at `depth`.
+    pub(crate) fn array(
+        &mut self,
+        key: Option<&str>,
+        items: &[Value],
+        line: Line,
+        depth: usize,
+    ) -> io::Result<()> {
+        let form = self.array_header(key, items, line)?;
+        if form != ArrayForm::Inline {
+            for item in items {
+                self.item(form, item, depth)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write one item of a list or table at `depth`.
+    fn item(&mut self, form: ArrayForm, item: &Value, depth: usize) -> io::Result<()> {
+        match (form, item) {
+            (ArrayForm::Tabular, Value::Object(fields)) => self.row(fields, depth),
+            _ => self.list_item(item, depth),
+        }
+    }
+
+    /// Write the `key[N]:` header, and the items too if they fit inline.
+    fn array_header(
+        &mut self,
+        key: Option<&str>,
+        items: &[Value],
+        line: Line,
+    ) -> io::Result<ArrayForm> {
+        self.start_line(line)?;
+        if let Some(key) = key {
+            self.key(key)?;
+        }
+        write!(
+            self.writer,
+            "[{}{}]",
+            items.len(),
+            self.options.delimiter.marker()
+        )?;
+
+        let form = ArrayForm::of(items, self.options);
+        if form == ArrayForm::Tabular
+            && let Some(fields) = table_fields(items)
+        {
+            self.writer.write_all(b"{")?;
+            for (i, (key, _)) in fields.iter().enumerate() {
+                if i > 0 {
+                    self.delimiter()?;
+                }
+                self.key(key)?;
+            }
+            self.writer.write_all(b"}")?;
+
+            self.columns = fields.iter().map(|(key, _)| key.clone()).collect();
+            self.widths = if self.spaced() {
+                self.column_widths(items)?
+            } else {
+                Vec::new()
+            };
+        }
+        self.writer.write_all(b":")?;
+
+        if form == ArrayForm::Inline && !items.is_empty() {
+            self.writer.write_all(b" ")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    self.delimiter()?;
+                }
+                self.primitive(item)?;
+            }
+        }
+        Ok(form)
+    }
+
+    /// Write the `key[N]:` header of an array laid out as list items.
+    pub(crate) fn list_header(
+        &mut self,
+        key: Option<&str>,
+        len: usize,
+        line: Line,
+    ) -> io::Result<()> {
+        self.start_line(line)?;
+        if let Some(key) = key {
+            self.key(key)?;
+        }
+        write!(self.writer, "[{}{}]:", len, self.options.delimiter.marker())
+    }
+
+    /// Write the values of a table row at `depth`, in header order.
+    fn row(&mut self, fields: &[(String, Value)], depth: usize) -> io::Result<()> {
+        self.start_line(Line::Indented(depth))?;
+        let columns = std::mem::take(&mut self.columns);
+        let cells = columns.iter().filter_map(|key| cell(fields, key));
+        let mut pad = 0;
+        for (i, value) in cells.enumerate() {
+            if i > 0 {
+                self.delimiter()?;
+                write!(self.writer, "{:1$}", "", pad)?;
+            }
+            match self.widths.get(i) {
+                Some(&width) => {
+                    let cell = self.render(value)?;
+                    self.writer.write_all(cell.as_bytes())?;
+                    pad = width - cell.chars().count();
+                }
+                None => self.primitive(value)?,
+            }
+        }
+        self.columns = columns;
+        Ok(())
+    }
+
+    /// The widest rendered value in each column of a table.
+    fn column_widths(&self, items: &[Value]) -> io::Result<Vec<usize>> {
+        let mut widths = vec![0; self.columns.len()];
+        for item in items {
+            if let Value::Object(fields) = item {
+                let cells = self.columns.iter().filter_map(|key| cell(fields, key));
+                for (width, value) in widths.iter_mut().zip(cells) {
+                    *width = (*width).max(self.render(value)?.chars().count());
+                }
+            }
+        }
+        Ok(widths)
+    }
+
+    /// Render a primitive as it would be written.
+    fn render(&self, value: &Value) -> io::Result<String> {
+        let mut encoder = Encoder::new(Vec::new(), self.options);
+        encoder.primitive(value)?;
+        Ok(String::from_utf8_lossy(&encoder.writer).into_owned())
+    }
+
+    /// Write a `- item` line with its hyphen at `depth`.
+    pub(crate) fn list_item(&mut self, item: &Value, depth: usize) -> io::Result<()> {
+        match item {
+            Value::Object(fields) => match fields.split_first() {
+                // The first field shares the hyphen's line
+                Some(((key, value), rest)) => {
+                    self.field(key, value, Line::ListItem(depth), depth + 1)?;
+                    for (key, value) in rest {
+                        self.field(key, value, Line::Indented(depth + 1), depth + 1)?;
+                    }
+                    Ok(())
+                }
+                None => self.empty_list_item(depth),
+            },
+            Value::Array(items) => self.array(None, items, Line::ListItem(depth), depth + 1),
+            primitive => {
+                self.start_line(Line::ListItem(depth))?;
+                self.primitive(primitive)
+            }
+        }
+    }
+
+    /// Write a list item holding an empty object.
+    pub(crate) fn empty_list_item(&mut self, depth: usize) -> io::Result<()> {
+        self.start_line(Line::Indented(depth))?;
+        self.writer.write_all(b"-")
+    }
+
+    fn start_line(&mut self, line: Line) -> io::Result<()> {
+        if self.started {
+            self.writer.write_all(b"\n")?;
+        }
+        self.started = true;
+
+        match line {
+            Line::Indented(depth) => write!(self.writer, "{:1$}", "", depth * INDENT),
+            Line::ListItem(depth) => write!(self.writer, "{:1$}- ", "", depth * INDENT),
+        }
+    }
+
+    fn key(&mut self, key: &str) -> io::Result<()> {
+        if is_bare_key(key) {
+            self.writer.write_all(key.as_bytes())
+        } else {
+            self.quoted(key)
+        }
+    }
+
+    fn primitive(&mut self, value: &Value) -> io::Result<()> {
+        match value {
+            Value::Null => self.writer.write_all(b"null"),
+            Value::Bool(b) => write!(self.writer, "{}", b),
+            // Floats in canonical decimal form: no exponent, no trailing
+            // `.0`, and no negative zero
+            Value::Number(n) if n.is_f64() => match n.as_f64() {
+                Some(0.0) => self.writer.write_all(b"0"),
+                Some(f) => write!(self.writer, "{}", f),
+                None => write!(self.writer, "{}", n),
+            },
+            Value::Number(n) => write!(self.writer, "{}", n),
+            Value::String(s) if self.quote(s) => self.quoted(s),
+            Value::String(s) => self.writer.write_all(s.as_bytes()),
+            Value::Array(_) | Value::Object(_) => {
+                unreachable!("only primitives are written inline")
+            }
+        }
+    }
+
+    fn quote(&self, s: &str) -> bool {
+        let delimiter = self.options.delimiter.as_char();
+        match self.options.quoting {
+            Quoting::Minimal => needs_quotes(s, delimiter),
+            Quoting::Always => true,
+            Quoting::Never => must_quote(s, delimiter),
+        }
+    }
+
+    fn delimiter(&mut self) -> io::Result<()> {
+        let mut buf = [0; 4];
+        let delimiter = self.options.delimiter.as_char().encode_utf8(&mut buf);
+        self.writer.write_all(delimiter.as_bytes())?;
+        if self.spaced() {
+            self.writer.write_all(b" ")?;
+        }
+        Ok(())
+    }
+
+    /// Whether delimiters are followed by a space and columns are aligned.
+    fn spaced(&self) -> bool {
+        self.options.pretty && self.options.delimiter != Delimiter::Tab
+    }
+
+    fn quoted(&mut self, s: &str) -> io::Result<()> {
+        self.writer.write_all(b"\"")?;
+        let mut rest = s;
+        while let Some(pos) = rest.find(['"', '\\', '\n', '\r', '\t']) {
+            self.writer.write_all(&rest.as_bytes()[..pos])?;
+            let escaped: &[u8] = match rest.as_bytes()[pos] {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                _ => b"\\t",
+            };
+            self.writer.write_all(escaped)?;
+            rest = &rest[pos + 1..];
+        }
+        self.writer.write_all(rest.as_bytes())?;
+        self.writer.write_all(b"\"")
+    }
+}
+
+/// Check if a key can be written without quotes.
+fn is_bare_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Check if a string value must be quoted for its line to parse.
+fn must_quote(s: &str, delimiter: char) -> bool {
+    s.is_empty()
+        || s.trim() != s
+        || s.contains(delimiter)
+        || s.chars().any(|c| matches!(c, ':' | '"') || c.is_control())
+}
+
+/// Check if a string value must be quoted to read back as the same string.
+fn needs_quotes(s: &str, delimiter: char) -> bool {
+    must_quote(s, delimiter)
+        || matches!(s, "true" | "false" | "null")
+        || looks_numeric(s)
+        || s.starts_with('-')
+        || s.chars().any(|c| matches!(c, '\\' | '[' | ']' | '{' | '}'))
+}
+
+/// Check if a string would be read back as a number.
+fn looks_numeric(s: &str) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    digits.starts_with(|c: char| c.is_ascii_digit()) && s.parse::<f64>().is_ok()
+        // Leading zeros such as "007" are kept as strings
+        || digits.len() > 1 && digits.starts_with('0') && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::to_value;
+    use serde::Serialize;
+
+    fn encode<T: Serialize>(value: &T) -> String {
+        encode_with(value, Options::default())
+    }
+
+    fn encode_with<T: Serialize>(value: &T, options: Options) -> String {
+        let mut out = Vec::new();
+        Encoder::new(&mut out, options)
+            .document(&to_value(value).unwrap())
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[derive(Serialize)]
+    struct User {
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_encode_object() {
+        let user = User {
+            id: 1,
+            name: "Ada Lovelace".to_string(),
+            tags: vec!["admin".to_string(), "ops".to_string()],
+        };
+        assert_eq!(
+            encode(&user),
+            "id: 1\nname: Ada Lovelace\ntags[2]: admin,ops"
+        );
+    }
+
+    #[test]
+    fn test_encode_nested() {
+        #[derive(Serialize)]
+        struct Team {
+            name: String,
+            members: Vec<User>,
+            meta: std::collections::BTreeMap<String, u32>,
+        }
+
+        let team = Team {
+            name: "core".to_string(),
+            members: vec![User {
+                id: 1,
+                name: "Ada".to_string(),
+                tags: vec![],
+            }],
+            meta: [("size".to_string(), 1)].into(),
+        };
+        assert_eq!(
+            encode(&team),
+            "name: core\nmembers[1]:\n  - id: 1\n    name: Ada\n    tags[0]:\nmeta:\n  size: 1"
+        );
+    }
+
+    #[test]
+    fn test_encode_root_array_and_primitive() {
+        assert_eq!(encode(&vec![1, 2, 3]), "[3]: 1,2,3");
+        assert_eq!(encode(&vec![vec![1], vec![]]), "[2]:\n  - [1]: 1\n  - [0]:");
+        assert_eq!(encode(&"hello"), "hello");
+        assert_eq!(encode(&Option::<u8>::None), "null");
+    }
+
+    #[test]
+    fn test_quoting() {
+        let values = vec![
+            "",
+            " padded",
+            "true",
+            "42",
+            "-1",
+            "007",
+            "a,b",
+            "x: y",
+            "line\nbreak",
+        ];
+        assert_eq!(
+            encode(&values),
+            r#"[9]: ""," padded","true","42","-1","007","a,b","x: y","line\nbreak""#
+        );
+        assert_eq!(encode(&vec!["plain text", "v1.2"]), "[2]: plain text,v1.2");
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert("content-type", 1);
+        assert_eq!(encode(&map), "\"content-type\": 1");
+    }
+
+    #[test]
+    fn test_encode_tabular() {
+        #[derive(Serialize)]
+        struct Row {
+            id: u32,
+            name: &'static str,
+        }
+
+        #[derive(Serialize)]
+        struct Page {
+            rows: Vec<Row>,
+            total: u32,
+        }
+
+        let page = Page {
+            rows: vec![
+                Row { id: 1, name: "Ada" },
+                Row {
+                    id: 2,
+                    name: "Grace, Admiral",
+                },
+            ],
+            total: 2,
+        };
+        let tabular = Options {
+            tabular: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            encode_with(&page, tabular),
+            "rows[2]{id,name}:\n  1,Ada\n  2,\"Grace, Admiral\"\ntotal: 2"
+        );
+        assert_eq!(
+            encode_with(&page.rows, tabular),
+            "[2]{id,name}:\n  1,Ada\n  2,\"Grace, Admiral\""
+        );
+        assert_eq!(
+            encode_with(&vec![page], tabular),
+            "[1]:\n  - rows[2]{id,name}:\n      1,Ada\n      2,\"Grace, Admiral\"\n    total: 2"
+        );
+
+        // Nested or mismatched objects fall back to list items
+        let mixed = serde_json::json!([{ "id": 1 }, { "name": "Ada" }]);
+        assert_eq!(
+            encode_with(&mixed, tabular),
+            "[2]:\n  - id: 1\n  - name: Ada"
+        );
+        assert_eq!(
+            encode_with(
+                &vec![User {
+                    id: 1,
+                    name: "Ada".to_string(),
+                    tags: vec![],
+                }],
+                tabular
+            ),
+            "[1]:\n  - id: 1\n    name: Ada\n    tags[0]:"
+        );
+    }
+
+    #[test]
+    fn test_encode_delimiters() {
+        let rows = serde_json::json!([
+            { "id": 1, "note": "a,b" },
+            { "id": 2, "note": "c|d" },
+        ]);
+        let options = |delimiter| Options {
+            tabular: true,
+            delimiter,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            encode_with(&rows, options(Delimiter::Tab)),
+            "[2\t]{id\tnote}:\n  1\ta,b\n  2\tc|d"
+        );
+        assert_eq!(
+            encode_with(&rows, options(Delimiter::Pipe)),
+            "[2|]{id|note}:\n  1|a,b\n  2|\"c|d\""
+        );
+        assert_eq!(
+            encode_with(&vec!["a\tb", "c"], options(Delimiter::Tab)),
+            "[2\t]: \"a\\tb\"\tc"
+        );
+    }
+
+    #[test]
+    fn test_quoting_policy() {
+        let values = vec!["plain", "true", "42", "a,b", "x: y", ""];
+        let options = |quoting| Options {
+            quoting,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            encode_with(&values, options(Quoting::Minimal)),
+            r#"[6]: plain,"true","42","a,b","x: y","""#
+        );
+        assert_eq!(
+            encode_with(&values, options(Quoting::Always)),
+            r#"[6]: "plain","true","42","a,b","x: y","""#
+        );
+        assert_eq!(
+            encode_with(&values, options(Quoting::Never)),
+            r#"[6]: plain,true,42,"a,b","x: y","""#
+        );
+    }
+
+    #[test]
+    fn test_encode_pretty() {
+        let pretty = Options {
+            pretty: true,
+            ..Options::default()
+        };
+        let user = User {
+            id: 1,
+            name: "Ada".to_string(),
+            tags: vec!["admin".to_string(), "ops".to_string()],
+        };
+        assert_eq!(
+            encode_with(&user, pretty),
+            "id: 1\nname: Ada\ntags[2]:\n  - admin\n  - ops"
+        );
+
+        let rows = serde_json::json!([
+            { "id": 1, "name": "Ada", "role": "admin" },
+            { "id": 20, "name": "Grace, Admiral", "role": "ops" },
+        ]);
+        let table = Options {
+            tabular: true,
+            ..pretty
+        };
+        assert_eq!(
+            encode_with(&rows, table),
+            "[2]{id, name, role}:\n  1,  Ada,              admin\n  20, \"Grace, Admiral\", ops"
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Lazy decoding of top-level TOON arrays.
+
+use crate::de::{self, Head, Line, Parser, Source};
+use crate::value::Value;
+use crate::{Result, ToonError};
+use serde::de::DeserializeOwned;
+use std::io::BufRead;
+use std::marker::PhantomData;
+
+/// Lazily deserializes the items of a top-level TOON array.
+///
+/// Each call to [`next`](Iterator::next) parses and deserializes one item,
+/// so a large array never has to be held in memory as a whole. Inline,
+/// list, and tabular arrays are supported.
+///
+/// Malformed input, a count that does not match the `[N]` header, or content
+/// after the array yields an error, after which the iterator ends. An item
+/// that parses but does not deserialize into `T` yields an error without
+/// ending iteration.
+///
+/// # Example
+///
+/// ```rust
+/// use armature_toon::ToonArrayReader;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User { id: u32, name: String }
+///
+/// let toon = "[2]{id,name}:\n  1,Alice\n  2,Bob";
+/// for user in ToonArrayReader::<User>::new(toon).unwrap() {
+///     let user = user.unwrap();
+///     println!("{}: {}", user.id, user.name);
+/// }
+/// ```
+pub struct ToonArrayReader<'a, T> {
+    parser: Parser<'a>,
+    items: Items,
+    /// The `[N]:` header line.
+    header: Line<'a>,
+    declared: usize,
+    read: usize,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// How the remaining items of a [`ToonArrayReader`] are stored.
+enum Items {
+    /// Already parsed from the header line.
+    Inline(std::vec::IntoIter<Value>),
+    /// One row per line, with these column names and delimiter.
+    Rows(Vec<String>, char),
+    /// One `- item` per item.
+    List,
+}
+
+impl<'a, T: DeserializeOwned> ToonArrayReader<'a, T> {
+    /// Read the array in a TOON string.
+    ///
+    /// Fails if the document does not start with a top-level array header.
+    pub fn new(input: &'a str) -> Result<Self> {
+        Self::start(Parser::new(Source::Str(input.lines())))
+    }
+
+    /// Read the array from a reader, consuming input line by line.
+    ///
+    /// Fails if the document does not start with a top-level array header.
+    pub fn from_reader<R: BufRead + 'a>(reader: R) -> Result<Self> {
+        Self::start(Parser::new(Source::Reader(Box::new(reader))))
+    }
+
+    fn start(mut parser: Parser<'a>) -> Result<Self> {
+        let Some(line) = parser.next_line()? else {
+            return Err(ToonError::DeserializeError(
+                "expected a top-level array".to_string(),
+            ));
+        };
+        let Some(Head {
+            key: None,
+            array: Some(header),
+            rest,
+        }) = de::head(&line, &line.content)?.filter(|_| line.depth == 0)
+        else {
+            return Err(line.error("expected a top-level array"));
+        };
+
+        let items = match header.fields {
+            Some(_) if !rest.is_empty() => {
+                return Err(line.error_at(rest, "unexpected values after a table header"));
+            }
+            Some(fields) => Items::Rows(fields, header.delimiter),
+            None if !rest.is_empty() => {
+                Items::Inline(de::inline(&line, rest, header.delimiter)?.into_iter())
+            }
+            None => Items::List,
+        };
+        let declared = header.len;
+
+        Ok(Self {
+            parser,
+            items,
+            header: line,
+            declared,
+            read: 0,
+            done: false,
+            _marker: PhantomData,
+        })
+    }
+
+    fn next_value(&mut self) -> Result<Option<Value>> {
+        match &mut self.items {
+            Items::Inline(values) => Ok(values.next()),
+            Items::Rows(fields, delimiter) => self.parser.row(fields, *delimiter, 1, self.read + 1),
+            Items::List => self.parser.list_item(1),
+        }
+    }
+
+    /// Check the item count and that nothing follows the array.
+    fn finish(&mut self) -> Result<()> {
+        if self.read != self.declared {
+            return Err(de::count_error(&self.header, self.declared, self.read));
+        }
+        match self.parser.peek()? {
+            Some(line) => Err(line.error("unexpected content after the array")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for ToonArrayReader<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = match self.next_value() {
+            Ok(Some(_)) if self.read == self.declared => Err(self.header.error(format!(
+                "array declares {} items but has more",
+                self.declared
+            ))),
+            Ok(Some(value)) => {
+                self.read += 1;
+                return Some(de::deserialize(value));
+            }
+            Ok(None) => match self.finish() {
+                Ok(()) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        };
+        self.done = true;
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct User {
+        id: u32,
+        name: String,
+    }
+
+    fn user(id: u32, name: &str) -> User {
+        User {
+            id,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_array_reader_table() {
+        let toon = "[3]{id,name}:\n  1,Ada\n  2,Grace\n  3,\"Hopper, Grace\"";
+        let mut reader = ToonArrayReader::<User>::new(toon).unwrap();
+
+        assert_eq!(reader.next().unwrap().unwrap(), user(1, "Ada"));
+        assert_eq!(reader.next().unwrap().unwrap(), user(2, "Grace"));
+        assert_eq!(reader.next().unwrap().unwrap(), user(3, "Hopper, Grace"));
+        assert!(reader.next().is_none());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_array_reader_from_reader() {
+        let toon = "[2]:\r\n  - id: 1\r\n    name: Ada\r\n\r\n  - id: 2\r\n    name: Grace\r\n";
+        let users = ToonArrayReader::<User>::from_reader(toon.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(users, vec![user(1, "Ada"), user(2, "Grace")]);
+
+        let ids = ToonArrayReader::<u32>::new("[3]: 1,2,3")
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_array_reader_errors() {
+        assert!(ToonArrayReader::<User>::new("id: 1").is_err());
+        assert!(ToonArrayReader::<User>::new("").is_err());
+
+        // Trailing junk ends iteration with an error
+        let mut reader = ToonArrayReader::<User>::new("[1]{id,name}:\n  1,Ada\nextra: 1").unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(
+            reader.next().unwrap(),
+            Err(ToonError::ParseError { line: 3, .. })
+        ));
+        assert!(reader.next().is_none());
+
+        // More or fewer items than declared
+        let results: Vec<_> = ToonArrayReader::<u32>::new("[1]: 1,2").unwrap().collect();
+        assert!(results[0].is_ok() && results[1].is_err());
+        let results: Vec<_> = ToonArrayReader::<u32>::new("[3]: 1,2").unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+
+        // A bad item is skipped without ending iteration
+        let results: Vec<_> = ToonArrayReader::<u32>::new("[2]: x,2").unwrap().collect();
+        assert!(results[0].is_err());
+        assert_eq!(results[1].as_ref().unwrap(), &2);
+    }
+}
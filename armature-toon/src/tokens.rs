@@ -0,0 +1,89 @@
+//! Exact token counting with tiktoken BPE encodings.
+//!
+//! The `chars / 4` estimate used elsewhere is a rough average for prose and
+//! can be far off for structured data, where punctuation and short keys each
+//! cost a token. These counts match what OpenAI models bill for.
+
+use tiktoken_rs::CoreBPE;
+
+/// A tiktoken BPE encoding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// GPT-4 and GPT-3.5 Turbo.
+    #[default]
+    Cl100kBase,
+    /// GPT-4o and later models.
+    O200kBase,
+    /// Codex and `text-davinci-002`/`003`.
+    P50kBase,
+    /// GPT-3 models such as `davinci`.
+    R50kBase,
+}
+
+impl Encoding {
+    fn bpe(self) -> &'static CoreBPE {
+        match self {
+            Encoding::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            Encoding::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Encoding::P50kBase => tiktoken_rs::p50k_base_singleton(),
+            Encoding::R50kBase => tiktoken_rs::r50k_base_singleton(),
+        }
+    }
+}
+
+/// Count the tokens `text` encodes to.
+///
+/// Special tokens such as `<|endoftext|>` are counted as ordinary text.
+///
+/// # Example
+///
+/// ```rust
+/// use armature_toon::{Encoding, count_tokens};
+///
+/// assert_eq!(count_tokens("hello world", Encoding::Cl100kBase), 2);
+/// ```
+pub fn count_tokens(text: &str, encoding: Encoding) -> usize {
+    encoding.bpe().encode_ordinary(text).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenCounter, estimate_tokens};
+
+    #[test]
+    fn test_count_tokens_beats_estimate() {
+        // Structured data is dense in single-character tokens: this is 36
+        // chars but 15 cl100k_base tokens, per OpenAI's tokenizer
+        let text = r#"{"id":1,"ok":true,"tags":["a","b"]}"#;
+        let known = 15;
+
+        let exact = count_tokens(text, Encoding::Cl100kBase);
+        let estimate = estimate_tokens(text);
+        assert_eq!(exact, known);
+        assert_ne!(exact, estimate);
+        assert!(estimate.abs_diff(known) > exact.abs_diff(known));
+    }
+
+    #[test]
+    fn test_token_counter_with_encoding() {
+        let mut exact = TokenCounter::with_encoding(Encoding::Cl100kBase);
+        let mut estimated = TokenCounter::new();
+        for counter in [&mut exact, &mut estimated] {
+            counter.add_raw("hello world");
+        }
+
+        assert_eq!(exact.total_tokens(), 2);
+        assert_eq!(estimated.total_tokens(), 3);
+        assert_eq!(exact.total_tokens_estimate(), 3);
+    }
+
+    #[test]
+    fn test_encodings_differ() {
+        let text = "    indented code with\ttabs";
+        assert_ne!(
+            count_tokens(text, Encoding::R50kBase),
+            count_tokens(text, Encoding::Cl100kBase)
+        );
+    }
+}
@@ -0,0 +1,910 @@
+//! Serde serializer that encodes TOON as values are visited.
+//!
+//! [`Serializer`] drives the [`Encoder`] from a `Serialize` impl directly, so
+//! writing a document never builds a [`Value`] tree for the whole input.
+//! Objects and list items are written as they arrive. An array is only held
+//! back while its layout is undecided: primitive items, which may still fit on
+//! the header line, and, with tabular output, flat objects that may still form
+//! a table. The first item that rules both out flushes the held items as list
+//! items and the rest are written one by one. Sequences that don't report
+//! their length are collected whole, since the header needs it.
+
+use crate::ser::{Encoder, Line, fits_table};
+use crate::value::{MapKeySerializer, Value, to_value};
+use crate::{Result, ToonError};
+use serde::Serialize;
+use serde::ser;
+use std::io::Write;
+
+/// Where the value being serialized is written.
+#[derive(Debug, Clone, Copy)]
+enum Slot<'k> {
+    /// The whole document.
+    Root,
+    /// An object field on `line`, with nested content at `depth + 1`.
+    Field {
+        key: &'k str,
+        line: Line,
+        depth: usize,
+    },
+    /// A list item with its hyphen at `depth`.
+    Item { depth: usize },
+}
+
+/// Serializes a value as TOON straight into an [`Encoder`].
+pub(crate) struct Serializer<'a, 'k, W> {
+    encoder: &'a mut Encoder<W>,
+    slot: Slot<'k>,
+}
+
+impl<'a, W: Write> Serializer<'a, 'static, W> {
+    /// Serialize a whole document.
+    pub(crate) fn new(encoder: &'a mut Encoder<W>) -> Self {
+        Self {
+            encoder,
+            slot: Slot::Root,
+        }
+    }
+}
+
+impl<'a, 'k, W: Write> Serializer<'a, 'k, W> {
+    fn primitive(self, value: Value) -> Result<()> {
+        match self.slot {
+            Slot::Root => self.encoder.document(&value)?,
+            Slot::Field { key, line, depth } => self.encoder.field(key, &value, line, depth)?,
+            Slot::Item { depth } => self.encoder.list_item(&value, depth)?,
+        }
+        Ok(())
+    }
+
+    fn object(self) -> Result<SerializeObject<'a, W>> {
+        let object = match self.slot {
+            Slot::Root => SerializeObject::new(self.encoder, Line::Indented(0), 0, None),
+            Slot::Field { key, line, depth } => {
+                self.encoder.object_header(key, line)?;
+                SerializeObject::new(self.encoder, Line::Indented(depth + 1), depth + 1, None)
+            }
+            // The first field shares the hyphen's line
+            Slot::Item { depth } => {
+                SerializeObject::new(self.encoder, Line::ListItem(depth), depth + 1, Some(depth))
+            }
+        };
+        Ok(object)
+    }
+
+    fn array(self, len: Option<usize>) -> Result<SerializeArray<'a, 'k, W>> {
+        let (key, line, depth) = match self.slot {
+            Slot::Root => (None, Line::Indented(0), 1),
+            Slot::Field { key, line, depth } => (Some(key), line, depth + 1),
+            Slot::Item { depth } => (None, Line::ListItem(depth), depth + 1),
+        };
+        let mut array = SerializeArray {
+            encoder: self.encoder,
+            key,
+            line,
+            depth,
+            len,
+            held: Vec::new(),
+            listing: false,
+            written: 0,
+        };
+
+        // Without tables, arrays that can't be inline are lists once non-empty
+        let options = array.encoder.options();
+        if !options.inline_arrays() && !options.tabular && len.is_some_and(|len| len > 0) {
+            array.start_list()?;
+        }
+        Ok(array)
+    }
+
+    /// Open the single-field object an enum variant is written as, returning
+    /// the serializer for its field.
+    fn variant(self, variant: &'static str) -> Result<Serializer<'a, 'static, W>> {
+        let object = self.object()?;
+        Ok(Serializer {
+            encoder: object.encoder,
+            slot: Slot::Field {
+                key: variant,
+                line: object.next_line,
+                depth: object.depth,
+            },
+        })
+    }
+}
+
+impl<'a, 'k, W: Write> ser::Serializer for Serializer<'a, 'k, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    type SerializeSeq = SerializeArray<'a, 'k, W>;
+    type SerializeTuple = SerializeArray<'a, 'k, W>;
+    type SerializeTupleStruct = SerializeArray<'a, 'k, W>;
+    type SerializeTupleVariant = SerializeArray<'a, 'static, W>;
+    type SerializeMap = SerializeObject<'a, W>;
+    type SerializeStruct = SerializeObject<'a, W>;
+    type SerializeStructVariant = SerializeObject<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.primitive(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.primitive(to_value(&v)?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.primitive(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.primitive(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.collect_seq(v)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.primitive(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.primitive(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.primitive(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.primitive(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self.variant(variant)?)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.array(len)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.array(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.array(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.variant(variant)?.array(Some(len))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.object()
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        self.object()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.variant(variant)?.object()
+    }
+}
+
+/// Writes the fields of an object as they are serialized.
+pub(crate) struct SerializeObject<'a, W> {
+    encoder: &'a mut Encoder<W>,
+    /// Line the next field starts on.
+    next_line: Line,
+    /// Depth of the fields.
+    depth: usize,
+    /// Hyphen depth of a list item that has no fields yet.
+    empty_item: Option<usize>,
+    next_key: Option<String>,
+}
+
+impl<'a, W: Write> SerializeObject<'a, W> {
+    fn new(
+        encoder: &'a mut Encoder<W>,
+        first_line: Line,
+        depth: usize,
+        empty_item: Option<usize>,
+    ) -> Self {
+        Self {
+            encoder,
+            next_line: first_line,
+            depth,
+            empty_item,
+            next_key: None,
+        }
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<()> {
+        let slot = Slot::Field {
+            key,
+            line: self.next_line,
+            depth: self.depth,
+        };
+        self.next_line = Line::Indented(self.depth);
+        self.empty_item = None;
+        value.serialize(Serializer {
+            encoder: &mut *self.encoder,
+            slot,
+        })
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Some(depth) = self.empty_item {
+            self.encoder.empty_list_item(depth)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeMap for SerializeObject<'_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ToonError::SerializeError("map value without a key".to_string()))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for SerializeObject<'_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for SerializeObject<'_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Writes the items of an array, holding them back while the layout is
+/// undecided.
+pub(crate) struct SerializeArray<'a, 'k, W> {
+    encoder: &'a mut Encoder<W>,
+    key: Option<&'k str>,
+    line: Line,
+    /// Depth of list items.
+    depth: usize,
+    /// Length reported by the `Serialize` impl.
+    len: Option<usize>,
+    /// Items that may still be written inline or as a table.
+    held: Vec<Value>,
+    /// Whether the header is written and items go out as list items.
+    listing: bool,
+    /// Items written as list items so far.
+    written: usize,
+}
+
+impl<'a, 'k, W: Write> SerializeArray<'a, 'k, W> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        if self.listing {
+            self.written += 1;
+            return value.serialize(Serializer {
+                encoder: &mut *self.encoder,
+                slot: Slot::Item { depth: self.depth },
+            });
+        }
+
+        if self.len.is_none() {
+            self.held.push(to_value(value)?);
+            return Ok(());
+        }
+
+        // A table needs every row before the layout is known, so items are
+        // collected one at a time until one can't be a row
+        if self.encoder.options().tabular {
+            let value = to_value(value)?;
+            if self.can_hold(&value) {
+                self.held.push(value);
+            } else {
+                self.start_list()?;
+                self.written += 1;
+                self.encoder.list_item(&value, self.depth)?;
+            }
+            return Ok(());
+        }
+
+        value.serialize(Element { array: self })
+    }
+
+    /// Check whether the held items plus `value` can still be written
+    /// inline or as a table.
+    fn can_hold(&self, value: &Value) -> bool {
+        let first = self.held.first();
+        if value.is_primitive() {
+            self.encoder.options().inline_arrays() && first.is_none_or(Value::is_primitive)
+        } else {
+            fits_table(first, value)
+        }
+    }
+
+    /// Write the header and the held items, then list further items as they
+    /// are serialized.
+    fn start_list(&mut self) -> Result<()> {
+        let len = self.len.unwrap_or(self.held.len());
+        self.encoder.list_header(self.key, len, self.line)?;
+        for item in std::mem::take(&mut self.held) {
+            self.encoder.list_item(&item, self.depth)?;
+            self.written += 1;
+        }
+        self.listing = true;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        if !self.listing {
+            self.encoder
+                .array(self.key, &self.held, self.line, self.depth)?;
+        } else if let Some(len) = self.len
+            && len != self.written
+        {
+            // The header already promised `len` items
+            return Err(ToonError::SerializeError(format!(
+                "sequence reported {} items but serialized {}",
+                len, self.written
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for SerializeArray<'_, '_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for SerializeArray<'_, '_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for SerializeArray<'_, '_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for SerializeArray<'_, '_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Serializes one item of an array that is not yet listing its items.
+///
+/// Primitives are held, since the array may still fit on its header line.
+/// Anything else switches the array to list items and is written directly.
+struct Element<'b, 'a, 'k, W> {
+    array: &'b mut SerializeArray<'a, 'k, W>,
+}
+
+impl<'b, W: Write> Element<'b, '_, '_, W> {
+    fn hold(self, value: Value) -> Result<()> {
+        self.array.held.push(value);
+        Ok(())
+    }
+
+    fn list(self) -> Result<Serializer<'b, 'static, W>> {
+        let array = self.array;
+        array.start_list()?;
+        array.written += 1;
+        Ok(Serializer {
+            encoder: &mut *array.encoder,
+            slot: Slot::Item { depth: array.depth },
+        })
+    }
+}
+
+impl<'b, W: Write> ser::Serializer for Element<'b, '_, '_, W> {
+    type Ok = ();
+    type Error = ToonError;
+
+    type SerializeSeq = SerializeArray<'b, 'static, W>;
+    type SerializeTuple = SerializeArray<'b, 'static, W>;
+    type SerializeTupleStruct = SerializeArray<'b, 'static, W>;
+    type SerializeTupleVariant = SerializeArray<'b, 'static, W>;
+    type SerializeMap = SerializeObject<'b, W>;
+    type SerializeStruct = SerializeObject<'b, W>;
+    type SerializeStructVariant = SerializeObject<'b, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.hold(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.hold(to_value(&v)?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.hold(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.hold(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.list()?.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.hold(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        self.hold(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.hold(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.hold(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.list()?
+            .serialize_newtype_variant(name, index, variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.list()?.serialize_seq(len)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.list()?.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.list()?.serialize_tuple_struct(name, len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.list()?
+            .serialize_tuple_variant(name, index, variant, len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.list()?.serialize_map(len)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.list()?.serialize_struct(name, len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.list()?
+            .serialize_struct_variant(name, index, variant, len)
+    }
+}
+
+/// Collects encoded output in fixed-size chunks for an async writer.
+///
+/// Chunks are never reallocated as output grows, and each can be freed once
+/// it is written.
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+pub(crate) struct ChunkWriter {
+    chunks: Vec<Vec<u8>>,
+}
+
+#[cfg(feature = "async")]
+impl ChunkWriter {
+    /// Bytes per chunk.
+    pub(crate) const CHUNK_SIZE: usize = 8 * 1024;
+
+    pub(crate) fn into_chunks(self) -> Vec<Vec<u8>> {
+        self.chunks
+    }
+}
+
+#[cfg(feature = "async")]
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            match self.chunks.last_mut() {
+                Some(chunk) if chunk.len() < Self::CHUNK_SIZE => {
+                    let n = rest.len().min(Self::CHUNK_SIZE - chunk.len());
+                    chunk.extend_from_slice(&rest[..n]);
+                    rest = &rest[n..];
+                }
+                _ => self.chunks.push(Vec::with_capacity(Self::CHUNK_SIZE)),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ser::{Delimiter, Options};
+    use serde::Serialize;
+    use serde_json::json;
+
+    /// Encode through the `Value` tree, independently of [`Serializer`].
+    fn tree<T: Serialize + ?Sized>(value: &T, options: Options) -> String {
+        let mut out = Vec::new();
+        Encoder::new(&mut out, options)
+            .document(&to_value(value).unwrap())
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn streamed<T: Serialize + ?Sized>(value: &T, options: Options) -> String {
+        let mut out = Vec::new();
+        value
+            .serialize(Serializer::new(&mut Encoder::new(&mut out, options)))
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[derive(Serialize)]
+    enum Event {
+        Started,
+        Moved(i32, i32),
+        Renamed(String),
+        Tagged { id: u32, tags: Vec<String> },
+    }
+
+    #[derive(Serialize)]
+    struct Report {
+        title: &'static str,
+        events: Vec<Event>,
+        scores: Vec<f64>,
+        matrix: Vec<Vec<u8>>,
+        rows: Vec<serde_json::Value>,
+        extra: Option<serde_json::Value>,
+    }
+
+    /// A sequence that doesn't report its length.
+    struct Unsized(Vec<serde_json::Value>);
+
+    impl Serialize for Unsized {
+        fn serialize<S: ser::Serializer>(
+            &self,
+            serializer: S,
+        ) -> std::result::Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.0.iter().filter(|_| true))
+        }
+    }
+
+    #[test]
+    fn test_matches_tree_encoder() {
+        let report = Report {
+            title: "weekly, v2",
+            events: vec![
+                Event::Started,
+                Event::Moved(1, -2),
+                Event::Renamed("x".to_string()),
+                Event::Tagged {
+                    id: 7,
+                    tags: vec!["a".to_string()],
+                },
+            ],
+            scores: vec![1.5, f64::NAN, 3.0],
+            matrix: vec![vec![1, 2], vec![]],
+            rows: vec![
+                json!({ "id": 1, "name": "Ada" }),
+                json!({ "id": 2, "name": "Bob" }),
+            ],
+            extra: Some(json!({ "nested": { "empty": {}, "list": [{}, 1, [2]] } })),
+        };
+        let values = [
+            serde_json::to_value(&report).unwrap(),
+            json!([]),
+            json!({}),
+            json!("root"),
+            json!([1, "two", null]),
+            json!([1, { "id": 1 }, [2]]),
+            json!([{ "id": 1 }, { "id": 2, "extra": [1] }]),
+            json!([{ "id": 1 }, 2]),
+            json!([{}, { "a": 1 }]),
+            json!([[{ "id": 1 }, { "id": 2 }]]),
+        ];
+
+        let option_sets = [
+            Options::default(),
+            Options {
+                tabular: true,
+                ..Options::default()
+            },
+            Options {
+                pretty: true,
+                ..Options::default()
+            },
+            Options {
+                pretty: true,
+                tabular: true,
+                ..Options::default()
+            },
+            Options {
+                tabular: true,
+                delimiter: Delimiter::Pipe,
+                ..Options::default()
+            },
+        ];
+
+        for options in option_sets {
+            assert_eq!(streamed(&report, options), tree(&report, options));
+            for value in &values {
+                assert_eq!(streamed(value, options), tree(value, options), "{}", value);
+            }
+            let unsized_rows = Unsized(vec![json!({ "id": 1 }), json!(2)]);
+            assert_eq!(
+                streamed(&unsized_rows, options),
+                tree(&unsized_rows, options)
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrong_length_is_an_error() {
+        /// Reports three items but serializes two.
+        struct Liar;
+
+        impl Serialize for Liar {
+            fn serialize<S: ser::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                use serde::ser::SerializeSeq;
+                let mut seq = serializer.serialize_seq(Some(3))?;
+                seq.serialize_element(&json!({ "id": 1 }))?;
+                seq.serialize_element(&json!({ "id": 2 }))?;
+                seq.end()
+            }
+        }
+
+        let mut out = Vec::new();
+        let result = Liar.serialize(Serializer::new(&mut Encoder::new(
+            &mut out,
+            Options::default(),
+        )));
+        assert!(matches!(result, Err(ToonError::SerializeError(_))));
+    }
+}
@@ -1,11 +1,15 @@
 //! Webhook client for sending outgoing webhooks
 
+use crate::retry::parse_retry_after;
+use crate::signature::headers;
 use crate::{
-    Result, WebhookConfig, WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint, WebhookError,
-    WebhookPayload, WebhookRegistry, WebhookSignature,
+    InMemoryDeliveryStore, Result, RetryResult, WebhookConfig, WebhookDelivery,
+    WebhookDeliveryStatus, WebhookDeliveryStore, WebhookEndpoint, WebhookError, WebhookPayload,
+    WebhookRegistry, WebhookSignature,
 };
 use chrono::Utc;
 use reqwest::Client;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
@@ -15,6 +19,7 @@ pub struct WebhookClient {
     config: WebhookConfig,
     http_client: Client,
     registry: Option<Arc<WebhookRegistry>>,
+    store: Arc<dyn WebhookDeliveryStore>,
 }
 
 impl WebhookClient {
@@ -27,10 +32,13 @@ impl WebhookClient {
             .build()
             .expect("Failed to create HTTP client");
 
+        let store = Arc::new(InMemoryDeliveryStore::new(config.delivery_history_capacity));
+
         Self {
             config,
             http_client,
             registry: None,
+            store,
         }
     }
 
@@ -41,6 +49,12 @@ impl WebhookClient {
         client
     }
 
+    /// Use a custom store for the delivery history
+    pub fn with_store(mut self, store: Arc<dyn WebhookDeliveryStore>) -> Self {
+        self.store = store;
+        self
+    }
+
     /// Send a webhook to a URL with automatic signing
     pub async fn send(&self, url: &str, payload: WebhookPayload) -> Result<WebhookDelivery> {
         self.send_with_secret(url, payload, None).await
@@ -89,6 +103,7 @@ impl WebhookClient {
         self.execute_with_retries(&mut delivery, request.body(body))
             .await?;
 
+        self.store.record(delivery.clone());
         Ok(delivery)
     }
 
@@ -99,6 +114,7 @@ impl WebhookClient {
         payload: WebhookPayload,
     ) -> Result<WebhookDelivery> {
         let mut delivery = WebhookDelivery::new(payload, &endpoint.url);
+        delivery.endpoint_id = Some(endpoint.id.clone());
         delivery.status = WebhookDeliveryStatus::InProgress;
 
         let body = delivery.payload.to_bytes()?;
@@ -129,6 +145,10 @@ impl WebhookClient {
             .header("X-Webhook-Event", &delivery.payload.event)
             .header("X-Webhook-Signature", signature);
 
+        if let Some(key_id) = &endpoint.key_id {
+            request = request.header(headers::KEY_ID, key_id);
+        }
+
         // Add custom headers from endpoint
         for (key, value) in &endpoint.headers {
             request = request.header(key, value);
@@ -147,6 +167,109 @@ impl WebhookClient {
             }
         }
 
+        self.store.record(delivery.clone());
+        Ok(delivery)
+    }
+
+    /// Replay a past delivery from the delivery history
+    ///
+    /// The original payload is re-sent unchanged, with an `X-Armature-Replay: true`
+    /// header and the original payload ID as the `Idempotency-Key`. If the original
+    /// delivery targeted a registered endpoint, the endpoint's current secret and
+    /// custom headers are used. The original record is left untouched; a new record
+    /// linked via `replay_of` is added to the history.
+    pub async fn replay(&self, delivery_id: &str) -> Result<WebhookDelivery> {
+        let original = self
+            .store
+            .get(delivery_id)
+            .ok_or_else(|| WebhookError::DeliveryNotFound(delivery_id.to_string()))?;
+
+        let endpoint = match (&original.endpoint_id, &self.registry) {
+            (Some(endpoint_id), Some(registry)) => Some(
+                registry
+                    .get(endpoint_id)
+                    .ok_or_else(|| WebhookError::EndpointNotFound(endpoint_id.clone()))?,
+            ),
+            _ => None,
+        };
+
+        match endpoint {
+            Some(endpoint) => {
+                let key = SigningKey {
+                    secret: &endpoint.secret,
+                    key_id: endpoint.key_id.as_deref(),
+                };
+                self.replay_delivery(&original, Some(key), &endpoint.headers)
+                    .await
+            }
+            None => self.replay_delivery(&original, None, &HashMap::new()).await,
+        }
+    }
+
+    /// Replay a past delivery, signing it with the given secret
+    ///
+    /// Use this for deliveries originally made with [`send_with_secret`](Self::send_with_secret),
+    /// since ad-hoc secrets are not kept in the delivery history.
+    pub async fn replay_with_secret(
+        &self,
+        delivery_id: &str,
+        secret: &str,
+    ) -> Result<WebhookDelivery> {
+        let original = self
+            .store
+            .get(delivery_id)
+            .ok_or_else(|| WebhookError::DeliveryNotFound(delivery_id.to_string()))?;
+
+        let key = SigningKey {
+            secret,
+            key_id: None,
+        };
+        self.replay_delivery(&original, Some(key), &HashMap::new())
+            .await
+    }
+
+    /// Re-send the payload of an earlier delivery as a new, linked delivery
+    async fn replay_delivery(
+        &self,
+        original: &WebhookDelivery,
+        key: Option<SigningKey<'_>>,
+        extra_headers: &HashMap<String, String>,
+    ) -> Result<WebhookDelivery> {
+        let mut delivery = WebhookDelivery::replay_of(original);
+        delivery.status = WebhookDeliveryStatus::InProgress;
+
+        let body = delivery.payload.to_bytes()?;
+
+        info!(
+            "Replaying webhook delivery {} to {}",
+            original.id, delivery.endpoint_url
+        );
+
+        let mut request = self
+            .http_client
+            .post(&delivery.endpoint_url)
+            .header("Content-Type", "application/json")
+            .header(headers::WEBHOOK_ID, &delivery.id)
+            .header(headers::EVENT_TYPE, &delivery.payload.event)
+            .header(headers::REPLAY, "true")
+            .header(headers::IDEMPOTENCY_KEY, &delivery.payload.id);
+
+        if let Some(key) = key {
+            let signer = WebhookSignature::new(key.secret);
+            request = request.header(headers::SIGNATURE, signer.sign(&body));
+            if let Some(key_id) = key.key_id {
+                request = request.header(headers::KEY_ID, key_id);
+            }
+        }
+
+        for (key, value) in extra_headers {
+            request = request.header(key, value);
+        }
+
+        self.execute_with_retries(&mut delivery, request.body(body))
+            .await?;
+
+        self.store.record(delivery.clone());
         Ok(delivery)
     }
 
@@ -191,6 +314,11 @@ impl WebhookClient {
             match request.send().await {
                 Ok(response) => {
                     let status = response.status();
+                    let retry_after = response
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(parse_retry_after);
                     let body = response.text().await.ok();
 
                     if status.is_success() {
@@ -203,54 +331,52 @@ impl WebhookClient {
                     }
 
                     // Check if we should retry based on status code
-                    let should_retry =
-                        Self::should_retry_status(status.as_u16()) && policy.should_retry(attempt);
-
-                    let next_retry = if should_retry {
-                        let delay = policy.delay_for_attempt(attempt);
-                        Some(Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default())
+                    let outcome: RetryResult<()> = if Self::should_retry_status(status.as_u16()) {
+                        policy.on_failure(attempt, format!("HTTP {}", status), retry_after)
                     } else {
-                        None
+                        RetryResult::Failed {
+                            attempts: attempt,
+                            error: format!("HTTP {}", status),
+                        }
                     };
 
                     warn!(
                         "Webhook delivery failed with status {} (attempt {})",
                         status, attempt
                     );
-                    delivery.mark_failed_with_status(status.as_u16(), body, next_retry);
+                    delivery.mark_failed_with_status(
+                        status.as_u16(),
+                        body,
+                        Self::next_retry_at(&outcome),
+                    );
 
-                    if !should_retry {
-                        return Ok(());
+                    match outcome.delay() {
+                        Some(delay) => tokio::time::sleep(delay).await,
+                        None => return Ok(()),
                     }
-
-                    // Wait before retry
-                    let delay = policy.delay_for_attempt(attempt);
-                    tokio::time::sleep(delay).await;
                 }
                 Err(e) => {
-                    let should_retry = policy.should_retry(attempt);
-                    let next_retry = if should_retry {
-                        let delay = policy.delay_for_attempt(attempt);
-                        Some(Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default())
-                    } else {
-                        None
-                    };
+                    let outcome: RetryResult<()> = policy.on_failure(attempt, e.to_string(), None);
 
                     error!("Webhook delivery error: {} (attempt {})", e, attempt);
-                    delivery.mark_failed(e.to_string(), next_retry);
+                    delivery.mark_failed(e.to_string(), Self::next_retry_at(&outcome));
 
-                    if !should_retry {
-                        return Ok(());
+                    match outcome.delay() {
+                        Some(delay) => tokio::time::sleep(delay).await,
+                        None => return Ok(()),
                     }
-
-                    // Wait before retry
-                    let delay = policy.delay_for_attempt(attempt);
-                    tokio::time::sleep(delay).await;
                 }
             }
         }
     }
 
+    /// Compute the timestamp of the next retry for a retry outcome
+    fn next_retry_at<T>(outcome: &RetryResult<T>) -> Option<chrono::DateTime<Utc>> {
+        outcome
+            .delay()
+            .map(|delay| Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default())
+    }
+
     /// Determine if a status code should be retried
     fn should_retry_status(status: u16) -> bool {
         matches!(
@@ -268,6 +394,17 @@ impl WebhookClient {
     pub fn registry(&self) -> Option<&Arc<WebhookRegistry>> {
         self.registry.as_ref()
     }
+
+    /// Get the delivery history store
+    pub fn deliveries(&self) -> &Arc<dyn WebhookDeliveryStore> {
+        &self.store
+    }
+}
+
+/// Secret used to sign a replayed delivery, with its optional key ID
+struct SigningKey<'a> {
+    secret: &'a str,
+    key_id: Option<&'a str>,
 }
 
 impl Default for WebhookClient {
@@ -331,4 +468,122 @@ mod tests {
         assert_eq!(delivery.status, WebhookDeliveryStatus::PermanentlyFailed);
         assert!(delivery.last_error.unwrap().contains("too large"));
     }
+
+    #[tokio::test]
+    async fn test_replay_unknown_delivery() {
+        let client = WebhookClient::default();
+
+        let result = client.replay("missing").await;
+        assert!(matches!(result, Err(WebhookError::DeliveryNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_replay_sends_idempotent_copy() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = WebhookClient::new(WebhookConfig::builder().no_retries().build());
+        let payload = WebhookPayload::new("order.created").with_id("evt_123");
+
+        let original = client
+            .send(&format!("{}/webhook", server.uri()), payload)
+            .await
+            .unwrap();
+        assert!(original.status.is_success());
+
+        Mock::given(method("POST"))
+            .and(header("X-Armature-Replay", "true"))
+            .and(header("Idempotency-Key", "evt_123"))
+            .respond_with(ResponseTemplate::new(202))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+
+        let replay = client.replay(&original.id).await.unwrap();
+
+        assert_eq!(replay.last_status_code, Some(202));
+        assert_eq!(replay.replay_of.as_deref(), Some(original.id.as_str()));
+        assert_eq!(replay.payload.id, "evt_123");
+
+        // The original record is unchanged and the replay is stored alongside it
+        let stored = client.deliveries().get(&original.id).unwrap();
+        assert_eq!(stored.last_status_code, Some(200));
+        assert!(stored.replay_of.is_none());
+        assert_eq!(client.deliveries().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_to_endpoint_key_id_header() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header(headers::KEY_ID, "2026-q4"))
+            .respond_with(ResponseTemplate::new(202))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let client = WebhookClient::new(WebhookConfig::builder().no_retries().build());
+
+        let endpoint = WebhookEndpoint::new(server.uri()).with_signing_key("2026-q4", "whsec_new");
+        let delivery = client
+            .send_to_endpoint(&endpoint, WebhookPayload::new("test"))
+            .await
+            .unwrap();
+        assert_eq!(delivery.last_status_code, Some(202));
+
+        // No key ID is sent unless one was assigned
+        let endpoint = WebhookEndpoint::new(server.uri()).with_secret("whsec_new");
+        let delivery = client
+            .send_to_endpoint(&endpoint, WebhookPayload::new("test"))
+            .await
+            .unwrap();
+        assert_eq!(delivery.last_status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_overrides_backoff() {
+        use crate::RetryPolicy;
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        // Without Retry-After this policy would wait an hour before retrying
+        let config = WebhookConfig::builder()
+            .retry_policy(RetryPolicy::fixed(2, Duration::from_secs(3600)))
+            .build();
+        let client = WebhookClient::new(config);
+
+        let delivery = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.send(&server.uri(), WebhookPayload::new("test")),
+        )
+        .await
+        .expect("Retry-After should override the computed backoff")
+        .unwrap();
+
+        assert!(delivery.status.is_success());
+        assert_eq!(delivery.attempts, 2);
+    }
 }
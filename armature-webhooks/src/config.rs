@@ -1,6 +1,6 @@
 //! Configuration for webhook client
 
-use crate::RetryPolicy;
+use crate::{JitterKind, RetryPolicy};
 use std::time::Duration;
 
 /// Configuration for the webhook client
@@ -26,6 +26,12 @@ pub struct WebhookConfig {
 
     /// Default signing algorithm
     pub signing_algorithm: SigningAlgorithm,
+
+    /// Number of recent deliveries kept in the delivery history
+    pub delivery_history_capacity: usize,
+
+    /// Maximum number of deliveries a dispatcher runs concurrently
+    pub max_concurrent_deliveries: usize,
 }
 
 impl Default for WebhookConfig {
@@ -38,6 +44,8 @@ impl Default for WebhookConfig {
             max_payload_size: 1024 * 1024, // 1MB
             timestamp_tolerance: 300,      // 5 minutes
             signing_algorithm: SigningAlgorithm::HmacSha256,
+            delivery_history_capacity: 1000,
+            max_concurrent_deliveries: 32,
         }
     }
 }
@@ -92,6 +100,14 @@ impl WebhookConfigBuilder {
         self
     }
 
+    /// Set the jitter strategy of the retry policy
+    ///
+    /// Retries use no jitter unless one is set here or in the policy.
+    pub fn retry_jitter(mut self, jitter: JitterKind) -> Self {
+        self.config.retry_policy.jitter = jitter;
+        self
+    }
+
     /// Disable retries
     pub fn no_retries(mut self) -> Self {
         self.config.retry_policy = RetryPolicy::none();
@@ -122,6 +138,18 @@ impl WebhookConfigBuilder {
         self
     }
 
+    /// Set the number of recent deliveries kept for inspection and replay
+    pub fn delivery_history_capacity(mut self, capacity: usize) -> Self {
+        self.config.delivery_history_capacity = capacity;
+        self
+    }
+
+    /// Set the maximum number of concurrent deliveries for dispatchers
+    pub fn max_concurrent_deliveries(mut self, max: usize) -> Self {
+        self.config.max_concurrent_deliveries = max;
+        self
+    }
+
     /// Build the configuration
     pub fn build(self) -> WebhookConfig {
         self.config
@@ -167,11 +195,15 @@ mod tests {
             .timeout_secs(60)
             .verify_ssl(false)
             .max_payload_size(2048)
+            .delivery_history_capacity(50)
+            .retry_jitter(JitterKind::Equal)
             .build();
 
         assert_eq!(config.timeout, Duration::from_secs(60));
         assert!(!config.verify_ssl);
         assert_eq!(config.max_payload_size, 2048);
+        assert_eq!(config.delivery_history_capacity, 50);
+        assert_eq!(config.retry_policy.jitter, JitterKind::Equal);
     }
 
     #[test]
@@ -0,0 +1,238 @@
+//! Concurrency-bounded fan-out of events to registered endpoints
+
+use crate::{WebhookClient, WebhookConfig, WebhookDelivery, WebhookPayload, WebhookRegistry};
+use futures::future::join_all;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::{debug, error};
+
+/// Dispatches events to every subscribed endpoint with a cap on concurrent deliveries
+///
+/// The concurrency limit is shared by all clones of a dispatcher, so it bounds
+/// the total number of in-flight deliveries across tasks.
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher {
+    client: WebhookClient,
+    registry: Arc<WebhookRegistry>,
+    semaphore: Arc<Semaphore>,
+    max_concurrent_deliveries: usize,
+}
+
+impl WebhookDispatcher {
+    /// Create a new dispatcher for the endpoints in the registry
+    pub fn new(config: WebhookConfig, registry: Arc<WebhookRegistry>) -> Self {
+        let client = WebhookClient::with_registry(config, registry.clone());
+        Self::with_client(client, registry)
+    }
+
+    /// Create a dispatcher that delivers through an existing client
+    ///
+    /// The concurrency limit is taken from the client's configuration.
+    pub fn with_client(client: WebhookClient, registry: Arc<WebhookRegistry>) -> Self {
+        let max_concurrent_deliveries = client.config().max_concurrent_deliveries.max(1);
+
+        Self {
+            client,
+            registry,
+            semaphore: Arc::new(Semaphore::new(max_concurrent_deliveries)),
+            max_concurrent_deliveries,
+        }
+    }
+
+    /// Deliver an event to all endpoints subscribed to it
+    ///
+    /// Returns one delivery record per subscribed endpoint.
+    pub async fn dispatch(
+        &self,
+        event: impl Into<String>,
+        payload: serde_json::Value,
+    ) -> Vec<WebhookDelivery> {
+        self.dispatch_payload(WebhookPayload::new(event).with_data(payload))
+            .await
+    }
+
+    /// Deliver a prepared payload to all endpoints subscribed to its event
+    pub async fn dispatch_payload(&self, payload: WebhookPayload) -> Vec<WebhookDelivery> {
+        let endpoints = self.registry.get_endpoints_for_event(&payload.event);
+
+        debug!(
+            "Dispatching {} to {} endpoints (max {} concurrent)",
+            payload.event,
+            endpoints.len(),
+            self.max_concurrent_deliveries
+        );
+
+        let deliveries = endpoints.iter().map(|endpoint| {
+            let payload = payload.clone();
+            async move {
+                let _permit = self
+                    .semaphore
+                    .acquire()
+                    .await
+                    .expect("delivery semaphore is never closed");
+
+                match self
+                    .client
+                    .send_to_endpoint(endpoint, payload.clone())
+                    .await
+                {
+                    Ok(delivery) => delivery,
+                    Err(e) => {
+                        error!("Webhook dispatch to {} failed: {}", endpoint.url, e);
+                        let mut delivery = WebhookDelivery::new(payload, &endpoint.url);
+                        delivery.endpoint_id = Some(endpoint.id.clone());
+                        delivery.mark_failed(e.to_string(), None);
+                        delivery
+                    }
+                }
+            }
+        });
+
+        join_all(deliveries).await
+    }
+
+    /// Get the maximum number of concurrent deliveries
+    pub fn max_concurrent_deliveries(&self) -> usize {
+        self.max_concurrent_deliveries
+    }
+
+    /// Get the number of deliveries that can start without waiting
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Get the underlying client
+    pub fn client(&self) -> &WebhookClient {
+        &self.client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{WebhookDeliveryStatus, WebhookEndpoint};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_zero_concurrency_clamped() {
+        let config = WebhookConfig::builder()
+            .max_concurrent_deliveries(0)
+            .build();
+        let dispatcher = WebhookDispatcher::new(config, Arc::new(WebhookRegistry::new()));
+
+        assert_eq!(dispatcher.max_concurrent_deliveries(), 1);
+        assert_eq!(dispatcher.available_permits(), 1);
+    }
+
+    /// Start an HTTP server that answers every request with 200 after `delay`
+    ///
+    /// Returns the server URL, the number of requests served and the peak
+    /// number of requests in flight at once.
+    async fn start_counting_server(
+        delay: Duration,
+    ) -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let served = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+
+        let (served_count, peak_count) = (served.clone(), peak.clone());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (served, peak, in_flight) =
+                    (served_count.clone(), peak_count.clone(), in_flight.clone());
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        // Read one request: headers, then a Content-Length body
+                        let header_end = loop {
+                            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                                break pos + 4;
+                            }
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                            }
+                        };
+                        let headers = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+                        let body_len = headers
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|len| len.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        while buf.len() < header_end + body_len {
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                            }
+                        }
+                        buf.drain(..header_end + body_len);
+
+                        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(current, Ordering::SeqCst);
+                        tokio::time::sleep(delay).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        served.fetch_add(1, Ordering::SeqCst);
+
+                        let response = b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n";
+                        if socket.write_all(response).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        (url, served, peak)
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_subscribed_endpoints() {
+        let (url, served, peak) = start_counting_server(Duration::from_millis(20)).await;
+
+        let registry = Arc::new(WebhookRegistry::new());
+        for i in 0..5 {
+            registry.register(
+                WebhookEndpoint::new(format!("{}/hook/{}", url, i))
+                    .with_events(vec!["order.created"]),
+            );
+        }
+        registry.register(
+            WebhookEndpoint::new(format!("{}/other", url)).with_events(vec!["user.created"]),
+        );
+
+        let config = WebhookConfig::builder()
+            .no_retries()
+            .max_concurrent_deliveries(2)
+            .build();
+        let dispatcher = WebhookDispatcher::new(config, registry);
+
+        let deliveries = dispatcher
+            .dispatch("order.created", serde_json::json!({"order_id": 1}))
+            .await;
+
+        assert_eq!(deliveries.len(), 5);
+        assert!(
+            deliveries
+                .iter()
+                .all(|d| d.status == WebhookDeliveryStatus::Succeeded)
+        );
+        assert_eq!(served.load(Ordering::SeqCst), 5);
+
+        // Never more deliveries in flight than the cap, but the cap is used
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(
+            peak <= dispatcher.max_concurrent_deliveries(),
+            "peak {}",
+            peak
+        );
+        assert!(peak >= 2, "peak {}", peak);
+        assert_eq!(dispatcher.available_permits(), 2);
+    }
+}
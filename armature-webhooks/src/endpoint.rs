@@ -1,5 +1,6 @@
 //! Webhook endpoint configuration
 
+use crate::WebhookReceiver;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
@@ -18,6 +19,21 @@ pub struct WebhookEndpoint {
     #[serde(skip_serializing)]
     pub secret: String,
 
+    /// Older secrets still accepted during rotation, newest first
+    ///
+    /// Deliveries are never signed with these. They are only added to the
+    /// receiver returned by [`receiver`](Self::receiver).
+    #[serde(default, skip_serializing)]
+    pub previous_secrets: Vec<String>,
+
+    /// Identifier for the signing secret, sent in the `X-Webhook-Key-Id` header
+    ///
+    /// Assigned by the caller (e.g. "2026-q4") so receivers can tell which
+    /// secret signed a request during rotation. It is opaque and not derived
+    /// from the secret. No header is sent when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+
     /// Events this endpoint is subscribed to
     pub events: HashSet<String>,
 
@@ -55,6 +71,8 @@ impl WebhookEndpoint {
             id: Uuid::new_v4().to_string(),
             url: url.into(),
             secret: generate_secret(),
+            previous_secrets: Vec::new(),
+            key_id: None,
             events: HashSet::new(),
             enabled: true,
             description: None,
@@ -78,6 +96,54 @@ impl WebhookEndpoint {
         self
     }
 
+    /// Set the signing secret and the older secrets still accepted
+    ///
+    /// The first secret is the primary and signs every delivery; the rest are
+    /// kept in [`previous_secrets`](Self::previous_secrets) for the overlap
+    /// window. An empty list leaves the signing secret unchanged. Use
+    /// [`with_key_id`](Self::with_key_id) to name the primary secret in the
+    /// `X-Webhook-Key-Id` header.
+    pub fn with_secrets<S: Into<String>>(mut self, secrets: Vec<S>) -> Self {
+        let mut secrets = secrets.into_iter().map(Into::into);
+        if let Some(primary) = secrets.next() {
+            self.secret = primary;
+        }
+        self.previous_secrets = secrets.collect();
+        self
+    }
+
+    /// Set the key ID sent in the `X-Webhook-Key-Id` header
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Set the signing secret along with its key ID
+    ///
+    /// Deliveries are signed with `secret` and carry `key_id` in the
+    /// `X-Webhook-Key-Id` header.
+    pub fn with_signing_key(
+        mut self,
+        key_id: impl Into<String>,
+        secret: impl Into<String>,
+    ) -> Self {
+        self.key_id = Some(key_id.into());
+        self.secret = secret.into();
+        self
+    }
+
+    /// Create a receiver accepting the signing secret and all previous secrets
+    ///
+    /// The primary secret is index 0 in [`WebhookReceiver::verify_key`],
+    /// followed by the previous secrets in order.
+    pub fn receiver(&self) -> WebhookReceiver {
+        let mut receiver = WebhookReceiver::new(self.secret.clone());
+        for secret in &self.previous_secrets {
+            receiver.add_secret(secret.clone());
+        }
+        receiver
+    }
+
     /// Subscribe to specific events
     pub fn with_events(mut self, events: Vec<&str>) -> Self {
         self.events = events.into_iter().map(String::from).collect();
@@ -95,25 +161,25 @@ impl WebhookEndpoint {
     }
 
     /// Check if this endpoint is subscribed to an event
+    ///
+    /// Exact subscriptions are checked first; wildcard patterns are only
+    /// evaluated when there is no exact match. See [`event_matches`] for the
+    /// wildcard syntax.
     pub fn is_subscribed_to(&self, event: &str) -> bool {
-        // Check exact match
-        if self.events.contains(event) {
-            return true;
-        }
+        self.events.contains(event) || self.matches_wildcard(event)
+    }
 
-        // Check wildcard subscriptions (e.g., "user.*" matches "user.created")
-        for subscribed in &self.events {
-            if subscribed.ends_with(".*") {
-                let prefix = &subscribed[..subscribed.len() - 2];
-                if event.starts_with(prefix) {
-                    return true;
-                }
-            } else if subscribed == "*" {
-                return true;
-            }
-        }
+    /// Check if any wildcard subscription of this endpoint matches an event
+    pub fn matches_wildcard(&self, event: &str) -> bool {
+        self.events
+            .iter()
+            .filter(|pattern| is_wildcard(pattern))
+            .any(|pattern| event_matches(pattern, event))
+    }
 
-        false
+    /// Check if this endpoint has any wildcard subscriptions
+    pub fn has_wildcard_subscriptions(&self) -> bool {
+        self.events.iter().any(|pattern| is_wildcard(pattern))
     }
 
     /// Record a successful delivery
@@ -145,6 +211,8 @@ impl WebhookEndpoint {
 
     /// Regenerate the signing secret
     pub fn rotate_secret(&mut self) -> String {
+        // The key ID named the old secret
+        self.key_id = None;
         self.secret = generate_secret();
         self.updated_at = Utc::now();
         self.secret.clone()
@@ -177,6 +245,18 @@ impl WebhookEndpointBuilder {
         self
     }
 
+    /// Set the signing secret and the older secrets still accepted
+    pub fn secrets<S: Into<String>>(mut self, secrets: Vec<S>) -> Self {
+        self.endpoint = self.endpoint.with_secrets(secrets);
+        self
+    }
+
+    /// Set the signing secret along with its key ID
+    pub fn signing_key(mut self, key_id: impl Into<String>, secret: impl Into<String>) -> Self {
+        self.endpoint = self.endpoint.with_signing_key(key_id, secret);
+        self
+    }
+
     /// Subscribe to events
     pub fn events(mut self, events: Vec<&str>) -> Self {
         self.endpoint.events = events.into_iter().map(String::from).collect();
@@ -213,6 +293,47 @@ impl WebhookEndpointBuilder {
     }
 }
 
+/// Check if an event pattern contains wildcards
+pub fn is_wildcard(pattern: &str) -> bool {
+    pattern.contains('*')
+}
+
+/// Match a hierarchical event name against a subscription pattern
+///
+/// Event names are split into segments on `.`. In a pattern, `*` matches
+/// exactly one segment and `**` matches all remaining segments (at least one).
+/// A pattern consisting only of `*` or `**` matches every event.
+///
+/// ```
+/// use armature_webhooks::event_matches;
+///
+/// assert!(event_matches("order.*", "order.created"));
+/// assert!(!event_matches("order.*", "order.items.added"));
+/// assert!(event_matches("order.**", "order.items.added"));
+/// ```
+pub fn event_matches(pattern: &str, event: &str) -> bool {
+    if pattern == "*" || pattern == "**" {
+        return true;
+    }
+
+    if !is_wildcard(pattern) {
+        return pattern == event;
+    }
+
+    let mut pattern_segments = pattern.split('.');
+    let mut event_segments = event.split('.');
+
+    loop {
+        match (pattern_segments.next(), event_segments.next()) {
+            (Some("**"), Some(_)) => return true,
+            (Some("*"), Some(_)) => continue,
+            (Some(p), Some(e)) if p == e => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
 /// Generate a random signing secret
 fn generate_secret() -> String {
     use std::time::SystemTime;
@@ -230,6 +351,7 @@ fn generate_secret() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::WebhookSignature;
 
     #[test]
     fn test_endpoint_creation() {
@@ -288,6 +410,38 @@ mod tests {
         assert!(!endpoint.is_subscribed_to("product.created"));
     }
 
+    #[test]
+    fn test_single_segment_wildcard() {
+        assert!(event_matches("order.*", "order.created"));
+        assert!(event_matches("order.*", "order.updated"));
+        assert!(!event_matches("order.*", "order.items.added"));
+        assert!(!event_matches("order.*", "order"));
+        assert!(!event_matches("order.*", "orders.created"));
+        assert!(event_matches("*.created", "user.created"));
+        assert!(!event_matches("*.created", "user.updated"));
+    }
+
+    #[test]
+    fn test_multi_segment_wildcard() {
+        assert!(event_matches("order.**", "order.created"));
+        assert!(event_matches("order.**", "order.items.added"));
+        assert!(!event_matches("order.**", "order"));
+        assert!(!event_matches("order.**", "user.created"));
+        assert!(event_matches("**", "anything.at.all"));
+    }
+
+    #[test]
+    fn test_exact_and_wildcard_subscriptions() {
+        let endpoint = WebhookEndpoint::new("https://example.com")
+            .with_events(vec!["order.*", "user.created"]);
+
+        assert!(endpoint.has_wildcard_subscriptions());
+        assert!(endpoint.is_subscribed_to("user.created"));
+        assert!(endpoint.is_subscribed_to("order.created"));
+        assert!(!endpoint.is_subscribed_to("order.items.added"));
+        assert!(!endpoint.matches_wildcard("user.created"));
+    }
+
     #[test]
     fn test_all_events_subscription() {
         let endpoint = WebhookEndpoint::builder("https://example.com")
@@ -315,6 +469,51 @@ mod tests {
         assert!(endpoint.last_success.is_some());
     }
 
+    #[test]
+    fn test_with_signing_key() {
+        let endpoint =
+            WebhookEndpoint::new("https://example.com").with_signing_key("2026-q4", "whsec_new");
+
+        assert_eq!(endpoint.secret, "whsec_new");
+        assert_eq!(endpoint.key_id.as_deref(), Some("2026-q4"));
+
+        let mut endpoint = WebhookEndpoint::builder("https://example.com")
+            .signing_key("2026-q4", "whsec_new")
+            .build();
+        endpoint.rotate_secret();
+        assert!(endpoint.key_id.is_none());
+    }
+
+    #[test]
+    fn test_with_secrets() {
+        let endpoint = WebhookEndpoint::new("https://example.com")
+            .with_secrets(vec!["whsec_new", "whsec_old"])
+            .with_key_id("2026-q4");
+
+        assert_eq!(endpoint.secret, "whsec_new");
+        assert_eq!(endpoint.previous_secrets, vec!["whsec_old".to_string()]);
+        assert_eq!(endpoint.key_id.as_deref(), Some("2026-q4"));
+
+        let payload = b"test payload";
+        let receiver = endpoint.receiver();
+        let new_signature = WebhookSignature::new("whsec_new").sign(payload);
+        let old_signature = WebhookSignature::new("whsec_old").sign(payload);
+        let other_signature = WebhookSignature::new("whsec_other").sign(payload);
+
+        assert_eq!(
+            receiver.verify_key(payload, &new_signature).unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            receiver.verify_key(payload, &old_signature).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            receiver.verify_key(payload, &other_signature).unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_secret_rotation() {
         let mut endpoint = WebhookEndpoint::new("https://example.com");
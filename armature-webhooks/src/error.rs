@@ -37,6 +37,10 @@ pub enum WebhookError {
     #[error("Endpoint not found: {0}")]
     EndpointNotFound(String),
 
+    /// Delivery not found in the delivery history
+    #[error("Delivery not found: {0}")]
+    DeliveryNotFound(String),
+
     /// Configuration error
     #[error("Configuration error: {0}")]
     ConfigError(String),
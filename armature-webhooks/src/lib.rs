@@ -10,7 +10,9 @@
 //! - **Signature Verification**: HMAC-SHA256 signing and verification
 //! - **Automatic Retries**: Configurable retry policies with exponential backoff
 //! - **Event System**: Subscribe endpoints to specific event types
+//! - **Bounded Fan-out**: Dispatch events to many endpoints with a concurrency cap
 //! - **Delivery Tracking**: Monitor webhook delivery status and history
+//! - **Replay**: Re-send past deliveries with idempotency keys
 //!
 //! # Example: Sending Webhooks
 //!
@@ -64,6 +66,7 @@
 
 mod client;
 mod config;
+mod dispatcher;
 mod endpoint;
 mod error;
 mod payload;
@@ -71,16 +74,19 @@ mod receiver;
 mod registry;
 mod retry;
 mod signature;
+mod store;
 
 pub use client::WebhookClient;
 pub use config::{WebhookConfig, WebhookConfigBuilder};
-pub use endpoint::{WebhookEndpoint, WebhookEndpointBuilder};
+pub use dispatcher::WebhookDispatcher;
+pub use endpoint::{WebhookEndpoint, WebhookEndpointBuilder, event_matches};
 pub use error::WebhookError;
 pub use payload::{WebhookDelivery, WebhookDeliveryStatus, WebhookPayload};
 pub use receiver::WebhookReceiver;
 pub use registry::WebhookRegistry;
-pub use retry::{RetryPolicy, RetryResult};
-pub use signature::WebhookSignature;
+pub use retry::{JitterKind, RetryPolicy, RetryResult, parse_retry_after};
+pub use signature::{VerificationOutcome, WebhookSignature};
+pub use store::{InMemoryDeliveryStore, WebhookDeliveryStore};
 
 /// Result type for webhook operations
 pub type Result<T> = std::result::Result<T, WebhookError>;
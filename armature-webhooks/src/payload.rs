@@ -120,6 +120,14 @@ pub struct WebhookDelivery {
     /// Target endpoint URL
     pub endpoint_url: String,
 
+    /// ID of the registered endpoint (if sent to a registered endpoint)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint_id: Option<String>,
+
+    /// ID of the original delivery if this delivery is a replay
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay_of: Option<String>,
+
     /// Current status
     pub status: WebhookDeliveryStatus,
 
@@ -156,6 +164,8 @@ impl WebhookDelivery {
             id: Uuid::new_v4().to_string(),
             payload,
             endpoint_url: endpoint_url.into(),
+            endpoint_id: None,
+            replay_of: None,
             status: WebhookDeliveryStatus::Pending,
             attempts: 0,
             last_attempt: None,
@@ -168,6 +178,22 @@ impl WebhookDelivery {
         }
     }
 
+    /// Create a new delivery record replaying an earlier delivery
+    ///
+    /// The payload (including its ID) and target are copied from the original,
+    /// while delivery state starts fresh and `replay_of` links back to it.
+    pub fn replay_of(original: &WebhookDelivery) -> Self {
+        let mut delivery = Self::new(original.payload.clone(), original.endpoint_url.clone());
+        delivery.endpoint_id = original.endpoint_id.clone();
+        delivery.replay_of = Some(original.id.clone());
+        delivery
+    }
+
+    /// Check if this delivery is a replay of an earlier delivery
+    pub fn is_replay(&self) -> bool {
+        self.replay_of.is_some()
+    }
+
     /// Record a successful delivery
     pub fn mark_succeeded(&mut self, status_code: u16, response_body: Option<String>) {
         self.status = WebhookDeliveryStatus::Succeeded;
@@ -274,4 +300,20 @@ mod tests {
         assert_eq!(delivery.status, WebhookDeliveryStatus::Succeeded);
         assert_eq!(delivery.attempts, 2);
     }
+
+    #[test]
+    fn test_delivery_replay_of() {
+        let payload = WebhookPayload::new("test");
+        let mut original = WebhookDelivery::new(payload, "https://example.com/webhook");
+        original.mark_succeeded(200, None);
+
+        let replay = WebhookDelivery::replay_of(&original);
+
+        assert!(replay.is_replay());
+        assert_ne!(replay.id, original.id);
+        assert_eq!(replay.replay_of.as_deref(), Some(original.id.as_str()));
+        assert_eq!(replay.payload.id, original.payload.id);
+        assert_eq!(replay.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(replay.attempts, 0);
+    }
 }
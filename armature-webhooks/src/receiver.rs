@@ -1,33 +1,105 @@
 //! Webhook receiver for handling incoming webhooks
 
-use crate::{Result, WebhookError, WebhookPayload, WebhookSignature};
+use crate::{
+    Result, VerificationOutcome, WebhookError, WebhookPayload, WebhookSignature, event_matches,
+};
+use std::time::Duration;
+
+/// Default timestamp tolerance (5 minutes)
+const DEFAULT_TOLERANCE: Duration = Duration::from_secs(300);
 
 /// Receiver for incoming webhooks
+///
+/// A receiver holds a primary secret and any number of additional secrets.
+/// Signatures made with any configured secret are accepted, which allows an
+/// overlap window while signing secrets are rotated.
 #[derive(Debug, Clone)]
 pub struct WebhookReceiver {
-    signature: WebhookSignature,
-    timestamp_tolerance: u64,
+    signatures: Vec<WebhookSignature>,
+    timestamp_tolerance: Duration,
 }
 
 impl WebhookReceiver {
     /// Create a new receiver with the given secret
     pub fn new(secret: impl Into<String>) -> Self {
         Self {
-            signature: WebhookSignature::new(secret),
-            timestamp_tolerance: 300, // 5 minutes default
+            signatures: vec![WebhookSignature::new(secret)],
+            timestamp_tolerance: DEFAULT_TOLERANCE,
         }
     }
 
-    /// Set the timestamp tolerance in seconds
-    pub fn with_tolerance(mut self, seconds: u64) -> Self {
-        self.timestamp_tolerance = seconds;
+    /// Accept an additional secret alongside the primary secret
+    pub fn add_secret(&mut self, secret: impl Into<String>) {
+        self.signatures.push(WebhookSignature::new(secret));
+    }
+
+    /// Make a new secret the primary secret
+    ///
+    /// Previously configured secrets remain accepted until removed.
+    pub fn set_primary_secret(&mut self, secret: impl Into<String>) {
+        self.signatures.insert(0, WebhookSignature::new(secret));
+    }
+
+    /// Stop accepting the secret at the given index
+    ///
+    /// The primary secret (index 0) cannot be removed.
+    pub fn remove_secret(&mut self, index: usize) -> Result<()> {
+        if index == 0 || index >= self.signatures.len() {
+            return Err(WebhookError::ConfigError(format!(
+                "Cannot remove secret at index {}",
+                index
+            )));
+        }
+        self.signatures.remove(index);
+        Ok(())
+    }
+
+    /// Get the number of accepted secrets
+    pub fn secret_count(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// Set the timestamp tolerance
+    ///
+    /// Signatures whose `t=` timestamp is older, or further in the future, than
+    /// the tolerance are rejected to prevent replay of captured requests.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.timestamp_tolerance = tolerance;
         self
     }
 
+    /// Get the timestamp tolerance
+    pub fn tolerance(&self) -> Duration {
+        self.timestamp_tolerance
+    }
+
     /// Verify an incoming webhook signature
     pub fn verify(&self, payload: &[u8], signature: &str) -> Result<bool> {
-        self.signature
-            .verify(payload, signature, self.timestamp_tolerance)
+        Ok(self.verify_key(payload, signature)?.is_some())
+    }
+
+    /// Verify an incoming webhook signature, returning the index of the matching secret
+    ///
+    /// Index 0 is the primary secret. Returns `Ok(None)` if no secret matches.
+    pub fn verify_key(&self, payload: &[u8], signature: &str) -> Result<Option<usize>> {
+        for (index, signer) in self.signatures.iter().enumerate() {
+            if signer.verify(payload, signature, self.timestamp_tolerance.as_secs())? {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Verify an incoming webhook signature, reporting why verification failed
+    pub fn verify_with_reason(&self, payload: &[u8], signature: &str) -> VerificationOutcome {
+        for signer in &self.signatures {
+            match signer.verify_with_reason(payload, signature, self.timestamp_tolerance.as_secs())
+            {
+                VerificationOutcome::BadSignature => continue,
+                outcome => return outcome,
+            }
+        }
+        VerificationOutcome::BadSignature
     }
 
     /// Verify and parse an incoming webhook
@@ -125,16 +197,7 @@ where
 
     /// Check if an event matches the filter
     fn matches_event(&self, event: &str) -> bool {
-        if self.event_filter == "*" {
-            return true;
-        }
-
-        if self.event_filter.ends_with(".*") {
-            let prefix = &self.event_filter[..self.event_filter.len() - 2];
-            return event.starts_with(prefix);
-        }
-
-        self.event_filter == event
+        event_matches(&self.event_filter, event)
     }
 }
 
@@ -145,13 +208,86 @@ mod tests {
     #[test]
     fn test_receiver_creation() {
         let receiver = WebhookReceiver::new("test-secret");
-        assert_eq!(receiver.timestamp_tolerance, 300);
+        assert_eq!(receiver.tolerance(), Duration::from_secs(300));
     }
 
     #[test]
     fn test_receiver_with_tolerance() {
-        let receiver = WebhookReceiver::new("test-secret").with_tolerance(60);
-        assert_eq!(receiver.timestamp_tolerance, 60);
+        let receiver = WebhookReceiver::new("test-secret").with_tolerance(Duration::from_secs(60));
+        assert_eq!(receiver.tolerance(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_verify_with_reason_rejects_stale_signature() {
+        let secret = "test-secret";
+        let receiver = WebhookReceiver::new(secret).with_tolerance(Duration::from_secs(60));
+        let signer = WebhookSignature::new(secret);
+
+        let payload = b"test payload";
+        let stale = (chrono::Utc::now().timestamp() - 120).to_string();
+        let signature = signer.sign_with_timestamp(payload, &stale);
+
+        assert_eq!(
+            receiver.verify_with_reason(payload, &signature),
+            VerificationOutcome::Expired
+        );
+        assert!(matches!(
+            receiver.verify(payload, &signature),
+            Err(WebhookError::TimestampInvalid(_))
+        ));
+
+        let fresh = signer.sign(payload);
+        assert_eq!(
+            receiver.verify_with_reason(payload, &fresh),
+            VerificationOutcome::Valid
+        );
+    }
+
+    #[test]
+    fn test_old_secret_verifies_after_rotation() {
+        let old_secret = "old-secret";
+        let new_secret = "new-secret";
+
+        let mut receiver = WebhookReceiver::new(old_secret);
+        let payload = b"test payload";
+        let old_signature = WebhookSignature::new(old_secret).sign(payload);
+
+        receiver.set_primary_secret(new_secret);
+        assert_eq!(receiver.secret_count(), 2);
+
+        // Payloads signed with the old secret are still accepted
+        assert_eq!(
+            receiver.verify_key(payload, &old_signature).unwrap(),
+            Some(1)
+        );
+        assert!(receiver.verify(payload, &old_signature).unwrap());
+
+        // New signatures match the primary secret
+        let new_signature = WebhookSignature::new(new_secret).sign(payload);
+        assert_eq!(
+            receiver.verify_key(payload, &new_signature).unwrap(),
+            Some(0)
+        );
+
+        // Once the old secret is retired, its signatures are rejected
+        receiver.remove_secret(1).unwrap();
+        assert_eq!(receiver.verify_key(payload, &old_signature).unwrap(), None);
+        assert_eq!(
+            receiver.verify_with_reason(payload, &old_signature),
+            VerificationOutcome::BadSignature
+        );
+    }
+
+    #[test]
+    fn test_add_secret() {
+        let mut receiver = WebhookReceiver::new("primary");
+        receiver.add_secret("secondary");
+
+        let payload = b"test payload";
+        let signature = WebhookSignature::new("secondary").sign(payload);
+
+        assert_eq!(receiver.verify_key(payload, &signature).unwrap(), Some(1));
+        assert!(receiver.remove_secret(0).is_err());
     }
 
     #[test]
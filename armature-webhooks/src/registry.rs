@@ -1,41 +1,101 @@
 //! Webhook endpoint registry
 
-use crate::{Result, WebhookEndpoint, WebhookError};
-use std::collections::HashMap;
+use crate::{Result, WebhookEndpoint, WebhookError, endpoint::is_wildcard};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 /// Registry for managing webhook endpoints
+///
+/// Exact event subscriptions are indexed so that resolving the endpoints for
+/// an event is a direct hash lookup. Wildcard subscriptions (see
+/// [`event_matches`](crate::event_matches)) are only scanned for endpoints
+/// that did not already match exactly.
 #[derive(Debug, Clone, Default)]
 pub struct WebhookRegistry {
-    endpoints: Arc<RwLock<HashMap<String, WebhookEndpoint>>>,
+    state: Arc<RwLock<RegistryState>>,
+}
+
+/// Endpoints and their subscription indexes
+#[derive(Debug, Default)]
+struct RegistryState {
+    endpoints: HashMap<String, WebhookEndpoint>,
+    /// Endpoint IDs keyed by exact event subscription
+    exact: HashMap<String, HashSet<String>>,
+    /// IDs of endpoints with at least one wildcard subscription
+    wildcard: HashSet<String>,
+}
+
+impl RegistryState {
+    fn insert(&mut self, endpoint: WebhookEndpoint) {
+        self.remove(&endpoint.id);
+        self.index(&endpoint);
+        self.endpoints.insert(endpoint.id.clone(), endpoint);
+    }
+
+    fn remove(&mut self, id: &str) -> Option<WebhookEndpoint> {
+        let endpoint = self.endpoints.remove(id)?;
+        self.unindex(&endpoint);
+        Some(endpoint)
+    }
+
+    fn index(&mut self, endpoint: &WebhookEndpoint) {
+        for event in &endpoint.events {
+            if is_wildcard(event) {
+                self.wildcard.insert(endpoint.id.clone());
+            } else {
+                self.exact
+                    .entry(event.clone())
+                    .or_default()
+                    .insert(endpoint.id.clone());
+            }
+        }
+    }
+
+    fn unindex(&mut self, endpoint: &WebhookEndpoint) {
+        self.wildcard.remove(&endpoint.id);
+        for event in &endpoint.events {
+            if let Some(ids) = self.exact.get_mut(event) {
+                ids.remove(&endpoint.id);
+                if ids.is_empty() {
+                    self.exact.remove(event);
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.endpoints.clear();
+        self.exact.clear();
+        self.wildcard.clear();
+    }
 }
 
 impl WebhookRegistry {
     /// Create a new empty registry
     pub fn new() -> Self {
         Self {
-            endpoints: Arc::new(RwLock::new(HashMap::new())),
+            state: Arc::new(RwLock::new(RegistryState::default())),
         }
     }
 
     /// Register a new endpoint
     pub fn register(&self, endpoint: WebhookEndpoint) -> String {
         let id = endpoint.id.clone();
-        let mut endpoints = self.endpoints.write().unwrap();
-        endpoints.insert(id.clone(), endpoint);
+        let mut state = self.state.write().unwrap();
+        state.insert(endpoint);
         id
     }
 
     /// Unregister an endpoint by ID
     pub fn unregister(&self, id: &str) -> Option<WebhookEndpoint> {
-        let mut endpoints = self.endpoints.write().unwrap();
-        endpoints.remove(id)
+        let mut state = self.state.write().unwrap();
+        state.remove(id)
     }
 
     /// Get an endpoint by ID
     pub fn get(&self, id: &str) -> Option<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints.get(id).cloned()
+        let state = self.state.read().unwrap();
+        state.endpoints.get(id).cloned()
     }
 
     /// Get a mutable reference to an endpoint (via callback)
@@ -43,58 +103,91 @@ impl WebhookRegistry {
     where
         F: FnOnce(&mut WebhookEndpoint) -> R,
     {
-        let mut endpoints = self.endpoints.write().unwrap();
-        match endpoints.get_mut(id) {
-            Some(endpoint) => Ok(f(endpoint)),
+        let mut state = self.state.write().unwrap();
+        match state.remove(id) {
+            Some(mut endpoint) => {
+                let result = f(&mut endpoint);
+                state.insert(endpoint);
+                Ok(result)
+            }
             None => Err(WebhookError::EndpointNotFound(id.to_string())),
         }
     }
 
     /// Get all endpoints subscribed to an event
     pub fn get_endpoints_for_event(&self, event: &str) -> Vec<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints
-            .values()
-            .filter(|e| e.enabled && e.is_subscribed_to(event))
+        let state = self.state.read().unwrap();
+
+        let mut ids: HashSet<&String> = state
+            .exact
+            .get(event)
+            .map(|ids| ids.iter().collect())
+            .unwrap_or_default();
+
+        for id in &state.wildcard {
+            if !ids.contains(id)
+                && state
+                    .endpoints
+                    .get(id)
+                    .is_some_and(|e| e.matches_wildcard(event))
+            {
+                ids.insert(id);
+            }
+        }
+
+        ids.into_iter()
+            .filter_map(|id| state.endpoints.get(id))
+            .filter(|e| e.enabled)
             .cloned()
             .collect()
     }
 
     /// Get all registered endpoints
     pub fn get_all(&self) -> Vec<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints.values().cloned().collect()
+        let state = self.state.read().unwrap();
+        state.endpoints.values().cloned().collect()
     }
 
     /// Get all enabled endpoints
     pub fn get_enabled(&self) -> Vec<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints.values().filter(|e| e.enabled).cloned().collect()
+        let state = self.state.read().unwrap();
+        state
+            .endpoints
+            .values()
+            .filter(|e| e.enabled)
+            .cloned()
+            .collect()
     }
 
     /// Get all disabled endpoints
     pub fn get_disabled(&self) -> Vec<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints.values().filter(|e| !e.enabled).cloned().collect()
+        let state = self.state.read().unwrap();
+        state
+            .endpoints
+            .values()
+            .filter(|e| !e.enabled)
+            .cloned()
+            .collect()
     }
 
     /// Get the number of registered endpoints
     pub fn count(&self) -> usize {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints.len()
+        let state = self.state.read().unwrap();
+        state.endpoints.len()
     }
 
     /// Check if an endpoint exists
     pub fn exists(&self, id: &str) -> bool {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints.contains_key(id)
+        let state = self.state.read().unwrap();
+        state.endpoints.contains_key(id)
     }
 
     /// Update an endpoint
-    pub fn update(&self, id: &str, endpoint: WebhookEndpoint) -> Result<()> {
-        let mut endpoints = self.endpoints.write().unwrap();
-        if endpoints.contains_key(id) {
-            endpoints.insert(id.to_string(), endpoint);
+    pub fn update(&self, id: &str, mut endpoint: WebhookEndpoint) -> Result<()> {
+        let mut state = self.state.write().unwrap();
+        if state.remove(id).is_some() {
+            endpoint.id = id.to_string();
+            state.insert(endpoint);
             Ok(())
         } else {
             Err(WebhookError::EndpointNotFound(id.to_string()))
@@ -123,8 +216,9 @@ impl WebhookRegistry {
 
     /// Get endpoints with high failure counts
     pub fn get_failing_endpoints(&self, threshold: u32) -> Vec<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints
+        let state = self.state.read().unwrap();
+        state
+            .endpoints
             .values()
             .filter(|e| e.failure_count >= threshold)
             .cloned()
@@ -133,14 +227,15 @@ impl WebhookRegistry {
 
     /// Clear all endpoints
     pub fn clear(&self) {
-        let mut endpoints = self.endpoints.write().unwrap();
-        endpoints.clear();
+        let mut state = self.state.write().unwrap();
+        state.clear();
     }
 
     /// Get endpoints by URL pattern
     pub fn find_by_url(&self, url_pattern: &str) -> Vec<WebhookEndpoint> {
-        let endpoints = self.endpoints.read().unwrap();
-        endpoints
+        let state = self.state.read().unwrap();
+        state
+            .endpoints
             .values()
             .filter(|e| e.url.contains(url_pattern))
             .cloned()
@@ -204,6 +299,54 @@ mod tests {
         assert!(no_endpoints.is_empty());
     }
 
+    #[test]
+    fn test_wildcard_endpoints_for_event() {
+        let registry = WebhookRegistry::new();
+
+        registry.register(create_test_endpoint("single", vec!["order.*"]));
+        registry.register(create_test_endpoint("multi", vec!["order.**"]));
+        registry.register(create_test_endpoint(
+            "both",
+            vec!["order.created", "order.*"],
+        ));
+
+        let mut ids: Vec<String> = registry
+            .get_endpoints_for_event("order.created")
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["both", "multi", "single"]);
+
+        let ids: Vec<String> = registry
+            .get_endpoints_for_event("order.items.added")
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec!["multi"]);
+
+        assert!(registry.get_endpoints_for_event("user.created").is_empty());
+    }
+
+    #[test]
+    fn test_index_follows_subscription_changes() {
+        let registry = WebhookRegistry::new();
+        let id = registry.register(create_test_endpoint("ep", vec!["user.created"]));
+
+        registry
+            .with_endpoint(&id, |e| {
+                e.unsubscribe("user.created");
+                e.subscribe("order.*");
+            })
+            .unwrap();
+
+        assert!(registry.get_endpoints_for_event("user.created").is_empty());
+        assert_eq!(registry.get_endpoints_for_event("order.shipped").len(), 1);
+
+        registry.unregister(&id);
+        assert!(registry.get_endpoints_for_event("order.shipped").is_empty());
+    }
+
     #[test]
     fn test_enable_disable() {
         let registry = WebhookRegistry::new();
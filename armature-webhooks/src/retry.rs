@@ -17,8 +17,8 @@ pub struct RetryPolicy {
     /// Multiplier for exponential backoff
     pub backoff_multiplier: f64,
 
-    /// Whether to add jitter to delays
-    pub jitter: bool,
+    /// Jitter strategy applied to computed delays
+    pub jitter: JitterKind,
 }
 
 impl Default for RetryPolicy {
@@ -28,7 +28,39 @@ impl Default for RetryPolicy {
             initial_delay: Duration::from_secs(1),
             max_delay: Duration::from_secs(60),
             backoff_multiplier: 2.0,
-            jitter: true,
+            jitter: JitterKind::None,
+        }
+    }
+}
+
+/// Jitter strategy for spreading out retry delays
+///
+/// Jitter prevents many clients that failed at the same moment from retrying
+/// in lockstep. For a computed backoff `d`:
+///
+/// - `None`: the delay is exactly `d`
+/// - `Full`: the delay is uniformly random in `[0, d]`
+/// - `Equal`: the delay is `d / 2` plus a random value in `[0, d / 2]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JitterKind {
+    /// No jitter
+    #[default]
+    None,
+
+    /// Random delay between zero and the computed backoff
+    Full,
+
+    /// Half the computed backoff plus a random share of the other half
+    Equal,
+}
+
+impl JitterKind {
+    /// Apply the jitter strategy to a delay in seconds
+    fn apply(&self, delay_secs: f64) -> f64 {
+        match self {
+            Self::None => delay_secs,
+            Self::Full => delay_secs * rand_jitter(),
+            Self::Equal => delay_secs / 2.0 + (delay_secs / 2.0) * rand_jitter(),
         }
     }
 }
@@ -49,7 +81,7 @@ impl RetryPolicy {
             initial_delay: delay,
             max_delay: delay,
             backoff_multiplier: 1.0,
-            jitter: false,
+            jitter: JitterKind::None,
         }
     }
 
@@ -61,7 +93,16 @@ impl RetryPolicy {
         }
     }
 
+    /// Set the jitter strategy
+    pub fn with_jitter(mut self, jitter: JitterKind) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
     /// Calculate the delay for a given attempt number
+    ///
+    /// The exponential backoff is capped at `max_delay` before jitter is applied,
+    /// so the returned delay never exceeds `max_delay`.
     pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
         if attempt == 0 {
             return Duration::ZERO;
@@ -72,31 +113,66 @@ impl RetryPolicy {
 
         let delay_secs = base_delay.min(self.max_delay.as_secs_f64());
 
-        let final_delay = if self.jitter {
-            // Add up to 25% jitter
-            let jitter_factor = 1.0 + (rand_jitter() * 0.25);
-            delay_secs * jitter_factor
-        } else {
-            delay_secs
-        };
-
-        Duration::from_secs_f64(final_delay)
+        Duration::from_secs_f64(self.jitter.apply(delay_secs))
     }
 
     /// Check if another retry should be attempted
     pub fn should_retry(&self, attempt: u32) -> bool {
         attempt < self.max_attempts
     }
+
+    /// Decide how to proceed after a failed attempt
+    ///
+    /// Returns [`RetryResult::Retry`] with the delay to wait before the next
+    /// attempt, or [`RetryResult::Failed`] if no attempts remain. A `retry_after`
+    /// hint (e.g. from a `Retry-After` response header) overrides the computed
+    /// backoff for this attempt, but is capped at `max_delay` so a receiver
+    /// can't stall deliveries indefinitely.
+    pub fn on_failure<T>(
+        &self,
+        attempt: u32,
+        error: impl Into<String>,
+        retry_after: Option<Duration>,
+    ) -> RetryResult<T> {
+        let error = error.into();
+
+        if !self.should_retry(attempt) {
+            return RetryResult::Failed {
+                attempts: attempt,
+                error,
+            };
+        }
+
+        RetryResult::Retry {
+            attempt,
+            error,
+            delay: match retry_after {
+                Some(retry_after) => retry_after.min(self.max_delay),
+                None => self.delay_for_attempt(attempt),
+            },
+        }
+    }
 }
 
-/// Simple deterministic jitter based on current time
+/// Parse a `Retry-After` header value
+///
+/// Accepts either a number of seconds or an HTTP date. Dates in the past
+/// yield a zero delay.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Random jitter factor in `[0, 1]`
 fn rand_jitter() -> f64 {
-    use std::time::SystemTime;
-    let nanos = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap_or_default()
-        .subsec_nanos();
-    (nanos % 1000) as f64 / 1000.0
+    rand::random_range(0.0..=1.0)
 }
 
 /// Result of a retry operation
@@ -105,8 +181,12 @@ pub enum RetryResult<T> {
     /// Operation succeeded
     Success(T),
 
-    /// Operation failed but can be retried
-    Retry { attempt: u32, error: String },
+    /// Operation failed but can be retried after `delay`
+    Retry {
+        attempt: u32,
+        error: String,
+        delay: Duration,
+    },
 
     /// Operation failed and should not be retried
     Failed { attempts: u32, error: String },
@@ -123,6 +203,14 @@ impl<T> RetryResult<T> {
         matches!(self, Self::Retry { .. })
     }
 
+    /// Get the delay before the next attempt, if a retry should be attempted
+    pub fn delay(&self) -> Option<Duration> {
+        match self {
+            Self::Retry { delay, .. } => Some(*delay),
+            _ => None,
+        }
+    }
+
     /// Get the inner value if successful
     pub fn ok(self) -> Option<T> {
         match self {
@@ -140,7 +228,9 @@ mod tests {
     fn test_default_policy() {
         let policy = RetryPolicy::default();
         assert_eq!(policy.max_attempts, 3);
-        assert!(policy.jitter);
+        assert_eq!(policy.jitter, JitterKind::None);
+        assert_eq!(policy.delay_for_attempt(1), Duration::from_secs(1));
+        assert_eq!(policy.delay_for_attempt(2), Duration::from_secs(2));
     }
 
     #[test]
@@ -155,7 +245,7 @@ mod tests {
         let policy = RetryPolicy::fixed(5, Duration::from_secs(10));
         assert_eq!(policy.max_attempts, 5);
         assert_eq!(policy.backoff_multiplier, 1.0);
-        assert!(!policy.jitter);
+        assert_eq!(policy.jitter, JitterKind::None);
 
         // All delays should be the same
         let delay1 = policy.delay_for_attempt(1);
@@ -170,7 +260,7 @@ mod tests {
             initial_delay: Duration::from_secs(1),
             max_delay: Duration::from_secs(60),
             backoff_multiplier: 2.0,
-            jitter: false,
+            jitter: JitterKind::None,
         };
 
         // Delays should increase exponentially
@@ -190,7 +280,7 @@ mod tests {
             initial_delay: Duration::from_secs(10),
             max_delay: Duration::from_secs(30),
             backoff_multiplier: 2.0,
-            jitter: false,
+            jitter: JitterKind::None,
         };
 
         // Should not exceed max_delay
@@ -217,7 +307,93 @@ mod tests {
         let retry: RetryResult<i32> = RetryResult::Retry {
             attempt: 1,
             error: "timeout".to_string(),
+            delay: Duration::from_secs(1),
         };
         assert!(retry.should_retry());
+        assert_eq!(retry.delay(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_full_jitter_bounds() {
+        let policy = RetryPolicy::exponential(10).with_jitter(JitterKind::Full);
+
+        for attempt in 1..=8 {
+            let cap = policy
+                .clone()
+                .with_jitter(JitterKind::None)
+                .delay_for_attempt(attempt);
+            for _ in 0..50 {
+                let delay = policy.delay_for_attempt(attempt);
+                assert!(delay <= cap, "{:?} > {:?}", delay, cap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_equal_jitter_bounds() {
+        let policy = RetryPolicy::exponential(10).with_jitter(JitterKind::Equal);
+
+        for attempt in 1..=8 {
+            let cap = policy
+                .clone()
+                .with_jitter(JitterKind::None)
+                .delay_for_attempt(attempt);
+            for _ in 0..50 {
+                let delay = policy.delay_for_attempt(attempt);
+                assert!(delay >= cap / 2, "{:?} < {:?}", delay, cap / 2);
+                assert!(delay <= cap, "{:?} > {:?}", delay, cap);
+            }
+        }
+    }
+
+    #[test]
+    fn test_jitter_never_exceeds_max_delay() {
+        let policy = RetryPolicy::exponential(20).with_jitter(JitterKind::Equal);
+        assert!(policy.delay_for_attempt(20) <= policy.max_delay);
+    }
+
+    #[test]
+    fn test_on_failure() {
+        let policy = RetryPolicy::fixed(2, Duration::from_secs(5));
+
+        let result: RetryResult<()> = policy.on_failure(1, "HTTP 503", None);
+        assert_eq!(result.delay(), Some(Duration::from_secs(5)));
+
+        let result: RetryResult<()> =
+            policy.on_failure(1, "HTTP 429", Some(Duration::from_secs(2)));
+        assert_eq!(result.delay(), Some(Duration::from_secs(2)));
+
+        let result: RetryResult<()> = policy.on_failure(2, "HTTP 503", None);
+        assert!(matches!(result, RetryResult::Failed { attempts: 2, .. }));
+        assert_eq!(result.delay(), None);
+    }
+
+    #[test]
+    fn test_on_failure_clamps_retry_after() {
+        let policy = RetryPolicy::exponential(3);
+
+        let result: RetryResult<()> =
+            policy.on_failure(1, "HTTP 429", Some(Duration::from_secs(86_400)));
+        assert_eq!(result.delay(), Some(policy.max_delay));
+
+        let retry_after = parse_retry_after(&u64::MAX.to_string());
+        let result: RetryResult<()> = policy.on_failure(1, "HTTP 503", retry_after);
+        assert_eq!(result.delay(), Some(policy.max_delay));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after(" 5 "), Some(Duration::from_secs(5)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+
+        let future = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let delay = parse_retry_after(&future).unwrap();
+        assert!(delay > Duration::from_secs(80) && delay <= Duration::from_secs(90));
+
+        assert_eq!(parse_retry_after("soon"), None);
     }
 }
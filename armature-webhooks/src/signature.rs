@@ -35,22 +35,44 @@ impl WebhookSignature {
 
     /// Verify a signature against the payload
     pub fn verify(&self, payload: &[u8], signature: &str, tolerance_secs: u64) -> Result<bool> {
-        let parts = Self::parse_signature(signature)?;
-
-        // Verify timestamp is within tolerance
-        let timestamp: i64 = parts
-            .timestamp
-            .parse()
-            .map_err(|_| WebhookError::TimestampInvalid("Invalid timestamp format".to_string()))?;
+        match self.verify_with_reason(payload, signature, tolerance_secs) {
+            VerificationOutcome::Valid => Ok(true),
+            VerificationOutcome::BadSignature => Ok(false),
+            VerificationOutcome::Expired => Err(WebhookError::TimestampInvalid(format!(
+                "Timestamp outside tolerance of {} seconds",
+                tolerance_secs
+            ))),
+            VerificationOutcome::MalformedHeader => Err(WebhookError::SignatureInvalid(
+                "Missing or invalid timestamp or signature".to_string(),
+            )),
+        }
+    }
 
+    /// Verify a signature against the payload, reporting why verification failed
+    ///
+    /// Signatures whose embedded timestamp is more than `tolerance_secs` in the
+    /// past or in the future are reported as [`VerificationOutcome::Expired`].
+    pub fn verify_with_reason(
+        &self,
+        payload: &[u8],
+        signature: &str,
+        tolerance_secs: u64,
+    ) -> VerificationOutcome {
+        let Ok(parts) = Self::parse_signature(signature) else {
+            return VerificationOutcome::MalformedHeader;
+        };
+
+        let Ok(timestamp) = parts.timestamp.parse::<i64>() else {
+            return VerificationOutcome::MalformedHeader;
+        };
+
+        // Verify timestamp is within tolerance; abs_diff can't overflow on
+        // extreme attacker-supplied timestamps
         let now = chrono::Utc::now().timestamp();
-        let age = (now - timestamp).unsigned_abs();
+        let age = now.abs_diff(timestamp);
 
         if age > tolerance_secs {
-            return Err(WebhookError::TimestampInvalid(format!(
-                "Timestamp too old: {} seconds (tolerance: {} seconds)",
-                age, tolerance_secs
-            )));
+            return VerificationOutcome::Expired;
         }
 
         // Compute expected signature
@@ -58,7 +80,11 @@ impl WebhookSignature {
         let expected = self.compute_hmac_sha256(signed_payload.as_bytes());
 
         // Constant-time comparison to prevent timing attacks
-        Ok(constant_time_compare(&parts.signature, &expected))
+        if constant_time_compare(&parts.signature, &expected) {
+            VerificationOutcome::Valid
+        } else {
+            VerificationOutcome::BadSignature
+        }
     }
 
     /// Compute HMAC-SHA256 signature
@@ -111,6 +137,29 @@ impl WebhookSignature {
     }
 }
 
+/// Outcome of verifying a webhook signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationOutcome {
+    /// The signature matches the payload and the timestamp is within tolerance
+    Valid,
+
+    /// The signature does not match the payload
+    BadSignature,
+
+    /// The signature timestamp is outside the allowed tolerance
+    Expired,
+
+    /// The signature header could not be parsed
+    MalformedHeader,
+}
+
+impl VerificationOutcome {
+    /// Check if the signature was valid
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+}
+
 /// Parsed signature components
 struct SignatureParts {
     timestamp: String,
@@ -147,6 +196,15 @@ pub mod headers {
 
     /// Event type header
     pub const EVENT_TYPE: &str = "X-Webhook-Event";
+
+    /// Identifier of the secret used to sign the request
+    pub const KEY_ID: &str = "X-Webhook-Key-Id";
+
+    /// Header marking a delivery as a replay of an earlier delivery
+    pub const REPLAY: &str = "X-Armature-Replay";
+
+    /// Idempotency key header (carries the original payload ID)
+    pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
 }
 
 #[cfg(test)]
@@ -217,6 +275,65 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_verify_with_reason() {
+        let signer = WebhookSignature::new("test-secret");
+        let payload = b"test payload";
+
+        let signature = signer.sign(payload);
+        assert_eq!(
+            signer.verify_with_reason(payload, &signature, 300),
+            VerificationOutcome::Valid
+        );
+
+        let other = WebhookSignature::new("other-secret").sign(payload);
+        assert_eq!(
+            signer.verify_with_reason(payload, &other, 300),
+            VerificationOutcome::BadSignature
+        );
+
+        let old_timestamp = (chrono::Utc::now().timestamp() - 1000).to_string();
+        let old = signer.sign_with_timestamp(payload, &old_timestamp);
+        assert_eq!(
+            signer.verify_with_reason(payload, &old, 60),
+            VerificationOutcome::Expired
+        );
+
+        let future_timestamp = (chrono::Utc::now().timestamp() + 1000).to_string();
+        let future = signer.sign_with_timestamp(payload, &future_timestamp);
+        assert_eq!(
+            signer.verify_with_reason(payload, &future, 60),
+            VerificationOutcome::Expired
+        );
+
+        assert_eq!(
+            signer.verify_with_reason(payload, "garbage", 300),
+            VerificationOutcome::MalformedHeader
+        );
+        assert_eq!(
+            signer.verify_with_reason(payload, "t=abc,v1=def", 300),
+            VerificationOutcome::MalformedHeader
+        );
+    }
+
+    #[test]
+    fn test_verify_extreme_timestamps() {
+        let signer = WebhookSignature::new("test-secret");
+        let payload = b"test payload";
+
+        for timestamp in [i64::MIN, i64::MAX] {
+            let signature = signer.sign_with_timestamp(payload, &timestamp.to_string());
+            assert_eq!(
+                signer.verify_with_reason(payload, &signature, 300),
+                VerificationOutcome::Expired
+            );
+            assert_eq!(
+                signer.verify_with_reason(payload, &signature, u64::MAX),
+                VerificationOutcome::Valid
+            );
+        }
+    }
+
     #[test]
     fn test_constant_time_compare() {
         assert!(constant_time_compare("abc", "abc"));
@@ -0,0 +1,166 @@
+//! Delivery history storage
+
+use crate::WebhookDelivery;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::RwLock;
+
+/// Storage for recent webhook delivery records
+///
+/// Stores are used by [`WebhookClient`](crate::WebhookClient) to keep a history
+/// of deliveries so that past events can be inspected and replayed.
+pub trait WebhookDeliveryStore: Send + Sync + Debug {
+    /// Record a delivery
+    ///
+    /// Recording a delivery whose ID is already stored replaces the previous record.
+    fn record(&self, delivery: WebhookDelivery);
+
+    /// Get a delivery by ID
+    fn get(&self, id: &str) -> Option<WebhookDelivery>;
+
+    /// Get the most recent deliveries, newest first
+    fn recent(&self, limit: usize) -> Vec<WebhookDelivery>;
+
+    /// Get the number of stored deliveries
+    fn len(&self) -> usize;
+
+    /// Check if the store is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all stored deliveries
+    fn clear(&self);
+}
+
+/// In-memory, size-bounded delivery store
+///
+/// Deliveries are kept in a ring buffer; once `capacity` records are stored,
+/// the oldest record is evicted for each new one.
+#[derive(Debug)]
+pub struct InMemoryDeliveryStore {
+    capacity: usize,
+    deliveries: RwLock<VecDeque<WebhookDelivery>>,
+}
+
+impl InMemoryDeliveryStore {
+    /// Create a new store holding at most `capacity` deliveries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            deliveries: RwLock::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Get the maximum number of deliveries kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Default for InMemoryDeliveryStore {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl WebhookDeliveryStore for InMemoryDeliveryStore {
+    fn record(&self, delivery: WebhookDelivery) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut deliveries = self.deliveries.write().unwrap();
+
+        if let Some(existing) = deliveries.iter_mut().find(|d| d.id == delivery.id) {
+            *existing = delivery;
+            return;
+        }
+
+        while deliveries.len() >= self.capacity {
+            deliveries.pop_front();
+        }
+        deliveries.push_back(delivery);
+    }
+
+    fn get(&self, id: &str) -> Option<WebhookDelivery> {
+        let deliveries = self.deliveries.read().unwrap();
+        deliveries.iter().find(|d| d.id == id).cloned()
+    }
+
+    fn recent(&self, limit: usize) -> Vec<WebhookDelivery> {
+        let deliveries = self.deliveries.read().unwrap();
+        deliveries.iter().rev().take(limit).cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        let deliveries = self.deliveries.read().unwrap();
+        deliveries.len()
+    }
+
+    fn clear(&self) {
+        let mut deliveries = self.deliveries.write().unwrap();
+        deliveries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WebhookPayload;
+
+    fn delivery(event: &str) -> WebhookDelivery {
+        WebhookDelivery::new(WebhookPayload::new(event), "https://example.com/webhook")
+    }
+
+    #[test]
+    fn test_record_and_get() {
+        let store = InMemoryDeliveryStore::new(10);
+        let d = delivery("user.created");
+        let id = d.id.clone();
+
+        store.record(d);
+
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&id).unwrap().payload.event, "user.created");
+        assert!(store.get("missing").is_none());
+    }
+
+    #[test]
+    fn test_ring_buffer_eviction() {
+        let store = InMemoryDeliveryStore::new(2);
+        let first = delivery("a");
+        let first_id = first.id.clone();
+
+        store.record(first);
+        store.record(delivery("b"));
+        store.record(delivery("c"));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&first_id).is_none());
+
+        let recent = store.recent(10);
+        assert_eq!(recent[0].payload.event, "c");
+        assert_eq!(recent[1].payload.event, "b");
+    }
+
+    #[test]
+    fn test_record_replaces_existing() {
+        let store = InMemoryDeliveryStore::new(10);
+        let mut d = delivery("a");
+        store.record(d.clone());
+
+        d.mark_succeeded(200, None);
+        store.record(d.clone());
+
+        assert_eq!(store.len(), 1);
+        assert!(store.get(&d.id).unwrap().status.is_success());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let store = InMemoryDeliveryStore::new(0);
+        store.record(delivery("a"));
+        assert!(store.is_empty());
+    }
+}
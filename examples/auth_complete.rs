@@ -153,7 +153,7 @@ fn main() {
     println!("7. Testing different password hashing algorithms...");
 
     println!("   Bcrypt:");
-    let bcrypt_hasher = PasswordHasher::new(armature_auth::password::HashAlgorithm::Bcrypt);
+    let bcrypt_hasher = PasswordHasher::new(armature_auth::password::HashAlgorithm::bcrypt());
     let bcrypt_hash = bcrypt_hasher.hash("test123").expect("Failed to hash");
     println!(
         "     Hash: {}...",
@@ -165,7 +165,7 @@ fn main() {
     );
 
     println!("   Argon2:");
-    let argon2_hasher = PasswordHasher::new(armature_auth::password::HashAlgorithm::Argon2);
+    let argon2_hasher = PasswordHasher::new(armature_auth::password::HashAlgorithm::argon2id());
     let argon2_hash = argon2_hasher.hash("test123").expect("Failed to hash");
     println!(
         "     Hash: {}...",
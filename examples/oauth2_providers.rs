@@ -25,7 +25,7 @@ async fn main() {
         Ok(provider) => {
             println!("   ✓ Provider: {}", provider.name());
 
-            if let Ok((auth_url, csrf_token)) = provider.authorization_url() {
+            if let Ok((auth_url, csrf_token, _)) = provider.authorization_url() {
                 println!(
                     "   ✓ Auth URL: {}...",
                     &auth_url.as_str()[..80.min(auth_url.as_str().len())]
@@ -50,7 +50,7 @@ async fn main() {
         Ok(provider) => {
             println!("   ✓ Provider: {}", provider.name());
 
-            if let Ok((auth_url, _)) = provider.authorization_url() {
+            if let Ok((auth_url, _, _)) = provider.authorization_url() {
                 println!(
                     "   ✓ Auth URL: {}...",
                     &auth_url.as_str()[..80.min(auth_url.as_str().len())]
@@ -81,7 +81,7 @@ async fn main() {
         Ok(provider) => {
             println!("   ✓ Provider: {}", provider.name());
 
-            if let Ok((auth_url, _)) = provider.authorization_url() {
+            if let Ok((auth_url, _, _)) = provider.authorization_url() {
                 println!(
                     "   ✓ Auth URL: {}...",
                     &auth_url.as_str()[..80.min(auth_url.as_str().len())]
@@ -106,7 +106,7 @@ async fn main() {
         Ok(provider) => {
             println!("   ✓ Provider: {}", provider.name());
 
-            if let Ok((auth_url, _)) = provider.authorization_url() {
+            if let Ok((auth_url, _, _)) = provider.authorization_url() {
                 println!(
                     "   ✓ Auth URL: {}...",
                     &auth_url.as_str()[..80.min(auth_url.as_str().len())]
@@ -132,7 +132,7 @@ async fn main() {
         Ok(provider) => {
             println!("   ✓ Provider: {}", provider.name());
 
-            if let Ok((auth_url, _)) = provider.authorization_url() {
+            if let Ok((auth_url, _, _)) = provider.authorization_url() {
                 println!(
                     "   ✓ Auth URL: {}...",
                     &auth_url.as_str()[..80.min(auth_url.as_str().len())]